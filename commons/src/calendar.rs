@@ -0,0 +1,430 @@
+//! Торговый календарь: расписание сессии, выходные и праздники.
+//!
+//! [`TradingCalendar`] задаёт время открытия и закрытия торгов в местном
+//! времени биржи (фиксированное смещение от UTC, без перехода на летнее
+//! время), торговые дни недели и праздничные даты. Метки времени — как и
+//! в котировках, миллисекунды Unix в UTC.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::calendar::{SessionState, TradingCalendar};
+//!
+//! let calendar = TradingCalendar::nyse();
+//! // Пятница, 2024-03-15 15:00 UTC = 10:00 в Нью-Йорке (UTC-5).
+//! let friday = 1_710_514_800_000;
+//!
+//! assert!(calendar.is_open(friday));
+//! // Следующее открытие — в понедельник, 2024-03-18 09:30 (14:30 UTC).
+//! assert_eq!(calendar.next_open(friday), Some(1_710_772_200_000));
+//! assert!(matches!(calendar.state(friday), SessionState::Open { .. }));
+//! ```
+
+use crate::errors::QuoteError;
+use crate::utils::{civil_from_days, days_from_civil, format_timestamp_ms};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+const MINUTE_MS: i64 = 60_000;
+const DAY_MINUTES: u32 = 24 * 60;
+const DAY_MS: i64 = DAY_MINUTES as i64 * MINUTE_MS;
+
+/// Сколько дней вперёд просматривается при поиске следующей сессии.
+///
+/// Ограничивает поиск для календаря без торговых дней.
+const MAX_SCAN_DAYS: i64 = 366;
+
+/// День недели.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    /// Рабочие дни: понедельник — пятница.
+    pub const WORKDAYS: [Weekday; 5] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ];
+
+    /// День недели по номеру дня от 1970-01-01 (четверг).
+    fn from_days(days: i64) -> Self {
+        match (days + 3).rem_euclid(7) {
+            0 => Weekday::Mon,
+            1 => Weekday::Tue,
+            2 => Weekday::Wed,
+            3 => Weekday::Thu,
+            4 => Weekday::Fri,
+            5 => Weekday::Sat,
+            _ => Weekday::Sun,
+        }
+    }
+
+    /// Бит дня недели в маске торговых дней.
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Состояние торгов на момент времени.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Торги идут и закроются в `closes_at` (мс Unix, UTC).
+    Open { closes_at: u64 },
+    /// Торги закрыты; откроются в `opens_at` или не откроются в ближайший
+    /// год (`None`).
+    Closed { opens_at: Option<u64> },
+}
+
+impl SessionState {
+    /// Торги идут.
+    pub fn is_open(&self) -> bool {
+        matches!(self, SessionState::Open { .. })
+    }
+}
+
+impl Display for SessionState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionState::Open { closes_at } => {
+                write!(f, "торги открыты до {}", format_timestamp_ms(*closes_at))
+            }
+            SessionState::Closed {
+                opens_at: Some(opens_at),
+            } => write!(f, "торги закрыты до {}", format_timestamp_ms(*opens_at)),
+            SessionState::Closed { opens_at: None } => f.write_str("торги закрыты"),
+        }
+    }
+}
+
+/// Расписание торгов.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingCalendar {
+    /// Смещение местного времени биржи от UTC, минуты.
+    utc_offset_min: i32,
+    /// Открытие, минуты от местной полуночи.
+    open_min: u32,
+    /// Закрытие, минуты от местной полуночи (не больше `24:00`).
+    close_min: u32,
+    /// Маска торговых дней недели ([`Weekday::bit`]).
+    days: u8,
+    /// Праздники: номера дней от 1970-01-01 по местному времени.
+    holidays: BTreeSet<i64>,
+}
+
+impl Default for TradingCalendar {
+    /// Круглосуточные торги без выходных.
+    fn default() -> Self {
+        Self::always_open()
+    }
+}
+
+impl TradingCalendar {
+    /// Календарь с сессией `open`–`close` (часы и минуты местного
+    /// времени) по рабочим дням.
+    ///
+    /// ## Args
+    ///
+    /// - `open`, `close` — время открытия и закрытия; закрытие `(24, 0)`
+    ///   означает полночь следующего дня
+    /// - `utc_offset_min` — смещение местного времени от UTC, минуты
+    ///   (`-300` для UTC-5)
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::ValueError`], если время некорректно, закрытие не позже
+    /// открытия (ночные сессии не поддерживаются) или смещение больше суток.
+    pub fn new(
+        open: (u32, u32),
+        close: (u32, u32),
+        utc_offset_min: i32,
+    ) -> Result<Self, QuoteError> {
+        let open_min = minutes_of_day(open)?;
+        let close_min = minutes_of_day(close)?;
+        if open_min >= close_min {
+            return Err(QuoteError::value_err(format!(
+                "закрытие торгов {} не позже открытия {}",
+                fmt_minutes(close_min),
+                fmt_minutes(open_min)
+            )));
+        }
+        if utc_offset_min.unsigned_abs() >= DAY_MINUTES {
+            return Err(QuoteError::value_err(format!(
+                "некорректное смещение от UTC: {utc_offset_min} мин"
+            )));
+        }
+        Ok(Self {
+            utc_offset_min,
+            open_min,
+            close_min,
+            days: 0,
+            holidays: BTreeSet::new(),
+        }
+        .with_trading_days(&Weekday::WORKDAYS))
+    }
+
+    /// Круглосуточные торги без выходных.
+    pub fn always_open() -> Self {
+        Self {
+            utc_offset_min: 0,
+            open_min: 0,
+            close_min: DAY_MINUTES,
+            days: u8::MAX,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// Основная сессия NYSE/NASDAQ: 09:30–16:00 по рабочим дням, UTC-5
+    /// (летнее время не учитывается).
+    pub fn nyse() -> Self {
+        Self::new((9, 30), (16, 0), -300).expect("корректное расписание NYSE")
+    }
+
+    /// Заменить торговые дни недели.
+    pub fn with_trading_days(mut self, days: &[Weekday]) -> Self {
+        self.days = days.iter().fold(0, |mask, day| mask | day.bit());
+        self
+    }
+
+    /// Добавить праздник — дату, в которую торгов нет.
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::ValueError`] для несуществующей даты.
+    pub fn with_holiday(mut self, year: i64, month: u32, day: u32) -> Result<Self, QuoteError> {
+        // Несуществующая дата (30 февраля) при обратном преобразовании
+        // даёт другой день.
+        let valid = (1..=12).contains(&month)
+            && civil_from_days(days_from_civil(year, month, day)) == (year, month, day);
+        if !valid {
+            return Err(QuoteError::value_err(format!(
+                "некорректная дата праздника: {year:04}-{month:02}-{day:02}"
+            )));
+        }
+        self.holidays.insert(days_from_civil(year, month, day));
+        Ok(self)
+    }
+
+    /// Торговый ли день `weekday` (без учёта праздников).
+    pub fn is_trading_weekday(&self, weekday: Weekday) -> bool {
+        self.days & weekday.bit() != 0
+    }
+
+    /// Идут ли торги в момент `ts_ms`.
+    pub fn is_open(&self, ts_ms: u64) -> bool {
+        let local = self.local_ms(ts_ms);
+        let day = local.div_euclid(DAY_MS);
+        let time = local.rem_euclid(DAY_MS);
+        self.is_trading_day(day) && (self.open_ms()..self.close_ms()).contains(&time)
+    }
+
+    /// Ближайшее открытие торгов строго после `ts_ms`.
+    ///
+    /// ## Returns
+    ///
+    /// `None`, если в ближайший год торговых дней нет.
+    pub fn next_open(&self, ts_ms: u64) -> Option<u64> {
+        self.next_boundary(ts_ms, self.open_ms())
+    }
+
+    /// Ближайшее закрытие торгов строго после `ts_ms`: конец текущей
+    /// сессии, если торги идут, иначе — следующей.
+    ///
+    /// ## Returns
+    ///
+    /// `None`, если в ближайший год торговых дней нет.
+    pub fn next_close(&self, ts_ms: u64) -> Option<u64> {
+        self.next_boundary(ts_ms, self.close_ms())
+    }
+
+    /// Состояние торгов в момент `ts_ms`.
+    pub fn state(&self, ts_ms: u64) -> SessionState {
+        if self.is_open(ts_ms) {
+            let closes_at = self
+                .next_close(ts_ms)
+                .expect("у открытой сессии есть закрытие");
+            SessionState::Open { closes_at }
+        } else {
+            SessionState::Closed {
+                opens_at: self.next_open(ts_ms),
+            }
+        }
+    }
+
+    fn is_trading_day(&self, day: i64) -> bool {
+        self.is_trading_weekday(Weekday::from_days(day)) && !self.holidays.contains(&day)
+    }
+
+    fn open_ms(&self) -> i64 {
+        i64::from(self.open_min) * MINUTE_MS
+    }
+
+    fn close_ms(&self) -> i64 {
+        i64::from(self.close_min) * MINUTE_MS
+    }
+
+    fn offset_ms(&self) -> i64 {
+        i64::from(self.utc_offset_min) * MINUTE_MS
+    }
+
+    fn local_ms(&self, ts_ms: u64) -> i64 {
+        i64::try_from(ts_ms).unwrap_or(i64::MAX - DAY_MS) + self.offset_ms()
+    }
+
+    /// Первая граница сессии (`time` от местной полуночи торгового дня)
+    /// после `ts_ms`.
+    fn next_boundary(&self, ts_ms: u64, time: i64) -> Option<u64> {
+        let local = self.local_ms(ts_ms);
+        let today = local.div_euclid(DAY_MS);
+        (today..=today + MAX_SCAN_DAYS)
+            .filter(|&day| self.is_trading_day(day))
+            .map(|day| day * DAY_MS + time)
+            .find(|&boundary| boundary > local)
+            .and_then(|boundary| u64::try_from(boundary - self.offset_ms()).ok())
+    }
+}
+
+/// Минуты от полуночи для времени `(часы, минуты)`; допускается `24:00`.
+fn minutes_of_day((hours, minutes): (u32, u32)) -> Result<u32, QuoteError> {
+    let total = hours * 60 + minutes;
+    if minutes >= 60 || total > DAY_MINUTES {
+        return Err(QuoteError::value_err(format!(
+            "некорректное время: {hours:02}:{minutes:02}"
+        )));
+    }
+    Ok(total)
+}
+
+fn fmt_minutes(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Метка времени UTC по дате и времени.
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> u64 {
+        (days_from_civil(year, month, day) * DAY_MS + (hour * 60 + minute) * MINUTE_MS) as u64
+    }
+
+    #[test]
+    fn weekdays_from_epoch() {
+        assert_eq!(Weekday::from_days(0), Weekday::Thu);
+        assert_eq!(Weekday::from_days(-1), Weekday::Wed);
+        assert_eq!(
+            Weekday::from_days(days_from_civil(2024, 3, 16)),
+            Weekday::Sat
+        );
+    }
+
+    #[test]
+    fn open_during_session_on_workdays() {
+        let calendar = TradingCalendar::nyse();
+        // Пятница: 09:30 и 15:59 местного времени — открыто, 16:00 — нет.
+        assert!(!calendar.is_open(utc(2024, 3, 15, 14, 29)));
+        assert!(calendar.is_open(utc(2024, 3, 15, 14, 30)));
+        assert!(calendar.is_open(utc(2024, 3, 15, 20, 59)));
+        assert!(!calendar.is_open(utc(2024, 3, 15, 21, 0)));
+        // Суббота.
+        assert!(!calendar.is_open(utc(2024, 3, 16, 15, 0)));
+    }
+
+    #[test]
+    fn next_open_and_close_skip_weekend() {
+        let calendar = TradingCalendar::nyse();
+        let friday_evening = utc(2024, 3, 15, 22, 0);
+        assert_eq!(
+            calendar.next_open(friday_evening),
+            Some(utc(2024, 3, 18, 14, 30))
+        );
+        assert_eq!(
+            calendar.next_close(friday_evening),
+            Some(utc(2024, 3, 18, 21, 0))
+        );
+
+        let monday_morning = utc(2024, 3, 18, 12, 0);
+        assert_eq!(
+            calendar.next_open(monday_morning),
+            Some(utc(2024, 3, 18, 14, 30))
+        );
+        assert_eq!(
+            calendar.next_close(utc(2024, 3, 18, 15, 0)),
+            Some(utc(2024, 3, 18, 21, 0))
+        );
+    }
+
+    #[test]
+    fn offset_moves_local_date() {
+        // Торги 10:00–18:00 по Москве (UTC+3): 06:30 UTC понедельника —
+        // 09:30 местного, ещё закрыто; 23:00 UTC воскресенья — уже
+        // понедельник 02:00 местного.
+        let calendar = TradingCalendar::new((10, 0), (18, 0), 180).unwrap();
+        assert!(!calendar.is_open(utc(2024, 3, 18, 6, 30)));
+        assert!(calendar.is_open(utc(2024, 3, 18, 7, 0)));
+        assert_eq!(
+            calendar.next_open(utc(2024, 3, 17, 23, 0)),
+            Some(utc(2024, 3, 18, 7, 0))
+        );
+    }
+
+    #[test]
+    fn holidays_are_closed() {
+        let calendar = TradingCalendar::nyse().with_holiday(2024, 3, 18).unwrap();
+        assert!(!calendar.is_open(utc(2024, 3, 18, 15, 0)));
+        assert_eq!(
+            calendar.next_open(utc(2024, 3, 15, 22, 0)),
+            Some(utc(2024, 3, 19, 14, 30))
+        );
+        assert!(TradingCalendar::nyse().with_holiday(2024, 2, 30).is_err());
+        assert!(TradingCalendar::nyse().with_holiday(2024, 13, 1).is_err());
+    }
+
+    #[test]
+    fn state_reports_next_boundary() {
+        let calendar = TradingCalendar::nyse();
+        assert_eq!(
+            calendar.state(utc(2024, 3, 15, 15, 0)),
+            SessionState::Open {
+                closes_at: utc(2024, 3, 15, 21, 0)
+            }
+        );
+        let closed = calendar.state(utc(2024, 3, 16, 12, 0));
+        assert_eq!(
+            closed,
+            SessionState::Closed {
+                opens_at: Some(utc(2024, 3, 18, 14, 30))
+            }
+        );
+        assert!(!closed.is_open());
+
+        let never = TradingCalendar::nyse().with_trading_days(&[]);
+        assert_eq!(never.state(0), SessionState::Closed { opens_at: None });
+        assert_eq!(never.next_close(0), None);
+    }
+
+    #[test]
+    fn always_open_and_invalid_sessions() {
+        let calendar = TradingCalendar::default();
+        assert!(calendar.is_open(0));
+        assert!(calendar.is_open(utc(2024, 3, 16, 23, 59)));
+        assert_eq!(calendar.next_close(0), Some(DAY_MS as u64));
+
+        assert!(TradingCalendar::new((16, 0), (9, 30), 0).is_err());
+        assert!(TradingCalendar::new((9, 60), (16, 0), 0).is_err());
+        assert!(TradingCalendar::new((9, 0), (24, 1), 0).is_err());
+        assert!(TradingCalendar::new((9, 0), (24, 0), 0).is_ok());
+        assert!(TradingCalendar::new((9, 0), (17, 0), 24 * 60).is_err());
+    }
+}
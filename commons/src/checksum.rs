@@ -0,0 +1,169 @@
+//! Контрольные суммы CRC-32 для двоичных данных.
+//!
+//! Используется вариант IEEE 802.3 (как в zlib и PNG). Запись с контрольной
+//! суммой — данные, за которыми следует CRC-32 этих данных (`u32`,
+//! little-endian): так повреждённые при передаче или обрезанные записи
+//! отбрасываются, а не разбираются в бессмысленные значения.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::checksum::{append_crc, crc32, verify_crc};
+//!
+//! assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+//!
+//! let mut record = b"AAPL".to_vec();
+//! append_crc(&mut record);
+//! assert_eq!(verify_crc(&record).unwrap(), b"AAPL");
+//!
+//! record[0] ^= 1;
+//! assert!(verify_crc(&record).is_err());
+//! ```
+
+use crate::errors::QuoteError;
+
+/// Размер контрольной суммы в записи, байт.
+pub const CRC_SIZE: usize = 4;
+
+/// Образующий многочлен CRC-32 (IEEE, в отражённом виде).
+const POLY: u32 = 0xEDB8_8320;
+
+/// Таблица остатков для побайтового вычисления.
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Пошаговое вычисление CRC-32 для данных, поступающих частями.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    /// Начать вычисление.
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Учесть очередную часть данных.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = TABLE[((self.state ^ byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// Контрольная сумма учтённых данных.
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+/// Контрольная сумма CRC-32 данных.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// Дописать в конец `buf` контрольную сумму его содержимого.
+pub fn append_crc(buf: &mut Vec<u8>) {
+    let crc = crc32(buf);
+    buf.extend_from_slice(&crc.to_le_bytes());
+}
+
+/// Проверить запись с контрольной суммой.
+///
+/// ## Returns
+///
+/// Данные записи без контрольной суммы.
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::ParseError`], если запись короче
+/// [`CRC_SIZE`] или контрольная сумма не совпадает.
+pub fn verify_crc(record: &[u8]) -> Result<&[u8], QuoteError> {
+    let Some((payload, crc)) = record.split_last_chunk::<CRC_SIZE>() else {
+        return Err(QuoteError::parse_err(format!(
+            "запись из {} байт короче контрольной суммы",
+            record.len()
+        )));
+    };
+    let expected = u32::from_le_bytes(*crc);
+    let actual = crc32(payload);
+    if actual != expected {
+        return Err(QuoteError::parse_err(format!(
+            "контрольная сумма не совпадает: {actual:08x} вместо {expected:08x}"
+        )));
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn incremental_matches_whole() {
+        let data = b"AAPL|150.5|100|1700000000000|buy|1";
+        let mut crc = Crc32::new();
+        for chunk in data.chunks(5) {
+            crc.update(chunk);
+        }
+        assert_eq!(crc.finish(), crc32(data));
+    }
+
+    #[test]
+    fn every_single_bit_flip_is_detected() {
+        let mut record = b"GOOGL 2801.25".to_vec();
+        append_crc(&mut record);
+
+        for bit in 0..record.len() * 8 {
+            let mut damaged = record.clone();
+            damaged[bit / 8] ^= 1 << (bit % 8);
+            assert!(verify_crc(&damaged).is_err(), "bit {bit}");
+        }
+    }
+
+    #[test]
+    fn truncated_records_are_rejected() {
+        let mut record = b"MSFT".to_vec();
+        append_crc(&mut record);
+
+        assert_eq!(verify_crc(&record).unwrap(), b"MSFT");
+        for len in 0..record.len() {
+            assert!(verify_crc(&record[..len]).is_err(), "len {len}");
+        }
+    }
+}
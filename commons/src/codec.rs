@@ -0,0 +1,365 @@
+//! Компактное двоичное представление [`StockQuote`].
+//!
+//! Запись имеет фиксированный размер [`QUOTE_BINARY_SIZE`] байт, числа
+//! записываются в порядке little-endian, строки — в поля фиксированной
+//! ширины, дополненные нулевыми байтами:
+//!
+//! | смещение | размер | поле                                  |
+//! |---------:|-------:|---------------------------------------|
+//! | 0        | 8      | `ticker`                              |
+//! | 8        | 8      | `price`, `f64`                        |
+//! | 16       | 4      | `volume`, `u32`                       |
+//! | 20       | 8      | `timestamp`, `u64`                    |
+//! | 28       | 1      | `transaction`, код вида (см. ниже)    |
+//! | 29       | 8      | `seq`, `u64`                          |
+//! | 37       | 1      | флаги заданных необязательных полей   |
+//! | 38       | 8      | `bid`, `f64`                          |
+//! | 46       | 8      | `ask`, `f64`                          |
+//! | 54       | 8      | `exchange`                            |
+//! | 62       | 3      | `currency`                            |
+//!
+//! Коды вида транзакции: `0` — sell, `1` — buy, `2` — short_sell,
+//! `3` — cancel, `4` — correction. Незаданное необязательное поле
+//! заполняется нулями, его бит во флагах сброшен.
+//!
+//! Запись генерирует derive-макрос `QuoteBinary` по полям модели, а типы
+//! полей описывают своё представление трейтом [`BinaryField`].
+//!
+//! Двоичная UDP-датаграмма трансляции ([`StreamQuote::to_checked_bytes`])
+//! — номер `stream_seq` (`u64`), запись котировки и CRC-32 этих данных
+//! ([`crate::checksum`]), всего [`DATAGRAM_CHECKED_SIZE`] байт: повреждённая
+//! или обрезанная датаграмма отбрасывается, а не превращается в неверную
+//! цену.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::codec::QUOTE_BINARY_SIZE;
+//! use commons::models::StockQuote;
+//!
+//! let quote: StockQuote = "AAPL|150.5|100|1700000000000|buy".parse().unwrap();
+//! let bytes = quote.to_bytes().unwrap();
+//! assert_eq!(bytes.len(), QUOTE_BINARY_SIZE);
+//!
+//! let decoded = StockQuote::from_bytes(&bytes).unwrap();
+//! assert_eq!(decoded.to_string(), quote.to_string());
+//! ```
+
+use crate::checksum::{CRC_SIZE, append_crc, verify_crc};
+use crate::errors::QuoteError;
+use crate::models::{StockQuote, StreamQuote, Transaction};
+use crate::price::Price;
+
+/// Ширина поля тикера, байт.
+pub const TICKER_WIDTH: usize = 8;
+
+/// Ширина поля биржи, байт.
+pub const EXCHANGE_WIDTH: usize = 8;
+
+/// Ширина поля валюты, байт (код ISO 4217).
+pub const CURRENCY_WIDTH: usize = 3;
+
+/// Размер двоичной записи котировки, байт.
+pub const QUOTE_BINARY_SIZE: usize = StockQuote::BINARY_SIZE;
+
+/// Размер двоичной датаграммы трансляции с контрольной суммой, байт.
+pub const DATAGRAM_CHECKED_SIZE: usize = SEQ_SIZE + QUOTE_BINARY_SIZE + CRC_SIZE;
+
+/// Размер номера `stream_seq` в датаграмме, байт.
+const SEQ_SIZE: usize = 8;
+
+/// Поле двоичной записи: значение фиксированной ширины.
+///
+/// Числа записываются в порядке little-endian, строки — в поле ширины
+/// из атрибута `#[binary(width = N)]`, дополненное нулевыми байтами.
+pub trait BinaryField: Sized {
+    /// Ширина поля, байт; `0` — задаётся атрибутом `#[binary(width = N)]`.
+    const SIZE: usize;
+
+    /// Записать значение в `buf` (длина — ширина поля); `field` — название
+    /// поля для сообщения об ошибке.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`], если значение не помещается
+    /// в поле.
+    fn write_to(&self, buf: &mut [u8], field: &str) -> Result<(), QuoteError>;
+
+    /// Прочитать значение из `buf` (длина — ширина поля).
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ParseError`], если значение некорректно.
+    fn read_from(buf: &[u8], field: &str) -> Result<Self, QuoteError>;
+}
+
+macro_rules! binary_number {
+    ($($ty:ty),*) => {$(
+        impl BinaryField for $ty {
+            const SIZE: usize = size_of::<$ty>();
+
+            fn write_to(&self, buf: &mut [u8], _field: &str) -> Result<(), QuoteError> {
+                buf.copy_from_slice(&self.to_le_bytes());
+                Ok(())
+            }
+
+            fn read_from(buf: &[u8], _field: &str) -> Result<Self, QuoteError> {
+                let mut bytes = [0u8; size_of::<$ty>()];
+                bytes.copy_from_slice(buf);
+                Ok(<$ty>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+binary_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl BinaryField for String {
+    const SIZE: usize = 0;
+
+    fn write_to(&self, buf: &mut [u8], field: &str) -> Result<(), QuoteError> {
+        let width = buf.len();
+        if self.len() > width || self.contains('\0') {
+            return Err(QuoteError::value_err(format!(
+                "значение {field} '{self}' не помещается в поле из {width} байт"
+            )));
+        }
+        buf[..self.len()].copy_from_slice(self.as_bytes());
+        Ok(())
+    }
+
+    /// Строка без дополнения нулями.
+    fn read_from(buf: &[u8], field: &str) -> Result<Self, QuoteError> {
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        String::from_utf8(buf[..len].to_vec())
+            .map_err(|_| QuoteError::parse_err(format!("поле {field} двоичной записи не в UTF-8")))
+    }
+}
+
+/// Цена записывается как `f64`.
+impl BinaryField for Price {
+    const SIZE: usize = f64::SIZE;
+
+    fn write_to(&self, buf: &mut [u8], field: &str) -> Result<(), QuoteError> {
+        self.to_f64().write_to(buf, field)
+    }
+
+    fn read_from(buf: &[u8], field: &str) -> Result<Self, QuoteError> {
+        let price = f64::read_from(buf, field)?;
+        Price::from_f64(price).ok_or_else(|| {
+            QuoteError::parse_err(format!("некорректная цена в двоичной записи: {price}"))
+        })
+    }
+}
+
+/// Вид транзакции записывается кодом из одного байта ([`Transaction::to_code`]).
+impl BinaryField for Transaction {
+    const SIZE: usize = 1;
+
+    fn write_to(&self, buf: &mut [u8], _field: &str) -> Result<(), QuoteError> {
+        buf[0] = self.to_code();
+        Ok(())
+    }
+
+    fn read_from(buf: &[u8], _field: &str) -> Result<Self, QuoteError> {
+        Transaction::from_code(buf[0]).ok_or_else(|| {
+            QuoteError::parse_err(format!(
+                "неизвестный вид транзакции в двоичной записи: {}",
+                buf[0]
+            ))
+        })
+    }
+}
+
+impl StreamQuote {
+    /// Двоичная датаграмма трансляции с контрольной суммой CRC-32.
+    ///
+    /// ## Ошибки
+    ///
+    /// Те же, что у [`StockQuote::to_bytes`].
+    pub fn to_checked_bytes(&self) -> Result<Vec<u8>, QuoteError> {
+        let mut datagram = Vec::with_capacity(DATAGRAM_CHECKED_SIZE);
+        datagram.extend_from_slice(&self.stream_seq.to_le_bytes());
+        datagram.extend_from_slice(&self.quote.to_bytes()?);
+        append_crc(&mut datagram);
+        Ok(datagram)
+    }
+
+    /// Восстановить датаграмму трансляции с контрольной суммой.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ParseError`], если размер датаграммы
+    /// отличается от [`DATAGRAM_CHECKED_SIZE`], контрольная сумма не
+    /// совпадает (датаграмма повреждена) или запись котировки некорректна
+    /// (см. [`StockQuote::from_bytes`]).
+    pub fn from_checked_bytes(bytes: &[u8]) -> Result<Self, QuoteError> {
+        if bytes.len() != DATAGRAM_CHECKED_SIZE {
+            return Err(QuoteError::parse_err(format!(
+                "ожидается датаграмма с контрольной суммой из {} байт, получено {}",
+                DATAGRAM_CHECKED_SIZE,
+                bytes.len()
+            )));
+        }
+        let data = verify_crc(bytes)?;
+        let (seq, quote) = data.split_at(SEQ_SIZE);
+        let mut seq_bytes = [0u8; SEQ_SIZE];
+        seq_bytes.copy_from_slice(seq);
+        Ok(Self {
+            stream_seq: u64::from_le_bytes(seq_bytes),
+            quote: StockQuote::from_bytes(quote)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn quote() -> StockQuote {
+        StockQuote {
+            ticker: "GOOGL".to_string(),
+            price: "2801.25".parse().unwrap(),
+            volume: 4_000_000_000,
+            timestamp: 1_700_000_000_123,
+            transaction: Transaction::Buy,
+            seq: u64::MAX,
+            bid: Some(2801.0),
+            ask: Some(2801.5),
+            exchange: Some("NASDAQ".to_string()),
+            currency: Some("USD".to_string()),
+        }
+    }
+
+    fn roundtrip(quote: &StockQuote) -> StockQuote {
+        StockQuote::from_bytes(&quote.to_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn layout_is_little_endian_and_fixed() {
+        let bytes = quote().to_bytes().unwrap();
+
+        assert_eq!(QUOTE_BINARY_SIZE, 65);
+        assert_eq!(&bytes[..8], b"GOOGL\0\0\0");
+        assert_eq!(&bytes[8..16], &2801.25f64.to_le_bytes());
+        assert_eq!(&bytes[16..20], &4_000_000_000u32.to_le_bytes());
+        assert_eq!(bytes[28], 1);
+        assert_eq!(bytes[37], 0b1111);
+        assert_eq!(&bytes[62..], b"USD");
+    }
+
+    #[test]
+    fn every_optional_combination_roundtrips() {
+        for flags in 0..16u8 {
+            let mut q = quote();
+            q.transaction = Transaction::ALL[flags as usize % Transaction::ALL.len()];
+            if flags & 0b0001 == 0 {
+                q.bid = None;
+            }
+            if flags & 0b0010 == 0 {
+                q.ask = None;
+            }
+            if flags & 0b0100 == 0 {
+                q.exchange = None;
+            }
+            if flags & 0b1000 == 0 {
+                q.currency = None;
+            }
+
+            let decoded = roundtrip(&q);
+            assert_eq!(decoded.to_string(), q.to_string(), "flags {flags:04b}");
+            assert_eq!(decoded.bid, q.bid);
+            assert_eq!(decoded.exchange, q.exchange);
+        }
+    }
+
+    #[test]
+    fn edge_values_roundtrip() {
+        let mut q = quote();
+        q.ticker = "ABCDEFGH".to_string();
+        q.price = Price::from_units(1);
+        q.volume = 0;
+        q.timestamp = u64::MAX;
+        q.seq = 0;
+        q.exchange = Some(String::new());
+        let decoded = roundtrip(&q);
+
+        assert_eq!(decoded.ticker, "ABCDEFGH");
+        assert_eq!(decoded.price, Price::from_units(1));
+        assert_eq!((decoded.volume, decoded.timestamp), (0, u64::MAX));
+        assert_eq!(decoded.exchange, Some(String::new()));
+    }
+
+    #[test]
+    fn oversized_strings_are_rejected() {
+        let mut q = quote();
+        q.ticker = "TOOLONGTK".to_string();
+        assert!(q.to_bytes().is_err());
+
+        let mut q = quote();
+        q.currency = Some("USDT".to_string());
+        assert!(q.to_bytes().is_err());
+
+        let mut q = quote();
+        q.ticker = "A\0B".to_string();
+        assert!(q.to_bytes().is_err());
+    }
+
+    #[test]
+    fn malformed_records_are_rejected() {
+        let bytes = quote().to_bytes().unwrap();
+        assert!(StockQuote::from_bytes(&bytes[..QUOTE_BINARY_SIZE - 1]).is_err());
+
+        let mut bad = bytes;
+        bad[28] = 5;
+        assert!(StockQuote::from_bytes(&bad).is_err());
+
+        let mut bad = bytes;
+        bad[37] = 0b1_0000;
+        assert!(StockQuote::from_bytes(&bad).is_err());
+
+        let mut bad = bytes;
+        bad[0] = 0xFF;
+        assert!(StockQuote::from_bytes(&bad).is_err());
+    }
+
+    #[test]
+    fn checked_datagrams_detect_corruption() {
+        let datagram = StreamQuote {
+            stream_seq: 7,
+            quote: quote(),
+        };
+        let bytes = datagram.to_checked_bytes().unwrap();
+        assert_eq!(bytes.len(), DATAGRAM_CHECKED_SIZE);
+        assert_eq!(&bytes[..8], &7u64.to_le_bytes());
+        assert_eq!(
+            &bytes[8..8 + QUOTE_BINARY_SIZE],
+            &quote().to_bytes().unwrap()
+        );
+
+        let decoded = StreamQuote::from_checked_bytes(&bytes).unwrap();
+        assert_eq!(decoded.stream_seq, 7);
+        assert_eq!(decoded.quote.to_string(), quote().to_string());
+
+        // Цена с изменённым битом прошла бы разбор без контрольной суммы.
+        let mut damaged = bytes.clone();
+        damaged[18] ^= 0b100;
+        assert!(StockQuote::from_bytes(&damaged[8..8 + QUOTE_BINARY_SIZE]).is_ok());
+        let err = StreamQuote::from_checked_bytes(&damaged).unwrap_err();
+        assert!(matches!(err, QuoteError::ParseError(_)));
+
+        assert!(StreamQuote::from_checked_bytes(&bytes[..DATAGRAM_CHECKED_SIZE - 1]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_quotes_roundtrip(quote: StockQuote) {
+            let decoded = roundtrip(&quote);
+            prop_assert_eq!(decoded.to_string(), quote.to_string());
+            let datagram = StreamQuote { stream_seq: 1, quote: quote.clone() };
+            let checked = StreamQuote::from_checked_bytes(&datagram.to_checked_bytes().unwrap()).unwrap();
+            prop_assert_eq!(checked.quote.to_string(), quote.to_string());
+        }
+    }
+}
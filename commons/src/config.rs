@@ -0,0 +1,269 @@
+//! Общая конфигурация приложений Quote.
+//!
+//! Содержит значения, общие для сервера и клиента, и загрузчик
+//! многоуровневой конфигурации [`ConfigLoader`]: значения по умолчанию
+//! перекрываются TOML-файлом, затем переменными окружения и, наконец,
+//! ключами командной строки. Файл и переменные окружения передаются
+//! загрузчику явно, поэтому конфигурацию можно собрать в тестах без
+//! глобального состояния.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::config::ConfigLoader;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct AppConfig {
+//!     port: u16,
+//!     log_folder: String,
+//! }
+//!
+//! impl Default for AppConfig {
+//!     fn default() -> Self {
+//!         Self { port: 8888, log_folder: "log".to_string() }
+//!     }
+//! }
+//!
+//! let config: AppConfig = ConfigLoader::new("APP")
+//!     .unwrap()
+//!     .env([("APP_LOG_FOLDER".to_string(), "logs".to_string())])
+//!     .set_opt("port", Some(9000u16))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(config.port, 9000);
+//! assert_eq!(config.log_folder, "logs");
+//! ```
+
+use crate::errors::{QuoteError, ResultExt};
+use crate::netdefaults::{PORTS_ALLOWED, is_allowed_port};
+use log::warn;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{fs, marker::PhantomData, path::Path};
+use toml::{Table, Value};
+
+/// Название директории для log-файлов.
+pub const LOG_FOLDER: &str = "log";
+
+/// Название каталога для хранения данных проекта.
+pub const DATA_FOLDER: &str = "data";
+
+/// Наименьшая допустимая временная метка котировки (2000-01-01 00:00:00 UTC),
+/// миллисекунды.
+pub const MIN_QUOTE_TIMESTAMP_MS: u64 = 946_684_800_000;
+
+/// Допустимое опережение временной метки котировки относительно местных
+/// часов (миллисекунды).
+pub const MAX_CLOCK_SKEW_MS: u64 = 300_000;
+
+/// Наибольший допустимый размер кадра с префиксом длины, байт.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// Валидатор порта для ключей командной строки: порт должен входить
+/// в [`PORTS_ALLOWED`].
+pub fn validate_port(s: &str) -> Result<u16, String> {
+    let port: u16 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid port number: {s}"))?;
+    check_port(port)
+}
+
+/// Проверить, что порт входит в [`PORTS_ALLOWED`].
+pub fn check_port(port: u16) -> Result<u16, String> {
+    if is_allowed_port(port) {
+        Ok(port)
+    } else {
+        Err(format!(
+            "port number {} not in range {} — {}",
+            port,
+            PORTS_ALLOWED.start(),
+            PORTS_ALLOWED.end()
+        ))
+    }
+}
+
+/// Загрузчик многоуровневой конфигурации в типизированную структуру `T`.
+///
+/// Уровни применяются в порядке вызова методов; каждый следующий
+/// перекрывает значения предыдущего.
+pub struct ConfigLoader<T> {
+    env_prefix: String,
+    table: Table,
+    config: PhantomData<T>,
+}
+
+impl<T> ConfigLoader<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    /// Создать загрузчик со значениями по умолчанию из `T::default()`.
+    ///
+    /// ## Args
+    /// - `env_prefix` — префикс переменных окружения, например `QSERVER`
+    ///   для `QSERVER_PORT`
+    pub fn new(env_prefix: &str) -> Result<Self, QuoteError> {
+        let table = Table::try_from(T::default()).map_err(|err| {
+            QuoteError::value_err(format!("некорректные значения по умолчанию: {err}"))
+        })?;
+        Ok(Self {
+            env_prefix: env_prefix.to_uppercase(),
+            table,
+            config: PhantomData,
+        })
+    }
+
+    /// Перекрыть значения содержимым TOML-файла.
+    ///
+    /// ## Args
+    /// - `path` — путь к файлу
+    /// - `required` — если `false`, отсутствующий файл пропускается
+    pub fn file(mut self, path: &Path, required: bool) -> Result<Self, QuoteError> {
+        if !required && !path.exists() {
+            return Ok(self);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("не удалось прочитать конфигурацию {}", path.display()))?;
+        let layer = content.parse::<Table>().map_err(|err| {
+            QuoteError::value_err(format!(
+                "некорректный файл конфигурации {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        self.table.extend(layer);
+        Ok(self)
+    }
+
+    /// Перекрыть значения переменными окружения вида `ПРЕФИКС_КЛЮЧ`.
+    ///
+    /// Переменные передаются явно: приложения — `std::env::vars()`,
+    /// тесты — собственный набор. Тип значения определяется по значению
+    /// по умолчанию, а для ключей без него — по виду строки (целое, дробное,
+    /// логическое или строка).
+    pub fn env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let prefix = format!("{}_", self.env_prefix);
+        for (name, raw) in vars {
+            let Some(key) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let key = key.to_lowercase();
+            let value = typed_value(self.table.get(&key), &raw);
+            self.table.insert(key, value);
+        }
+        self
+    }
+
+    /// Перекрыть значение ключом командной строки, если он задан.
+    pub fn set_opt<V: Serialize>(mut self, key: &str, value: Option<V>) -> Self {
+        match value.map(Value::try_from) {
+            Some(Ok(value)) => {
+                self.table.insert(key.to_string(), value);
+            }
+            Some(Err(err)) => warn!("Значение ключа {key} пропущено: {err}"),
+            None => {}
+        }
+        self
+    }
+
+    /// Сформировать итоговую конфигурацию.
+    pub fn build(self) -> Result<T, QuoteError> {
+        self.table
+            .try_into()
+            .map_err(|err| QuoteError::value_err(format!("некорректная конфигурация: {err}")))
+    }
+}
+
+/// Преобразовать строку из переменной окружения в значение TOML.
+fn typed_value(current: Option<&Value>, raw: &str) -> Value {
+    let raw = raw.trim();
+    match current {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        _ => raw
+            .parse::<i64>()
+            .map(Value::Integer)
+            .or_else(|_| raw.parse::<f64>().map(Value::Float))
+            .or_else(|_| raw.parse::<bool>().map(Value::Boolean))
+            .unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestConfig {
+        port: u16,
+        name: String,
+        udp: Option<u16>,
+    }
+
+    impl Default for TestConfig {
+        fn default() -> Self {
+            Self {
+                port: 8888,
+                name: "log".to_string(),
+                udp: None,
+            }
+        }
+    }
+
+    #[test]
+    fn layers_override_in_order() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "port = 9000\nname = \"file\"").unwrap();
+
+        let config: TestConfig = ConfigLoader::new("test")
+            .unwrap()
+            .file(file.path(), true)
+            .unwrap()
+            .env([
+                ("TEST_NAME".to_string(), "123".to_string()),
+                ("TEST_UDP".to_string(), "34254".to_string()),
+                ("OTHER_PORT".to_string(), "1".to_string()),
+            ])
+            .set_opt("port", Some(9100u16))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config,
+            TestConfig {
+                port: 9100,
+                name: "123".to_string(),
+                udp: Some(34254),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_optional_file_is_skipped() {
+        let loader = ConfigLoader::<TestConfig>::new("test").unwrap();
+        let path = Path::new("/non/existent/config.toml");
+
+        assert!(loader.file(path, false).unwrap().build().is_ok());
+        let loader = ConfigLoader::<TestConfig>::new("test").unwrap();
+        assert!(loader.file(path, true).is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let result = ConfigLoader::<TestConfig>::new("test")
+            .unwrap()
+            .env([("TEST_PORT".to_string(), "many".to_string())])
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn port_validator_checks_range() {
+        assert_eq!(validate_port("8888"), Ok(8888));
+        assert!(validate_port("80").is_err());
+        assert!(validate_port("port").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Чтение и запись моделей в формате CSV (RFC 4180).
+//!
+//! Модель описывает свои колонки через [`CsvRecord`], а функции модуля
+//! берут на себя заголовок, экранирование и разбор строк. При чтении
+//! колонки сопоставляются по заголовку, поэтому их порядок в файле может
+//! отличаться, а отсутствующие колонки считаются пустыми.
+//!
+//! Значения с переводом строки внутри кавычек не поддерживаются: одна
+//! запись — одна строка файла.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::csv::{read_csv, write_csv};
+//! use commons::models::StockQuote;
+//!
+//! let quote: StockQuote = "AAPL|150.5|100|1700000000000|buy".parse().unwrap();
+//! let mut buf = Vec::new();
+//! write_csv(&mut buf, [&quote]).unwrap();
+//!
+//! let quotes: Vec<StockQuote> = read_csv(buf.as_slice()).unwrap();
+//! assert_eq!(quotes[0].ticker, "AAPL");
+//! ```
+
+use crate::errors::{QuoteError, ResultExt};
+use std::borrow::Cow;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+/// Модель, представимая строкой CSV.
+pub trait CsvRecord: Sized {
+    /// Названия колонок в порядке записи.
+    const HEADER: &'static [&'static str];
+
+    /// Значения колонок в порядке [`CsvRecord::HEADER`].
+    fn to_fields(&self) -> Vec<String>;
+
+    /// Собрать модель из значений в порядке [`CsvRecord::HEADER`];
+    /// отсутствующее значение — пустая строка.
+    fn from_fields(fields: &[String]) -> Result<Self, QuoteError>;
+
+    /// Строка заголовка (без перевода строки); то же, что [`header`].
+    fn csv_header() -> String {
+        header::<Self>()
+    }
+
+    /// Запись строкой CSV; то же, что [`to_csv_line`].
+    fn to_csv_row(&self) -> String {
+        to_csv_line(self)
+    }
+
+    /// Разобрать строку CSV; то же, что [`from_csv_line`].
+    fn from_csv_row(line: &str) -> Result<Self, QuoteError> {
+        from_csv_line(line)
+    }
+}
+
+/// Строка заголовка для модели `T` (без перевода строки).
+pub fn header<T: CsvRecord>() -> String {
+    join_fields(T::HEADER.iter().copied())
+}
+
+/// Представить запись строкой CSV (без перевода строки).
+pub fn to_csv_line<T: CsvRecord>(record: &T) -> String {
+    join_fields(record.to_fields().iter().map(String::as_str))
+}
+
+/// Разобрать строку CSV с колонками в порядке [`CsvRecord::HEADER`].
+pub fn from_csv_line<T: CsvRecord>(line: &str) -> Result<T, QuoteError> {
+    let mut fields = split_line(line)?;
+    fields.resize(T::HEADER.len().max(fields.len()), String::new());
+    T::from_fields(&fields[..T::HEADER.len()])
+}
+
+/// Записать заголовок и записи.
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::IoError`] при ошибке записи.
+pub fn write_csv<'a, W, T, I>(mut writer: W, records: I) -> Result<(), QuoteError>
+where
+    W: Write,
+    T: CsvRecord + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let io_err = |err| QuoteError::io_err("ошибка записи CSV", err);
+    writeln!(writer, "{}", header::<T>()).map_err(io_err)?;
+    for record in records {
+        writeln!(writer, "{}", to_csv_line(record)).map_err(io_err)?;
+    }
+    writer.flush().map_err(io_err)
+}
+
+/// Прочитать записи; первая строка — заголовок.
+///
+/// Колонки сопоставляются по названию; неизвестные колонки пропускаются,
+/// пустые строки игнорируются.
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::ValueError`] с номером строки, если заголовок
+/// отсутствует или запись не удалось разобрать, и [`QuoteError::IoError`]
+/// при ошибке чтения.
+pub fn read_csv<T: CsvRecord, R: BufRead>(reader: R) -> Result<Vec<T>, QuoteError> {
+    let mut lines = reader.lines().enumerate();
+    let columns = match lines.next() {
+        Some((_, line)) => {
+            let line = line.context("ошибка чтения CSV")?;
+            column_positions::<T>(&split_line(&line)?)
+        }
+        None => return Err(QuoteError::value_err("CSV без заголовка")),
+    };
+
+    let mut records = Vec::new();
+    for (num, line) in lines {
+        let line = line.context("ошибка чтения CSV")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_line(&line)?;
+        let ordered: Vec<String> = columns
+            .iter()
+            .map(|pos| pos.and_then(|i| fields.get(i).cloned()).unwrap_or_default())
+            .collect();
+        let record = T::from_fields(&ordered)
+            .map_err(|err| QuoteError::value_err(format!("строка {} CSV: {}", num + 1, err)))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Разобрать значение колонки; `field` — название для сообщения об ошибке.
+pub fn parse_field<T: FromStr>(value: &str, field: &str) -> Result<T, QuoteError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| QuoteError::value_err(format!("некорректное значение {field}: '{value}'")))
+}
+
+/// Разобрать необязательное значение колонки: пустое значение — `None`.
+pub fn parse_opt_field<T: FromStr>(value: &str, field: &str) -> Result<Option<T>, QuoteError> {
+    if value.trim().is_empty() {
+        Ok(None)
+    } else {
+        parse_field(value, field).map(Some)
+    }
+}
+
+/// Позиции колонок модели в заголовке файла.
+fn column_positions<T: CsvRecord>(header: &[String]) -> Vec<Option<usize>> {
+    T::HEADER
+        .iter()
+        .map(|name| header.iter().position(|column| column.trim() == *name))
+        .collect()
+}
+
+fn join_fields<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    fields.map(escape).collect::<Vec<_>>().join(",")
+}
+
+/// Заключить значение в кавычки, если в нём есть запятая, кавычка или
+/// перевод строки.
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Разбить строку CSV на значения с учётом кавычек.
+fn split_line(line: &str) -> Result<Vec<String>, QuoteError> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(QuoteError::value_err(format!(
+            "незакрытая кавычка в строке CSV: {line}"
+        )));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Candle, StockQuote, Transaction};
+    use proptest::prelude::*;
+
+    fn quote() -> StockQuote {
+        StockQuote {
+            ticker: "AAPL".to_string(),
+            price: "150.5".parse().unwrap(),
+            volume: 100,
+            timestamp: 1_700_000_000_000,
+            transaction: Transaction::Sell,
+            seq: 7,
+            bid: Some(150.4),
+            ask: None,
+            exchange: Some("NYSE, Inc \"Big Board\"".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn quotes_roundtrip_with_header() {
+        let mut buf = Vec::new();
+        write_csv(&mut buf, [&quote()]).unwrap();
+        let text = String::from_utf8(buf.clone()).unwrap();
+
+        assert_eq!(
+            text,
+            "ticker,price,volume,timestamp,transaction,seq,bid,ask,exchange,currency\n\
+             AAPL,150.5,100,1700000000000,sell,7,150.4,,\"NYSE, Inc \"\"Big Board\"\"\",\n"
+        );
+        let quotes: Vec<StockQuote> = read_csv(buf.as_slice()).unwrap();
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes[0].exchange, quote().exchange);
+        assert_eq!(quotes[0].seq, 7);
+        assert!(quotes[0].ask.is_none());
+    }
+
+    #[test]
+    fn columns_are_matched_by_header() {
+        let text = "timestamp,ticker,price,volume,transaction,note\n\
+                    1700000000000,TSLA,10,5,buy,ignored\n\n";
+        let quotes: Vec<StockQuote> = read_csv(text.as_bytes()).unwrap();
+
+        assert_eq!(quotes[0].ticker, "TSLA");
+        assert_eq!(quotes[0].timestamp, 1_700_000_000_000);
+        assert_eq!(quotes[0].seq, 0);
+    }
+
+    #[test]
+    fn candle_roundtrip_by_line() {
+        let candle = Candle::new(&quote(), 60_000);
+        let line = to_csv_line(&candle);
+
+        assert_eq!(
+            header::<Candle>(),
+            "ticker,open,high,low,close,volume,start_ts,interval"
+        );
+        assert_eq!(from_csv_line::<Candle>(&line).unwrap(), candle);
+    }
+
+    #[test]
+    fn row_methods_match_line_functions() {
+        let quote = quote();
+        assert_eq!(StockQuote::csv_header(), header::<StockQuote>());
+        assert_eq!(quote.to_csv_row(), to_csv_line(&quote));
+
+        let parsed = StockQuote::from_csv_row(&quote.to_csv_row()).unwrap();
+        assert_eq!(parsed.to_string(), quote.to_string());
+    }
+
+    #[test]
+    fn invalid_csv_is_rejected() {
+        assert!(read_csv::<StockQuote, _>("".as_bytes()).is_err());
+        let text = "ticker,price,volume,timestamp,transaction\nAAPL,cheap,1,1,buy\n";
+        let err = read_csv::<StockQuote, _>(text.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("строка 2"));
+        assert!(split_line("\"AAPL,1").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_records_roundtrip_by_line(quote: StockQuote, candle: Candle) {
+            let decoded = from_csv_line::<StockQuote>(&to_csv_line(&quote)).unwrap();
+            prop_assert_eq!(decoded.to_string(), quote.to_string());
+            prop_assert_eq!(from_csv_line::<Candle>(&to_csv_line(&candle)).unwrap(), candle);
+        }
+    }
+}
@@ -1,10 +1,141 @@
 //! Собственные типы ошибок приложения.
 //!
 //! Для поддержки функциональности применяется крейт `thiserror`.
+//!
+//! Ошибки ввода-вывода и JSON сохраняют исходную ошибку: она входит
+//! в сообщение и доступна через [`std::error::Error::source`].
+//!
+//! Каждому варианту ошибки соответствует вид [`ErrorKind`] со стабильными
+//! числовым и строковым кодами: их используют для кодов завершения
+//! и сетевых ответов вместо сравнения текстов сообщений.
+//!
+//! Подписи видов ошибок выводятся на текущем языке ([`crate::i18n`]).
+//!
+//! Описание операции к ошибке добавляет [`ResultExt`]: исходная ошибка
+//! остаётся источником, а вид ошибки не меняется.
+//!
+//! ```
+//! use commons::errors::{ErrorKind, QuoteError, ResultExt};
+//!
+//! fn parse(text: &str) -> Result<u32, QuoteError> {
+//!     text.parse()
+//!         .map_err(|_| QuoteError::value_err(format!("не число: '{text}'")))
+//! }
+//!
+//! let err = parse("x").context("разбор порта").unwrap_err();
+//! assert_eq!(err.to_string(), "разбор порта: не число: 'x'");
+//! assert_eq!(err.kind(), ErrorKind::Value);
+//! ```
 
+use crate::i18n::Msg;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::PoisonError;
 use thiserror::Error;
 
+/// Вид ошибки со стабильными кодами.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Некорректное значение.
+    Value,
+    /// Ошибка генерации тикеров.
+    Ticker,
+    /// Ошибка блокировки.
+    Lock,
+    /// Ошибка сервера или сети.
+    Server,
+    /// Некорректная или отклонённая команда.
+    Command,
+    /// Нарушение протокола обмена.
+    Protocol,
+    /// Истекло время ожидания.
+    Timeout,
+    /// Ошибка ввода-вывода.
+    Io,
+    /// Ошибка разбора данных.
+    Parse,
+    /// Системная ошибка.
+    Runtime,
+}
+
+impl ErrorKind {
+    /// Все виды ошибок.
+    pub const ALL: [ErrorKind; 10] = [
+        ErrorKind::Value,
+        ErrorKind::Ticker,
+        ErrorKind::Lock,
+        ErrorKind::Server,
+        ErrorKind::Command,
+        ErrorKind::Protocol,
+        ErrorKind::Timeout,
+        ErrorKind::Io,
+        ErrorKind::Parse,
+        ErrorKind::Runtime,
+    ];
+
+    /// Числовой код, пригодный в качестве кода завершения процесса.
+    pub fn code(self) -> u8 {
+        match self {
+            ErrorKind::Value => 10,
+            ErrorKind::Ticker => 11,
+            ErrorKind::Lock => 12,
+            ErrorKind::Server => 20,
+            ErrorKind::Command => 21,
+            ErrorKind::Protocol => 22,
+            ErrorKind::Timeout => 23,
+            ErrorKind::Io => 30,
+            ErrorKind::Parse => 31,
+            ErrorKind::Runtime => 40,
+        }
+    }
+
+    /// Строковый код для сетевых ответов и журналов.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Value => "value",
+            ErrorKind::Ticker => "ticker",
+            ErrorKind::Lock => "lock",
+            ErrorKind::Server => "server",
+            ErrorKind::Command => "command",
+            ErrorKind::Protocol => "protocol",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Io => "io",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Runtime => "runtime",
+        }
+    }
+
+    /// Вид ошибки по числовому коду.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorKind {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| QuoteError::value_err(format!("неизвестный код ошибки: {s}")))
+    }
+}
+
+const TICKER: Msg = Msg::new("ошибка при формировании тикера", "ticker error");
+const LOCK: Msg = Msg::new("ошибка блокировки", "lock error");
+const PARSE: Msg = Msg::new("ошибка разбора", "parse error");
+const TIMEOUT: Msg = Msg::new("истекло время ожидания", "timed out");
+const PROTOCOL: Msg = Msg::new("нарушение протокола", "protocol violation");
+const IO: Msg = Msg::new("ошибка ввода-вывода", "I/O error");
+const JSON: Msg = Msg::new("ошибка JSON", "JSON error");
+
 /// Дерево ошибок приложений Quote.
 #[derive(Error, Debug)]
 pub enum QuoteError {
@@ -15,11 +146,11 @@ pub enum QuoteError {
     ValueError(String),
 
     /// Ошибка генерации тикеров.
-    #[error("ошибка при формировании тикера: {0}")]
+    #[error("{TICKER}: {0}")]
     TickerError(String),
 
     /// Ошибка блокировки mutex.
-    #[error("ошибка блокировки: {0}")]
+    #[error("{LOCK}: {0}")]
     LockError(String),
 
     // Ошибки при работе сервера.
@@ -33,6 +164,48 @@ pub enum QuoteError {
     /// Системная ошибка, аналог Panic, когда дальнейшая работа невозможна.
     #[error("RuntimeError: {0}")]
     RunTimeError(String),
+
+    /// Ошибка ввода-вывода.
+    #[error("{context}: {source}")]
+    IoError {
+        /// Описание операции, при которой возникла ошибка.
+        context: String,
+        /// Исходная ошибка.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Ошибка сериализации или разбора JSON.
+    #[error("{context}: {source}")]
+    JsonError {
+        /// Описание операции, при которой возникла ошибка.
+        context: String,
+        /// Исходная ошибка.
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// Ошибка разбора данных.
+    #[error("{PARSE}: {0}")]
+    ParseError(String),
+
+    /// Истекло время ожидания.
+    #[error("{TIMEOUT}: {0}")]
+    TimeoutError(String),
+
+    /// Нарушение протокола обмена с сервером.
+    #[error("{PROTOCOL}: {0}")]
+    ProtocolError(String),
+
+    /// Ошибка, дополненная описанием операции ([`ResultExt`]).
+    #[error("{context}: {source}")]
+    Context {
+        /// Описание операции, при которой возникла ошибка.
+        context: String,
+        /// Исходная ошибка.
+        #[source]
+        source: Box<QuoteError>,
+    },
 }
 
 impl<T> From<PoisonError<T>> for QuoteError {
@@ -41,6 +214,25 @@ impl<T> From<PoisonError<T>> for QuoteError {
     }
 }
 
+/// Сообщение об ошибке значения — [`QuoteError::ValueError`].
+impl From<String> for QuoteError {
+    fn from(message: String) -> Self {
+        QuoteError::value_err(message)
+    }
+}
+
+impl From<std::io::Error> for QuoteError {
+    fn from(err: std::io::Error) -> Self {
+        QuoteError::io_err(IO.text(), err)
+    }
+}
+
+impl From<serde_json::Error> for QuoteError {
+    fn from(err: serde_json::Error) -> Self {
+        QuoteError::json_err(JSON.text(), err)
+    }
+}
+
 impl QuoteError {
     /// Конструктор для ошибки [`QuoteError::ValueError`].
     pub fn value_err(message: impl Into<String>) -> QuoteError {
@@ -66,4 +258,186 @@ impl QuoteError {
     pub fn runtime_err(message: impl Into<String>) -> QuoteError {
         Self::RunTimeError(message.into())
     }
+
+    /// Конструктор для ошибки [`QuoteError::IoError`].
+    ///
+    /// ## Args
+    /// - `context` — описание операции, например путь к файлу
+    /// - `source` — исходная ошибка ввода-вывода
+    pub fn io_err(context: impl Into<String>, source: std::io::Error) -> QuoteError {
+        Self::IoError {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Конструктор для ошибки [`QuoteError::JsonError`].
+    pub fn json_err(context: impl Into<String>, source: serde_json::Error) -> QuoteError {
+        Self::JsonError {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Конструктор для ошибки [`QuoteError::ParseError`].
+    pub fn parse_err(message: impl Into<String>) -> QuoteError {
+        Self::ParseError(message.into())
+    }
+
+    /// Конструктор для ошибки [`QuoteError::TimeoutError`].
+    pub fn timeout_err(message: impl Into<String>) -> QuoteError {
+        Self::TimeoutError(message.into())
+    }
+
+    /// Конструктор для ошибки [`QuoteError::ProtocolError`].
+    pub fn protocol_err(message: impl Into<String>) -> QuoteError {
+        Self::ProtocolError(message.into())
+    }
+
+    /// Вид ошибки.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            QuoteError::ValueError(_) => ErrorKind::Value,
+            QuoteError::TickerError(_) => ErrorKind::Ticker,
+            QuoteError::LockError(_) => ErrorKind::Lock,
+            QuoteError::ServerError(_) => ErrorKind::Server,
+            QuoteError::CommandError(_) => ErrorKind::Command,
+            QuoteError::RunTimeError(_) => ErrorKind::Runtime,
+            QuoteError::IoError { .. } => ErrorKind::Io,
+            QuoteError::JsonError { .. } => ErrorKind::Parse,
+            QuoteError::ParseError(_) => ErrorKind::Parse,
+            QuoteError::TimeoutError(_) => ErrorKind::Timeout,
+            QuoteError::ProtocolError(_) => ErrorKind::Protocol,
+            QuoteError::Context { source, .. } => source.kind(),
+        }
+    }
+
+    /// Исходная ошибка без описаний, добавленных [`ResultExt`].
+    pub fn root(&self) -> &QuoteError {
+        match self {
+            QuoteError::Context { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// Числовой код ошибки ([`ErrorKind::code`]).
+    pub fn code(&self) -> u8 {
+        self.kind().code()
+    }
+}
+
+/// Дополнение ошибки описанием операции вместо повторяющихся
+/// `map_err(|err| ...(format!(...), err))`.
+///
+/// Для [`QuoteError`] исходная ошибка сохраняется в
+/// [`QuoteError::Context`]; ошибки ввода-вывода и JSON становятся
+/// [`QuoteError::IoError`] и [`QuoteError::JsonError`] с этим описанием.
+pub trait ResultExt<T> {
+    /// Дополнить ошибку описанием, сформированным только при ошибке.
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, QuoteError>;
+
+    /// Дополнить ошибку описанием `context`.
+    fn context(self, context: impl Into<String>) -> Result<T, QuoteError>
+    where
+        Self: Sized,
+    {
+        self.with_context(|| context)
+    }
+}
+
+impl<T> ResultExt<T> for Result<T, QuoteError> {
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, QuoteError> {
+        self.map_err(|source| QuoteError::Context {
+            context: context().into(),
+            source: Box::new(source),
+        })
+    }
+}
+
+impl<T> ResultExt<T> for Result<T, std::io::Error> {
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, QuoteError> {
+        self.map_err(|err| QuoteError::io_err(context(), err))
+    }
+}
+
+impl<T> ResultExt<T> for Result<T, serde_json::Error> {
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, QuoteError> {
+        self.map_err(|err| QuoteError::json_err(context(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_codes_are_unique_and_round_trip() {
+        for kind in ErrorKind::ALL {
+            assert_eq!(ErrorKind::from_code(kind.code()), Some(kind));
+            assert_eq!(kind.as_str().parse::<ErrorKind>().unwrap(), kind);
+        }
+        assert!(ErrorKind::from_code(0).is_none());
+        assert!("unknown".parse::<ErrorKind>().is_err());
+    }
+
+    #[test]
+    fn error_reports_kind() {
+        assert_eq!(QuoteError::command_err("x").kind(), ErrorKind::Command);
+        assert_eq!(QuoteError::timeout_err("x").code(), 23);
+        assert_eq!(
+            QuoteError::from(PoisonError::new(())).kind(),
+            ErrorKind::Lock
+        );
+    }
+
+    #[test]
+    fn io_and_json_errors_keep_source() {
+        let err: QuoteError = std::io::Error::other("disk").into();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(err.to_string(), "ошибка ввода-вывода: disk");
+
+        let json = serde_json::from_str::<u32>("x").unwrap_err();
+        let err = QuoteError::json_err("разбор состояния", json);
+        assert_eq!(err.kind(), ErrorKind::Parse);
+        assert!(std::error::Error::source(&err).is_some());
+        assert!(err.to_string().starts_with("разбор состояния: "));
+    }
+
+    #[test]
+    fn context_keeps_kind_and_source() {
+        let result: Result<(), QuoteError> = Err(QuoteError::timeout_err("нет ответа"));
+        let err = result
+            .context("подписка")
+            .with_context(|| format!("сервер {}", 1))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "сервер 1: подписка: истекло время ожидания: нет ответа"
+        );
+        assert_eq!(err.kind(), ErrorKind::Timeout);
+        assert!(matches!(err.root(), QuoteError::TimeoutError(_)));
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(
+            source.to_string(),
+            "подписка: истекло время ожидания: нет ответа"
+        );
+    }
+
+    #[test]
+    fn context_for_io_and_json_results() {
+        let io: Result<(), std::io::Error> = Err(std::io::Error::other("disk"));
+        let err = io.context("чтение файла").unwrap_err();
+        assert!(matches!(err, QuoteError::IoError { .. }));
+        assert_eq!(err.to_string(), "чтение файла: disk");
+
+        let err = serde_json::from_str::<u32>("x")
+            .with_context(|| "разбор состояния")
+            .unwrap_err();
+        assert!(matches!(err, QuoteError::JsonError { .. }));
+
+        let ok: Result<u32, std::io::Error> = Ok(1);
+        assert_eq!(ok.with_context(|| -> String { unreachable!() }).unwrap(), 1);
+    }
 }
@@ -0,0 +1,139 @@
+//! Экранирование разделителя полей в строковом формате.
+//!
+//! Используется кодом, который генерирует `QuoteDisplay` с атрибутом
+//! `#[quote_display(escape)]`. Перед символом `\` и первым символом
+//! разделителя в значении записывается `\`, поэтому разделитель внутри
+//! значения не разбивает строку на лишние поля. При разборе `\` снимается,
+//! а следующий за ним символ берётся как есть.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::escape::{escape, split_escaped};
+//!
+//! let line = format!("{}|{}", escape("a|b", "|"), escape(r"c\d", "|"));
+//! assert_eq!(line, r"a\|b|c\\d");
+//! assert_eq!(split_escaped(&line, "|"), ["a|b", r"c\d"]);
+//! ```
+
+use std::fmt;
+
+/// Символ экранирования.
+pub const ESCAPE: char = '\\';
+
+/// Обёртка над `fmt::Write`, экранирующая записываемый текст.
+///
+/// Экранируется первый символ разделителя, а не весь разделитель: так
+/// разделитель из нескольких символов, записанный по частям, тоже
+/// не попадёт в строку неэкранированным.
+pub struct Escaper<'a, W: fmt::Write + ?Sized> {
+    out: &'a mut W,
+    /// Первый символ разделителя.
+    first: char,
+}
+
+impl<'a, W: fmt::Write + ?Sized> Escaper<'a, W> {
+    /// Экранирующая запись в `out` для разделителя `delimiter`.
+    ///
+    /// ## Паника
+    ///
+    /// Если разделитель пуст.
+    pub fn new(out: &'a mut W, delimiter: &str) -> Self {
+        let first = delimiter.chars().next().expect("пустой разделитель полей");
+        Self { out, first }
+    }
+}
+
+impl<W: fmt::Write + ?Sized> fmt::Write for Escaper<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(pos) = rest.find([ESCAPE, self.first]) {
+            self.out.write_str(&rest[..pos])?;
+            self.out.write_char(ESCAPE)?;
+            let c = rest[pos..].chars().next().expect("символ найден");
+            self.out.write_char(c)?;
+            rest = &rest[pos + c.len_utf8()..];
+        }
+        self.out.write_str(rest)
+    }
+}
+
+/// Значение `value` с экранированием для разделителя `delimiter`.
+pub fn escape(value: &str, delimiter: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    fmt::Write::write_str(&mut Escaper::new(&mut escaped, delimiter), value)
+        .expect("запись в String не завершается ошибкой");
+    escaped
+}
+
+/// Разделить строку `line` по неэкранированным разделителям `delimiter`
+/// и снять экранирование полей.
+///
+/// Одиночный `\` в конце строки сохраняется как есть.
+pub fn split_escaped(line: &str, delimiter: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c == ESCAPE {
+            rest = &rest[c.len_utf8()..];
+            match rest.chars().next() {
+                Some(next) => {
+                    part.push(next);
+                    rest = &rest[next.len_utf8()..];
+                }
+                None => part.push(ESCAPE),
+            }
+        } else if let Some(after) = rest.strip_prefix(delimiter) {
+            parts.push(std::mem::take(&mut part));
+            rest = after;
+        } else {
+            part.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    parts.push(part);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_values_are_unchanged() {
+        assert_eq!(escape("AAPL", "|"), "AAPL");
+        assert_eq!(split_escaped("AAPL|10|", "|"), ["AAPL", "10", ""]);
+        assert_eq!(split_escaped("", "|"), [""]);
+    }
+
+    #[test]
+    fn roundtrip_with_delimiter_and_escape() {
+        for delimiter in ["|", ";", "{}", "::"] {
+            let values = ["a|b", r"c\d", "{x}", "e::f:", r"\", "", "ёж;"];
+            let line = values
+                .iter()
+                .map(|value| escape(value, delimiter))
+                .collect::<Vec<_>>()
+                .join(delimiter);
+            assert_eq!(split_escaped(&line, delimiter), values, "{delimiter}");
+        }
+    }
+
+    #[test]
+    fn escaper_handles_split_writes() {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let mut escaper = Escaper::new(&mut out, "{}");
+        escaper.write_str("a{").unwrap();
+        escaper.write_str("}b").unwrap();
+        assert_eq!(out, r"a\{}b");
+        assert_eq!(split_escaped(&out, "{}"), ["a{}b"]);
+    }
+
+    #[test]
+    fn trailing_escape_is_kept() {
+        assert_eq!(split_escaped(r"a|b\", "|"), ["a", r"b\"]);
+    }
+}
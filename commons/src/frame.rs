@@ -0,0 +1,529 @@
+//! Кадры с префиксом длины для двоичных протоколов поверх TCP.
+//!
+//! Кадр — длина данных `u32` (little-endian), затем сами данные. Размер
+//! данных ограничен [`MAX_FRAME_SIZE`] (или значением, заданным читателю
+//! и писателю), чтобы повреждённый префикс не приводил к выделению
+//! гигантского буфера.
+//!
+//! [`FrameReader`] накапливает частично прочитанный кадр: если чтение
+//! прервано таймаутом, следующий вызов продолжает с того же места.
+//!
+//! С feature `async` доступны [`AsyncFrameReader`] и [`AsyncFrameWriter`]
+//! для `tokio::io`; формат кадров тот же.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::frame::{FrameReader, FrameWriter};
+//!
+//! let mut writer = FrameWriter::new(Vec::new());
+//! writer.write_frame(b"PING").unwrap();
+//! writer.write_frame(b"").unwrap();
+//! let bytes = writer.into_inner();
+//! assert_eq!(&bytes[..8], b"\x04\0\0\0PING");
+//!
+//! let mut reader = FrameReader::new(bytes.as_slice());
+//! assert_eq!(reader.read_frame().unwrap().unwrap(), b"PING");
+//! assert_eq!(reader.read_frame().unwrap().unwrap(), b"");
+//! assert!(reader.read_frame().unwrap().is_none());
+//! ```
+
+use crate::config::MAX_FRAME_SIZE;
+use crate::errors::{QuoteError, ResultExt};
+use std::io::{self, Read, Write};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Размер префикса длины кадра, байт.
+pub const FRAME_HEADER_SIZE: usize = 4;
+
+/// Проверить размер данных кадра.
+fn check_len(len: usize, max_size: usize) -> Result<(), QuoteError> {
+    if len > max_size {
+        return Err(QuoteError::protocol_err(format!(
+            "размер кадра {len} байт превышает {max_size}"
+        )));
+    }
+    Ok(())
+}
+
+/// Кадр с префиксом длины для записи одним вызовом.
+fn encode(payload: &[u8], max_size: usize) -> Result<Vec<u8>, QuoteError> {
+    check_len(payload.len(), max_size)?;
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Состояние сборки кадра из прочитанных байт.
+enum Assembly {
+    /// Кадр собран.
+    Frame(Vec<u8>),
+    /// Не хватает указанного числа байт.
+    Need(usize),
+}
+
+/// Проверить накопленные байты `buf`: выдать собранный кадр (буфер
+/// очищается) или число недостающих байт.
+///
+/// При длине больше `max_size` буфер очищается и возвращается
+/// [`QuoteError::ProtocolError`].
+fn assemble(buf: &mut Vec<u8>, max_size: usize) -> Result<Assembly, QuoteError> {
+    let Some(header) = buf.first_chunk::<FRAME_HEADER_SIZE>() else {
+        return Ok(Assembly::Need(FRAME_HEADER_SIZE - buf.len()));
+    };
+    let len = u32::from_le_bytes(*header) as usize;
+    if let Err(err) = check_len(len, max_size) {
+        buf.clear();
+        return Err(err);
+    }
+    if buf.len() < FRAME_HEADER_SIZE + len {
+        return Ok(Assembly::Need(FRAME_HEADER_SIZE + len - buf.len()));
+    }
+    let mut frame = std::mem::take(buf);
+    frame.drain(..FRAME_HEADER_SIZE);
+    Ok(Assembly::Frame(frame))
+}
+
+/// Ошибка потока, закончившегося посреди кадра; частичный кадр
+/// отбрасывается.
+fn truncated(buf: &mut Vec<u8>) -> QuoteError {
+    buf.clear();
+    QuoteError::protocol_err("кадр оборван")
+}
+
+/// Запись кадров в `Write`.
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    inner: W,
+    max_size: usize,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Создать писателя с ограничением [`MAX_FRAME_SIZE`].
+    pub fn new(inner: W) -> Self {
+        Self::with_max_size(inner, MAX_FRAME_SIZE)
+    }
+
+    /// Создать писателя с ограничением размера данных кадра `max_size`.
+    pub fn with_max_size(inner: W, max_size: usize) -> Self {
+        Self { inner, max_size }
+    }
+
+    /// Записать кадр. Префикс и данные передаются одним вызовом
+    /// `write_all`, поэтому кадры из разных потоков не перемешиваются при
+    /// записи в общий `TcpStream`.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ProtocolError`], если данные длиннее
+    /// ограничения (в поток ничего не записывается), и
+    /// [`QuoteError::IoError`] при ошибке записи.
+    pub fn write_frame(&mut self, payload: &[u8]) -> Result<(), QuoteError> {
+        let frame = encode(payload, self.max_size)?;
+        self.inner.write_all(&frame).context("ошибка записи кадра")
+    }
+
+    /// Сбросить буферы нижележащего `Write`.
+    pub fn flush(&mut self) -> Result<(), QuoteError> {
+        self.inner.flush().context("ошибка записи кадра")
+    }
+
+    /// Нижележащий `Write`.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Изменяемая ссылка на нижележащий `Write`.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Вернуть нижележащий `Write`.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Чтение кадров из `Read`.
+///
+/// Из нижележащего потока читается не больше, чем нужно для текущего
+/// кадра, поэтому после кадров поток можно читать и напрямую.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+    max_size: usize,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Создать читателя с ограничением [`MAX_FRAME_SIZE`].
+    pub fn new(inner: R) -> Self {
+        Self::with_max_size(inner, MAX_FRAME_SIZE)
+    }
+
+    /// Создать читателя с ограничением размера данных кадра `max_size`.
+    pub fn with_max_size(inner: R, max_size: usize) -> Self {
+        Self {
+            inner,
+            max_size,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Прочитать кадр.
+    ///
+    /// ## Returns
+    ///
+    /// Данные кадра или `None`, если поток закончился до начала кадра.
+    ///
+    /// ## Ошибки
+    ///
+    /// - [`QuoteError::ProtocolError`] — длина превышает ограничение или
+    ///   поток закончился посреди кадра;
+    /// - [`QuoteError::TimeoutError`] — истёк таймаут чтения (`WouldBlock`,
+    ///   `TimedOut`); уже прочитанная часть кадра сохраняется, и вызов
+    ///   можно повторить;
+    /// - [`QuoteError::IoError`] — прочие ошибки чтения.
+    pub fn read_frame(&mut self) -> Result<Option<Vec<u8>>, QuoteError> {
+        loop {
+            let needed = match assemble(&mut self.buf, self.max_size)? {
+                Assembly::Frame(frame) => return Ok(Some(frame)),
+                Assembly::Need(needed) => needed,
+            };
+
+            let start = self.buf.len();
+            self.buf.resize(start + needed, 0);
+            let result = self.inner.read(&mut self.buf[start..]);
+            self.buf.truncate(start + *result.as_ref().unwrap_or(&0));
+
+            match result {
+                Ok(0) if self.buf.is_empty() => return Ok(None),
+                Ok(0) => return Err(truncated(&mut self.buf)),
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(QuoteError::timeout_err("нет данных от удалённой стороны"));
+                }
+                Err(err) => return Err(QuoteError::io_err("ошибка чтения кадра", err)),
+            }
+        }
+    }
+
+    /// Количество байт частично прочитанного кадра (включая префикс).
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Нижележащий `Read`.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Изменяемая ссылка на нижележащий `Read`.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Вернуть нижележащий `Read`; частично прочитанный кадр теряется.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Запись кадров в `tokio::io::AsyncWrite` (feature `async`).
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncFrameWriter<W> {
+    inner: W,
+    max_size: usize,
+}
+
+#[cfg(feature = "async")]
+impl<W: AsyncWrite + Unpin> AsyncFrameWriter<W> {
+    /// Создать писателя с ограничением [`MAX_FRAME_SIZE`].
+    pub fn new(inner: W) -> Self {
+        Self::with_max_size(inner, MAX_FRAME_SIZE)
+    }
+
+    /// Создать писателя с ограничением размера данных кадра `max_size`.
+    pub fn with_max_size(inner: W, max_size: usize) -> Self {
+        Self { inner, max_size }
+    }
+
+    /// Записать кадр (см. [`FrameWriter::write_frame`]).
+    ///
+    /// Если future отменён посреди записи, кадр может быть записан
+    /// частично; после этого поток следует закрыть.
+    pub async fn write_frame(&mut self, payload: &[u8]) -> Result<(), QuoteError> {
+        let frame = encode(payload, self.max_size)?;
+        self.inner
+            .write_all(&frame)
+            .await
+            .context("ошибка записи кадра")
+    }
+
+    /// Сбросить буферы нижележащего `AsyncWrite`.
+    pub async fn flush(&mut self) -> Result<(), QuoteError> {
+        self.inner.flush().await.context("ошибка записи кадра")
+    }
+
+    /// Нижележащий `AsyncWrite`.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Изменяемая ссылка на нижележащий `AsyncWrite`.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Вернуть нижележащий `AsyncWrite`.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Чтение кадров из `tokio::io::AsyncRead` (feature `async`).
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncFrameReader<R> {
+    inner: R,
+    max_size: usize,
+    buf: Vec<u8>,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncFrameReader<R> {
+    /// Создать читателя с ограничением [`MAX_FRAME_SIZE`].
+    pub fn new(inner: R) -> Self {
+        Self::with_max_size(inner, MAX_FRAME_SIZE)
+    }
+
+    /// Создать читателя с ограничением размера данных кадра `max_size`.
+    pub fn with_max_size(inner: R, max_size: usize) -> Self {
+        Self {
+            inner,
+            max_size,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Прочитать кадр (см. [`FrameReader::read_frame`]).
+    ///
+    /// Отмена безопасна: прочитанная часть кадра сохраняется, поэтому
+    /// вызов можно оборачивать в `tokio::time::timeout` или `select!`
+    /// и повторять.
+    ///
+    /// ## Ошибки
+    ///
+    /// - [`QuoteError::ProtocolError`] — длина превышает ограничение или
+    ///   поток закончился посреди кадра;
+    /// - [`QuoteError::IoError`] — ошибки чтения.
+    pub async fn read_frame(&mut self) -> Result<Option<Vec<u8>>, QuoteError> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            let needed = match assemble(&mut self.buf, self.max_size)? {
+                Assembly::Frame(frame) => return Ok(Some(frame)),
+                Assembly::Need(needed) => needed,
+            };
+
+            let want = needed.min(chunk.len());
+            match self.inner.read(&mut chunk[..want]).await {
+                Ok(0) if self.buf.is_empty() => return Ok(None),
+                Ok(0) => return Err(truncated(&mut self.buf)),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(QuoteError::io_err("ошибка чтения кадра", err)),
+            }
+        }
+    }
+
+    /// Количество байт частично прочитанного кадра (включая префикс).
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Нижележащий `AsyncRead`.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Изменяемая ссылка на нижележащий `AsyncRead`.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Вернуть нижележащий `AsyncRead`; частично прочитанный кадр теряется.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Источник, отдающий данные заданными порциями; `None` — таймаут.
+    struct Chunked(VecDeque<Option<Vec<u8>>>);
+
+    impl Read for Chunked {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                None => Ok(0),
+                Some(None) => Err(io::ErrorKind::WouldBlock.into()),
+                Some(Some(mut chunk)) => {
+                    let n = chunk.len().min(buf.len());
+                    buf[..n].copy_from_slice(&chunk[..n]);
+                    if n < chunk.len() {
+                        self.0.push_front(Some(chunk.split_off(n)));
+                    }
+                    Ok(n)
+                }
+            }
+        }
+    }
+
+    fn chunked(chunks: &[Option<&[u8]>]) -> FrameReader<Chunked> {
+        FrameReader::new(Chunked(
+            chunks.iter().map(|c| c.map(<[u8]>::to_vec)).collect(),
+        ))
+    }
+
+    fn encode(payloads: &[&[u8]]) -> Vec<u8> {
+        let mut writer = FrameWriter::new(Vec::new());
+        for payload in payloads {
+            writer.write_frame(payload).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn frames_roundtrip() {
+        let big = vec![7u8; MAX_FRAME_SIZE];
+        let bytes = encode(&[b"abc", b"", &big]);
+        let mut reader = FrameReader::new(bytes.as_slice());
+
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"abc");
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"");
+        assert_eq!(reader.read_frame().unwrap().unwrap(), big);
+        assert!(reader.read_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn byte_by_byte_reads_are_assembled() {
+        let bytes = encode(&[b"hello", b"world"]);
+        let chunks: Vec<Option<&[u8]>> = bytes.chunks(1).map(Some).collect();
+        let mut reader = chunked(&chunks);
+
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"hello");
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"world");
+        assert!(reader.read_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn timeout_keeps_partial_frame() {
+        let mut reader = chunked(&[Some(b"\x05\0"), None, Some(b"\0\0ab"), None, Some(b"cde")]);
+
+        let err = reader.read_frame().unwrap_err();
+        assert!(matches!(err, QuoteError::TimeoutError(_)));
+        assert_eq!(reader.pending(), 2);
+
+        assert!(reader.read_frame().is_err());
+        assert_eq!(reader.pending(), 6);
+
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"abcde");
+        assert_eq!(reader.pending(), 0);
+    }
+
+    #[test]
+    fn truncated_frames_are_rejected() {
+        for bytes in [&b"\x05\0"[..], b"\x05\0\0\0ab", b"\x01"] {
+            let mut reader = FrameReader::new(bytes);
+            let err = reader.read_frame().unwrap_err();
+            assert!(matches!(err, QuoteError::ProtocolError(_)), "{bytes:?}");
+            assert_eq!(reader.pending(), 0);
+        }
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let mut reader = FrameReader::new(&b"\xff\xff\xff\x7fabc"[..]);
+        let err = reader.read_frame().unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
+
+        let mut reader = FrameReader::with_max_size(&b"\x04\0\0\0abcd"[..], 3);
+        assert!(reader.read_frame().is_err());
+
+        let mut writer = FrameWriter::with_max_size(Vec::new(), 3);
+        let err = writer.write_frame(b"abcd").unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
+        assert!(writer.get_ref().is_empty());
+        writer.write_frame(b"abc").unwrap();
+        assert_eq!(writer.get_ref().len(), FRAME_HEADER_SIZE + 3);
+    }
+
+    #[test]
+    fn reader_does_not_consume_past_frame() {
+        let mut bytes = encode(&[b"abc"]);
+        bytes.extend_from_slice(b"tail");
+        let mut reader = FrameReader::new(bytes.as_slice());
+
+        assert_eq!(reader.read_frame().unwrap().unwrap(), b"abc");
+        let mut rest = Vec::new();
+        Read::read_to_end(&mut reader.into_inner(), &mut rest).unwrap();
+        assert_eq!(rest, b"tail");
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_frames_roundtrip_through_duplex() {
+        let (client, server) = tokio::io::duplex(16);
+        let mut writer = AsyncFrameWriter::new(client);
+        let mut reader = AsyncFrameReader::new(server);
+
+        let big = vec![7u8; 1000];
+        let sent = big.clone();
+        let task = tokio::spawn(async move {
+            for payload in [&b"abc"[..], b"", &sent] {
+                writer.write_frame(payload).await.unwrap();
+            }
+        });
+
+        assert_eq!(reader.read_frame().await.unwrap().unwrap(), b"abc");
+        assert_eq!(reader.read_frame().await.unwrap().unwrap(), b"");
+        assert_eq!(reader.read_frame().await.unwrap().unwrap(), big);
+        task.await.unwrap();
+        assert!(reader.read_frame().await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_read_survives_cancellation() {
+        use tokio::io::AsyncWriteExt;
+        use tokio::time::{Duration, timeout};
+
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader = AsyncFrameReader::new(server);
+
+        client.write_all(b"\x05\0\0\0ab").await.unwrap();
+        let cancelled = timeout(Duration::from_millis(20), reader.read_frame()).await;
+        assert!(cancelled.is_err());
+        assert_eq!(reader.pending(), 6);
+
+        client.write_all(b"cde").await.unwrap();
+        assert_eq!(reader.read_frame().await.unwrap().unwrap(), b"abcde");
+
+        client.write_all(b"\x05\0").await.unwrap();
+        drop(client);
+        let err = reader.read_frame().await.unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
+    }
+}
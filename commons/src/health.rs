@@ -0,0 +1,334 @@
+//! Реестр работоспособности подсистем.
+//!
+//! Подсистема (генератор, диспетчер, UDP-трансляция, приёмник котировок)
+//! регистрируется в [`Health`] и через полученный [`HealthHandle`] отмечает
+//! свою активность и состояние. Подсистема, от которой активности не было
+//! дольше заданного при регистрации срока, считается неработающей — так
+//! замечаются зависшие и завершившиеся паникой потоки. Сводка
+//! ([`HealthReport`]) сериализуется в JSON для точки проверки
+//! и файла статуса.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::health::{Health, HealthState};
+//! use std::time::Duration;
+//!
+//! let health = Health::new();
+//! let generator = health.register("generator", Some(Duration::from_secs(5)));
+//! let udp = health.register("udp 127.0.0.1:34254", None);
+//!
+//! generator.beat();
+//! udp.degraded("датаграммы не отправляются");
+//!
+//! let report = health.report();
+//! assert_eq!(report.status, HealthState::Degraded);
+//! assert!(report.to_json().contains("\"generator\":{\"state\":\"up\""));
+//! ```
+
+use crate::utils::{fmt_duration, get_timestamp_ms};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// Состояние подсистемы; состояния упорядочены от лучшего к худшему.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    /// Работает.
+    #[default]
+    Up,
+    /// Работает с ошибками.
+    Degraded,
+    /// Не работает.
+    Down,
+}
+
+impl Display for HealthState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HealthState::Up => "работает",
+            HealthState::Degraded => "работает с ошибками",
+            HealthState::Down => "не работает",
+        })
+    }
+}
+
+/// Состояние подсистемы в сводке.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentReport {
+    /// Состояние с учётом давности активности.
+    pub state: HealthState,
+    /// Время последней активности (Unix, миллисекунды).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<u64>,
+    /// Пояснение к состоянию.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Сводка о работоспособности всех подсистем.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Худшее из состояний подсистем; без подсистем — [`HealthState::Up`].
+    pub status: HealthState,
+    /// Подсистемы по именам.
+    pub components: BTreeMap<String, ComponentReport>,
+    /// Время формирования сводки (Unix, миллисекунды).
+    pub updated: u64,
+}
+
+impl HealthReport {
+    /// Все подсистемы работают.
+    pub fn is_up(&self) -> bool {
+        self.status == HealthState::Up
+    }
+
+    /// Сводка в формате JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Подсистемы, которые работают с ошибками или не работают,
+    /// с пояснениями: `udp 127.0.0.1:34254 — не работает (нет активности 6.0s)`.
+    pub fn problems(&self) -> Vec<String> {
+        self.components
+            .iter()
+            .filter(|(_, component)| component.state != HealthState::Up)
+            .map(|(name, component)| match &component.detail {
+                Some(detail) => format!("{name} — {} ({detail})", component.state),
+                None => format!("{name} — {}", component.state),
+            })
+            .collect()
+    }
+}
+
+/// Зарегистрированная подсистема.
+#[derive(Debug)]
+struct Component {
+    state: HealthState,
+    detail: Option<String>,
+    last_activity: Option<u64>,
+    stale_after: Option<Duration>,
+}
+
+/// Реестр подсистем; копии экземпляра разделяют общий реестр.
+#[derive(Debug, Clone, Default)]
+pub struct Health {
+    components: Arc<Mutex<BTreeMap<String, Component>>>,
+}
+
+impl Health {
+    /// Пустой реестр.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Зарегистрировать подсистему `name` в состоянии
+    /// [`HealthState::Up`] с текущим временем активности. Повторная
+    /// регистрация имени сбрасывает его состояние.
+    ///
+    /// ## Args
+    ///
+    /// - `name` — имя подсистемы в сводке
+    /// - `stale_after` — через сколько без активности подсистема считается
+    ///   неработающей; `None` — не проверять
+    pub fn register(&self, name: impl Into<String>, stale_after: Option<Duration>) -> HealthHandle {
+        let name = name.into();
+        self.lock().insert(
+            name.clone(),
+            Component {
+                state: HealthState::Up,
+                detail: None,
+                last_activity: Some(get_timestamp_ms()),
+                stale_after,
+            },
+        );
+        HealthHandle {
+            health: self.clone(),
+            name,
+        }
+    }
+
+    /// Удалить подсистему из реестра.
+    pub fn remove(&self, name: &str) {
+        self.lock().remove(name);
+    }
+
+    /// Сводка на текущий момент.
+    pub fn report(&self) -> HealthReport {
+        self.report_at(get_timestamp_ms())
+    }
+
+    /// Сводка на момент `now_ms` (Unix, миллисекунды).
+    pub fn report_at(&self, now_ms: u64) -> HealthReport {
+        let components: BTreeMap<String, ComponentReport> = self
+            .lock()
+            .iter()
+            .map(|(name, component)| (name.clone(), component.report(now_ms)))
+            .collect();
+        HealthReport {
+            status: components
+                .values()
+                .map(|component| component.state)
+                .max()
+                .unwrap_or(HealthState::Up),
+            components,
+            updated: now_ms,
+        }
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut Component)) {
+        if let Some(component) = self.lock().get_mut(name) {
+            f(component);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Component>> {
+        self.components
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Component {
+    fn report(&self, now_ms: u64) -> ComponentReport {
+        let idle = self
+            .last_activity
+            .map(|at| Duration::from_millis(now_ms.saturating_sub(at)));
+        match (idle, self.stale_after) {
+            (Some(idle), Some(limit)) if idle > limit => ComponentReport {
+                state: HealthState::Down,
+                last_activity: self.last_activity,
+                detail: Some(format!("нет активности {}", fmt_duration(idle))),
+            },
+            _ => ComponentReport {
+                state: self.state,
+                last_activity: self.last_activity,
+                detail: self.detail.clone(),
+            },
+        }
+    }
+}
+
+/// Подсистема в реестре [`Health`]: через неё подсистема сообщает
+/// о своей активности и состоянии.
+#[derive(Debug, Clone)]
+pub struct HealthHandle {
+    health: Health,
+    name: String,
+}
+
+impl HealthHandle {
+    /// Имя подсистемы.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Отметить активность в текущий момент.
+    pub fn beat(&self) {
+        self.beat_at(get_timestamp_ms());
+    }
+
+    /// Отметить активность в момент `ts_ms` (Unix, миллисекунды).
+    pub fn beat_at(&self, ts_ms: u64) {
+        self.health.update(&self.name, |component| {
+            component.last_activity = Some(ts_ms)
+        });
+    }
+
+    /// Отметить, что подсистема работает.
+    pub fn up(&self) {
+        self.set(HealthState::Up, None);
+    }
+
+    /// Отметить, что подсистема работает с ошибками.
+    pub fn degraded(&self, detail: impl Into<String>) {
+        self.set(HealthState::Degraded, Some(detail.into()));
+    }
+
+    /// Отметить, что подсистема не работает.
+    pub fn down(&self, detail: impl Into<String>) {
+        self.set(HealthState::Down, Some(detail.into()));
+    }
+
+    fn set(&self, state: HealthState, detail: Option<String>) {
+        self.health.update(&self.name, |component| {
+            component.state = state;
+            component.detail = detail;
+        });
+    }
+
+    /// Удалить подсистему из реестра (например, по окончании трансляции).
+    pub fn remove(self) {
+        self.health.remove(&self.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_is_worst_component_state() {
+        let health = Health::new();
+        assert!(health.report().is_up());
+
+        let generator = health.register("generator", None);
+        let udp = health.register("udp", None);
+        udp.degraded("ошибка отправки");
+        assert_eq!(health.report().status, HealthState::Degraded);
+
+        generator.down("нет тикеров");
+        let report = health.report();
+        assert_eq!(report.status, HealthState::Down);
+        assert_eq!(
+            report.problems(),
+            [
+                "generator — не работает (нет тикеров)",
+                "udp — работает с ошибками (ошибка отправки)"
+            ]
+        );
+
+        generator.up();
+        udp.remove();
+        assert!(health.report().is_up());
+    }
+
+    #[test]
+    fn stale_component_is_down() {
+        let health = Health::new();
+        let dispatcher = health.register("dispatcher", Some(Duration::from_secs(5)));
+        let idle = health.register("idle", None);
+        dispatcher.beat_at(1_000);
+        idle.beat_at(1_000);
+
+        assert!(health.report_at(6_000).is_up());
+        let report = health.report_at(7_500);
+        assert_eq!(report.status, HealthState::Down);
+        assert_eq!(
+            report.components["dispatcher"].detail.as_deref(),
+            Some("нет активности 6.5s")
+        );
+        assert_eq!(report.components["idle"].state, HealthState::Up);
+
+        dispatcher.beat_at(7_000);
+        assert!(health.report_at(7_500).is_up());
+    }
+
+    #[test]
+    fn report_roundtrips_json() {
+        let health = Health::new();
+        health.register("receiver", None).degraded("пропуски");
+        let report = health.report_at(10);
+
+        let json = report.to_json();
+        assert!(json.contains(r#""status":"degraded""#));
+        assert!(json.contains(r#""detail":"пропуски""#));
+        assert_eq!(serde_json::from_str::<HealthReport>(&json).unwrap(), report);
+    }
+}
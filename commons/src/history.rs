@@ -0,0 +1,209 @@
+//! Ограниченная история значений по ключам.
+//!
+//! [`HistoryBuffer`] хранит для каждого ключа (например, тикера) не больше
+//! заданного количества последних значений: при переполнении вытесняется
+//! самое старое. Буфер потокобезопасен — запись и чтение идут через общую
+//! ссылку, а чтение возвращает копию (снимок), поэтому не удерживает
+//! блокировку, пока вызывающий обрабатывает значения.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::history::HistoryBuffer;
+//!
+//! let history = HistoryBuffer::new(2);
+//! for price in [101, 102, 103] {
+//!     history.push("AAPL".to_string(), price);
+//! }
+//!
+//! assert_eq!(history.snapshot("AAPL"), vec![102, 103]);
+//! assert_eq!(history.latest("AAPL"), Some(103));
+//! assert!(history.snapshot("TSLA").is_empty());
+//! ```
+
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::{Mutex, MutexGuard},
+};
+
+/// Последние значения по ключам, не больше `capacity` на ключ.
+#[derive(Debug)]
+pub struct HistoryBuffer<T, K = String> {
+    capacity: usize,
+    entries: Mutex<HashMap<K, VecDeque<T>>>,
+}
+
+impl<T, K: Eq + Hash> HistoryBuffer<T, K> {
+    /// Буфер на `capacity` значений для каждого ключа.
+    ///
+    /// ## Паника
+    ///
+    /// Буфер нулевой ёмкости не имеет смысла и вызывает панику.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "нулевая ёмкость истории");
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Ёмкость буфера для одного ключа.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Добавить значение по ключу.
+    ///
+    /// ## Returns
+    ///
+    /// Вытесненное самое старое значение, если история ключа была полна.
+    pub fn push(&self, key: K, value: T) -> Option<T> {
+        let mut entries = self.lock();
+        let values = entries.entry(key).or_default();
+        let evicted = if values.len() == self.capacity {
+            values.pop_front()
+        } else {
+            None
+        };
+        values.push_back(value);
+        evicted
+    }
+
+    /// Количество значений по ключу.
+    pub fn len<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock().get(key).map_or(0, VecDeque::len)
+    }
+
+    /// Нет ни одного значения.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Удалить историю ключа.
+    pub fn remove<Q>(&self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock().remove(key);
+    }
+
+    /// Удалить всю историю.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, VecDeque<T>>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T: Clone, K: Eq + Hash + Clone> HistoryBuffer<T, K> {
+    /// Копия истории ключа, от старых значений к новым.
+    pub fn snapshot<Q>(&self, key: &Q) -> Vec<T>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock()
+            .get(key)
+            .map(|values| values.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Последнее значение по ключу.
+    pub fn latest<Q>(&self, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock()
+            .get(key)
+            .and_then(|values| values.back().cloned())
+    }
+
+    /// Копия истории всех ключей (порядок ключей не определён).
+    pub fn snapshot_all(&self) -> Vec<(K, Vec<T>)> {
+        self.lock()
+            .iter()
+            .map(|(key, values)| (key.clone(), values.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Ключи, по которым есть история.
+    pub fn keys(&self) -> Vec<K> {
+        self.lock().keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn capacity_is_per_key() {
+        let history = HistoryBuffer::new(2);
+        assert_eq!(history.push("A".to_string(), 1), None);
+        history.push("A".to_string(), 2);
+        assert_eq!(history.push("A".to_string(), 3), Some(1));
+        history.push("B".to_string(), 10);
+
+        assert_eq!(history.snapshot("A"), [2, 3]);
+        assert_eq!(history.snapshot("B"), [10]);
+        assert_eq!((history.len("A"), history.len("C")), (2, 0));
+        assert_eq!(history.latest("C"), None);
+
+        let mut all = history.snapshot_all();
+        all.sort();
+        assert_eq!(
+            all,
+            [("A".to_string(), vec![2, 3]), ("B".to_string(), vec![10])]
+        );
+    }
+
+    #[test]
+    fn remove_and_clear() {
+        let history: HistoryBuffer<u32> = HistoryBuffer::new(4);
+        history.push("A".to_string(), 1);
+        history.push("B".to_string(), 2);
+
+        history.remove("A");
+        assert_eq!(history.keys(), ["B".to_string()]);
+        history.clear();
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn concurrent_writers_keep_bound() {
+        let history = Arc::new(HistoryBuffer::<usize, usize>::new(10));
+        let handles: Vec<_> = (0..4)
+            .map(|key| {
+                let history = Arc::clone(&history);
+                thread::spawn(move || {
+                    for value in 0..1000 {
+                        history.push(key % 2, value);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(history.keys().len(), 2);
+        assert!(history.snapshot_all().iter().all(|(_, v)| v.len() == 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "нулевая ёмкость истории")]
+    fn zero_capacity_panics() {
+        HistoryBuffer::<u32>::new(0);
+    }
+}
@@ -0,0 +1,195 @@
+//! Язык сообщений приложений: русский или английский.
+//!
+//! Тексты хранятся в виде пар [`Msg`] и выбираются по текущему языку
+//! ([`lang`]). Язык задаётся параметром `lang` конфигурации или переменной
+//! окружения [`LANG_ENV`]; по умолчанию — русский.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::i18n::{Lang, Msg};
+//!
+//! const GREETING: Msg = Msg::new("Привет", "Hello");
+//!
+//! assert_eq!(GREETING.get(Lang::En), "Hello");
+//! ```
+
+use crate::errors::QuoteError;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Переменная окружения с языком сообщений, например `QUOTE_LANG=en`.
+pub const LANG_ENV: &str = "QUOTE_LANG";
+
+/// Текущий язык: `0` — ещё не определён.
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+/// Язык сообщений.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// Русский.
+    #[default]
+    Ru,
+    /// Английский.
+    En,
+}
+
+impl Lang {
+    fn id(self) -> u8 {
+        match self {
+            Lang::Ru => 1,
+            Lang::En => 2,
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = QuoteError;
+
+    /// Разобрать код языка; допускаются и локали вида `en_US.UTF-8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().get(..2).unwrap_or_default().to_ascii_lowercase();
+        match code.as_str() {
+            "ru" => Ok(Lang::Ru),
+            "en" => Ok(Lang::En),
+            _ => Err(QuoteError::value_err(format!(
+                "неизвестный язык: '{s}' (допустимо ru, en)"
+            ))),
+        }
+    }
+}
+
+/// Текущий язык сообщений.
+///
+/// Если язык не установлен ([`set_lang`], [`init`]), он определяется
+/// по переменной окружения [`LANG_ENV`].
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::Ru,
+        2 => Lang::En,
+        _ => init(None),
+    }
+}
+
+/// Установить язык сообщений.
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang.id(), Ordering::Relaxed);
+}
+
+/// Установить язык: из конфигурации, если задан, иначе из переменной
+/// окружения [`LANG_ENV`], иначе русский.
+///
+/// ## Returns
+///
+/// Выбранный язык.
+pub fn init(configured: Option<Lang>) -> Lang {
+    let lang = configured
+        .or_else(|| lang_from_env(env::var(LANG_ENV).ok().as_deref()))
+        .unwrap_or_default();
+    set_lang(lang);
+    lang
+}
+
+/// Язык из значения переменной окружения; некорректное значение
+/// игнорируется.
+fn lang_from_env(value: Option<&str>) -> Option<Lang> {
+    value.and_then(|value| value.parse().ok())
+}
+
+/// Сообщение на двух языках.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Msg {
+    ru: &'static str,
+    en: &'static str,
+}
+
+impl Msg {
+    /// Создать сообщение.
+    pub const fn new(ru: &'static str, en: &'static str) -> Self {
+        Self { ru, en }
+    }
+
+    /// Текст на языке `lang`.
+    pub fn get(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ru => self.ru,
+            Lang::En => self.en,
+        }
+    }
+
+    /// Текст на текущем языке ([`lang`]).
+    pub fn text(&self) -> &'static str {
+        self.get(lang())
+    }
+}
+
+impl Display for Msg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// Выбрать вариант сообщения по текущему языку ([`lang`]).
+///
+/// Подходит для сообщений с подстановками, которые нельзя хранить в [`Msg`]:
+///
+/// ```
+/// use commons::i18n::{self, Lang};
+///
+/// i18n::set_lang(Lang::En);
+/// let port = 80;
+/// let message = i18n::tr(format!("порт {port} занят"), format!("port {port} is busy"));
+/// assert_eq!(message, "port 80 is busy");
+/// ```
+pub fn tr<T>(ru: T, en: T) -> T {
+    match lang() {
+        Lang::Ru => ru,
+        Lang::En => en,
+    }
+}
+
+/// Подпись сообщения об ошибке в консоли.
+pub const ERROR: Msg = Msg::new("Ошибка", "Error");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lang_codes_are_parsed() {
+        assert_eq!("ru".parse::<Lang>().unwrap(), Lang::Ru);
+        assert_eq!(" EN ".parse::<Lang>().unwrap(), Lang::En);
+        assert_eq!("en_US.UTF-8".parse::<Lang>().unwrap(), Lang::En);
+        assert!("de".parse::<Lang>().is_err());
+        assert!("".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn env_value_is_optional() {
+        assert_eq!(lang_from_env(Some("en")), Some(Lang::En));
+        assert_eq!(lang_from_env(Some("klingon")), None);
+        assert_eq!(lang_from_env(None), None);
+    }
+
+    #[test]
+    fn message_is_selected_by_lang() {
+        assert_eq!(ERROR.get(Lang::Ru), "Ошибка");
+        assert_eq!(ERROR.get(Lang::En), "Error");
+    }
+
+    #[test]
+    fn lang_is_deserialized_from_config() {
+        #[derive(Deserialize)]
+        struct Config {
+            lang: Lang,
+        }
+        let config: Config = toml::from_str("lang = \"en\"").unwrap();
+        assert_eq!(config.lang, Lang::En);
+    }
+}
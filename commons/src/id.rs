@@ -0,0 +1,174 @@
+//! Идентификаторы сессий и событий.
+//!
+//! [`Id`] — 64-битное число: старшие 48 бит — время создания в миллисекундах
+//! Unix, младшие 16 — счётчик в пределах миллисекунды. Идентификаторы
+//! одного генератора строго возрастают, а после перезапуска приложения
+//! продолжаются с текущего времени, поэтому не повторяют выданные ранее
+//! и сортируются по времени создания.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::id::{Id, IdGen};
+//!
+//! static SESSIONS: IdGen = IdGen::new();
+//!
+//! let first = SESSIONS.next_id();
+//! let second = SESSIONS.next_id();
+//! assert!(first < second);
+//! assert_eq!(second.to_string().parse::<Id>().unwrap(), second);
+//! ```
+
+use crate::errors::QuoteError;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Число бит счётчика в пределах миллисекунды.
+const COUNTER_BITS: u32 = 16;
+
+/// Идентификатор сессии или события.
+///
+/// В текстовом виде — 16 шестнадцатеричных цифр, поэтому строки
+/// сортируются так же, как значения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(u64);
+
+impl Id {
+    /// Числовое значение.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Время создания, миллисекунды Unix.
+    pub fn timestamp_ms(self) -> u64 {
+        self.0 >> COUNTER_BITS
+    }
+}
+
+impl From<u64> for Id {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Id {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Id {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s.trim(), 16)
+            .map(Self)
+            .map_err(|_| QuoteError::parse_err(format!("некорректный идентификатор: '{s}'")))
+    }
+}
+
+/// Генератор идентификаторов [`Id`].
+///
+/// Потокобезопасен и может быть `static`. Если за миллисекунду выдано
+/// больше 65 536 идентификаторов или часы переведены назад, генератор
+/// продолжает от последнего значения, сохраняя возрастание.
+#[derive(Debug, Default)]
+pub struct IdGen {
+    last: AtomicU64,
+}
+
+impl IdGen {
+    /// Создать генератор.
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Выдать следующий идентификатор.
+    pub fn next_id(&self) -> Id {
+        self.next_at(now_ms())
+    }
+
+    fn next_at(&self, now_ms: u64) -> Id {
+        let floor = now_ms << COUNTER_BITS;
+        let previous = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(floor.max(last + 1))
+            })
+            .unwrap_or_default();
+        Id(floor.max(previous + 1))
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    #[test]
+    fn ids_grow_within_and_across_milliseconds() {
+        let ids = IdGen::new();
+        let a = ids.next_at(1_700_000_000_000);
+        let b = ids.next_at(1_700_000_000_000);
+        let c = ids.next_at(1_700_000_000_001);
+
+        assert_eq!(a.timestamp_ms(), 1_700_000_000_000);
+        assert_eq!(b.as_u64(), a.as_u64() + 1);
+        assert_eq!(c.timestamp_ms(), 1_700_000_000_001);
+        assert_eq!(c.as_u64() & 0xffff, 0);
+    }
+
+    #[test]
+    fn clock_going_back_keeps_order() {
+        let ids = IdGen::new();
+        let a = ids.next_at(1_700_000_000_500);
+        let b = ids.next_at(1_700_000_000_000);
+        assert!(b > a);
+    }
+
+    #[test]
+    fn restart_does_not_repeat_ids() {
+        let before = IdGen::new().next_at(1_700_000_000_000);
+        let after = IdGen::new().next_at(1_700_000_000_001);
+        assert!(after > before);
+    }
+
+    #[test]
+    fn concurrent_ids_are_unique() {
+        let ids = Arc::new(IdGen::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let ids = Arc::clone(&ids);
+                thread::spawn(move || (0..1000).map(|_| ids.next_id()).collect::<Vec<_>>())
+            })
+            .collect();
+        let all: HashSet<Id> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        assert_eq!(all.len(), 4000);
+    }
+
+    #[test]
+    fn text_form_is_sortable_and_parsed() {
+        let small = Id::from(0xff);
+        let large = Id::from(0x1_0000);
+        assert_eq!(small.to_string(), "00000000000000ff");
+        assert!(small.to_string() < large.to_string());
+        assert_eq!("00000000000000ff".parse::<Id>().unwrap(), small);
+        assert!("session".parse::<Id>().is_err());
+    }
+}
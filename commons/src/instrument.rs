@@ -0,0 +1,79 @@
+//! Журналирование вызовов функций, размеченных `#[quote_instrument]`.
+//!
+//! Макрос оборачивает тело функции замером [`CallTimer`]: при входе
+//! в журнал пишется вызов, при выходе — длительность, а для результата
+//! `Err` — ещё и ошибка. Цель сообщений — модуль размеченной функции,
+//! поэтому их уровень настраивается так же, как для остальных сообщений
+//! модуля ([`crate::logging::LogTargets`]).
+//!
+//! ```text
+//! 12:30:05 TRACE qserver::generator: Вызов next_gen
+//! 12:30:05 TRACE qserver::generator: next_gen завершён за 14.2µs
+//! ```
+
+use std::{fmt::Display, time::Instant};
+
+pub use log::Level;
+
+/// Выполнить тело размеченной функции `body`.
+///
+/// Замыкание принимается как `FnOnce`: так оно владеет захваченными
+/// аргументами и может вернуть ссылку, полученную из них (например,
+/// из `&mut self`).
+pub fn call<R>(body: impl FnOnce() -> R) -> R {
+    body()
+}
+
+/// Замер вызова функции.
+pub struct CallTimer {
+    /// Цель сообщений журнала.
+    target: &'static str,
+    /// Имя функции в журнале.
+    name: &'static str,
+    level: Level,
+    started: Instant,
+}
+
+impl CallTimer {
+    /// Начать замер вызова `name` и записать вызов в журнал.
+    ///
+    /// ## Args
+    ///
+    /// - `target` — цель сообщений журнала, обычно `module_path!()`.
+    /// - `name` — имя функции в журнале.
+    /// - `level` — уровень сообщений о вызове и его завершении.
+    pub fn start(target: &'static str, name: &'static str, level: Level) -> Self {
+        log::log!(target: target, level, "Вызов {name}");
+        Self {
+            target,
+            name,
+            level,
+            started: Instant::now(),
+        }
+    }
+
+    /// Завершить замер и записать длительность вызова.
+    pub fn finish(self) {
+        log::log!(
+            target: self.target,
+            self.level,
+            "{} завершён за {:?}",
+            self.name,
+            self.started.elapsed()
+        );
+    }
+
+    /// Завершить замер вызова, вернувшего ошибку `err`.
+    ///
+    /// Ошибка записывается с уровнем не ниже `Warn`, чтобы не теряться
+    /// при отключённых отладочных сообщениях.
+    pub fn fail(self, err: &impl Display) {
+        log::log!(
+            target: self.target,
+            self.level.min(Level::Warn),
+            "{} завершён с ошибкой за {:?}: {err}",
+            self.name,
+            self.started.elapsed()
+        );
+    }
+}
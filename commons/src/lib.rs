@@ -1,19 +1,56 @@
-use crate::errors::QuoteError;
+use crate::errors::{QuoteError, ResultExt};
+use crate::tickers::TickerRules;
 use log::*;
-use simplelog::{CombinedLogger, Config, WriteLogger};
+use std::env;
 use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+// Генерируемый макросами код ссылается на `::commons::...`, в том числе
+// внутри этого крейта.
+extern crate self as commons;
+
+pub mod calendar;
+pub mod checksum;
+pub mod codec;
+pub mod config;
+pub mod csv;
 pub mod errors;
+pub mod escape;
+pub mod frame;
+pub mod health;
+pub mod history;
+pub mod i18n;
+pub mod id;
+pub mod instrument;
+pub mod logging;
+pub mod metrics;
 pub mod models;
+pub mod netdefaults;
+pub mod paths;
+pub mod pool;
+pub mod price;
 pub mod randomizer;
+pub mod ratelimit;
+pub mod retry;
+pub mod rolling;
+pub mod schema;
+pub mod seq;
+pub mod shutdown;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tickers;
 pub mod traits;
 pub mod utils;
+pub mod watch;
 
 /// Предоставить вектор с наименованием тикеров из файла.
 ///
+/// Файл может быть в любом формате, поддерживаемом
+/// [`tickers::load_ticker_info`]; дополнительные сведения о тикерах
+/// отбрасываются. Тикеры проверяются правилами по умолчанию
+/// ([`TickerRules::default`]), повторы пропускаются с предупреждением.
+///
 /// ## Пример
 ///
 /// ```
@@ -31,27 +68,90 @@ pub mod utils;
 /// Возвращает вектор при успешной подгрузке данных или None, если вектор
 /// получился пустой.
 ///
-/// Паникует при невозможности извлечь данные.
-pub fn get_ticker_data(path: &PathBuf) -> Result<Option<Vec<String>>, QuoteError> {
-    let file = File::open(path).map_err(|err| {
-        QuoteError::ticker_err(format!(
-            "Не удалось открыть файл с тикерами {}: {}",
-            path.to_string_lossy(),
-            err
-        ))
-    })?;
-
-    let tickers: Vec<String> = BufReader::new(file)
-        .lines()
-        .map_while(Result::ok)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if tickers.is_empty() {
-        return Ok(None);
-    }
-    Ok(Some(tickers))
+/// Возвращает [`QuoteError::IoError`], если файл не удалось открыть,
+/// и ошибку вида [`ErrorKind::Value`](errors::ErrorKind::Value), если
+/// содержимое файла некорректно или есть отклонённые тикеры (с номерами
+/// строк).
+pub fn get_ticker_data(path: &Path) -> Result<Option<Vec<String>>, QuoteError> {
+    get_ticker_data_with(path, &TickerRules::default())
+}
+
+/// Аналог [`get_ticker_data`] с явно заданными правилами проверки тикеров.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::get_ticker_data_with;
+/// use commons::tickers::TickerRules;
+/// use commons::utils::get_workspace_root;
+///
+/// let rules = TickerRules { max_len: 5, ..TickerRules::default() };
+/// let path_to_file = get_workspace_root().join("data").join("tickers.txt");
+/// assert!(get_ticker_data_with(&path_to_file, &rules).unwrap().is_some());
+/// ```
+pub fn get_ticker_data_with(
+    path: &Path,
+    rules: &TickerRules,
+) -> Result<Option<Vec<String>>, QuoteError> {
+    let (text, is_toml) = tickers::read_ticker_file(path)?;
+    tickers::checked_ticker_list(path, &text, is_toml, rules)
+}
+
+/// Асинхронный вариант [`get_ticker_data`] (feature `async`): файл читается
+/// через `tokio::fs`.
+#[cfg(feature = "async")]
+pub async fn get_ticker_data_async(path: &Path) -> Result<Option<Vec<String>>, QuoteError> {
+    get_ticker_data_async_with(path, &TickerRules::default()).await
+}
+
+/// Асинхронный вариант [`get_ticker_data_with`] (feature `async`).
+#[cfg(feature = "async")]
+pub async fn get_ticker_data_async_with(
+    path: &Path,
+    rules: &TickerRules,
+) -> Result<Option<Vec<String>>, QuoteError> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| tickers::read_error(path, err))?;
+    tickers::checked_ticker_list(path, &text, tickers::is_toml_path(path), rules)
+}
+
+/// Переменная окружения с уровнем журналирования, например `QUOTE_LOG=debug`
+/// или, с уровнями по модулям, `QUOTE_LOG=info,qserver::udp=debug`
+/// (см. [`logging::LogTargets`]).
+pub const LOG_LEVEL_ENV: &str = "QUOTE_LOG";
+
+/// Определить уровень журналирования.
+///
+/// Приоритет: явно заданный уровень (например, ключ `--log-level`), затем
+/// общий уровень из переменной [`LOG_LEVEL_ENV`], затем `default`.
+/// Некорректное значение переменной игнорируется.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::resolve_log_level;
+/// use log::LevelFilter;
+///
+/// let level = resolve_log_level(Some(LevelFilter::Debug), LevelFilter::Info);
+/// assert_eq!(level, LevelFilter::Debug);
+/// ```
+pub fn resolve_log_level(explicit: Option<LevelFilter>, default: LevelFilter) -> LevelFilter {
+    explicit
+        .or_else(|| env_log_targets().default_level())
+        .unwrap_or(default)
+}
+
+/// Уровни журнала по модулям из переменной [`LOG_LEVEL_ENV`].
+fn env_log_targets() -> logging::LogTargets {
+    targets_from_env(env::var(LOG_LEVEL_ENV).ok().as_deref())
+}
+
+/// Разобрать значение переменной [`LOG_LEVEL_ENV`].
+fn targets_from_env(value: Option<&str>) -> logging::LogTargets {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
 }
 
 /// Фабрика по созданию индивидуальных логгеров для приложений.
@@ -82,20 +182,47 @@ pub fn get_ticker_data(path: &PathBuf) -> Result<Option<Vec<String>>, QuoteError
 /// error!("Шторм разрушил усадьбу, сэр!");
 /// ```
 ///
+/// Уровень журналирования — `Info`, если не переопределён переменной
+/// [`LOG_LEVEL_ENV`].
+///
 /// Возвращает ошибки создания (открытия) директории и (или) log-файла,
 /// и при инициализации логгера (предоставляет сообщение о причинах, если
 /// есть).
 pub fn init_simple_logger(app_name: &str, log_dir: PathBuf) -> Result<(), QuoteError> {
-    let config = Config::default();
+    init_logger_with_level(
+        app_name,
+        log_dir,
+        resolve_log_level(None, LevelFilter::Info),
+        None,
+    )
+}
+
+/// Аналог [`init_simple_logger`] с явно заданным уровнем журналирования
+/// и необязательным дублированием журнала в консоль.
+///
+/// ## Args
+///
+/// - `app_name` — название приложения (будет использовано для создания файла)
+/// - `log_dir` — путь к директории расположения log-файлов
+/// - `level` — минимальный уровень записываемых в файл сообщений
+/// - `console_level` — если задан, сообщения этого уровня и выше
+///   дополнительно выводятся в stderr (stdout остаётся свободным для данных)
+///
+/// Журнал ведётся через `tracing` (см. [`logging`]); сообщения макросов
+/// `log` попадают в него вместе с полями активных областей. Уровни
+/// отдельных модулей из переменной [`LOG_LEVEL_ENV`] действуют поверх
+/// `level` для log-файла.
+pub fn init_logger_with_level(
+    app_name: &str,
+    log_dir: PathBuf,
+    level: LevelFilter,
+    console_level: Option<LevelFilter>,
+) -> Result<(), QuoteError> {
     let log_file_path = log_dir.join(format!("{}.log", app_name));
 
     if !log_dir.exists() {
-        fs::create_dir_all(&log_dir).map_err(|_| {
-            QuoteError::runtime_err(format!(
-                "не удалось сформировать путь: {}",
-                log_dir.display()
-            ))
-        })?;
+        fs::create_dir_all(&log_dir)
+            .with_context(|| format!("не удалось сформировать путь: {}", log_dir.display()))?;
     }
 
     let log_file = if log_file_path.is_file() {
@@ -103,20 +230,9 @@ pub fn init_simple_logger(app_name: &str, log_dir: PathBuf) -> Result<(), QuoteE
     } else {
         File::create(&log_file_path)
     }
-    .map_err(|err| {
-        QuoteError::runtime_err(format!(
-            "ошибка работы с log-файлом ({}): {}",
-            log_file_path.display(),
-            err
-        ))
-    })?;
+    .with_context(|| format!("ошибка работы с log-файлом ({})", log_file_path.display()))?;
 
-    let logger = WriteLogger::new(LevelFilter::Info, config, log_file);
-
-    CombinedLogger::init(vec![logger])
-        .map_err(|e| QuoteError::runtime_err(format!("ошибка инициализации логгера: {e}")))?;
-
-    Ok(())
+    logging::init(log_file, level, &env_log_targets(), console_level)
 }
 
 #[cfg(test)]
@@ -168,6 +284,51 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_get_ticker_data_rejects_invalid_lines() {
+        let mut temp_file = NamedTempFile::new().expect("Не удалось создать временный файл");
+        writeln!(temp_file, "AAPL\n// список\nMSFT\nAAPL").expect("Не удалось записать в файл");
+
+        let err = get_ticker_data(temp_file.path()).unwrap_err().to_string();
+        assert!(err.contains("строка 2 '// список'"), "{err}");
+
+        let mut temp_file = NamedTempFile::new().expect("Не удалось создать временный файл");
+        writeln!(temp_file, "AAPL\nMSFT\nAAPL").expect("Не удалось записать в файл");
+        let tickers = get_ticker_data(temp_file.path()).unwrap().unwrap();
+        assert_eq!(tickers, vec!["AAPL", "MSFT"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_get_ticker_data_async_matches_sync() {
+        let mut temp_file = NamedTempFile::new().expect("Не удалось создать временный файл");
+        writeln!(temp_file, "AAPL\nMSFT\n#").expect("Не удалось записать в файл");
+        assert!(get_ticker_data_async(temp_file.path()).await.is_err());
+
+        let path = utils::get_workspace_root().join("data").join("tickers.txt");
+        let tickers = get_ticker_data_async(&path).await.unwrap();
+        assert_eq!(tickers, get_ticker_data(&path).unwrap());
+        assert!(
+            get_ticker_data_async(Path::new("/non/existent"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn log_level_from_env_value() {
+        let level = |value| targets_from_env(value).default_level();
+        assert_eq!(level(Some("debug")), Some(LevelFilter::Debug));
+        assert_eq!(level(Some(" WARN ")), Some(LevelFilter::Warn));
+        assert_eq!(
+            level(Some("info,qserver::udp=debug")),
+            Some(LevelFilter::Info)
+        );
+        assert_eq!(level(Some("qserver::udp=debug")), None);
+        assert_eq!(level(Some("loud=debug")), None);
+        assert_eq!(level(None), None);
+    }
+
     #[test]
     fn test_get_ticker_data_with_empty_file() {
         // Создаем пустой временный файл
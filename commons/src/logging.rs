@@ -0,0 +1,271 @@
+//! Журналирование на основе `tracing`.
+//!
+//! Сообщения макросов крейта `log` перенаправляются в `tracing`, поэтому
+//! существующий код журналирования работает без изменений, а каждая строка
+//! журнала дополняется полями активных областей (`span`) — например,
+//! сеанса клиента:
+//!
+//! ```text
+//! 12:30:05  INFO session{id=01a14c0a1faf0000 peer=127.0.0.1:50412}: Ответ: OK
+//! ```
+//!
+//! Уровень журнала задаётся отдельно для целей (модулей) в синтаксисе
+//! `RUST_LOG` ([`LogTargets`]): например, `info,qserver::udp=debug`
+//! включает отладку UDP-трансляций, не засоряя журнал остальных модулей.
+
+use crate::errors::QuoteError;
+use log::LevelFilter;
+use std::{
+    fmt::Result as FmtResult,
+    fs::File,
+    io::{self, IsTerminal},
+    str::FromStr,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::Subscriber;
+use tracing_subscriber::{
+    Layer,
+    filter::{LevelFilter as TraceLevel, Targets},
+    fmt::{self, MakeWriter, format::Writer, time::FormatTime},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+};
+
+/// Время суток (UTC) в формате `ЧЧ:ММ:СС`.
+struct ClockTime;
+
+impl FormatTime for ClockTime {
+    fn format_time(&self, w: &mut Writer<'_>) -> FmtResult {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+            % 86_400;
+        write!(
+            w,
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+/// Уровень `log` в терминах `tracing`.
+fn to_trace_level(level: LevelFilter) -> TraceLevel {
+    match level {
+        LevelFilter::Off => TraceLevel::OFF,
+        LevelFilter::Error => TraceLevel::ERROR,
+        LevelFilter::Warn => TraceLevel::WARN,
+        LevelFilter::Info => TraceLevel::INFO,
+        LevelFilter::Debug => TraceLevel::DEBUG,
+        LevelFilter::Trace => TraceLevel::TRACE,
+    }
+}
+
+/// Уровни журнала по целям в синтаксисе `RUST_LOG`.
+///
+/// Директивы разделяются запятыми: `цель=уровень` задаёт уровень для целей,
+/// начинающихся с `цель` (то есть и для подмодулей), одиночный уровень — для
+/// остальных целей, одиночная цель включает для неё все уровни. Цель сообщения — путь модуля, в котором
+/// оно записано, например `qserver::udp` или `commons::pool`.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::logging::LogTargets;
+/// use log::LevelFilter;
+///
+/// let targets: LogTargets = "info, qserver::udp=debug".parse().unwrap();
+///
+/// assert_eq!(targets.default_level(), Some(LevelFilter::Info));
+/// assert_eq!(targets.level_for("qserver::udp::stream"), Some(LevelFilter::Debug));
+/// assert_eq!(targets.level_for("qserver::tcp"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogTargets {
+    default: Option<LevelFilter>,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl LogTargets {
+    /// Уровень для целей без отдельной директивы.
+    pub fn default_level(&self) -> Option<LevelFilter> {
+        self.default
+    }
+
+    /// Уровень, заданный для цели `target` (по самой длинной подходящей
+    /// директиве); `None`, если директивы для неё нет.
+    pub fn level_for(&self, target: &str) -> Option<LevelFilter> {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|&(_, level)| level)
+    }
+
+    /// Фильтр `tracing`: директивы по целям и уровень `default` для
+    /// остальных.
+    fn filter(&self, default: LevelFilter) -> Targets {
+        Targets::new()
+            .with_targets(
+                self.targets
+                    .iter()
+                    .map(|(target, level)| (target.clone(), to_trace_level(*level))),
+            )
+            .with_default(to_trace_level(default))
+    }
+}
+
+impl FromStr for LogTargets {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut targets = Self::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let invalid =
+                || QuoteError::value_err(format!("некорректная директива журнала: '{directive}'"));
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(invalid());
+                    }
+                    let level = level.trim().parse().map_err(|_| invalid())?;
+                    targets.targets.push((target.to_string(), level));
+                }
+                None => match directive.parse() {
+                    Ok(level) => targets.default = Some(level),
+                    Err(_) => targets
+                        .targets
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        Ok(targets)
+    }
+}
+
+/// Слой форматирования строк журнала.
+fn format_layer<S, W>(writer: W, ansi: bool) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    fmt::layer()
+        .with_timer(ClockTime)
+        .with_target(false)
+        .with_ansi(ansi)
+        .with_writer(writer)
+}
+
+/// Установить глобальный обработчик журнала: запись в `log_file`
+/// (уровень `level` с поправками `targets`) и, если задан `console_level`,
+/// в stderr.
+pub(crate) fn init(
+    log_file: File,
+    level: LevelFilter,
+    targets: &LogTargets,
+    console_level: Option<LevelFilter>,
+) -> Result<(), QuoteError> {
+    let file_layer = format_layer(Mutex::new(log_file), false).with_filter(targets.filter(level));
+    let console_layer = console_level.map(|console_level| {
+        format_layer(io::stderr, io::stderr().is_terminal())
+            .with_filter(to_trace_level(console_level))
+    });
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(console_layer)
+        .try_init()
+        .map_err(|e| QuoteError::runtime_err(format!("ошибка инициализации логгера: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::info_span;
+
+    /// Буфер, в который слой записывает строки журнала.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lines_carry_span_fields() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(format_layer(move || writer.clone(), false).with_filter(TraceLevel::INFO));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _session = info_span!("session", id = 1001, peer = "127.0.0.1:5000").entered();
+            tracing::info!("Ответ: OK");
+            tracing::debug!("не попадёт в журнал");
+        });
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.contains("INFO session{id=1001 peer=\"127.0.0.1:5000\"}: Ответ: OK"));
+    }
+
+    #[test]
+    fn targets_override_default_level() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let targets: LogTargets = "qserver::udp=debug".parse().unwrap();
+        let subscriber = tracing_subscriber::registry().with(
+            format_layer(move || writer.clone(), false)
+                .with_filter(targets.filter(LevelFilter::Info)),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "qserver::udp", "датаграмма отправлена");
+            tracing::debug!(target: "qserver::tcp", "не попадёт в журнал");
+            tracing::info!(target: "qserver::tcp", "клиент подключён");
+        });
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.contains("датаграмма отправлена"));
+        assert!(text.contains("клиент подключён"));
+    }
+
+    #[test]
+    fn directives_are_parsed() {
+        let targets: LogTargets = "warn,commons::pool=trace, qserver, qserver::tcp = off"
+            .parse()
+            .unwrap();
+        assert_eq!(targets.default_level(), Some(LevelFilter::Warn));
+        assert_eq!(targets.level_for("commons::pool"), Some(LevelFilter::Trace));
+        assert_eq!(targets.level_for("qserver::udp"), Some(LevelFilter::Trace));
+        assert_eq!(
+            targets.level_for("qserver::tcp::session"),
+            Some(LevelFilter::Off)
+        );
+        assert_eq!(targets.level_for("commons"), None);
+
+        assert_eq!("".parse::<LogTargets>().unwrap(), LogTargets::default());
+        assert!("qserver=loud".parse::<LogTargets>().is_err());
+        assert!("=debug".parse::<LogTargets>().is_err());
+    }
+
+    #[test]
+    fn levels_are_converted() {
+        assert_eq!(to_trace_level(LevelFilter::Warn), TraceLevel::WARN);
+        assert_eq!(to_trace_level(LevelFilter::Off), TraceLevel::OFF);
+    }
+}
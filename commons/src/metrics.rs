@@ -0,0 +1,360 @@
+//! Метрики приложений: счётчики, показатели и гистограммы.
+//!
+//! Метрики регистрируются в [`MetricsRegistry`] под уникальным именем
+//! и обновляются атомарно из любых потоков. Реестр выводит их значения
+//! в виде простого текста ([`MetricsRegistry::render_text`]) или в формате
+//! Prometheus ([`MetricsRegistry::render_prometheus`]).
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::metrics::MetricsRegistry;
+//!
+//! let registry = MetricsRegistry::new();
+//! let received = registry.counter("quotes_received_total", "Получено котировок");
+//! received.inc();
+//!
+//! assert!(registry.render_prometheus().contains("quotes_received_total 1"));
+//! ```
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Монотонно возрастающий счётчик.
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: AtomicU64,
+}
+
+impl Counter {
+    /// Увеличить на единицу.
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Увеличить на `n`.
+    pub fn add(&self, n: u64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Текущее значение.
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Показатель, который может как расти, так и уменьшаться.
+#[derive(Debug, Default)]
+pub struct Gauge {
+    value: AtomicI64,
+}
+
+impl Gauge {
+    /// Установить значение.
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    /// Увеличить на единицу.
+    pub fn inc(&self) {
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Уменьшить на единицу.
+    pub fn dec(&self) {
+        self.value.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Текущее значение.
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Гистограмма с фиксированными верхними границами корзин.
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    /// Сумма наблюдений: биты `f64`.
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    /// Создать гистограмму; границы сортируются, нечисловые отбрасываются.
+    pub fn new(bounds: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| !b.is_nan()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        Self {
+            buckets: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+            bounds,
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    /// Учесть наблюдение.
+    pub fn observe(&self, value: f64) {
+        if let Some(pos) = self.bounds.iter().position(|&bound| value <= bound) {
+            self.buckets[pos].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    /// Количество наблюдений.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Сумма наблюдений.
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum.load(Ordering::Relaxed))
+    }
+
+    /// Накопленные количества наблюдений по верхним границам корзин
+    /// (без корзины `+Inf`, равной [`Histogram::count`]).
+    pub fn cumulative(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        self.bounds
+            .iter()
+            .zip(&self.buckets)
+            .map(|(&bound, bucket)| {
+                total += bucket.load(Ordering::Relaxed);
+                (bound, total)
+            })
+            .collect()
+    }
+}
+
+/// Зарегистрированная метрика.
+#[derive(Debug, Clone)]
+enum Metric {
+    Counter(Arc<Counter>),
+    Gauge(Arc<Gauge>),
+    Histogram(Arc<Histogram>),
+}
+
+impl Metric {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Metric::Counter(_) => "counter",
+            Metric::Gauge(_) => "gauge",
+            Metric::Histogram(_) => "histogram",
+        }
+    }
+}
+
+/// Реестр метрик приложения.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    metrics: Mutex<Vec<(String, String, Metric)>>,
+}
+
+impl MetricsRegistry {
+    /// Пустой реестр.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Зарегистрировать счётчик или вернуть уже зарегистрированный
+    /// под этим именем.
+    ///
+    /// ## Args
+    /// - `name` — имя в формате Prometheus (`[a-zA-Z_:][a-zA-Z0-9_:]*`)
+    /// - `help` — описание метрики
+    ///
+    /// ## Паника
+    ///
+    /// Если имя некорректно или занято метрикой другого вида.
+    pub fn counter(&self, name: &str, help: &str) -> Arc<Counter> {
+        match self.register(name, help, || Metric::Counter(Arc::default())) {
+            Metric::Counter(counter) => counter,
+            other => panic!(
+                "метрика {name} уже зарегистрирована как {}",
+                other.type_name()
+            ),
+        }
+    }
+
+    /// Зарегистрировать показатель (см. [`MetricsRegistry::counter`]).
+    pub fn gauge(&self, name: &str, help: &str) -> Arc<Gauge> {
+        match self.register(name, help, || Metric::Gauge(Arc::default())) {
+            Metric::Gauge(gauge) => gauge,
+            other => panic!(
+                "метрика {name} уже зарегистрирована как {}",
+                other.type_name()
+            ),
+        }
+    }
+
+    /// Зарегистрировать гистограмму с верхними границами корзин `bounds`
+    /// (см. [`MetricsRegistry::counter`]).
+    pub fn histogram(&self, name: &str, help: &str, bounds: &[f64]) -> Arc<Histogram> {
+        match self.register(name, help, || {
+            Metric::Histogram(Arc::new(Histogram::new(bounds)))
+        }) {
+            Metric::Histogram(histogram) => histogram,
+            other => panic!(
+                "метрика {name} уже зарегистрирована как {}",
+                other.type_name()
+            ),
+        }
+    }
+
+    fn register(&self, name: &str, help: &str, make: impl FnOnce() -> Metric) -> Metric {
+        assert!(valid_name(name), "некорректное имя метрики: {name}");
+        let mut metrics = self.metrics.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some((_, _, metric)) = metrics.iter().find(|(n, _, _)| n == name) {
+            return metric.clone();
+        }
+        let metric = make();
+        metrics.push((name.to_string(), help.to_string(), metric.clone()));
+        metric
+    }
+
+    /// Значения метрик простым текстом: `имя значение` на строку; для
+    /// гистограмм — количество, сумма и среднее.
+    pub fn render_text(&self) -> String {
+        let metrics = self.metrics.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+        for (name, _, metric) in metrics.iter() {
+            let _ = match metric {
+                Metric::Counter(counter) => writeln!(out, "{name} {}", counter.get()),
+                Metric::Gauge(gauge) => writeln!(out, "{name} {}", gauge.get()),
+                Metric::Histogram(histogram) => {
+                    let count = histogram.count();
+                    let avg = if count > 0 {
+                        histogram.sum() / count as f64
+                    } else {
+                        0.0
+                    };
+                    writeln!(
+                        out,
+                        "{name} count={count} sum={} avg={avg:.3}",
+                        histogram.sum()
+                    )
+                }
+            };
+        }
+        out
+    }
+
+    /// Значения метрик в текстовом формате Prometheus.
+    pub fn render_prometheus(&self) -> String {
+        let metrics = self.metrics.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+        for (name, help, metric) in metrics.iter() {
+            let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {}", metric.type_name());
+            let _ = match metric {
+                Metric::Counter(counter) => writeln!(out, "{name} {}", counter.get()),
+                Metric::Gauge(gauge) => writeln!(out, "{name} {}", gauge.get()),
+                Metric::Histogram(histogram) => {
+                    for (bound, count) in histogram.cumulative() {
+                        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+                    }
+                    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", histogram.count());
+                    let _ = writeln!(out, "{name}_sum {}", histogram.sum());
+                    writeln!(out, "{name}_count {}", histogram.count())
+                }
+            };
+        }
+        out
+    }
+}
+
+/// Имя метрики допустимо в формате Prometheus.
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn counters_and_gauges_are_shared() {
+        let registry = MetricsRegistry::new();
+        let counter = registry.counter("events_total", "События");
+        let again = registry.counter("events_total", "События");
+        let gauge = registry.gauge("clients", "Клиенты");
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || (0..1000).for_each(|_| counter.inc()))
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        gauge.inc();
+        gauge.inc();
+        gauge.dec();
+
+        assert_eq!(again.get(), 4000);
+        assert_eq!(gauge.get(), 1);
+    }
+
+    #[test]
+    fn histogram_counts_by_bucket() {
+        let histogram = Histogram::new(&[10.0, 1.0, 5.0, f64::NAN]);
+        for value in [0.5, 1.0, 3.0, 7.0, 100.0] {
+            histogram.observe(value);
+        }
+
+        assert_eq!(histogram.cumulative(), vec![(1.0, 2), (5.0, 3), (10.0, 4)]);
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.sum(), 111.5);
+    }
+
+    #[test]
+    fn prometheus_rendering() {
+        let registry = MetricsRegistry::new();
+        registry.counter("quotes_total", "Котировки").add(3);
+        registry.gauge("clients", "Клиенты").set(-2);
+        registry
+            .histogram("latency_ms", "Задержка", &[1.0])
+            .observe(0.5);
+
+        let text = registry.render_prometheus();
+        assert!(text.contains("# TYPE quotes_total counter\nquotes_total 3\n"));
+        assert!(text.contains("clients -2\n"));
+        assert!(text.contains("latency_ms_bucket{le=\"1\"} 1\n"));
+        assert!(text.contains("latency_ms_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("latency_ms_count 1\n"));
+
+        let plain = registry.render_text();
+        assert!(plain.starts_with("quotes_total 3\nclients -2\n"));
+        assert!(plain.contains("latency_ms count=1 sum=0.5 avg=0.500"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn kind_mismatch_panics() {
+        let registry = MetricsRegistry::new();
+        registry.counter("value", "");
+        registry.gauge("value", "");
+    }
+
+    #[test]
+    fn names_are_validated() {
+        assert!(valid_name("quotes_total"));
+        assert!(valid_name("ns:metric_1"));
+        assert!(!valid_name("1metric"));
+        assert!(!valid_name("bad-name"));
+        assert!(!valid_name(""));
+    }
+}
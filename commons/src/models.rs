@@ -1,31 +1,867 @@
 //! Модели данных для приложений.
 
+use crate::codec::{CURRENCY_WIDTH, EXCHANGE_WIDTH, TICKER_WIDTH};
+use crate::config::{MAX_CLOCK_SKEW_MS, MIN_QUOTE_TIMESTAMP_MS};
 use crate::errors::QuoteError;
-use macros::{QuoteDisplay, QuoteEnumDisplay};
+use crate::price::Price;
+use crate::traits::Validate;
+use crate::utils::get_timestamp_ms;
+use macros::{
+    QuoteBinary, QuoteBuilder, QuoteCsv, QuoteDisplay, QuoteEnumDisplay, QuoteRandom, QuoteSchema,
+};
 use serde::{Deserialize, Serialize};
 
 /// Вид транзакций для биржевого события.
-#[derive(Debug, Clone, QuoteEnumDisplay, Serialize, Deserialize)]
+///
+/// В JSON вид записывается именем варианта (`"ShortSell"`), в строковом
+/// представлении — тегом из атрибута `str` (`short_sell`), в двоичном —
+/// кодом из атрибута `code`. Значение по умолчанию — покупка.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    QuoteEnumDisplay,
+    QuoteRandom,
+    QuoteSchema,
+    Serialize,
+    Deserialize,
+)]
 pub enum Transaction {
     /// Продажа.
     #[str("sell")]
+    #[code(0)]
+    #[serde(rename = "Sell")]
     Sell,
     /// Покупка.
     #[str("buy")]
+    #[code(1)]
+    #[serde(rename = "Buy")]
+    #[default]
     Buy,
+    /// Продажа без покрытия (открытие короткой позиции).
+    #[str("short_sell")]
+    #[code(2)]
+    #[serde(rename = "ShortSell")]
+    ShortSell,
+    /// Отмена ранее опубликованной сделки.
+    #[str("cancel")]
+    #[code(3)]
+    #[serde(rename = "Cancel")]
+    Cancel,
+    /// Исправление ранее опубликованной сделки.
+    #[str("correction")]
+    #[code(4)]
+    #[serde(rename = "Correction")]
+    Correction,
+}
+
+impl Transaction {
+    /// Все виды транзакций.
+    pub const ALL: [Transaction; 5] = [
+        Transaction::Sell,
+        Transaction::Buy,
+        Transaction::ShortSell,
+        Transaction::Cancel,
+        Transaction::Correction,
+    ];
+
+    /// Сделка на стороне покупки.
+    pub fn is_buy(self) -> bool {
+        matches!(self, Transaction::Buy)
+    }
+
+    /// Сделка на стороне продажи, в том числе без покрытия.
+    pub fn is_sell(self) -> bool {
+        matches!(self, Transaction::Sell | Transaction::ShortSell)
+    }
+
+    /// Служебная запись о ранее опубликованной сделке (отмена или
+    /// исправление), а не новая сделка: её объём не добавляется к обороту.
+    pub fn is_amendment(self) -> bool {
+        matches!(self, Transaction::Cancel | Transaction::Correction)
+    }
 }
 
 /// Структура биржевого события.
-#[derive(Debug, Clone, QuoteDisplay, Serialize, Deserialize)]
+///
+/// В строковом представлении поля разделены `|`. За основными полями
+/// следуют необязательные поля (отсутствующее значение — пустой сегмент),
+/// последним — номер последовательности; позиции закреплены атрибутами
+/// `#[pos(n)]`. Недостающие в конце строки сегменты при разборе заполняются
+/// значениями по умолчанию, поэтому строки старого формата (без `seq`
+/// или без необязательных полей) также разбираются. Строка записывается без перевода
+/// строки, а при разборе `\n` или `\r\n` в конце допускаются.
+///
+/// Значение удобно собирать построителем [`StockQuoteBuilder`]:
+/// обязательные поля проверяются при компиляции, `try_build` также
+/// проверяет значения ([`Validate`]). Двоичное представление описано
+/// в модуле [`codec`](crate::codec). Случайные котировки для тестов
+/// и нагрузочных прогонов даёт [`StockQuote::random`], JSON Schema
+/// представления — [`root_schema`](crate::schema::root_schema).
+/// Значение по умолчанию (пустой тикер, нулевые поля) проверку не проходит
+/// и служит основой тестовых значений: `..Default::default()`.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    Serialize,
+    Deserialize,
+    QuoteBuilder,
+    QuoteDisplay,
+    QuoteCsv,
+    QuoteBinary,
+    QuoteRandom,
+    QuoteSchema,
+)]
+#[quote_builder(validate)]
 pub struct StockQuote {
     /// Короткое наименование биржевого инструмента (тикер).
+    #[binary(width = TICKER_WIDTH)]
+    #[pos(0)]
     pub ticker: String,
     /// Текущая цена за единицу.
-    pub price: f64,
+    #[pos(1)]
+    pub price: Price,
     /// Количество приобретённых (проданных) акций.
+    #[random(range(1, 10_000))]
+    #[pos(2)]
     pub volume: u32,
-    /// Временная метка операции.
+    /// Временная метка операции, миллисекунды от начала эпохи UNIX.
+    #[random(range(MIN_QUOTE_TIMESTAMP_MS, RANDOM_MAX_TIMESTAMP_MS))]
+    #[pos(3)]
     pub timestamp: u64,
     /// Вид транзакции.
+    #[pos(4)]
     pub transaction: Transaction,
+    /// Сквозной номер котировки, присвоенный генератором сервера; `0` —
+    /// номер не задан (например, в записях старого формата).
+    #[serde(default)]
+    #[builder(default)]
+    #[quote(default)]
+    #[pos(9)]
+    pub seq: u64,
+    /// Лучшая цена покупки.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[quote(optional)]
+    #[random(default)]
+    #[pos(5)]
+    pub bid: Option<f64>,
+    /// Лучшая цена продажи.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[quote(optional)]
+    #[random(default)]
+    #[pos(6)]
+    pub ask: Option<f64>,
+    /// Биржа, на которой совершена сделка.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[quote(optional)]
+    #[binary(width = EXCHANGE_WIDTH)]
+    #[random(choice("NASDAQ", "NYSE", "MOEX"))]
+    #[pos(7)]
+    pub exchange: Option<String>,
+    /// Валюта цены (код ISO 4217).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[quote(optional)]
+    #[binary(width = CURRENCY_WIDTH)]
+    #[random(choice("USD", "EUR", "RUB"))]
+    #[pos(8)]
+    pub currency: Option<String>,
+}
+
+/// Наибольшая временная метка случайной котировки ([`StockQuote::random`]),
+/// ноябрь 2023 г.: заведомо не опережает местные часы.
+const RANDOM_MAX_TIMESTAMP_MS: u64 = 1_700_000_000_000;
+
+impl Validate for StockQuote {
+    /// Тикер не пустой, цены положительные и конечные, временная метка
+    /// не раньше [`MIN_QUOTE_TIMESTAMP_MS`] и опережает местные часы
+    /// не более чем на [`MAX_CLOCK_SKEW_MS`].
+    fn validate(&self) -> Result<(), QuoteError> {
+        if self.ticker.trim().is_empty() {
+            return Err(QuoteError::value_err("пустой тикер"));
+        }
+        if !self.price.is_positive() {
+            return Err(QuoteError::value_err(format!(
+                "{}: некорректное значение price {}",
+                self.ticker, self.price
+            )));
+        }
+        for (field, price) in [("bid", self.bid), ("ask", self.ask)] {
+            if let Some(price) = price
+                && !(price.is_finite() && price > 0.0)
+            {
+                return Err(QuoteError::value_err(format!(
+                    "{}: некорректное значение {field} {price}",
+                    self.ticker
+                )));
+            }
+        }
+        if let (Some(bid), Some(ask)) = (self.bid, self.ask)
+            && bid > ask
+        {
+            return Err(QuoteError::value_err(format!(
+                "{}: bid {bid} больше ask {ask}",
+                self.ticker
+            )));
+        }
+        let latest = get_timestamp_ms().saturating_add(MAX_CLOCK_SKEW_MS);
+        if !(MIN_QUOTE_TIMESTAMP_MS..=latest).contains(&self.timestamp) {
+            return Err(QuoteError::value_err(format!(
+                "{}: некорректная временная метка {}",
+                self.ticker, self.timestamp
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Котировка в датаграмме UDP-трансляции.
+///
+/// Сквозной номер котировки [`StockQuote::seq`] при фильтрации тикеров
+/// идёт с пропусками, поэтому трансляция одному клиенту нумерует свои
+/// датаграммы отдельно (`stream_seq`, с 1): по его пропускам клиент видит
+/// потерянные датаграммы именно своей подписки. В JSON номер записывается
+/// рядом с полями котировки, поэтому датаграмму можно прочитать и как
+/// [`StockQuote`], а котировка без номера разбирается с `stream_seq = 0`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamQuote {
+    /// Номер датаграммы в трансляции; `0` — номер не задан.
+    #[serde(default)]
+    pub stream_seq: u64,
+    /// Котировка.
+    #[serde(flatten)]
+    pub quote: StockQuote,
+}
+
+/// Свеча (OHLC) по котировкам одного тикера за интервал времени.
+///
+/// В строковом представлении позиции полей закреплены атрибутами
+/// `#[pos(n)]`: новые поля добавляются в конец строки.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::models::{Candle, StockQuote};
+///
+/// let first: StockQuote = "AAPL|10|5|60000|buy".parse().unwrap();
+/// let second: StockQuote = "AAPL|12|3|61000|sell".parse().unwrap();
+///
+/// let mut candle = Candle::new(&first, 60_000);
+/// candle.update(&second);
+///
+/// assert_eq!((candle.open, candle.high, candle.close), (10.0, 12.0, 12.0));
+/// assert_eq!(candle.volume, 8);
+/// ```
+#[derive(Debug, Clone, PartialEq, QuoteDisplay, QuoteCsv, QuoteSchema, Serialize, Deserialize)]
+pub struct Candle {
+    /// Тикер.
+    #[pos(0)]
+    pub ticker: String,
+    /// Цена открытия — первая котировка интервала.
+    #[pos(1)]
+    pub open: f64,
+    /// Максимальная цена за интервал.
+    #[pos(2)]
+    pub high: f64,
+    /// Минимальная цена за интервал.
+    #[pos(3)]
+    pub low: f64,
+    /// Цена закрытия — последняя учтённая котировка.
+    #[pos(4)]
+    pub close: f64,
+    /// Суммарный объём сделок.
+    #[pos(5)]
+    pub volume: u64,
+    /// Начало интервала, миллисекунды от начала эпохи UNIX.
+    #[pos(6)]
+    pub start_ts: u64,
+    /// Длительность интервала, миллисекунды.
+    #[pos(7)]
+    pub interval: u64,
+}
+
+impl Candle {
+    /// Открыть свечу по первой котировке. Начало интервала выравнивается
+    /// по границе, кратной `interval`.
+    ///
+    /// ## Args
+    /// - `quote` — первая котировка интервала
+    /// - `interval` — длительность интервала, миллисекунды; `0` — интервал
+    ///   без ограничения по времени
+    pub fn new(quote: &StockQuote, interval: u64) -> Self {
+        let start_ts = match interval {
+            0 => quote.timestamp,
+            _ => quote.timestamp - quote.timestamp % interval,
+        };
+        Self {
+            ticker: quote.ticker.clone(),
+            open: quote.price.to_f64(),
+            high: quote.price.to_f64(),
+            low: quote.price.to_f64(),
+            close: quote.price.to_f64(),
+            volume: quote.volume as u64,
+            start_ts,
+            interval,
+        }
+    }
+
+    /// Котировка относится к этой свече: тот же тикер и время внутри
+    /// интервала.
+    pub fn contains(&self, quote: &StockQuote) -> bool {
+        quote.ticker == self.ticker
+            && quote.timestamp >= self.start_ts
+            && (self.interval == 0 || quote.timestamp < self.end_ts())
+    }
+
+    /// Конец интервала (не включительно), миллисекунды.
+    pub fn end_ts(&self) -> u64 {
+        self.start_ts.saturating_add(self.interval)
+    }
+
+    /// Учесть котировку. Котировки, не относящиеся к свече
+    /// (см. [`Candle::contains`]), пропускаются.
+    pub fn update(&mut self, quote: &StockQuote) -> &mut Self {
+        if self.contains(quote) {
+            let price = quote.price.to_f64();
+            self.high = self.high.max(price);
+            self.low = self.low.min(price);
+            self.close = price;
+            self.volume += quote.volume as u64;
+        }
+        self
+    }
+}
+
+/// Сторона биржевого стакана.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, QuoteEnumDisplay, Serialize, Deserialize)]
+pub enum BookSide {
+    /// Заявки на покупку.
+    #[str("bid")]
+    Bid,
+    /// Заявки на продажу.
+    #[str("ask")]
+    Ask,
+}
+
+/// Ценовой уровень стакана: цена и суммарный объём заявок по ней.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookLevel {
+    /// Цена уровня.
+    pub price: f64,
+    /// Суммарный объём заявок.
+    pub size: u64,
+}
+
+/// Изменение одного уровня стакана. Нулевой `size` удаляет уровень.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookUpdate {
+    /// Сторона стакана.
+    pub side: BookSide,
+    /// Цена уровня.
+    pub price: f64,
+    /// Новый объём уровня.
+    pub size: u64,
+    /// Временная метка изменения, миллисекунды от начала эпохи UNIX.
+    pub timestamp: u64,
+}
+
+/// Биржевой стакан (L2) одного тикера.
+///
+/// Уровни покупки упорядочены по убыванию цены, продажи — по возрастанию,
+/// так что лучшие цены всегда первые.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::models::{BookSide, BookUpdate, OrderBook};
+///
+/// let mut book = OrderBook::new("AAPL");
+/// book.apply(&BookUpdate { side: BookSide::Bid, price: 99.5, size: 10, timestamp: 1 });
+/// book.apply(&BookUpdate { side: BookSide::Ask, price: 100.5, size: 7, timestamp: 2 });
+///
+/// assert_eq!(book.spread(), Some(1.0));
+/// assert_eq!(book.mid(), Some(100.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderBook {
+    /// Тикер.
+    pub ticker: String,
+    /// Уровни покупки, от лучшего (наибольшая цена).
+    pub bids: Vec<BookLevel>,
+    /// Уровни продажи, от лучшего (наименьшая цена).
+    pub asks: Vec<BookLevel>,
+    /// Время последнего изменения, миллисекунды от начала эпохи UNIX.
+    pub timestamp: u64,
+}
+
+impl OrderBook {
+    /// Создать пустой стакан.
+    pub fn new(ticker: &str) -> Self {
+        Self {
+            ticker: ticker.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Уровни одной стороны стакана, от лучшего.
+    pub fn levels(&self, side: BookSide) -> &[BookLevel] {
+        match side {
+            BookSide::Bid => &self.bids,
+            BookSide::Ask => &self.asks,
+        }
+    }
+
+    /// Лучший уровень покупки.
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids.first()
+    }
+
+    /// Лучший уровень продажи.
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks.first()
+    }
+
+    /// Спред между лучшими ценами продажи и покупки.
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Средняя цена между лучшими уровнями.
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / 2.0)
+    }
+
+    /// Применить изменение уровня: добавить, заменить объём или удалить
+    /// (при нулевом `size`), сохраняя порядок уровней.
+    pub fn apply(&mut self, update: &BookUpdate) {
+        let levels = match update.side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        // Позиция в порядке «от лучшего»: для покупки — по убыванию цены.
+        let search = levels.binary_search_by(|level| match update.side {
+            BookSide::Bid => update.price.total_cmp(&level.price),
+            BookSide::Ask => level.price.total_cmp(&update.price),
+        });
+
+        match (search, update.size) {
+            (Ok(pos), 0) => {
+                levels.remove(pos);
+            }
+            (Ok(pos), size) => levels[pos].size = size,
+            (Err(_), 0) => {}
+            (Err(pos), size) => levels.insert(
+                pos,
+                BookLevel {
+                    price: update.price,
+                    size,
+                },
+            ),
+        }
+        self.timestamp = self.timestamp.max(update.timestamp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn quote() -> StockQuote {
+        StockQuote {
+            ticker: "AAPL".to_string(),
+            price: "150.5".parse().unwrap(),
+            volume: 100,
+            timestamp: 1_700_000_000,
+            transaction: Transaction::Buy,
+            seq: 42,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn text_roundtrip_with_optional_fields() {
+        let quote = StockQuote {
+            bid: Some(150.4),
+            ask: Some(150.6),
+            exchange: Some("NASDAQ".to_string()),
+            currency: Some("USD".to_string()),
+            ..quote()
+        };
+        let line = quote.to_string();
+        assert_eq!(
+            line,
+            "AAPL|150.5|100|1700000000|buy|150.4|150.6|NASDAQ|USD|42"
+        );
+
+        let parsed: StockQuote = line.parse().unwrap();
+        assert_eq!(parsed.bid, Some(150.4));
+        assert_eq!(parsed.exchange.as_deref(), Some("NASDAQ"));
+    }
+
+    #[test]
+    fn legacy_text_and_json_are_accepted() {
+        let parsed: StockQuote = "AAPL|150.5|100|1700000000|sell".parse().unwrap();
+        assert_eq!(parsed.ticker, "AAPL");
+        assert_eq!(parsed.seq, 0);
+        assert!(parsed.bid.is_none() && parsed.currency.is_none());
+
+        let parsed: StockQuote = "AAPL|150.5|100|1700000000|sell|150.4|150.6|NASDAQ|USD"
+            .parse()
+            .unwrap();
+        assert_eq!(parsed.seq, 0);
+        assert_eq!(parsed.bid, Some(150.4));
+        assert_eq!(parsed.ask, Some(150.6));
+        assert_eq!(parsed.exchange.as_deref(), Some("NASDAQ"));
+        assert_eq!(parsed.currency.as_deref(), Some("USD"));
+
+        let json = r#"{"ticker":"AAPL","price":150.5,"volume":100,"timestamp":1700000000,"transaction":"Buy"}"#;
+        let parsed: StockQuote = serde_json::from_str(json).unwrap();
+        assert!(parsed.ask.is_none() && parsed.exchange.is_none());
+        assert_eq!(parsed.seq, 0);
+    }
+
+    #[test]
+    fn stream_quote_keeps_both_numbers() {
+        let datagram = StreamQuote {
+            stream_seq: 3,
+            quote: quote(),
+        };
+        let json = serde_json::to_string(&datagram).unwrap();
+
+        let parsed: StreamQuote = serde_json::from_str(&json).unwrap();
+        assert_eq!((parsed.stream_seq, parsed.quote.seq), (3, 42));
+        let plain: StockQuote = serde_json::from_str(&json).unwrap();
+        assert_eq!(plain.to_string(), quote().to_string());
+
+        let json = serde_json::to_string(&quote()).unwrap();
+        let parsed: StreamQuote = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.stream_seq, 0);
+    }
+
+    #[test]
+    fn quote_is_flattened_into_envelope() {
+        #[derive(Debug, QuoteDisplay)]
+        struct Envelope {
+            seq: u64,
+            #[quote(flatten)]
+            quote: StockQuote,
+        }
+
+        let envelope = Envelope {
+            seq: 7,
+            quote: quote(),
+        };
+        let line = envelope.to_string();
+        assert_eq!(line, "7|AAPL|150.5|100|1700000000|buy|||||42");
+        let parsed: Envelope = line.parse().unwrap();
+        assert_eq!(parsed.seq, 7);
+        assert_eq!(parsed.quote.to_string(), quote().to_string());
+
+        // Котировка в конце строки может быть в коротком формате.
+        let parsed: Envelope = "8|AAPL|150.5|100|1700000000|sell".parse().unwrap();
+        assert_eq!(parsed.quote.transaction, Transaction::Sell);
+        assert!("8|AAPL|150.5".parse::<Envelope>().is_err());
+    }
+
+    #[test]
+    fn transaction_tags_roundtrip() {
+        for transaction in Transaction::ALL {
+            let text = transaction.to_string();
+            assert_eq!(text.parse::<Transaction>().unwrap(), transaction);
+
+            let json = serde_json::to_string(&transaction).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Transaction>(&json).unwrap(),
+                transaction
+            );
+        }
+        assert_eq!(Transaction::ShortSell.to_string(), "short_sell");
+        assert_eq!(
+            serde_json::to_string(&Transaction::ShortSell).unwrap(),
+            r#""ShortSell""#
+        );
+
+        assert!(Transaction::ShortSell.is_sell() && !Transaction::ShortSell.is_amendment());
+        assert!(Transaction::Buy.is_buy());
+        let amendments = Transaction::ALL.iter().filter(|t| t.is_amendment());
+        assert_eq!(amendments.count(), 2);
+    }
+
+    #[test]
+    fn json_schema_describes_json_payload() {
+        use crate::schema::root_schema;
+
+        let schema = root_schema::<StockQuote>();
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["ticker", "price", "volume", "timestamp", "transaction"])
+        );
+        assert_eq!(
+            schema["properties"]["transaction"]["enum"],
+            serde_json::json!(["Sell", "Buy", "ShortSell", "Cancel", "Correction"])
+        );
+
+        // Каждое свойство полной котировки описано в схеме.
+        let quote = StockQuote {
+            bid: Some(1.0),
+            ask: Some(2.0),
+            exchange: Some("NYSE".to_string()),
+            currency: Some("USD".to_string()),
+            ..quote()
+        };
+        let json = serde_json::to_value(&quote).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in json.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "{key}");
+        }
+        assert_eq!(properties.len(), json.as_object().unwrap().len());
+    }
+
+    #[test]
+    fn empty_optional_fields_are_empty_segments() {
+        let line = quote().to_string();
+        assert_eq!(line, "AAPL|150.5|100|1700000000|buy|||||42");
+        assert!(line.parse::<StockQuote>().unwrap().bid.is_none());
+
+        let json = serde_json::to_string(&quote()).unwrap();
+        assert!(!json.contains("bid"));
+    }
+
+    #[test]
+    fn partial_optional_fields_keep_positions() {
+        let quote = StockQuote {
+            currency: Some("RUB".to_string()),
+            ..quote()
+        };
+        let line = quote.to_string();
+        assert_eq!(line, "AAPL|150.5|100|1700000000|buy||||RUB|42");
+
+        let parsed: StockQuote = line.parse().unwrap();
+        assert!(parsed.bid.is_none());
+        assert_eq!(parsed.seq, 42);
+        assert_eq!(parsed.currency.as_deref(), Some("RUB"));
+    }
+
+    #[test]
+    fn invalid_segments_are_rejected() {
+        assert!("AAPL|150.5|100".parse::<StockQuote>().is_err());
+        assert!(
+            "AAPL|150.5|100|1|buy|cheap||||1"
+                .parse::<StockQuote>()
+                .is_err()
+        );
+        assert!("AAPL|150.5|100|1|buy|||||-1".parse::<StockQuote>().is_err());
+        assert!(
+            "AAPL|1|1|1|buy|1|1|X|USD|1|extra"
+                .parse::<StockQuote>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn candle_tracks_ohlc_within_interval() {
+        let at = |price: f64, timestamp: u64| StockQuote {
+            price: Price::try_from(price).unwrap(),
+            timestamp,
+            ..quote()
+        };
+        let mut candle = Candle::new(&at(10.0, 61_500), 60_000);
+        candle
+            .update(&at(12.0, 62_000))
+            .update(&at(9.0, 100_000))
+            .update(&at(11.0, 119_999))
+            .update(&at(50.0, 120_000))
+            .update(&StockQuote {
+                ticker: "MSFT".to_string(),
+                ..at(1.0, 70_000)
+            });
+
+        assert_eq!(candle.start_ts, 60_000);
+        assert_eq!(candle.end_ts(), 120_000);
+        assert_eq!(
+            (candle.open, candle.high, candle.low, candle.close),
+            (10.0, 12.0, 9.0, 11.0)
+        );
+        assert_eq!(candle.volume, 400);
+    }
+
+    #[test]
+    fn candle_text_and_json_roundtrip() {
+        let candle = Candle::new(&quote(), 60_000);
+        let line = candle.to_string();
+        assert_eq!(line, "AAPL|150.5|150.5|150.5|150.5|100|1699980000|60000");
+        assert_eq!(line.parse::<Candle>().unwrap(), candle);
+
+        let json = serde_json::to_string(&candle).unwrap();
+        assert_eq!(serde_json::from_str::<Candle>(&json).unwrap(), candle);
+    }
+
+    fn book_update(side: BookSide, price: f64, size: u64) -> BookUpdate {
+        BookUpdate {
+            side,
+            price,
+            size,
+            timestamp: 1,
+        }
+    }
+
+    #[test]
+    fn order_book_keeps_best_levels_first() {
+        let mut book = OrderBook::new("AAPL");
+        for (price, size) in [(99.0, 5), (99.5, 3), (98.0, 1)] {
+            book.apply(&book_update(BookSide::Bid, price, size));
+        }
+        for (price, size) in [(101.0, 2), (100.5, 4), (102.0, 6)] {
+            book.apply(&book_update(BookSide::Ask, price, size));
+        }
+
+        let prices = |side| -> Vec<f64> { book.levels(side).iter().map(|l| l.price).collect() };
+        assert_eq!(prices(BookSide::Bid), vec![99.5, 99.0, 98.0]);
+        assert_eq!(prices(BookSide::Ask), vec![100.5, 101.0, 102.0]);
+        assert_eq!(book.best_bid().map(|l| l.size), Some(3));
+        assert_eq!(book.spread(), Some(1.0));
+        assert_eq!(book.mid(), Some(100.0));
+    }
+
+    #[test]
+    fn order_book_updates_and_removes_levels() {
+        let mut book = OrderBook::new("AAPL");
+        book.apply(&book_update(BookSide::Ask, 100.0, 4));
+        book.apply(&book_update(BookSide::Ask, 100.0, 9));
+        assert_eq!(
+            book.asks,
+            vec![BookLevel {
+                price: 100.0,
+                size: 9
+            }]
+        );
+
+        book.apply(&book_update(BookSide::Ask, 100.0, 0));
+        book.apply(&book_update(BookSide::Ask, 105.0, 0));
+        assert!(book.asks.is_empty());
+        assert_eq!(book.spread(), None);
+    }
+
+    #[test]
+    fn order_book_json_roundtrip() {
+        let mut book = OrderBook::new("AAPL");
+        book.apply(&book_update(BookSide::Bid, 99.0, 5));
+        let json = serde_json::to_string(&book).unwrap();
+
+        assert_eq!(serde_json::from_str::<OrderBook>(&json).unwrap(), book);
+        assert_eq!(BookSide::Ask.to_string(), "ask");
+    }
+
+    #[test]
+    fn valid_quote_passes_validation() {
+        let quote = StockQuote {
+            timestamp: get_timestamp_ms(),
+            bid: Some(150.4),
+            ask: Some(150.6),
+            ..quote()
+        };
+        assert!(quote.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_quotes_are_rejected_by_validation() {
+        let now = get_timestamp_ms();
+        let valid = StockQuote {
+            timestamp: now,
+            ..quote()
+        };
+        let invalid = [
+            StockQuote {
+                ticker: " ".to_string(),
+                ..valid.clone()
+            },
+            StockQuote {
+                price: Price::ZERO,
+                ..valid.clone()
+            },
+            StockQuote {
+                price: "-1".parse().unwrap(),
+                ..valid.clone()
+            },
+            StockQuote {
+                bid: Some(-1.0),
+                ..valid.clone()
+            },
+            StockQuote {
+                bid: Some(151.0),
+                ask: Some(150.0),
+                ..valid.clone()
+            },
+            StockQuote {
+                timestamp: 1_700_000_000,
+                ..valid.clone()
+            },
+            StockQuote {
+                timestamp: now + MAX_CLOCK_SKEW_MS * 2,
+                ..valid.clone()
+            },
+        ];
+
+        for quote in invalid {
+            let err = quote.validate().unwrap_err();
+            assert!(matches!(err, QuoteError::ValueError(_)), "{quote:?}");
+        }
+    }
+
+    #[test]
+    fn builder_fills_optional_fields_and_validates() {
+        let quote = StockQuote::builder()
+            .ticker("AAPL")
+            .price(Price::from_units(1_505_000))
+            .volume(100u32)
+            .timestamp(get_timestamp_ms())
+            .transaction(Transaction::Buy)
+            .bid(150.4)
+            .try_build()
+            .unwrap();
+        assert_eq!(quote.seq, 0);
+        assert_eq!((quote.bid, quote.ask), (Some(150.4), None));
+
+        let err = StockQuoteBuilder::new()
+            .ticker("AAPL")
+            .price(Price::ZERO)
+            .volume(1u32)
+            .timestamp(get_timestamp_ms())
+            .transaction(Transaction::Sell)
+            .try_build()
+            .unwrap_err();
+        assert!(matches!(err, QuoteError::ValueError(_)));
+    }
+
+    #[test]
+    fn random_quotes_are_valid_and_roundtrip() {
+        let mut rng = crate::randomizer::Randomizer::with_seed(7);
+        for _ in 0..100 {
+            let quote = StockQuote::random(&mut rng);
+            quote.validate().unwrap();
+
+            let parsed: StockQuote = quote.to_string().parse().unwrap();
+            assert_eq!(parsed.to_string(), quote.to_string());
+            let decoded = StockQuote::from_bytes(&quote.to_bytes().unwrap()).unwrap();
+            assert_eq!(decoded.to_string(), quote.to_string());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_quote_display_roundtrips(quote: StockQuote) {
+            let line = quote.to_string();
+            prop_assert_eq!(line.parse::<StockQuote>().unwrap().to_string(), line);
+        }
+
+        #[test]
+        fn arbitrary_candle_display_roundtrips(candle: Candle) {
+            prop_assert_eq!(candle.to_string().parse::<Candle>().unwrap(), candle);
+        }
+    }
 }
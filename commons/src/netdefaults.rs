@@ -0,0 +1,96 @@
+//! Сетевые значения по умолчанию, общие для сервера и клиента.
+//!
+//! Сервер слушает, а клиент по умолчанию подключается к одному и тому же
+//! адресу [`default_server_addr`]; разрешённые порты ([`PORTS_ALLOWED`])
+//! проверяются одинаково в обоих приложениях, а коды временных отказов
+//! ([`RATE_LIMITED`], [`SERVER_FULL`]) сервер отправляет, а клиент
+//! распознаёт по одним и тем же константам.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::netdefaults::{self, DEFAULT_SERVER_PORT};
+//!
+//! assert_eq!(netdefaults::default_server_addr().port(), DEFAULT_SERVER_PORT);
+//! assert_eq!(netdefaults::udp_callback_addr(34254).to_string(), "127.0.0.1:34254");
+//! assert!(netdefaults::is_allowed_port(DEFAULT_SERVER_PORT));
+//! assert!(netdefaults::is_binary_format(Some("format=binary")));
+//! ```
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::ops::RangeInclusive;
+
+/// Диапазон портов TCP и UDP, разрешённых в приложениях.
+pub const PORTS_ALLOWED: RangeInclusive<u16> = 1024..=49151;
+
+/// Порт TCP-сервера по умолчанию.
+pub const DEFAULT_SERVER_PORT: u16 = 8888;
+
+/// Адрес, на котором сервер принимает подключения и к которому клиент
+/// подключается по умолчанию.
+pub const SERVER_HOST: Ipv4Addr = Ipv4Addr::LOCALHOST;
+
+/// Адрес, на который клиент просит сервер присылать UDP-датаграммы.
+pub const UDP_CALLBACK_HOST: Ipv4Addr = Ipv4Addr::LOCALHOST;
+
+/// Код отказа сервера `ERROR|rate_limited|...`: превышена частота команд,
+/// команду можно повторить позже.
+pub const RATE_LIMITED: &str = "rate_limited";
+
+/// Код отказа сервера `ERROR|server_full|...`: все обработчики клиентов
+/// заняты, подключение можно повторить позже.
+pub const SERVER_FULL: &str = "server_full";
+
+/// Параметр UDP-ссылки подписки, которым клиент просит присылать
+/// двоичные датаграммы с контрольной суммой
+/// ([`StreamQuote::to_checked_bytes`](crate::models::StreamQuote::to_checked_bytes))
+/// вместо JSON: `udp://127.0.0.1:34254?format=binary`.
+pub const BINARY_FORMAT_QUERY: &str = "format=binary";
+
+/// Запрошены ли двоичные датаграммы строкой параметров UDP-ссылки `query`
+/// ([`BINARY_FORMAT_QUERY`]).
+pub fn is_binary_format(query: Option<&str>) -> bool {
+    query.is_some_and(|query| query.split('&').any(|pair| pair == BINARY_FORMAT_QUERY))
+}
+
+/// Входит ли порт в [`PORTS_ALLOWED`].
+pub fn is_allowed_port(port: u16) -> bool {
+    PORTS_ALLOWED.contains(&port)
+}
+
+/// Адрес TCP-сервера на [`SERVER_HOST`] с портом `port`.
+pub fn server_addr(port: u16) -> SocketAddr {
+    SocketAddr::from((SERVER_HOST, port))
+}
+
+/// Адрес TCP-сервера по умолчанию.
+pub fn default_server_addr() -> SocketAddr {
+    server_addr(DEFAULT_SERVER_PORT)
+}
+
+/// Адрес приёма UDP-датаграмм клиентом на порту `port`.
+pub fn udp_callback_addr(port: u16) -> SocketAddr {
+    SocketAddr::from((UDP_CALLBACK_HOST, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_consistent() {
+        assert!(is_allowed_port(DEFAULT_SERVER_PORT));
+        assert_eq!(default_server_addr(), "127.0.0.1:8888".parse().unwrap());
+        assert_eq!(server_addr(9000).ip(), SERVER_HOST);
+        assert!(!is_allowed_port(80));
+        assert!(!is_allowed_port(PORTS_ALLOWED.end() + 1));
+    }
+
+    #[test]
+    fn binary_format_is_read_from_query() {
+        assert!(is_binary_format(Some("format=binary")));
+        assert!(is_binary_format(Some("tag=a&format=binary")));
+        assert!(!is_binary_format(Some("format=json")));
+        assert!(!is_binary_format(None));
+    }
+}
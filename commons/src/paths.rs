@@ -0,0 +1,162 @@
+//! Расположение файлов приложений: корневая директория, данные и журналы.
+//!
+//! Корневая директория берётся из переменной окружения [`HOME_ENV`], а если
+//! она не задана — определяется по расположению исполняемого файла
+//! ([`get_workspace_root`]). Директории данных и log-файлов по умолчанию
+//! находятся в корневой ([`DATA_FOLDER`], [`LOG_FOLDER`]) и переопределяются
+//! ключами командной строки `--data-dir` и `--log-dir`.
+//!
+//! Приложение устанавливает итоговые пути при запуске ([`init`]), а модули
+//! получают их через [`current`].
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::paths::Paths;
+//! use std::path::Path;
+//!
+//! let paths = Paths::new("/opt/quote").with_log_dir("/var/log/quote");
+//!
+//! assert_eq!(paths.data_file("tickers.txt"), Path::new("/opt/quote/data/tickers.txt"));
+//! assert_eq!(paths.log_dir(), Path::new("/var/log/quote"));
+//! ```
+
+use crate::config::{DATA_FOLDER, LOG_FOLDER};
+use crate::utils::get_workspace_root;
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Переменная окружения с корневой директорией, например
+/// `QUOTE_HOME=/opt/quote`.
+pub const HOME_ENV: &str = "QUOTE_HOME";
+
+/// Пути, установленные приложением при запуске.
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Директории приложения.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    root: PathBuf,
+    data_dir: PathBuf,
+    log_dir: PathBuf,
+}
+
+impl Paths {
+    /// Пути с корневой директорией `root` и директориями данных
+    /// и log-файлов внутри неё.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            data_dir: root.join(DATA_FOLDER),
+            log_dir: root.join(LOG_FOLDER),
+            root,
+        }
+    }
+
+    /// Пути с корневой директорией из переменной [`HOME_ENV`] или, если она
+    /// не задана, по расположению исполняемого файла.
+    pub fn from_env() -> Self {
+        Self::from_home(env::var_os(HOME_ENV))
+    }
+
+    fn from_home(home: Option<OsString>) -> Self {
+        match home.filter(|home| !home.is_empty()) {
+            Some(home) => Self::new(home),
+            None => Self::new(get_workspace_root()),
+        }
+    }
+
+    /// Заменить директорию данных.
+    pub fn with_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = dir.into();
+        self
+    }
+
+    /// Заменить директорию log-файлов.
+    pub fn with_log_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.log_dir = dir.into();
+        self
+    }
+
+    /// Корневая директория: файлы конфигурации и состояния.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Директория данных.
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// Директория log-файлов.
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// Полный путь к файлу `name` в директории данных.
+    pub fn data_file(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.data_dir.join(name)
+    }
+}
+
+impl Default for Paths {
+    /// Пути по умолчанию — [`Paths::from_env`].
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Установить пути приложения.
+///
+/// Действует только первый вызов: пути не меняются во время работы.
+///
+/// ## Returns
+///
+/// Установленные пути.
+pub fn init(paths: Paths) -> &'static Paths {
+    PATHS.get_or_init(|| paths)
+}
+
+/// Пути приложения; если они не установлены ([`init`]) —
+/// [`Paths::from_env`].
+pub fn current() -> &'static Paths {
+    PATHS.get_or_init(Paths::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirs_default_to_root() {
+        let paths = Paths::new("/opt/quote");
+        assert_eq!(paths.root(), Path::new("/opt/quote"));
+        assert_eq!(paths.data_dir(), Path::new("/opt/quote/data"));
+        assert_eq!(paths.log_dir(), Path::new("/opt/quote/log"));
+    }
+
+    #[test]
+    fn overrides_replace_single_dir() {
+        let paths = Paths::new("/opt/quote").with_data_dir("/srv/data");
+        assert_eq!(
+            paths.data_file("tickers.txt"),
+            Path::new("/srv/data/tickers.txt")
+        );
+        assert_eq!(paths.log_dir(), Path::new("/opt/quote/log"));
+        assert_eq!(paths.root(), Path::new("/opt/quote"));
+    }
+
+    #[test]
+    fn home_overrides_workspace_root() {
+        let home = Paths::from_home(Some("/home/quote".into()));
+        assert_eq!(home, Paths::new("/home/quote"));
+
+        let workspace = Paths::new(get_workspace_root());
+        assert_eq!(Paths::from_home(None), workspace);
+        assert_eq!(Paths::from_home(Some(OsString::new())), workspace);
+    }
+}
@@ -0,0 +1,224 @@
+//! Пул потоков фиксированного размера.
+//!
+//! Задачи ставятся в очередь и выполняются освободившимися потоками пула,
+//! поэтому число потоков не растёт с нагрузкой. Паника в задаче
+//! записывается в журнал и не останавливает поток пула.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::pool::ThreadPool;
+//! use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+//!
+//! let pool = ThreadPool::new("example", 2).unwrap();
+//! let done = Arc::new(AtomicUsize::new(0));
+//! for _ in 0..10 {
+//!     let done = done.clone();
+//!     pool.execute(move || { done.fetch_add(1, Ordering::SeqCst); }).unwrap();
+//! }
+//! pool.join();
+//!
+//! assert_eq!(done.load(Ordering::SeqCst), 10);
+//! ```
+
+use crate::errors::{QuoteError, ResultExt};
+use log::error;
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{Receiver, Sender, channel},
+    },
+    thread::{Builder, JoinHandle},
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Пул потоков фиксированного размера.
+///
+/// При уничтожении пул перестаёт принимать задачи, а потоки завершаются
+/// после выполнения уже поставленных, не блокируя владельца. Дождаться их
+/// завершения позволяет [`ThreadPool::join`].
+pub struct ThreadPool {
+    name: String,
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    busy: Arc<AtomicUsize>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl ThreadPool {
+    /// Создать пул и запустить его потоки.
+    ///
+    /// ## Args
+    /// - `name` — имя пула; потоки называются `name-N`
+    /// - `size` — число потоков
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::ValueError`] при нулевом размере и
+    /// [`QuoteError::IoError`], если поток не удалось запустить.
+    pub fn new(name: &str, size: usize) -> Result<Self, QuoteError> {
+        if size == 0 {
+            return Err(QuoteError::value_err(format!(
+                "размер пула потоков {name} должен быть больше нуля"
+            )));
+        }
+
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let busy = Arc::new(AtomicUsize::new(0));
+        let queued = Arc::new(AtomicUsize::new(0));
+
+        let workers = (1..=size)
+            .map(|num| {
+                let receiver = receiver.clone();
+                let busy = busy.clone();
+                let queued = queued.clone();
+                Builder::new()
+                    .name(format!("{name}-{num}"))
+                    .spawn(move || worker_loop(&receiver, &busy, &queued))
+                    .with_context(|| format!("не удалось запустить поток пула {name}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            sender: Some(sender),
+            workers,
+            busy,
+            queued,
+        })
+    }
+
+    /// Поставить задачу в очередь.
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::RunTimeError`], если потоки пула недоступны.
+    pub fn execute(&self, job: impl FnOnce() + Send + 'static) -> Result<(), QuoteError> {
+        let sender = self
+            .sender
+            .as_ref()
+            .ok_or_else(|| QuoteError::runtime_err(format!("пул {} закрыт", self.name)))?;
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        sender.send(Box::new(job)).map_err(|_| {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            QuoteError::runtime_err(format!("потоки пула {} недоступны", self.name))
+        })
+    }
+
+    /// Число потоков пула.
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Число выполняемых задач.
+    pub fn busy(&self) -> usize {
+        self.busy.load(Ordering::SeqCst)
+    }
+
+    /// Число задач в очереди.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Закрыть очередь и дождаться выполнения всех поставленных задач.
+    pub fn join(mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                error!("Поток пула {} завершился с паникой", self.name);
+            }
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.sender.take();
+    }
+}
+
+/// Цикл потока пула: выполнять задачи, пока очередь открыта.
+fn worker_loop(receiver: &Mutex<Receiver<Job>>, busy: &AtomicUsize, queued: &AtomicUsize) {
+    loop {
+        let job = {
+            let receiver = receiver.lock().unwrap_or_else(|err| err.into_inner());
+            receiver.recv()
+        };
+        let Ok(job) = job else {
+            break;
+        };
+
+        // Сначала `busy`: сумма `busy + queued` не должна проседать.
+        busy.fetch_add(1, Ordering::SeqCst);
+        queued.fetch_sub(1, Ordering::SeqCst);
+        if catch_unwind(AssertUnwindSafe(job)).is_err() {
+            error!("Задача в пуле потоков завершилась с паникой");
+        }
+        busy.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{Barrier, mpsc},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn zero_size_is_rejected() {
+        assert!(ThreadPool::new("empty", 0).is_err());
+    }
+
+    #[test]
+    fn jobs_run_concurrently_up_to_size() {
+        let pool = ThreadPool::new("concurrent", 3).unwrap();
+        let barrier = Arc::new(Barrier::new(4));
+        for _ in 0..3 {
+            let barrier = barrier.clone();
+            pool.execute(move || {
+                barrier.wait();
+            })
+            .unwrap();
+        }
+
+        // Все три задачи выполняются одновременно, иначе барьер не пройти.
+        barrier.wait();
+        assert_eq!(pool.size(), 3);
+        pool.join();
+    }
+
+    #[test]
+    fn extra_jobs_wait_in_queue() {
+        let pool = ThreadPool::new("queue", 1).unwrap();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        pool.execute(move || {
+            let _ = release_rx.recv();
+        })
+        .unwrap();
+        pool.execute(|| {}).unwrap();
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!((pool.busy(), pool.queued()), (1, 1));
+
+        release_tx.send(()).unwrap();
+        pool.join();
+    }
+
+    #[test]
+    fn panic_does_not_kill_worker() {
+        let pool = ThreadPool::new("panic", 1).unwrap();
+        let (tx, rx) = mpsc::channel();
+        pool.execute(|| panic!("сбой задачи")).unwrap();
+        pool.execute(move || tx.send(42).unwrap()).unwrap();
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(42));
+        pool.join();
+    }
+}
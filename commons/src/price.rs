@@ -0,0 +1,348 @@
+//! Цена с фиксированной точкой.
+//!
+//! [`Price`] хранит цену целым числом десятитысячных долей
+//! ([`PRICE_DECIMALS`] знака после запятой), поэтому сложение, вычитание
+//! и вывод не накапливают погрешностей двоичной арифметики (`101.39999999`
+//! вместо `101.4`). В JSON и двоичной записи цена передаётся числом,
+//! как и прежде; при разборе значение округляется до [`PRICE_DECIMALS`]
+//! знаков.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::price::Price;
+//!
+//! let price: Price = "101.3".parse().unwrap();
+//! let tick: Price = "0.1".parse().unwrap();
+//!
+//! assert_eq!((price + tick).to_string(), "101.4");
+//! assert_eq!(Price::try_from(0.1 + 0.2).unwrap().to_string(), "0.3");
+//! assert_eq!(format!("{:.2}", price), "101.30");
+//! ```
+
+use crate::errors::QuoteError;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::Sum,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// Количество знаков после запятой, хранимых [`Price`].
+pub const PRICE_DECIMALS: u32 = 4;
+
+/// Множитель: единиц [`Price`] в одной денежной единице.
+const SCALE: i64 = 10_i64.pow(PRICE_DECIMALS);
+
+/// Цена с точностью [`PRICE_DECIMALS`] знака после запятой.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(i64);
+
+impl Price {
+    /// Нулевая цена.
+    pub const ZERO: Price = Price(0);
+
+    /// Цена из числа десятитысячных долей.
+    pub const fn from_units(units: i64) -> Self {
+        Self(units)
+    }
+
+    /// Число десятитысячных долей.
+    pub const fn units(self) -> i64 {
+        self.0
+    }
+
+    /// Цена из числа с плавающей точкой, округлённого до
+    /// [`PRICE_DECIMALS`] знаков.
+    ///
+    /// ## Returns
+    ///
+    /// `None`, если значение не конечно или не помещается в [`Price`].
+    pub fn from_f64(value: f64) -> Option<Self> {
+        let units = (value * SCALE as f64).round();
+        (units.is_finite() && units.abs() < i64::MAX as f64).then_some(Self(units as i64))
+    }
+
+    /// Значение числом с плавающей точкой (для статистики и индикаторов).
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    /// Цена больше нуля.
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    /// Сумма; `None` при переполнении.
+    pub fn checked_add(self, other: Price) -> Option<Price> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Разность; `None` при переполнении.
+    pub fn checked_sub(self, other: Price) -> Option<Price> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Цена, умноженная на `factor` и округлённая до [`PRICE_DECIMALS`]
+    /// знаков; `None`, если результат не помещается в [`Price`].
+    pub fn mul_f64(self, factor: f64) -> Option<Price> {
+        Self::from_f64(self.to_f64() * factor)
+    }
+
+    /// Цена, округлённая до `decimals` знаков после запятой (половина —
+    /// от нуля).
+    pub fn round_dp(self, decimals: u32) -> Price {
+        if decimals >= PRICE_DECIMALS {
+            return self;
+        }
+        let step = 10_i64.pow(PRICE_DECIMALS - decimals);
+        let rest = self.0 % step;
+        let rounded = if rest.abs() * 2 >= step {
+            self.0 - rest + step * rest.signum()
+        } else {
+            self.0 - rest
+        };
+        Self(rounded)
+    }
+}
+
+impl TryFrom<f64> for Price {
+    type Error = QuoteError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::from_f64(value)
+            .ok_or_else(|| QuoteError::value_err(format!("некорректная цена: {value}")))
+    }
+}
+
+impl Display for Price {
+    /// Без заданной точности выводит значение без лишних нулей (`150.5`);
+    /// с точностью (`{:.2}`) — округлённое до неё.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.precision().is_some() {
+            return Display::fmt(&self.to_f64(), f);
+        }
+        let abs = self.0.unsigned_abs();
+        let scale = SCALE as u64;
+        let mut digits = (abs / scale).to_string();
+        let frac = abs % scale;
+        if frac > 0 {
+            let frac = format!("{frac:0width$}", width = PRICE_DECIMALS as usize);
+            digits.push('.');
+            digits.push_str(frac.trim_end_matches('0'));
+        }
+        f.pad_integral(self.0 >= 0, "", &digits)
+    }
+}
+
+impl FromStr for Price {
+    type Err = QuoteError;
+
+    /// Разобрать десятичную запись; более длинная дробная часть
+    /// и экспоненциальная запись округляются до [`PRICE_DECIMALS`] знаков.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || QuoteError::value_err(format!("некорректная цена: '{s}'"));
+        if let Some(price) = parse_decimal(s) {
+            return Ok(price);
+        }
+        let value: f64 = s.parse().map_err(|_| invalid())?;
+        Self::from_f64(value).ok_or_else(invalid)
+    }
+}
+
+/// Точный разбор записи вида `-123.45` (не более [`PRICE_DECIMALS`] знаков).
+fn parse_decimal(s: &str) -> Option<Price> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (int_part.is_empty() && frac_part.is_empty())
+        || frac_part.len() > PRICE_DECIMALS as usize
+        || !all_digits(int_part)
+        || !all_digits(frac_part)
+    {
+        return None;
+    }
+
+    let int: i64 = if int_part.is_empty() {
+        0
+    } else {
+        int_part.parse().ok()?
+    };
+    let frac: i64 = if frac_part.is_empty() {
+        0
+    } else {
+        frac_part.parse::<i64>().ok()? * 10_i64.pow(PRICE_DECIMALS - frac_part.len() as u32)
+    };
+    let units = int.checked_mul(SCALE)?.checked_add(frac)?;
+    Some(Price(if negative { -units } else { units }))
+}
+
+impl Add for Price {
+    type Output = Price;
+
+    fn add(self, other: Price) -> Price {
+        Price(self.0 + other.0)
+    }
+}
+
+impl Sub for Price {
+    type Output = Price;
+
+    fn sub(self, other: Price) -> Price {
+        Price(self.0 - other.0)
+    }
+}
+
+impl AddAssign for Price {
+    fn add_assign(&mut self, other: Price) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Price {
+    fn sub_assign(&mut self, other: Price) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Price {
+    type Output = Price;
+
+    fn neg(self) -> Price {
+        Price(-self.0)
+    }
+}
+
+impl Sum for Price {
+    fn sum<I: Iterator<Item = Price>>(iter: I) -> Price {
+        iter.fold(Price::ZERO, Add::add)
+    }
+}
+
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PriceVisitor)
+    }
+}
+
+/// Разбор цены из числа или строки.
+struct PriceVisitor;
+
+impl Visitor<'_> for PriceVisitor {
+    type Value = Price;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("цена числом или строкой")
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Price, E> {
+        Price::try_from(value).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Price, E> {
+        value
+            .checked_mul(SCALE)
+            .map(Price)
+            .ok_or_else(|| E::custom(format!("некорректная цена: {value}")))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Price, E> {
+        i64::try_from(value)
+            .map_err(|_| E::custom(format!("некорректная цена: {value}")))
+            .and_then(|value| self.visit_i64(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Price, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(s: &str) -> Price {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn decimal_text_roundtrips_exactly() {
+        for text in ["0", "150.5", "101.4", "-0.25", "1234567.8912", "0.0001"] {
+            assert_eq!(price(text).to_string(), text);
+        }
+        assert_eq!(price("+7.50").to_string(), "7.5");
+        assert_eq!(price(".5").units(), 5_000);
+        assert_eq!(price("2.").units(), 20_000);
+    }
+
+    #[test]
+    fn long_and_exponent_forms_are_rounded() {
+        assert_eq!(price("101.39999999").to_string(), "101.4");
+        assert_eq!(price("1.5e2").to_string(), "150");
+        for text in ["", ".", "-", "abc", "1.2.3", "NaN", "inf", "1,5"] {
+            assert!(text.parse::<Price>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn arithmetic_has_no_float_artifacts() {
+        let tick = price("0.1");
+        let total: Price = std::iter::repeat_n(tick, 10).sum();
+        assert_eq!(total, price("1"));
+        assert_eq!((price("101.3") + tick).to_string(), "101.4");
+        assert_eq!((price("1") - price("1.25")).to_string(), "-0.25");
+        assert_eq!(price("100").mul_f64(1.1), Some(price("110")));
+        assert!(Price::from_units(i64::MAX).checked_add(tick).is_none());
+    }
+
+    #[test]
+    fn rounding_to_decimals() {
+        assert_eq!(price("1.2345").round_dp(2), price("1.23"));
+        assert_eq!(price("1.235").round_dp(2), price("1.24"));
+        assert_eq!(price("-1.235").round_dp(2), price("-1.24"));
+        assert_eq!(price("9.99").round_dp(0), price("10"));
+        assert_eq!(price("1.2345").round_dp(6), price("1.2345"));
+    }
+
+    #[test]
+    fn formatting_options() {
+        assert_eq!(format!("{:.2}", price("1.5")), "1.50");
+        assert_eq!(format!("{:>8}", price("-1.5")), "    -1.5");
+        assert_eq!(format!("{:<6}|", price("2")), "2     |");
+    }
+
+    #[test]
+    fn conversion_from_f64() {
+        assert_eq!(Price::try_from(0.1 + 0.2).unwrap(), price("0.3"));
+        assert!(Price::try_from(f64::NAN).is_err());
+        assert!(Price::from_f64(1e300).is_none());
+        assert_eq!(price("2801.25").to_f64(), 2801.25);
+    }
+
+    #[test]
+    fn serde_uses_plain_numbers() {
+        assert_eq!(serde_json::to_string(&price("150.5")).unwrap(), "150.5");
+        assert_eq!(
+            serde_json::from_str::<Price>("150.5").unwrap(),
+            price("150.5")
+        );
+        assert_eq!(serde_json::from_str::<Price>("12").unwrap(), price("12"));
+        assert_eq!(
+            serde_json::from_str::<Price>("\"0.3\"").unwrap(),
+            price("0.3")
+        );
+        assert!(serde_json::from_str::<Price>("true").is_err());
+    }
+}
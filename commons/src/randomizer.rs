@@ -1,7 +1,22 @@
 //! Инструменты для генерации случайных данных и последовательностей.
+//!
+//! Свободные функции используют генератор текущего потока и подходят для
+//! разовых вызовов. Для воспроизводимых последовательностей (например,
+//! повтора сессии генератора котировок) используется [`Randomizer`],
+//! создаваемый из зерна.
+//!
+//! Случайные значения типов (например, для тестовых данных) описывает
+//! трейт [`RandomValue`]; для структур и перечислений его реализует
+//! derive-макрос `QuoteRandom`.
 
+use crate::errors::QuoteError;
+use crate::price::Price;
 use rand::distr::uniform::SampleUniform;
 use rand::prelude::*;
+use rand::rngs::ThreadRng;
+use rand_distr::{LogNormal, Normal};
+
+pub use rand::Rng;
 
 /// Выбрать случайный элемент из массива или вектора строк.
 ///
@@ -24,17 +39,34 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<str>,
 {
-    let mut rng = rand::rng();
-    seq.into_iter()
-        .choose(&mut rng)
-        .map(|s| s.as_ref().to_string())
+    Randomizer::thread().choice_str(seq)
+}
+
+/// Выбрать случайный элемент с учётом веса: вероятность выбора
+/// пропорциональна весу элемента.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::random_choice_weighted;
+///
+/// let items = [("SBER", 5.0), ("GAZP", 3.0), ("TGKA", 0.5)];
+/// let result = random_choice_weighted(&items).unwrap();
+///
+/// println!("Traded: {}", result);
+/// ```
+///
+/// ## Returns
+///
+/// Ссылку на выбранный элемент. Если массив пустой, веса отрицательные,
+/// не конечные или все равны нулю, то `None`.
+pub fn random_choice_weighted<T>(items: &[(T, f64)]) -> Option<&T> {
+    Randomizer::thread().choice_weighted(items)
 }
 
 /// Перемешать случайным образом вектор.
-pub fn shuffle_vec<T>(mut vec: Vec<T>) -> Vec<T> {
-    let mut rng = rand::rng();
-    vec.shuffle(&mut rng);
-    vec
+pub fn shuffle_vec<T>(vec: Vec<T>) -> Vec<T> {
+    Randomizer::thread().shuffle_vec(vec)
 }
 
 /// Генерировать случайное число из заданного числового диапазона
@@ -56,8 +88,7 @@ pub fn random<T>(min: T, max: T) -> T
 where
     T: SampleUniform + PartialOrd,
 {
-    let mut rng = rand::rng();
-    rng.random_range(min..=max)
+    Randomizer::thread().random(min, max)
 }
 
 /// Обёртка для функции [`random`]: позволяет генерировать случайное число
@@ -78,13 +109,363 @@ where
 ///
 /// Паникует, если `prob < 0` или `prob > 1`.
 pub fn random_bool(prob: f64) -> bool {
-    let mut rng = rand::rng();
-    rng.random_bool(prob)
+    Randomizer::thread().random_bool(prob)
+}
+
+/// Случайное значение из нормального распределения
+/// (`rand_distr::Normal`).
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::random_normal;
+///
+/// let change = random_normal(0.0, 0.02).unwrap();
+/// println!("Price change: {:.2} %", change * 100.0);
+/// ```
+///
+/// ## Args
+///
+/// - `mean` — математическое ожидание
+/// - `std_dev` — среднеквадратичное отклонение
+///
+/// ## Ошибки
+///
+/// [`QuoteError::ValueError`], если `std_dev < 0` или параметры не конечны.
+pub fn random_normal(mean: f64, std_dev: f64) -> Result<f64, QuoteError> {
+    Randomizer::thread().normal(mean, std_dev)
+}
+
+/// Случайное значение из логнормального распределения
+/// (`rand_distr::LogNormal`): `exp(X)`, где `X` распределено нормально
+/// с параметрами `mu` и `sigma`.
+///
+/// Подходит для величин, которые не бывают отрицательными и имеют длинный
+/// «хвост», например объёмов сделок или множителей цены.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::random_lognormal;
+///
+/// let volume = random_lognormal(8.0, 1.0).unwrap().round() as u32;
+/// println!("Volume: {}", volume);
+/// ```
+///
+/// ## Ошибки
+///
+/// [`QuoteError::ValueError`], если `sigma < 0` или параметры не конечны.
+pub fn random_lognormal(mu: f64, sigma: f64) -> Result<f64, QuoteError> {
+    Randomizer::thread().lognormal(mu, sigma)
+}
+
+/// Случайное значение из экспоненциального распределения со средним `mean`.
+///
+/// Так распределены интервалы между независимыми событиями, происходящими
+/// с постоянной интенсивностью `1 / mean`, например между сделками.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::random_exp;
+///
+/// let pause_ms = random_exp(100.0);
+/// println!("Next trade in {:.0} ms", pause_ms);
+/// ```
+///
+/// Паникует, если `mean < 0` или не конечно.
+pub fn random_exp(mean: f64) -> f64 {
+    Randomizer::thread().exp(mean)
+}
+
+/// Случайное значение из распределения Пуассона с интенсивностью `lambda`:
+/// число независимых событий за интервал, в среднем `lambda`.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::random_poisson;
+///
+/// let trades = random_poisson(4.5);
+/// println!("Trades this second: {}", trades);
+/// ```
+///
+/// Время выборки растёт линейно с `lambda`. Паникует, если `lambda < 0` или
+/// не конечно.
+pub fn random_poisson(lambda: f64) -> u64 {
+    Randomizer::thread().poisson(lambda)
+}
+
+/// Наибольшая интенсивность, выбираемая за один проход в
+/// [`Randomizer::poisson`]: `exp(-POISSON_CHUNK)` ещё представимо в `f64`.
+const POISSON_CHUNK: f64 = 256.0;
+
+/// Проверить параметры нормального распределения: `rand_distr` не проверяет
+/// среднее и допускает отрицательное отклонение.
+fn check_normal_params(mean: f64, std_dev: f64) -> Result<(), String> {
+    if !mean.is_finite() {
+        Err("среднее не конечно".to_string())
+    } else if std_dev < 0.0 {
+        Err("отрицательное отклонение".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Ошибка параметров распределения `name` («нормального» и т.п.).
+fn distribution_err(name: &str, mean: f64, std_dev: f64, reason: String) -> QuoteError {
+    QuoteError::value_err(format!(
+        "некорректные параметры {name} распределения {mean}, {std_dev}: {reason}"
+    ))
+}
+
+/// Источник случайных значений с собственным состоянием.
+///
+/// Методы повторяют свободные функции модуля. Экземпляр, созданный
+/// через [`Randomizer::with_seed`], выдаёт одну и ту же последовательность
+/// при одинаковом зерне.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::Randomizer;
+///
+/// let mut first = Randomizer::with_seed(42);
+/// let mut second = Randomizer::with_seed(42);
+///
+/// assert_eq!(first.random(1, 1000), second.random(1, 1000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Randomizer<R = StdRng> {
+    rng: R,
+}
+
+impl Randomizer {
+    /// Создать экземпляр с зерном из источника энтропии ОС.
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Создать экземпляр с заданным зерном для воспроизводимой
+    /// последовательности.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for Randomizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Randomizer<ThreadRng> {
+    /// Экземпляр поверх генератора текущего потока, для свободных функций.
+    fn thread() -> Self {
+        Self { rng: rand::rng() }
+    }
+}
+
+impl<R: Rng> Randomizer<R> {
+    /// См. [`random_choice_str`].
+    pub fn choice_str<I, S>(&mut self, seq: I) -> Option<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        seq.into_iter()
+            .choose(&mut self.rng)
+            .map(|s| s.as_ref().to_string())
+    }
+
+    /// См. [`random_choice_weighted`].
+    pub fn choice_weighted<'a, T>(&mut self, items: &'a [(T, f64)]) -> Option<&'a T> {
+        if items.iter().any(|(_, weight)| !weight.is_finite()) {
+            return None;
+        }
+        items
+            .choose_weighted(&mut self.rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(item, _)| item)
+    }
+
+    /// См. [`shuffle_vec`].
+    pub fn shuffle_vec<T>(&mut self, mut vec: Vec<T>) -> Vec<T> {
+        vec.shuffle(&mut self.rng);
+        vec
+    }
+
+    /// См. [`random`].
+    pub fn random<T>(&mut self, min: T, max: T) -> T
+    where
+        T: SampleUniform + PartialOrd,
+    {
+        self.rng.random_range(min..=max)
+    }
+
+    /// См. [`random_by_tuple`].
+    pub fn random_by_tuple<T>(&mut self, t: (T, T)) -> T
+    where
+        T: SampleUniform + PartialOrd,
+    {
+        self.random(t.0, t.1)
+    }
+
+    /// См. [`random_bool`].
+    pub fn random_bool(&mut self, prob: f64) -> bool {
+        self.rng.random_bool(prob)
+    }
+
+    /// См. [`random_normal`].
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> Result<f64, QuoteError> {
+        let normal = check_normal_params(mean, std_dev)
+            .and_then(|_| Normal::new(mean, std_dev).map_err(|err| err.to_string()))
+            .map_err(|reason| distribution_err("нормального", mean, std_dev, reason))?;
+        Ok(normal.sample(&mut self.rng))
+    }
+
+    /// См. [`random_lognormal`].
+    pub fn lognormal(&mut self, mu: f64, sigma: f64) -> Result<f64, QuoteError> {
+        let lognormal = check_normal_params(mu, sigma)
+            .and_then(|_| LogNormal::new(mu, sigma).map_err(|err| err.to_string()))
+            .map_err(|reason| distribution_err("логнормального", mu, sigma, reason))?;
+        Ok(lognormal.sample(&mut self.rng))
+    }
+
+    /// См. [`random_exp`].
+    pub fn exp(&mut self, mean: f64) -> f64 {
+        assert!(
+            mean.is_finite() && mean >= 0.0,
+            "некорректное среднее экспоненциального распределения: {mean}"
+        );
+        // Обратная функция распределения; 1 - [0, 1) даёт (0, 1].
+        -mean * (1.0 - self.rng.random::<f64>()).ln()
+    }
+
+    /// См. [`random_poisson`].
+    pub fn poisson(&mut self, lambda: f64) -> u64 {
+        assert!(
+            lambda.is_finite() && lambda >= 0.0,
+            "некорректная интенсивность распределения Пуассона: {lambda}"
+        );
+        // Сумма независимых пуассоновских величин распределена по Пуассону,
+        // поэтому большая интенсивность выбирается частями.
+        let mut remaining = lambda;
+        let mut count = 0;
+        while remaining > 0.0 {
+            let chunk = remaining.min(POISSON_CHUNK);
+            remaining -= chunk;
+            // Метод Кнута: число равномерных множителей до того, как
+            // произведение станет меньше exp(-chunk).
+            let limit = (-chunk).exp();
+            let mut product = self.rng.random::<f64>();
+            while product >= limit {
+                count += 1;
+                product *= self.rng.random::<f64>();
+            }
+        }
+        count
+    }
+
+    /// Случайное значение типа `T` (см. [`RandomValue`]).
+    pub fn value<T: RandomValue>(&mut self) -> T {
+        T::random_value(&mut self.rng)
+    }
+}
+
+/// Сам [`Randomizer`] — генератор: его можно передать туда, где ожидается
+/// [`Rng`] (например, в `random` типов с `QuoteRandom`).
+impl<R: Rng> RngCore for Randomizer<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst)
+    }
+}
+
+/// Тип, значение которого можно получить случайно.
+///
+/// Для структур и перечислений реализуется derive-макросом `QuoteRandom`.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::randomizer::{RandomValue, Randomizer};
+///
+/// let mut rng = Randomizer::with_seed(7);
+/// let ticker: String = rng.value();
+/// assert!((1..=8).contains(&ticker.len()));
+///
+/// let volume = u32::random_value(&mut rng);
+/// println!("volume: {volume}");
+/// ```
+pub trait RandomValue: Sized {
+    /// Случайное значение из генератора `rng`.
+    fn random_value<R: Rng + ?Sized>(rng: &mut R) -> Self;
+}
+
+/// Числа и `bool`: целые — по всему диапазону типа, с плавающей точкой —
+/// из `[0, 1)`.
+macro_rules! random_value_standard {
+    ($($ty:ty),*) => {$(
+        impl RandomValue for $ty {
+            fn random_value<R: Rng + ?Sized>(rng: &mut R) -> Self {
+                rng.random()
+            }
+        }
+    )*};
+}
+
+random_value_standard!(
+    u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64, bool
+);
+
+/// Наибольшая длина случайной строки.
+const RANDOM_STRING_MAX_LEN: usize = 8;
+
+/// Строка из 1–8 заглавных латинских букв (как тикер).
+impl RandomValue for String {
+    fn random_value<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let len = rng.random_range(1..=RANDOM_STRING_MAX_LEN);
+        (0..len)
+            .map(|_| char::from(rng.random_range(b'A'..=b'Z')))
+            .collect()
+    }
+}
+
+/// `None` или значение — с вероятностью 1/2.
+impl<T: RandomValue> RandomValue for Option<T> {
+    fn random_value<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        rng.random_bool(0.5).then(|| T::random_value(rng))
+    }
+}
+
+/// Наибольшая случайная цена, в центах.
+const RANDOM_PRICE_MAX_CENTS: i64 = 1_000_000;
+
+/// Положительная цена с точностью до цента, не больше 10 000.
+impl RandomValue for Price {
+    fn random_value<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let cents = rng.random_range(1..=RANDOM_PRICE_MAX_CENTS);
+        // Цент — 100 десятитысячных долей.
+        Price::from_units(cents * 100)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::ErrorKind;
 
     #[test]
     fn test_random_choice_str_with_valid_input() {
@@ -117,6 +498,151 @@ mod tests {
         assert!(result_vec.is_some());
     }
 
+    #[test]
+    fn test_random_choice_weighted_respects_weights() {
+        let items = [("liquid", 9.0), ("illiquid", 1.0), ("never", 0.0)];
+        let mut liquid = 0;
+        for _ in 0..1000 {
+            match *random_choice_weighted(&items).unwrap() {
+                "liquid" => liquid += 1,
+                "illiquid" => {}
+                other => panic!("выбран элемент с нулевым весом: {other}"),
+            }
+        }
+
+        // Ожидается около 900 из 1000
+        assert!((800..=980).contains(&liquid), "liquid = {liquid}");
+    }
+
+    #[test]
+    fn test_random_choice_weighted_with_invalid_weights() {
+        let empty: [(&str, f64); 0] = [];
+        assert!(random_choice_weighted(&empty).is_none());
+        assert!(random_choice_weighted(&[("a", 0.0), ("b", 0.0)]).is_none());
+        assert!(random_choice_weighted(&[("a", -1.0), ("b", 2.0)]).is_none());
+        assert!(random_choice_weighted(&[("a", f64::NAN), ("b", 2.0)]).is_none());
+    }
+
+    /// Выборочные среднее и дисперсия.
+    fn moments(samples: &[f64]) -> (f64, f64) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, var)
+    }
+
+    #[test]
+    fn test_random_normal_moments() {
+        let samples: Vec<f64> = (0..20_000)
+            .map(|_| random_normal(10.0, 2.0).unwrap())
+            .collect();
+        let (mean, var) = moments(&samples);
+
+        // Стандартная ошибка среднего 2 / sqrt(20000) ≈ 0.014
+        assert!((mean - 10.0).abs() < 0.1, "mean = {mean}");
+        assert!((var - 4.0).abs() < 0.3, "var = {var}");
+    }
+
+    #[test]
+    fn test_random_normal_zero_std() {
+        assert_eq!(random_normal(5.0, 0.0).unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_random_normal_invalid_params() {
+        let err = random_normal(0.0, -1.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Value);
+        assert!(random_normal(f64::NAN, 1.0).is_err());
+        assert!(random_lognormal(0.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_random_lognormal_moments() {
+        let (mu, sigma) = (0.0, 0.5);
+        let samples: Vec<f64> = (0..20_000)
+            .map(|_| random_lognormal(mu, sigma).unwrap())
+            .collect();
+        let (mean, var) = moments(&samples);
+
+        let expected_mean = (mu + sigma * sigma / 2.0_f64).exp();
+        let expected_var = ((sigma * sigma).exp() - 1.0) * (2.0 * mu + sigma * sigma).exp();
+        assert!(samples.iter().all(|x| *x > 0.0));
+        assert!((mean - expected_mean).abs() < 0.03, "mean = {mean}");
+        assert!((var - expected_var).abs() < 0.05, "var = {var}");
+    }
+
+    #[test]
+    fn test_random_exp_moments() {
+        let samples: Vec<f64> = (0..20_000).map(|_| random_exp(2.0)).collect();
+        let (mean, var) = moments(&samples);
+
+        assert!(samples.iter().all(|x| *x >= 0.0));
+        assert!((mean - 2.0).abs() < 0.1, "mean = {mean}");
+        assert!((var - 4.0).abs() < 0.4, "var = {var}");
+        assert_eq!(random_exp(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_random_poisson_moments() {
+        // 300 больше POISSON_CHUNK: проверяется выборка по частям.
+        for lambda in [0.5, 4.0, 300.0] {
+            let samples: Vec<f64> = (0..5_000).map(|_| random_poisson(lambda) as f64).collect();
+            let (mean, var) = moments(&samples);
+
+            // Среднее и дисперсия распределения Пуассона равны lambda.
+            assert!((mean - lambda).abs() < lambda.sqrt() * 0.1, "mean = {mean}");
+            assert!((var - lambda).abs() < lambda * 0.15, "var = {var}");
+        }
+        assert_eq!(random_poisson(0.0), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_poisson_negative_lambda() {
+        random_poisson(-1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_exp_infinite_mean() {
+        random_exp(f64::INFINITY);
+    }
+
+    #[test]
+    fn test_randomizer_same_seed_same_sequence() {
+        let mut first = Randomizer::with_seed(7);
+        let mut second = Randomizer::with_seed(7);
+        let items = [("a", 1.0), ("b", 2.0), ("c", 3.0)];
+
+        for _ in 0..100 {
+            assert_eq!(first.random(0u32, 1000), second.random(0u32, 1000));
+            assert_eq!(
+                first.normal(0.0, 1.0).unwrap(),
+                second.normal(0.0, 1.0).unwrap()
+            );
+            assert_eq!(first.exp(1.0), second.exp(1.0));
+            assert_eq!(first.poisson(3.0), second.poisson(3.0));
+            assert_eq!(
+                first.choice_weighted(&items),
+                second.choice_weighted(&items)
+            );
+        }
+        assert_eq!(
+            first.shuffle_vec((0..20).collect::<Vec<_>>()),
+            second.shuffle_vec((0..20).collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn test_randomizer_different_seeds_differ() {
+        let mut first = Randomizer::with_seed(1);
+        let mut second = Randomizer::with_seed(2);
+
+        let a: Vec<u64> = (0..10).map(|_| first.random(0, u64::MAX)).collect();
+        let b: Vec<u64> = (0..10).map(|_| second.random(0, u64::MAX)).collect();
+        assert_ne!(a, b);
+    }
+
     #[test]
     fn test_shuffle_vec() {
         let original = vec![1, 2, 3, 4, 5];
@@ -180,4 +706,23 @@ mod tests {
     fn test_random_bool_invalid_greater_than_one() {
         random_bool(1.5);
     }
+
+    #[test]
+    fn test_random_value_defaults() {
+        let mut rng = Randomizer::with_seed(11);
+        for _ in 0..100 {
+            let ticker: String = rng.value();
+            assert!((1..=RANDOM_STRING_MAX_LEN).contains(&ticker.len()));
+            assert!(ticker.chars().all(|c| c.is_ascii_uppercase()));
+
+            let price: Price = rng.value();
+            assert!(price.is_positive());
+            assert_eq!(price.round_dp(2), price);
+
+            let unit: f64 = rng.value();
+            assert!((0.0..1.0).contains(&unit));
+        }
+        let options: Vec<Option<u8>> = (0..100).map(|_| rng.value()).collect();
+        assert!(options.iter().any(Option::is_some) && options.iter().any(Option::is_none));
+    }
 }
@@ -0,0 +1,173 @@
+//! Ограничение частоты операций по алгоритму «маркерной корзины».
+//!
+//! Корзина вмещает до `capacity` маркеров и пополняется со скоростью
+//! `refill_per_sec` маркеров в секунду; операция выполняется, только если
+//! в корзине есть маркер. Так допускаются короткие всплески до `capacity`
+//! операций, а средняя частота не превышает скорости пополнения.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::ratelimit::RateLimiter;
+//!
+//! let mut limiter = RateLimiter::new(2, 1.0);
+//!
+//! assert!(limiter.try_acquire());
+//! assert!(limiter.try_acquire());
+//! assert!(!limiter.try_acquire());
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Ограничитель частоты («маркерная корзина»).
+///
+/// Для совместного использования из нескольких потоков экземпляр
+/// помещается в `Mutex`.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Создать ограничитель с полной корзиной.
+    ///
+    /// ## Args
+    /// - `capacity` — наибольшее число маркеров (допустимый всплеск)
+    /// - `refill_per_sec` — пополнение, маркеров в секунду; `0` — корзина
+    ///   не пополняется
+    ///
+    /// ## Паника
+    ///
+    /// Если `refill_per_sec` отрицательное или не является конечным числом.
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        assert!(
+            refill_per_sec.is_finite() && refill_per_sec >= 0.0,
+            "некорректная скорость пополнения: {refill_per_sec}"
+        );
+        Self {
+            capacity: capacity as f64,
+            refill_per_sec,
+            tokens: capacity as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Взять один маркер.
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если маркер получен и операцию можно выполнять.
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_n(1)
+    }
+
+    /// Взять `n` маркеров сразу; если их недостаточно, корзина
+    /// не меняется.
+    pub fn try_acquire_n(&mut self, n: u32) -> bool {
+        self.try_acquire_at(n, Instant::now())
+    }
+
+    /// Число доступных маркеров.
+    pub fn available(&mut self) -> u32 {
+        self.refill(Instant::now());
+        self.tokens as u32
+    }
+
+    /// Время, через которое станут доступны `n` маркеров; `None`, если
+    /// этого не произойдёт (`n` больше ёмкости или корзина
+    /// не пополняется).
+    pub fn wait_time(&mut self, n: u32) -> Option<Duration> {
+        self.wait_time_at(n, Instant::now())
+    }
+
+    fn try_acquire_at(&mut self, n: u32, now: Instant) -> bool {
+        self.refill(now);
+        let n = n as f64;
+        if self.tokens >= n {
+            self.tokens -= n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn wait_time_at(&mut self, n: u32, now: Instant) -> Option<Duration> {
+        self.refill(now);
+        let missing = n as f64 - self.tokens;
+        if missing <= 0.0 {
+            return Some(Duration::ZERO);
+        }
+        if n as f64 > self.capacity || self.refill_per_sec == 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(missing / self.refill_per_sec))
+    }
+
+    /// Пополнить корзину за время, прошедшее с последнего обращения.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_is_limited_by_capacity() {
+        let mut limiter = RateLimiter::new(3, 1.0);
+        let now = limiter.updated;
+
+        assert!((0..3).all(|_| limiter.try_acquire_at(1, now)));
+        assert!(!limiter.try_acquire_at(1, now));
+    }
+
+    #[test]
+    fn tokens_refill_over_time() {
+        let mut limiter = RateLimiter::new(2, 4.0);
+        let start = limiter.updated;
+        assert!(limiter.try_acquire_at(2, start));
+        assert!(!limiter.try_acquire_at(1, start + Duration::from_millis(200)));
+        assert!(limiter.try_acquire_at(1, start + Duration::from_millis(260)));
+
+        // Пополнение не превышает ёмкость.
+        assert!(limiter.try_acquire_at(2, start + Duration::from_secs(60)));
+        assert!(!limiter.try_acquire_at(1, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn failed_acquire_keeps_tokens() {
+        let mut limiter = RateLimiter::new(3, 0.0);
+        let now = limiter.updated;
+
+        assert!(!limiter.try_acquire_at(4, now));
+        assert!(limiter.try_acquire_at(3, now));
+        assert_eq!(limiter.available(), 0);
+    }
+
+    #[test]
+    fn wait_time_estimates_refill() {
+        let mut limiter = RateLimiter::new(2, 2.0);
+        let now = limiter.updated;
+        assert_eq!(limiter.wait_time_at(1, now), Some(Duration::ZERO));
+
+        limiter.try_acquire_at(2, now);
+        assert_eq!(
+            limiter.wait_time_at(1, now),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(limiter.wait_time_at(3, now), None);
+        assert_eq!(RateLimiter::new(0, 0.0).wait_time(1), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_rate_panics() {
+        RateLimiter::new(1, -1.0);
+    }
+}
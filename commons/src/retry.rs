@@ -0,0 +1,197 @@
+//! Повтор операций с экспоненциально нарастающей паузой.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::retry::{RetryPolicy, retry_with_backoff};
+//! use std::time::Duration;
+//!
+//! let policy = RetryPolicy::new(3, Duration::from_millis(1)).with_jitter(0.0);
+//! let result: Result<u32, &str> = retry_with_backoff(&policy, |attempt| {
+//!     if attempt < 3 { Err("занято") } else { Ok(attempt) }
+//! });
+//!
+//! assert_eq!(result, Ok(3));
+//! ```
+
+use rand::Rng;
+use std::{thread, time::Duration};
+
+/// Политика повторов.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Наибольшее число попыток, включая первую.
+    pub max_attempts: u32,
+    /// Пауза перед первым повтором.
+    pub initial_delay: Duration,
+    /// Верхняя граница паузы.
+    pub max_delay: Duration,
+    /// Множитель паузы для каждого следующего повтора.
+    pub multiplier: f64,
+    /// Доля случайного отклонения паузы: `0.2` — ±20 %.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Политика с заданным числом попыток и начальной паузой; остальные
+    /// параметры — по умолчанию.
+    pub fn new(max_attempts: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_delay,
+            ..Self::default()
+        }
+    }
+
+    /// Установить верхнюю границу паузы.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Установить множитель паузы (не меньше `1`).
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Установить долю случайного отклонения паузы (от `0` до `1`).
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Пауза перед повтором с номером `retry` (начиная с `1`) без учёта
+    /// случайного отклонения.
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.powi(retry.saturating_sub(1) as i32);
+        let secs = self.initial_delay.as_secs_f64() * factor;
+        Duration::from_secs_f64(secs.min(self.max_delay.as_secs_f64()))
+    }
+
+    /// Пауза перед повтором с номером `retry` со случайным отклонением.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let base = self.base_delay(retry);
+        if self.jitter == 0.0 {
+            return base;
+        }
+        let factor = rand::rng().random_range(1.0 - self.jitter..=1.0 + self.jitter);
+        base.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+/// Выполнять операцию, пока она не завершится успешно или не будут
+/// исчерпаны попытки.
+///
+/// ## Args
+/// - `policy` — политика повторов
+/// - `op` — операция; получает номер попытки, начиная с `1`
+///
+/// ## Returns
+///
+/// Результат первой успешной попытки или ошибку последней.
+pub fn retry_with_backoff<T, E>(
+    policy: &RetryPolicy,
+    op: impl FnMut(u32) -> Result<T, E>,
+) -> Result<T, E> {
+    retry_with_backoff_if(policy, op, |_, _, _| true)
+}
+
+/// То же, что [`retry_with_backoff`], но перед каждым повтором вызывается
+/// `on_error` с ошибкой, номером повтора и паузой до него; если он вернёт
+/// `false`, повторов больше не будет и вернётся эта ошибка.
+pub fn retry_with_backoff_if<T, E>(
+    policy: &RetryPolicy,
+    mut op: impl FnMut(u32) -> Result<T, E>,
+    mut on_error: impl FnMut(&E, u32, Duration) -> bool,
+) -> Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        match op(attempt) {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= policy.max_attempts => return Err(err),
+            Err(err) => {
+                let delay = policy.delay(attempt);
+                if !on_error(&err, attempt, delay) {
+                    return Err(err);
+                }
+                thread::sleep(delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts, Duration::from_millis(1)).with_jitter(0.0)
+    }
+
+    #[test]
+    fn delays_grow_exponentially_up_to_limit() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500))
+            .with_jitter(0.0);
+
+        let delays: Vec<_> = (1..=5).map(|n| policy.delay(n).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1000)).with_jitter(0.2);
+        for _ in 0..100 {
+            let ms = policy.delay(1).as_millis();
+            assert!((800..=1200).contains(&ms), "{ms}");
+        }
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut calls = 0;
+        let result: Result<(), u32> = retry_with_backoff(&fast_policy(3), |attempt| {
+            calls += 1;
+            Err(attempt)
+        });
+
+        assert_eq!(result, Err(3));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn on_error_can_stop_retries() {
+        let mut seen = Vec::new();
+        let result: Result<(), &str> = retry_with_backoff_if(
+            &fast_policy(5),
+            |attempt| {
+                Err(if attempt < 2 {
+                    "временная"
+                } else {
+                    "фатальная"
+                })
+            },
+            |err, retry, _| {
+                seen.push(retry);
+                *err == "временная"
+            },
+        );
+
+        assert_eq!(result, Err("фатальная"));
+        assert_eq!(seen, vec![1, 2]);
+    }
+}
@@ -0,0 +1,340 @@
+//! Статистика по скользящему окну.
+//!
+//! [`RollingWindow`] хранит последние значения — заданное количество
+//! ([`WindowSize::Count`]) или за заданное время ([`WindowSize::Time`]) —
+//! и считает по ним среднее, минимум, максимум, стандартное отклонение
+//! и процентили. [`Ema`] — экспоненциальное скользящее среднее, которому
+//! окно не нужно.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::rolling::RollingWindow;
+//!
+//! let mut window = RollingWindow::with_count(3);
+//! for value in [1.0, 2.0, 3.0, 7.0] {
+//!     window.push(value);
+//! }
+//!
+//! assert_eq!(window.len(), 3);
+//! assert_eq!(window.mean(), Some(4.0));
+//! assert_eq!(window.min(), Some(2.0));
+//! assert_eq!(window.percentile(50.0), Some(3.0));
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
+};
+
+/// Размер скользящего окна.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSize {
+    /// Последние `n` значений.
+    Count(usize),
+    /// Значения, добавленные не раньше, чем заданное время назад.
+    Time(Duration),
+}
+
+/// Сводка по значениям окна.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollingStats {
+    /// Количество значений.
+    pub count: usize,
+    /// Среднее.
+    pub mean: f64,
+    /// Минимум.
+    pub min: f64,
+    /// Максимум.
+    pub max: f64,
+    /// Стандартное отклонение (по генеральной совокупности).
+    pub stddev: f64,
+}
+
+impl Display for RollingStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} среднее={:.2} мин={:.2} макс={:.2} σ={:.2}",
+            self.count, self.mean, self.min, self.max, self.stddev
+        )
+    }
+}
+
+/// Скользящее окно значений.
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    size: WindowSize,
+    /// Значения с моментом добавления, от старых к новым.
+    samples: VecDeque<(Instant, f64)>,
+    /// Сумма значений окна.
+    sum: f64,
+}
+
+impl RollingWindow {
+    /// Окно размера `size`.
+    ///
+    /// ## Паника
+    ///
+    /// Окно на `0` значений или нулевое время не имеет смысла и вызывает
+    /// панику.
+    pub fn new(size: WindowSize) -> Self {
+        assert!(
+            !matches!(size, WindowSize::Count(0)) && size != WindowSize::Time(Duration::ZERO),
+            "пустое скользящее окно"
+        );
+        Self {
+            size,
+            samples: VecDeque::new(),
+            sum: 0.0,
+        }
+    }
+
+    /// Окно из последних `count` значений.
+    pub fn with_count(count: usize) -> Self {
+        Self::new(WindowSize::Count(count))
+    }
+
+    /// Окно значений за последнее время `period`.
+    pub fn with_period(period: Duration) -> Self {
+        Self::new(WindowSize::Time(period))
+    }
+
+    /// Размер окна.
+    pub fn size(&self) -> WindowSize {
+        self.size
+    }
+
+    /// Добавить значение с текущим моментом времени.
+    pub fn push(&mut self, value: f64) {
+        self.push_at(value, Instant::now());
+    }
+
+    /// Добавить значение, полученное в момент `at`, и вытеснить
+    /// устаревшие.
+    pub fn push_at(&mut self, value: f64, at: Instant) {
+        self.samples.push_back((at, value));
+        self.sum += value;
+        match self.size {
+            WindowSize::Count(count) => {
+                while self.samples.len() > count {
+                    self.pop_front();
+                }
+            }
+            WindowSize::Time(_) => self.expire(at),
+        }
+    }
+
+    /// Вытеснить значения, вышедшие из окна по времени к моменту `now`.
+    ///
+    /// Для окна по количеству ничего не делает. Вызывается перед чтением
+    /// статистики, если новые значения давно не поступали.
+    pub fn expire(&mut self, now: Instant) {
+        let WindowSize::Time(period) = self.size else {
+            return;
+        };
+        while let Some(&(at, _)) = self.samples.front() {
+            if now.saturating_duration_since(at) <= period {
+                break;
+            }
+            self.pop_front();
+        }
+    }
+
+    fn pop_front(&mut self) {
+        if let Some((_, value)) = self.samples.pop_front() {
+            self.sum -= value;
+        }
+        if self.samples.is_empty() {
+            // Сбросить накопленную погрешность суммы.
+            self.sum = 0.0;
+        }
+    }
+
+    /// Удалить все значения.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.sum = 0.0;
+    }
+
+    /// Количество значений в окне.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Окно пусто.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Окно по количеству заполнено (для окна по времени — всегда `false`).
+    pub fn is_full(&self) -> bool {
+        matches!(self.size, WindowSize::Count(count) if self.samples.len() == count)
+    }
+
+    /// Значения окна от старых к новым.
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.samples.iter().map(|&(_, value)| value)
+    }
+
+    /// Сумма значений.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Среднее; `None` для пустого окна.
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.sum / self.len() as f64)
+    }
+
+    /// Минимум; `None` для пустого окна.
+    pub fn min(&self) -> Option<f64> {
+        self.values().reduce(f64::min)
+    }
+
+    /// Максимум; `None` для пустого окна.
+    pub fn max(&self) -> Option<f64> {
+        self.values().reduce(f64::max)
+    }
+
+    /// Стандартное отклонение по генеральной совокупности; `None` для
+    /// пустого окна.
+    pub fn stddev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance = self
+            .values()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / self.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    /// Процентиль `pct` (от 0 до 100) методом ближайшего ранга; `None` для
+    /// пустого окна.
+    pub fn percentile(&self, pct: f64) -> Option<f64> {
+        let mut sorted: Vec<f64> = self.values().collect();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let rank = ((pct.clamp(0.0, 100.0) / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.clamp(1, sorted.len().max(1)) - 1).copied()
+    }
+
+    /// Сводка по окну; `None` для пустого окна.
+    pub fn stats(&self) -> Option<RollingStats> {
+        Some(RollingStats {
+            count: self.len(),
+            mean: self.mean()?,
+            min: self.min()?,
+            max: self.max()?,
+            stddev: self.stddev()?,
+        })
+    }
+}
+
+/// Экспоненциальное скользящее среднее с периодом `period`
+/// (коэффициент сглаживания `2 / (period + 1)`).
+///
+/// Первое значение становится начальным средним.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Среднее с периодом `period` (`0` считается за `1`).
+    pub fn new(period: usize) -> Self {
+        Self {
+            alpha: 2.0 / (period.max(1) as f64 + 1.0),
+            value: None,
+        }
+    }
+
+    /// Учесть значение и вернуть новое среднее.
+    pub fn push(&mut self, value: f64) -> f64 {
+        let ema = match self.value {
+            Some(prev) => self.alpha * value + (1.0 - self.alpha) * prev,
+            None => value,
+        };
+        self.value = Some(ema);
+        ema
+    }
+
+    /// Текущее среднее; `None`, пока значений не было.
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_window_keeps_last_values() {
+        let mut window = RollingWindow::with_count(3);
+        assert_eq!(window.stats(), None);
+
+        for value in [1.0, 2.0, 3.0] {
+            window.push(value);
+        }
+        assert!(window.is_full());
+        assert_eq!(window.mean(), Some(2.0));
+
+        window.push(7.0);
+        assert_eq!(window.values().collect::<Vec<_>>(), [2.0, 3.0, 7.0]);
+        assert_eq!(window.sum(), 12.0);
+        assert_eq!((window.min(), window.max()), (Some(2.0), Some(7.0)));
+    }
+
+    #[test]
+    fn time_window_expires_old_values() {
+        let start = Instant::now();
+        let mut window = RollingWindow::with_period(Duration::from_secs(10));
+        window.push_at(1.0, start);
+        window.push_at(2.0, start + Duration::from_secs(5));
+        window.push_at(3.0, start + Duration::from_secs(11));
+        assert_eq!(window.values().collect::<Vec<_>>(), [2.0, 3.0]);
+        assert!(!window.is_full());
+
+        window.expire(start + Duration::from_secs(30));
+        assert!(window.is_empty());
+        assert_eq!(window.sum(), 0.0);
+        assert_eq!(window.mean(), None);
+    }
+
+    #[test]
+    fn stddev_and_percentiles() {
+        let mut window = RollingWindow::with_count(100);
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            window.push(value);
+        }
+        let stats = window.stats().unwrap();
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.stddev, 2.0);
+        assert_eq!((stats.min, stats.max), (2.0, 9.0));
+
+        assert_eq!(window.percentile(50.0), Some(4.0));
+        assert_eq!(window.percentile(100.0), Some(9.0));
+        assert_eq!(window.percentile(0.0), Some(2.0));
+
+        window.clear();
+        assert_eq!(window.percentile(50.0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "пустое скользящее окно")]
+    fn empty_window_panics() {
+        RollingWindow::with_count(0);
+    }
+
+    #[test]
+    fn ema_is_seeded_by_first_value() {
+        let mut ema = Ema::new(3);
+        assert_eq!(ema.value(), None);
+        assert_eq!(ema.push(10.0), 10.0);
+        assert_eq!(ema.push(20.0), 15.0);
+        assert_eq!(ema.value(), Some(15.0));
+    }
+}
@@ -0,0 +1,179 @@
+//! JSON Schema моделей для потребителей JSON-представления на других
+//! языках.
+//!
+//! Схему значения типа описывает трейт [`JsonSchema`]; для структур
+//! и перечислений без полей его реализует derive-макрос `QuoteSchema`,
+//! учитывающий атрибуты `serde` (`rename`, `default`, `skip`) и
+//! документацию полей. Схема вложенных типов встраивается на месте,
+//! без `$defs`.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::models::StockQuote;
+//! use commons::schema::root_schema;
+//!
+//! let schema = root_schema::<StockQuote>();
+//! assert_eq!(schema["type"], "object");
+//! assert!(schema["required"].as_array().unwrap().contains(&"ticker".into()));
+//! ```
+
+use crate::price::Price;
+use serde_json::{Map, json};
+
+pub use serde_json::Value;
+
+/// Версия JSON Schema, указываемая в корневой схеме.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// JSON Schema значения типа.
+pub trait JsonSchema {
+    /// Схема значения.
+    fn json_schema() -> Value;
+}
+
+/// Корневая схема типа `T`: схема значения с указанием версии
+/// JSON Schema (`$schema`).
+pub fn root_schema<T: JsonSchema>() -> Value {
+    let mut schema = T::json_schema();
+    if let Value::Object(map) = &mut schema {
+        map.insert("$schema".into(), SCHEMA_DIALECT.into());
+    }
+    schema
+}
+
+/// Свойство объекта в схеме структуры.
+pub struct Property {
+    /// Имя свойства в JSON.
+    pub name: &'static str,
+    /// Описание свойства; пустое не записывается.
+    pub description: &'static str,
+    /// Свойство обязательно в JSON.
+    pub required: bool,
+    /// Схема значения свойства.
+    pub schema: Value,
+}
+
+/// Схема объекта с заголовком `title`, описанием `description` (пустое
+/// не записывается) и свойствами `properties` в порядке объявления.
+pub fn object(title: &str, description: &str, properties: Vec<Property>) -> Value {
+    let mut schema = titled(title, description);
+    let mut props = Map::new();
+    let mut required = Vec::new();
+    for property in properties {
+        let mut value = property.schema;
+        if let Value::Object(map) = &mut value
+            && !property.description.is_empty()
+        {
+            map.insert("description".into(), property.description.into());
+        }
+        props.insert(property.name.into(), value);
+        if property.required {
+            required.push(Value::from(property.name));
+        }
+    }
+    schema.insert("type".into(), "object".into());
+    schema.insert("properties".into(), Value::Object(props));
+    schema.insert("required".into(), Value::Array(required));
+    Value::Object(schema)
+}
+
+/// Схема строки из набора `values` с заголовком `title` и описанием
+/// `description` (пустое не записывается).
+pub fn string_enum(title: &str, description: &str, values: &[&str]) -> Value {
+    let mut schema = titled(title, description);
+    schema.insert("type".into(), "string".into());
+    schema.insert("enum".into(), values.into());
+    Value::Object(schema)
+}
+
+/// Начало схемы: заголовок и непустое описание.
+fn titled(title: &str, description: &str) -> Map<String, Value> {
+    let mut schema = Map::new();
+    schema.insert("title".into(), title.into());
+    if !description.is_empty() {
+        schema.insert("description".into(), description.into());
+    }
+    schema
+}
+
+/// Реализации [`JsonSchema`] с постоянной схемой для типов `$t`.
+macro_rules! fixed_schema {
+    ($schema:tt => $($t:ty),*) => {
+        $(impl JsonSchema for $t {
+            fn json_schema() -> Value {
+                json!($schema)
+            }
+        })*
+    };
+}
+
+fixed_schema!({ "type": "integer", "minimum": 0 } => u8, u16, u32, u64, usize);
+fixed_schema!({ "type": "integer" } => i8, i16, i32, i64, isize);
+fixed_schema!({ "type": "number" } => f32, f64);
+fixed_schema!({ "type": "boolean" } => bool);
+fixed_schema!({ "type": "string" } => String);
+
+/// Значение или `null`.
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+/// Цена записывается числом (см. [`Price`]).
+impl JsonSchema for Price {
+    fn json_schema() -> Value {
+        json!({ "type": "number" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitive_schemas() {
+        assert_eq!(
+            u32::json_schema(),
+            json!({ "type": "integer", "minimum": 0 })
+        );
+        assert_eq!(i64::json_schema(), json!({ "type": "integer" }));
+        assert_eq!(
+            Option::<String>::json_schema(),
+            json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] })
+        );
+    }
+
+    #[test]
+    fn object_collects_required_properties() {
+        let schema = object(
+            "Fill",
+            "",
+            vec![
+                Property {
+                    name: "ticker",
+                    description: "Тикер.",
+                    required: true,
+                    schema: String::json_schema(),
+                },
+                Property {
+                    name: "note",
+                    description: "",
+                    required: false,
+                    schema: Option::<String>::json_schema(),
+                },
+            ],
+        );
+        assert_eq!(schema["title"], "Fill");
+        assert!(schema.get("description").is_none());
+        assert_eq!(schema["properties"]["ticker"]["description"], "Тикер.");
+        assert_eq!(schema["required"], json!(["ticker"]));
+    }
+}
@@ -0,0 +1,150 @@
+//! Номера последовательности котировок.
+//!
+//! Номер `0` означает «без номера» (записи старого формата), поэтому
+//! [`SeqGen`] выдаёт номера с `1` и при переполнении `u64` пропускает `0`.
+//! Сравнение номеров ([`seq_after`], [`seq_gap`]) учитывает переполнение:
+//! номер, выданный сразу после `u64::MAX`, считается следующим, а не
+//! меньшим.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::seq::{SeqGen, seq_gap};
+//!
+//! let seq = SeqGen::new();
+//! let first = seq.next_seq();
+//! let second = seq.next_seq();
+//! assert_eq!((first, second), (1, 2));
+//! assert_eq!(seq_gap(second, 5), 2);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Генератор номеров последовательности.
+///
+/// Потокобезопасен; экземпляр заводится на каждый независимый поток
+/// котировок (генератор, трансляция одному клиенту).
+#[derive(Debug)]
+pub struct SeqGen {
+    next: AtomicU64,
+}
+
+impl Default for SeqGen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeqGen {
+    /// Создать генератор, выдающий номера с `1`.
+    pub const fn new() -> Self {
+        Self::starting_at(1)
+    }
+
+    /// Создать генератор, выдающий номера с `first` (`0` заменяется на `1`).
+    pub const fn starting_at(first: u64) -> Self {
+        Self {
+            next: AtomicU64::new(if first == 0 { 1 } else { first }),
+        }
+    }
+
+    /// Выдать очередной номер.
+    pub fn next_seq(&self) -> u64 {
+        self.next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |seq| {
+                Some(successor(seq))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Номер, который будет выдан следующим.
+    pub fn peek(&self) -> u64 {
+        self.next.load(Ordering::Relaxed)
+    }
+}
+
+/// Номер, следующий за `seq`, с пропуском `0`.
+fn successor(seq: u64) -> u64 {
+    seq.checked_add(1).unwrap_or(1)
+}
+
+/// Следует ли номер `seq` за номером `last`.
+///
+/// Номера сравниваются по кратчайшему расстоянию на «кольце» `u64`, поэтому
+/// после переполнения порядок сохраняется.
+pub fn seq_after(seq: u64, last: u64) -> bool {
+    (seq.wrapping_sub(last) as i64) > 0
+}
+
+/// Сколько номеров пропущено между `last` и `seq`.
+///
+/// ## Returns
+///
+/// `0`, если `seq` следует сразу за `last`, повторяет его или опоздал
+/// (не следует за `last`).
+pub fn seq_gap(last: u64, seq: u64) -> u64 {
+    if !seq_after(seq, last) {
+        return 0;
+    }
+    let distance = seq.wrapping_sub(last) - 1;
+    // Номер 0 генератор не выдаёт, поэтому при переходе через него
+    // пропуском не считается.
+    if seq < last {
+        distance.saturating_sub(1)
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    #[test]
+    fn numbers_start_at_one_and_grow() {
+        let seq = SeqGen::new();
+        assert_eq!(seq.peek(), 1);
+        assert_eq!((seq.next_seq(), seq.next_seq()), (1, 2));
+        assert_eq!(SeqGen::starting_at(0).next_seq(), 1);
+    }
+
+    #[test]
+    fn wrap_skips_zero() {
+        let seq = SeqGen::starting_at(u64::MAX);
+        assert_eq!(seq.next_seq(), u64::MAX);
+        assert_eq!(seq.next_seq(), 1);
+        assert_eq!(seq.next_seq(), 2);
+    }
+
+    #[test]
+    fn gaps_and_order_survive_wrap() {
+        assert_eq!(seq_gap(1, 2), 0);
+        assert_eq!(seq_gap(2, 6), 3);
+        assert_eq!(seq_gap(6, 6), 0);
+        assert_eq!(seq_gap(6, 3), 0);
+        assert_eq!(seq_gap(u64::MAX, 1), 0);
+        assert_eq!(seq_gap(u64::MAX - 1, 2), 2);
+
+        assert!(seq_after(1, u64::MAX));
+        assert!(!seq_after(u64::MAX, 1));
+        assert!(!seq_after(5, 5));
+    }
+
+    #[test]
+    fn concurrent_numbers_are_unique() {
+        let seq = Arc::new(SeqGen::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let seq = Arc::clone(&seq);
+                thread::spawn(move || (0..1000).map(|_| seq.next_seq()).collect::<Vec<_>>())
+            })
+            .collect();
+        let all: HashSet<u64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        assert_eq!(all.len(), 4000);
+        assert_eq!(seq.peek(), 4001);
+    }
+}
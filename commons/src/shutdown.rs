@@ -0,0 +1,258 @@
+//! Согласованное завершение работы потоков приложения.
+//!
+//! [`ShutdownToken`] — признак отмены, который клонируется в потоки.
+//! Отмена токена отменяет и все его дочерние области ([`ShutdownToken::child`]),
+//! но не затрагивает родителя. Обработчик Ctrl-C и сигналов завершения
+//! регистрируется один раз на процесс ([`ShutdownToken::install`]).
+//!
+//! С feature `async` отмены можно дождаться в задаче tokio
+//! ([`ShutdownToken::cancelled`]), например в ветке `select!`.
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::shutdown::ShutdownToken;
+//! use std::time::Duration;
+//!
+//! let root = ShutdownToken::new();
+//! let worker = root.child();
+//!
+//! worker.cancel();
+//! assert!(!root.is_cancelled());
+//!
+//! root.cancel();
+//! assert!(root.cancelled_wait(Duration::from_millis(10)));
+//! ```
+
+use crate::errors::QuoteError;
+use std::{
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Токен, зарегистрированный обработчиком сигналов.
+static INSTALLED: Mutex<Option<ShutdownToken>> = Mutex::new(None);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: Arc<AtomicBool>,
+    children: Mutex<Vec<Weak<Inner>>>,
+    condvar: Condvar,
+    #[cfg(feature = "async")]
+    notify: tokio::sync::Notify,
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, Vec<Weak<Inner>>> {
+        self.children.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn cancel(&self) {
+        let children = {
+            let mut children = self.lock();
+            if self.cancelled.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            self.condvar.notify_all();
+            #[cfg(feature = "async")]
+            self.notify.notify_waiters();
+            std::mem::take(&mut *children)
+        };
+        children
+            .iter()
+            .filter_map(Weak::upgrade)
+            .for_each(|child| child.cancel());
+    }
+}
+
+/// Признак завершения работы, разделяемый потоками.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownToken {
+    inner: Arc<Inner>,
+}
+
+impl ShutdownToken {
+    /// Новый неотменённый токен.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Токен, отменяемый по Ctrl-C и сигналам завершения (SIGINT, SIGTERM
+    /// и SIGHUP; `ctrlc` с feature `termination`).
+    ///
+    /// Обработчик сигналов регистрируется при первом вызове; последующие
+    /// вызовы возвращают тот же токен.
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::RunTimeError`], если обработчик сигналов уже
+    /// установлен в обход этого метода.
+    pub fn install() -> Result<Self, QuoteError> {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(token) = installed.as_ref() {
+            return Ok(token.clone());
+        }
+
+        let token = Self::new();
+        let handler = token.clone();
+        ctrlc::set_handler(move || handler.cancel()).map_err(|err| {
+            QuoteError::runtime_err(format!("ошибка установки обработчика Ctrl-C: {err}"))
+        })?;
+        *installed = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Дочерняя область: отменяется вместе с текущим токеном, но её
+    /// собственная отмена на текущий токен не влияет.
+    pub fn child(&self) -> Self {
+        let child = Self::new();
+        {
+            let mut children = self.inner.lock();
+            if !self.is_cancelled() {
+                children.retain(|weak| weak.strong_count() > 0);
+                children.push(Arc::downgrade(&child.inner));
+                return child;
+            }
+        }
+        child.cancel();
+        child
+    }
+
+    /// Отменить токен и все дочерние области.
+    pub fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    /// Токен отменён.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Ждать отмены не дольше `timeout`.
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если токен отменён; `false` — по истечении времени ожидания.
+    pub fn cancelled_wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.inner.lock();
+        while !self.is_cancelled() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            guard = self
+                .inner
+                .condvar
+                .wait_timeout(guard, left)
+                .unwrap_or_else(|err| err.into_inner())
+                .0;
+        }
+        true
+    }
+
+    /// Дождаться отмены в асинхронной задаче (feature `async`).
+    ///
+    /// Отмена безопасна: future можно использовать в `select!`.
+    #[cfg(feature = "async")]
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            let mut notified = std::pin::pin!(notified);
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Флаг отмены для API, принимающих `Arc<AtomicBool>`.
+    ///
+    /// Флаг только для чтения: запись в него не оповещает ожидающих
+    /// и не отменяет дочерние области.
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.inner.cancelled.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn cancel_propagates_to_children_only() {
+        let root = ShutdownToken::new();
+        let child = root.child();
+        let grandchild = child.child();
+        let sibling = root.child();
+
+        child.cancel();
+        assert!(child.is_cancelled() && grandchild.is_cancelled());
+        assert!(!root.is_cancelled() && !sibling.is_cancelled());
+
+        root.cancel();
+        assert!(sibling.is_cancelled());
+        assert!(root.child().is_cancelled());
+    }
+
+    #[test]
+    fn cancelled_wait_wakes_up_on_cancel() {
+        let token = ShutdownToken::new();
+        assert!(!token.cancelled_wait(Duration::from_millis(10)));
+
+        let waiter = token.child();
+        let handle = thread::spawn(move || waiter.cancelled_wait(Duration::from_secs(10)));
+        thread::sleep(Duration::from_millis(20));
+        let started = Instant::now();
+        token.cancel();
+
+        assert!(handle.join().unwrap());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn cancelled_future_completes_on_parent_cancel() {
+        let root = ShutdownToken::new();
+        let child = root.child();
+        let waiter = tokio::spawn(async move { child.cancelled().await });
+
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        root.cancel();
+
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        root.cancelled().await;
+    }
+
+    #[test]
+    fn flag_follows_token() {
+        let token = ShutdownToken::new();
+        let flag = token.flag();
+        assert!(!flag.load(Ordering::SeqCst));
+
+        token.clone().cancel();
+        assert!(flag.load(Ordering::SeqCst));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sigterm_cancels_installed_token() {
+        let token = ShutdownToken::install().unwrap();
+        let status = std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+        assert!(token.cancelled_wait(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,246 @@
+//! Генераторы моделей для тестов на основе свойств (`proptest`) и
+//! построитель котировок для модульных тестов ([`QuoteBuilder`]).
+//!
+//! Модуль доступен при включённой функции `testing`: крейты подключают её
+//! в `dev-dependencies` и используют одни и те же генераторы.
+//! Сгенерированные значения проходят проверку [`Validate`](crate::traits::Validate)
+//! и умещаются в двоичный формат [`codec`](crate::codec).
+//!
+//! ## Пример
+//!
+//! ```
+//! use commons::models::StockQuote;
+//! use commons::traits::Validate;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(quote: StockQuote)| {
+//!     prop_assert!(quote.validate().is_ok());
+//! });
+//! ```
+
+use crate::config::MIN_QUOTE_TIMESTAMP_MS;
+use crate::models::{Candle, StockQuote, Transaction};
+use crate::price::Price;
+use proptest::prelude::*;
+
+/// Наибольшая временная метка генераторов (ноябрь 2023 г.), заведомо
+/// не опережающая местные часы.
+const MAX_TIMESTAMP_MS: u64 = 1_700_000_000_000;
+
+/// Тикер: от 1 до 8 заглавных латинских букв.
+pub fn ticker() -> impl Strategy<Value = String> {
+    "[A-Z]{1,8}"
+}
+
+/// Положительная цена с точностью до цента.
+pub fn price() -> impl Strategy<Value = Price> {
+    cents().prop_map(|cents| Price::from_units(cents as i64 * 100))
+}
+
+/// Цена в центах.
+fn cents() -> impl Strategy<Value = u64> {
+    1u64..=100_000_000
+}
+
+/// Цена в денежных единицах по числу центов.
+fn from_cents(cents: f64) -> f64 {
+    cents / 100.0
+}
+
+/// Допустимая временная метка, миллисекунды.
+pub fn timestamp() -> impl Strategy<Value = u64> {
+    MIN_QUOTE_TIMESTAMP_MS..=MAX_TIMESTAMP_MS
+}
+
+/// Вид транзакции.
+pub fn transaction() -> impl Strategy<Value = Transaction> {
+    proptest::sample::select(Transaction::ALL.to_vec())
+}
+
+/// Котировка; `bid` и `ask`, если заданы, не нарушают `bid <= price <= ask`.
+///
+/// Все цены кратны центу, поэтому без потерь переживают текстовые форматы
+/// (JSON, CSV, `Display`).
+pub fn stock_quote() -> impl Strategy<Value = StockQuote> {
+    (
+        ticker(),
+        cents(),
+        1u32..=10_000_000,
+        timestamp(),
+        transaction(),
+        any::<u64>(),
+        proptest::option::of(0.0..0.5f64),
+        proptest::option::of(0.0..0.5f64),
+        proptest::option::of("[A-Z]{2,8}"),
+        proptest::option::of("[A-Z]{3}"),
+    )
+        .prop_map(
+            |(ticker, cents, volume, timestamp, transaction, seq, bid, ask, exchange, currency)| {
+                let price = Price::from_units(cents as i64 * 100);
+                let cents = cents as f64;
+                StockQuote {
+                    ticker,
+                    price,
+                    volume,
+                    timestamp,
+                    transaction,
+                    seq,
+                    bid: bid.map(|spread| from_cents((cents * (1.0 - spread)).floor().max(1.0))),
+                    ask: ask.map(|spread| from_cents((cents * (1.0 + spread)).ceil())),
+                    exchange,
+                    currency,
+                }
+            },
+        )
+}
+
+/// Свеча по последовательности от 1 до 16 цен.
+pub fn candle() -> impl Strategy<Value = Candle> {
+    (
+        ticker(),
+        proptest::collection::vec(price(), 1..=16),
+        any::<u32>(),
+        timestamp(),
+        1u64..=86_400_000,
+    )
+        .prop_map(|(ticker, prices, volume, start_ts, interval)| Candle {
+            ticker,
+            open: prices[0].to_f64(),
+            high: prices.iter().max().map_or(0.0, |p| p.to_f64()),
+            low: prices.iter().min().map_or(0.0, |p| p.to_f64()),
+            close: prices[prices.len() - 1].to_f64(),
+            volume: volume as u64,
+            start_ts: start_ts - start_ts % interval,
+            interval,
+        })
+}
+
+/// Котировка покупки `ticker` по цене `price` с объёмом и временной меткой `1`.
+///
+/// Сокращение для `QuoteBuilder::new(ticker).price(price).build()`.
+pub fn quote(ticker: &str, price: f64) -> StockQuote {
+    QuoteBuilder::new(ticker).price(price).build()
+}
+
+/// Построитель котировок для модульных тестов.
+///
+/// По умолчанию — покупка по цене `1` с объёмом `1` и временной меткой `1`;
+/// остальные поля пусты.
+///
+/// ```
+/// use commons::testing::QuoteBuilder;
+///
+/// let quote = QuoteBuilder::new("AAPL").price(10.5).volume(100).build();
+/// assert_eq!(quote.volume, 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuoteBuilder {
+    quote: StockQuote,
+}
+
+impl QuoteBuilder {
+    /// Начать котировку тикера `ticker`.
+    pub fn new(ticker: &str) -> Self {
+        Self {
+            quote: StockQuote {
+                ticker: ticker.to_string(),
+                volume: 1,
+                timestamp: 1,
+                transaction: Transaction::Buy,
+                ..Default::default()
+            },
+        }
+        .price(1.0)
+    }
+
+    /// Цена; паникует, если `price` непредставима в [`Price`].
+    pub fn price(mut self, price: f64) -> Self {
+        self.quote.price = Price::try_from(price).expect("некорректная цена в тесте");
+        self
+    }
+
+    /// Объём сделки.
+    pub fn volume(mut self, volume: u32) -> Self {
+        self.quote.volume = volume;
+        self
+    }
+
+    /// Временная метка, миллисекунды.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.quote.timestamp = timestamp;
+        self
+    }
+
+    /// Сквозной номер котировки.
+    pub fn seq(mut self, seq: u64) -> Self {
+        self.quote.seq = seq;
+        self
+    }
+
+    /// Вид транзакции.
+    pub fn transaction(mut self, transaction: Transaction) -> Self {
+        self.quote.transaction = transaction;
+        self
+    }
+
+    /// Готовая котировка.
+    pub fn build(self) -> StockQuote {
+        self.quote
+    }
+}
+
+impl Arbitrary for Transaction {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        transaction().boxed()
+    }
+}
+
+impl Arbitrary for StockQuote {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        stock_quote().boxed()
+    }
+}
+
+impl Arbitrary for Candle {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        candle().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Validate;
+
+    #[test]
+    fn builder_fills_required_fields() {
+        let built = QuoteBuilder::new("AAPL").price(10.5).timestamp(7).build();
+        assert_eq!(built.ticker, "AAPL");
+        assert_eq!(built.price.to_f64(), 10.5);
+        assert_eq!((built.volume, built.timestamp), (1, 7));
+        assert_eq!(QuoteBuilder::new("X").build().price, quote("X", 1.0).price);
+    }
+
+    proptest! {
+        #[test]
+        fn quotes_are_valid(quote: StockQuote) {
+            prop_assert!(quote.validate().is_ok());
+        }
+
+        #[test]
+        fn candles_are_consistent(candle: Candle) {
+            prop_assert!(candle.low <= candle.open && candle.open <= candle.high);
+            prop_assert!(candle.low <= candle.close && candle.close <= candle.high);
+            prop_assert_eq!(candle.start_ts % candle.interval, 0);
+        }
+    }
+}
@@ -0,0 +1,712 @@
+//! Файл тикеров с необязательными сведениями об инструментах.
+//!
+//! Поддерживаются три формата, формат определяется автоматически:
+//!
+//! - простой список — один тикер на строку (исходный `tickers.txt`);
+//! - CSV с заголовком, первая колонка — `ticker`:
+//!
+//!   ```text
+//!   ticker,name,sector,lot_size,base_price
+//!   AAPL,Apple Inc.,Technology,10,190.5
+//!   ```
+//!
+//! - TOML (файл с расширением `.toml`):
+//!
+//!   ```toml
+//!   [[tickers]]
+//!   ticker = "AAPL"
+//!   name = "Apple Inc."
+//!   sector = "Technology"
+//!   lot_size = 10
+//!   base_price = 190.5
+//!   ```
+//!
+//! Все поля, кроме `ticker`, необязательны.
+//!
+//! Загруженные сведения хранит [`TickerRegistry`]: поиск и проверка
+//! тикеров, повторная загрузка файла.
+//!
+//! [`check_tickers`] дополнительно проверяет сами тикеры по правилам
+//! [`TickerRules`] (латинские буквы и цифры, длина) и отмечает повторы,
+//! чтобы комментарий или BOM в файле не превращались в тикер.
+
+use crate::codec::TICKER_WIDTH;
+use crate::csv::read_csv;
+use crate::errors::{QuoteError, ResultExt};
+use crate::traits::Validate;
+use log::warn;
+use macros::QuoteCsv;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Сведения о биржевом инструменте.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, QuoteCsv)]
+pub struct TickerInfo {
+    /// Тикер.
+    pub ticker: String,
+    /// Полное наименование эмитента.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Сектор экономики.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+    /// Размер лота, штук.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lot_size: Option<u32>,
+    /// Базовая (начальная) цена.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_price: Option<f64>,
+}
+
+impl TickerInfo {
+    /// Сведения, содержащие только тикер.
+    pub fn new(ticker: impl Into<String>) -> Self {
+        Self {
+            ticker: ticker.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Validate for TickerInfo {
+    /// Тикер не пустой, размер лота и базовая цена (если заданы)
+    /// положительные.
+    fn validate(&self) -> Result<(), QuoteError> {
+        if self.ticker.trim().is_empty() {
+            return Err(QuoteError::value_err("пустой тикер в файле тикеров"));
+        }
+        if self.lot_size == Some(0) {
+            return Err(QuoteError::value_err(format!(
+                "нулевой размер лота у {}",
+                self.ticker
+            )));
+        }
+        if let Some(price) = self.base_price
+            && !(price.is_finite() && price > 0.0)
+        {
+            return Err(QuoteError::value_err(format!(
+                "некорректная базовая цена {price} у {}",
+                self.ticker
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Содержимое TOML-файла тикеров.
+#[derive(Deserialize)]
+struct TickerFile {
+    #[serde(default)]
+    tickers: Vec<TickerInfo>,
+}
+
+/// Загрузить сведения о тикерах из файла любого поддерживаемого формата.
+///
+/// ## Returns
+///
+/// Сведения о тикерах в порядке файла или `None`, если файл не содержит
+/// тикеров.
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::IoError`], если файл не удалось прочитать,
+/// и ошибку вида [`ErrorKind::Value`](crate::errors::ErrorKind::Value)
+/// с путём к файлу, если содержимое некорректно.
+pub fn load_ticker_info(path: &Path) -> Result<Option<Vec<TickerInfo>>, QuoteError> {
+    let (text, is_toml) = read_ticker_file(path)?;
+    let infos = parse_ticker_info(&text, is_toml)
+        .with_context(|| format!("файл тикеров {}", path.display()))?;
+    Ok(Some(infos).filter(|infos| !infos.is_empty()))
+}
+
+/// Прочитать файл тикеров.
+///
+/// ## Returns
+///
+/// Содержимое файла и признак формата TOML (по расширению `.toml`).
+pub(crate) fn read_ticker_file(path: &Path) -> Result<(String, bool), QuoteError> {
+    let text = fs::read_to_string(path).map_err(|err| read_error(path, err))?;
+    Ok((text, is_toml_path(path)))
+}
+
+/// Ошибка чтения файла тикеров `path`.
+pub(crate) fn read_error(path: &Path, err: std::io::Error) -> QuoteError {
+    QuoteError::io_err(
+        format!("не удалось открыть файл с тикерами {}", path.display()),
+        err,
+    )
+}
+
+/// Файл тикеров в формате TOML (по расширению `.toml`).
+pub(crate) fn is_toml_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Разобрать содержимое файла тикеров; `is_toml` — содержимое в формате
+/// TOML, иначе CSV или простой список.
+pub fn parse_ticker_info(text: &str, is_toml: bool) -> Result<Vec<TickerInfo>, QuoteError> {
+    let infos = if is_toml {
+        toml::from_str::<TickerFile>(text)
+            .map_err(|err| QuoteError::value_err(format!("ошибка разбора TOML: {err}")))?
+            .tickers
+    } else if is_csv(text) {
+        read_csv(text.as_bytes())?
+    } else {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(TickerInfo::new)
+            .collect()
+    };
+
+    infos
+        .into_iter()
+        .map(|mut info| {
+            info.ticker = info.ticker.trim().to_string();
+            info.validate().map(|_| info)
+        })
+        .collect()
+}
+
+/// Правила проверки тикеров в [`check_tickers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickerRules {
+    /// Наименьшая длина тикера.
+    pub min_len: usize,
+    /// Наибольшая длина тикера (по умолчанию — [`TICKER_WIDTH`], предел
+    /// двоичной записи котировки).
+    pub max_len: usize,
+    /// Принимать строчные буквы, приводя тикер к верхнему регистру.
+    pub allow_lowercase: bool,
+}
+
+impl Default for TickerRules {
+    fn default() -> Self {
+        Self {
+            min_len: 1,
+            max_len: TICKER_WIDTH,
+            allow_lowercase: false,
+        }
+    }
+}
+
+impl TickerRules {
+    /// Проверить тикер: только латинские буквы и цифры, длина
+    /// от [`TickerRules::min_len`] до [`TickerRules::max_len`].
+    ///
+    /// ## Returns
+    ///
+    /// Тикер в верхнем регистре или причину отказа.
+    pub fn check(&self, ticker: &str) -> Result<String, TickerIssue> {
+        let allowed = |ch: char| {
+            ch.is_ascii_uppercase()
+                || ch.is_ascii_digit()
+                || (self.allow_lowercase && ch.is_ascii_lowercase())
+        };
+        if let Some(ch) = ticker.chars().find(|&ch| !allowed(ch)) {
+            return Err(if ch.is_ascii_lowercase() {
+                TickerIssue::Lowercase
+            } else {
+                TickerIssue::InvalidChar(ch)
+            });
+        }
+        if !(self.min_len..=self.max_len).contains(&ticker.len()) {
+            return Err(TickerIssue::Length {
+                len: ticker.len(),
+                min: self.min_len,
+                max: self.max_len,
+            });
+        }
+        Ok(ticker.to_ascii_uppercase())
+    }
+}
+
+/// Причина, по которой тикер не принят.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickerIssue {
+    /// Длина вне допустимых пределов.
+    Length { len: usize, min: usize, max: usize },
+    /// Строчные буквы, не разрешённые правилами.
+    Lowercase,
+    /// Символ, отличный от латинской буквы и цифры.
+    InvalidChar(char),
+}
+
+impl Display for TickerIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TickerIssue::Length { len, min, max } => {
+                write!(f, "длина {len} вне пределов {min}–{max}")
+            }
+            TickerIssue::Lowercase => f.write_str("строчные буквы"),
+            TickerIssue::InvalidChar(ch) if ch.is_ascii_graphic() => {
+                write!(f, "недопустимый символ '{ch}'")
+            }
+            TickerIssue::InvalidChar(ch) => {
+                write!(f, "недопустимый символ U+{:04X}", *ch as u32)
+            }
+        }
+    }
+}
+
+/// Строка файла тикеров, не прошедшая проверку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedTicker {
+    /// Номер строки (для CSV и TOML — номер записи), с 1.
+    pub line: usize,
+    /// Содержимое строки без пробелов по краям.
+    pub text: String,
+    /// Причина отказа.
+    pub issue: TickerIssue,
+}
+
+impl Display for RejectedTicker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "строка {} '{}': {}",
+            self.line,
+            self.text.escape_debug(),
+            self.issue
+        )
+    }
+}
+
+/// Итог проверки тикеров файла.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TickerCheck {
+    /// Принятые тикеры в порядке файла, без повторов.
+    pub tickers: Vec<String>,
+    /// Строки, не прошедшие проверку.
+    pub rejected: Vec<RejectedTicker>,
+    /// Повторы: номер строки и тикер, встретившийся раньше.
+    pub duplicates: Vec<(usize, String)>,
+}
+
+impl TickerCheck {
+    /// Принятые тикеры, если отклонённых строк нет.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`] с перечнем отклонённых строк.
+    pub fn into_result(self) -> Result<Vec<String>, QuoteError> {
+        if self.rejected.is_empty() {
+            return Ok(self.tickers);
+        }
+        let lines: Vec<String> = self.rejected.iter().map(ToString::to_string).collect();
+        Err(QuoteError::value_err(format!(
+            "отклонено строк: {} ({})",
+            lines.len(),
+            lines.join("; ")
+        )))
+    }
+}
+
+/// Проверить тикеры в содержимом файла тикеров по правилам `rules`.
+///
+/// Начальный BOM пропускается. Повторы не считаются ошибкой: остаётся
+/// первое вхождение, о каждом повторе пишется предупреждение в журнал.
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::ValueError`], если CSV или TOML не удалось
+/// разобрать; некорректные тикеры перечисляются в
+/// [`TickerCheck::rejected`].
+pub fn check_tickers(
+    text: &str,
+    is_toml: bool,
+    rules: &TickerRules,
+) -> Result<TickerCheck, QuoteError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let entries: Vec<(usize, String)> = if is_toml || is_csv(text) {
+        parse_ticker_info(text, is_toml)?
+            .into_iter()
+            .enumerate()
+            .map(|(idx, info)| (idx + 1, info.ticker))
+            .collect()
+    } else {
+        text.lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim().to_string()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect()
+    };
+
+    let mut check = TickerCheck::default();
+    let mut first_lines: HashMap<String, usize> = HashMap::new();
+    for (line, text) in entries {
+        let ticker = match rules.check(&text) {
+            Ok(ticker) => ticker,
+            Err(issue) => {
+                check.rejected.push(RejectedTicker { line, text, issue });
+                continue;
+            }
+        };
+        if let Some(first) = first_lines.get(&ticker) {
+            warn!("Тикер {ticker} в строке {line} повторяет строку {first}");
+            check.duplicates.push((line, ticker));
+        } else {
+            first_lines.insert(ticker.clone(), line);
+            check.tickers.push(ticker);
+        }
+    }
+    Ok(check)
+}
+
+/// Загрузить файл тикеров и проверить тикеры (см. [`check_tickers`]).
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError::IoError`], если файл не удалось прочитать,
+/// и ошибку вида [`ErrorKind::Value`](crate::errors::ErrorKind::Value)
+/// с путём к файлу, если CSV или TOML не удалось разобрать.
+pub fn load_checked_tickers(path: &Path, rules: &TickerRules) -> Result<TickerCheck, QuoteError> {
+    let (text, is_toml) = read_ticker_file(path)?;
+    check_tickers(&text, is_toml, rules).with_context(|| format!("файл тикеров {}", path.display()))
+}
+
+/// Проверенные тикеры содержимого файла `path` для
+/// [`crate::get_ticker_data`] и его асинхронного варианта.
+///
+/// ## Returns
+///
+/// Тикеры или `None`, если файл их не содержит.
+///
+/// ## Ошибки
+///
+/// Возвращает ошибку вида [`ErrorKind::Value`](crate::errors::ErrorKind::Value)
+/// с путём к файлу, если содержимое некорректно или есть отклонённые
+/// тикеры.
+pub(crate) fn checked_ticker_list(
+    path: &Path,
+    text: &str,
+    is_toml: bool,
+    rules: &TickerRules,
+) -> Result<Option<Vec<String>>, QuoteError> {
+    let tickers = check_tickers(text, is_toml, rules)
+        .and_then(TickerCheck::into_result)
+        .with_context(|| format!("файл тикеров {}", path.display()))?;
+    Ok(Some(tickers).filter(|tickers| !tickers.is_empty()))
+}
+
+/// Реестр известных тикеров.
+///
+/// Тикеры хранятся нормализованными (см. [`TickerRegistry::normalize`]),
+/// в порядке файла и без повторов; поиск не зависит от регистра.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::tickers::{TickerInfo, TickerRegistry};
+///
+/// let registry = TickerRegistry::new(["aapl", "MSFT"].map(TickerInfo::new));
+///
+/// assert!(registry.contains(" AAPL"));
+/// assert_eq!(registry.tickers(), vec!["AAPL", "MSFT"]);
+/// assert!(registry.parse_list("msft,TSLA").is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TickerRegistry {
+    infos: Vec<TickerInfo>,
+    index: HashMap<String, usize>,
+    path: Option<PathBuf>,
+}
+
+impl TickerRegistry {
+    /// Реестр из готовых сведений о тикерах.
+    pub fn new(infos: impl IntoIterator<Item = TickerInfo>) -> Self {
+        let mut registry = Self::default();
+        registry.replace(infos.into_iter().collect());
+        registry
+    }
+
+    /// Загрузить реестр из файла (см. [`load_ticker_info`]); пустой файл
+    /// даёт пустой реестр.
+    ///
+    /// ## Ошибки
+    ///
+    /// Те же, что у [`load_ticker_info`].
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, QuoteError> {
+        let mut registry = Self::default();
+        registry.reload_from(path)?;
+        Ok(registry)
+    }
+
+    /// Привести тикер к единому виду: без пробелов по краям, в верхнем
+    /// регистре.
+    pub fn normalize(ticker: &str) -> String {
+        ticker.trim().to_uppercase()
+    }
+
+    /// Тикер известен реестру.
+    pub fn contains(&self, ticker: &str) -> bool {
+        self.index.contains_key(&Self::normalize(ticker))
+    }
+
+    /// Сведения о тикере.
+    pub fn get(&self, ticker: &str) -> Option<&TickerInfo> {
+        self.index
+            .get(&Self::normalize(ticker))
+            .map(|&pos| &self.infos[pos])
+    }
+
+    /// Сведения о тикерах в порядке загрузки.
+    pub fn iter(&self) -> impl Iterator<Item = &TickerInfo> {
+        self.infos.iter()
+    }
+
+    /// Названия тикеров в порядке загрузки.
+    pub fn tickers(&self) -> Vec<String> {
+        self.infos.iter().map(|info| info.ticker.clone()).collect()
+    }
+
+    /// Количество тикеров.
+    pub fn len(&self) -> usize {
+        self.infos.len()
+    }
+
+    /// Реестр пуст.
+    pub fn is_empty(&self) -> bool {
+        self.infos.is_empty()
+    }
+
+    /// Файл, из которого реестр загружен последним.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Разобрать список тикеров через запятую, например `aapl, MSFT`.
+    /// Пустые элементы пропускаются.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::TickerError`] с перечнем неизвестных
+    /// тикеров.
+    pub fn parse_list(&self, list: &str) -> Result<HashSet<String>, QuoteError> {
+        let tickers: HashSet<String> = list
+            .split(',')
+            .map(Self::normalize)
+            .filter(|ticker| !ticker.is_empty())
+            .collect();
+
+        let mut unknown: Vec<&str> = tickers
+            .iter()
+            .filter(|ticker| !self.index.contains_key(*ticker))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(QuoteError::ticker_err(format!(
+                "неизвестные тикеры: {}",
+                unknown.join(",")
+            )));
+        }
+        Ok(tickers)
+    }
+
+    /// Перечитать файл, из которого реестр загружен.
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если состав или порядок тикеров изменился.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`], если реестр не загружался
+    /// из файла, и ошибки [`load_ticker_info`]; при ошибке реестр
+    /// не меняется.
+    pub fn reload(&mut self) -> Result<bool, QuoteError> {
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| QuoteError::value_err("реестр тикеров не связан с файлом"))?;
+        self.reload_from(path)
+    }
+
+    /// Заменить содержимое реестра сведениями из файла `path` и запомнить
+    /// его для [`TickerRegistry::reload`].
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если состав или порядок тикеров изменился.
+    pub fn reload_from(&mut self, path: impl Into<PathBuf>) -> Result<bool, QuoteError> {
+        let path = path.into();
+        let infos = load_ticker_info(&path)?.unwrap_or_default();
+        let before = self.tickers();
+        self.replace(infos);
+        self.path = Some(path);
+        Ok(self.tickers() != before)
+    }
+
+    /// Заменить сведения, нормализовав тикеры и отбросив повторы.
+    fn replace(&mut self, infos: Vec<TickerInfo>) {
+        self.infos.clear();
+        self.index.clear();
+        for mut info in infos {
+            info.ticker = Self::normalize(&info.ticker);
+            if !self.index.contains_key(&info.ticker) {
+                self.index.insert(info.ticker.clone(), self.infos.len());
+                self.infos.push(info);
+            }
+        }
+    }
+}
+
+/// Первая непустая строка — заголовок CSV с колонкой `ticker` в начале.
+fn is_csv(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .and_then(|line| line.split(',').next())
+        .is_some_and(|first| first.trim().eq_ignore_ascii_case("ticker") && text.contains(','))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::Builder;
+
+    #[test]
+    fn plain_list_stays_supported() {
+        let infos = parse_ticker_info("AAPL\n\n  MSFT \n", false).unwrap();
+        assert_eq!(
+            infos,
+            vec![TickerInfo::new("AAPL"), TickerInfo::new("MSFT")]
+        );
+    }
+
+    #[test]
+    fn csv_with_partial_columns() {
+        let text = "ticker,sector,base_price\nAAPL,Technology,190.5\nXOM,,\n";
+        let infos = parse_ticker_info(text, false).unwrap();
+
+        assert_eq!(infos[0].sector.as_deref(), Some("Technology"));
+        assert_eq!(infos[0].base_price, Some(190.5));
+        assert_eq!(infos[0].lot_size, None);
+        assert_eq!(infos[1], TickerInfo::new("XOM"));
+    }
+
+    #[test]
+    fn toml_file_is_detected_by_extension() {
+        let mut file = Builder::new().suffix(".toml").tempfile().unwrap();
+        writeln!(
+            file,
+            "[[tickers]]\nticker = \"AAPL\"\nname = \"Apple Inc.\"\nlot_size = 10\n\n\
+             [[tickers]]\nticker = \"MSFT\""
+        )
+        .unwrap();
+        let infos = load_ticker_info(file.path()).unwrap().unwrap();
+
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].name.as_deref(), Some("Apple Inc."));
+        assert_eq!(infos[0].lot_size, Some(10));
+        assert_eq!(infos[1], TickerInfo::new("MSFT"));
+    }
+
+    #[test]
+    fn empty_file_gives_none() {
+        let file = Builder::new().suffix(".txt").tempfile().unwrap();
+        assert!(load_ticker_info(file.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_records_are_rejected() {
+        assert!(parse_ticker_info("ticker,lot_size\nAAPL,0\n", false).is_err());
+        assert!(parse_ticker_info("ticker,base_price\nAAPL,-1\n", false).is_err());
+        assert!(parse_ticker_info("ticker,base_price\n,10\n", false).is_err());
+        assert!(parse_ticker_info("[[tickers]]\nname = \"x\"", true).is_err());
+    }
+
+    #[test]
+    fn ticker_rules() {
+        let rules = TickerRules::default();
+        assert_eq!(rules.check("BRK2"), Ok("BRK2".to_string()));
+        assert_eq!(rules.check("aapl"), Err(TickerIssue::Lowercase));
+        assert_eq!(rules.check("BRK.B"), Err(TickerIssue::InvalidChar('.')));
+        assert!(matches!(
+            rules.check("ABCDEFGHI"),
+            Err(TickerIssue::Length { len: 9, .. })
+        ));
+
+        let relaxed = TickerRules {
+            min_len: 2,
+            allow_lowercase: true,
+            ..rules
+        };
+        assert_eq!(relaxed.check("aapl"), Ok("AAPL".to_string()));
+        assert!(relaxed.check("V").is_err());
+    }
+
+    #[test]
+    fn rejected_lines_are_listed_with_numbers() {
+        let text = "\u{feff}AAPL\n# комментарий\n\nMSFT\n\u{feff}TSLA\n";
+        let check = check_tickers(text, false, &TickerRules::default()).unwrap();
+
+        assert_eq!(check.tickers, vec!["AAPL", "MSFT"]);
+        let lines: Vec<usize> = check.rejected.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![2, 5]);
+        assert_eq!(check.rejected[0].issue, TickerIssue::InvalidChar('#'));
+
+        let err = check.into_result().unwrap_err().to_string();
+        assert!(err.contains("строка 2 '# комментарий'"), "{err}");
+        assert!(
+            err.contains("строка 5 '\\u{feff}TSLA': недопустимый символ U+FEFF"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn duplicates_keep_first_occurrence() {
+        let text = "ticker,name\nAAPL,Apple\nMSFT,\nAAPL,Again\n";
+        let check = check_tickers(text, false, &TickerRules::default()).unwrap();
+
+        assert_eq!(check.tickers, vec!["AAPL", "MSFT"]);
+        assert_eq!(check.duplicates, vec![(3, "AAPL".to_string())]);
+        assert!(check.into_result().is_ok());
+    }
+
+    #[test]
+    fn registry_normalizes_and_deduplicates() {
+        let registry = TickerRegistry::new([" aapl", "MSFT", "AAPL"].map(TickerInfo::new));
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.tickers(), vec!["AAPL", "MSFT"]);
+        assert!(registry.contains("msft"));
+        assert!(registry.get("Aapl").is_some());
+        assert!(!registry.contains("TSLA"));
+        assert!(registry.path().is_none());
+    }
+
+    #[test]
+    fn registry_parses_ticker_lists() {
+        let registry = TickerRegistry::new(["AAPL", "MSFT"].map(TickerInfo::new));
+
+        let parsed = registry.parse_list(" aapl,,MSFT ").unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(registry.parse_list(",").unwrap().is_empty());
+
+        let err = registry.parse_list("TSLA,AAPL,GME").unwrap_err();
+        assert!(err.to_string().contains("GME,TSLA"));
+    }
+
+    #[test]
+    fn registry_reloads_from_file() {
+        let mut file = Builder::new().suffix(".txt").tempfile().unwrap();
+        writeln!(file, "AAPL").unwrap();
+        let mut registry = TickerRegistry::load(file.path()).unwrap();
+        assert!(!registry.reload().unwrap());
+
+        writeln!(file, "MSFT").unwrap();
+        assert!(registry.reload().unwrap());
+        assert_eq!(registry.tickers(), vec!["AAPL", "MSFT"]);
+
+        assert!(TickerRegistry::default().reload().is_err());
+        assert!(registry.reload_from("/non/existent/tickers.txt").is_err());
+        assert_eq!(registry.len(), 2);
+    }
+}
@@ -1,25 +1,365 @@
 //! Универсальные трейты для приложений Quote Server и Quote Client.
 
-use std::io::Write;
+use crate::errors::QuoteError;
+use crate::frame::FrameReader;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
 
+/// Проверка корректности значений модели, передаваемой по сети.
+///
+/// Сервер проверяет данные перед отправкой, клиент — после разбора, чтобы
+/// формально корректные, но бессмысленные записи (нулевая цена, пустой
+/// тикер) не попадали в обработку.
+pub trait Validate {
+    /// Проверить значения.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`] с описанием первого
+    /// найденного нарушения.
+    fn validate(&self) -> Result<(), QuoteError>;
+}
+
+/// Строковый формат `QuoteDisplay`, разбитый на поля: для вложения
+/// структуры в другую атрибутом `#[quote(flatten)]`.
+///
+/// Поля передаются без разделителей — их расставляет (и экранирует)
+/// внешняя структура. Для структур с `QuoteDisplay` трейт реализует
+/// derive-макрос, если генерируются и `Display`, и `FromStr`.
+pub trait DisplaySegments: Sized {
+    /// Число полей в строке.
+    const SEGMENTS: usize;
+
+    /// Число обязательных полей; остальные в конце строки можно опустить.
+    const REQUIRED_SEGMENTS: usize;
+
+    /// Значения полей по порядку, ровно [`Self::SEGMENTS`].
+    fn segments(&self) -> Vec<String>;
+
+    /// Разобрать значение из полей `parts`.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает описание ошибки, если полей меньше
+    /// [`Self::REQUIRED_SEGMENTS`] или больше [`Self::SEGMENTS`], или
+    /// значение поля не разбирается.
+    fn from_segments(parts: &[&str]) -> Result<Self, String>;
+}
+
+/// Запись текстовых строк с передачей ошибок вызывающему.
+///
+/// Если у потока задан таймаут записи (`TcpStream::set_write_timeout`),
+/// его истечение возвращается как [`QuoteError::TimeoutError`], прочие
+/// ошибки записи — как [`QuoteError::IoError`]. Варианты `_lossy`
+/// игнорируют ошибки — для записи, неудача которой ничего не меняет.
 pub trait WriteExt {
-    /// Отправляет в `Write` переданную текстовую строку, преобразуя её
-    /// в байтовую.
-    fn write_str(&mut self, s: impl AsRef<str>);
-    /// Обёртка для `writer.flush()`, скрывающая обработку `Result`.
-    fn flush_ext(&mut self);
+    /// Отправить текстовую строку; перевод строки добавляется, если его нет.
+    fn write_str(&mut self, s: impl AsRef<str>) -> Result<(), QuoteError>;
+
+    /// Сбросить буфер записи.
+    fn flush_ext(&mut self) -> Result<(), QuoteError>;
+
+    /// [`WriteExt::write_str`] без обработки ошибок.
+    fn write_str_lossy(&mut self, s: impl AsRef<str>) {
+        let _ = self.write_str(s);
+    }
+
+    /// [`WriteExt::flush_ext`] без обработки ошибок.
+    fn flush_lossy(&mut self) {
+        let _ = self.flush_ext();
+    }
 }
 
 impl<W: Write> WriteExt for W {
-    fn write_str(&mut self, s: impl AsRef<str>) {
+    fn write_str(&mut self, s: impl AsRef<str>) -> Result<(), QuoteError> {
         let s = s.as_ref();
-        let _ = self.write_all(s.as_bytes());
+        self.write_all(s.as_bytes()).map_err(write_err)?;
         if !s.ends_with('\n') {
-            let _ = self.write_all(b"\n");
+            self.write_all(b"\n").map_err(write_err)?;
+        }
+        Ok(())
+    }
+
+    fn flush_ext(&mut self) -> Result<(), QuoteError> {
+        self.flush().map_err(write_err)
+    }
+}
+
+/// Ошибка записи в терминах [`QuoteError`].
+fn write_err(err: io::Error) -> QuoteError {
+    match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            QuoteError::timeout_err("удалённая сторона не принимает данные")
         }
+        _ => QuoteError::io_err("ошибка записи", err),
+    }
+}
+
+/// Чтение из TCP-потока с таймаутом и передачей ошибок вызывающему.
+///
+/// Парный трейт к [`WriteExt`]. Параметр `timeout` ограничивает ожидание
+/// одной операции чтения и действует только на время вызова; `None` —
+/// используется текущая настройка потока.
+///
+/// Истечение таймаута возвращается как [`QuoteError::TimeoutError`],
+/// прочие ошибки чтения — как [`QuoteError::IoError`].
+pub trait ReadExt {
+    /// Прочитать строку без завершающего перевода строки.
+    ///
+    /// ## Returns
+    ///
+    /// `None`, если соединение закрыто.
+    fn read_line_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<String>, QuoteError>;
+
+    /// Читать строки до строки `token` (без учёта регистра и пробелов
+    /// по краям), например до `READY` в приветствии сервера.
+    ///
+    /// ## Returns
+    ///
+    /// Строки, прочитанные до `token`.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ProtocolError`], если соединение закрыто
+    /// раньше, чем получен `token`.
+    fn read_until_token(
+        &mut self,
+        token: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<String>, QuoteError>;
+
+    /// Прочитать кадр (см. [`crate::frame`]): длина `u32` (little-endian),
+    /// затем данные.
+    ///
+    /// ## Returns
+    ///
+    /// `None`, если соединение закрыто до начала кадра.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ProtocolError`], если длина превышает
+    /// [`MAX_FRAME_SIZE`](crate::config::MAX_FRAME_SIZE) или соединение
+    /// закрыто посреди кадра. Кадр, прерванный таймаутом, теряется; чтобы
+    /// продолжить его чтение, используйте [`FrameReader`].
+    fn read_frame(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<u8>>, QuoteError>;
+}
+
+impl ReadExt for BufReader<TcpStream> {
+    fn read_line_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<String>, QuoteError> {
+        with_read_timeout(self, timeout, |reader| {
+            let mut line = String::new();
+            match reader.read_line(&mut line)? {
+                0 => Ok(None),
+                _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+            }
+        })
+    }
+
+    fn read_until_token(
+        &mut self,
+        token: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<String>, QuoteError> {
+        let mut lines = Vec::new();
+        loop {
+            let Some(line) = self.read_line_timeout(timeout)? else {
+                return Err(QuoteError::protocol_err(format!(
+                    "соединение закрыто до получения {}",
+                    token.trim()
+                )));
+            };
+            if line.trim().eq_ignore_ascii_case(token.trim()) {
+                return Ok(lines);
+            }
+            lines.push(line);
+        }
+    }
+
+    fn read_frame(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<u8>>, QuoteError> {
+        with_read_timeout(self, timeout, |reader| {
+            Ok(FrameReader::new(reader).read_frame())
+        })?
+    }
+}
+
+/// Выполнить чтение с временно установленным таймаутом потока.
+fn with_read_timeout<T>(
+    reader: &mut BufReader<TcpStream>,
+    timeout: Option<Duration>,
+    read: impl FnOnce(&mut BufReader<TcpStream>) -> io::Result<T>,
+) -> Result<T, QuoteError> {
+    let io_err = |err| QuoteError::io_err("ошибка настройки таймаута чтения", err);
+    let previous = match timeout {
+        Some(timeout) => {
+            let previous = reader.get_ref().read_timeout().map_err(io_err)?;
+            reader
+                .get_ref()
+                .set_read_timeout(Some(timeout))
+                .map_err(io_err)?;
+            Some(previous)
+        }
+        None => None,
+    };
+
+    let result = read(reader);
+
+    if let Some(previous) = previous {
+        reader
+            .get_ref()
+            .set_read_timeout(previous)
+            .map_err(io_err)?;
     }
+    result.map_err(|err| match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            QuoteError::timeout_err("нет данных от удалённой стороны")
+        }
+        _ => QuoteError::io_err("ошибка чтения", err),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Соединение, на другом конце которого записываются `data`, после
+    /// чего запись закрывается (если `close`).
+    fn reader_for(data: &'static [u8], close: bool) -> BufReader<TcpStream> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(data).unwrap();
+            if close {
+                stream.shutdown(std::net::Shutdown::Write).unwrap();
+            } else {
+                thread::sleep(Duration::from_secs(2));
+            }
+        });
+        BufReader::new(TcpStream::connect(addr).unwrap())
+    }
+
+    /// Поток записи, возвращающий ошибку вида `kind` после `capacity` байт.
+    struct Failing {
+        written: Vec<u8>,
+        capacity: usize,
+        kind: io::ErrorKind,
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let room = self.capacity - self.written.len();
+            if room == 0 {
+                return Err(self.kind.into());
+            }
+            let n = buf.len().min(room);
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn failing(capacity: usize, kind: io::ErrorKind) -> Failing {
+        Failing {
+            written: Vec::new(),
+            capacity,
+            kind,
+        }
+    }
+
+    #[test]
+    fn write_str_appends_newline() {
+        let mut buf = Vec::new();
+        buf.write_str("OK").unwrap();
+        buf.write_str("READY\n").unwrap();
+        buf.flush_ext().unwrap();
+        assert_eq!(buf, b"OK\nREADY\n");
+    }
+
+    #[test]
+    fn write_errors_are_returned() {
+        let mut writer = failing(2, io::ErrorKind::BrokenPipe);
+        let err = writer.write_str("OK").unwrap_err();
+        assert!(matches!(err, QuoteError::IoError { .. }));
+        assert_eq!(writer.written, b"OK");
+
+        let mut writer = failing(0, io::ErrorKind::WouldBlock);
+        let err = writer.write_str("OK").unwrap_err();
+        assert!(matches!(err, QuoteError::TimeoutError(_)));
+
+        // Вариант без обработки ошибок их игнорирует.
+        writer.write_str_lossy("OK");
+        writer.flush_lossy();
+    }
+
+    #[test]
+    fn read_line_and_eof() {
+        let mut reader = reader_for(b"hello\r\n", true);
+
+        assert_eq!(
+            reader.read_line_timeout(None).unwrap().as_deref(),
+            Some("hello")
+        );
+        assert!(reader.read_line_timeout(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn read_line_times_out_and_restores_timeout() {
+        let mut reader = reader_for(b"", false);
+        let err = reader
+            .read_line_timeout(Some(Duration::from_millis(50)))
+            .unwrap_err();
+
+        assert!(matches!(err, QuoteError::TimeoutError(_)));
+        assert_eq!(reader.get_ref().read_timeout().unwrap(), None);
+    }
+
+    #[test]
+    fn read_until_token_collects_preceding_lines() {
+        let mut reader = reader_for(b"Hello!\n\nready\nOK\n", true);
+        let lines = reader.read_until_token("READY", None).unwrap();
+
+        assert_eq!(lines, vec!["Hello!".to_string(), String::new()]);
+        assert_eq!(
+            reader.read_line_timeout(None).unwrap().as_deref(),
+            Some("OK")
+        );
+    }
+
+    #[test]
+    fn read_until_token_fails_on_eof() {
+        let mut reader = reader_for(b"Hello!\n", true);
+        let err = reader.read_until_token("READY", None).unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
+    }
+
+    #[test]
+    fn read_frame_handles_payload_eof_and_truncation() {
+        let mut reader = reader_for(b"\x03\0\0\0abc", true);
+        assert_eq!(reader.read_frame(None).unwrap().unwrap(), b"abc");
+        assert!(reader.read_frame(None).unwrap().is_none());
+
+        let mut reader = reader_for(b"\x05\0\0\0ab", true);
+        let err = reader.read_frame(None).unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
+
+        let mut reader = reader_for(b"\x05\0", true);
+        let err = reader.read_frame(None).unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
 
-    fn flush_ext(&mut self) {
-        let _ = self.flush();
+        let mut reader = reader_for(b"\xff\xff\xff\x7f", true);
+        let err = reader.read_frame(None).unwrap_err();
+        assert!(matches!(err, QuoteError::ProtocolError(_)));
     }
 }
@@ -1,7 +1,7 @@
 //! Универсальные утилиты.
 
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// Возвращает количество секунд от начала эпохи UNIX, на основе системного
 /// времени.
@@ -18,6 +18,150 @@ pub fn get_timestamp() -> u64 {
     }
 }
 
+/// Возвращает количество миллисекунд от начала эпохи UNIX, на основе
+/// системного времени. Используется для временных меток котировок.
+///
+/// Возможна паника, если системные часы выставлены на время ранее
+/// 1 января 1970 года 0:00:00 UTC.
+pub fn get_timestamp_ms() -> u64 {
+    match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => d.as_millis() as u64,
+        Err(_) => panic!("Системное время раньше 01.01.1970 0:00:00 UTC"),
+    }
+}
+
+/// Представить временную метку в миллисекундах в формате RFC 3339 (UTC).
+///
+/// ## Пример
+///
+/// ```
+/// use commons::utils::format_timestamp_ms;
+///
+/// assert_eq!(format_timestamp_ms(1_700_000_000_123), "2023-11-14T22:13:20.123Z");
+/// ```
+pub fn format_timestamp_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+/// Дата григорианского календаря по номеру дня от 1970-01-01
+/// (алгоритм Г. Хиннанта).
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Номер дня от 1970-01-01 по дате григорианского календаря (обратное
+/// к [`civil_from_days`]).
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Цена с разделителями тысяч и двумя знаками после запятой.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::utils::fmt_price;
+///
+/// assert_eq!(fmt_price(1234567.891), "1,234,567.89");
+/// assert_eq!(fmt_price(-0.5), "-0.50");
+/// ```
+pub fn fmt_price(price: f64) -> String {
+    if !price.is_finite() {
+        return price.to_string();
+    }
+    let fixed = format!("{:.2}", price.abs());
+    let (int_part, frac_part) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+
+    let mut grouped = String::with_capacity(fixed.len() + int_part.len() / 3 + 1);
+    if price.is_sign_negative() && fixed != "0.00" {
+        grouped.push('-');
+    }
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped}.{frac_part}")
+}
+
+/// Объём в сокращённой записи: `950`, `12.5K`, `1.2M`, `3.0B`.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::utils::fmt_volume;
+///
+/// assert_eq!(fmt_volume(950), "950");
+/// assert_eq!(fmt_volume(1_234_567), "1.2M");
+/// ```
+pub fn fmt_volume(volume: u32) -> String {
+    if volume < 1000 {
+        return volume.to_string();
+    }
+    let mut value = volume as f64;
+    for unit in ["K", "M", "B"] {
+        value /= 1000.0;
+        // Значение, округляемое до 1000.0, переносится в следующий разряд.
+        if value < 999.95 || unit == "B" {
+            return format!("{value:.1}{unit}");
+        }
+    }
+    unreachable!("последний разряд возвращается в цикле")
+}
+
+/// Длительность в сокращённой записи: `850ms`, `12.3s`, `5m 07s`,
+/// `2h 03m`, `3d 04h`.
+///
+/// ## Пример
+///
+/// ```
+/// use commons::utils::fmt_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(fmt_duration(Duration::from_millis(850)), "850ms");
+/// assert_eq!(fmt_duration(Duration::from_secs(307)), "5m 07s");
+/// ```
+pub fn fmt_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..60 => format!("{:.1}s", duration.as_secs_f64()),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        3600..86_400 => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {:02}h", secs / 86_400, secs % 86_400 / 3600),
+    }
+}
+
 /// Предоставить родительский каталог проекта.
 ///
 /// Для `debug` это будет директория расположения `cargo.toml`, а для `release`
@@ -53,3 +197,64 @@ pub fn get_workspace_root() -> PathBuf {
         project_root.to_path_buf()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_ms_is_close_to_seconds() {
+        let secs = get_timestamp();
+        let ms = get_timestamp_ms();
+        assert!(ms / 1000 >= secs && ms / 1000 - secs <= 1);
+    }
+
+    #[test]
+    fn timestamp_is_formatted_as_rfc3339() {
+        assert_eq!(format_timestamp_ms(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp_ms(951_782_400_007),
+            "2000-02-29T00:00:00.007Z"
+        );
+        assert_eq!(
+            format_timestamp_ms(1_792_274_091_999),
+            "2026-10-17T21:54:51.999Z"
+        );
+        assert_eq!(
+            format_timestamp_ms(4_102_444_799_000),
+            "2099-12-31T23:59:59.000Z"
+        );
+    }
+
+    #[test]
+    fn price_is_grouped_by_thousands() {
+        assert_eq!(fmt_price(0.0), "0.00");
+        assert_eq!(fmt_price(999.999), "1,000.00");
+        assert_eq!(fmt_price(100.0), "100.00");
+        assert_eq!(fmt_price(123456.7), "123,456.70");
+        assert_eq!(fmt_price(-1234.5), "-1,234.50");
+        assert_eq!(fmt_price(-0.001), "0.00");
+        assert_eq!(fmt_price(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn volume_is_abbreviated() {
+        assert_eq!(fmt_volume(0), "0");
+        assert_eq!(fmt_volume(999), "999");
+        assert_eq!(fmt_volume(1_000), "1.0K");
+        assert_eq!(fmt_volume(12_549), "12.5K");
+        assert_eq!(fmt_volume(999_999), "1.0M");
+        assert_eq!(fmt_volume(u32::MAX), "4.3B");
+    }
+
+    #[test]
+    fn duration_picks_two_largest_units() {
+        assert_eq!(fmt_duration(Duration::ZERO), "0ms");
+        assert_eq!(fmt_duration(Duration::from_millis(12_340)), "12.3s");
+        assert_eq!(fmt_duration(Duration::from_secs(3600 * 2 + 180)), "2h 03m");
+        assert_eq!(
+            fmt_duration(Duration::from_secs(86_400 * 3 + 3600 * 4)),
+            "3d 04h"
+        );
+    }
+}
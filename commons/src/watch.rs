@@ -0,0 +1,212 @@
+//! Отслеживание изменений файла для горячей перезагрузки.
+//!
+//! [`FileWatcher`] получает уведомления файловой системы (крейт `notify`)
+//! и вызывает обработчик, когда поток событий затихает на время
+//! `debounce`: серия записей при сохранении файла редактором даёт один
+//! вызов. Отслеживается директория файла, поэтому замена файла
+//! переименованием (так сохраняют многие редакторы) тоже замечается.
+//!
+//! ## Пример
+//!
+//! ```no_run
+//! use commons::watch::FileWatcher;
+//! use std::time::Duration;
+//!
+//! let watcher = FileWatcher::spawn("data/tickers.txt", Duration::from_millis(200), || {
+//!     println!("Файл тикеров изменён");
+//! })
+//! .unwrap();
+//!
+//! // Отслеживание прекращается при удалении `watcher`.
+//! drop(watcher);
+//! ```
+
+use crate::errors::QuoteError;
+use log::warn;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Отслеживание изменений одного файла.
+///
+/// Обработчик вызывается в отдельном потоке; при удалении значения
+/// отслеживание прекращается, а поток завершается.
+pub struct FileWatcher {
+    path: PathBuf,
+    watcher: Option<RecommendedWatcher>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FileWatcher {
+    /// Начать отслеживание файла `path`.
+    ///
+    /// ## Args
+    ///
+    /// - `path` — отслеживаемый файл (может пока не существовать, но его
+    ///   директория должна существовать)
+    /// - `debounce` — сколько событий не должно поступать, чтобы изменение
+    ///   считалось завершённым
+    /// - `on_change` — обработчик изменения
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::IoError`], если отслеживание не удалось
+    /// начать (нет директории, исчерпан лимит наблюдателей ОС).
+    pub fn spawn<F>(
+        path: impl Into<PathBuf>,
+        debounce: Duration,
+        mut on_change: F,
+    ) -> Result<Self, QuoteError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let path = path.into();
+        let (dir, name) = split_path(&path);
+
+        let (tx, rx) = mpsc::channel();
+        let target = name.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if concerns(&event, &target) => {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                Err(err) => warn!("Ошибка отслеживания файла: {err}"),
+            })
+            .map_err(|err| watch_err(&path, err))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|err| watch_err(&path, err))?;
+
+        let handle = thread::spawn(move || {
+            // `recv` возвращает ошибку, когда наблюдатель удалён.
+            while rx.recv().is_ok() {
+                loop {
+                    match rx.recv_timeout(debounce) {
+                        Ok(()) => continue,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                on_change();
+            }
+        });
+
+        Ok(Self {
+            path,
+            watcher: Some(watcher),
+            handle: Some(handle),
+        })
+    }
+
+    /// Отслеживаемый файл.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        // Удаление наблюдателя закрывает канал событий и завершает поток.
+        self.watcher.take();
+        if let Some(handle) = self.handle.take()
+            && handle.join().is_err()
+        {
+            warn!(
+                "Обработчик изменений {} завершился паникой",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Директория и имя файла; для пути без директории — текущая директория.
+fn split_path(path: &Path) -> (PathBuf, Option<OsString>) {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    (dir, path.file_name().map(ToOwned::to_owned))
+}
+
+/// Событие касается отслеживаемого файла (и не является только чтением).
+fn concerns(event: &Event, name: &Option<OsString>) -> bool {
+    if event.kind.is_access() {
+        return false;
+    }
+    event
+        .paths
+        .iter()
+        .any(|path| name.is_none() || path.file_name() == name.as_deref())
+}
+
+fn watch_err(path: &Path, err: notify::Error) -> QuoteError {
+    let context = format!("не удалось отслеживать файл {}", path.display());
+    match err.kind {
+        notify::ErrorKind::Io(io) => QuoteError::io_err(context, io),
+        other => QuoteError::io_err(context, std::io::Error::other(format!("{other:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use std::time::Instant;
+
+    fn wait_for(counter: &AtomicUsize, expected: usize) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if counter.load(Ordering::SeqCst) >= expected {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn burst_of_writes_gives_one_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickers.txt");
+        let other = dir.path().join("other.txt");
+        fs::write(&path, "AAPL\n").unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let watcher = FileWatcher::spawn(&path, Duration::from_millis(100), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        assert_eq!(watcher.path(), path);
+
+        fs::write(&other, "x").unwrap();
+        for content in ["AAPL\nMSFT\n", "AAPL\nMSFT\nTSLA\n"] {
+            fs::write(&path, content).unwrap();
+        }
+        assert!(wait_for(&calls, 1));
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Замена файла переименованием.
+        let tmp = dir.path().join("tickers.txt.tmp");
+        fs::write(&tmp, "GOOGL\n").unwrap();
+        fs::rename(&tmp, &path).unwrap();
+        assert!(wait_for(&calls, 2));
+    }
+
+    #[test]
+    fn missing_directory_is_an_error() {
+        let result = FileWatcher::spawn("/non/existent/tickers.txt", Duration::ZERO, || {});
+        assert!(matches!(result, Err(QuoteError::IoError { .. })));
+    }
+}
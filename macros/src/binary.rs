@@ -0,0 +1,225 @@
+//! Генерация двоичного представления фиксированного размера
+//! (`QuoteBinary`).
+
+use crate::paths::{binary_field_trait, commons_root, quote_error};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DataStruct, DeriveInput, Expr, Fields, Ident, Type, spanned::Spanned};
+
+/// Наибольшее число необязательных полей: по биту в байте флагов.
+const MAX_OPTIONAL: usize = 8;
+
+/// `BINARY_SIZE`, `to_bytes` и `from_bytes` для структуры `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (binary_field, error) = (
+        binary_field_trait(&commons_root()),
+        quote_error(&commons_root()),
+    );
+    let named = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct { fields, .. }) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "QuoteBinary поддерживает только структуры с именованными полями",
+            ));
+        }
+        Data::Enum(e) => {
+            return Err(syn::Error::new(
+                e.enum_token.span,
+                "QuoteBinary допустимо использовать только со структурами",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteBinary допустимо использовать только со структурами",
+            ));
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "QuoteBinary не поддерживает обобщённые структуры",
+        ));
+    }
+
+    let fields = named
+        .iter()
+        .map(LayoutField::new)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let optional: Vec<&LayoutField> = fields.iter().filter(|field| field.optional).collect();
+    if let Some(extra) = optional.get(MAX_OPTIONAL) {
+        return Err(syn::Error::new(
+            extra.ident.span(),
+            format!("QuoteBinary поддерживает не более {MAX_OPTIONAL} полей Option<T>"),
+        ));
+    }
+    let all_flags: u8 = (0..optional.len()).map(|bit| 1u8 << bit).sum();
+
+    // Байт флагов стоит перед первым необязательным полем.
+    let flags_at = fields.iter().position(|field| field.optional);
+    let sizes = fields.iter().map(|field| field.size());
+    let flags_size = flags_at.map(|_| quote! { + 1 });
+
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    let mut bit = 0u8;
+    for (i, field) in fields.iter().enumerate() {
+        if flags_at == Some(i) {
+            writes.push(quote! { let flags_pos = pos; pos += 1; });
+            reads.push(quote! {
+                let flags = bytes[pos];
+                pos += 1;
+                if flags & !#all_flags != 0 {
+                    return Err(#error::parse_err(format!(
+                        "неизвестные флаги в двоичной записи: {flags:#010b}"
+                    )));
+                }
+            });
+        }
+        let ident = field.ident;
+        let ty = field.value_ty();
+        let size = field.size();
+        let slot = quote! { pos..pos + #size };
+        if field.optional {
+            let flag = 1u8 << bit;
+            bit += 1;
+            writes.push(quote! {
+                if let Some(value) = &self.#ident {
+                    #binary_field::write_to(value, &mut buf[#slot], stringify!(#ident))?;
+                    flags |= #flag;
+                }
+                pos += #size;
+            });
+            reads.push(quote! {
+                let #ident = match flags & #flag {
+                    0 => None,
+                    _ => Some(<#ty as #binary_field>::read_from(&bytes[#slot], stringify!(#ident))?),
+                };
+                pos += #size;
+            });
+        } else {
+            writes.push(quote! {
+                #binary_field::write_to(&self.#ident, &mut buf[#slot], stringify!(#ident))?;
+                pos += #size;
+            });
+            reads.push(quote! {
+                let #ident = <#ty as #binary_field>::read_from(&bytes[#slot], stringify!(#ident))?;
+                pos += #size;
+            });
+        }
+    }
+    let write_flags = flags_at.map(|_| quote! { buf[flags_pos] = flags; });
+    let flags_var = flags_at.map(|_| quote! { let mut flags = 0u8; });
+    let idents = fields.iter().map(|field| field.ident);
+
+    // Ширину полей без атрибута задаёт тип; у строк её нужно указать явно.
+    let width_checks = fields
+        .iter()
+        .filter(|field| field.width.is_none())
+        .map(|field| {
+            let ty = field.value_ty();
+            let message = format!(
+                "QuoteBinary: у поля {} нет ширины, нужен атрибут #[binary(width = N)]",
+                field.ident
+            );
+            quote! { assert!(<#ty as #binary_field>::SIZE > 0, #message); }
+        });
+
+    Ok(quote! {
+        const _: () = { #(#width_checks)* };
+
+        impl #name {
+            /// Размер двоичной записи, байт.
+            pub const BINARY_SIZE: usize = 0 #(+ #sizes)* #flags_size;
+
+            /// Представить значение двоичной записью фиксированного размера.
+            pub fn to_bytes(&self) -> Result<[u8; Self::BINARY_SIZE], #error> {
+                let mut buf = [0u8; Self::BINARY_SIZE];
+                let mut pos = 0usize;
+                #flags_var
+                #(#writes)*
+                #write_flags
+                let _ = pos;
+                Ok(buf)
+            }
+
+            /// Восстановить значение из двоичной записи.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, #error> {
+                if bytes.len() != Self::BINARY_SIZE {
+                    return Err(#error::parse_err(format!(
+                        "ожидается двоичная запись {} из {} байт, получено {}",
+                        stringify!(#name),
+                        Self::BINARY_SIZE,
+                        bytes.len()
+                    )));
+                }
+                let mut pos = 0usize;
+                #(#reads)*
+                let _ = pos;
+                Ok(Self { #(#idents),* })
+            }
+        }
+    })
+}
+
+/// Поле структуры в двоичной записи.
+struct LayoutField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    /// Поле `Option<T>`: наличие значения отмечается битом во флагах.
+    optional: bool,
+    /// Ширина из атрибута `#[binary(width = N)]`.
+    width: Option<Expr>,
+}
+
+impl<'a> LayoutField<'a> {
+    fn new(field: &'a syn::Field) -> syn::Result<Self> {
+        Ok(Self {
+            ident: field
+                .ident
+                .as_ref()
+                .expect("у поля именованной структуры есть имя"),
+            ty: &field.ty,
+            optional: option_inner(&field.ty).is_some(),
+            width: width(&field.attrs)?,
+        })
+    }
+
+    /// Тип значения: `T` для поля `Option<T>`.
+    fn value_ty(&self) -> &Type {
+        option_inner(self.ty).unwrap_or(self.ty)
+    }
+
+    /// Размер поля, байт (выражение).
+    fn size(&self) -> TokenStream {
+        match &self.width {
+            Some(width) => quote! { (#width) },
+            None => {
+                let (ty, binary_field) = (self.value_ty(), binary_field_trait(&commons_root()));
+                quote! { <#ty as #binary_field>::SIZE }
+            }
+        }
+    }
+}
+
+/// Ширина поля из атрибута `#[binary(width = N)]`.
+fn width(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    let mut width = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("binary")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("width") {
+                width = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("неизвестный параметр binary"))
+            }
+        })?;
+    }
+    Ok(width)
+}
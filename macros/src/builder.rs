@@ -0,0 +1,296 @@
+//! Генерация построителя структуры (`QuoteBuilder`).
+//!
+//! Состояние обязательных полей хранится в параметрах типа построителя:
+//! `()` — поле не задано, `(T,)` — задано. Сеттер обязательного поля
+//! доступен только в состоянии `()`, а `build` — только когда заданы все
+//! обязательные поля, поэтому пропущенное или повторно заданное поле —
+//! ошибка компиляции.
+
+use crate::paths::{commons_root, quote_error, validate_trait};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DataStruct, DeriveInput, Fields, Ident, Type, spanned::Spanned};
+
+/// Построитель для структуры `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let options = BuilderOptions::from_attrs(&input.attrs)?;
+    let named = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct { fields, .. }) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "QuoteBuilder поддерживает только структуры с именованными полями",
+            ));
+        }
+        Data::Enum(e) => {
+            return Err(syn::Error::new(
+                e.enum_token.span,
+                "QuoteBuilder допустимо использовать только со структурами",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteBuilder допустимо использовать только со структурами",
+            ));
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "QuoteBuilder не поддерживает обобщённые структуры",
+        ));
+    }
+
+    let builder = format_ident!("{}Builder", name);
+    let fields = named
+        .iter()
+        .map(BuilderField::new)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // Параметр типа построителя для каждого обязательного поля.
+    let params: Vec<Option<Ident>> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            matches!(field.kind, FieldKind::Required)
+                .then(|| format_ident!("__QuoteBuilderField{i}"))
+        })
+        .collect();
+    let state = |set: Option<usize>, done: bool| -> Vec<TokenStream> {
+        fields
+            .iter()
+            .zip(&params)
+            .enumerate()
+            .filter_map(|(i, (field, param))| {
+                let param = param.as_ref()?;
+                let ty = field.ty;
+                Some(match (set == Some(i), done) {
+                    (true, false) => quote! { () },
+                    (true, true) | (false, true) => quote! { (#ty,) },
+                    (false, false) => quote! { #param },
+                })
+            })
+            .collect()
+    };
+    let all_params: Vec<&Ident> = params.iter().flatten().collect();
+
+    let storage = fields.iter().zip(&params).map(|(field, param)| {
+        let (ident, ty) = (field.ident, field.ty);
+        match field.kind {
+            FieldKind::Required => quote! { #ident: #param },
+            FieldKind::Optional(_) => quote! { #ident: #ty },
+            FieldKind::Default(_) => quote! { #ident: ::std::option::Option<#ty> },
+        }
+    });
+    let empty = fields.iter().map(|field| {
+        let ident = field.ident;
+        match field.kind {
+            FieldKind::Required => quote! { #ident: () },
+            _ => quote! { #ident: ::std::option::Option::None },
+        }
+    });
+
+    let setters = fields.iter().enumerate().map(|(i, field)| {
+        let ident = field.ident;
+        let doc = format!("Задать поле `{ident}`.");
+        match &field.kind {
+            FieldKind::Required => {
+                let ty = field.ty;
+                let before = state(Some(i), false);
+                let after: Vec<TokenStream> = fields
+                    .iter()
+                    .zip(&params)
+                    .enumerate()
+                    .filter_map(|(j, (_, param))| {
+                        let param = param.as_ref()?;
+                        Some(match i == j {
+                            true => quote! { (#ty,) },
+                            false => quote! { #param },
+                        })
+                    })
+                    .collect();
+                let impl_params: Vec<&Ident> = params
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .filter_map(|(_, param)| param.as_ref())
+                    .collect();
+                let moves = fields.iter().map(|other| {
+                    let other = other.ident;
+                    match other == ident {
+                        true => quote! { #other: (value.into(),) },
+                        false => quote! { #other: self.#other },
+                    }
+                });
+                quote! {
+                    impl<#(#impl_params),*> #builder<#(#before),*> {
+                        #[doc = #doc]
+                        pub fn #ident(self, value: impl ::std::convert::Into<#ty>) -> #builder<#(#after),*> {
+                            #builder { #(#moves),* }
+                        }
+                    }
+                }
+            }
+            FieldKind::Optional(inner) | FieldKind::Default(inner) => quote! {
+                impl<#(#all_params),*> #builder<#(#all_params),*> {
+                    #[doc = #doc]
+                    pub fn #ident(mut self, value: impl ::std::convert::Into<#inner>) -> Self {
+                        self.#ident = ::std::option::Option::Some(value.into());
+                        self
+                    }
+                }
+            },
+        }
+    });
+
+    let done = state(None, true);
+    let assigns = fields.iter().map(|field| {
+        let ident = field.ident;
+        match field.kind {
+            FieldKind::Required => quote! { #ident: self.#ident.0 },
+            FieldKind::Optional(_) => quote! { #ident: self.#ident },
+            FieldKind::Default(_) => quote! { #ident: self.#ident.unwrap_or_default() },
+        }
+    });
+    let try_build = options.validate.then(|| {
+        let (validate, error) = (
+            validate_trait(&commons_root()),
+            quote_error(&commons_root()),
+        );
+        quote! {
+            /// Собрать значение и проверить его (`Validate::validate`).
+            pub fn try_build(self) -> Result<#name, #error> {
+                let value = self.build();
+                #validate::validate(&value)?;
+                Ok(value)
+            }
+        }
+    });
+    let builder_doc = format!("Построитель [`{name}`].");
+    let defaults = all_params.iter().map(|param| quote! { #param = () });
+
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #[must_use]
+        #vis struct #builder<#(#defaults),*> {
+            #(#storage),*
+        }
+
+        impl #builder {
+            /// Построитель без заданных полей.
+            pub fn new() -> Self {
+                #builder { #(#empty),* }
+            }
+        }
+
+        impl ::std::default::Default for #builder {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #name {
+            #[doc = #builder_doc]
+            pub fn builder() -> #builder {
+                #builder::new()
+            }
+        }
+
+        #(#setters)*
+
+        impl #builder<#(#done),*> {
+            /// Собрать значение; поля по умолчанию, которые не заданы,
+            /// получают `Default::default()`.
+            pub fn build(self) -> #name {
+                #name { #(#assigns),* }
+            }
+
+            #try_build
+        }
+    })
+}
+
+/// Параметры `QuoteBuilder` из атрибута `#[quote_builder(...)]`.
+#[derive(Default)]
+struct BuilderOptions {
+    /// Генерировать `try_build` с проверкой `Validate`.
+    validate: bool,
+}
+
+impl BuilderOptions {
+    /// Прочитать параметры из атрибутов структуры.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("quote_builder"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("validate") {
+                    options.validate = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр quote_builder"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Вид поля в построителе.
+enum FieldKind<'a> {
+    /// Обязательное поле: без него `build` недоступен.
+    Required,
+    /// Поле `Option<T>`: сеттер принимает `T`, по умолчанию `None`.
+    Optional(&'a Type),
+    /// Поле `#[builder(default)]`: по умолчанию `Default::default()`.
+    Default(&'a Type),
+}
+
+/// Поле структуры в построителе.
+struct BuilderField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    kind: FieldKind<'a>,
+}
+
+impl<'a> BuilderField<'a> {
+    fn new(field: &'a syn::Field) -> syn::Result<Self> {
+        let mut default = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("builder"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр builder"))
+                }
+            })?;
+        }
+        let kind = match (option_inner(&field.ty), default) {
+            (Some(inner), _) => FieldKind::Optional(inner),
+            (None, true) => FieldKind::Default(&field.ty),
+            (None, false) => FieldKind::Required,
+        };
+        Ok(Self {
+            ident: field
+                .ident
+                .as_ref()
+                .expect("у поля именованной структуры есть имя"),
+            ty: &field.ty,
+            kind,
+        })
+    }
+}
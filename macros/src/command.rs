@@ -0,0 +1,291 @@
+//! Генерация разбора команд текстового протокола (`QuoteCommand`).
+//!
+//! Команда — строка варианта и аргументы через пробельные символы:
+//! `stream udp://127.0.0.1:34254 AAPL,MSFT`. Аргументы — поля варианта
+//! по порядку, каждое разбирается своим `FromStr`.
+
+use crate::enum_display::variant_strs;
+use crate::paths::{QuoteAttrs, tr, value_err};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use syn::{Data, DeriveInput, Field, Fields, Ident, LitStr, Type, spanned::Spanned};
+
+/// Реализация `FromStr` и строки использования для перечисления `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let attrs = QuoteAttrs::from_attrs(&input.attrs)?;
+    let (error, root) = (&attrs.error, &attrs.root);
+    let variants = match &input.data {
+        Data::Enum(e) => &e.variants,
+        Data::Struct(s) => {
+            return Err(syn::Error::new(
+                s.struct_token.span,
+                "QuoteCommand допустимо использовать только с enum",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteCommand допустимо использовать только с enum",
+            ));
+        }
+    };
+
+    let mut strs = Vec::new();
+    let mut usages = Vec::new();
+    let mut usage_arms = Vec::new();
+    let mut parse_arms = Vec::new();
+    let mut seen: HashMap<String, &Ident> = HashMap::new();
+
+    for v in variants {
+        let ident = &v.ident;
+        let (lit, aliases) = variant_strs(ident, &v.attrs)?;
+        // Команды разбираются без учёта регистра.
+        let patterns: Vec<LitStr> = std::iter::once(&lit)
+            .chain(&aliases)
+            .map(|s| LitStr::new(&s.value().to_lowercase(), s.span()))
+            .collect();
+        for s in &patterns {
+            if let Some(other) = seen.insert(s.value(), ident) {
+                return Err(syn::Error::new(
+                    s.span(),
+                    format!("строка \"{}\" уже задана для варианта {other}", s.value()),
+                ));
+            }
+        }
+
+        let args = v
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| CommandArg::new(i, field))
+            .collect::<syn::Result<Vec<_>>>()?;
+        check_order(&args)?;
+
+        let usage = std::iter::once(lit.value())
+            .chain(args.iter().map(CommandArg::usage))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let usage = LitStr::new(&usage, lit.span());
+
+        let missing = value_err(
+            error,
+            tr(
+                root,
+                quote! { format!("команда неполная, ожидается: {}", #usage) },
+                quote! { format!("incomplete command, expected: {}", #usage) },
+            ),
+        );
+        let parses = args.iter().map(|arg| arg.parse(&attrs, &missing));
+        let vars = args.iter().map(|arg| &arg.var);
+        let value = match &v.fields {
+            Fields::Named(_) => quote! { #name::#ident { #(#vars),* } },
+            Fields::Unnamed(_) => quote! { #name::#ident(#(#vars),*) },
+            Fields::Unit => quote! { #name::#ident },
+        };
+        let extra = value_err(
+            error,
+            tr(
+                root,
+                quote! {
+                    format!("лишние аргументы '{}', ожидается: {}", extra.join(" "), #usage)
+                },
+                quote! {
+                    format!("extra arguments '{}', expected: {}", extra.join(" "), #usage)
+                },
+            ),
+        );
+        parse_arms.push(quote! {
+            #(#patterns)|* => {
+                #(#parses)*
+                let extra: Vec<&str> = args.collect();
+                if !extra.is_empty() {
+                    return Err(#extra);
+                }
+                Ok(#value)
+            }
+        });
+        usage_arms.push(quote! { #name::#ident { .. } => #usage, });
+        strs.push(lit);
+        usages.push(usage);
+    }
+
+    let empty = value_err(
+        error,
+        tr(
+            root,
+            quote! { "пустая команда".to_string() },
+            quote! { "empty command".to_string() },
+        ),
+    );
+    let unknown = value_err(
+        error,
+        tr(
+            root,
+            quote! {
+                format!(
+                    "неизвестная команда '{}', ожидается: {}",
+                    command,
+                    #name::usages().join(", ")
+                )
+            },
+            quote! {
+                format!(
+                    "unknown command '{}', expected: {}",
+                    command,
+                    #name::usages().join(", ")
+                )
+            },
+        ),
+    );
+
+    Ok(quote! {
+        impl #name {
+            /// Строки команд (без синонимов) в порядке объявления.
+            pub fn variant_strs() -> &'static [&'static str] {
+                &[#(#strs),*]
+            }
+
+            /// Строки использования всех команд в порядке объявления.
+            pub fn usages() -> &'static [&'static str] {
+                &[#(#usages),*]
+            }
+
+            /// Строка использования команды: `stream <udp> <tickers...>`.
+            pub fn usage(&self) -> &'static str {
+                match self { #(#usage_arms)* }
+            }
+        }
+
+        impl std::str::FromStr for #name {
+            type Err = #error;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut args = s.split_whitespace();
+                let command = args.next().ok_or_else(|| #empty)?;
+                match command.to_lowercase().as_str() {
+                    #(#parse_arms)*
+                    _ => Err(#unknown),
+                }
+            }
+        }
+    })
+}
+
+/// Аргумент команды — поле варианта.
+struct CommandArg<'a> {
+    /// Имя переменной в разборе.
+    var: Ident,
+    /// Имя аргумента в строке использования и ошибках.
+    name: String,
+    /// Тип значения: `T` для необязательного аргумента `Option<T>`.
+    value_ty: &'a Type,
+    /// Аргумент `Option<T>` можно не указывать.
+    optional: bool,
+    /// Аргумент забирает остаток строки (`#[arg(rest)]`).
+    rest: bool,
+    span: proc_macro2::Span,
+}
+
+impl<'a> CommandArg<'a> {
+    fn new(index: usize, field: &'a Field) -> syn::Result<Self> {
+        let var = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("arg{}", index + 1),
+        };
+        let mut name = var.to_string();
+        let mut rest = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("arg"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rest") {
+                    rest = true;
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    name = lit.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр arg"))
+                }
+            })?;
+        }
+        let inner = option_inner(&field.ty);
+        Ok(Self {
+            var,
+            name,
+            value_ty: inner.unwrap_or(&field.ty),
+            optional: inner.is_some(),
+            rest,
+            span: field.span(),
+        })
+    }
+
+    /// Аргумент в строке использования: `<udp>`, `[note]`, `<tickers...>`.
+    fn usage(&self) -> String {
+        let dots = if self.rest { "..." } else { "" };
+        match self.optional {
+            true => format!("[{}{dots}]", self.name),
+            false => format!("<{}{dots}>", self.name),
+        }
+    }
+
+    /// Разбор аргумента из итератора `args` в переменную `var`.
+    fn parse(&self, attrs: &QuoteAttrs, missing: &TokenStream) -> TokenStream {
+        let (var, ty, name) = (&self.var, self.value_ty, &self.name);
+        let bad = value_err(
+            &attrs.error,
+            tr(
+                &attrs.root,
+                quote! { format!("некорректный аргумент {} '{}': {}", #name, arg, err) },
+                quote! { format!("invalid argument {} '{}': {}", #name, arg, err) },
+            ),
+        );
+        let parse = quote! { arg.parse::<#ty>().map_err(|err| #bad)? };
+        // Остаток строки — аргументы через пробел.
+        let next = match self.rest {
+            true => quote! {
+                Some(args.by_ref().collect::<Vec<_>>().join(" ")).filter(|arg| !arg.is_empty())
+            },
+            false => quote! { args.next() },
+        };
+        match self.optional {
+            true => quote! {
+                let #var = match #next {
+                    Some(arg) => Some(#parse),
+                    None => None,
+                };
+            },
+            false => quote! {
+                let #var = {
+                    let arg = #next.ok_or_else(|| #missing)?;
+                    #parse
+                };
+            },
+        }
+    }
+}
+
+/// Остаток строки — только у последнего аргумента, необязательные
+/// аргументы — только после обязательных.
+fn check_order(args: &[CommandArg]) -> syn::Result<()> {
+    for (i, arg) in args.iter().enumerate() {
+        if arg.rest && i + 1 != args.len() {
+            return Err(syn::Error::new(
+                arg.span,
+                "параметр rest допустим только у последнего аргумента",
+            ));
+        }
+        if i > 0 && args[i - 1].optional && !arg.optional {
+            return Err(syn::Error::new(
+                arg.span,
+                "обязательный аргумент после необязательного",
+            ));
+        }
+    }
+    Ok(())
+}
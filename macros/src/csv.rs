@@ -0,0 +1,194 @@
+//! Генерация реализации `CsvRecord` (`QuoteCsv`).
+
+use crate::display::{FieldOptions, display_with};
+use crate::generics::with_bounds;
+use crate::paths::{commons_root, csv_record_trait, quote_error};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Fields, Ident, LitStr, Type, spanned::Spanned};
+
+/// Реализация `CsvRecord` для структуры `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (csv_record, error) = (
+        csv_record_trait(&commons_root()),
+        quote_error(&commons_root()),
+    );
+    let named = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct { fields, .. }) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "QuoteCsv поддерживает только структуры с именованными полями",
+            ));
+        }
+        Data::Enum(e) => {
+            return Err(syn::Error::new(
+                e.enum_token.span,
+                "QuoteCsv допустимо использовать только со структурами",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteCsv допустимо использовать только со структурами",
+            ));
+        }
+    };
+
+    let fields = named
+        .iter()
+        .map(|field| {
+            let options = FieldOptions::from_attrs(&field.attrs)?;
+            if options.flatten {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "QuoteCsv не поддерживает параметр flatten",
+                ));
+            }
+            Ok(CsvField {
+                ident: field
+                    .ident
+                    .as_ref()
+                    .expect("у поля именованной структуры есть имя"),
+                ty: &field.ty,
+                // Поле `Option<T>` необязательно и без `#[quote(optional)]`.
+                value_ty: option_inner(&field.ty),
+                options,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let columns: Vec<&CsvField> = fields.iter().filter(|field| !field.options.skip).collect();
+
+    let header = columns
+        .iter()
+        .map(|field| LitStr::new(&field.ident.to_string(), field.ident.span()));
+    let to_fields = columns.iter().map(|field| field.to_field());
+    let from_fields = fields.iter().map(|field| {
+        let ident = field.ident;
+        match columns.iter().position(|column| column.ident == ident) {
+            None => quote! { #ident: ::std::default::Default::default(), },
+            Some(i) => {
+                let parse = field.parse();
+                quote! {
+                    #ident: {
+                        let value = fields.get(#i).map(|value| value.trim()).unwrap_or_default();
+                        #parse.map_err(|_| #error::value_err(format!(
+                            "некорректное значение {}: '{}'",
+                            stringify!(#ident), value
+                        )))?
+                    },
+                }
+            }
+        }
+    });
+
+    // Обобщённые параметры — как у `QuoteDisplay`: значениям колонок нужны
+    // Display и FromStr, пропущенным полям и полям с `default` — Default.
+    let plain = || columns.iter().filter(|field| field.options.with.is_none());
+    let mut generics = with_bounds(
+        &input.generics,
+        plain().map(|field| field.value_ty()),
+        quote! { ::std::fmt::Display },
+    );
+    generics = with_bounds(
+        &generics,
+        plain().map(|field| field.value_ty()),
+        quote! { ::std::str::FromStr },
+    );
+    generics = with_bounds(
+        &generics,
+        fields
+            .iter()
+            .filter(|field| field.options.skip || field.options.default)
+            .map(|field| field.ty),
+        quote! { ::std::default::Default },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #csv_record for #name #ty_generics #where_clause {
+            const HEADER: &'static [&'static str] = &[#(#header),*];
+
+            fn to_fields(&self) -> Vec<String> {
+                vec![#(#to_fields),*]
+            }
+
+            fn from_fields(fields: &[String]) -> Result<Self, #error> {
+                Ok(Self {
+                    #(#from_fields)*
+                })
+            }
+        }
+    })
+}
+
+/// Поле структуры с его параметрами.
+struct CsvField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    /// `T` для поля `Option<T>`.
+    value_ty: Option<&'a Type>,
+    options: FieldOptions,
+}
+
+impl CsvField<'_> {
+    /// Тип значения в колонке.
+    fn value_ty(&self) -> &Type {
+        self.value_ty.unwrap_or(self.ty)
+    }
+
+    /// Значение поля строкой; `None` — пустая строка.
+    fn to_field(&self) -> TokenStream {
+        let ident = self.ident;
+        let value_ty = self.value_ty();
+        let value = match self.value_ty {
+            Some(_) => quote! { value },
+            None => quote! { &self.#ident },
+        };
+        let to_string = match (&self.options.format, &self.options.with) {
+            (_, Some(with)) => {
+                let display = display_with(value_ty, &value, with);
+                quote! { ::std::string::ToString::to_string(&#display) }
+            }
+            (Some(format), None) => quote! { format!(#format, #value) },
+            (None, None) => quote! { ::std::string::ToString::to_string(#value) },
+        };
+        match self.value_ty {
+            Some(_) => quote! {
+                self.#ident.as_ref().map(|value| #to_string).unwrap_or_default()
+            },
+            None => to_string,
+        }
+    }
+
+    /// Разбор значения `value`; результат — `Result`. Пустое значение
+    /// поля `Option<T>` — `None`, поля с `default` — `Default::default()`.
+    fn parse(&self) -> TokenStream {
+        let parse = match &self.options.with {
+            Some(with) => quote! { #with::parse(value) },
+            None => quote! { value.parse() },
+        };
+        if self.value_ty.is_some() {
+            quote! {
+                match value {
+                    "" => Ok(None),
+                    value => #parse.map(Some),
+                }
+            }
+        } else if self.options.default {
+            quote! {
+                match value {
+                    "" => Ok(::std::default::Default::default()),
+                    value => #parse,
+                }
+            }
+        } else {
+            parse
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Генерация `Display` и `FromStr` для структур (`QuoteDisplay`).
+
+use crate::generics::with_bounds;
+use crate::paths::{QuoteAttrs, display_segments_trait, escape_module, value_err};
+use crate::types::option_inner;
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Field, Fields, Ident, LitBool, LitInt, LitStr, Path,
+    Token, Type, spanned::Spanned,
+};
+
+/// Реализации `Display` и `FromStr` для структуры `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+    let options = DisplayOptions::from_attrs(&input.attrs)?;
+    let QuoteAttrs { error, root } = QuoteAttrs::from_attrs(&input.attrs)?;
+    let delimiter = &options.delimiter;
+
+    // Сбор названия полей.
+    let fields_name = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct { fields, .. }) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "QuoteDisplay поддерживает только структуры с именованными полями",
+            ));
+        }
+        Data::Enum(e) => {
+            return Err(syn::Error::new(
+                e.enum_token.span,
+                "QuoteDisplay допустимо использовать только со структурами",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteDisplay допустимо использовать только со структурами",
+            ));
+        }
+    };
+
+    let fields = fields_name
+        .iter()
+        .map(WireField::new)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // Поля строкового формата, по порядку позиций.
+    let wire = wire_order(&fields)?;
+    // Необязательные поля и поля с `default` в конце строки можно опустить:
+    // строки, записанные до их появления, по-прежнему разбираются.
+    let required_fields = wire.len()
+        - wire
+            .iter()
+            .rev()
+            .take_while(|field| field.options.optional || field.options.default)
+            .count();
+    // Вложенная структура (`flatten`) занимает несколько частей строки,
+    // поэтому позиции частей и их число — выражения `usize`.
+    let segments = display_segments_trait(&root);
+    let offsets: Vec<_> = (0..=wire.len())
+        .map(|i| segment_count(&wire[..i], None, &segments))
+        .collect();
+    let fields_count = &offsets[wire.len()];
+    let required_count = match wire[..required_fields].split_last() {
+        // Последняя вложенная структура сама допускает короткую строку.
+        Some((last, rest)) if last.options.flatten && required_fields == wire.len() => {
+            let ty = last.ty;
+            segment_count(
+                rest,
+                Some(quote! { <#ty as #segments>::REQUIRED_SEGMENTS }),
+                &segments,
+            )
+        }
+        _ => offsets[required_fields].clone(),
+    };
+    let expected = match wire.iter().any(|field| field.options.flatten) {
+        true => quote! {
+            match (#required_count, #fields_count) {
+                (required, count) if required == count => count.to_string(),
+                (required, count) => format!("от {required} до {count}"),
+            }
+        },
+        false if required_fields == wire.len() => wire.len().to_string().into_token_stream(),
+        false => format!("от {required_fields} до {}", wire.len()).into_token_stream(),
+    };
+
+    // Display: поля через разделитель, каждое в своём формате.
+    let escape = escape_module(&root);
+    let fields_writes: Vec<_> = wire
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let write_delimiter = (i > 0).then(|| quote! { f.write_str(#delimiter)?; });
+            let write_field = match (field.options.flatten, options.escape) {
+                (true, escaped) => {
+                    let (ident, ty) = (field.ident, field.ty);
+                    let write_part = match escaped {
+                        true => quote! {
+                            ::std::fmt::Write::write_str(
+                                &mut #escape::Escaper::new(f, #delimiter),
+                                part,
+                            )?;
+                        },
+                        false => quote! { f.write_str(part)?; },
+                    };
+                    quote! {
+                        for (i, part) in <#ty as #segments>::segments(&self.#ident).iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(#delimiter)?;
+                            }
+                            #write_part
+                        }
+                    }
+                }
+                (false, true) => {
+                    let write_field = field.write_escaped();
+                    quote! {{
+                        let mut out = #escape::Escaper::new(f, #delimiter);
+                        #write_field
+                    }}
+                }
+                (false, false) => field.write(),
+            };
+            quote! { #write_delimiter #write_field }
+        })
+        .collect();
+    let split = match options.escape {
+        true => quote! {
+            let parts = #escape::split_escaped(s, #delimiter);
+            let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        },
+        false => quote! {
+            let parts: Vec<&str> = s.split(#delimiter).collect();
+        },
+    };
+
+    // Разбор частей `parts` строки `s`; `error` строит ошибку из сообщения.
+    let parse_parts = |error: &dyn Fn(TokenStream) -> TokenStream| {
+        let fields_parses = fields.iter().map(|field| {
+            let field_name = field.ident;
+            let Some(i) = wire.iter().position(|wire| wire.ident == field_name) else {
+                return quote! {
+                    #field_name: ::std::default::Default::default(),
+                };
+            };
+            let offset = &offsets[i];
+            if field.options.flatten {
+                let (ty, end) = (field.ty, &offsets[i + 1]);
+                let err = error(quote! {
+                    format!(
+                        "Ошибка парсинга строки {} на позиции {} для поля {}: {}",
+                        s, #offset, stringify!(#field_name), err
+                    )
+                });
+                return quote! {
+                    #field_name: {
+                        let end = parts.len().min(#end);
+                        <#ty as #segments>::from_segments(&parts[#offset..end])
+                            .map_err(|err| #err)?
+                    },
+                };
+            }
+            let part = match i < required_fields {
+                true => quote! { parts[#offset] },
+                false => quote! { parts.get(#offset).copied().unwrap_or_default() },
+            };
+            let parse = field.parse(part);
+            let err = error(quote! {
+                format!(
+                    "Ошибка парсинга строки {} на позиции {} для поля {}",
+                    s, #offset, stringify!(#field_name)
+                )
+            });
+            quote! {
+                #field_name: #parse.map_err(|_| #err)?,
+            }
+        });
+        let count_err = error(quote! {
+            format!(
+                "ожидается {} типа, разделённых '{}', получено {} в строке {}",
+                #expected,
+                #delimiter,
+                parts.len(),
+                s
+            )
+        });
+        quote! {
+            if !(#required_count..=#fields_count).contains(&parts.len()) {
+                Err(#count_err)
+            } else {
+                Ok(Self {
+                    #(#fields_parses)*
+                })
+            }
+        }
+    };
+
+    // Обобщённые параметры: значениям полей нужны Display и FromStr,
+    // вложенным структурам — DisplaySegments, пропущенным полям и полям
+    // с `default` — Default. Поля с `with` ограничений не требуют.
+    let plain = || {
+        wire.iter()
+            .filter(|field| field.options.with.is_none() && !field.options.flatten)
+    };
+    let nested = || {
+        wire.iter()
+            .filter(|field| field.options.flatten)
+            .map(|field| field.ty)
+    };
+    let mut display_generics = with_bounds(
+        &input.generics,
+        plain().map(|field| field.value_ty),
+        quote! { ::std::fmt::Display },
+    );
+    display_generics = with_bounds(&display_generics, nested(), segments.clone());
+    let mut parse_generics = with_bounds(
+        &input.generics,
+        plain().map(|field| field.value_ty),
+        quote! { ::std::str::FromStr },
+    );
+    parse_generics = with_bounds(&parse_generics, nested(), segments.clone());
+    let skipped = fields
+        .iter()
+        .filter(|field| field.options.skip || field.options.default);
+    parse_generics = with_bounds(
+        &parse_generics,
+        skipped.map(|field| field.ty),
+        quote! { ::std::default::Default },
+    );
+    let segments_generics = with_bounds(
+        &parse_generics,
+        plain().map(|field| field.value_ty),
+        quote! { ::std::fmt::Display },
+    );
+    let (display_impl, ty_generics, display_where) = display_generics.split_for_impl();
+    let (parse_impl, _, parse_where) = parse_generics.split_for_impl();
+    let (segments_impl, _, segments_where) = segments_generics.split_for_impl();
+    let finish = match options.newline {
+        true => quote! { writeln!(f) },
+        false => quote! { Ok(()) },
+    };
+
+    let display = options.display.then(|| {
+        quote! {
+            impl #display_impl std::fmt::Display for #struct_name #ty_generics #display_where {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    #(#fields_writes)*
+                    #finish
+                }
+            }
+        }
+    });
+    let parse = options.parse.then(|| {
+        let parse_parts = parse_parts(&|message| value_err(&error, message));
+        quote! {
+            impl #parse_impl std::str::FromStr for #struct_name #ty_generics #parse_where {
+                type Err = #error;
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    // Строка, прочитанная вместе с переводом строки.
+                    let s = s.trim_end_matches(['\r', '\n']);
+                    #split
+                    #parse_parts
+                }
+            }
+        }
+    });
+    // Вложение в другие структуры — только при обоих направлениях.
+    let nesting = (options.display && options.parse).then(|| {
+        let to_segments = wire.iter().map(|field| field.to_segments(&segments));
+        let parse_parts = parse_parts(&|message| message);
+        quote! {
+            impl #segments_impl #segments for #struct_name #ty_generics #segments_where {
+                const SEGMENTS: usize = #fields_count;
+                const REQUIRED_SEGMENTS: usize = #required_count;
+
+                fn segments(&self) -> Vec<String> {
+                    let mut segments = Vec::with_capacity(Self::SEGMENTS);
+                    #(#to_segments)*
+                    segments
+                }
+
+                fn from_segments(parts: &[&str]) -> Result<Self, String> {
+                    let s = parts.join(#delimiter);
+                    #parse_parts
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #display
+        #parse
+        #nesting
+    })
+}
+
+/// Число частей строки, занимаемых полями `fields` и слагаемым `extra`:
+/// выражение `usize`, постоянное без вложенных структур; `segments_trait`
+/// — путь трейта `DisplaySegments`.
+fn segment_count(
+    fields: &[&WireField],
+    extra: Option<TokenStream>,
+    segments_trait: &TokenStream,
+) -> TokenStream {
+    let plain = fields.iter().filter(|field| !field.options.flatten).count();
+    let terms: Vec<_> = fields
+        .iter()
+        .filter(|field| field.options.flatten)
+        .map(|field| {
+            let ty = field.ty;
+            quote! { <#ty as #segments_trait>::SEGMENTS }
+        })
+        .chain(extra)
+        .collect();
+    match (plain, terms.as_slice()) {
+        (plain, []) => quote! { #plain },
+        (0, [term]) => term.clone(),
+        (0, terms) => quote! { (#(#terms)+*) },
+        (plain, terms) => quote! { (#plain #(+ #terms)*) },
+    }
+}
+
+/// Поля строкового формата по порядку позиций.
+///
+/// Без атрибутов `#[pos(n)]` позиции задаёт порядок объявления полей.
+/// С ними позиция нужна каждому полю формата, а позиции должны идти
+/// подряд с 0: так порядок полей в структуре можно менять, не меняя
+/// формат.
+fn wire_order<'f, 'a>(fields: &'f [WireField<'a>]) -> syn::Result<Vec<&'f WireField<'a>>> {
+    if let Some(field) = fields
+        .iter()
+        .find(|field| field.options.skip && field.pos.is_some())
+    {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            "у поля с quote(skip) не может быть позиции",
+        ));
+    }
+    let mut wire: Vec<&WireField> = fields.iter().filter(|field| !field.options.skip).collect();
+    let Some(first) = wire.iter().find(|field| field.pos.is_some()) else {
+        return Ok(wire);
+    };
+    if let Some(field) = wire.iter().find(|field| field.pos.is_none()) {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            format!(
+                "позиции заданы (поле {}), укажите #[pos(n)] и для поля {}",
+                first.ident, field.ident
+            ),
+        ));
+    }
+
+    wire.sort_by_key(|field| field.pos.map(|(pos, _)| pos));
+    for (i, field) in wire.iter().enumerate() {
+        let (pos, span) = field.pos.expect("позиция задана у всех полей");
+        if pos < i {
+            return Err(syn::Error::new(
+                span,
+                format!("позиция {pos} уже задана для поля {}", wire[i - 1].ident),
+            ));
+        }
+        if pos > i {
+            return Err(syn::Error::new(
+                span,
+                format!("пропущена позиция {i}: позиции полей идут подряд с 0"),
+            ));
+        }
+    }
+    Ok(wire)
+}
+
+/// Параметры `QuoteDisplay` из атрибута контейнера `#[quote_display(...)]`.
+struct DisplayOptions {
+    /// Разделитель полей.
+    delimiter: String,
+    /// Экранировать разделитель в значениях (`commons::escape`).
+    escape: bool,
+    /// Завершать строку переводом строки (`writeln!`).
+    newline: bool,
+    /// Генерировать `Display`; отключается `only = "fromstr"`.
+    display: bool,
+    /// Генерировать `FromStr`; отключается `only = "display"`.
+    parse: bool,
+}
+
+impl DisplayOptions {
+    /// Разделитель по умолчанию.
+    const DELIMITER: &str = "|";
+
+    /// Прочитать параметры из атрибутов структуры.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self {
+            delimiter: Self::DELIMITER.to_string(),
+            escape: false,
+            newline: false,
+            display: true,
+            parse: true,
+        };
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("quote_display"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("delimiter") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    if lit.value().is_empty() {
+                        return Err(syn::Error::new(lit.span(), "пустой разделитель полей"));
+                    }
+                    options.delimiter = lit.value();
+                    Ok(())
+                } else if meta.path.is_ident("escape") {
+                    options.escape = true;
+                    Ok(())
+                } else if meta.path.is_ident("newline") {
+                    options.newline = match meta.input.peek(Token![=]) {
+                        true => meta.value()?.parse::<LitBool>()?.value,
+                        false => true,
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("only") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    (options.display, options.parse) = match lit.value().as_str() {
+                        "display" => (true, false),
+                        "fromstr" => (false, true),
+                        _ => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "ожидается only = \"display\" или \"fromstr\"",
+                            ));
+                        }
+                    };
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр quote_display"))
+                }
+            })?;
+            if options.escape && options.delimiter.starts_with('\\') {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "при escape разделитель не может начинаться с `\\`",
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Поле структуры с его параметрами.
+struct WireField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    /// Тип значения в строке: `T` для необязательного поля `Option<T>`,
+    /// иначе тип поля.
+    value_ty: &'a Type,
+    /// Позиция из атрибута `#[pos(n)]`.
+    pos: Option<(usize, Span)>,
+    options: FieldOptions,
+}
+
+impl<'a> WireField<'a> {
+    fn new(field: &'a Field) -> syn::Result<Self> {
+        let options = FieldOptions::from_attrs(&field.attrs)?;
+        let mut pos = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("pos"))
+        {
+            if pos.is_some() {
+                return Err(syn::Error::new_spanned(attr, "позиция поля уже задана"));
+            }
+            let lit: LitInt = attr.parse_args()?;
+            pos = Some((lit.base10_parse()?, lit.span()));
+        }
+        let value_ty = match options.optional {
+            true => option_inner(&field.ty).ok_or_else(|| {
+                syn::Error::new(
+                    field.ty.span(),
+                    "параметр optional допустим только для поля Option<T>",
+                )
+            })?,
+            false => &field.ty,
+        };
+        Ok(Self {
+            ident: field
+                .ident
+                .as_ref()
+                .expect("у поля именованной структуры есть имя"),
+            ty: &field.ty,
+            value_ty,
+            pos,
+            options,
+        })
+    }
+
+    /// Запись значения поля в форматтер `f`; `None` необязательного поля
+    /// записывается пустой строкой.
+    fn write(&self) -> TokenStream {
+        let ident = self.ident;
+        let value = match self.options.optional {
+            true => quote! { value },
+            false => quote! { &self.#ident },
+        };
+        let write = match (&self.options.format, &self.options.with) {
+            (_, Some(with)) => quote! { #with::fmt(#value, f)?; },
+            (Some(format), None) => quote! { write!(f, #format, #value)?; },
+            (None, None) => quote! { write!(f, "{}", #value)?; },
+        };
+        match self.options.optional {
+            true => quote! { if let Some(value) = &self.#ident { #write } },
+            false => write,
+        }
+    }
+
+    /// Запись значения поля в экранирующий `out` (`commons::escape::Escaper`).
+    fn write_escaped(&self) -> TokenStream {
+        let ident = self.ident;
+        let value = match self.options.optional {
+            true => quote! { value },
+            false => quote! { &self.#ident },
+        };
+        let args = match (&self.options.format, &self.options.with) {
+            (_, Some(with)) => {
+                let display = display_with(self.value_ty, &value, with);
+                quote! { "{}", #display }
+            }
+            (Some(format), None) => quote! { #format, #value },
+            (None, None) => quote! { "{}", #value },
+        };
+        let write = quote! {
+            ::std::fmt::Write::write_fmt(&mut out, format_args!(#args))?;
+        };
+        match self.options.optional {
+            true => quote! { if let Some(value) = &self.#ident { #write } },
+            false => write,
+        }
+    }
+
+    /// Добавление значений поля в вектор `segments`: значение поля строкой
+    /// (`None` — пустая строка) или поля вложенной структуры (трейт
+    /// `segments_trait`).
+    fn to_segments(&self, segments_trait: &TokenStream) -> TokenStream {
+        let ident = self.ident;
+        if self.options.flatten {
+            let ty = self.ty;
+            return quote! { segments.extend(<#ty as #segments_trait>::segments(&self.#ident)); };
+        }
+        let value = match self.options.optional {
+            true => quote! { value },
+            false => quote! { &self.#ident },
+        };
+        let to_string = match (&self.options.format, &self.options.with) {
+            (_, Some(with)) => {
+                let display = display_with(self.value_ty, &value, with);
+                quote! { ::std::string::ToString::to_string(&#display) }
+            }
+            (Some(format), None) => quote! { format!(#format, #value) },
+            (None, None) => quote! { ::std::string::ToString::to_string(#value) },
+        };
+        match self.options.optional {
+            true => quote! {
+                segments.push(self.#ident.as_ref().map(|value| #to_string).unwrap_or_default());
+            },
+            false => quote! { segments.push(#to_string); },
+        }
+    }
+
+    /// Разбор значения поля из строки `part`; результат — `Result`.
+    /// Пустая строка необязательного поля — `None`, поля с `default` —
+    /// `Default::default()`.
+    fn parse(&self, part: TokenStream) -> TokenStream {
+        let parse = |part: TokenStream| match &self.options.with {
+            Some(with) => quote! { #with::parse(#part) },
+            None => quote! { #part.parse() },
+        };
+        if self.options.optional {
+            let parse = parse(quote! { part });
+            quote! {
+                match #part {
+                    "" => Ok(None),
+                    part => #parse.map(Some),
+                }
+            }
+        } else if self.options.default {
+            let parse = parse(quote! { part });
+            quote! {
+                match #part {
+                    "" => Ok(::std::default::Default::default()),
+                    part => #parse,
+                }
+            }
+        } else {
+            parse(part)
+        }
+    }
+}
+
+/// Значение `value` типа `&ty`, выводимое в `Display` функцией `fmt`
+/// модуля `with`.
+pub fn display_with(ty: &Type, value: &TokenStream, with: &Path) -> TokenStream {
+    quote! {{
+        struct With<'a, T>(&'a T, fn(&T, &mut ::std::fmt::Formatter) -> ::std::fmt::Result);
+        impl<T> ::std::fmt::Display for With<'_, T> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                (self.1)(self.0, f)
+            }
+        }
+        With::<#ty>(#value, #with::fmt)
+    }}
+}
+
+/// Параметры поля из атрибута `#[quote(...)]`.
+#[derive(Default)]
+pub struct FieldOptions {
+    /// Поле не входит в строковый формат и при разборе заполняется
+    /// `Default::default()`.
+    pub skip: bool,
+    /// Строка формата значения, например `"{:.2}"`.
+    pub format: Option<LitStr>,
+    /// Модуль с функциями `fmt(&T, &mut Formatter) -> fmt::Result`
+    /// и `parse(&str) -> Result<T, E>`.
+    pub with: Option<Path>,
+    /// Поле `Option<T>`: `None` записывается пустой строкой.
+    pub optional: bool,
+    /// Пустое значение разбирается в `Default::default()`.
+    pub default: bool,
+    /// Поля вложенной структуры записываются на месте поля
+    /// (`commons::traits::DisplaySegments`).
+    pub flatten: bool,
+}
+
+impl FieldOptions {
+    /// Прочитать параметры из атрибутов поля.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("quote")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    options.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("optional") {
+                    options.optional = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    options.default = true;
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    options.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("format") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    if !lit.value().contains('{') {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "в формате поля нет подстановки `{}`",
+                        ));
+                    }
+                    options.format = Some(lit);
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    options.with = Some(lit.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр quote"))
+                }
+            })?;
+            if options.format.is_some() && options.with.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "параметры format и with несовместимы",
+                ));
+            }
+            if options.optional && options.default {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "параметры optional и default несовместимы",
+                ));
+            }
+            if options.skip
+                && (options.format.is_some()
+                    || options.with.is_some()
+                    || options.optional
+                    || options.default)
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "пропущенному полю формат не нужен",
+                ));
+            }
+            if options.flatten
+                && (options.skip
+                    || options.format.is_some()
+                    || options.with.is_some()
+                    || options.optional
+                    || options.default)
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "параметр flatten несовместим с другими параметрами поля",
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
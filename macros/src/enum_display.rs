@@ -0,0 +1,328 @@
+//! Генерация `Display` и `FromStr` для перечислений (`QuoteEnumDisplay`).
+
+use crate::paths::{QuoteAttrs, tr, value_err};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashMap;
+use syn::{
+    Attribute, Data, DeriveInput, Fields, Ident, LitBool, LitInt, LitStr, Token,
+    parse::ParseStream, spanned::Spanned,
+};
+
+/// Разделитель строки варианта и его значения: `limit:42.5`.
+const DATA_SEPARATOR: char = ':';
+
+/// Реализации `Display` и `FromStr` для перечисления `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let options = EnumOptions::from_attrs(&input.attrs)?;
+    let QuoteAttrs { error, root } = QuoteAttrs::from_attrs(&input.attrs)?;
+
+    let variants = match &input.data {
+        Data::Enum(e) => &e.variants,
+        Data::Struct(s) => {
+            return Err(syn::Error::new(
+                s.struct_token.span,
+                "QuoteEnumDisplay допустимо использовать только с enum",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteEnumDisplay допустимо использовать только с enum",
+            ));
+        }
+    };
+
+    let mut to_arms = Vec::new();
+    let mut from_arms = Vec::new();
+    let mut data_arms = Vec::new();
+    let mut units = Vec::new();
+    let mut strs = Vec::new();
+    let mut seen: HashMap<String, &syn::Ident> = HashMap::new();
+    let mut codes = Vec::new();
+    let mut seen_codes: HashMap<u8, &syn::Ident> = HashMap::new();
+
+    for v in variants {
+        let ident = &v.ident;
+        let has_data = match &v.fields {
+            Fields::Unit => false,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => true,
+            fields => {
+                return Err(syn::Error::new(
+                    fields.span(),
+                    "QuoteEnumDisplay поддерживает варианты без полей \
+                     или с одним безымянным полем",
+                ));
+            }
+        };
+        let (lit, aliases) = variant_strs(ident, &v.attrs)?;
+        // Строки, с которыми сравнивается ввод при разборе.
+        let patterns: Vec<LitStr> = std::iter::once(&lit)
+            .chain(&aliases)
+            .map(|s| options.pattern(s))
+            .collect();
+        for s in &patterns {
+            if let Some(other) = seen.insert(s.value(), ident) {
+                return Err(syn::Error::new(
+                    s.span(),
+                    format!("строка \"{}\" уже задана для варианта {other}", s.value()),
+                ));
+            }
+        }
+        strs.push(lit.clone());
+        if let Some((code, span)) = variant_code(&v.attrs)? {
+            if has_data {
+                return Err(syn::Error::new(
+                    span,
+                    "#[code] допустим только у вариантов без полей",
+                ));
+            }
+            if let Some(other) = seen_codes.insert(code, ident) {
+                return Err(syn::Error::new(
+                    span,
+                    format!("код {code} уже задан для варианта {other}"),
+                ));
+            }
+            codes.push((ident, code));
+        }
+        if has_data {
+            to_arms.push(quote! {
+                #name::#ident(value) => write!(f, "{}{}{}", #lit, #DATA_SEPARATOR, value),
+            });
+            let err = value_err(
+                &error,
+                tr(
+                    &root,
+                    quote! {
+                        format!(
+                            "некорректное значение {}::{}: '{}'",
+                            stringify!(#name), stringify!(#ident), value
+                        )
+                    },
+                    quote! {
+                        format!(
+                            "invalid value {}::{}: '{}'",
+                            stringify!(#name), stringify!(#ident), value
+                        )
+                    },
+                ),
+            );
+            data_arms.push(quote! {
+                #(#patterns)|* => value.parse().map(#name::#ident).map_err(|_| #err),
+            });
+        } else {
+            units.push(ident);
+            to_arms.push(quote! { #name::#ident => f.write_str(#lit), });
+            from_arms.push(quote! { #(#patterns)|* => Ok(#name::#ident), });
+        }
+    }
+
+    // Числовые коды: либо у всех вариантов, либо ни у одного.
+    let code_fns = match codes.len() {
+        0 => None,
+        n if n == variants.len() => {
+            let to_code = codes
+                .iter()
+                .map(|(ident, code)| quote! { #name::#ident => #code, });
+            let from_code = codes
+                .iter()
+                .map(|(ident, code)| quote! { #code => Some(#name::#ident), });
+            Some(quote! {
+                /// Числовой код варианта из атрибута `#[code(n)]`.
+                pub fn to_code(&self) -> u8 {
+                    match self { #(#to_code)* }
+                }
+
+                /// Вариант по числовому коду; `None` — неизвестный код.
+                pub fn from_code(code: u8) -> Option<Self> {
+                    match code {
+                        #(#from_code)*
+                        _ => None,
+                    }
+                }
+            })
+        }
+        _ => {
+            let (coded, _) = codes[0];
+            let missing = variants
+                .iter()
+                .find(|v| codes.iter().all(|(ident, _)| *ident != &v.ident))
+                .expect("вариант без кода есть");
+            return Err(syn::Error::new(
+                missing.ident.span(),
+                format!(
+                    "коды заданы (вариант {coded}), укажите #[code(n)] и для варианта {}",
+                    missing.ident
+                ),
+            ));
+        }
+    };
+
+    let input = match options.trim {
+        true => quote! { s.trim() },
+        false => quote! { s },
+    };
+    let trim_tag = options.trim.then(|| quote! { let tag = tag.trim_end(); });
+    let key = |s: TokenStream| match options.case_sensitive {
+        true => s,
+        false => quote! { #s.to_lowercase().as_str() },
+    };
+    let (input_key, tag_key) = (key(quote! { input }), key(quote! { tag }));
+    let unknown_err = value_err(
+        &error,
+        tr(
+            &root,
+            quote! {
+                format!("некорректное значение {}: '{}'", stringify!(#name), s)
+            },
+            quote! {
+                format!("invalid value {}: '{}'", stringify!(#name), s)
+            },
+        ),
+    );
+
+    Ok(quote! {
+        impl #name {
+            /// Варианты без полей в порядке объявления.
+            pub fn variants() -> &'static [Self] {
+                &[#(#name::#units),*]
+            }
+
+            /// Строки всех вариантов (без синонимов) в порядке объявления.
+            pub fn variant_strs() -> &'static [&'static str] {
+                &[#(#strs),*]
+            }
+
+            #code_fns
+        }
+
+        impl std::fmt::Display for #name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self { #(#to_arms)* }
+            }
+        }
+
+        impl std::str::FromStr for #name {
+            type Err = #error;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let unknown = || #unknown_err;
+                let input = #input;
+                match #input_key {
+                    #(#from_arms)*
+                    _ => {
+                        // Вариант с данными: значение после разделителя
+                        // регистр сохраняет.
+                        let (tag, value) = input
+                            .split_once(#DATA_SEPARATOR)
+                            .ok_or_else(unknown)?;
+                        #trim_tag
+                        match #tag_key {
+                            #(#data_arms)*
+                            _ => Err(unknown()),
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Параметры `QuoteEnumDisplay` из атрибута перечисления
+/// `#[quote_enum(...)]`.
+struct EnumOptions {
+    /// Сравнивать строки с учётом регистра.
+    case_sensitive: bool,
+    /// Отбрасывать пробелы вокруг строки перед разбором.
+    trim: bool,
+}
+
+impl EnumOptions {
+    /// Прочитать параметры из атрибутов перечисления.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self {
+            case_sensitive: false,
+            trim: true,
+        };
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("quote_enum"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("case") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    options.case_sensitive = match lit.value().as_str() {
+                        "sensitive" => true,
+                        "insensitive" => false,
+                        _ => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "ожидается case = \"sensitive\" или \"insensitive\"",
+                            ));
+                        }
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("trim") {
+                    let lit: LitBool = meta.value()?.parse()?;
+                    options.trim = lit.value;
+                    Ok(())
+                } else {
+                    Err(meta.error("неизвестный параметр quote_enum"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+
+    /// Строка `s` в том виде, в котором с ней сравнивается ввод: без учёта
+    /// регистра — в нижнем регистре.
+    fn pattern(&self, s: &LitStr) -> LitStr {
+        match self.case_sensitive {
+            true => s.clone(),
+            false => LitStr::new(&s.value().to_lowercase(), s.span()),
+        }
+    }
+}
+
+/// Числовой код варианта из атрибута `#[code(1)]`.
+fn variant_code(attrs: &[Attribute]) -> syn::Result<Option<(u8, proc_macro2::Span)>> {
+    let mut code = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("code")) {
+        if code.is_some() {
+            return Err(syn::Error::new_spanned(attr, "код варианта уже задан"));
+        }
+        let lit: LitInt = attr.parse_args()?;
+        code = Some((lit.base10_parse()?, lit.span()));
+    }
+    Ok(code)
+}
+
+/// Строка варианта и её синонимы из атрибута
+/// `#[str("cancel", alias = "stop")]`; без атрибута — имя варианта
+/// в нижнем регистре.
+pub fn variant_strs(ident: &Ident, attrs: &[Attribute]) -> syn::Result<(LitStr, Vec<LitStr>)> {
+    let mut lit = LitStr::new(&ident.to_string().to_lowercase(), ident.span());
+    let mut aliases = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("str")) {
+        let bad_str =
+            |err: syn::Error| syn::Error::new(err.span(), "ожидается строка: #[str(\"...\")]");
+        attr.meta.require_list().map_err(bad_str)?;
+        attr.parse_args_with(|input: ParseStream| {
+            lit = input.parse().map_err(bad_str)?;
+            while !input.is_empty() {
+                input.parse::<Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+                let key: Ident = input.parse()?;
+                if key != "alias" {
+                    return Err(syn::Error::new(key.span(), "неизвестный параметр str"));
+                }
+                input.parse::<Token![=]>()?;
+                aliases.push(input.parse()?);
+            }
+            Ok(())
+        })?;
+    }
+    Ok((lit, aliases))
+}
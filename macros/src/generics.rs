@@ -0,0 +1,62 @@
+//! Обобщённые параметры в генерируемых реализациях.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::HashSet;
+use syn::{Generics, Ident, Type, parse_quote};
+
+/// Параметры `generics` с ограничением `bound` для каждого из `types`,
+/// в котором упоминается обобщённый параметр (`T`, `Vec<T>`, `&'a str`).
+///
+/// Ограничения накладываются на типы полей, а не на сами параметры: поле
+/// `Vec<T>` требует `Vec<T>: Default`, а не `T: Default`. Типы без
+/// параметров ограничений не получают. Невыполнимое ограничение
+/// (`&'a str: FromStr`) не мешает компиляции: реализация просто
+/// не применяется.
+pub fn with_bounds<'a>(
+    generics: &Generics,
+    types: impl IntoIterator<Item = &'a Type>,
+    bound: TokenStream,
+) -> Generics {
+    let params = Params {
+        types: generics.type_params().map(|param| &param.ident).collect(),
+        lifetimes: generics
+            .lifetimes()
+            .map(|param| &param.lifetime.ident)
+            .collect(),
+    };
+    let mut generics = generics.clone();
+    if params.types.is_empty() && params.lifetimes.is_empty() {
+        return generics;
+    }
+    let where_clause = generics.make_where_clause();
+    for ty in types {
+        if params.mentioned_in(ty.to_token_stream()) {
+            where_clause.predicates.push(parse_quote!(#ty: #bound));
+        }
+    }
+    generics
+}
+
+/// Имена обобщённых параметров.
+struct Params<'a> {
+    types: HashSet<&'a Ident>,
+    lifetimes: HashSet<&'a Ident>,
+}
+
+impl Params<'_> {
+    /// Встречается ли в `tokens` один из параметров.
+    fn mentioned_in(&self, tokens: TokenStream) -> bool {
+        let mut lifetime = false;
+        tokens.into_iter().any(|token| {
+            let found = match &token {
+                TokenTree::Ident(ident) if lifetime => self.lifetimes.contains(ident),
+                TokenTree::Ident(ident) => self.types.contains(ident),
+                TokenTree::Group(group) => self.mentioned_in(group.stream()),
+                TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+            };
+            lifetime = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+            found
+        })
+    }
+}
@@ -0,0 +1,119 @@
+//! Замер и журналирование вызовов функций (`#[quote_instrument]`).
+
+use crate::paths::{commons_root, instrument_module};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{Ident, ItemFn, LitStr, ReturnType, Type, meta::ParseNestedMeta};
+
+/// Параметры атрибута `#[quote_instrument(level = "trace", name = "gen")]`.
+#[derive(Default)]
+pub struct Options {
+    /// Уровень сообщений о вызове; по умолчанию `debug`.
+    level: Option<Ident>,
+    /// Имя функции в журнале; по умолчанию — её идентификатор.
+    name: Option<LitStr>,
+}
+
+impl Options {
+    /// Разобрать параметр атрибута.
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("level") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let level = match lit.value().to_lowercase().as_str() {
+                "trace" => "Trace",
+                "debug" => "Debug",
+                "info" => "Info",
+                "warn" => "Warn",
+                "error" => "Error",
+                _ => {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "ожидается уровень trace, debug, info, warn или error",
+                    ));
+                }
+            };
+            self.level = Some(Ident::new(level, lit.span()));
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("неизвестный параметр quote_instrument"))
+        }
+    }
+}
+
+/// Функция `item` с телом, обёрнутым замером `CallTimer`.
+pub fn expand(options: Options, mut item: ItemFn) -> syn::Result<TokenStream> {
+    let sig = &item.sig;
+    if let Some(token) = &sig.asyncness {
+        return Err(syn::Error::new(
+            token.span,
+            "quote_instrument не поддерживает async-функции",
+        ));
+    }
+    if let Some(token) = &sig.constness {
+        return Err(syn::Error::new(
+            token.span,
+            "quote_instrument не поддерживает const-функции",
+        ));
+    }
+
+    let instrument = instrument_module(&commons_root());
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(&sig.ident.to_string(), sig.ident.span()));
+    let level = options
+        .level
+        .unwrap_or_else(|| Ident::new("Debug", Span::call_site()));
+    let block = &item.block;
+
+    // Тело выполняется в замыкании, чтобы `return` и `?` не миновали
+    // запись о завершении.
+    let finish = match result_type(&sig.output) {
+        Some(ty) => quote! {
+            let __quote_result: #ty = #instrument::call(move || #block);
+            match &__quote_result {
+                ::std::result::Result::Ok(_) => __quote_timer.finish(),
+                ::std::result::Result::Err(err) => __quote_timer.fail(err),
+            }
+        },
+        None => quote! {
+            let __quote_result = #instrument::call(move || #block);
+            __quote_timer.finish();
+        },
+    };
+    let body = quote! {
+        {
+            let __quote_timer = #instrument::CallTimer::start(
+                ::std::module_path!(),
+                #name,
+                #instrument::Level::#level,
+            );
+            #finish
+            __quote_result
+        }
+    };
+    item.block = Box::new(syn::parse2(body)?);
+    Ok(item.into_token_stream())
+}
+
+/// Тип результата функции, если она возвращает `Result<T, E>` или
+/// псевдоним с именем `...Result`; ошибка такого результата попадает
+/// в журнал. `impl Trait` в типе не допускается в аннотации переменной,
+/// поэтому такие результаты записываются без ошибки.
+fn result_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(path) = ty.as_ref() else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    let is_result = last.ident.to_string().ends_with("Result");
+    let has_impl = ty
+        .to_token_stream()
+        .into_iter()
+        .any(|token| token.to_string() == "impl");
+    (is_result && !has_impl).then_some(ty.as_ref())
+}
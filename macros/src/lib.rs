@@ -1,13 +1,94 @@
 //! Набор универсальных макросов для приложений Quote.
+//!
+//! Генерируемый код ссылается на `commons` полными путями
+//! (`::commons::...`), поэтому крейт `commons` должен быть в зависимостях,
+//! а импортировать его типы и трейты не нужно.
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{Data, DataStruct, DeriveInput, Fields, LitStr, parse_macro_input};
+use syn::{DeriveInput, ItemFn, parse_macro_input};
+
+mod binary;
+mod builder;
+mod command;
+mod csv;
+mod display;
+mod enum_display;
+mod generics;
+mod instrument;
+mod paths;
+mod random;
+mod schema;
+mod types;
+mod validate;
 
 /// Макрос `QuoteDisplay` автоматически генерирует для структуры реализацию
 /// `Display` и `FromStr`, чтобы сериализовать/десериализовать её в строковый
 /// формат с полями, разделёнными |. Он валидирует количество полей и
 /// возвращает понятные ошибки парсинга.
 ///
+/// Разделитель меняется атрибутом контейнера
+/// `#[quote_display(delimiter = ";")]` — например, если значения полей
+/// могут содержать `|` или формат задан внешней системой.
+///
+/// Без дополнительных атрибутов значение с разделителем внутри ломает
+/// разбор. Атрибут `#[quote_display(escape)]` включает экранирование
+/// (`commons::escape`): `\` и первый символ разделителя в значениях
+/// записываются с `\` перед ними, а при разборе экранирование снимается.
+///
+/// Если нужно только одно направление, атрибут
+/// `#[quote_display(only = "display")]` генерирует лишь `Display`
+/// (например, для структур, которые только пишутся в журнал), а
+/// `only = "fromstr"` — лишь `FromStr` (для входящих сообщений); тогда
+/// от полей не требуется реализация другого направления.
+///
+/// Строка записывается без перевода строки: значения передаются
+/// по протоколу и в журнал, где строки разделяет сам получатель.
+/// Атрибут `#[quote_display(newline)]` (или `newline = true`) завершает
+/// строку `\n` — например, для записи в файл построчно. Разбор в любом
+/// случае допускает `\n` или `\r\n` в конце строки.
+///
+/// Поле с атрибутом `#[quote(skip)]` не входит в строковый формат, а при
+/// разборе заполняется значением `Default::default()`: так в структуре
+/// можно держать данные времени выполнения (кэши, дескрипторы), не меняя
+/// формат.
+///
+/// Формат поля задаётся атрибутом `#[quote(format = "{:.2}")]` (влияет
+/// только на `Display`), а собственные преобразования —
+/// `#[quote(with = "module")]`: модуль должен содержать функции
+/// `fmt(&T, &mut Formatter) -> fmt::Result` и `parse(&str) -> Result<T, E>`.
+///
+/// Поле `Option<T>` с атрибутом `#[quote(optional)]` записывается пустой
+/// строкой, если значения нет, а пустая строка разбирается в `None`: так
+/// в структуру можно добавлять необязательные поля.
+///
+/// Поле с атрибутом `#[quote(default)]` разбирается из пустой строки
+/// в `Default::default()`. Необязательные поля и поля с `default` в конце
+/// строки можно опустить — так разбираются строки, записанные до их
+/// появления.
+///
+/// Позиции полей по умолчанию задаёт порядок объявления. Атрибут
+/// `#[pos(n)]` закрепляет позицию явно (с 0): тогда он нужен каждому полю
+/// формата, позиции идут подряд, а поля структуры можно переставлять,
+/// не меняя формат.
+///
+/// Поле с атрибутом `#[quote(flatten)]` — вложенная структура: её поля
+/// записываются на месте поля через разделитель внешней структуры,
+/// а при разборе она получает столько частей строки, сколько занимает.
+/// Тип поля должен реализовывать `commons::traits::DisplaySegments` —
+/// его реализует `QuoteDisplay` без `only`. Вложенная структура в конце
+/// строки допускает короткую строку, как и её собственный разбор.
+///
+/// Обобщённые структуры поддерживаются: реализации получают те же
+/// параметры, а типы полей, зависящие от них, — ограничения `Display`
+/// и `FromStr` (пропущенные поля — `Default`).
+///
+/// Ошибка разбора по умолчанию — `commons::errors::QuoteError`; другой
+/// тип задаётся атрибутом контейнера `#[quote(error = "my::Error")]`
+/// и должен реализовывать `From<String>`.
+/// Сгенерированный код обращается к `commons` по полному пути
+/// `::commons::...`; если крейт доступен под другим путём (например,
+/// реэкспортирован), корень задаётся атрибутом
+/// `#[quote(crate = "my::commons")]`.
+///
 /// ## Пример
 ///
 /// ```ignore
@@ -24,85 +105,114 @@ use syn::{Data, DataStruct, DeriveInput, Fields, LitStr, parse_macro_input};
 ///     price: 1000f64,
 /// };
 ///
-/// assert_eq!("YNX|1000\n", quote.to_string());
-#[proc_macro_derive(QuoteDisplay)]
+/// assert_eq!("YNX|1000", quote.to_string());
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// #[quote_display(delimiter = ";")]
+/// struct Fill {
+///     ticker: String,
+///     volume: u32,
+/// }
+///
+/// let fill: Fill = "YNX;10".parse().unwrap();
+/// assert_eq!(fill.volume, 10);
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// #[quote_display(escape)]
+/// struct Note {
+///     ticker: String,
+///     text: String,
+/// }
+///
+/// let note = Note { ticker: "YNX".to_string(), text: "a|b".to_string() };
+/// assert_eq!(note.to_string(), "YNX|a\\|b");
+/// assert_eq!("YNX|a\\|b".parse::<Note>().unwrap().text, "a|b");
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// struct Cached {
+///     ticker: String,
+///     #[quote(skip)]
+///     hits: u64,
+/// }
+///
+/// let cached: Cached = "YNX".parse().unwrap();
+/// assert_eq!(cached.hits, 0);
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// struct Pinned {
+///     #[pos(1)]
+///     volume: u32,
+///     #[pos(0)]
+///     ticker: String,
+/// }
+///
+/// let pinned: Pinned = "YNX|10".parse().unwrap();
+/// assert_eq!(pinned.volume, 10);
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// struct Envelope {
+///     seq: u64,
+///     #[quote(flatten)]
+///     fill: Fill,
+/// }
+///
+/// let envelope: Envelope = "7|YNX|10".parse().unwrap();
+/// assert_eq!(envelope.fill.volume, 10);
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// struct Price {
+///     ticker: String,
+///     #[quote(format = "{:.2}")]
+///     price: f64,
+/// }
+///
+/// let price = Price { ticker: "YNX".to_string(), price: 10.5 };
+/// assert_eq!(price.to_string(), "YNX|10.50");
+///
+/// #[derive(Debug, Clone, QuoteDisplay)]
+/// #[quote_display(newline)]
+/// struct Line {
+///     ticker: String,
+/// }
+///
+/// let line = Line { ticker: "YNX".to_string() };
+/// assert_eq!(line.to_string(), "YNX\n");
+/// assert_eq!("YNX\r\n".parse::<Line>().unwrap().ticker, "YNX");
+/// ```
+#[proc_macro_derive(QuoteDisplay, attributes(quote_display, quote, pos))]
 pub fn macros_quote_display(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = &input.ident;
-
-    // Сбор названия полей.
-    let fields_name = match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
-            ..
-        }) => &fields.named,
-        _ => panic!("QuoteDisplay допустимо использовать только со структурами"),
-    };
-
-    let fields: Vec<_> = fields_name
-        .iter()
-        .filter_map(|field| field.ident.as_ref())
-        .collect();
-
-    let fields_count = fields.len();
-
-    // Формат для Display: "{}|{}|{}"
-    let fmt_string = vec!["{}"; fields_count].join("|");
-    let fmt_lit = LitStr::new(&fmt_string, proc_macro2::Span::call_site());
-
-    let fields_parses: Vec<_> = (0..fields_count)
-        .map(|i| {
-            let field_name = &fields[i];
-            quote! {
-                #field_name: parts[#i]
-                    .parse()
-                    .map_err(|_| QuoteError::value_err(format!(
-                        "Ошибка парсинга строки {} на позиции {} для поля {}",
-                        s, #i, stringify!(#field_name)
-                    )))?,
-            }
-        })
-        .collect();
-
-    let output: proc_macro2::TokenStream = {
-        quote! {
-            impl std::fmt::Display for #struct_name {
-                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                    writeln!(
-                        f,
-                        #fmt_lit,
-                        #(self.#fields),*
-                    )
-                }
-            }
-
-            impl std::str::FromStr for #struct_name {
-                type Err = QuoteError;
-                fn from_str(s: &str) -> Result<Self, Self::Err> {
-                    let parts: Vec<&str> = s.split('|').collect();
-                    if parts.len() != #fields_count {
-                        Err(QuoteError::value_err(format!(
-                            "ожидается {} типа, разделённых '|', получено {} в строке {}",
-                            #fields_count,
-                            parts.len(),
-                            s
-                        )))
-                    } else {
-                        Ok(Self {
-                            #(#fields_parses)*
-                        })
-                    }
-                }
-            }
-        }
-    };
-
-    TokenStream::from(output)
+    display::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
 /// Derive-макрос для `Enum`: автоматически добавляет реализации
 /// [`std::fmt::Display`] и [`std::str::FromStr`].
 ///
+/// Вариант с одним безымянным полем записывается как строка варианта
+/// и значение через двоеточие: `limit:42.5`. Значение поля должно
+/// реализовывать `Display` и `FromStr`.
+///
+/// Синонимы `#[str("cancel", alias = "stop")]` принимаются при разборе,
+/// а `Display` выводит основную строку.
+///
+/// По умолчанию разбор не учитывает регистр и отбрасывает пробелы вокруг
+/// строки; атрибут `#[quote_enum(case = "sensitive", trim = false)]`
+/// отключает это.
+///
+/// Также генерируются `variants()` — варианты без полей — и
+/// `variant_strs()` — строки всех вариантов, например для справки
+/// по командам.
+///
+/// Атрибут `#[code(1)]` задаёт варианту числовой код (`u8`) — например,
+/// для двоичного протокола. Коды нужны всем вариантам сразу, варианты
+/// с полями их не поддерживают; генерируются `to_code()` и
+/// `from_code(u8) -> Option<Self>`.
+///
+/// Тип ошибки разбора и корень путей к `commons` задаются так же, как
+/// у [`QuoteDisplay`]: `#[quote(error = "my::Error", crate = "my::commons")]`.
+///
 /// ## Пример
 ///
 /// ```ignore
@@ -118,57 +228,370 @@ pub fn macros_quote_display(input: TokenStream) -> TokenStream {
 ///
 /// let start = Commands::Start;
 /// assert_eq!(stringify!(start), "start");
+///
+/// #[derive(Debug, Clone, PartialEq, QuoteEnumDisplay)]
+/// enum Order {
+///     #[str("market")]
+///     Market,
+///     #[str("limit")]
+///     Limit(f64),
+/// }
+///
+/// assert_eq!(Order::Limit(42.5).to_string(), "limit:42.5");
+/// assert_eq!("limit:42.5".parse::<Order>().unwrap(), Order::Limit(42.5));
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, QuoteEnumDisplay)]
+/// enum Side {
+///     #[code(1)]
+///     Buy,
+///     #[code(2)]
+///     Sell,
+/// }
+///
+/// assert_eq!(Side::Sell.to_code(), 2);
+/// assert_eq!(Side::from_code(1), Some(Side::Buy));
+/// assert_eq!(Side::from_code(3), None);
 /// ```
-#[proc_macro_derive(QuoteEnumDisplay, attributes(str))]
+#[proc_macro_derive(QuoteEnumDisplay, attributes(str, quote_enum, quote, code))]
 pub fn derive_display_fromstr(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = &input.ident;
+    enum_display::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive-макрос `QuoteValidate`: реализация трейта
+/// `commons::traits::Validate` по правилам из атрибутов полей.
+///
+/// Правила `#[validate(...)]`:
+///
+/// - `range(min = 0.0, max = 100.0)` — значение в пределах (любой
+///   из пределов можно опустить);
+/// - `non_empty` — значение не пустое (`is_empty`);
+/// - `len(min = 1, max = 8)` — длина (`len`) в пределах.
+///
+/// Поле `Option<T>` проверяется, только если значение задано. Нарушение
+/// возвращает `QuoteError::ValueError` с именем поля.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use commons::traits::Validate;
+/// use macros::QuoteValidate;
+///
+/// #[derive(QuoteValidate)]
+/// struct Order {
+///     #[validate(non_empty, len(max = 8))]
+///     ticker: String,
+///     #[validate(range(min = 0.0))]
+///     price: f64,
+/// }
+///
+/// let order = Order {
+///     ticker: "AAPL".to_string(),
+///     price: -1.0,
+/// };
+/// assert_eq!(
+///     order.validate().unwrap_err().to_string(),
+///     "price: значение -1 меньше 0"
+/// );
+/// ```
+#[proc_macro_derive(QuoteValidate, attributes(validate))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    validate::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive-макрос `QuoteBuilder`: построитель `<Имя>Builder` с сеттерами
+/// по именам полей.
+///
+/// Поля `Option<T>` и поля с атрибутом `#[builder(default)]`
+/// необязательны (`None` и `Default::default()` соответственно),
+/// остальные — обязательны: `build()` доступен, только когда все они
+/// заданы, а пропущенное поле — ошибка компиляции. Сеттеры принимают
+/// `impl Into<T>`.
+///
+/// С атрибутом `#[quote_builder(validate)]` генерируется также
+/// `try_build()`, проверяющий значение трейтом `commons::traits::Validate`.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use macros::QuoteBuilder;
+///
+/// #[derive(QuoteBuilder)]
+/// struct Order {
+///     ticker: String,
+///     price: f64,
+///     #[builder(default)]
+///     volume: u32,
+///     exchange: Option<String>,
+/// }
+///
+/// let order = OrderBuilder::new().ticker("AAPL").price(10.0).build();
+/// assert_eq!(order.volume, 0);
+/// assert_eq!(order.exchange, None);
+///
+/// // Ошибка компиляции: не задано поле price.
+/// // let order = Order::builder().ticker("AAPL").build();
+/// ```
+#[proc_macro_derive(QuoteBuilder, attributes(quote_builder, builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    builder::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let variants = match input.data {
-        Data::Enum(e) => e.variants,
-        _ => panic!("QuoteEnumDisplay допустимо использовать только с enum"),
-    };
+/// Derive-макрос `QuoteCsv`: реализация трейта `commons::csv::CsvRecord`
+/// (колонки в порядке полей, названия колонок — имена полей).
+///
+/// Учитываются те же атрибуты полей, что и у [`QuoteDisplay`]:
+/// `#[quote(skip)]`, `format`, `with` и `default`. Поле `Option<T>`
+/// необязательно: `None` — пустая колонка.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use commons::csv::CsvRecord;
+/// use macros::QuoteCsv;
+///
+/// #[derive(QuoteCsv)]
+/// struct Fill {
+///     ticker: String,
+///     #[quote(format = "{:.2}")]
+///     price: f64,
+///     exchange: Option<String>,
+/// }
+///
+/// assert_eq!(Fill::csv_header(), "ticker,price,exchange");
+/// let fill = Fill::from_csv_row("AAPL,10.5,").unwrap();
+/// assert_eq!(fill.to_csv_row(), "AAPL,10.50,");
+/// ```
+#[proc_macro_derive(QuoteCsv, attributes(quote))]
+pub fn derive_csv(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    csv::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let mut to_arms = Vec::new();
-    let mut from_arms = Vec::new();
+/// Derive-макрос `QuoteBinary`: двоичное представление фиксированного
+/// размера — константа `BINARY_SIZE` и методы `to_bytes()`/`from_bytes()`.
+///
+/// Поля записываются подряд в порядке объявления, каждое — трейтом
+/// `commons::codec::BinaryField` (числа — little-endian). Ширина строк
+/// задаётся атрибутом `#[binary(width = N)]`, строка дополняется нулевыми
+/// байтами. Перед первым полем `Option<T>` записывается байт флагов: бит
+/// `i` установлен, если задано `i`-е необязательное поле (не более
+/// восьми); незаданное поле заполняется нулями.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use macros::QuoteBinary;
+///
+/// #[derive(QuoteBinary)]
+/// struct Fill {
+///     #[binary(width = 8)]
+///     ticker: String,
+///     volume: u32,
+///     price: Option<f64>,
+/// }
+///
+/// assert_eq!(Fill::BINARY_SIZE, 8 + 4 + 1 + 8);
+/// let fill = Fill {
+///     ticker: "AAPL".to_string(),
+///     volume: 10,
+///     price: None,
+/// };
+/// let bytes = fill.to_bytes().unwrap();
+/// assert_eq!(Fill::from_bytes(&bytes).unwrap().volume, 10);
+/// ```
+#[proc_macro_derive(QuoteBinary, attributes(binary))]
+pub fn derive_binary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    binary::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    for v in variants {
-        let ident = v.ident;
-        if !matches!(v.fields, Fields::Unit) {
-            panic!("Только unit-variants");
-        }
-        let mut lit = ident.to_string().to_lowercase();
-        for attr in v.attrs {
-            if attr.path().is_ident("str") {
-                let s: syn::LitStr = attr.parse_args().expect("str(\"...\")");
-                lit = s.value();
-            }
-        }
-        let lit_str = syn::LitStr::new(&lit, proc_macro2::Span::call_site());
-        to_arms.push(quote! { #name::#ident => write!(f, #lit_str), });
-        from_arms.push(quote! { #lit_str => Ok(#name::#ident), });
-    }
+/// Derive-макрос `QuoteRandom`: случайные значения для тестовых данных —
+/// реализация трейта `commons::randomizer::RandomValue` и метод
+/// `random(rng: &mut impl Rng) -> Self`.
+///
+/// Поле без атрибута получает случайное значение своего типа
+/// (`RandomValue`). Правила `#[random(...)]`:
+///
+/// - `range(1.0, 100.0)` — значение в пределах, включая `max`;
+/// - `choice("AAPL", "MSFT")` — одно из значений (строковые литералы
+///   приводятся к типу поля через `Into`);
+/// - `default` — `Default::default()`.
+///
+/// Поле `Option<T>` получает значение с вероятностью 1/2; пределы
+/// и варианты относятся к `T`. У перечисления варианты выбираются
+/// равновероятно.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use commons::randomizer::Randomizer;
+/// use macros::QuoteRandom;
+///
+/// #[derive(QuoteRandom)]
+/// struct Fill {
+///     #[random(choice("AAPL", "MSFT"))]
+///     ticker: String,
+///     #[random(range(1.0, 100.0))]
+///     price: f64,
+///     volume: u32,
+/// }
+///
+/// // Воспроизводимые данные — из генератора с зерном.
+/// let mut rng = Randomizer::with_seed(42);
+/// let fill = Fill::random(&mut rng);
+/// assert!((1.0..=100.0).contains(&fill.price));
+///
+/// let other: Fill = rng.value();
+/// ```
+#[proc_macro_derive(QuoteRandom, attributes(random))]
+pub fn derive_random(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    random::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let expanded = quote! {
-        impl std::fmt::Display for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                match self { #(#to_arms)* }
-            }
-        }
+/// Derive-макрос `QuoteCommand`: разбор команд текстового протокола
+/// в перечисление (`FromStr`) и строки использования.
+///
+/// Команда — строка варианта (атрибут `#[str("stream", alias = "sub")]`,
+/// как у [`QuoteEnumDisplay`]; регистр не учитывается) и аргументы через
+/// пробельные символы. Аргументы — поля варианта по порядку; каждое
+/// разбирается своим `FromStr`, ошибка которого должна реализовывать
+/// `Display`. Поле `Option<T>` — необязательный аргумент в конце команды,
+/// `#[arg(rest)]` у последнего поля отдаёт ему остаток строки,
+/// `#[arg(name = "...")]` задаёт имя аргумента в справке.
+///
+/// Генерируются `variant_strs()`, `usages()` — строки использования всех
+/// команд (`stream <udp> <tickers...>`) — и `usage(&self)`. Ошибки
+/// разбора (неизвестная команда, нехватка или избыток аргументов,
+/// некорректный аргумент) содержат строку использования. Тип ошибки
+/// и корень путей к `commons` задаются так же, как у [`QuoteDisplay`]:
+/// `#[quote(error = "my::Error", crate = "my::commons")]`.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use macros::QuoteCommand;
+///
+/// #[derive(Debug, PartialEq, QuoteCommand)]
+/// enum Command {
+///     #[str("stream")]
+///     Stream {
+///         port: u16,
+///         #[arg(rest)]
+///         tickers: String,
+///     },
+///     #[str("cancel", alias = "stop")]
+///     Cancel,
+/// }
+///
+/// assert_eq!(Command::usages(), ["stream <port> <tickers...>", "cancel"]);
+/// assert_eq!(
+///     "STREAM 9000 AAPL, MSFT".parse::<Command>().unwrap(),
+///     Command::Stream { port: 9000, tickers: "AAPL, MSFT".to_string() }
+/// );
+/// assert!("stream 9000".parse::<Command>().is_err());
+/// ```
+#[proc_macro_derive(QuoteCommand, attributes(str, arg, quote))]
+pub fn derive_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    command::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-        impl std::str::FromStr for #name {
-            type Err = QuoteError;
-            fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match s.trim().to_lowercase().as_str() {
-                    #(#from_arms)*
-                    _ => Err(QuoteError::value_err(format!(
-                        "некорректное значение {}: '{}'",
-                        stringify!(#name), s
-                    ))),
-                }
-            }
-        }
-    };
+/// Derive-макрос `QuoteSchema`: JSON Schema JSON-представления типа —
+/// реализация трейта `commons::schema::JsonSchema`, например, чтобы
+/// потребители на других языках проверяли полезную нагрузку протокола.
+///
+/// Структура описывается объектом, перечисление без полей — строкой
+/// из набора имён вариантов. Учитываются атрибуты `serde`: `rename` —
+/// имя в JSON, `default` и тип `Option<T>` — необязательное свойство,
+/// `skip` — свойство не описывается; `rename_all` не поддерживается.
+/// Описания берутся из первого абзаца документации типа и полей, схемы
+/// вложенных типов (тоже `JsonSchema`) встраиваются на месте.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use commons::schema::{JsonSchema, root_schema};
+/// use macros::QuoteSchema;
+/// use serde::Serialize;
+///
+/// /// Сделка.
+/// #[derive(Serialize, QuoteSchema)]
+/// struct Fill {
+///     /// Тикер.
+///     ticker: String,
+///     #[serde(rename = "qty")]
+///     volume: u32,
+///     #[serde(default)]
+///     note: String,
+/// }
+///
+/// let schema = root_schema::<Fill>();
+/// assert_eq!(schema["description"], "Сделка.");
+/// assert_eq!(schema["required"], serde_json::json!(["ticker", "qty"]));
+/// ```
+#[proc_macro_derive(QuoteSchema)]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    TokenStream::from(expanded)
+/// Атрибут `#[quote_instrument]`: замер и журналирование вызовов функции
+/// (`commons::instrument`) — для горячих участков без ручного кода.
+///
+/// При входе в функцию в журнал пишется вызов, при выходе — длительность.
+/// Если функция возвращает `Result` (или псевдоним `...Result`), ошибка
+/// записывается вместе с длительностью с уровнем не ниже `warn`. Цель
+/// сообщений — модуль функции.
+///
+/// Параметры:
+///
+/// - `level = "trace"` — уровень сообщений (`trace`, `debug`, `info`,
+///   `warn`, `error`); по умолчанию `debug`;
+/// - `name = "gen"` — имя функции в журнале; по умолчанию — её имя.
+///
+/// Тело функции выполняется в замыкании с `move`, поэтому `return` и `?`
+/// не обходят запись о завершении. `async`- и `const`-функции
+/// не поддерживаются.
+///
+/// ## Пример
+///
+/// ```ignore
+/// use commons::errors::QuoteError;
+/// use macros::quote_instrument;
+///
+/// #[quote_instrument(level = "trace")]
+/// fn next_price(last: u64) -> Result<u64, QuoteError> {
+///     last.checked_add(1)
+///         .ok_or_else(|| QuoteError::value_err("переполнение цены"))
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn quote_instrument(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = instrument::Options::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(attr with parser);
+    let item = parse_macro_input!(item as ItemFn);
+    instrument::expand(options, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,106 @@
+//! Пути к типам и трейтам `commons` в генерируемом коде.
+//!
+//! Пути полные, поэтому генерируемый код не требует импортов. Корень путей
+//! — крейт `commons` ([`commons_root`]); derive-макросы с разбором строк
+//! принимают другой корень атрибутом `#[quote(crate = "...")]`
+//! ([`QuoteAttrs`]), например при реэкспорте `commons` из другого крейта.
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{Attribute, LitStr, Path};
+
+/// Корень путей по умолчанию — крейт `commons`.
+pub fn commons_root() -> TokenStream {
+    quote! { ::commons }
+}
+
+/// Тип ошибки `errors::QuoteError`.
+pub fn quote_error(root: &TokenStream) -> TokenStream {
+    quote! { #root::errors::QuoteError }
+}
+
+/// Трейт `traits::Validate`.
+pub fn validate_trait(root: &TokenStream) -> TokenStream {
+    quote! { #root::traits::Validate }
+}
+
+/// Трейт `traits::DisplaySegments`.
+pub fn display_segments_trait(root: &TokenStream) -> TokenStream {
+    quote! { #root::traits::DisplaySegments }
+}
+
+/// Трейт `csv::CsvRecord`.
+pub fn csv_record_trait(root: &TokenStream) -> TokenStream {
+    quote! { #root::csv::CsvRecord }
+}
+
+/// Трейт `codec::BinaryField`.
+pub fn binary_field_trait(root: &TokenStream) -> TokenStream {
+    quote! { #root::codec::BinaryField }
+}
+
+/// Модуль `escape`.
+pub fn escape_module(root: &TokenStream) -> TokenStream {
+    quote! { #root::escape }
+}
+
+/// Модуль `randomizer`.
+pub fn randomizer_module(root: &TokenStream) -> TokenStream {
+    quote! { #root::randomizer }
+}
+
+/// Модуль `schema`.
+pub fn schema_module(root: &TokenStream) -> TokenStream {
+    quote! { #root::schema }
+}
+
+/// Модуль `instrument`.
+pub fn instrument_module(root: &TokenStream) -> TokenStream {
+    quote! { #root::instrument }
+}
+
+/// Сообщение на текущем языке: `i18n::tr(ru, en)`.
+pub fn tr(root: &TokenStream, ru: TokenStream, en: TokenStream) -> TokenStream {
+    quote! { #root::i18n::tr(#ru, #en) }
+}
+
+/// Параметры контейнера `#[quote(error = "my::Error", crate = "my::commons")]`
+/// для derive-макросов с разбором строк.
+pub struct QuoteAttrs {
+    /// Тип ошибки `FromStr`; по умолчанию — [`quote_error`]. Тип должен
+    /// реализовывать `From<String>`.
+    pub error: TokenStream,
+    /// Корень путей к `commons`; по умолчанию — [`commons_root`].
+    pub root: TokenStream,
+}
+
+impl QuoteAttrs {
+    /// Прочитать параметры из атрибутов `#[quote(...)]`.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let (mut error, mut root) = (None, None);
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("quote")) {
+            attr.parse_nested_meta(|meta| {
+                let target = if meta.path.is_ident("error") {
+                    &mut error
+                } else if meta.path.is_ident("crate") {
+                    &mut root
+                } else {
+                    return Err(meta.error("неизвестный параметр quote"));
+                };
+                let lit: LitStr = meta.value()?.parse()?;
+                *target = Some(lit.parse::<Path>()?.into_token_stream());
+                Ok(())
+            })?;
+        }
+        let root = root.unwrap_or_else(commons_root);
+        let error = error.unwrap_or_else(|| quote_error(&root));
+        Ok(Self { error, root })
+    }
+}
+
+/// Ошибка типа `error` с сообщением `message` (выражение `String`).
+pub fn value_err(error: &TokenStream, message: TokenStream) -> TokenStream {
+    quote! {
+        <#error as ::std::convert::From<::std::string::String>>::from(#message)
+    }
+}
@@ -0,0 +1,185 @@
+//! Генерация случайных значений (`QuoteRandom`).
+
+use crate::paths::{commons_root, randomizer_module};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, Token, Type, meta::ParseNestedMeta,
+    punctuated::Punctuated, spanned::Spanned,
+};
+
+/// Реализация `RandomValue` и метод `random` для типа `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let randomizer = randomizer_module(&commons_root());
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "QuoteRandom не поддерживает обобщённые типы",
+        ));
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => construct(quote! { Self }, &data.fields)?,
+        Data::Enum(e) => {
+            if e.variants.is_empty() {
+                return Err(syn::Error::new(
+                    e.brace_token.span.join(),
+                    "QuoteRandom: у перечисления нет вариантов",
+                ));
+            }
+            let count = e.variants.len();
+            let arms = e
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    let ident = &variant.ident;
+                    let value = construct(quote! { Self::#ident }, &variant.fields)?;
+                    Ok(quote! { #i => #value, })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match #randomizer::Rng::random_range(rng, 0..#count) {
+                    #(#arms)*
+                    _ => unreachable!(),
+                }
+            }
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteRandom допустимо использовать только со структурами и перечислениями",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #randomizer::RandomValue for #name {
+            fn random_value<__QuoteRng: #randomizer::Rng + ?Sized>(rng: &mut __QuoteRng) -> Self {
+                #body
+            }
+        }
+
+        impl #name {
+            /// Случайное значение (см. `RandomValue`).
+            pub fn random(rng: &mut impl #randomizer::Rng) -> Self {
+                <Self as #randomizer::RandomValue>::random_value(rng)
+            }
+        }
+    })
+}
+
+/// Значение `path` со случайными полями `fields`.
+fn construct(path: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let values = fields
+        .iter()
+        .map(|field| Ok((field, Rule::from_attrs(&field.attrs)?.value(&field.ty))))
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(match fields {
+        Fields::Named(_) => {
+            let values = values.iter().map(|(field, value)| {
+                let ident = &field.ident;
+                quote! { #ident: #value }
+            });
+            quote! { #path { #(#values),* } }
+        }
+        Fields::Unnamed(_) => {
+            let values = values.iter().map(|(_, value)| value);
+            quote! { #path(#(#values),*) }
+        }
+        Fields::Unit => path,
+    })
+}
+
+/// Правило генерации поля из атрибута `#[random(...)]`.
+enum Rule {
+    /// Без атрибута: `RandomValue` типа поля.
+    Any,
+    /// `Default::default()`.
+    Default,
+    /// Значение в пределах `min..=max`.
+    Range(Box<Expr>, Box<Expr>),
+    /// Одно из перечисленных значений.
+    Choice(Vec<Expr>),
+}
+
+impl Rule {
+    /// Прочитать правило из атрибутов поля.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut rule = Rule::Any;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("random")) {
+            attr.parse_nested_meta(|meta| {
+                if !matches!(rule, Rule::Any) {
+                    return Err(meta.error("у поля может быть только одно правило random"));
+                }
+                rule = if meta.path.is_ident("default") {
+                    Rule::Default
+                } else if meta.path.is_ident("range") {
+                    let mut bounds = exprs(&meta)?.into_iter();
+                    match (bounds.next(), bounds.next(), bounds.next()) {
+                        (Some(min), Some(max), None) => Rule::Range(Box::new(min), Box::new(max)),
+                        _ => return Err(meta.error("ожидается range(min, max)")),
+                    }
+                } else if meta.path.is_ident("choice") {
+                    let items = exprs(&meta)?;
+                    if items.is_empty() {
+                        return Err(meta.error("пустой список choice"));
+                    }
+                    Rule::Choice(items)
+                } else {
+                    return Err(meta.error("неизвестное правило random"));
+                };
+                Ok(())
+            })?;
+        }
+        Ok(rule)
+    }
+
+    /// Случайное значение поля типа `ty`. Для поля `Option<T>` пределы
+    /// и варианты относятся к `T`, а значение задаётся с вероятностью 1/2.
+    fn value(&self, ty: &Type) -> TokenStream {
+        let randomizer = randomizer_module(&commons_root());
+        let value = match self {
+            Rule::Any => return quote! { <#ty as #randomizer::RandomValue>::random_value(rng) },
+            Rule::Default => return quote! { ::std::default::Default::default() },
+            Rule::Range(min, max) => {
+                quote! { #randomizer::Rng::random_range(rng, (#min)..=(#max)) }
+            }
+            Rule::Choice(items) => {
+                let count = items.len();
+                let arms = items.iter().enumerate().map(|(i, item)| match item {
+                    // Строковый литерал приводится к типу поля (`String`).
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(_), ..
+                    }) => quote! { #i => ::std::convert::Into::into(#item), },
+                    _ => quote! { #i => #item, },
+                });
+                quote! {
+                    match #randomizer::Rng::random_range(rng, 0..#count) {
+                        #(#arms)*
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        };
+        match option_inner(ty) {
+            Some(_) => quote! {
+                match #randomizer::Rng::random_bool(rng, 0.5) {
+                    true => ::std::option::Option::Some(#value),
+                    false => ::std::option::Option::None,
+                }
+            },
+            None => value,
+        }
+    }
+}
+
+/// Список выражений правила: `choice("AAPL", "MSFT")`.
+fn exprs(meta: &ParseNestedMeta) -> syn::Result<Vec<Expr>> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    let items = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+    Ok(items.into_iter().collect())
+}
@@ -0,0 +1,167 @@
+//! Генерация JSON Schema (`QuoteSchema`).
+//!
+//! Схема описывает JSON-представление `serde`, поэтому учитываются
+//! атрибуты `#[serde(rename, default, skip)]`; описания берутся
+//! из документации типа и полей.
+
+use crate::paths::{commons_root, schema_module};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitStr, Meta, Token,
+    meta::ParseNestedMeta, spanned::Spanned,
+};
+
+/// Реализация `JsonSchema` для типа `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let schema = schema_module(&commons_root());
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "QuoteSchema не поддерживает обобщённые типы",
+        ));
+    }
+    let container = SerdeOptions::from_attrs(&input.attrs)?;
+    if let Some(span) = container.rename_all {
+        return Err(syn::Error::new(
+            span,
+            "QuoteSchema не поддерживает serde(rename_all)",
+        ));
+    }
+    let title = name.to_string();
+    let description = doc_summary(&input.attrs);
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(syn::Error::new(
+                    data.fields.span(),
+                    "QuoteSchema поддерживает только структуры с именованными полями",
+                ));
+            };
+            let mut properties = Vec::new();
+            for field in &fields.named {
+                let options = SerdeOptions::from_attrs(&field.attrs)?;
+                if options.skip {
+                    continue;
+                }
+                let ident = field.ident.as_ref().expect("у именованного поля есть имя");
+                let json_name = options.rename.unwrap_or_else(|| ident.to_string());
+                let field_description = doc_summary(&field.attrs);
+                // Отсутствующее `Option` serde разбирает в `None`.
+                let required = !options.default && option_inner(&field.ty).is_none();
+                let ty = &field.ty;
+                properties.push(quote! {
+                    #schema::Property {
+                        name: #json_name,
+                        description: #field_description,
+                        required: #required,
+                        schema: <#ty as #schema::JsonSchema>::json_schema(),
+                    }
+                });
+            }
+            quote! { #schema::object(#title, #description, vec![#(#properties),*]) }
+        }
+        Data::Enum(e) => {
+            let mut values = Vec::new();
+            for v in &e.variants {
+                if !matches!(v.fields, Fields::Unit) {
+                    return Err(syn::Error::new(
+                        v.fields.span(),
+                        "QuoteSchema поддерживает перечисления только с вариантами без полей",
+                    ));
+                }
+                let options = SerdeOptions::from_attrs(&v.attrs)?;
+                if !options.skip {
+                    values.push(options.rename.unwrap_or_else(|| v.ident.to_string()));
+                }
+            }
+            quote! { #schema::string_enum(#title, #description, &[#(#values),*]) }
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteSchema допустимо использовать только со структурами и перечислениями",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #schema::JsonSchema for #name {
+            fn json_schema() -> #schema::Value {
+                #body
+            }
+        }
+    })
+}
+
+/// Параметры `serde`, влияющие на JSON-представление.
+#[derive(Default)]
+struct SerdeOptions {
+    /// Имя в JSON из `rename = "..."`.
+    rename: Option<String>,
+    /// Отсутствующее значение заполняется по умолчанию (`default`).
+    default: bool,
+    /// Поле или вариант не входит в JSON (`skip`).
+    skip: bool,
+    /// Место атрибута `rename_all`, если он задан.
+    rename_all: Option<proc_macro2::Span>,
+}
+
+impl SerdeOptions {
+    /// Прочитать параметры из атрибутов `#[serde(...)]`; остальные
+    /// параметры `serde` пропускаются.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    options.rename = Some(lit.value());
+                } else if meta.path.is_ident("default") {
+                    options.default = true;
+                    skip_meta(meta)?;
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("rename_all") {
+                    options.rename_all = Some(meta.path.span());
+                    skip_meta(meta)?;
+                } else {
+                    skip_meta(meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Пропустить значение параметра: `key = expr` или `key(...)`.
+fn skip_meta(meta: ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(skip_meta)?;
+    }
+    Ok(())
+}
+
+/// Первый абзац документации: строки `///` до пустой строки, через пробел.
+fn doc_summary(attrs: &[Attribute]) -> String {
+    let lines = attrs.iter().filter_map(|attr| match &attr.meta {
+        Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) => Some(s.value()),
+            _ => None,
+        },
+        _ => None,
+    });
+    lines
+        .map(|line| line.trim().to_string())
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,22 @@
+//! Разбор типов полей.
+
+use syn::{GenericArgument, PathArguments, Type};
+
+/// Тип `T` из `Option<T>` (в том числе `std::option::Option<T>`).
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match (
+        segment.ident == "Option",
+        args.args.first(),
+        args.args.len(),
+    ) {
+        (true, Some(GenericArgument::Type(inner)), 1) => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,192 @@
+//! Генерация реализации `Validate` (`QuoteValidate`).
+
+use crate::paths::{commons_root, quote_error, validate_trait};
+use crate::types::option_inner;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Expr, Fields, Ident, meta::ParseNestedMeta,
+    spanned::Spanned,
+};
+
+/// Реализация `Validate` для структуры `input`.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct { fields, .. }) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "QuoteValidate поддерживает только структуры с именованными полями",
+            ));
+        }
+        Data::Enum(e) => {
+            return Err(syn::Error::new(
+                e.enum_token.span,
+                "QuoteValidate допустимо использовать только со структурами",
+            ));
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "QuoteValidate допустимо использовать только со структурами",
+            ));
+        }
+    };
+
+    let mut checks = Vec::new();
+    for field in fields {
+        let rules = Rule::from_attrs(&field.attrs)?;
+        if rules.is_empty() {
+            continue;
+        }
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("у поля именованной структуры есть имя");
+        let rules = rules.iter().map(|rule| rule.check(ident));
+        // Необязательное поле проверяется, только если значение задано.
+        checks.push(match option_inner(&field.ty) {
+            Some(_) => quote! {
+                if let Some(value) = &self.#ident { #(#rules)* }
+            },
+            None => quote! {
+                { let value = &self.#ident; #(#rules)* }
+            },
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (validate, error) = (
+        validate_trait(&commons_root()),
+        quote_error(&commons_root()),
+    );
+    Ok(quote! {
+        impl #impl_generics #validate for #name #ty_generics #where_clause {
+            fn validate(&self) -> Result<(), #error> {
+                #(#checks)*
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Правило проверки поля из атрибута `#[validate(...)]`.
+enum Rule {
+    /// Значение в пределах `min..=max`; NaN пределам не удовлетворяет.
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    /// Значение не пустое (`is_empty`).
+    NonEmpty,
+    /// Длина (`len`) в пределах `min..=max`.
+    Len {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+}
+
+impl Rule {
+    /// Прочитать правила из атрибутов поля.
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Vec<Self>> {
+        let mut rules = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("non_empty") {
+                    rules.push(Rule::NonEmpty);
+                } else if meta.path.is_ident("range") {
+                    let (min, max) = bounds(&meta)?;
+                    rules.push(Rule::Range { min, max });
+                } else if meta.path.is_ident("len") {
+                    let (min, max) = bounds(&meta)?;
+                    rules.push(Rule::Len { min, max });
+                } else {
+                    return Err(meta.error("неизвестное правило validate"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(rules)
+    }
+
+    /// Проверка значения `value` поля `ident`; нарушение возвращает
+    /// `QuoteError::ValueError`.
+    fn check(&self, ident: &Ident) -> TokenStream {
+        let error = quote_error(&commons_root());
+        let fail = |message: &str, args: TokenStream| {
+            quote! {
+                return Err(#error::value_err(format!(
+                    concat!("{}: ", #message), stringify!(#ident) #args
+                )));
+            }
+        };
+        match self {
+            Rule::Range { min, max } => {
+                let min = min.as_ref().map(|min| {
+                    let fail = fail("значение {} меньше {}", quote! { , value, #min });
+                    quote! {
+                        if !matches!(
+                            ::std::cmp::PartialOrd::partial_cmp(value, &#min),
+                            Some(::std::cmp::Ordering::Greater | ::std::cmp::Ordering::Equal)
+                        ) {
+                            #fail
+                        }
+                    }
+                });
+                let max = max.as_ref().map(|max| {
+                    let fail = fail("значение {} больше {}", quote! { , value, #max });
+                    quote! {
+                        if !matches!(
+                            ::std::cmp::PartialOrd::partial_cmp(value, &#max),
+                            Some(::std::cmp::Ordering::Less | ::std::cmp::Ordering::Equal)
+                        ) {
+                            #fail
+                        }
+                    }
+                });
+                quote! { #min #max }
+            }
+            Rule::NonEmpty => {
+                let fail = fail("пустое значение", quote! {});
+                quote! {
+                    if value.is_empty() { #fail }
+                }
+            }
+            Rule::Len { min, max } => {
+                let min = min.as_ref().map(|min| {
+                    let fail = fail("длина {} меньше {}", quote! { , value.len(), #min });
+                    quote! { if value.len() < #min { #fail } }
+                });
+                let max = max.as_ref().map(|max| {
+                    let fail = fail("длина {} больше {}", quote! { , value.len(), #max });
+                    quote! { if value.len() > #max { #fail } }
+                });
+                quote! { #min #max }
+            }
+        }
+    }
+}
+
+/// Пределы `min` и `max` правила: `range(min = 0.0, max = 1.0)`;
+/// нужен хотя бы один.
+fn bounds(meta: &ParseNestedMeta) -> syn::Result<(Option<Expr>, Option<Expr>)> {
+    let (mut min, mut max) = (None, None);
+    meta.parse_nested_meta(|bound| {
+        if bound.path.is_ident("min") {
+            min = Some(bound.value()?.parse()?);
+        } else if bound.path.is_ident("max") {
+            max = Some(bound.value()?.parse()?);
+        } else {
+            return Err(bound.error("ожидается min или max"));
+        }
+        Ok(())
+    })?;
+    if min.is_none() && max.is_none() {
+        return Err(meta.error("ожидается min или max"));
+    }
+    Ok((min, max))
+}
@@ -0,0 +1,72 @@
+//! Проверки кода, генерируемого `QuoteBinary`.
+
+use commons::errors::QuoteError;
+use macros::QuoteBinary;
+
+#[derive(Debug, PartialEq, QuoteBinary)]
+struct Fill {
+    #[binary(width = 6)]
+    ticker: String,
+    volume: u32,
+    price: Option<f64>,
+    seq: i16,
+    #[binary(width = 3)]
+    currency: Option<String>,
+}
+
+fn fill() -> Fill {
+    Fill {
+        ticker: "AAPL".to_string(),
+        volume: 258,
+        price: Some(1.5),
+        seq: -2,
+        currency: None,
+    }
+}
+
+#[test]
+fn layout_is_fixed_and_little_endian() {
+    assert_eq!(Fill::BINARY_SIZE, 6 + 4 + 1 + 8 + 2 + 3);
+
+    let bytes = fill().to_bytes().unwrap();
+    assert_eq!(&bytes[..6], b"AAPL\0\0");
+    assert_eq!(&bytes[6..10], &[2, 1, 0, 0]);
+    // Флаги перед первым необязательным полем: задана только цена.
+    assert_eq!(bytes[10], 0b01);
+    assert_eq!(&bytes[11..19], &1.5f64.to_le_bytes());
+    assert_eq!(&bytes[19..21], &(-2i16).to_le_bytes());
+    assert_eq!(&bytes[21..], &[0, 0, 0]);
+
+    assert_eq!(Fill::from_bytes(&bytes).unwrap(), fill());
+}
+
+#[test]
+fn optional_fields_roundtrip() {
+    let value = Fill {
+        price: None,
+        currency: Some("USD".to_string()),
+        ..fill()
+    };
+    let bytes = value.to_bytes().unwrap();
+    assert_eq!(bytes[10], 0b10);
+    assert_eq!(Fill::from_bytes(&bytes).unwrap(), value);
+}
+
+#[test]
+fn invalid_records_are_rejected() {
+    let long = Fill {
+        ticker: "TOOLONG".to_string(),
+        ..fill()
+    };
+    let err = long.to_bytes().unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+    assert!(err.to_string().contains("ticker"));
+
+    let bytes = fill().to_bytes().unwrap();
+    let err = Fill::from_bytes(&bytes[1..]).unwrap_err();
+    assert!(matches!(err, QuoteError::ParseError(_)));
+
+    let mut bad = bytes;
+    bad[10] = 0b100;
+    assert!(Fill::from_bytes(&bad).is_err());
+}
@@ -0,0 +1,64 @@
+//! Проверки кода, генерируемого `QuoteBuilder`.
+
+use commons::errors::QuoteError;
+use macros::{QuoteBuilder, QuoteValidate};
+
+#[derive(Debug, PartialEq, QuoteBuilder)]
+struct Order {
+    ticker: String,
+    price: f64,
+    #[builder(default)]
+    volume: u32,
+    exchange: Option<String>,
+}
+
+#[test]
+fn required_fields_in_any_order() {
+    let order = OrderBuilder::new().price(10.0).ticker("AAPL").build();
+    assert_eq!(
+        order,
+        Order {
+            ticker: "AAPL".to_string(),
+            price: 10.0,
+            volume: 0,
+            exchange: None,
+        }
+    );
+}
+
+#[test]
+fn optional_and_default_fields() {
+    let order = Order::builder()
+        .exchange("NYSE")
+        .ticker("AAPL")
+        .volume(5u32)
+        .price(1.5)
+        .build();
+    assert_eq!(order.volume, 5);
+    assert_eq!(order.exchange.as_deref(), Some("NYSE"));
+}
+
+#[derive(Debug, QuoteBuilder, QuoteValidate)]
+#[quote_builder(validate)]
+struct Fill {
+    #[validate(non_empty)]
+    ticker: String,
+    #[validate(range(min = 1))]
+    volume: u32,
+}
+
+#[test]
+fn try_build_validates() {
+    let fill = Fill::builder().ticker("AAPL").volume(3u32).try_build();
+    assert_eq!(fill.unwrap().volume, 3);
+
+    let err = Fill::builder()
+        .ticker("AAPL")
+        .volume(0u32)
+        .try_build()
+        .unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+
+    // Без проверки значение собирается как есть.
+    assert_eq!(Fill::builder().ticker("").volume(0u32).build().ticker, "");
+}
@@ -0,0 +1,136 @@
+//! Проверки кода, генерируемого `QuoteCommand`.
+
+use commons::errors::QuoteError;
+use commons::i18n::{self, Lang};
+use macros::QuoteCommand;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, QuoteCommand)]
+enum Command {
+    #[str("stream")]
+    Stream {
+        udp: String,
+        #[arg(rest)]
+        tickers: String,
+    },
+    #[str("cancel", alias = "stop")]
+    Cancel { udp: Option<String> },
+    #[str("limit")]
+    Limit(u32, #[arg(name = "note")] Option<String>),
+    #[str("ping")]
+    Ping,
+}
+
+#[test]
+fn parses_commands_case_insensitive() {
+    assert_eq!(
+        Command::from_str("STREAM udp://127.0.0.1:1 AAPL, MSFT").unwrap(),
+        Command::Stream {
+            udp: "udp://127.0.0.1:1".into(),
+            tickers: "AAPL, MSFT".into(),
+        }
+    );
+    assert_eq!(
+        Command::from_str("Stop").unwrap(),
+        Command::Cancel { udp: None }
+    );
+    assert_eq!(
+        Command::from_str("cancel udp://x").unwrap(),
+        Command::Cancel {
+            udp: Some("udp://x".into())
+        }
+    );
+    assert_eq!(
+        Command::from_str("  limit 10\t").unwrap(),
+        Command::Limit(10, None)
+    );
+    assert_eq!(Command::from_str("ping").unwrap(), Command::Ping);
+}
+
+#[test]
+fn usages_follow_declaration_order() {
+    assert_eq!(
+        Command::variant_strs(),
+        ["stream", "cancel", "limit", "ping"]
+    );
+    assert_eq!(
+        Command::usages(),
+        [
+            "stream <udp> <tickers...>",
+            "cancel [udp]",
+            "limit <arg1> [note]",
+            "ping"
+        ]
+    );
+    assert_eq!(Command::Ping.usage(), "ping");
+    assert_eq!(Command::Limit(1, None).usage(), "limit <arg1> [note]");
+}
+
+#[test]
+fn errors_name_the_problem() {
+    let err = |s: &str| Command::from_str(s).unwrap_err().to_string();
+
+    i18n::set_lang(Lang::Ru);
+    assert!(err("   ").contains("пустая команда"));
+    assert!(err("subscribe").contains("неизвестная команда 'subscribe'"));
+    assert!(err("subscribe").contains("cancel [udp]"));
+    assert!(err("stream udp://x").contains("ожидается: stream <udp> <tickers...>"));
+    assert!(err("limit ten").contains("некорректный аргумент arg1 'ten'"));
+    assert!(err("ping now").contains("лишние аргументы 'now'"));
+    assert!(matches!(
+        Command::from_str("ping now"),
+        Err(QuoteError::ValueError(_))
+    ));
+
+    i18n::set_lang(Lang::En);
+    assert!(err("   ").contains("empty command"));
+    assert!(err("subscribe").contains("unknown command 'subscribe', expected:"));
+    assert!(err("stream udp://x").contains("expected: stream <udp> <tickers...>"));
+    assert!(err("limit ten").contains("invalid argument arg1 'ten'"));
+    assert!(err("ping now").contains("extra arguments 'now'"));
+}
+
+mod custom {
+    use macros::QuoteCommand;
+    use std::str::FromStr;
+
+    #[derive(Debug, PartialEq)]
+    pub struct ParseError(pub String);
+
+    impl From<String> for ParseError {
+        fn from(message: String) -> Self {
+            Self(message)
+        }
+    }
+
+    #[derive(Debug, PartialEq, QuoteCommand)]
+    #[quote(error = "ParseError")]
+    enum Command {
+        #[str("quit")]
+        Quit,
+    }
+
+    #[test]
+    fn custom_error_type() {
+        assert_eq!(Command::from_str("QUIT"), Ok(Command::Quit));
+        assert!(Command::from_str("exit").unwrap_err().0.contains("quit"));
+    }
+
+    /// `commons` под другим путём, как в крейте, который его реэкспортирует.
+    mod shared {
+        pub use commons::i18n;
+    }
+
+    #[derive(Debug, PartialEq, QuoteCommand)]
+    #[quote(error = "ParseError", crate = "shared")]
+    enum Reexported {
+        #[str("quit")]
+        Quit,
+    }
+
+    #[test]
+    fn crate_root_is_configurable() {
+        assert_eq!(Reexported::from_str("quit"), Ok(Reexported::Quit));
+        assert!(Reexported::from_str("exit").unwrap_err().0.contains("quit"));
+    }
+}
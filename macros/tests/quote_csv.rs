@@ -0,0 +1,81 @@
+//! Проверки кода, генерируемого `QuoteCsv`.
+
+use commons::csv::{CsvRecord, read_csv};
+use commons::errors::QuoteError;
+use macros::{QuoteCsv, QuoteDisplay};
+
+/// Сумма в центах, записываемая как `12.05`.
+mod cents {
+    use std::fmt::{self, Formatter};
+
+    pub fn fmt(value: &u64, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{:02}", value / 100, value % 100)
+    }
+
+    pub fn parse(s: &str) -> Result<u64, String> {
+        let (units, cents) = s.split_once('.').ok_or("нет копеек")?;
+        let units: u64 = units.parse().map_err(|_| "units")?;
+        let cents: u64 = cents.parse().map_err(|_| "cents")?;
+        Ok(units * 100 + cents)
+    }
+}
+
+#[derive(Debug, PartialEq, QuoteCsv, QuoteDisplay)]
+struct Fill {
+    ticker: String,
+    #[quote(format = "{:.2}")]
+    price: f64,
+    #[quote(with = "cents")]
+    fee: u64,
+    #[quote(skip)]
+    hits: u32,
+    #[quote(default)]
+    seq: u64,
+    #[quote(optional)]
+    exchange: Option<String>,
+}
+
+fn fill() -> Fill {
+    Fill {
+        ticker: "AAPL".to_string(),
+        price: 10.5,
+        fee: 1205,
+        hits: 3,
+        seq: 7,
+        exchange: Some("NYSE, Inc".to_string()),
+    }
+}
+
+#[test]
+fn header_follows_field_order() {
+    assert_eq!(Fill::HEADER, ["ticker", "price", "fee", "seq", "exchange"]);
+    assert_eq!(Fill::csv_header(), "ticker,price,fee,seq,exchange");
+}
+
+#[test]
+fn row_uses_field_formats() {
+    let row = fill().to_csv_row();
+    assert_eq!(row, "AAPL,10.50,12.05,7,\"NYSE, Inc\"");
+    // Строковое представление и CSV используют одни и те же форматы.
+    assert_eq!(fill().to_string(), "AAPL|10.50|12.05|7|NYSE, Inc");
+
+    let parsed = Fill::from_csv_row(&row).unwrap();
+    assert_eq!(parsed, Fill { hits: 0, ..fill() });
+}
+
+#[test]
+fn empty_columns_use_defaults() {
+    let text = "exchange,fee,price,ticker\n,0.10,1,MSFT\n";
+    let fills: Vec<Fill> = read_csv(text.as_bytes()).unwrap();
+    assert_eq!(fills[0].seq, 0);
+    assert_eq!(fills[0].fee, 10);
+    assert_eq!(fills[0].exchange, None);
+
+    let err = Fill::from_csv_row("AAPL,1,0.10,x,").unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+    assert!(err.to_string().contains("seq"));
+    // Пустая строка — допустимое значение строкового поля.
+    assert_eq!(Fill::from_csv_row(",1,0.10,1,").unwrap().ticker, "");
+    let err = Fill::from_csv_row("AAPL,1,10,1,").unwrap_err();
+    assert!(err.to_string().contains("fee"));
+}
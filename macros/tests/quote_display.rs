@@ -0,0 +1,532 @@
+//! Проверки кода, генерируемого `QuoteDisplay`.
+
+use commons::errors::QuoteError;
+use macros::QuoteDisplay;
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Pipe {
+    ticker: String,
+    volume: u32,
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(delimiter = ";", newline)]
+struct Semicolon {
+    ticker: String,
+    note: String,
+    volume: u32,
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(delimiter = "{}", newline = false)]
+struct Braces {
+    left: u8,
+    right: u8,
+}
+
+#[test]
+fn default_delimiter_is_pipe() {
+    let pipe = Pipe {
+        ticker: "AAPL".to_string(),
+        volume: 10,
+    };
+    assert_eq!(pipe.to_string(), "AAPL|10");
+    assert_eq!("AAPL|10".parse::<Pipe>().unwrap(), pipe);
+}
+
+#[test]
+fn custom_delimiter_and_newline() {
+    let value = Semicolon {
+        ticker: "AAPL".to_string(),
+        note: "a|b".to_string(),
+        volume: 5,
+    };
+    let line = value.to_string();
+    assert_eq!(line, "AAPL;a|b;5\n");
+    assert_eq!(line.parse::<Semicolon>().unwrap(), value);
+
+    let err = "AAPL|a|5".parse::<Semicolon>().unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+    assert!(err.to_string().contains("разделённых ';'"));
+}
+
+#[test]
+fn delimiter_braces_are_escaped() {
+    let value = Braces { left: 1, right: 2 };
+    assert_eq!(value.to_string(), "1{}2");
+    assert_eq!("1{}2".parse::<Braces>().unwrap(), value);
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct WithCache {
+    ticker: String,
+    #[quote(skip)]
+    hits: u64,
+    volume: u32,
+    #[quote(skip)]
+    label: Option<String>,
+}
+
+#[test]
+fn skipped_fields_are_not_on_the_wire() {
+    let value = WithCache {
+        ticker: "AAPL".to_string(),
+        hits: 42,
+        volume: 7,
+        label: Some("runtime".to_string()),
+    };
+    assert_eq!(value.to_string(), "AAPL|7");
+
+    let parsed: WithCache = "AAPL|7".parse().unwrap();
+    assert_eq!(
+        parsed,
+        WithCache {
+            hits: 0,
+            label: None,
+            ..value
+        }
+    );
+    assert!("AAPL|42|7".parse::<WithCache>().is_err());
+}
+
+/// Сумма в центах, записываемая как `12.05`.
+mod cents {
+    use std::fmt::{self, Formatter};
+
+    pub fn fmt(value: &u64, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{:02}", value / 100, value % 100)
+    }
+
+    pub fn parse(s: &str) -> Result<u64, String> {
+        let (units, cents) = s.split_once('.').ok_or("нет копеек")?;
+        let units: u64 = units.parse().map_err(|_| "units")?;
+        let cents: u64 = cents.parse().map_err(|_| "cents")?;
+        Ok(units * 100 + cents)
+    }
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Formatted {
+    #[quote(format = "{:>5}")]
+    ticker: String,
+    #[quote(format = "{:.2}")]
+    price: f64,
+    #[quote(with = "cents")]
+    fee: u64,
+}
+
+#[test]
+fn field_formats_and_hooks() {
+    let value = Formatted {
+        ticker: "MSFT".to_string(),
+        price: 10.0,
+        fee: 1205,
+    };
+    assert_eq!(value.to_string(), " MSFT|10.00|12.05");
+
+    let parsed: Formatted = "MSFT|10.00|12.05".parse().unwrap();
+    assert_eq!(parsed, value);
+
+    let err = "MSFT|10|12".parse::<Formatted>().unwrap_err();
+    assert!(err.to_string().contains("для поля fee"));
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Tagged<T, U: Copy>
+where
+    T: Clone,
+{
+    tag: String,
+    value: T,
+    extra: U,
+    #[quote(skip)]
+    cache: Vec<T>,
+}
+
+#[test]
+fn generics_are_propagated() {
+    let value = Tagged {
+        tag: "AAPL".to_string(),
+        value: 5u32,
+        extra: 'x',
+        cache: vec![1, 2],
+    };
+    assert_eq!(value.to_string(), "AAPL|5|x");
+
+    let parsed: Tagged<u32, char> = "AAPL|5|x".parse().unwrap();
+    assert_eq!(
+        parsed,
+        Tagged {
+            cache: vec![],
+            ..value
+        }
+    );
+}
+
+#[derive(QuoteDisplay)]
+struct Labeled<'a> {
+    label: &'a str,
+    count: u8,
+}
+
+#[test]
+fn borrowed_fields_get_display() {
+    let label = String::from("AAPL");
+    let value = Labeled {
+        label: &label,
+        count: 3,
+    };
+    assert_eq!(value.to_string(), "AAPL|3");
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Evolving {
+    ticker: String,
+    #[quote(optional)]
+    bid: Option<f64>,
+    volume: u32,
+    #[quote(optional, format = "{:.1}")]
+    ask: Option<f64>,
+    #[quote(optional)]
+    exchange: Option<String>,
+}
+
+#[test]
+fn optional_fields_use_empty_segments() {
+    let value = Evolving {
+        ticker: "AAPL".to_string(),
+        bid: None,
+        volume: 3,
+        ask: Some(10.25),
+        exchange: None,
+    };
+    let line = value.to_string();
+    assert_eq!(line, "AAPL||3|10.2|");
+    let parsed: Evolving = line.parse().unwrap();
+    assert_eq!(parsed.bid, None);
+    assert_eq!(parsed.ask, Some(10.2));
+    assert_eq!(parsed.exchange, None);
+
+    let full: Evolving = "AAPL|9.5|3|10|NYSE".parse().unwrap();
+    assert_eq!(full.bid, Some(9.5));
+    assert_eq!(full.exchange.as_deref(), Some("NYSE"));
+}
+
+#[test]
+fn trailing_optional_fields_may_be_missing() {
+    let old: Evolving = "AAPL|9.5|3".parse().unwrap();
+    assert_eq!((old.ask, old.exchange), (None, None));
+    assert!("AAPL|9.5|3|1|NYSE|extra".parse::<Evolving>().is_err());
+
+    // Обязательное поле опустить нельзя.
+    let err = "AAPL|9.5".parse::<Evolving>().unwrap_err();
+    assert!(err.to_string().contains("ожидается от 3 до 5 типа"));
+}
+
+/// Формат `ticker|volume|seq|venue`: поля объявлены в другом порядке.
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Pinned {
+    #[pos(1)]
+    volume: u32,
+    #[quote(skip)]
+    cache: Vec<u8>,
+    #[pos(3)]
+    #[quote(optional)]
+    venue: Option<String>,
+    #[pos(0)]
+    ticker: String,
+    #[pos(2)]
+    #[quote(default)]
+    seq: u64,
+}
+
+#[test]
+fn explicit_positions_pin_the_layout() {
+    let value = Pinned {
+        volume: 10,
+        cache: vec![1],
+        venue: Some("NYSE".to_string()),
+        ticker: "AAPL".to_string(),
+        seq: 7,
+    };
+    assert_eq!(value.to_string(), "AAPL|10|7|NYSE");
+
+    let parsed: Pinned = "AAPL|10|7|NYSE".parse().unwrap();
+    assert_eq!(
+        parsed,
+        Pinned {
+            cache: vec![],
+            ..value
+        }
+    );
+
+    // Строка до появления `seq` и `venue`.
+    let old: Pinned = "AAPL|10".parse().unwrap();
+    assert_eq!((old.seq, old.venue), (0, None));
+
+    let err = "AAPL|x".parse::<Pinned>().unwrap_err();
+    assert!(
+        err.to_string().contains("на позиции 1 для поля volume"),
+        "{err}"
+    );
+}
+
+/// Значение без `FromStr`.
+#[derive(Debug, PartialEq)]
+struct Elapsed(u64);
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+/// Значение без `Display`.
+#[derive(Debug, PartialEq)]
+struct Level(u8);
+
+impl std::str::FromStr for Level {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim_start_matches('L').parse().map(Level)
+    }
+}
+
+#[derive(QuoteDisplay)]
+#[quote_display(only = "display")]
+struct LogLine {
+    ticker: String,
+    elapsed: Elapsed,
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(only = "fromstr", delimiter = ";")]
+struct Inbound {
+    ticker: String,
+    level: Level,
+}
+
+#[test]
+fn single_direction_derives() {
+    let line = LogLine {
+        ticker: "AAPL".to_string(),
+        elapsed: Elapsed(12),
+    };
+    assert_eq!(line.to_string(), "AAPL|12ms");
+
+    assert_eq!(
+        "MSFT;L2".parse::<Inbound>().unwrap(),
+        Inbound {
+            ticker: "MSFT".to_string(),
+            level: Level(2),
+        }
+    );
+    assert!("MSFT;x".parse::<Inbound>().is_err());
+}
+
+mod custom {
+    /// Собственная ошибка разбора.
+    #[derive(Debug, PartialEq)]
+    pub struct ParseError(pub String);
+
+    impl From<String> for ParseError {
+        fn from(message: String) -> Self {
+            ParseError(message)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote(error = "custom::ParseError")]
+struct CustomError {
+    ticker: String,
+    volume: u32,
+}
+
+#[test]
+fn custom_error_type() {
+    let value: CustomError = "AAPL|10".parse().unwrap();
+    assert_eq!(value.volume, 10);
+
+    let custom::ParseError(message) = "AAPL".parse::<CustomError>().unwrap_err();
+    assert!(message.contains("2"), "{message}");
+    let custom::ParseError(message) = "AAPL|x".parse::<CustomError>().unwrap_err();
+    assert!(message.contains("volume"), "{message}");
+}
+
+/// `commons` под другим путём, как в крейте, который его реэкспортирует.
+mod shared {
+    pub use commons::{errors, escape, traits};
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote(crate = "shared")]
+#[quote_display(escape)]
+struct Reexported {
+    ticker: String,
+    note: String,
+}
+
+#[test]
+fn crate_root_is_configurable() {
+    let value = Reexported {
+        ticker: "AAPL".to_string(),
+        note: "a|b".to_string(),
+    };
+    assert_eq!(value.to_string().parse::<Reexported>().unwrap(), value);
+    assert!(matches!(
+        "AAPL".parse::<Reexported>(),
+        Err(shared::errors::QuoteError::ValueError(_))
+    ));
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(escape)]
+struct Escaped {
+    ticker: String,
+    note: String,
+    #[quote(optional)]
+    comment: Option<String>,
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(delimiter = "{}", escape)]
+struct EscapedBraces {
+    left: String,
+    right: String,
+}
+
+#[test]
+fn escape_roundtrips_delimiter_in_values() {
+    let value = Escaped {
+        ticker: "AAPL".to_string(),
+        note: r"a|b\c".to_string(),
+        comment: Some("|".to_string()),
+    };
+    let line = value.to_string();
+    assert_eq!(line, "AAPL|a\\|b\\\\c|\\|");
+    assert_eq!(line.parse::<Escaped>().unwrap(), value);
+
+    let empty: Escaped = "AAPL||".parse().unwrap();
+    assert_eq!(empty.comment, None);
+    assert!("AAPL|a|b|c".parse::<Escaped>().is_err());
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(delimiter = ".", escape)]
+struct EscapedCents {
+    #[quote(with = "cents")]
+    amount: u64,
+    volume: u32,
+}
+
+#[test]
+fn escape_applies_to_with_fields() {
+    let value = EscapedCents {
+        amount: 1205,
+        volume: 3,
+    };
+    let line = value.to_string();
+    assert_eq!(line, "12\\.05.3");
+    assert_eq!(line.parse::<EscapedCents>().unwrap(), value);
+}
+
+#[test]
+fn escape_with_multichar_delimiter() {
+    let value = EscapedBraces {
+        left: "{}".to_string(),
+        right: "}{".to_string(),
+    };
+    let line = value.to_string();
+    assert_eq!(line, "\\{}{}}\\{");
+    assert_eq!(line.parse::<EscapedBraces>().unwrap(), value);
+}
+
+/// Уровень стакана для вложения: объём в конце строки необязателен.
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct BookLevel {
+    price: u32,
+    #[quote(optional)]
+    size: Option<u32>,
+}
+
+/// Формат `seq|bid.price|bid.size|ask.price|ask.size|venue`.
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Envelope {
+    seq: u64,
+    #[quote(flatten)]
+    bid: BookLevel,
+    #[quote(flatten)]
+    ask: BookLevel,
+    venue: String,
+}
+
+#[test]
+fn flattened_fields_are_spliced_inline() {
+    let value = Envelope {
+        seq: 1,
+        bid: BookLevel {
+            price: 10,
+            size: Some(5),
+        },
+        ask: BookLevel {
+            price: 11,
+            size: None,
+        },
+        venue: "NYSE".to_string(),
+    };
+    let line = value.to_string();
+    assert_eq!(line, "1|10|5|11||NYSE");
+    assert_eq!(line.parse::<Envelope>().unwrap(), value);
+
+    let err = "1|10|5|x||NYSE".parse::<Envelope>().unwrap_err();
+    assert!(err.to_string().contains("на позиции 3 для поля ask"));
+    let err = "1|10|11|NYSE".parse::<Envelope>().unwrap_err();
+    assert!(err.to_string().contains("ожидается 6 типа"));
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+struct Tail<T> {
+    tag: String,
+    #[quote(flatten)]
+    inner: T,
+}
+
+#[test]
+fn trailing_flattened_field_may_be_short() {
+    let value: Tail<BookLevel> = "a|10".parse().unwrap();
+    assert_eq!(
+        value.inner,
+        BookLevel {
+            price: 10,
+            size: None
+        }
+    );
+    assert_eq!(value.to_string(), "a|10|");
+
+    let err = "a".parse::<Tail<BookLevel>>().unwrap_err();
+    assert!(err.to_string().contains("ожидается от 2 до 3 типа"));
+}
+
+#[derive(Debug, PartialEq, QuoteDisplay)]
+#[quote_display(escape)]
+struct EscapedEnvelope {
+    #[quote(flatten)]
+    pipe: Pipe,
+    note: String,
+}
+
+#[test]
+fn flattened_fields_are_escaped_by_outer_struct() {
+    let value = EscapedEnvelope {
+        pipe: Pipe {
+            ticker: "A|B".to_string(),
+            volume: 2,
+        },
+        note: "x".to_string(),
+    };
+    let line = value.to_string();
+    assert_eq!(line, r"A\|B|2|x");
+    assert_eq!(line.parse::<EscapedEnvelope>().unwrap(), value);
+}
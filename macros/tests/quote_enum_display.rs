@@ -0,0 +1,161 @@
+//! Проверки кода, генерируемого `QuoteEnumDisplay`.
+
+use commons::errors::QuoteError;
+use macros::QuoteEnumDisplay;
+
+#[derive(Debug, PartialEq, QuoteEnumDisplay)]
+enum Order {
+    #[str("market")]
+    Market,
+    #[str("limit")]
+    Limit(f64),
+    Ticker(String),
+}
+
+#[test]
+fn unit_variants_are_case_insensitive() {
+    assert_eq!(Order::Market.to_string(), "market");
+    assert_eq!(" MARKET ".parse::<Order>().unwrap(), Order::Market);
+}
+
+#[test]
+fn data_variants_roundtrip() {
+    let limit = Order::Limit(42.5);
+    assert_eq!(limit.to_string(), "limit:42.5");
+    assert_eq!("limit:42.5".parse::<Order>().unwrap(), limit);
+    assert_eq!("LIMIT:42.5".parse::<Order>().unwrap(), limit);
+
+    // Значение после разделителя берётся как есть, с регистром.
+    let ticker = Order::Ticker("AAPL".to_string());
+    assert_eq!(ticker.to_string(), "ticker:AAPL");
+    assert_eq!("ticker:AAPL".parse::<Order>().unwrap(), ticker);
+}
+
+#[test]
+fn bad_data_is_value_error() {
+    let err = "limit:abc".parse::<Order>().unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+    assert!(err.to_string().contains("Order::Limit"));
+
+    assert!("limit".parse::<Order>().is_err());
+    assert!("market:1".parse::<Order>().is_err());
+    assert!("stop:1".parse::<Order>().is_err());
+}
+
+#[derive(Debug, PartialEq, QuoteEnumDisplay)]
+enum Command {
+    #[str("stream")]
+    Stream,
+    #[str("cancel", alias = "stop", alias = "unsubscribe")]
+    Cancel,
+    #[str("limit", alias = "lim")]
+    Limit(u32),
+}
+
+#[test]
+fn aliases_parse_to_canonical_variant() {
+    for s in ["cancel", "STOP", "unsubscribe"] {
+        assert_eq!(s.parse::<Command>().unwrap(), Command::Cancel);
+    }
+    assert_eq!(Command::Cancel.to_string(), "cancel");
+
+    let limit: Command = "lim:5".parse().unwrap();
+    assert_eq!(limit, Command::Limit(5));
+    assert_eq!(limit.to_string(), "limit:5");
+}
+
+#[derive(Debug, PartialEq, QuoteEnumDisplay)]
+#[quote_enum(case = "sensitive", trim = false)]
+enum Unit {
+    #[str("M")]
+    Mega,
+    #[str("m")]
+    Milli,
+    #[str("x")]
+    Scale(u8),
+}
+
+#[test]
+fn case_sensitive_without_trim() {
+    assert_eq!("M".parse::<Unit>().unwrap(), Unit::Mega);
+    assert_eq!("m".parse::<Unit>().unwrap(), Unit::Milli);
+    assert_eq!("x:2".parse::<Unit>().unwrap(), Unit::Scale(2));
+    assert!("X:2".parse::<Unit>().is_err());
+    assert!(" M".parse::<Unit>().is_err());
+    assert!("x :2".parse::<Unit>().is_err());
+}
+
+#[derive(Debug, PartialEq, QuoteEnumDisplay)]
+enum Mixed {
+    #[str("Start")]
+    Start,
+}
+
+#[test]
+fn insensitive_matches_mixed_case_strings() {
+    assert_eq!("start".parse::<Mixed>().unwrap(), Mixed::Start);
+    assert_eq!(" START ".parse::<Mixed>().unwrap(), Mixed::Start);
+    assert_eq!(Mixed::Start.to_string(), "Start");
+}
+
+#[test]
+fn variants_and_strs_follow_declaration_order() {
+    assert_eq!(Order::variants(), [Order::Market]);
+    assert_eq!(Order::variant_strs(), ["market", "limit", "ticker"]);
+    assert_eq!(Command::variants(), [Command::Stream, Command::Cancel]);
+    assert_eq!(Command::variant_strs(), ["stream", "cancel", "limit"]);
+
+    for variant in Unit::variants() {
+        assert_eq!(&variant.to_string().parse::<Unit>().unwrap(), variant);
+    }
+}
+
+/// Собственная ошибка разбора.
+#[derive(Debug, PartialEq)]
+struct ParseError(String);
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        ParseError(message)
+    }
+}
+
+#[derive(Debug, PartialEq, QuoteEnumDisplay)]
+#[quote(error = "ParseError")]
+enum Side {
+    #[str("buy")]
+    Buy,
+    #[str("qty")]
+    Qty(u32),
+}
+
+#[test]
+fn custom_error_type() {
+    assert_eq!("qty:5".parse::<Side>().unwrap(), Side::Qty(5));
+    let ParseError(message) = "sell".parse::<Side>().unwrap_err();
+    assert!(message.contains("sell"), "{message}");
+    assert!("qty:x".parse::<Side>().is_err());
+    assert_eq!(Side::Buy.to_string(), "buy");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, QuoteEnumDisplay)]
+enum Tag {
+    #[str("new")]
+    #[code(1)]
+    New,
+    #[code(7)]
+    Filled,
+    #[code(255)]
+    Rejected,
+}
+
+#[test]
+fn numeric_codes_roundtrip() {
+    assert_eq!(Tag::New.to_code(), 1);
+    assert_eq!(Tag::Rejected.to_code(), 255);
+    for tag in Tag::variants() {
+        assert_eq!(Tag::from_code(tag.to_code()), Some(*tag));
+    }
+    assert_eq!(Tag::from_code(0), None);
+    assert_eq!(Tag::Filled.to_string(), "filled");
+}
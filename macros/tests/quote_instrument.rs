@@ -0,0 +1,110 @@
+//! Проверки кода, генерируемого `#[quote_instrument]`.
+
+use commons::errors::QuoteError;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use macros::quote_instrument;
+use std::sync::Mutex;
+
+/// Журнал, сохраняющий сообщения для проверки.
+struct Capture(Mutex<Vec<(Level, String, String)>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+/// Сообщения о вызовах `name`, записанные при выполнении `f`.
+fn captured(name: &str, f: impl FnOnce()) -> Vec<(Level, String, String)> {
+    if log::set_logger(&CAPTURE).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+    f();
+    CAPTURE
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, _, message)| message.contains(name))
+        .cloned()
+        .collect()
+}
+
+#[quote_instrument]
+fn sum(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[quote_instrument(level = "trace", name = "parse_volume")]
+fn parse(s: &str) -> Result<u32, QuoteError> {
+    if s.is_empty() {
+        return Err(QuoteError::value_err("пустой объём"));
+    }
+    let volume = s
+        .parse::<u32>()
+        .map_err(|err| QuoteError::value_err(err.to_string()))?;
+    Ok(volume)
+}
+
+struct Counter(u32);
+
+impl Counter {
+    #[quote_instrument(level = "info")]
+    fn bump(&mut self) -> &u32 {
+        self.0 += 1;
+        &self.0
+    }
+}
+
+#[test]
+fn logs_call_and_duration() {
+    let records = captured("sum", || assert_eq!(sum(2, 3), 5));
+    assert_eq!(records.len(), 2, "{records:?}");
+    assert_eq!(records[0].0, Level::Debug);
+    assert_eq!(records[0].1, module_path!());
+    assert_eq!(records[0].2, "Вызов sum");
+    assert!(records[1].2.starts_with("sum завершён за "), "{records:?}");
+}
+
+#[test]
+fn logs_errors_with_warn_level() {
+    let records = captured("parse_volume", || {
+        assert_eq!(parse("10").unwrap(), 10);
+        assert!(parse("").is_err());
+        assert!(parse("x").is_err());
+    });
+    let levels: Vec<Level> = records.iter().map(|(level, ..)| *level).collect();
+    assert_eq!(
+        levels,
+        [
+            Level::Trace,
+            Level::Trace,
+            Level::Trace,
+            Level::Warn,
+            Level::Trace,
+            Level::Warn
+        ]
+    );
+    assert!(records[3].2.contains("с ошибкой"), "{records:?}");
+    assert!(records[3].2.ends_with("пустой объём"), "{records:?}");
+}
+
+#[test]
+fn instruments_methods() {
+    let mut counter = Counter(0);
+    let records = captured("bump", || assert_eq!(*counter.bump(), 1));
+    assert_eq!(records.len(), 2, "{records:?}");
+    assert_eq!(records[1].0, Level::Info);
+}
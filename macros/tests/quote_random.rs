@@ -0,0 +1,95 @@
+//! Проверки кода, генерируемого `QuoteRandom`.
+
+use commons::randomizer::Randomizer;
+use macros::QuoteRandom;
+
+#[derive(Debug, Clone, Copy, PartialEq, QuoteRandom)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, PartialEq, QuoteRandom)]
+enum Event {
+    Tick,
+    Trade(Side, #[random(range(1, 10))] u32),
+    Halt {
+        #[random(choice("news", "limit"))]
+        reason: String,
+    },
+}
+
+#[derive(Debug, PartialEq, QuoteRandom)]
+struct Fill {
+    #[random(choice("AAPL", "MSFT"))]
+    ticker: String,
+    #[random(range(1.0, 100.0))]
+    price: f64,
+    #[random(range(-5, 5))]
+    offset: i32,
+    #[random(choice(10, 20, 30))]
+    lot: u32,
+    #[random(default)]
+    seq: u64,
+    #[random(range(0.5, 1.5))]
+    fee: Option<f64>,
+    side: Side,
+    note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, QuoteRandom)]
+struct Pair(u8, #[random(range(1, 3))] u8);
+
+#[test]
+fn fields_follow_rules() {
+    let mut rng = Randomizer::with_seed(1);
+    for _ in 0..200 {
+        let fill = Fill::random(&mut rng);
+        assert!(["AAPL", "MSFT"].contains(&fill.ticker.as_str()));
+        assert!((1.0..=100.0).contains(&fill.price));
+        assert!((-5..=5).contains(&fill.offset));
+        assert!([10, 20, 30].contains(&fill.lot));
+        assert_eq!(fill.seq, 0);
+        assert!(fill.fee.is_none_or(|fee| (0.5..=1.5).contains(&fee)));
+        if let Some(note) = &fill.note {
+            assert!((1..=8).contains(&note.len()));
+        }
+
+        let Pair(_, small) = rng.value();
+        assert!((1..=3).contains(&small));
+    }
+}
+
+#[test]
+fn all_variants_and_options_occur() {
+    let mut rng = Randomizer::with_seed(2);
+    let fills: Vec<Fill> = (0..200).map(|_| rng.value()).collect();
+    assert!(fills.iter().any(|fill| fill.side == Side::Buy));
+    assert!(fills.iter().any(|fill| fill.side == Side::Sell));
+    assert!(fills.iter().any(|fill| fill.fee.is_some()));
+    assert!(fills.iter().any(|fill| fill.fee.is_none()));
+
+    let events: Vec<Event> = (0..200).map(|_| rng.value()).collect();
+    assert!(events.contains(&Event::Tick));
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, Event::Trade(_, 1..=10)))
+    );
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, Event::Halt { reason } if reason == "news"))
+    );
+}
+
+#[test]
+fn same_seed_same_values() {
+    let first: Vec<Fill> = {
+        let mut rng = Randomizer::with_seed(3);
+        (0..10).map(|_| Fill::random(&mut rng)).collect()
+    };
+    let mut rng = Randomizer::with_seed(3);
+    let second: Vec<Fill> = (0..10).map(|_| Fill::random(&mut rng)).collect();
+    assert_eq!(first, second);
+}
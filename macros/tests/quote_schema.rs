@@ -0,0 +1,75 @@
+//! Проверки кода, генерируемого `QuoteSchema`.
+
+use commons::schema::{JsonSchema, root_schema};
+use macros::QuoteSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Сторона сделки.
+///
+/// Второй абзац в описание не входит.
+#[derive(Debug, PartialEq, Serialize, Deserialize, QuoteSchema)]
+enum Side {
+    #[serde(rename = "buy")]
+    Buy,
+    Sell,
+}
+
+/// Сделка
+/// по тикеру.
+#[derive(Debug, PartialEq, Serialize, Deserialize, QuoteSchema)]
+struct Fill {
+    /// Тикер.
+    ticker: String,
+    #[serde(rename = "qty")]
+    volume: u32,
+    side: Side,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Комиссия, если известна.
+    fee: Option<f64>,
+    #[serde(skip)]
+    cache: u64,
+}
+
+#[test]
+fn struct_schema_follows_serde_attributes() {
+    let schema = root_schema::<Fill>();
+    assert_eq!(schema["$schema"], commons::schema::SCHEMA_DIALECT);
+    assert_eq!(schema["title"], "Fill");
+    assert_eq!(schema["description"], "Сделка по тикеру.");
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["ticker", "qty", "side"]));
+
+    let properties = &schema["properties"];
+    assert_eq!(
+        properties["ticker"],
+        json!({ "type": "string", "description": "Тикер." })
+    );
+    assert_eq!(
+        properties["qty"],
+        json!({ "type": "integer", "minimum": 0 })
+    );
+    assert_eq!(properties["tags"]["items"], json!({ "type": "string" }));
+    assert_eq!(properties["fee"]["anyOf"][1], json!({ "type": "null" }));
+    assert!(properties.get("volume").is_none());
+    assert!(properties.get("cache").is_none());
+}
+
+#[test]
+fn enum_schema_lists_serde_names() {
+    assert_eq!(
+        Side::json_schema(),
+        json!({
+            "title": "Side",
+            "description": "Сторона сделки.",
+            "type": "string",
+            "enum": ["buy", "Sell"],
+        })
+    );
+    assert_eq!(
+        root_schema::<Fill>()["properties"]["side"]["enum"],
+        json!(["buy", "Sell"])
+    );
+    assert_eq!(serde_json::to_value(Side::Buy).unwrap(), "buy");
+}
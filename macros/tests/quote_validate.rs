@@ -0,0 +1,91 @@
+//! Проверки кода, генерируемого `QuoteValidate`.
+
+use commons::errors::QuoteError;
+use commons::traits::Validate;
+use macros::QuoteValidate;
+
+#[derive(QuoteValidate)]
+struct Order {
+    #[validate(non_empty, len(max = 8))]
+    ticker: String,
+    #[validate(range(min = 0.0))]
+    price: f64,
+    #[validate(range(min = 1, max = 1000))]
+    volume: u32,
+    #[validate(len(min = 3, max = 3))]
+    currency: Option<String>,
+}
+
+fn order() -> Order {
+    Order {
+        ticker: "AAPL".to_string(),
+        price: 10.5,
+        volume: 100,
+        currency: None,
+    }
+}
+
+fn error(order: Order) -> String {
+    let err = order.validate().unwrap_err();
+    assert!(matches!(err, QuoteError::ValueError(_)));
+    err.to_string()
+}
+
+#[test]
+fn valid_values_pass() {
+    assert!(order().validate().is_ok());
+    let order = Order {
+        price: 0.0,
+        currency: Some("USD".to_string()),
+        ..order()
+    };
+    assert!(order.validate().is_ok());
+}
+
+#[test]
+fn violations_name_the_field() {
+    let empty = Order {
+        ticker: String::new(),
+        ..order()
+    };
+    assert_eq!(error(empty), "ticker: пустое значение");
+
+    let long = Order {
+        ticker: "TOOLONGTICKER".to_string(),
+        ..order()
+    };
+    assert_eq!(error(long), "ticker: длина 13 больше 8");
+
+    let negative = Order {
+        price: -1.5,
+        ..order()
+    };
+    assert_eq!(error(negative), "price: значение -1.5 меньше 0");
+
+    let nan = Order {
+        price: f64::NAN,
+        ..order()
+    };
+    assert!(error(nan).starts_with("price:"));
+
+    let huge = Order {
+        volume: 5000,
+        ..order()
+    };
+    assert_eq!(error(huge), "volume: значение 5000 больше 1000");
+
+    let zero = Order {
+        volume: 0,
+        ..order()
+    };
+    assert_eq!(error(zero), "volume: значение 0 меньше 1");
+}
+
+#[test]
+fn optional_field_is_checked_when_set() {
+    let bad = Order {
+        currency: Some("DOLLAR".to_string()),
+        ..order()
+    };
+    assert_eq!(error(bad), "currency: длина 6 больше 3");
+}
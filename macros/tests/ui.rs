@@ -0,0 +1,7 @@
+//! Ошибки компиляции derive-макросов: сообщения указывают на место ошибки.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,15 @@
+use macros::QuoteBinary;
+
+#[derive(QuoteBinary)]
+struct Fill {
+    ticker: String,
+    volume: u32,
+}
+
+#[derive(QuoteBinary)]
+struct Tagged {
+    #[binary(len = 8)]
+    ticker: String,
+}
+
+fn main() {}
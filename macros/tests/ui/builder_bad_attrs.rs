@@ -0,0 +1,20 @@
+use macros::QuoteBuilder;
+
+#[derive(QuoteBuilder)]
+#[quote_builder(strict)]
+struct Strict {
+    ticker: String,
+}
+
+#[derive(QuoteBuilder)]
+struct Generic<T> {
+    value: T,
+}
+
+#[derive(QuoteBuilder)]
+struct Field {
+    #[builder(skip)]
+    ticker: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use macros::QuoteBuilder;
+
+#[derive(QuoteBuilder)]
+struct Order {
+    ticker: String,
+    price: f64,
+}
+
+fn main() {
+    let _ = Order::builder().ticker("AAPL").build();
+    let _ = Order::builder().ticker("AAPL").ticker("MSFT");
+}
@@ -0,0 +1,41 @@
+use macros::QuoteCommand;
+
+#[derive(QuoteCommand)]
+enum RestNotLast {
+    #[str("stream")]
+    Stream {
+        #[arg(rest)]
+        tickers: String,
+        udp: String,
+    },
+}
+
+#[derive(QuoteCommand)]
+enum RequiredAfterOptional {
+    #[str("cancel")]
+    Cancel { note: Option<String>, udp: String },
+}
+
+#[derive(QuoteCommand)]
+enum UnknownParam {
+    #[str("stream")]
+    Stream {
+        #[arg(split)]
+        tickers: String,
+    },
+}
+
+#[derive(QuoteCommand)]
+enum Duplicate {
+    #[str("stop")]
+    Stop,
+    #[str("cancel", alias = "STOP")]
+    Cancel,
+}
+
+#[derive(QuoteCommand)]
+struct NotEnum {
+    udp: String,
+}
+
+fn main() {}
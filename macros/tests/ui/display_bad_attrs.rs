@@ -0,0 +1,50 @@
+use macros::{QuoteCsv, QuoteDisplay};
+
+#[derive(QuoteDisplay)]
+#[quote_display(delimiter = "")]
+struct Empty {
+    ticker: String,
+}
+
+#[derive(QuoteDisplay)]
+struct Unknown {
+    #[quote(rename = "t")]
+    ticker: String,
+}
+
+#[derive(QuoteDisplay)]
+#[quote_display(delimiter = "\\;", escape)]
+struct BackslashDelimiter {
+    ticker: String,
+}
+
+#[derive(QuoteDisplay)]
+#[quote_display(newline = "yes")]
+struct BadNewline {
+    ticker: String,
+}
+
+#[derive(QuoteDisplay)]
+#[quote_display(only = "parse")]
+struct BadOnly {
+    ticker: String,
+}
+
+#[derive(QuoteDisplay)]
+struct Inner {
+    price: u32,
+}
+
+#[derive(QuoteDisplay)]
+struct FlattenOptional {
+    #[quote(flatten, optional)]
+    inner: Option<Inner>,
+}
+
+#[derive(QuoteCsv)]
+struct FlattenCsv {
+    #[quote(flatten)]
+    inner: Inner,
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+use macros::QuoteDisplay;
+
+#[derive(QuoteDisplay)]
+struct MissingPos {
+    #[pos(0)]
+    ticker: String,
+    volume: u32,
+}
+
+#[derive(QuoteDisplay)]
+struct DuplicatePos {
+    #[pos(0)]
+    ticker: String,
+    #[pos(0)]
+    volume: u32,
+}
+
+#[derive(QuoteDisplay)]
+struct GapPos {
+    #[pos(0)]
+    ticker: String,
+    #[pos(2)]
+    volume: u32,
+}
+
+#[derive(QuoteDisplay)]
+struct SkippedPos {
+    ticker: String,
+    #[pos(1)]
+    #[quote(skip)]
+    cache: u32,
+}
+
+#[derive(QuoteDisplay)]
+struct BadPos {
+    #[pos(first)]
+    ticker: String,
+}
+
+fn main() {}
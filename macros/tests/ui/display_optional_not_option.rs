@@ -0,0 +1,10 @@
+use macros::QuoteDisplay;
+
+#[derive(QuoteDisplay)]
+struct Quote {
+    ticker: String,
+    #[quote(optional)]
+    bid: f64,
+}
+
+fn main() {}
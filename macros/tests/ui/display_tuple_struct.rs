@@ -0,0 +1,6 @@
+use macros::QuoteDisplay;
+
+#[derive(QuoteDisplay)]
+struct Pair(String, u32);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use macros::QuoteDisplay;
+
+#[derive(QuoteDisplay)]
+union Raw {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+enum Command {
+    #[str("stream", alias = "go")]
+    Stream,
+    #[str("resume", alias = "go")]
+    Resume,
+}
+
+#[derive(QuoteEnumDisplay)]
+enum Side {
+    #[str("buy", synonym = "long")]
+    Buy,
+}
+
+fn main() {}
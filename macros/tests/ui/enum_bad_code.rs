@@ -0,0 +1,32 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+enum Partial {
+    #[code(1)]
+    Buy,
+    Sell,
+}
+
+#[derive(QuoteEnumDisplay)]
+enum Duplicate {
+    #[code(1)]
+    Buy,
+    #[code(1)]
+    Sell,
+}
+
+#[derive(QuoteEnumDisplay)]
+enum WithData {
+    #[code(1)]
+    Market,
+    #[code(2)]
+    Limit(f64),
+}
+
+#[derive(QuoteEnumDisplay)]
+enum TooLarge {
+    #[code(256)]
+    Buy,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+#[quote_enum(case = "upper")]
+enum Side {
+    Buy,
+}
+
+#[derive(QuoteEnumDisplay)]
+#[quote_enum(strip = true)]
+enum Kind {
+    Limit,
+}
+
+#[derive(QuoteEnumDisplay)]
+enum Twice {
+    #[str("Go")]
+    Start,
+    #[str("go")]
+    Resume,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+enum Side {
+    #[str(buy)]
+    Buy,
+    Sell,
+}
+
+#[derive(QuoteEnumDisplay)]
+enum Twice {
+    #[str("go")]
+    Start,
+    #[str("go")]
+    Resume,
+}
+
+fn main() {}
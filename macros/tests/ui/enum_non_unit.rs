@@ -0,0 +1,15 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+enum Command {
+    Stream,
+    Limit { price: f64 },
+}
+
+#[derive(QuoteEnumDisplay)]
+enum Range {
+    All,
+    Between(f64, f64),
+}
+
+fn main() {}
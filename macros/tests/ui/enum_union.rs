@@ -0,0 +1,9 @@
+use macros::QuoteEnumDisplay;
+
+#[derive(QuoteEnumDisplay)]
+union Raw {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use macros::quote_instrument;
+
+#[quote_instrument(level = "verbose")]
+fn bad_level() {}
+
+#[quote_instrument(target = "server")]
+fn unknown_param() {}
+
+#[quote_instrument]
+async fn async_fn() {}
+
+#[quote_instrument]
+const fn const_fn() {}
+
+fn main() {}
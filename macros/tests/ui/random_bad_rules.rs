@@ -0,0 +1,30 @@
+use macros::QuoteRandom;
+
+#[derive(QuoteRandom)]
+struct BadRange {
+    #[random(range(1))]
+    volume: u32,
+}
+
+#[derive(QuoteRandom)]
+struct EmptyChoice {
+    #[random(choice())]
+    ticker: String,
+}
+
+#[derive(QuoteRandom)]
+struct TwoRules {
+    #[random(default, range(1, 2))]
+    volume: u32,
+}
+
+#[derive(QuoteRandom)]
+struct Unknown {
+    #[random(regex("[A-Z]+"))]
+    ticker: String,
+}
+
+#[derive(QuoteRandom)]
+enum Empty {}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use macros::QuoteSchema;
+
+#[derive(QuoteSchema)]
+struct Tuple(u32);
+
+#[derive(QuoteSchema)]
+enum WithData {
+    Market,
+    Limit(f64),
+}
+
+#[derive(QuoteSchema)]
+struct Generic<T> {
+    value: T,
+}
+
+#[derive(serde::Serialize, QuoteSchema)]
+#[serde(rename_all = "lowercase")]
+enum Renamed {
+    Buy,
+}
+
+fn main() {}
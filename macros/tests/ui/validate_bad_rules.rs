@@ -0,0 +1,26 @@
+use macros::QuoteValidate;
+
+#[derive(QuoteValidate)]
+struct Unknown {
+    #[validate(positive)]
+    price: f64,
+}
+
+#[derive(QuoteValidate)]
+struct NoBounds {
+    #[validate(range)]
+    volume: u32,
+}
+
+#[derive(QuoteValidate)]
+struct BadBound {
+    #[validate(len(limit = 8))]
+    ticker: String,
+}
+
+#[derive(QuoteValidate)]
+enum Side {
+    Buy,
+}
+
+fn main() {}
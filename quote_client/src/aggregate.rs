@@ -0,0 +1,119 @@
+//! Объединение потоков котировок от нескольких серверов.
+
+use crate::cli::Subscription;
+use crate::output::console;
+use commons::i18n::tr;
+use commons::models::StockQuote;
+use commons::utils::fmt_duration;
+use log::{info, warn};
+use quote_client_lib::{QuoteStream, StreamControl};
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::AtomicBool,
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+use tracing::info_span;
+
+/// Подписаться на все серверы и объединить их котировки в один канал.
+///
+/// Подписки оформляются последовательно; временные отказы сервера
+/// повторяются с сообщением в консоль, а серверы, окончательно отклонившие
+/// подписку, пропускаются с предупреждением. Каждая подписка затем читается
+/// в своём потоке, котировки помечаются адресом сервера-источника;
+/// количество котировок, не прошедших проверку, сообщается при завершении
+/// подписки, потерянные и повторные датаграммы — в журнал. Канал
+/// закрывается, когда завершены все подписки (по флагу `stop` или из-за
+/// ошибок).
+///
+/// ## Args
+/// - `subscriptions` — подписки на серверы
+/// - `parse_error_hook` — обработчик датаграмм, которые не удалось разобрать
+/// - `stop` — флаг остановки приёма
+///
+/// ## Returns
+///
+/// Канал котировок и пульты управления действующими подписками.
+pub fn merge_streams(
+    subscriptions: &[Subscription],
+    parse_error_hook: Option<fn(&str)>,
+    stop: Arc<AtomicBool>,
+) -> (Receiver<(SocketAddr, StockQuote)>, Vec<StreamControl>) {
+    let (tx, rx) = mpsc::channel();
+    let mut controls = Vec::new();
+
+    for subscription in subscriptions {
+        let server_addr = subscription.server_addr;
+        let span = info_span!(
+            "subscription",
+            server = %server_addr,
+            tickers = subscription.ticker_count()
+        );
+        let _subscription = span.enter();
+        let mut quotes = match QuoteStream::subscribe_with_retry(
+            server_addr,
+            &subscription.udp_url,
+            &subscription.command,
+            stop.clone(),
+            |attempt, delay, response| {
+                let delay = fmt_duration(delay);
+                console(tr(
+                    format!(
+                        "Сервер {server_addr} ответил {response}, повтор {attempt} через {delay}"
+                    ),
+                    format!("Server {server_addr} replied {response}, retry {attempt} in {delay}"),
+                ))
+            },
+        ) {
+            Ok(quotes) => quotes,
+            Err(err) => {
+                warn!("Сервер {server_addr}: {err}");
+                console(tr(
+                    format!("Сервер {server_addr} отклонил подписку: {err}"),
+                    format!("Server {server_addr} refused subscription: {err}"),
+                ));
+                continue;
+            }
+        };
+        if let Some(hook) = parse_error_hook {
+            quotes.set_parse_error_hook(hook);
+        }
+        let control = quotes.control();
+        controls.push(control.clone());
+
+        let tx = tx.clone();
+        let span = span.clone();
+        thread::spawn(move || {
+            let _subscription = span.enter();
+            for quote in quotes {
+                if tx.send((server_addr, quote)).is_err() {
+                    break;
+                }
+            }
+            info!("Подписка на сервер {server_addr} завершена");
+            let corrupted = control.corrupted();
+            if corrupted > 0 {
+                warn!("Сервер {server_addr}: отброшено повреждённых датаграмм: {corrupted}");
+            }
+            let (lost, duplicates) = (control.lost(), control.duplicates());
+            if lost > 0 || duplicates > 0 {
+                warn!(
+                    "Сервер {server_addr}: потеряно датаграмм: {lost}, отброшено повторов: {duplicates}"
+                );
+            }
+            let rejected = control.rejected();
+            if rejected > 0 {
+                warn!("Сервер {server_addr}: отклонено некорректных котировок: {rejected}");
+                console(tr(
+                    format!("Сервер {server_addr}: отклонено некорректных котировок: {rejected}"),
+                    format!("Server {server_addr}: invalid quotes rejected: {rejected}"),
+                ));
+            }
+        });
+    }
+
+    (rx, controls)
+}
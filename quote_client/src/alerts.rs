@@ -0,0 +1,356 @@
+//! Правила оповещений по котировкам и действия при их срабатывании.
+//!
+//! Правило задаётся строкой вида `AAPL>150` или `TSLA<90`. Оповещение
+//! срабатывает в момент, когда условие становится истинным, и повторно —
+//! только после того, как условие перестанет выполняться.
+//!
+//! При срабатывании, помимо записи в лог, могут выполняться действия:
+//! - запуск программы: JSON котировки передаётся первым аргументом,
+//!   текст правила — вторым;
+//! - HTTP(S) POST: JSON котировки в теле запроса, текст правила в заголовке
+//!   [`ALERT_HEADER`].
+//!
+//! Действия выполняются по очереди одним рабочим потоком: очередь
+//! ограничена [`ALERT_QUEUE_SIZE`], при переполнении действие пропускается
+//! с предупреждением, чтобы медленный webhook не копил потоки.
+
+use crate::config::{ALERT_POST_TIMEOUT_SECS, ALERT_QUEUE_SIZE};
+use crate::output::console;
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+#[cfg(feature = "notifications")]
+use commons::utils::{fmt_price, fmt_volume};
+use log::{info, warn};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    sync::mpsc::{self, SyncSender, TrySendError},
+    thread,
+    time::Duration,
+};
+use url::Url;
+
+/// HTTP-заголовок с текстом сработавшего правила.
+pub const ALERT_HEADER: &str = "X-Quote-Alert";
+
+/// Условие правила оповещения.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertCondition {
+    /// Цена выше порога.
+    Above(f64),
+    /// Цена ниже порога.
+    Below(f64),
+}
+
+/// Правило оповещения для тикера.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    /// Тикер, к которому применяется правило.
+    pub ticker: String,
+    /// Условие срабатывания.
+    pub condition: AlertCondition,
+}
+
+impl AlertRule {
+    /// Выполняется ли условие правила для котировки. Тикер сравнивается
+    /// без учёта регистра: псевдонимы (`--aliases`) вроде `Yandex` пишутся
+    /// в смешанном регистре.
+    pub fn matches(&self, quote: &StockQuote) -> bool {
+        if quote.ticker.to_uppercase() != self.ticker {
+            return false;
+        }
+        match self.condition {
+            AlertCondition::Above(limit) => quote.price.to_f64() > limit,
+            AlertCondition::Below(limit) => quote.price.to_f64() < limit,
+        }
+    }
+}
+
+impl Display for AlertRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.condition {
+            AlertCondition::Above(limit) => write!(f, "{}>{}", self.ticker, limit),
+            AlertCondition::Below(limit) => write!(f, "{}<{}", self.ticker, limit),
+        }
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pos, sign) = s
+            .char_indices()
+            .find(|(_, c)| *c == '>' || *c == '<')
+            .ok_or_else(|| {
+                QuoteError::value_err(format!("правило '{s}' должно содержать '>' или '<'"))
+            })?;
+
+        let ticker = s[..pos].trim().to_uppercase();
+        if ticker.is_empty() {
+            return Err(QuoteError::value_err(format!("в правиле '{s}' нет тикера")));
+        }
+
+        let limit: f64 = s[pos + 1..]
+            .trim()
+            .parse()
+            .map_err(|_| QuoteError::value_err(format!("некорректный порог в правиле '{s}'")))?;
+
+        let condition = if sign == '>' {
+            AlertCondition::Above(limit)
+        } else {
+            AlertCondition::Below(limit)
+        };
+
+        Ok(Self { ticker, condition })
+    }
+}
+
+/// Валидатор для ключа `--alert`.
+pub fn validate_alert_rule(s: &str) -> Result<AlertRule, String> {
+    s.parse().map_err(|err: QuoteError| err.to_string())
+}
+
+/// Действие при срабатывании оповещения.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertAction {
+    /// Запустить программу.
+    Exec(PathBuf),
+    /// Отправить HTTP POST.
+    Post(Url),
+    /// Показать системное уведомление.
+    #[cfg(feature = "notifications")]
+    Notify,
+}
+
+impl AlertAction {
+    /// Выполнить действие.
+    fn run(&self, rule: &str, payload: &str) -> Result<(), QuoteError> {
+        match self {
+            AlertAction::Exec(path) => run_exec(path, payload, rule),
+            AlertAction::Post(url) => run_post(url, payload, rule),
+            #[cfg(feature = "notifications")]
+            AlertAction::Notify => run_notify(payload, rule),
+        }
+    }
+}
+
+/// Задание рабочему потоку: действие, текст правила и JSON котировки.
+type ActionJob = (AlertAction, String, String);
+
+/// Рабочий поток действий с ограниченной очередью.
+struct ActionWorker {
+    sender: SyncSender<ActionJob>,
+}
+
+impl ActionWorker {
+    /// Запустить рабочий поток. Поток завершается, когда `ActionWorker`
+    /// удалён и очередь разобрана.
+    fn spawn() -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<ActionJob>(ALERT_QUEUE_SIZE);
+        thread::spawn(move || {
+            for (action, rule, payload) in receiver {
+                if let Err(err) = action.run(&rule, &payload) {
+                    warn!("Действие оповещения {action:?} завершилось ошибкой: {err}");
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    /// Поставить действие в очередь, не задерживая приём котировок.
+    fn submit(&self, action: &AlertAction, rule: &AlertRule, payload: &str) {
+        let job = (action.clone(), rule.to_string(), payload.to_string());
+        match self.sender.try_send(job) {
+            Ok(()) => {}
+            Err(TrySendError::Full((action, ..))) => {
+                warn!("Очередь действий оповещений переполнена, {action:?} пропущено");
+            }
+            Err(TrySendError::Disconnected((action, ..))) => {
+                warn!("Рабочий поток оповещений завершён, {action:?} пропущено");
+            }
+        }
+    }
+}
+
+/// Запустить программу и дождаться её завершения.
+fn run_exec(path: &Path, payload: &str, rule: &str) -> Result<(), QuoteError> {
+    let status = Command::new(path)
+        .arg(payload)
+        .arg(rule)
+        .status()
+        .map_err(|err| QuoteError::runtime_err(format!("не удалось запустить: {err}")))?;
+
+    if !status.success() {
+        return Err(QuoteError::runtime_err(format!(
+            "программа вернула статус {status}"
+        )));
+    }
+    Ok(())
+}
+
+/// Отправить котировку HTTP POST-запросом.
+fn run_post(url: &Url, payload: &str, rule: &str) -> Result<(), QuoteError> {
+    ureq::post(url.as_str())
+        .timeout(Duration::from_secs(ALERT_POST_TIMEOUT_SECS))
+        .set("Content-Type", "application/json")
+        .set(ALERT_HEADER, rule)
+        .send_string(payload)
+        .map_err(|err| QuoteError::server_err(format!("ошибка запроса: {err}")))?;
+    Ok(())
+}
+
+/// Показать системное уведомление о сработавшем правиле.
+#[cfg(feature = "notifications")]
+fn run_notify(payload: &str, rule: &str) -> Result<(), QuoteError> {
+    let body = match serde_json::from_str::<StockQuote>(payload) {
+        Ok(quote) => format!(
+            "{}: {} ({} шт.)",
+            quote.ticker,
+            fmt_price(quote.price.to_f64()),
+            fmt_volume(quote.volume)
+        ),
+        Err(_) => payload.to_string(),
+    };
+
+    notify_rust::Notification::new()
+        .appname("qclient")
+        .summary(&format!("Оповещение {rule}"))
+        .body(&body)
+        .show()
+        .map_err(|err| QuoteError::runtime_err(format!("ошибка уведомления: {err}")))?;
+    Ok(())
+}
+
+/// Валидатор для ключа `--on-alert-post`: допускаются схемы `http`
+/// и `https`.
+pub fn validate_hook_url(s: &str) -> Result<Url, String> {
+    let url = Url::parse(s).map_err(|err| format!("invalid hook url '{s}': {err}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("hook supports only http:// and https:// urls: {s}"));
+    }
+    Ok(url)
+}
+
+/// Проверка котировок по правилам и запуск действий.
+pub struct AlertEngine {
+    /// Правила и признак того, что правило сейчас в сработавшем состоянии.
+    rules: Vec<(AlertRule, bool)>,
+    /// Действия при срабатывании.
+    actions: Vec<AlertAction>,
+    /// Рабочий поток действий; без действий не запускается.
+    worker: Option<ActionWorker>,
+}
+
+impl AlertEngine {
+    /// Создать экземпляр.
+    pub fn new(rules: Vec<AlertRule>, actions: Vec<AlertAction>) -> Self {
+        Self {
+            rules: rules.into_iter().map(|rule| (rule, false)).collect(),
+            worker: (!actions.is_empty()).then(ActionWorker::spawn),
+            actions,
+        }
+    }
+
+    /// Проверить котировку и выполнить действия по сработавшим правилам.
+    ///
+    /// ## Returns
+    ///
+    /// Правила, сработавшие на этой котировке.
+    pub fn check(&mut self, quote: &StockQuote) -> Vec<AlertRule> {
+        let mut fired = Vec::new();
+        let ticker = quote.ticker.to_uppercase();
+
+        for (rule, active) in self.rules.iter_mut() {
+            if rule.ticker != ticker {
+                continue;
+            }
+            let matched = rule.matches(quote);
+            if matched && !*active {
+                fired.push(rule.clone());
+            }
+            *active = matched;
+        }
+
+        if fired.is_empty() {
+            return fired;
+        }
+
+        let payload = match serde_json::to_string(quote) {
+            Ok(json) => json,
+            Err(err) => {
+                warn!(
+                    "Ошибка преобразования котировки {} в json: {err}",
+                    quote.ticker
+                );
+                return fired;
+            }
+        };
+
+        for rule in &fired {
+            let message = format!("Оповещение {rule}: {quote}");
+            info!("{}", message);
+            console(&message);
+            if let Some(worker) = &self.worker {
+                for action in &self.actions {
+                    worker.submit(action, rule, &payload);
+                }
+            }
+        }
+
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::quote;
+
+    #[test]
+    fn rule_parses_and_displays() {
+        let rule: AlertRule = "aapl > 150.5".parse().unwrap();
+
+        assert_eq!(rule.ticker, "AAPL");
+        assert_eq!(rule.condition, AlertCondition::Above(150.5));
+        assert_eq!(rule.to_string(), "AAPL>150.5");
+    }
+
+    #[test]
+    fn rule_rejects_malformed() {
+        assert!("AAPL=150".parse::<AlertRule>().is_err());
+        assert!(">150".parse::<AlertRule>().is_err());
+        assert!("AAPL<abc".parse::<AlertRule>().is_err());
+    }
+
+    #[test]
+    fn engine_fires_on_edge_only() {
+        let rule: AlertRule = "AAPL<10".parse().unwrap();
+        let mut engine = AlertEngine::new(vec![rule.clone()], Vec::new());
+
+        assert!(engine.check(&quote("AAPL", 12.0)).is_empty());
+        assert_eq!(engine.check(&quote("AAPL", 9.0)), vec![rule.clone()]);
+        assert!(engine.check(&quote("AAPL", 8.0)).is_empty());
+        assert!(engine.check(&quote("TSLA", 1.0)).is_empty());
+        assert!(engine.check(&quote("AAPL", 11.0)).is_empty());
+        assert_eq!(engine.check(&quote("AAPL", 9.5)), vec![rule]);
+    }
+
+    #[test]
+    fn rules_match_mixed_case_aliases() {
+        let rule: AlertRule = "Yandex>3000".parse().unwrap();
+        let mut engine = AlertEngine::new(vec![rule.clone()], Vec::new());
+
+        assert!(rule.matches(&quote("Yandex", 3100.0)));
+        assert_eq!(engine.check(&quote("Yandex", 3100.0)), vec![rule]);
+    }
+
+    #[test]
+    fn hook_url_validator() {
+        assert!(validate_hook_url("http://localhost:8080/hook").is_ok());
+        assert!(validate_hook_url("https://example.com/hook").is_ok());
+        assert!(validate_hook_url("ftp://localhost/hook").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Отображение тикеров сервера в символы, удобные пользователю.
+//!
+//! Соответствия задаются TOML-файлом из пар `ТИКЕР = "символ"`:
+//!
+//! ```toml
+//! YNDX = "Yandex"
+//! SBER = "Sber"
+//! ```
+//!
+//! Переименование выполняется в начале конвейера, поэтому символы
+//! используются во всём выводе, оповещениях и ретрансляции. Тикеры
+//! файла и котировок, а также символы в правилах `--alert` и портфеле
+//! сравниваются без учёта регистра.
+
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Таблица псевдонимов тикеров.
+#[derive(Debug, Clone, Default)]
+pub struct TickerAliases {
+    map: HashMap<String, String>,
+}
+
+impl TickerAliases {
+    /// Загрузить псевдонимы из TOML-файла.
+    pub fn load(path: &Path) -> Result<Self, QuoteError> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            QuoteError::value_err(format!(
+                "не удалось прочитать файл псевдонимов {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        Self::parse(&content).map_err(|err| {
+            QuoteError::value_err(format!("файл псевдонимов {}: {}", path.display(), err))
+        })
+    }
+
+    /// Разобрать содержимое TOML-файла псевдонимов.
+    fn parse(content: &str) -> Result<Self, QuoteError> {
+        let map: HashMap<String, String> = toml::from_str(content)
+            .map_err(|err| QuoteError::value_err(format!("некорректный формат: {err}")))?;
+
+        if let Some((ticker, _)) = map.iter().find(|(_, alias)| alias.trim().is_empty()) {
+            return Err(QuoteError::value_err(format!(
+                "пустой псевдоним для тикера {ticker}"
+            )));
+        }
+
+        let map = map
+            .into_iter()
+            .map(|(ticker, alias)| (ticker.to_uppercase(), alias))
+            .collect();
+        Ok(Self { map })
+    }
+
+    /// Заменить тикер котировки псевдонимом, если он задан.
+    pub fn apply(&self, mut quote: StockQuote) -> StockQuote {
+        if let Some(alias) = self.map.get(&quote.ticker.to_uppercase()) {
+            quote.ticker = alias.clone();
+        }
+        quote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::quote;
+
+    #[test]
+    fn apply_renames_known_tickers() {
+        let aliases = TickerAliases::parse("YNDX = \"Yandex\"\n").unwrap();
+
+        assert_eq!(aliases.apply(quote("YNDX", 10.0)).ticker, "Yandex");
+        assert_eq!(aliases.apply(quote("AAPL", 10.0)).ticker, "AAPL");
+    }
+
+    #[test]
+    fn file_tickers_are_case_insensitive() {
+        let aliases = TickerAliases::parse("yndx = \"Yandex\"\n").unwrap();
+
+        assert_eq!(aliases.apply(quote("YNDX", 10.0)).ticker, "Yandex");
+    }
+
+    #[test]
+    fn parse_rejects_bad_files() {
+        assert!(TickerAliases::parse("YNDX = 1").is_err());
+        assert!(TickerAliases::parse("YNDX = \" \"").is_err());
+        assert!(TickerAliases::parse("not toml").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Офлайн-анализ записанной трансляции котировок.
+//!
+//! По файлу записи (одна котировка в JSON на строку) для каждого тикера
+//! рассчитываются OHLC, волатильность, профиль объёма по ценовым уровням
+//! и статистика пропусков во времени.
+
+use crate::cli::AnalyzeSet;
+use crate::replay::parse_record_line;
+use commons::errors::{QuoteError, ResultExt};
+use commons::i18n::tr;
+use commons::models::{StockQuote, Transaction};
+use commons::utils::{fmt_duration, fmt_price};
+use log::info;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    time::Duration,
+};
+
+/// Количество ценовых уровней в профиле объёма.
+const PROFILE_BINS: usize = 5;
+
+/// Накопленная статистика по одному тикеру.
+#[derive(Debug, Clone)]
+pub struct TickerStats {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    count: usize,
+    buy_volume: u64,
+    sell_volume: u64,
+    prices: Vec<(f64, u32)>,
+    log_returns: Vec<f64>,
+    last_timestamp: u64,
+    gap_count: usize,
+    max_gap: u64,
+}
+
+impl TickerStats {
+    fn new(quote: &StockQuote) -> Self {
+        let price = quote.price.to_f64();
+        Self {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            count: 0,
+            buy_volume: 0,
+            sell_volume: 0,
+            prices: Vec::new(),
+            log_returns: Vec::new(),
+            last_timestamp: quote.timestamp,
+            gap_count: 0,
+            max_gap: 0,
+        }
+    }
+
+    /// Учесть очередную котировку. `gap` — порог пропуска в секундах.
+    fn update(&mut self, quote: &StockQuote, gap: u64) {
+        let gap_ms = gap.saturating_mul(1000);
+        let price = quote.price.to_f64();
+        if self.count > 0 {
+            if self.close > 0.0 && price > 0.0 {
+                self.log_returns.push((price / self.close).ln());
+            }
+            let delta = quote.timestamp.saturating_sub(self.last_timestamp);
+            if delta > gap_ms {
+                self.gap_count += 1;
+            }
+            self.max_gap = self.max_gap.max(delta);
+        }
+
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.count += 1;
+        self.last_timestamp = quote.timestamp;
+        self.prices.push((price, quote.volume));
+        match quote.transaction {
+            Transaction::Buy => self.buy_volume += quote.volume as u64,
+            Transaction::Sell | Transaction::ShortSell => self.sell_volume += quote.volume as u64,
+            // Отмены и исправления не добавляют оборота.
+            Transaction::Cancel | Transaction::Correction => {}
+        }
+    }
+
+    /// Волатильность: стандартное отклонение логарифмических доходностей, %.
+    pub fn volatility(&self) -> Option<f64> {
+        let n = self.log_returns.len();
+        if n < 2 {
+            return None;
+        }
+        let mean = self.log_returns.iter().sum::<f64>() / n as f64;
+        let var = self
+            .log_returns
+            .iter()
+            .map(|r| (r - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1) as f64;
+        Some(var.sqrt() * 100.0)
+    }
+
+    /// Профиль объёма: суммарный объём по равным ценовым уровням
+    /// от `low` до `high`.
+    pub fn volume_profile(&self) -> [u64; PROFILE_BINS] {
+        let mut bins = [0u64; PROFILE_BINS];
+        let range = self.high - self.low;
+
+        for &(price, volume) in &self.prices {
+            let idx = if range > 0.0 {
+                (((price - self.low) / range) * PROFILE_BINS as f64) as usize
+            } else {
+                0
+            };
+            bins[idx.min(PROFILE_BINS - 1)] += volume as u64;
+        }
+        bins
+    }
+}
+
+impl Display for TickerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let volatility = self
+            .volatility()
+            .map_or("-".to_string(), |v| format!("{:.3}%", v));
+        let profile: Vec<String> = self.volume_profile().iter().map(u64::to_string).collect();
+
+        write!(
+            f,
+            "{:>10} {:>10} {:>10} {:>10} {:>6} {:>9} {:>10} {:>10} {:>5} {:>7}  [{}]",
+            fmt_price(self.open),
+            fmt_price(self.high),
+            fmt_price(self.low),
+            fmt_price(self.close),
+            self.count,
+            volatility,
+            self.buy_volume,
+            self.sell_volume,
+            self.gap_count,
+            fmt_duration(Duration::from_millis(self.max_gap)),
+            profile.join(" ")
+        )
+    }
+}
+
+/// Рассчитать статистику по котировкам, сгруппировав их по тикерам.
+///
+/// ## Args
+/// - `quotes` — котировки в порядке записи
+/// - `gap` — порог пропуска между соседними котировками тикера, секунды
+pub fn collect_stats<I>(quotes: I, gap: u64) -> BTreeMap<String, TickerStats>
+where
+    I: IntoIterator<Item = StockQuote>,
+{
+    let mut stats: BTreeMap<String, TickerStats> = BTreeMap::new();
+    for quote in quotes {
+        stats
+            .entry(quote.ticker.clone())
+            .or_insert_with(|| TickerStats::new(&quote))
+            .update(&quote, gap);
+    }
+    stats
+}
+
+/// Прочитать файл записи: одна котировка в JSON на строку.
+///
+/// Некорректные строки пропускаются; файл без котировок считается ошибкой.
+pub fn read_capture(path: &Path) -> Result<Vec<StockQuote>, QuoteError> {
+    let file = File::open(path)
+        .with_context(|| format!("не удалось открыть файл записи {}", path.display()))?;
+
+    let mut quotes = Vec::new();
+    for (num, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("ошибка чтения строки {}", num + 1))?;
+        if let Some(quote) = parse_record_line(&line, num + 1) {
+            quotes.push(quote);
+        }
+    }
+
+    if quotes.is_empty() {
+        return Err(QuoteError::value_err(format!(
+            "файл записи {} не содержит котировок",
+            path.display()
+        )));
+    }
+    Ok(quotes)
+}
+
+/// Проанализировать файл записи и вывести отчёт в консоль.
+pub fn analyze(settings: AnalyzeSet) -> Result<(), QuoteError> {
+    info!("Анализ записи: {}", settings.capture.display());
+    let quotes = read_capture(&settings.capture)?;
+
+    let total = quotes.len();
+    let stats = collect_stats(quotes, settings.gap);
+
+    println!(
+        "{:<8} {:>10} {:>10} {:>10} {:>10} {:>6} {:>9} {:>10} {:>10} {:>5} {:>7}  volume profile",
+        "ticker",
+        "open",
+        "high",
+        "low",
+        "close",
+        "count",
+        "volat.",
+        "buy vol",
+        "sell vol",
+        "gaps",
+        "max gap"
+    );
+    for (ticker, ticker_stats) in &stats {
+        println!("{:<8} {}", ticker, ticker_stats);
+    }
+    let tickers = stats.len();
+    println!(
+        "\n{}",
+        tr(
+            format!("Котировок: {total}, тикеров: {tickers}"),
+            format!("Quotes: {total}, tickers: {tickers}"),
+        )
+    );
+
+    info!(
+        "Анализ завершён: {} котировок, {} тикеров",
+        total,
+        stats.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::QuoteBuilder;
+
+    #[test]
+    fn collect_stats_builds_ohlc_per_ticker() {
+        let quotes = vec![
+            QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+            QuoteBuilder::new("MSFT").price(50.0).timestamp(1).build(),
+            QuoteBuilder::new("AAPL").price(12.0).timestamp(2).build(),
+            QuoteBuilder::new("AAPL").price(9.0).timestamp(3).build(),
+            QuoteBuilder::new("AAPL").price(11.0).timestamp(4).build(),
+        ];
+        let stats = collect_stats(quotes, 5);
+        let aapl = &stats["AAPL"];
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            (aapl.open, aapl.high, aapl.low, aapl.close),
+            (10.0, 12.0, 9.0, 11.0)
+        );
+        assert_eq!(aapl.count, 4);
+        assert_eq!(aapl.buy_volume, 4);
+    }
+
+    #[test]
+    fn gaps_are_counted_above_threshold() {
+        let quotes = [1_000, 3_000, 8_000, 25_000]
+            .map(|timestamp| {
+                QuoteBuilder::new("AAPL")
+                    .price(10.0)
+                    .timestamp(timestamp)
+                    .build()
+            })
+            .to_vec();
+        let stats = collect_stats(quotes, 5);
+
+        assert_eq!(stats["AAPL"].gap_count, 1);
+        assert_eq!(stats["AAPL"].max_gap, 17_000);
+    }
+
+    #[test]
+    fn volatility_needs_two_returns() {
+        let stats = collect_stats(
+            vec![
+                QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+                QuoteBuilder::new("AAPL").price(11.0).timestamp(2).build(),
+            ],
+            5,
+        );
+        assert!(stats["AAPL"].volatility().is_none());
+
+        let stats = collect_stats(
+            vec![
+                QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+                QuoteBuilder::new("AAPL").price(11.0).timestamp(2).build(),
+                QuoteBuilder::new("AAPL").price(10.0).timestamp(3).build(),
+            ],
+            5,
+        );
+        assert!(stats["AAPL"].volatility().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn volume_profile_spreads_by_price() {
+        let quotes = vec![
+            QuoteBuilder::new("AAPL")
+                .price(10.0)
+                .volume(3)
+                .timestamp(1)
+                .build(),
+            QuoteBuilder::new("AAPL")
+                .price(20.0)
+                .volume(7)
+                .timestamp(2)
+                .build(),
+        ];
+        let profile = collect_stats(quotes, 5)["AAPL"].volume_profile();
+
+        assert_eq!(profile[0], 3);
+        assert_eq!(profile[PROFILE_BINS - 1], 7);
+        assert_eq!(profile.iter().sum::<u64>(), 10);
+    }
+}
@@ -0,0 +1,128 @@
+//! Режим замера пропускной способности трансляции.
+//!
+//! Котировки принимаются без форматирования и вывода: замеряется только
+//! разбор JSON. Раз в секунду в консоль и log-файл выводится сводка.
+
+use commons::models::StockQuote;
+use log::{info, warn};
+use quote_client_lib::QuoteStream;
+use quote_client_lib::config::UDP_BUFFER_SIZE;
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+/// Интервал вывода статистики.
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Накопленная за интервал статистика приёма.
+#[derive(Default)]
+struct BenchStats {
+    /// Количество разобранных котировок.
+    quotes: u64,
+    /// Объём полученных данных (байты).
+    bytes: u64,
+    /// Время разбора каждой котировки.
+    parse_times: Vec<Duration>,
+    /// Датаграммы, которые не удалось разобрать.
+    drops: u64,
+}
+
+impl BenchStats {
+    /// Учесть полученную датаграмму.
+    fn record(&mut self, size: usize, parse_time: Duration, parsed: bool) {
+        self.bytes += size as u64;
+        if parsed {
+            self.quotes += 1;
+            self.parse_times.push(parse_time);
+        } else {
+            self.drops += 1;
+        }
+    }
+
+    /// Сформировать сводку за прошедший интервал.
+    fn summary(&mut self, elapsed: Duration) -> String {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        self.parse_times.sort_unstable();
+
+        format!(
+            "quotes/s: {:.0} | MB/s: {:.3} | parse p50/p90/p99: {}/{}/{} µs | drops: {}",
+            self.quotes as f64 / secs,
+            self.bytes as f64 / secs / 1_000_000.0,
+            percentile(&self.parse_times, 50.0).as_micros(),
+            percentile(&self.parse_times, 90.0).as_micros(),
+            percentile(&self.parse_times, 99.0).as_micros(),
+            self.drops
+        )
+    }
+}
+
+/// Процентиль по отсортированной выборке (ближайший ранг).
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Принимать котировки до остановки подписки, выводя статистику раз
+/// в секунду.
+///
+/// Потери в сети без порядковых номеров котировок не обнаружить, поэтому
+/// `drops` учитывает датаграммы, которые не удалось разобрать.
+pub fn run(quotes: &QuoteStream) {
+    let stop = quotes.stop_flag();
+    let mut buf = [0u8; UDP_BUFFER_SIZE];
+    let mut stats = BenchStats::default();
+    let mut started = Instant::now();
+
+    while !stop.load(Ordering::SeqCst) {
+        match quotes.udp().recv_raw(&mut buf) {
+            Ok(Some(size)) => {
+                let parse_start = Instant::now();
+                let parsed = serde_json::from_slice::<StockQuote>(&buf[..size]).is_ok();
+                stats.record(size, parse_start.elapsed(), parsed);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!("Приём котировок прерван: {err}");
+                break;
+            }
+        }
+
+        if started.elapsed() >= REPORT_INTERVAL {
+            let summary = stats.summary(started.elapsed());
+            info!("{}", summary);
+            println!("{}", summary);
+            stats = BenchStats::default();
+            started = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sample: Vec<Duration> = (1..=100).map(Duration::from_micros).collect();
+
+        assert_eq!(percentile(&sample, 50.0), Duration::from_micros(50));
+        assert_eq!(percentile(&sample, 99.0), Duration::from_micros(99));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn summary_reports_rates_and_drops() {
+        let mut stats = BenchStats::default();
+        stats.record(500_000, Duration::from_micros(10), true);
+        stats.record(500_000, Duration::from_micros(30), true);
+        stats.record(10, Duration::ZERO, false);
+
+        let summary = stats.summary(Duration::from_secs(2));
+        assert!(summary.starts_with("quotes/s: 1 | MB/s: 0.500"));
+        assert!(summary.ends_with("10/30/30 µs | drops: 1"));
+    }
+}
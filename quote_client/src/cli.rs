@@ -4,34 +4,70 @@
 //! - адрес и порт TCP-сервера
 //! - порт для приёма UDP-данных
 //! - путь к файлу со списком тикеров для подписки
+//! - путь к файлу записи трансляции для локального воспроизведения
 
+use crate::alerts::{AlertAction, AlertRule, validate_alert_rule, validate_hook_url};
+use crate::aliases::TickerAliases;
 use crate::config::*;
-use clap::{Parser, Subcommand};
-use commons::errors::QuoteError;
-use commons::get_ticker_data;
-use log::{error, info};
+use crate::discovery::{self, choose_server};
+use crate::indicators::validate_period;
+use crate::output::{Verbosity, console, human_output, set_human_output, validate_interval};
+#[cfg(feature = "parquet")]
+use crate::parquet_sink::ParquetSink;
+use crate::portfolio::{Position, load_positions};
+use crate::relay::{RelayFormat, validate_relay_url};
+use crate::scripting::QuoteScript;
+use crate::state::SessionState;
+use clap::{ArgAction, Parser, Subcommand};
+use commons::config::validate_port;
+use commons::errors::{ErrorKind, QuoteError};
+use commons::i18n;
+use commons::netdefaults;
+use commons::paths::Paths;
+use commons::resolve_log_level;
+use commons::tickers::TickerRegistry;
+use commons::utils::get_timestamp_ms;
+use log::{LevelFilter, error, info};
 use std::fmt::{Display, Formatter};
+use std::io::{Write, stderr, stdin, stdout};
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
-use std::ops::RangeInclusive;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::Duration;
 use url::Url;
 
-/// Перечисление ошибок при завершении приложения.
-#[derive(Copy, Clone)]
-#[repr(u8)]
-#[allow(dead_code)]
-enum ExitCode {
+/// Коды завершения приложения.
+///
+/// Ошибки аргументов получают собственные коды 1–9, ошибки во время
+/// работы — код своего вида ([`ErrorKind::code`], от 10).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ExitCode {
     /// Ошибка формирования сокета TCP.
-    InvalidServerSocket = 1,
+    InvalidServerSocket,
     /// Ошибка формирования ссылки UDP.
     InvalidUDP,
     /// Файл не найден или не содержит данные
     InvalidTicketFile,
+    /// Не указан обязательный параметр.
+    MissingArgument,
+    /// Не удалось найти или выбрать сервер через mDNS.
+    DiscoveryFailed,
+    /// Файл портфеля не найден или некорректен.
+    InvalidPortfolioFile,
+    /// Сценарий не найден или не компилируется.
+    InvalidScript,
+    /// Файл псевдонимов тикеров не найден или некорректен.
+    InvalidAliasFile,
+    /// Файл состояния подписки не найден или некорректен.
+    InvalidStateFile,
+    /// Записи сравниваемых файлов различаются (`compare`, как у `diff`).
+    RecordsDiffer,
+    /// Ошибка во время работы.
+    Failed(ErrorKind),
 }
 
 impl ExitCode {
-    /// Предоставить цифровое значение выбранного перечисления (`u8`).
+    /// Предоставить цифровое значение кода завершения (`u8`).
     ///
     /// ## Пример
     ///
@@ -40,63 +76,194 @@ impl ExitCode {
     ///
     /// let code = ExitCode::InvalidUDP;
     /// assert_eq!(code.value(), 2u8);
-    /// ```  
+    /// ```
     pub fn value(&self) -> u8 {
-        *self as u8
+        match self {
+            ExitCode::InvalidServerSocket | ExitCode::RecordsDiffer => 1,
+            ExitCode::InvalidUDP => 2,
+            ExitCode::InvalidTicketFile => 3,
+            ExitCode::MissingArgument => 4,
+            ExitCode::DiscoveryFailed => 5,
+            ExitCode::InvalidPortfolioFile => 6,
+            ExitCode::InvalidScript => 7,
+            ExitCode::InvalidAliasFile => 8,
+            ExitCode::InvalidStateFile => 9,
+            ExitCode::Failed(kind) => kind.code(),
+        }
+    }
+}
+
+impl From<&QuoteError> for ExitCode {
+    fn from(err: &QuoteError) -> Self {
+        ExitCode::Failed(err.kind())
     }
 }
 
 #[derive(Debug, Parser)]
 #[command(about = "Quote Client. Real-time ticker data streaming.")]
 #[command(author, version, long_about = None)]
-struct CliArgs {
+pub struct CliArgs {
     /// TCP server socket address.
-    #[arg(short, long, required = false, default_value_t = default_server_socket())]
+    #[arg(short, long, required = false, default_value_t = netdefaults::SERVER_HOST)]
     socket: Ipv4Addr,
 
-    /// TCP server port (for example 8888).
-    #[arg(short, long, required = false, default_value_t = DEFAULT_SERVER_PORT, value_parser=validate_tcp_port
-    )]
-    port: u16,
+    /// TCP server port (for example 8888). Default: 8888.
+    #[arg(short, long, required = false, value_parser = validate_port)]
+    port: Option<u16>,
+
+    /// UDP port for receiving data (for example 34254). Required for network
+    /// commands unless set in the configuration.
+    #[arg(short, long, required = false, value_parser = validate_port)]
+    udp: Option<u16>,
 
-    /// UDP port for receiving data (for example 34254).
-    #[arg(short, long, required = true, value_parser=validate_udp_port)]
-    udp: u16,
+    /// Configuration file (TOML). Default: qclient.toml in the project root
+    /// (QUOTE_HOME), if present.
+    #[arg(long, required = false, value_name = "FILE")]
+    config: Option<PathBuf>,
 
-    /// Print verbose output to console
+    /// Directory for log files. Default: log_folder from the configuration
+    /// in the project root (QUOTE_HOME).
+    #[arg(long, required = false, value_name = "DIR")]
+    log_dir: Option<PathBuf>,
+
+    /// Increase console output: -v prints quotes, -vv also prints raw payload
+    /// parse errors and enables debug logging.
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Print only errors to the console and log warnings and errors only.
     #[arg(short, long, default_value = "false", required = false)]
-    verbose: bool,
+    quiet: bool,
+
+    /// Log file level (off, error, warn, info, debug, trace). Overrides the
+    /// level implied by -v/-q and the QUOTE_LOG environment variable.
+    #[arg(long, required = false, value_name = "LEVEL")]
+    log_level: Option<LevelFilter>,
+
+    /// Also print log messages of this level and above to stderr.
+    #[arg(long, required = false, value_name = "LEVEL")]
+    log_console: Option<LevelFilter>,
+
+    /// Find servers on the local network via mDNS instead of --socket/--port.
+    #[arg(long, default_value = "false", required = false)]
+    discover: bool,
+
+    /// Server address, repeatable to merge streams from several servers
+    /// (overrides --socket/--port). Each server gets its own UDP port,
+    /// counting up from --udp.
+    #[arg(
+        long = "server",
+        required = false,
+        value_name = "ADDR",
+        conflicts_with = "discover"
+    )]
+    servers: Vec<SocketAddr>,
+
+    /// Disable colored console output (for dumb terminals).
+    #[arg(long, default_value = "false", required = false)]
+    no_color: bool,
+
+    /// Write exactly one JSON quote per line to stdout and nothing else
+    /// (for piping into other programs).
+    #[arg(long, default_value = "false", required = false)]
+    stdout_only: bool,
+
+    /// Periodically write JSON health info (connected, quotes/sec, last quote
+    /// time) to this file.
+    #[arg(long, required = false, value_name = "FILE")]
+    status_file: Option<PathBuf>,
+
+    /// Serve the same health info over HTTP on this address
+    /// (for example 127.0.0.1:9100).
+    #[arg(long, required = false, value_name = "ADDR")]
+    health_addr: Option<SocketAddr>,
+
+    /// Forward every received quote to another UDP destination
+    /// (for example udp://10.0.0.5:40000).
+    #[arg(long, required = false, value_name = "URL", value_parser = validate_relay_url)]
+    relay: Option<Url>,
+
+    /// Encoding of relayed quotes.
+    #[arg(long, required = false, value_enum, default_value_t = RelayFormat::Json)]
+    relay_format: RelayFormat,
+
+    /// Alert rule, repeatable (for example AAPL>150 or TSLA<90).
+    #[arg(long = "alert", required = false, value_name = "RULE", value_parser = validate_alert_rule)]
+    alerts: Vec<AlertRule>,
+
+    /// Program to run on alert: quote JSON is passed as the first argument,
+    /// the rule as the second.
+    #[arg(long, required = false, value_name = "PATH")]
+    on_alert_exec: Vec<PathBuf>,
+
+    /// http:// or https:// URL to POST the alerting quote JSON to.
+    #[arg(long, required = false, value_name = "URL", value_parser = validate_hook_url)]
+    on_alert_post: Vec<Url>,
+
+    /// Show a native desktop notification when an alert rule triggers.
+    #[cfg(feature = "notifications")]
+    #[arg(long, default_value = "false", required = false)]
+    notify: bool,
+
+    /// Simple moving average window per ticker (number of quotes).
+    #[arg(long, required = false, value_name = "N", value_parser = validate_period)]
+    sma: Option<usize>,
+
+    /// Exponential moving average period per ticker (number of quotes).
+    #[arg(long, required = false, value_name = "N", value_parser = validate_period)]
+    ema: Option<usize>,
+
+    /// Positions file (CSV: ticker,quantity,cost basis) for P&L tracking.
+    #[arg(long, required = false, value_name = "FILE")]
+    portfolio: Option<PathBuf>,
+
+    /// Rhai script with an `on_quote(q)` callback to filter, transform or
+    /// alert on quotes.
+    #[arg(long, required = false, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// TOML file mapping server tickers to display symbols
+    /// (`YNDX = "Yandex"`). Alert rules and portfolio use the symbols;
+    /// tickers and symbols are matched case-insensitively.
+    #[arg(long, required = false, value_name = "FILE")]
+    aliases: Option<PathBuf>,
+
+    /// Show at most the latest quote per ticker per interval on the console
+    /// (for example 250ms); relay and alerts still get every quote.
+    #[arg(long, required = false, value_name = "INTERVAL", value_parser = validate_interval)]
+    display_interval: Option<Duration>,
+
+    /// Write every received quote to a Parquet file.
+    #[cfg(feature = "parquet")]
+    #[arg(long, required = false, value_name = "FILE")]
+    parquet: Option<PathBuf>,
+
+    /// Append a unicode sparkline of the last N prices to each quote line.
+    #[arg(long, required = false, value_name = "N", value_parser = validate_period)]
+    sparkline: Option<usize>,
+
+    /// Periodically show the top gainers and losers since subscription start.
+    #[arg(long, default_value = "false", required = false)]
+    movers: bool,
 
     /// Supported server commands.
     #[command(subcommand)]
     command: Commands,
 }
 
-/// Валидатор для полей `port` и аналогичных.
-fn port_in_range(s: &str, range: RangeInclusive<u16>) -> Result<u16, String> {
-    let port: u16 = s.parse().map_err(|_| format!("invalid port number: {s}"))?;
-    if range.contains(&port) {
-        Ok(port)
+/// Валидатор для ключа `--speed`: принимает `5`, `5x` или `0.5x`.
+fn validate_speed(s: &str) -> Result<f64, String> {
+    let value = s.trim().trim_end_matches(['x', 'X']);
+    let speed: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid speed value: {s}"))?;
+    if speed.is_finite() && speed > 0.0 {
+        Ok(speed)
     } else {
-        Err(format!(
-            "port number {} not in range {} — {}",
-            s,
-            range.start(),
-            range.end()
-        ))
+        Err(format!("speed must be greater than zero: {s}"))
     }
 }
 
-/// Валидатор для поля `port`.
-fn validate_tcp_port(s: &str) -> Result<u16, String> {
-    port_in_range(s, ALLOW_TCP_PORTS)
-}
-
-/// Валидатор для поля `udp`.
-fn validate_udp_port(s: &str) -> Result<u16, String> {
-    port_in_range(s, ALLOW_UDP_PORTS)
-}
-
 /// Supported server commands.
 #[derive(Debug, Subcommand)]
 enum Commands {
@@ -105,49 +272,371 @@ enum Commands {
     Stream {
         #[arg(short, long, required = false, value_name = "FILE")]
         file: Option<PathBuf>,
+        /// Re-read the file when it changes and update the subscription.
+        #[arg(long, default_value = "false", requires = "file")]
+        watch: bool,
     },
     /// Cancel previously scheduled data transmission.
     Cancel,
+    /// Re-establish the subscription saved by the last `stream` session.
+    Resume,
+    /// Subscribe to ALL and report throughput statistics every second.
+    /// Measures a single server: repeated --server is rejected.
+    Bench,
+    /// Replay a recorded stream (JSON lines) locally, without network.
+    Replay {
+        /// Recorded stream file: one JSON quote per line.
+        #[arg(value_name = "RECORD")]
+        record: PathBuf,
+        /// Playback speed (for example 5x). Default: real time.
+        #[arg(long, default_value = "1x", value_parser = validate_speed)]
+        speed: f64,
+        /// Filters to tickers listed in the file.
+        #[arg(short, long, required = false, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    /// Analyze a recorded stream: per-ticker OHLC, volatility, volume profile
+    /// and gaps.
+    Analyze {
+        /// Recorded stream file: one JSON quote per line.
+        #[arg(value_name = "CAPTURE")]
+        capture: PathBuf,
+        /// Minimum pause between quotes of a ticker counted as a gap, seconds.
+        #[arg(long, default_value = "5")]
+        gap: u64,
+    },
+    /// Compare two recorded streams: per-ticker message counts, price paths
+    /// and timing.
+    Compare {
+        /// First recorded stream file.
+        #[arg(value_name = "A")]
+        first: PathBuf,
+        /// Second recorded stream file.
+        #[arg(value_name = "B")]
+        second: PathBuf,
+    },
 }
 
-/// Параметры, полученные из командной строки при запуске приложения.
-pub struct ClientSet {
+/// Режим работы приложения, выбранный пользователем.
+pub enum RunMode {
+    /// Взаимодействие с сервером котировок.
+    Network(ClientSet),
+    /// Локальное воспроизведение записанной трансляции.
+    Replay(ReplaySet),
+    /// Замер пропускной способности трансляции.
+    Bench(ClientSet),
+    /// Офлайн-анализ записанной трансляции.
+    Analyze(AnalyzeSet),
+    /// Сравнение двух записанных трансляций.
+    Compare(CompareSet),
+}
+
+/// Параметры конвейера обработки котировок, общие для трансляции
+/// и воспроизведения записи.
+pub struct PipelineSet {
+    /// Цветной вывод в консоль.
+    pub color: bool,
+    /// Правила оповещений.
+    pub alert_rules: Vec<AlertRule>,
+    /// Действия при срабатывании оповещений.
+    pub alert_actions: Vec<AlertAction>,
+    /// Размер окна простой скользящей средней.
+    pub sma: Option<usize>,
+    /// Период экспоненциальной скользящей средней.
+    pub ema: Option<usize>,
+    /// Позиции портфеля для расчёта P&L.
+    pub portfolio: Option<Vec<Position>>,
+    /// Пользовательский сценарий обработки котировок.
+    pub script: Option<QuoteScript>,
+    /// Псевдонимы тикеров для отображения.
+    pub aliases: Option<TickerAliases>,
+    /// Панель лидеров роста и падения.
+    pub movers: bool,
+    /// Интервал прореживания вывода котировок в консоль.
+    pub display_interval: Option<Duration>,
+    /// Длина мини-графика цен в строке котировки.
+    pub sparkline: Option<usize>,
+    /// Экспорт котировок в Parquet-файл.
+    #[cfg(feature = "parquet")]
+    pub parquet: Option<ParquetSink>,
+    /// Машинный вывод: только котировки в JSON, по одной на строку.
+    pub stdout_only: bool,
+    /// Уровень подробности вывода.
+    pub verbosity: Verbosity,
+}
+
+impl PipelineSet {
+    /// Сформировать экземпляр [`PipelineSet`] на основе данных из командной
+    /// строки.
+    fn new(args: &CliArgs) -> Self {
+        let exec = args.on_alert_exec.iter().cloned().map(AlertAction::Exec);
+        let post = args.on_alert_post.iter().cloned().map(AlertAction::Post);
+        #[allow(unused_mut)]
+        let mut alert_actions: Vec<AlertAction> = exec.chain(post).collect();
+        #[cfg(feature = "notifications")]
+        if args.notify {
+            alert_actions.push(AlertAction::Notify);
+        }
+
+        Self {
+            color: !args.no_color,
+            alert_rules: args.alerts.clone(),
+            alert_actions,
+            sma: args.sma,
+            ema: args.ema,
+            portfolio: args.portfolio.as_deref().map(|path| {
+                load_positions(path)
+                    .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidPortfolioFile))
+            }),
+            script: args.script.as_deref().map(|path| {
+                QuoteScript::load(path)
+                    .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidScript))
+            }),
+            aliases: args.aliases.as_deref().map(|path| {
+                TickerAliases::load(path)
+                    .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidAliasFile))
+            }),
+            movers: args.movers,
+            display_interval: args.display_interval,
+            sparkline: args.sparkline,
+            #[cfg(feature = "parquet")]
+            parquet: args.parquet.as_deref().map(|path| {
+                ParquetSink::create(path)
+                    .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::from(&e)))
+            }),
+            stdout_only: args.stdout_only,
+            verbosity: args.verbosity(),
+        }
+    }
+}
+
+/// Параметры локального воспроизведения записанной трансляции.
+pub struct ReplaySet {
+    /// Путь к файлу записи.
+    pub record: PathBuf,
+    /// Множитель скорости воспроизведения.
+    pub speed: f64,
+    /// Тикеры для отбора (пустой вектор — все тикеры).
+    pub tickers: Vec<String>,
+    /// Параметры конвейера обработки котировок.
+    pub pipeline: PipelineSet,
+}
+
+impl ReplaySet {
+    /// Сформировать экземпляр [`ReplaySet`] на основе данных из командной
+    /// строки.
+    fn new(args: &CliArgs, record: &Path, speed: f64, file: &Option<PathBuf>) -> Self {
+        let tickers = match file {
+            Some(path) => ClientSet::get_tickers(path)
+                .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidTicketFile)),
+            None => Vec::new(),
+        };
+
+        // Воспроизведение выводит котировки, если не задан `--quiet`.
+        let mut pipeline = PipelineSet::new(args);
+        if pipeline.verbosity == Verbosity::Normal {
+            pipeline.verbosity = Verbosity::Verbose;
+        }
+
+        Self {
+            record: record.to_path_buf(),
+            speed,
+            tickers,
+            pipeline,
+        }
+    }
+}
+
+/// Параметры офлайн-анализа записанной трансляции.
+pub struct AnalyzeSet {
+    /// Путь к файлу записи.
+    pub capture: PathBuf,
+    /// Порог пропуска между котировками тикера, секунды.
+    pub gap: u64,
+}
+
+/// Параметры сравнения двух записанных трансляций.
+pub struct CompareSet {
+    /// Путь к первому файлу записи.
+    pub first: PathBuf,
+    /// Путь ко второму файлу записи.
+    pub second: PathBuf,
+}
+
+/// Подписка на один сервер котировок.
+pub struct Subscription {
     /// Адрес TCP-сервера.
     pub server_addr: SocketAddr,
     /// UDP-адрес для получения данных.
     pub udp_url: Url,
-    /// Список тикеров для подписки.
-    #[allow(dead_code)]
-    pub tickers: Vec<String>,
     /// Подготовленная команда для сервера.
     pub command: String,
-    /// Вывод технической информации в консоль.
-    pub verbose: bool,
 }
 
-impl Display for ClientSet {
+impl Subscription {
+    /// Количество тикеров в команде; `0` — все тикеры.
+    pub fn ticker_count(&self) -> usize {
+        match self.command.split_whitespace().nth(2) {
+            Some(arg) if !arg.eq_ignore_ascii_case("ALL") => arg.split(',').count(),
+            _ => 0,
+        }
+    }
+}
+
+impl Display for Subscription {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "server: {} | udp: {}", self.server_addr, self.udp_url)
     }
 }
 
+/// Параметры, полученные из командной строки при запуске приложения.
+pub struct ClientSet {
+    /// Подписки на серверы: одна или несколько (при повторе `--server`).
+    pub subscriptions: Vec<Subscription>,
+    /// Файл тикеров, изменения которого применяются на лету.
+    pub watchlist: Option<PathBuf>,
+    /// Список тикеров для подписки.
+    pub tickers: Vec<String>,
+    /// Адрес и формат ретрансляции полученных котировок.
+    pub relay: Option<(Url, RelayFormat)>,
+    /// Параметры конвейера обработки котировок.
+    pub pipeline: PipelineSet,
+    /// Сохраняемое состояние сеанса (для трансляции и её возобновления).
+    pub session: Option<SessionState>,
+    /// Параметры надзора за работоспособностью клиента.
+    pub health: HealthSet,
+}
+
+/// Параметры надзора за работоспособностью клиента.
+pub struct HealthSet {
+    /// Файл статуса, обновляемый во время трансляции.
+    pub status_file: Option<PathBuf>,
+    /// Адрес HTTP-точки проверки работоспособности.
+    pub addr: Option<SocketAddr>,
+}
+
+impl HealthSet {
+    fn new(args: &CliArgs) -> Self {
+        Self {
+            status_file: args.status_file.clone(),
+            addr: args.health_addr,
+        }
+    }
+
+    /// Задан хотя бы один способ надзора.
+    pub fn is_enabled(&self) -> bool {
+        self.status_file.is_some() || self.addr.is_some()
+    }
+}
+
+impl Display for ClientSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let subscriptions: Vec<String> = self.subscriptions.iter().map(|s| s.to_string()).collect();
+        write!(f, "{}", subscriptions.join("; "))
+    }
+}
+
 impl ClientSet {
     /// Сформировать экземпляр [`ClientSet`] на основе данных из командной
     /// строки.
     ///
     /// При обнаружении ошибок в значениях приложение завершиться.
-    fn new(args: &CliArgs) -> Self {
-        let server_addr = Self::make_server_addr(args.socket, args.port);
-        let udp_url = Self::make_udp_url(args.udp);
-        let (tickers, command) = Self::tickers_and_command(&args.command, &udp_url);
-        let verbose = args.verbose;
+    fn new(args: &CliArgs, config: &ClientConfig) -> Self {
+        let servers = if !args.servers.is_empty() {
+            args.servers.clone()
+        } else if args.discover {
+            vec![Self::discover_server_addr()]
+        } else {
+            vec![Self::make_server_addr(args.socket, config.port)]
+        };
+        let port_udp = config.udp.unwrap_or_else(|| {
+            exit_err(
+                i18n::tr(
+                    "не указан UDP-порт для приёма данных (--udp)",
+                    "UDP port for receiving data is not set (--udp)",
+                ),
+                ExitCode::MissingArgument,
+            )
+        });
+
+        let mut tickers = Vec::new();
+        let subscriptions: Vec<Subscription> = servers
+            .into_iter()
+            .enumerate()
+            .map(|(idx, server_addr)| {
+                let udp_url =
+                    Self::make_udp_url(Self::udp_port_for(port_udp, idx), config.binary_udp);
+                let (selected, command) = Self::tickers_and_command(&args.command, &udp_url);
+                tickers = selected;
+                Subscription {
+                    server_addr,
+                    udp_url,
+                    command,
+                }
+            })
+            .collect();
+        let relay = args.relay.clone().map(|url| (url, args.relay_format));
+
+        let watchlist = match &args.command {
+            Commands::Stream {
+                file: Some(path),
+                watch: true,
+            } => Some(path.clone()),
+            _ => None,
+        };
+
+        let session = matches!(args.command, Commands::Stream { .. })
+            .then(|| SessionState::new(&subscriptions));
 
         Self {
-            server_addr,
-            udp_url,
+            subscriptions,
+            watchlist,
             tickers,
-            command,
-            verbose,
+            relay,
+            pipeline: PipelineSet::new(args),
+            session,
+            health: HealthSet::new(args),
+        }
+    }
+
+    /// Сформировать экземпляр [`ClientSet`] по сохранённому состоянию
+    /// (команда `resume`).
+    ///
+    /// При отсутствии или повреждении файла состояния приложение завершится.
+    fn resume(args: &CliArgs, config: &ClientConfig, path: &Path) -> Self {
+        let session = SessionState::load(path)
+            .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidStateFile));
+
+        let missed = session.missed_secs(get_timestamp_ms()).map_or_else(
+            || i18n::tr("нет данных", "no data").to_string(),
+            |secs| i18n::tr(format!("{secs} с"), format!("{secs} s")),
+        );
+        let received = session.received;
+        let message = i18n::tr(
+            format!("Возобновление подписки: получено ранее {received}, пропущено {missed}"),
+            format!("Resuming subscription: received earlier {received}, missed {missed}"),
+        );
+        info!("{}", message);
+        console(&message);
+
+        let subscriptions = session
+            .subscriptions
+            .iter()
+            .map(|saved| Subscription {
+                server_addr: saved.server_addr,
+                udp_url: Self::make_udp_url(saved.udp_port, config.binary_udp),
+                command: saved.command.clone(),
+            })
+            .collect();
+
+        Self {
+            subscriptions,
+            watchlist: None,
+            tickers: Vec::new(),
+            relay: args.relay.clone().map(|url| (url, args.relay_format)),
+            pipeline: PipelineSet::new(args),
+            session: Some(session),
+            health: HealthSet::new(args),
         }
     }
 
@@ -162,14 +651,61 @@ impl ClientSet {
         SocketAddr::V4(SocketAddrV4::new(address, port))
     }
 
-    /// Проверить UDP-порт и вернуть корректный UDP-адрес.
+    /// Найти серверы через mDNS и предложить пользователю выбрать один.
+    ///
+    /// В случае ошибки приложение завершается с выводом причины.
+    fn discover_server_addr() -> SocketAddr {
+        // Диалог выбора не должен попадать в stdout в машинном режиме.
+        let mut prompt: Box<dyn Write> = if human_output() {
+            Box::new(stdout())
+        } else {
+            Box::new(stderr())
+        };
+        let _ = writeln!(
+            prompt,
+            "{}",
+            i18n::tr(
+                "Поиск серверов в локальной сети...",
+                "Searching for servers on the local network...",
+            )
+        );
+        discovery::browse()
+            .and_then(|servers| choose_server(&servers, stdin().lock(), prompt))
+            .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::DiscoveryFailed))
+    }
+
+    /// UDP-порт для подписки с номером `idx`: каждому серверу выделяется
+    /// свой порт, начиная с `base`.
+    ///
+    /// Если порт выходит за допустимый диапазон, приложение завершается.
+    fn udp_port_for(base: u16, idx: usize) -> u16 {
+        u16::try_from(idx)
+            .ok()
+            .and_then(|idx| base.checked_add(idx))
+            .filter(|&port| netdefaults::is_allowed_port(port))
+            .unwrap_or_else(|| {
+                let err_msg = i18n::tr(
+                    format!("UDP-порт для сервера №{} вне диапазона", idx + 1),
+                    format!("UDP port for server #{} is out of range", idx + 1),
+                );
+                exit_err(&err_msg, ExitCode::InvalidUDP)
+            })
+    }
+
+    /// Проверить UDP-порт и вернуть корректный UDP-адрес; `binary` —
+    /// запросить двоичные датаграммы ([`netdefaults::BINARY_FORMAT_QUERY`]).
     ///
     /// В случае ошибки приложение завершается с выводом причины.
-    fn make_udp_url(port_udp: u16) -> Url {
-        Url::parse(&format!("udp://{}:{}", UDP_CALLBACK, port_udp)).unwrap_or_else(|error| {
-            let err_msg = format!(
-                "не удалось сформировать `udp_url` (base_url: {}, port: {}): {}",
-                UDP_CALLBACK, port_udp, error
+    fn make_udp_url(port_udp: u16, binary: bool) -> Url {
+        let addr = netdefaults::udp_callback_addr(port_udp);
+        let query = match binary {
+            true => format!("?{}", netdefaults::BINARY_FORMAT_QUERY),
+            false => String::new(),
+        };
+        Url::parse(&format!("udp://{addr}{query}")).unwrap_or_else(|error| {
+            let err_msg = i18n::tr(
+                format!("не удалось сформировать `udp_url` ({addr}): {error}"),
+                format!("failed to build `udp_url` ({addr}): {error}"),
             );
             exit_err(&err_msg, ExitCode::InvalidUDP)
         })
@@ -181,24 +717,26 @@ impl ClientSet {
     ///
     /// Если файл успешно открыт и прочитан, возвращается вектор с данными,
     /// в ином случае ошибка [`QuoteError`].
-    fn get_tickers(path: &PathBuf) -> Result<Vec<String>, QuoteError> {
-        get_ticker_data(path)?.ok_or_else(|| {
-            QuoteError::ticker_err(format!(
+    fn get_tickers(path: &Path) -> Result<Vec<String>, QuoteError> {
+        let registry = TickerRegistry::load(path)?;
+        if registry.is_empty() {
+            return Err(QuoteError::ticker_err(format!(
                 "Файл ({}) не содержит данных",
                 path.to_string_lossy()
-            ))
-        })
+            )));
+        }
+        Ok(registry.tickers())
     }
 
     /// Сформировать команду для сервера на основе пользовательского выбора,
     /// а также вернуть список отобранных тикеров, когда это требуется.
     fn tickers_and_command(command: &Commands, udp_url: &Url) -> (Vec<String>, String) {
-        const STREAM: &str = "STREAM";
-
         match command {
             Commands::Cancel => (vec![], format!("CANCEL {udp_url}")),
 
-            Commands::Stream { file } => {
+            Commands::Bench => (vec![], stream_command(udp_url, &[])),
+
+            Commands::Stream { file, .. } => {
                 let tickers = if let Some(path) = file {
                     Self::get_tickers(path)
                         .unwrap_or_else(|e| exit_err(&e.to_string(), ExitCode::InvalidTicketFile))
@@ -206,20 +744,69 @@ impl ClientSet {
                     Vec::new()
                 };
 
-                let arg = if tickers.is_empty() {
-                    "ALL".to_string()
-                } else {
-                    tickers.join(",")
-                };
+                let command = stream_command(udp_url, &tickers);
+                info!("Сформирована команда: {}", command);
 
-                info!("Собраны тикеры из файла: {}", arg);
+                (tickers, command)
+            }
 
-                (tickers, format!("{STREAM} {udp_url} {arg}"))
+            Commands::Replay { .. } | Commands::Analyze { .. } | Commands::Compare { .. } => {
+                unreachable!("офлайн-режимы не отправляют команд серверу")
             }
+
+            Commands::Resume => unreachable!("resume берёт команду из файла состояния"),
         }
     }
 }
 
+impl CliArgs {
+    /// Уровень подробности вывода по флагам `-q` и `-v`.
+    pub fn verbosity(&self) -> Verbosity {
+        Verbosity::from_flags(self.quiet, self.verbose)
+    }
+
+    /// Уровень журналирования: `--log-level`, затем переменная `QUOTE_LOG`,
+    /// затем уровень по флагам `-q` и `-v`.
+    pub fn log_level(&self) -> LevelFilter {
+        resolve_log_level(self.log_level, self.verbosity().log_level())
+    }
+
+    /// Загрузить конфигурацию клиента: файл (`--config` или [`CONFIG_FILE`]
+    /// в корневой директории `paths`), переменные окружения `vars`
+    /// и ключи `--port`, `--udp`.
+    pub fn load_config(
+        &self,
+        paths: &Paths,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<ClientConfig, QuoteError> {
+        ClientConfig::load(paths, self.config.as_deref(), vars, self.port, self.udp)
+    }
+
+    /// Применить к директориям `log_folder` из конфигурации и ключ
+    /// `--log-dir`.
+    pub fn resolve_paths(&self, config: &ClientConfig, paths: Paths) -> Paths {
+        let log_dir = self
+            .log_dir
+            .clone()
+            .unwrap_or_else(|| paths.root().join(&config.log_folder));
+        paths.with_log_dir(log_dir)
+    }
+
+    /// Уровень дублирования журнала в консоль (`--log-console`).
+    pub fn log_console(&self) -> Option<LevelFilter> {
+        self.log_console
+    }
+}
+
+/// Разобрать аргументы командной строки.
+///
+/// Выполняется до инициализации логгера, чтобы учесть уровень подробности
+/// и расположение log-файлов; конфигурацию затем загружает
+/// [`CliArgs::load_config`].
+pub fn parse_cli_args() -> CliArgs {
+    CliArgs::parse()
+}
+
 /// Получить от пользователя первичные настройки приложения.
 ///
 /// Гарантировано, что данные получены и проверены в доступных пределах.
@@ -231,62 +818,387 @@ impl ClientSet {
 /// Если полученные данные некорректные, приложение завершает работу с выводом
 /// сообщения об ошибке в консоль и log-файл. При завершении работы приложение
 /// возвращает ОС ошибку, в соответствии с [`ExitCode`].
-pub fn parse_cli_args() -> ClientSet {
-    let args = CliArgs::parse();
+pub fn run_mode(args: &CliArgs, config: &ClientConfig, paths: &Paths) -> RunMode {
+    set_human_output(!args.stdout_only && args.verbosity().shows_chrome());
+
+    match &args.command {
+        Commands::Replay {
+            record,
+            speed,
+            file,
+        } => RunMode::Replay(ReplaySet::new(args, record, *speed, file)),
+        Commands::Analyze { capture, gap } => RunMode::Analyze(AnalyzeSet {
+            capture: capture.clone(),
+            gap: *gap,
+        }),
+        Commands::Compare { first, second } => RunMode::Compare(CompareSet {
+            first: first.clone(),
+            second: second.clone(),
+        }),
+        Commands::Bench => {
+            check_bench_servers(&args.servers)
+                .unwrap_or_else(|message| exit_err(&message, ExitCode::InvalidServerSocket));
+            RunMode::Bench(ClientSet::new(args, config))
+        }
+        Commands::Resume => {
+            RunMode::Network(ClientSet::resume(args, config, &config.state_path(paths)))
+        }
+        _ => RunMode::Network(ClientSet::new(args, config)),
+    }
+}
+
+/// Проверить, что для замера задан не более чем один сервер: сводка
+/// `bench` описывает приём с одного сервера.
+fn check_bench_servers(servers: &[SocketAddr]) -> Result<(), String> {
+    match servers.len() {
+        0 | 1 => Ok(()),
+        n => Err(i18n::tr(
+            format!("bench замеряет один сервер, указано --server: {n}"),
+            format!("bench measures a single server, --server given: {n}"),
+        )),
+    }
+}
 
-    ClientSet::new(&args)
+/// Сформировать команду `STREAM` для тикеров (пустой список — все тикеры).
+pub fn stream_command(udp_url: &Url, tickers: &[String]) -> String {
+    let arg = if tickers.is_empty() {
+        "ALL".to_string()
+    } else {
+        tickers.join(",")
+    };
+    format!("STREAM {udp_url} {arg}")
 }
 
 /// Опубликовать сообщение об ошибке и завершить работу приложения.
-fn exit_err(message: &str, code: ExitCode) -> ! {
+pub(crate) fn exit_err(message: &str, code: ExitCode) -> ! {
     error!("Ошибка: {} (код {})", message, code.value());
-    eprintln!("Ошибка: {}", message);
-    exit(code.value() as i32);
+    eprintln!("{}: {}", i18n::ERROR, message);
+    exit(code.value().into());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use commons::netdefaults::{PORTS_ALLOWED, UDP_CALLBACK_HOST};
     use std::fs;
 
+    /// Корневая директория без файла конфигурации.
+    const NO_CONFIG_ROOT: &str = "/nonexistent/quote";
+
+    /// Конфигурация по ключам `args` без файла и переменных окружения.
+    fn load(args: &CliArgs) -> ClientConfig {
+        args.load_config(&Paths::new(NO_CONFIG_ROOT), []).unwrap()
+    }
+
+    #[test]
+    fn flags_override_configured_ports() {
+        let args = CliArgs::parse_from(["qclient", "-p", "9000", "-u", "34254", "stream"]);
+        let config = load(&args);
+
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.udp, Some(34254));
+    }
+
+    #[test]
+    fn file_env_and_flags_are_layered() {
+        let root = std::env::temp_dir().join("qclient_config_test");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(CONFIG_FILE), "port = 9100\nudp = 34000\n").unwrap();
+
+        let args = CliArgs::parse_from(["qclient", "-u", "34254", "stream"]);
+        let vars = [("QCLIENT_PORT".to_string(), "9200".to_string())];
+        let config = args.load_config(&Paths::new(&root), vars).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(config.port, 9200);
+        assert_eq!(config.udp, Some(34254));
+    }
+
     #[test]
     fn tcp_port_validator_accepts_allowed() {
-        let ok = ALLOW_TCP_PORTS.start().to_string();
-        assert!(validate_tcp_port(&ok).is_ok());
+        let ok = PORTS_ALLOWED.start().to_string();
+        let args = CliArgs::try_parse_from(["qclient", "-p", &ok, "stream"]).unwrap();
+        assert_eq!(args.port, Some(*PORTS_ALLOWED.start()));
     }
 
     #[test]
     fn udp_port_validator_rejects_out_of_range() {
-        let bad = (ALLOW_UDP_PORTS.end() + 1).to_string();
-        assert!(validate_udp_port(&bad).is_err());
+        let bad = (PORTS_ALLOWED.end() + 1).to_string();
+        assert!(CliArgs::try_parse_from(["qclient", "-u", &bad, "stream"]).is_err());
+        let low = (PORTS_ALLOWED.start() - 1).to_string();
+        assert!(CliArgs::try_parse_from(["qclient", "-u", &low, "stream"]).is_err());
+    }
+
+    #[test]
+    fn port_flags_are_validated() {
+        assert!(CliArgs::try_parse_from(["qclient", "-p", "9000", "stream"]).is_ok());
+        let bad = (PORTS_ALLOWED.end() + 1).to_string();
+        assert!(CliArgs::try_parse_from(["qclient", "-u", &bad, "stream"]).is_err());
     }
 
     #[test]
     fn make_udp_url_is_correct() {
-        let url = ClientSet::make_udp_url(34254);
-        assert_eq!(url.as_str(), format!("udp://{}:34254", UDP_CALLBACK));
+        let url = ClientSet::make_udp_url(34254, false);
+        assert_eq!(url.as_str(), format!("udp://{}:34254", UDP_CALLBACK_HOST));
+        let url = ClientSet::make_udp_url(34254, true);
+        assert_eq!(url.query(), Some("format=binary"));
     }
 
     #[test]
     fn stream_command_all_if_no_file() {
         let udp_url = Url::parse("udp://127.0.0.1:34254").unwrap();
-        let (tickers, cmd) =
-            ClientSet::tickers_and_command(&Commands::Stream { file: None }, &udp_url);
+        let (tickers, cmd) = ClientSet::tickers_and_command(
+            &Commands::Stream {
+                file: None,
+                watch: false,
+            },
+            &udp_url,
+        );
 
         assert!(tickers.is_empty());
         assert_eq!(cmd, "STREAM udp://127.0.0.1:34254 ALL");
     }
 
+    #[test]
+    fn bench_rejects_several_servers() {
+        let server: SocketAddr = "127.0.0.1:8888".parse().unwrap();
+        assert!(check_bench_servers(&[]).is_ok());
+        assert!(check_bench_servers(&[server]).is_ok());
+
+        let err = check_bench_servers(&[server, "10.0.0.2:8888".parse().unwrap()]).unwrap_err();
+        assert!(err.contains("один сервер"));
+    }
+
     #[test]
     fn stream_command_from_file() {
         let tmp = std::env::temp_dir().join("tickers_test.txt");
         fs::write(&tmp, "AAPL\nTSLA\n").unwrap();
 
         let udp_url = Url::parse("udp://127.0.0.1:34254").unwrap();
-        let (tickers, cmd) =
-            ClientSet::tickers_and_command(&Commands::Stream { file: Some(tmp) }, &udp_url);
+        let (tickers, cmd) = ClientSet::tickers_and_command(
+            &Commands::Stream {
+                file: Some(tmp),
+                watch: false,
+            },
+            &udp_url,
+        );
 
         assert_eq!(tickers, vec!["AAPL", "TSLA"]);
         assert_eq!(cmd, "STREAM udp://127.0.0.1:34254 AAPL,TSLA");
     }
+
+    #[test]
+    fn subscription_counts_tickers_in_command() {
+        let udp_url = Url::parse("udp://127.0.0.1:34254").unwrap();
+        let subscription = |tickers: &[String]| Subscription {
+            server_addr: "127.0.0.1:8888".parse().unwrap(),
+            command: stream_command(&udp_url, tickers),
+            udp_url: udp_url.clone(),
+        };
+
+        assert_eq!(subscription(&[]).ticker_count(), 0);
+        assert_eq!(
+            subscription(&["AAPL".into(), "TSLA".into()]).ticker_count(),
+            2
+        );
+    }
+
+    #[test]
+    fn bench_command_subscribes_to_all() {
+        let udp_url = Url::parse("udp://127.0.0.1:34254").unwrap();
+        let (tickers, cmd) = ClientSet::tickers_and_command(&Commands::Bench, &udp_url);
+
+        assert!(tickers.is_empty());
+        assert_eq!(cmd, "STREAM udp://127.0.0.1:34254 ALL");
+    }
+
+    #[test]
+    fn relay_args_are_parsed() {
+        let args = CliArgs::parse_from([
+            "qclient",
+            "--udp",
+            "34254",
+            "--relay",
+            "udp://127.0.0.1:40000",
+            "--relay-format",
+            "text",
+            "stream",
+        ]);
+
+        assert_eq!(args.relay.unwrap().as_str(), "udp://127.0.0.1:40000");
+        assert_eq!(args.relay_format, RelayFormat::Text);
+    }
+
+    #[test]
+    fn alert_args_are_collected() {
+        let args = CliArgs::parse_from([
+            "qclient",
+            "--alert",
+            "AAPL>150",
+            "--alert",
+            "TSLA<90",
+            "--on-alert-exec",
+            "./notify.sh",
+            "--on-alert-post",
+            "http://localhost:8080/hook",
+            "replay",
+            "record.jsonl",
+        ]);
+        let pipeline = PipelineSet::new(&args);
+
+        assert_eq!(pipeline.alert_rules.len(), 2);
+        assert_eq!(
+            pipeline.alert_actions,
+            vec![
+                AlertAction::Exec(PathBuf::from("./notify.sh")),
+                AlertAction::Post(Url::parse("http://localhost:8080/hook").unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn indicator_periods_are_parsed() {
+        let args =
+            CliArgs::parse_from(["qclient", "--sma", "20", "--ema", "50", "replay", "r.jsonl"]);
+        let pipeline = PipelineSet::new(&args);
+
+        assert_eq!(pipeline.sma, Some(20));
+        assert_eq!(pipeline.ema, Some(50));
+    }
+
+    #[test]
+    fn repeated_servers_get_distinct_udp_ports() {
+        let args = CliArgs::parse_from([
+            "qclient",
+            "--udp",
+            "34254",
+            "--server",
+            "127.0.0.1:8888",
+            "--server",
+            "10.0.0.2:8888",
+            "stream",
+        ]);
+        let set = ClientSet::new(&args, &load(&args));
+
+        assert_eq!(set.subscriptions.len(), 2);
+        assert_eq!(
+            set.subscriptions[1].server_addr.to_string(),
+            "10.0.0.2:8888"
+        );
+        assert_eq!(
+            set.subscriptions[1].command,
+            format!("STREAM udp://{}:34255 ALL", UDP_CALLBACK_HOST)
+        );
+    }
+
+    #[test]
+    fn speed_validator_accepts_multiplier() {
+        assert_eq!(validate_speed("5x").unwrap(), 5.0);
+        assert_eq!(validate_speed("0.5").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn speed_validator_rejects_non_positive() {
+        assert!(validate_speed("0x").is_err());
+        assert!(validate_speed("-2").is_err());
+        assert!(validate_speed("fast").is_err());
+    }
+
+    #[test]
+    fn replay_does_not_require_udp() {
+        let args = CliArgs::parse_from(["qclient", "replay", "record.jsonl", "--speed", "5x"]);
+
+        assert!(args.udp.is_none());
+        assert!(matches!(args.command, Commands::Replay { speed, .. } if speed == 5.0));
+    }
+
+    #[test]
+    fn verbosity_flags_are_counted() {
+        let verbosity = |args: &[&str]| CliArgs::parse_from(args).verbosity();
+
+        assert_eq!(verbosity(&["qclient", "stream"]), Verbosity::Normal);
+        assert_eq!(verbosity(&["qclient", "-v", "stream"]), Verbosity::Verbose);
+        assert_eq!(verbosity(&["qclient", "-vv", "stream"]), Verbosity::Debug);
+        assert_eq!(verbosity(&["qclient", "-q", "stream"]), Verbosity::Quiet);
+        assert!(CliArgs::try_parse_from(["qclient", "-q", "-v", "stream"]).is_err());
+    }
+
+    #[test]
+    fn log_level_flag_overrides_verbosity() {
+        let args = CliArgs::parse_from(["qclient", "-q", "--log-level", "trace", "stream"]);
+        assert_eq!(args.log_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn replay_shows_quotes_unless_quiet() {
+        let set = ReplaySet::new(
+            &CliArgs::parse_from(["qclient", "replay", "r.jsonl"]),
+            Path::new("r.jsonl"),
+            1.0,
+            &None,
+        );
+        assert_eq!(set.pipeline.verbosity, Verbosity::Verbose);
+    }
+
+    #[test]
+    fn only_stream_sessions_are_persisted() {
+        let set = |command: &str| {
+            let args = CliArgs::parse_from(["qclient", "--udp", "34254", command]);
+            ClientSet::new(&args, &load(&args))
+        };
+
+        assert!(set("stream").session.is_some());
+        assert!(set("cancel").session.is_none());
+    }
+
+    #[test]
+    fn log_dir_flag_overrides_configured_folder() {
+        let args = CliArgs::parse_from(["qclient", "stream"]);
+        let config = load(&args);
+        let paths = args.resolve_paths(&config, Paths::new(NO_CONFIG_ROOT));
+        let root = Path::new(NO_CONFIG_ROOT);
+        assert_eq!(paths.log_dir(), root.join(&config.log_folder));
+        assert_eq!(
+            config.state_path(&paths),
+            root.join(STATE_FOLDER).join(STATE_FILE)
+        );
+
+        let args = CliArgs::parse_from(["qclient", "--log-dir", "/tmp/qlog", "stream"]);
+        let paths = args.resolve_paths(&config, Paths::new(NO_CONFIG_ROOT));
+        assert_eq!(paths.log_dir(), Path::new("/tmp/qlog"));
+    }
+
+    #[test]
+    fn resume_restores_saved_subscriptions() {
+        let path = std::env::temp_dir().join("qclient_resume_test.json");
+        let args = CliArgs::parse_from(["qclient", "--udp", "34254", "stream"]);
+        SessionState::new(&ClientSet::new(&args, &load(&args)).subscriptions)
+            .save(&path)
+            .unwrap();
+
+        let set = ClientSet::resume(
+            &CliArgs::parse_from(["qclient", "resume"]),
+            &ClientConfig::default(),
+            &path,
+        );
+        assert_eq!(
+            set.subscriptions[0].command,
+            format!("STREAM udp://{}:34254 ALL", UDP_CALLBACK_HOST)
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stdout_only_is_passed_to_pipeline() {
+        let args = CliArgs::parse_from(["qclient", "--stdout-only", "replay", "record.jsonl"]);
+
+        assert!(PipelineSet::new(&args).stdout_only);
+    }
+
+    #[test]
+    fn analyze_parses_gap_threshold() {
+        let args = CliArgs::parse_from(["qclient", "analyze", "capture.jsonl", "--gap", "10"]);
+
+        assert!(matches!(args.command, Commands::Analyze { gap: 10, .. }));
+    }
 }
@@ -0,0 +1,246 @@
+//! Сравнение двух записанных трансляций котировок.
+//!
+//! Для каждого тикера сопоставляются количество котировок, последовательность
+//! цен и временные интервалы. Позволяет убедиться, что воспроизведение,
+//! прореживание или обновление сервера не изменили доставленные данные.
+
+use crate::analyze::read_capture;
+use crate::cli::CompareSet;
+use commons::errors::QuoteError;
+use commons::i18n::tr;
+use commons::models::StockQuote;
+use log::info;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+};
+
+/// Допустимое расхождение цен, при котором они считаются равными.
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// Котировки одного тикера в одной записи.
+#[derive(Debug, Clone, Default)]
+struct TickerPath {
+    prices: Vec<f64>,
+    timestamps: Vec<u64>,
+}
+
+impl TickerPath {
+    /// Длительность интервала между первой и последней котировками,
+    /// миллисекунды.
+    fn span(&self) -> u64 {
+        match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => last.saturating_sub(*first),
+            _ => 0,
+        }
+    }
+}
+
+/// Различия по одному тикеру между двумя записями.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerDiff {
+    /// Количество котировок в первой и второй записях.
+    pub counts: (usize, usize),
+    /// Количество несовпадающих цен на общем отрезке последовательностей.
+    pub price_mismatches: usize,
+    /// Позиция первого несовпадения цен.
+    pub first_mismatch: Option<usize>,
+    /// Максимальное расхождение цен на общем отрезке.
+    pub max_price_diff: f64,
+    /// Длительность трансляции тикера в каждой записи, миллисекунды.
+    pub spans: (u64, u64),
+}
+
+impl TickerDiff {
+    /// Записи по тикеру совпадают.
+    pub fn is_identical(&self) -> bool {
+        self.counts.0 == self.counts.1 && self.price_mismatches == 0 && self.spans.0 == self.spans.1
+    }
+}
+
+impl Display for TickerDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let first = self
+            .first_mismatch
+            .map_or("-".to_string(), |pos| pos.to_string());
+        write!(
+            f,
+            "{:>7} {:>7} {:>9} {:>9} {:>10.4} {:>7.1} {:>7.1}",
+            self.counts.0,
+            self.counts.1,
+            self.price_mismatches,
+            first,
+            self.max_price_diff,
+            self.spans.0 as f64 / 1000.0,
+            self.spans.1 as f64 / 1000.0
+        )
+    }
+}
+
+/// Сгруппировать котировки записи по тикерам.
+fn group_paths<I>(quotes: I) -> BTreeMap<String, TickerPath>
+where
+    I: IntoIterator<Item = StockQuote>,
+{
+    let mut paths: BTreeMap<String, TickerPath> = BTreeMap::new();
+    for quote in quotes {
+        let path = paths.entry(quote.ticker).or_default();
+        path.prices.push(quote.price.to_f64());
+        path.timestamps.push(quote.timestamp);
+    }
+    paths
+}
+
+/// Сравнить пути цен одного тикера.
+fn diff_paths(a: &TickerPath, b: &TickerPath) -> TickerDiff {
+    let mut price_mismatches = 0;
+    let mut first_mismatch = None;
+    let mut max_price_diff: f64 = 0.0;
+
+    for (pos, (pa, pb)) in a.prices.iter().zip(&b.prices).enumerate() {
+        let delta = (pa - pb).abs();
+        if delta > PRICE_EPSILON {
+            price_mismatches += 1;
+            first_mismatch.get_or_insert(pos);
+        }
+        max_price_diff = max_price_diff.max(delta);
+    }
+
+    TickerDiff {
+        counts: (a.prices.len(), b.prices.len()),
+        price_mismatches,
+        first_mismatch,
+        max_price_diff,
+        spans: (a.span(), b.span()),
+    }
+}
+
+/// Сравнить две последовательности котировок по тикерам.
+///
+/// Тикер, отсутствующий в одной из записей, сравнивается с пустым путём.
+pub fn compare_quotes<A, B>(a: A, b: B) -> BTreeMap<String, TickerDiff>
+where
+    A: IntoIterator<Item = StockQuote>,
+    B: IntoIterator<Item = StockQuote>,
+{
+    let a = group_paths(a);
+    let b = group_paths(b);
+    let empty = TickerPath::default();
+
+    let tickers: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    tickers
+        .into_iter()
+        .map(|ticker| {
+            let diff = diff_paths(
+                a.get(ticker).unwrap_or(&empty),
+                b.get(ticker).unwrap_or(&empty),
+            );
+            (ticker.clone(), diff)
+        })
+        .collect()
+}
+
+/// Сравнить два файла записи и вывести отчёт в консоль.
+///
+/// ## Returns
+///
+/// Признак полного совпадения записей.
+pub fn compare(settings: CompareSet) -> Result<bool, QuoteError> {
+    info!(
+        "Сравнение записей: {} и {}",
+        settings.first.display(),
+        settings.second.display()
+    );
+    let diffs = compare_quotes(
+        read_capture(&settings.first)?,
+        read_capture(&settings.second)?,
+    );
+
+    println!(
+        "{:<8} {:>7} {:>7} {:>9} {:>9} {:>10} {:>7} {:>7}",
+        "ticker", "count A", "count B", "mismatch", "first", "max diff", "span A", "span B"
+    );
+    let mut differing = 0;
+    for (ticker, diff) in &diffs {
+        let mark = if diff.is_identical() {
+            ""
+        } else {
+            differing += 1;
+            "  *"
+        };
+        println!("{:<8} {}{}", ticker, diff, mark);
+    }
+
+    let tickers = diffs.len();
+    let summary = if differing == 0 {
+        tr(
+            format!("Записи совпадают: тикеров {tickers}"),
+            format!("Records match: {tickers} tickers"),
+        )
+    } else {
+        tr(
+            format!("Различия по {differing} из {tickers} тикеров отмечены '*'"),
+            format!("Differences in {differing} of {tickers} tickers are marked '*'"),
+        )
+    };
+    println!("\n{summary}");
+
+    info!(
+        "Сравнение завершено: тикеров {}, с различиями {}",
+        diffs.len(),
+        differing
+    );
+    Ok(differing == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::QuoteBuilder;
+
+    #[test]
+    fn identical_records_have_no_differences() {
+        let quotes = vec![
+            QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+            QuoteBuilder::new("AAPL").price(11.0).timestamp(2).build(),
+        ];
+        let diffs = compare_quotes(quotes.clone(), quotes);
+
+        assert!(diffs["AAPL"].is_identical());
+    }
+
+    #[test]
+    fn price_path_mismatches_are_reported() {
+        let a = vec![
+            QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+            QuoteBuilder::new("AAPL").price(11.0).timestamp(2).build(),
+            QuoteBuilder::new("AAPL").price(12.0).timestamp(3).build(),
+        ];
+        let b = vec![
+            QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+            QuoteBuilder::new("AAPL").price(11.5).timestamp(2).build(),
+            QuoteBuilder::new("AAPL").price(12.0).timestamp(5).build(),
+        ];
+        let diff = &compare_quotes(a, b)["AAPL"];
+
+        assert_eq!(diff.price_mismatches, 1);
+        assert_eq!(diff.first_mismatch, Some(1));
+        assert_eq!(diff.max_price_diff, 0.5);
+        assert_eq!(diff.spans, (2, 4));
+        assert!(!diff.is_identical());
+    }
+
+    #[test]
+    fn missing_ticker_compares_with_empty_path() {
+        let a = vec![
+            QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build(),
+            QuoteBuilder::new("TSLA").price(5.0).timestamp(1).build(),
+        ];
+        let b = vec![QuoteBuilder::new("AAPL").price(10.0).timestamp(1).build()];
+        let diffs = compare_quotes(a, b);
+
+        assert_eq!(diffs["TSLA"].counts, (1, 0));
+        assert!(!diffs["TSLA"].is_identical());
+        assert!(diffs["AAPL"].is_identical());
+    }
+}
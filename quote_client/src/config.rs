@@ -1,31 +1,140 @@
 //! Конфигурационный файл Quote Client.
+//!
+//! Параметры запуска, которые можно переопределить, собраны в
+//! [`ClientConfig`] и загружаются по уровням: значения по умолчанию, файл
+//! [`CONFIG_FILE`] (или файл из ключа `--config`), переменные окружения
+//! с префиксом [`ENV_PREFIX`] и ключи командной строки.
 
-use std::net::Ipv4Addr;
-use std::ops::RangeInclusive;
-use std::str::FromStr;
+use commons::config::{ConfigLoader, LOG_FOLDER, check_port};
+use commons::errors::QuoteError;
+use commons::i18n::Lang;
+use commons::netdefaults::DEFAULT_SERVER_PORT;
+use commons::paths::Paths;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
-/// Название директории для log-файлов.
-pub const LOG_FOLDER: &str = "log";
+/// Имя файла конфигурации клиента в корневой директории ([`Paths::root`]).
+pub const CONFIG_FILE: &str = "qclient.toml";
 
-/// Адрес TCP-сервера по умолчанию.
-const DEFAULT_SERVER_SOCKET: &str = "127.0.0.1";
+/// Префикс переменных окружения клиента, например `QCLIENT_UDP`.
+pub const ENV_PREFIX: &str = "QCLIENT";
 
-pub fn default_server_socket() -> Ipv4Addr {
-    Ipv4Addr::from_str(DEFAULT_SERVER_SOCKET)
-        .unwrap_or_else(|e| panic!("Invalid default server address: {}", e))
+/// Название директории для файла состояния подписки.
+pub const STATE_FOLDER: &str = "state";
+
+/// Переопределяемые параметры запуска клиента.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// Порт TCP-сервера.
+    pub port: u16,
+    /// Базовый UDP-порт приёма котировок.
+    pub udp: Option<u16>,
+    /// Директория log-файлов относительно корневой директории; ключ
+    /// `--log-dir` задаёт её полный путь.
+    pub log_folder: String,
+    /// Директория файла состояния подписки относительно корневой
+    /// директории.
+    pub state_folder: String,
+    /// Язык сообщений консоли; если не задан — из переменной окружения
+    /// `QUOTE_LANG`.
+    pub lang: Option<Lang>,
+    /// Просить сервер присылать двоичные датаграммы с контрольной суммой
+    /// вместо JSON: повреждённые датаграммы отбрасываются и учитываются
+    /// в метрике `qclient_datagrams_corrupted_total`.
+    pub binary_udp: bool,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_SERVER_PORT,
+            udp: None,
+            log_folder: LOG_FOLDER.to_string(),
+            state_folder: STATE_FOLDER.to_string(),
+            lang: None,
+            binary_udp: false,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Загрузить конфигурацию.
+    ///
+    /// ## Args
+    /// - `paths` — директории приложения
+    /// - `file` — файл конфигурации из командной строки; если не задан,
+    ///   используется [`CONFIG_FILE`] в корневой директории, при его наличии
+    /// - `vars` — переменные окружения (`std::env::vars()`)
+    /// - `port`, `udp` — порты из командной строки
+    pub fn load(
+        paths: &Paths,
+        file: Option<&Path>,
+        vars: impl IntoIterator<Item = (String, String)>,
+        port: Option<u16>,
+        udp: Option<u16>,
+    ) -> Result<Self, QuoteError> {
+        let default_file = paths.root().join(CONFIG_FILE);
+        let config: Self = ConfigLoader::new(ENV_PREFIX)?
+            .file(file.unwrap_or(&default_file), file.is_some())?
+            .env(vars)
+            .set_opt("port", port)
+            .set_opt("udp", udp)
+            .build()?;
+
+        for port in std::iter::once(config.port).chain(config.udp) {
+            check_port(port).map_err(QuoteError::value_err)?;
+        }
+        Ok(config)
+    }
+
+    /// Полный путь к файлу состояния подписки в директории `state_folder`.
+    pub fn state_path(&self, paths: &Paths) -> PathBuf {
+        paths.root().join(&self.state_folder).join(STATE_FILE)
+    }
 }
 
-/// Порт для подключения к TCP-серверу по умолчанию.
-pub const DEFAULT_SERVER_PORT: u16 = 8888;
+/// Имя файла состояния подписки.
+pub const STATE_FILE: &str = "qclient.json";
+
+/// Тип сервиса Quote Server для поиска через mDNS.
+pub const MDNS_SERVICE_TYPE: &str = "_quote._tcp.local.";
+
+/// Время поиска серверов через mDNS (секунды).
+pub const DISCOVERY_TIMEOUT_SECS: u64 = 3;
+
+/// Timeout HTTP-запроса действия оповещения (секунды).
+pub const ALERT_POST_TIMEOUT_SECS: u64 = 5;
+
+/// Наибольшее число действий оповещений в очереди рабочего потока.
+pub const ALERT_QUEUE_SIZE: usize = 64;
+
+/// Пауза после последнего изменения файла списка наблюдения, после
+/// которой он перечитывается (миллисекунды).
+pub const WATCHLIST_DEBOUNCE_MS: u64 = 200;
+
+/// Период проверки команд с клавиатуры во время трансляции (миллисекунды).
+pub const KEYS_POLL_MS: u64 = 100;
+
+/// Период обновления панели лидеров роста и падения (секунды).
+pub const MOVERS_REFRESH_SECS: u64 = 5;
+
+/// Количество тикеров в каждом блоке панели лидеров.
+pub const MOVERS_COUNT: usize = 5;
+
+/// Период сохранения состояния подписки (секунды).
+pub const STATE_SAVE_INTERVAL_SECS: u64 = 5;
 
-/// Диапазон разрешённых TCP-портов.
-pub const ALLOW_TCP_PORTS: RangeInclusive<u16> = RangeInclusive::new(1024, 49151);
+/// Окно статистики задержки доставки в сводке по команде `s` (секунды).
+pub const LATENCY_WINDOW_SECS: u64 = 60;
 
-/// Диапазон разрешённых в приложении UDP-портов.
-pub const ALLOW_UDP_PORTS: RangeInclusive<u16> = RangeInclusive::new(1024, 49151);
+/// Период обновления сводки о работоспособности клиента (секунды).
+pub const STATUS_INTERVAL_SECS: u64 = 2;
 
-/// Базовый UDP-адрес для приёма данных от сервера.
-pub const UDP_CALLBACK: &str = "127.0.0.1";
+/// Число строк в группе Parquet-файла, после которого она записывается.
+#[cfg(feature = "parquet")]
+pub const PARQUET_ROW_GROUP_ROWS: usize = 10_000;
 
-/// Интервал отправки ping-сообщений (секунды).
-pub const PING_INTERVAL_SECS: u64 = 2;
+/// Максимальный интервал записи группы строк Parquet-файла (секунды).
+#[cfg(feature = "parquet")]
+pub const PARQUET_FLUSH_SECS: u64 = 10;
@@ -0,0 +1,173 @@
+//! Поиск серверов котировок в локальной сети через mDNS (zeroconf).
+
+use crate::config::{DISCOVERY_TIMEOUT_SECS, MDNS_SERVICE_TYPE};
+use commons::errors::QuoteError;
+use commons::i18n::tr;
+use log::info;
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent};
+use std::{
+    fmt::{Display, Formatter},
+    io::{BufRead, Write},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// Сервер, обнаруженный через mDNS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredServer {
+    /// Полное имя сервиса.
+    pub name: String,
+    /// Адрес TCP-сервера.
+    pub addr: SocketAddr,
+}
+
+impl Display for DiscoveredServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.addr)
+    }
+}
+
+/// Собрать анонсы серверов за [`DISCOVERY_TIMEOUT_SECS`].
+pub fn browse() -> Result<Vec<DiscoveredServer>, QuoteError> {
+    let daemon = ServiceDaemon::new()
+        .map_err(|err| QuoteError::server_err(format!("ошибка запуска mDNS: {err}")))?;
+    // Сервер на этой же машине анонсируется через loopback.
+    daemon
+        .enable_interface(IfKind::LoopbackV4)
+        .map_err(|err| QuoteError::server_err(format!("ошибка настройки mDNS: {err}")))?;
+    let receiver = daemon
+        .browse(MDNS_SERVICE_TYPE)
+        .map_err(|err| QuoteError::server_err(format!("ошибка поиска через mDNS: {err}")))?;
+
+    let deadline = Instant::now() + Duration::from_secs(DISCOVERY_TIMEOUT_SECS);
+    let mut servers: Vec<DiscoveredServer> = Vec::new();
+
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        let Ok(event) = receiver.recv_timeout(left) else {
+            break;
+        };
+        if let ServiceEvent::ServiceResolved(service) = event {
+            for ip in service.get_addresses() {
+                let server = DiscoveredServer {
+                    name: service.get_fullname().to_string(),
+                    addr: SocketAddr::new(*ip, service.get_port()),
+                };
+                if !servers.contains(&server) {
+                    info!("Обнаружен сервер: {server}");
+                    servers.push(server);
+                }
+            }
+        }
+    }
+
+    let _ = daemon.shutdown();
+    Ok(servers)
+}
+
+/// Предложить пользователю выбрать сервер из списка.
+///
+/// Если сервер единственный, он выбирается без вопросов.
+///
+/// ## Args
+///
+/// - `servers` — обнаруженные серверы
+/// - `input` — источник ответа пользователя (обычно `stdin`)
+/// - `output` — приёмник списка и приглашения (обычно `stdout`)
+pub fn choose_server(
+    servers: &[DiscoveredServer],
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> Result<SocketAddr, QuoteError> {
+    match servers {
+        [] => {
+            return Err(QuoteError::server_err(tr(
+                "серверы в локальной сети не найдены",
+                "no servers found on the local network",
+            )));
+        }
+        [single] => return Ok(single.addr),
+        _ => {}
+    }
+
+    for (num, server) in servers.iter().enumerate() {
+        writeln!(output, "{}. {}", num + 1, server)?;
+    }
+    let count = servers.len();
+    write!(
+        output,
+        "{}",
+        tr(
+            format!("Выберите сервер [1-{count}]: "),
+            format!("Choose a server [1-{count}]: "),
+        )
+    )?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+
+    answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|num| num.checked_sub(1))
+        .and_then(|idx| servers.get(idx))
+        .map(|server| server.addr)
+        .ok_or_else(|| {
+            let answer = answer.trim();
+            QuoteError::value_err(tr(
+                format!("некорректный выбор: '{answer}'"),
+                format!("invalid choice: '{answer}'"),
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn servers() -> Vec<DiscoveredServer> {
+        vec![
+            DiscoveredServer {
+                name: "qserver-8888._quote._tcp.local.".to_string(),
+                addr: "192.168.0.10:8888".parse().unwrap(),
+            },
+            DiscoveredServer {
+                name: "qserver-9999._quote._tcp.local.".to_string(),
+                addr: "192.168.0.11:9999".parse().unwrap(),
+            },
+        ]
+    }
+
+    #[test]
+    fn choose_single_server_without_prompt() {
+        let servers = &servers()[..1];
+        let mut output = Vec::new();
+
+        let addr = choose_server(servers, Cursor::new(""), &mut output).unwrap();
+
+        assert_eq!(addr, servers[0].addr);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn choose_server_by_number() {
+        let mut output = Vec::new();
+        let addr = choose_server(&servers(), Cursor::new("2\n"), &mut output).unwrap();
+
+        assert_eq!(addr, servers()[1].addr);
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("1. qserver-8888")
+        );
+    }
+
+    #[test]
+    fn choose_server_rejects_bad_input() {
+        assert!(choose_server(&servers(), Cursor::new("0\n"), Vec::new()).is_err());
+        assert!(choose_server(&servers(), Cursor::new("abc\n"), Vec::new()).is_err());
+        assert!(choose_server(&[], Cursor::new(""), Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Сведения о работоспособности клиента для внешнего надзора.
+//!
+//! Во время трансляции клиент может периодически записывать JSON-сводку
+//! в файл статуса и отдавать ту же сводку по HTTP: `200 OK`, пока
+//! подписка действует, и `503 Service Unavailable` после её завершения.
+//! Сводка включает состояние подсистем клиента ([`commons::health`]):
+//! приёмник котировок отмечает активность при каждой котировке.
+
+use crate::config::STATUS_INTERVAL_SECS;
+use commons::errors::{QuoteError, ResultExt};
+use commons::health::{Health, HealthHandle, HealthReport};
+use commons::models::StockQuote;
+use commons::shutdown::ShutdownToken;
+use commons::utils::get_timestamp_ms;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Сводка о состоянии клиента.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HealthStatus {
+    /// Подписка действует и котировки принимаются.
+    pub connected: bool,
+    /// Количество действующих подписок.
+    pub subscriptions: usize,
+    /// Количество котировок, полученных с начала работы.
+    pub received: u64,
+    /// Скорость приёма за последний интервал обновления.
+    pub quotes_per_sec: f64,
+    /// Временная метка последней полученной котировки (миллисекунды).
+    pub last_quote_timestamp: Option<u64>,
+    /// Время обновления сводки (Unix, миллисекунды).
+    pub updated: u64,
+    /// Состояние подсистем клиента.
+    pub health: HealthReport,
+}
+
+/// Разделяемая между потоками сводка о состоянии клиента.
+#[derive(Clone)]
+pub struct HealthMonitor {
+    status: Arc<Mutex<HealthStatus>>,
+    health: Health,
+    receiver: HealthHandle,
+}
+
+impl HealthMonitor {
+    /// Создать экземпляр для `subscriptions` действующих подписок.
+    pub fn new(subscriptions: usize) -> Self {
+        let health = Health::new();
+        let monitor = Self {
+            status: Arc::default(),
+            receiver: health.register("receiver", None),
+            health,
+        };
+        monitor.update(|status| {
+            status.connected = subscriptions > 0;
+            status.subscriptions = subscriptions;
+        });
+        monitor
+    }
+
+    /// Учесть полученную котировку.
+    pub fn record(&self, quote: &StockQuote) {
+        self.receiver.beat();
+        self.update(|status| {
+            status.received += 1;
+            status.last_quote_timestamp = Some(quote.timestamp);
+        });
+    }
+
+    /// Отметить завершение подписки.
+    pub fn disconnect(&self) {
+        self.receiver.down("подписка завершена");
+        self.update(|status| status.connected = false);
+    }
+
+    /// Текущая сводка.
+    pub fn snapshot(&self) -> HealthStatus {
+        let mut snapshot = self
+            .status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_default();
+        snapshot.health = self.health.report();
+        snapshot
+    }
+
+    fn update(&self, f: impl FnOnce(&mut HealthStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            f(&mut status);
+            status.updated = get_timestamp_ms();
+        }
+    }
+}
+
+/// Записать сводку в файл статуса через временный файл.
+pub fn write_status(path: &Path, status: &HealthStatus) -> Result<(), QuoteError> {
+    let json = serde_json::to_string(status).context("ошибка сериализации статуса")?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .with_context(|| format!("не удалось записать файл статуса {}", path.display()))
+}
+
+/// Запустить поток, который раз в [`STATUS_INTERVAL_SECS`] пересчитывает
+/// скорость приёма и, если задан `status_file`, записывает сводку в файл.
+///
+/// Поток завершается при отмене `shutdown`.
+pub fn spawn_reporter(
+    monitor: HealthMonitor,
+    status_file: Option<PathBuf>,
+    shutdown: ShutdownToken,
+) {
+    thread::spawn(move || {
+        let interval = Duration::from_secs(STATUS_INTERVAL_SECS);
+        let mut last_received = 0;
+        let mut last_tick = Instant::now();
+
+        while !shutdown.cancelled_wait(Duration::from_millis(100)) {
+            let elapsed = last_tick.elapsed();
+            if elapsed >= interval {
+                monitor.update(|status| {
+                    status.quotes_per_sec =
+                        (status.received - last_received) as f64 / elapsed.as_secs_f64();
+                    last_received = status.received;
+                });
+                last_tick = Instant::now();
+
+                if let Some(path) = &status_file
+                    && let Err(err) = write_status(path, &monitor.snapshot())
+                {
+                    warn!("{err}");
+                }
+            }
+        }
+    });
+}
+
+/// Запустить HTTP-точку проверки работоспособности.
+///
+/// На любой запрос отвечает JSON-сводкой.
+///
+/// ## Returns
+///
+/// Фактический адрес, на котором принимаются запросы.
+pub fn spawn_endpoint(monitor: HealthMonitor, addr: SocketAddr) -> Result<SocketAddr, QuoteError> {
+    let listener = TcpListener::bind(addr).map_err(|err| {
+        QuoteError::server_err(format!("не удалось открыть точку проверки {addr}: {err}"))
+    })?;
+    let local_addr = listener.local_addr().map_err(|err| {
+        QuoteError::server_err(format!("не удалось получить адрес точки проверки: {err}"))
+    })?;
+    info!("Точка проверки работоспособности: http://{local_addr}/");
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(err) = respond(stream, &monitor.snapshot()) {
+                warn!("Ошибка ответа точки проверки: {err}");
+            }
+        }
+    });
+    Ok(local_addr)
+}
+
+/// Прочитать заголовки запроса и ответить сводкой.
+fn respond(stream: TcpStream, status: &HealthStatus) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line.trim_end() != "" {
+        line.clear();
+    }
+
+    let body = serde_json::to_string(status).unwrap_or_default();
+    let code = if status.connected {
+        "200 OK"
+    } else {
+        "503 Service Unavailable"
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {code}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::QuoteBuilder;
+    use std::io::Read;
+
+    #[test]
+    fn monitor_tracks_quotes_and_connection() {
+        let monitor = HealthMonitor::new(2);
+        monitor.record(&QuoteBuilder::new("AAPL").timestamp(10).build());
+        monitor.record(&QuoteBuilder::new("AAPL").timestamp(12).build());
+
+        let status = monitor.snapshot();
+        assert!(status.connected);
+        assert_eq!(status.subscriptions, 2);
+        assert_eq!(status.received, 2);
+        assert_eq!(status.last_quote_timestamp, Some(12));
+
+        assert!(status.health.is_up());
+        assert!(status.health.components["receiver"].last_activity.is_some());
+
+        monitor.disconnect();
+        let status = monitor.snapshot();
+        assert!(!status.connected);
+        assert_eq!(
+            status.health.problems(),
+            ["receiver — не работает (подписка завершена)"]
+        );
+    }
+
+    #[test]
+    fn status_file_contains_json() {
+        let path = std::env::temp_dir().join(format!("qclient-status-{}.json", std::process::id()));
+        let monitor = HealthMonitor::new(1);
+        monitor.record(&QuoteBuilder::new("AAPL").timestamp(5).build());
+
+        write_status(&path, &monitor.snapshot()).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(json["connected"], true);
+        assert_eq!(json["received"], 1);
+        assert_eq!(json["last_quote_timestamp"], 5);
+        assert_eq!(json["health"]["components"]["receiver"]["state"], "up");
+    }
+
+    #[test]
+    fn endpoint_reports_status_code() {
+        let monitor = HealthMonitor::new(1);
+        let addr = spawn_endpoint(monitor.clone(), "127.0.0.1:0".parse().unwrap()).unwrap();
+
+        let get = || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        assert!(get().starts_with("HTTP/1.1 200 OK"));
+        monitor.disconnect();
+        let response = get();
+        assert!(response.starts_with("HTTP/1.1 503"));
+        assert!(response.contains("\"connected\":false"));
+    }
+}
@@ -0,0 +1,140 @@
+//! Скользящие средние по тикерам: простая (SMA) и экспоненциальная (EMA).
+
+use commons::models::StockQuote;
+use commons::rolling::{Ema, RollingWindow};
+use std::collections::HashMap;
+
+/// Значение индикатора для вывода: подпись и значение (`None`, пока данных
+/// недостаточно).
+pub type IndicatorValue = (String, Option<f64>);
+
+/// Состояние индикаторов одного тикера.
+struct TickerState {
+    /// Окно последних цен для SMA.
+    sma: Option<RollingWindow>,
+    /// Экспоненциальное среднее цен.
+    ema: Option<Ema>,
+}
+
+/// Расчёт скользящих средних по каждому тикеру.
+pub struct Indicators {
+    /// Размер окна SMA.
+    sma_period: Option<usize>,
+    /// Период EMA.
+    ema_period: Option<usize>,
+    /// Состояние по тикерам.
+    tickers: HashMap<String, TickerState>,
+}
+
+impl Indicators {
+    /// Создать экземпляр. Индикаторы без периода не рассчитываются.
+    pub fn new(sma_period: Option<usize>, ema_period: Option<usize>) -> Self {
+        Self {
+            sma_period,
+            ema_period,
+            tickers: HashMap::new(),
+        }
+    }
+
+    /// Учесть цену котировки и вернуть актуальные значения индикаторов
+    /// по её тикеру.
+    pub fn update(&mut self, quote: &StockQuote) -> Vec<IndicatorValue> {
+        let mut values = Vec::new();
+        if self.sma_period.is_none() && self.ema_period.is_none() {
+            return values;
+        }
+
+        let (sma_period, ema_period) = (self.sma_period, self.ema_period);
+        let state = self
+            .tickers
+            .entry(quote.ticker.clone())
+            .or_insert_with(|| TickerState {
+                sma: sma_period.map(RollingWindow::with_count),
+                ema: ema_period.map(Ema::new),
+            });
+        let price = quote.price.to_f64();
+
+        if let (Some(period), Some(window)) = (sma_period, state.sma.as_mut()) {
+            window.push(price);
+            let sma = window.mean().filter(|_| window.is_full());
+            values.push((format!("SMA{period}"), sma));
+        }
+
+        if let (Some(period), Some(ema)) = (ema_period, state.ema.as_mut()) {
+            values.push((format!("EMA{period}"), Some(ema.push(price))));
+        }
+
+        values
+    }
+}
+
+/// Валидатор для ключей `--sma` и `--ema`.
+pub fn validate_period(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(period) if period > 0 => Ok(period),
+        _ => Err(format!("period must be a positive integer: {s}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::quote;
+
+    #[test]
+    fn sma_waits_for_full_window() {
+        let mut indicators = Indicators::new(Some(3), None);
+
+        assert_eq!(
+            indicators.update(&quote("A", 1.0)),
+            vec![("SMA3".into(), None)]
+        );
+        indicators.update(&quote("A", 2.0));
+        assert_eq!(
+            indicators.update(&quote("A", 3.0)),
+            vec![("SMA3".into(), Some(2.0))]
+        );
+        assert_eq!(
+            indicators.update(&quote("A", 7.0)),
+            vec![("SMA3".into(), Some(4.0))]
+        );
+    }
+
+    #[test]
+    fn ema_is_seeded_by_first_price() {
+        let mut indicators = Indicators::new(None, Some(3));
+
+        assert_eq!(
+            indicators.update(&quote("A", 10.0)),
+            vec![("EMA3".into(), Some(10.0))]
+        );
+        assert_eq!(
+            indicators.update(&quote("A", 20.0)),
+            vec![("EMA3".into(), Some(15.0))]
+        );
+    }
+
+    #[test]
+    fn tickers_are_independent() {
+        let mut indicators = Indicators::new(Some(1), None);
+        indicators.update(&quote("A", 10.0));
+
+        assert_eq!(
+            indicators.update(&quote("B", 5.0)),
+            vec![("SMA1".into(), Some(5.0))]
+        );
+    }
+
+    #[test]
+    fn disabled_indicators_are_empty() {
+        let mut indicators = Indicators::new(None, None);
+        assert!(indicators.update(&quote("A", 1.0)).is_empty());
+    }
+
+    #[test]
+    fn period_validator() {
+        assert_eq!(validate_period("20").unwrap(), 20);
+        assert!(validate_period("0").is_err());
+        assert!(validate_period("-3").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Управление трансляцией с клавиатуры.
+//!
+//! Команды вводятся в консоли одной буквой с подтверждением Enter:
+//! это не требует перевода терминала в «сырой» режим и не ломает
+//! построчный вывод котировок.
+
+use commons::i18n::Msg;
+use log::{info, warn};
+use std::io::{BufRead, stdin};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Подсказка по доступным командам.
+pub const KEYS_HELP: Msg = Msg::new(
+    "Команды (буква + Enter): p — пауза вывода, c — отменить подписку, s — статистика, q — выход",
+    "Commands (letter + Enter): p — pause output, c — cancel subscription, s — statistics, q — quit",
+);
+
+/// Вывод котировок приостановлен.
+pub const OUTPUT_PAUSED: Msg = Msg::new("Вывод приостановлен", "Output paused");
+
+/// Вывод котировок возобновлён.
+pub const OUTPUT_RESUMED: Msg = Msg::new("Вывод возобновлён", "Output resumed");
+
+/// Подписка отменена (далее — UDP-адрес).
+pub const SUBSCRIPTION_CANCELED: Msg = Msg::new("Подписка отменена", "Subscription canceled");
+
+/// Команда, введённая с клавиатуры.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCommand {
+    /// Приостановить или возобновить вывод котировок.
+    TogglePause,
+    /// Отправить серверу `CANCEL`.
+    Cancel,
+    /// Показать статистику сеанса.
+    Stats,
+    /// Корректно завершить работу.
+    Quit,
+}
+
+impl KeyCommand {
+    /// Распознать команду по первому символу строки.
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.trim().chars().next()?.to_ascii_lowercase() {
+            'p' => Some(Self::TogglePause),
+            'c' => Some(Self::Cancel),
+            's' => Some(Self::Stats),
+            'q' => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Запустить фоновое чтение команд из stdin.
+pub fn spawn_reader() -> Receiver<KeyCommand> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match KeyCommand::parse(&line) {
+                Some(command) => {
+                    info!("Команда с клавиатуры: {command:?}");
+                    if tx.send(command).is_err() {
+                        break;
+                    }
+                }
+                None if line.trim().is_empty() => {}
+                None => {
+                    warn!("Неизвестная команда с клавиатуры: {}", line.trim());
+                    println!("{KEYS_HELP}");
+                }
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uses_first_letter() {
+        assert_eq!(KeyCommand::parse("p"), Some(KeyCommand::TogglePause));
+        assert_eq!(KeyCommand::parse(" Q\n"), Some(KeyCommand::Quit));
+        assert_eq!(KeyCommand::parse("stats"), Some(KeyCommand::Stats));
+        assert_eq!(KeyCommand::parse("x"), None);
+        assert_eq!(KeyCommand::parse(""), None);
+    }
+}
@@ -1,110 +1,239 @@
 //! Quote Client. Приложение для взаимодействия с Quote Server.
 
-use log::{error, info, warn};
+use log::{LevelFilter, info, warn};
 use std::{
-    io::{BufRead, BufReader, Result, Write},
-    net::TcpStream,
+    env,
+    io::{IsTerminal, Result, stdin},
+    path::PathBuf,
     process::exit,
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
+    sync::mpsc::RecvTimeoutError,
+    time::Duration,
 };
 
+mod aggregate;
+mod alerts;
+mod aliases;
+mod analyze;
+mod bench;
 mod cli;
+mod compare;
 mod config;
-mod udp;
-
-use cli::parse_cli_args;
+mod discovery;
+mod health;
+mod indicators;
+mod keys;
+mod metrics;
+mod movers;
+mod output;
+#[cfg(feature = "parquet")]
+mod parquet_sink;
+mod pipeline;
+mod portfolio;
+mod relay;
+mod replay;
+mod scripting;
+mod sparkline;
+mod state;
+mod watchlist;
+
+use cli::{ExitCode, RunMode, parse_cli_args, run_mode};
 use commons::errors::QuoteError;
-use commons::{init_simple_logger, utils::get_workspace_root};
-use config::LOG_FOLDER;
+use commons::i18n;
+use commons::init_logger_with_level;
+use commons::paths::{self, Paths};
+use commons::shutdown::ShutdownToken;
+use config::KEYS_POLL_MS;
+use health::HealthMonitor;
+use keys::KeyCommand;
+use pipeline::QuotePipeline;
+use quote_client_lib::{QuoteStream, StreamControl};
+use state::StateRecorder;
+use watchlist::Watchlist;
 
 fn main() -> Result<()> {
-    if let Err(err) = init_logger() {
-        error!("{}", err);
-        exit(1);
+    let args = parse_cli_args();
+    let config = args
+        .load_config(&Paths::from_env(), env::vars())
+        .unwrap_or_else(|err| exit_err(&err));
+    i18n::init(config.lang);
+    let paths = paths::init(args.resolve_paths(&config, Paths::from_env()));
+    if let Err(err) = init_logger(
+        paths.log_dir().to_path_buf(),
+        args.log_level(),
+        args.log_console(),
+    ) {
+        exit_err(&err);
     }
-    let client_set = parse_cli_args();
+    let (client_set, bench_mode) = match run_mode(&args, &config, paths) {
+        RunMode::Network(client_set) => (client_set, false),
+        RunMode::Bench(client_set) => (client_set, true),
+        RunMode::Replay(replay_set) => {
+            if let Err(err) = replay::replay(replay_set, install_shutdown()) {
+                exit_err(&err);
+            }
+            return Ok(());
+        }
+        RunMode::Analyze(analyze_set) => {
+            if let Err(err) = analyze::analyze(analyze_set) {
+                exit_err(&err);
+            }
+            return Ok(());
+        }
+        RunMode::Compare(compare_set) => match compare::compare(compare_set) {
+            Ok(true) => return Ok(()),
+            Ok(false) => exit(ExitCode::RecordsDiffer.value().into()),
+            Err(err) => exit_err(&err),
+        },
+    };
 
     info!("Quote Client запущен");
 
-    let stream = TcpStream::connect(client_set.server_addr)
-        .unwrap_or_else(|e| panic!("Ошибка подключения к {}: {}", client_set.server_addr, e));
-
-    let mut reader = BufReader::new(stream.try_clone()?);
-    let mut writer = stream;
+    let relay = client_set.relay.as_ref().map(|(url, format)| {
+        relay::QuoteRelay::new(url, *format).unwrap_or_else(|err| exit_err(&err))
+    });
+
+    let shutdown = install_shutdown();
+
+    if bench_mode {
+        let subscription = &client_set.subscriptions[0];
+        match QuoteStream::subscribe(
+            subscription.server_addr,
+            &subscription.udp_url,
+            &subscription.command,
+            shutdown.flag(),
+        ) {
+            Ok(quotes) => bench::run(&quotes),
+            Err(err) => exit_err(&err),
+        }
+        return Ok(());
+    }
 
-    info!(
-        "Установлено соединение с сервером: {}",
-        client_set.server_addr
-    );
+    // Источник указывается, только если серверов несколько.
+    let tag_source = client_set.subscriptions.len() > 1;
+    let parse_error_hook = client_set
+        .pipeline
+        .verbosity
+        .shows_parse_errors()
+        .then_some(output::print_parse_error as fn(&str));
+    let (quotes, controls) =
+        aggregate::merge_streams(&client_set.subscriptions, parse_error_hook, shutdown.flag());
+    if let Some(path) = client_set.watchlist {
+        let watchlist = Watchlist::new(path, client_set.tickers);
+        watchlist::spawn_watcher(watchlist, controls.clone(), shutdown.child());
+    }
 
-    // Пропуск приветствия и служебной информации.
+    // Управление с клавиатуры — только в интерактивной консоли.
+    let keys = (stdin().is_terminal() && output::human_output()).then(|| {
+        println!("{}", keys::KEYS_HELP);
+        keys::spawn_reader()
+    });
+
+    let health = client_set.health.is_enabled().then(|| {
+        let monitor = HealthMonitor::new(controls.len());
+        if let Some(addr) = client_set.health.addr
+            && let Err(err) = health::spawn_endpoint(monitor.clone(), addr)
+        {
+            exit_err(&err);
+        }
+        health::spawn_reporter(
+            monitor.clone(),
+            client_set.health.status_file.clone(),
+            shutdown.child(),
+        );
+        monitor
+    });
+
+    let mut pipeline = QuotePipeline::new(client_set.pipeline).with_relay(relay);
+    let mut recorder = client_set
+        .session
+        .map(|session| StateRecorder::new(session, config.state_path(paths)));
     loop {
-        let mut line = String::new();
-        let bytes = reader.read_line(&mut line)?;
-        if bytes == 0 {
-            break;
+        for command in keys.iter().flat_map(|rx| rx.try_iter()) {
+            handle_key(command, &mut pipeline, &controls, &shutdown);
         }
-        if line.trim_end().to_uppercase() == "READY" {
-            break;
+
+        match quotes.recv_timeout(Duration::from_millis(KEYS_POLL_MS)) {
+            Ok((source, quote)) => {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record(&quote, source);
+                }
+                if let Some(monitor) = &health {
+                    monitor.record(&quote);
+                }
+                pipeline.process(quote, tag_source.then_some(source));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
         }
+        pipeline.tick();
     }
-
-    writer.write_all(client_set.command.as_bytes())?;
-    writer.write_all(b"\n")?;
-    writer.flush()?;
-
-    info!("Отправлена команда: {}", client_set.command);
-
-    let mut server_response = String::new();
-    let bytes = reader.read_line(&mut server_response)?;
-    if bytes == 0 {
-        let err_msg = "Пустой ответ от сервера или сервер закрыл соединение.";
-        warn!("{}", err_msg);
-        return Ok(());
+    pipeline.record_lost(controls.iter().map(StreamControl::lost).sum());
+    pipeline.record_corrupted(controls.iter().map(StreamControl::corrupted).sum());
+    pipeline.finish();
+
+    if let Some(monitor) = &health {
+        monitor.disconnect();
+        if let Some(path) = &client_set.health.status_file
+            && let Err(err) = health::write_status(path, &monitor.snapshot())
+        {
+            warn!("{err}");
+        }
     }
 
-    let response = server_response.trim_end();
-    info!("Ответ сервера: {}", response);
-
-    if !response.starts_with("OK") {
-        warn!("Сервер отклонил команду");
-        return Ok(());
-    }
+    Ok(())
+}
 
-    let stop_flag = Arc::new(AtomicBool::new(false));
-    let stop_flag_clone = stop_flag.clone();
-
-    ctrlc::set_handler(move || {
-        stop_flag_clone.store(true, Ordering::SeqCst);
-    })
-    .expect("Ошибка установки Ctrl-C");
-
-    let udp = udp::UdpClient::bind_url(&client_set.udp_url)?;
-    let ping_handle = match udp.spawn_ping(stop_flag.clone()) {
-        Ok(h) => h,
-        Err(err) => {
-            warn!(
-                "Не удалось клонировать UDP‑сокет для {}: {}",
-                client_set.udp_url, err
-            );
-            return Ok(());
+/// Выполнить команду, введённую с клавиатуры.
+fn handle_key(
+    command: KeyCommand,
+    pipeline: &mut QuotePipeline,
+    controls: &[StreamControl],
+    shutdown: &ShutdownToken,
+) {
+    match command {
+        KeyCommand::TogglePause => {
+            let paused = pipeline.toggle_pause();
+            if paused {
+                println!("{}", keys::OUTPUT_PAUSED);
+            } else {
+                println!("{}", keys::OUTPUT_RESUMED);
+            }
         }
-    };
+        KeyCommand::Cancel => {
+            for control in controls {
+                match control.cancel() {
+                    Ok(()) => println!("{}: {}", keys::SUBSCRIPTION_CANCELED, control.udp_url()),
+                    Err(err) => warn!("{err}"),
+                }
+            }
+        }
+        KeyCommand::Stats => println!("{}", pipeline.stats()),
+        KeyCommand::Quit => shutdown.cancel(),
+    }
+}
 
-    udp.recv_loop(stop_flag, client_set.verbose);
-    let _ = ping_handle.join();
+/// Опубликовать ошибку в log-файл и консоль и завершить работу приложения.
+///
+/// Код завершения соответствует виду ошибки ([`ExitCode::Failed`]).
+fn exit_err(err: &QuoteError) -> ! {
+    cli::exit_err(&err.to_string(), ExitCode::from(err));
+}
 
-    Ok(())
+/// Установить обработчик Ctrl-C и вернуть токен завершения работы.
+fn install_shutdown() -> ShutdownToken {
+    ShutdownToken::install().unwrap_or_else(|err| exit_err(&err))
 }
 
 /// Инициализировать логгер приложения.
 ///
-/// Используется метод [`init_simple_logger`] из крейта [`commons`].
-fn init_logger() -> std::result::Result<(), QuoteError> {
-    let log_folder = get_workspace_root().join(LOG_FOLDER);
+/// Используется метод [`init_logger_with_level`] из крейта [`commons`].
+fn init_logger(
+    log_folder: PathBuf,
+    level: LevelFilter,
+    console_level: Option<LevelFilter>,
+) -> std::result::Result<(), QuoteError> {
     let app_name = env!("CARGO_PKG_NAME");
-    init_simple_logger(app_name, log_folder)?;
+    init_logger_with_level(app_name, log_folder, level, console_level)?;
 
     Ok(())
 }
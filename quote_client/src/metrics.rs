@@ -0,0 +1,165 @@
+//! Метрики клиента: полученные котировки, пропуски и задержка доставки.
+
+use crate::config::LATENCY_WINDOW_SECS;
+use commons::metrics::{Counter, Gauge, Histogram, MetricsRegistry};
+use commons::models::StockQuote;
+use commons::rolling::RollingWindow;
+use commons::utils::get_timestamp_ms;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Верхние границы корзин задержки доставки, миллисекунды.
+const LATENCY_BOUNDS_MS: [f64; 8] = [1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0];
+
+/// Метрики сеанса клиента.
+pub struct ClientMetrics {
+    registry: MetricsRegistry,
+    received: Arc<Counter>,
+    filtered: Arc<Counter>,
+    seq_gaps: Arc<Counter>,
+    corrupted: Arc<Counter>,
+    tickers: Arc<Gauge>,
+    latency: Arc<Histogram>,
+    /// Задержки доставки за последние [`LATENCY_WINDOW_SECS`] секунд.
+    recent_latency: RollingWindow,
+    seen: HashSet<String>,
+}
+
+impl ClientMetrics {
+    /// Зарегистрировать метрики клиента.
+    pub fn new() -> Self {
+        let registry = MetricsRegistry::new();
+        Self {
+            received: registry.counter("qclient_quotes_received_total", "Получено котировок"),
+            filtered: registry.counter(
+                "qclient_quotes_filtered_total",
+                "Котировок, отброшенных сценарием",
+            ),
+            seq_gaps: registry.counter(
+                "qclient_seq_gaps_total",
+                "Потеряно датаграмм (пропуски номеров трансляции)",
+            ),
+            corrupted: registry.counter(
+                "qclient_datagrams_corrupted_total",
+                "Повреждённых датаграмм (неверная контрольная сумма)",
+            ),
+            tickers: registry.gauge("qclient_tickers", "Различных тикеров в потоке"),
+            latency: registry.histogram(
+                "qclient_delivery_latency_ms",
+                "Задержка доставки котировки, мс",
+                &LATENCY_BOUNDS_MS,
+            ),
+            recent_latency: RollingWindow::with_period(Duration::from_secs(LATENCY_WINDOW_SECS)),
+            registry,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Учесть полученную котировку.
+    pub fn record(&mut self, quote: &StockQuote) {
+        self.received.inc();
+        if !self.seen.contains(&quote.ticker) {
+            self.seen.insert(quote.ticker.clone());
+            self.tickers.set(self.seen.len() as i64);
+        }
+
+        let now = get_timestamp_ms();
+        if now >= quote.timestamp {
+            let latency = (now - quote.timestamp) as f64;
+            self.latency.observe(latency);
+            self.recent_latency.push(latency);
+        }
+    }
+
+    /// Учесть датаграммы, потерянные подпиской
+    /// ([`StreamControl::lost`](quote_client_lib::StreamControl::lost)).
+    pub fn record_lost(&self, count: u64) {
+        self.seq_gaps.add(count);
+    }
+
+    /// Учесть повреждённые датаграммы подписок
+    /// ([`StreamControl::corrupted`](quote_client_lib::StreamControl::corrupted)).
+    pub fn record_corrupted(&self, count: u64) {
+        self.corrupted.add(count);
+    }
+
+    /// Учесть котировку, отброшенную сценарием.
+    pub fn record_filtered(&self) {
+        self.filtered.inc();
+    }
+
+    /// Количество полученных котировок.
+    pub fn received(&self) -> u64 {
+        self.received.get()
+    }
+
+    /// Количество различных тикеров.
+    pub fn tickers(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Задержка доставки за последние [`LATENCY_WINDOW_SECS`] секунд:
+    /// среднее и процентили; `None`, если котировок за это время не было.
+    pub fn latency_summary(&mut self) -> Option<String> {
+        self.recent_latency.expire(Instant::now());
+        let stats = self.recent_latency.stats()?;
+        let p50 = self.recent_latency.percentile(50.0)?;
+        let p99 = self.recent_latency.percentile(99.0)?;
+        Some(format!(
+            "задержка за {LATENCY_WINDOW_SECS} с: среднее {:.1} мс, p50/p99 {p50:.0}/{p99:.0} мс, макс {:.0} мс",
+            stats.mean, stats.max
+        ))
+    }
+
+    /// Значения метрик простым текстом.
+    pub fn render(&self) -> String {
+        self.registry.render_text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::QuoteBuilder;
+
+    #[test]
+    fn quotes_and_losses_are_counted() {
+        let mut metrics = ClientMetrics::new();
+        let now = get_timestamp_ms();
+        let quote = |ticker, seq| QuoteBuilder::new(ticker).seq(seq).timestamp(now).build();
+
+        // Пропуски сквозных номеров (отфильтрованные тикеры) потерями
+        // не считаются: их сообщает подписка.
+        for seq in [1, 2, 5, 6] {
+            metrics.record(&quote("AAPL", seq));
+        }
+        metrics.record(&quote("MSFT", 100));
+        metrics.record(&quote("MSFT", 0));
+        metrics.record_lost(2);
+        metrics.record_corrupted(1);
+
+        assert_eq!(metrics.received(), 6);
+        assert_eq!(metrics.corrupted.get(), 1);
+        assert_eq!(metrics.tickers(), 2);
+        assert_eq!(metrics.seq_gaps.get(), 2);
+        assert_eq!(metrics.latency.count(), 6);
+        assert!(
+            metrics
+                .latency_summary()
+                .unwrap()
+                .starts_with("задержка за 60 с")
+        );
+    }
+
+    #[test]
+    fn render_lists_all_metrics() {
+        let metrics = ClientMetrics::new();
+        metrics.record_filtered();
+        let text = metrics.render();
+
+        assert!(text.contains("qclient_quotes_received_total 0"));
+        assert!(text.contains("qclient_quotes_filtered_total 1"));
+        assert!(text.contains("qclient_delivery_latency_ms count=0"));
+    }
+}
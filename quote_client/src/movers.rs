@@ -0,0 +1,123 @@
+//! Панель лидеров роста и падения.
+//!
+//! Для каждого тикера запоминается первая цена с начала подписки;
+//! по последним ценам периодически выводится блок из лучших
+//! и худших тикеров по изменению в процентах.
+
+use crate::config::{MOVERS_COUNT, MOVERS_REFRESH_SECS};
+use commons::models::StockQuote;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Изменение цен по тикерам с начала подписки.
+pub struct TopMovers {
+    /// Первая и последняя цена по тикеру.
+    prices: HashMap<String, (f64, f64)>,
+    last_render: Instant,
+}
+
+impl TopMovers {
+    /// Создать пустую панель.
+    pub fn new() -> Self {
+        Self {
+            prices: HashMap::new(),
+            last_render: Instant::now(),
+        }
+    }
+
+    /// Учесть котировку.
+    pub fn update(&mut self, quote: &StockQuote) {
+        let price = quote.price.to_f64();
+        self.prices
+            .entry(quote.ticker.clone())
+            .and_modify(|(_, last)| *last = price)
+            .or_insert((price, price));
+    }
+
+    /// Пора ли обновить панель. Отсчёт интервала начинается заново.
+    pub fn due(&mut self) -> bool {
+        if self.last_render.elapsed() < Duration::from_secs(MOVERS_REFRESH_SECS) {
+            return false;
+        }
+        self.last_render = Instant::now();
+        true
+    }
+
+    /// Тикеры с изменением цены в процентах, от лучшего к худшему.
+    pub fn ranked(&self) -> Vec<(&str, f64)> {
+        let mut ranked: Vec<(&str, f64)> = self
+            .prices
+            .iter()
+            .filter(|(_, (first, _))| *first > 0.0)
+            .map(|(ticker, (first, last))| (ticker.as_str(), (last - first) / first * 100.0))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked
+    }
+
+    /// Сформировать блок лидеров роста и падения.
+    pub fn render(&self) -> String {
+        let ranked = self.ranked();
+        let gainers = ranked
+            .iter()
+            .filter(|(_, change)| *change > 0.0)
+            .take(MOVERS_COUNT);
+        let losers = ranked
+            .iter()
+            .rev()
+            .filter(|(_, change)| *change < 0.0)
+            .take(MOVERS_COUNT);
+
+        let mut block = format!("--- Топ-{MOVERS_COUNT}: рост ---\n");
+        for (ticker, change) in gainers {
+            block.push_str(&format!("{:<8} {:>+8.2}%\n", ticker, change));
+        }
+        block.push_str(&format!("--- Топ-{MOVERS_COUNT}: падение ---\n"));
+        for (ticker, change) in losers {
+            block.push_str(&format!("{:<8} {:>+8.2}%\n", ticker, change));
+        }
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::quote;
+
+    #[test]
+    fn ranked_uses_change_since_first_price() {
+        let mut movers = TopMovers::new();
+        for (ticker, price) in [
+            ("AAPL", 10.0),
+            ("TSLA", 20.0),
+            ("AAPL", 11.0),
+            ("TSLA", 15.0),
+        ] {
+            movers.update(&quote(ticker, price));
+        }
+        let ranked = movers.ranked();
+
+        assert_eq!(ranked[0].0, "AAPL");
+        assert!((ranked[0].1 - 10.0).abs() < 1e-9);
+        assert!((ranked[1].1 + 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn render_splits_gainers_and_losers() {
+        let mut movers = TopMovers::new();
+        for (ticker, price) in [
+            ("AAPL", 10.0),
+            ("TSLA", 20.0),
+            ("AAPL", 11.0),
+            ("TSLA", 15.0),
+        ] {
+            movers.update(&quote(ticker, price));
+        }
+        let block = movers.render();
+        let (gainers, losers) = block.split_once("падение").unwrap();
+
+        assert!(gainers.contains("AAPL") && !gainers.contains("TSLA"));
+        assert!(losers.contains("TSLA") && !losers.contains("AAPL"));
+    }
+}
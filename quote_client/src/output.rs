@@ -0,0 +1,453 @@
+//! Вывод полученных котировок пользователю.
+//!
+//! В консоль котировки выводятся выровненными колонками. Цена подсвечивается
+//! зелёным, если выросла относительно предыдущего значения по тикеру,
+//! и красным, если снизилась. В log-файл пишется исходное строковое
+//! представление котировки.
+//!
+//! В режиме `--stdout-only` вывод для человека (таблица, оповещения,
+//! сводки) отключается, а в stdout пишется ровно одна котировка в JSON
+//! на строку — для передачи другой программе через конвейер.
+
+use crate::indicators::IndicatorValue;
+use crate::sparkline::Sparklines;
+use commons::i18n::tr;
+use commons::models::StockQuote;
+use commons::price::Price;
+use commons::utils::{fmt_price, fmt_volume, format_timestamp_ms};
+use log::{LevelFilter, info, warn};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::io::{Write, stdout};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+
+/// ANSI-последовательность зелёного цвета.
+const ANSI_GREEN: &str = "\x1b[32m";
+/// ANSI-последовательность красного цвета.
+const ANSI_RED: &str = "\x1b[31m";
+/// ANSI-последовательность сброса цвета.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Уровень подробности вывода в консоль и log-файл.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// `-q`: в консоль только ошибки, в журнал — предупреждения и ошибки.
+    Quiet,
+    /// По умолчанию: оповещения и сводки, котировки только в журнал.
+    Normal,
+    /// `-v`: дополнительно котировки в консоль.
+    Verbose,
+    /// `-vv`: дополнительно ошибки разбора датаграмм и отладочный журнал.
+    Debug,
+}
+
+impl Verbosity {
+    /// Определить уровень по флагам командной строки.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            _ => Self::Debug,
+        }
+    }
+
+    /// Уровень журналирования в log-файл.
+    pub fn log_level(self) -> LevelFilter {
+        match self {
+            Self::Quiet => LevelFilter::Warn,
+            Self::Normal | Self::Verbose => LevelFilter::Info,
+            Self::Debug => LevelFilter::Debug,
+        }
+    }
+
+    /// Выводить оповещения, сводки и подсказки.
+    pub fn shows_chrome(self) -> bool {
+        self >= Self::Normal
+    }
+
+    /// Выводить котировки.
+    pub fn shows_quotes(self) -> bool {
+        self >= Self::Verbose
+    }
+
+    /// Выводить датаграммы, которые не удалось разобрать.
+    pub fn shows_parse_errors(self) -> bool {
+        self >= Self::Debug
+    }
+}
+
+/// Вывести в stderr датаграмму, которую не удалось разобрать.
+pub fn print_parse_error(payload: &str) {
+    let payload = payload.trim_end();
+    eprintln!(
+        "{}",
+        tr(
+            format!("Некорректные данные от сервера: {payload}"),
+            format!("Malformed data from server: {payload}"),
+        )
+    );
+}
+
+/// Разрешён ли вывод для человека в консоль.
+static HUMAN_OUTPUT: AtomicBool = AtomicBool::new(true);
+
+/// Включить или отключить вывод для человека (оповещения, сводки, подсказки).
+pub fn set_human_output(enabled: bool) {
+    HUMAN_OUTPUT.store(enabled, AtomicOrdering::SeqCst);
+}
+
+/// Разрешён ли вывод для человека в консоль.
+pub fn human_output() -> bool {
+    HUMAN_OUTPUT.load(AtomicOrdering::SeqCst)
+}
+
+/// Вывести сообщение для человека, если это разрешено.
+pub fn console(message: impl Display) {
+    if human_output() {
+        println!("{}", message);
+    }
+}
+
+/// Вывод котировок с учётом предыдущих цен по каждому тикеру.
+pub struct QuotePrinter {
+    /// Дублировать котировки в консоль.
+    verbose: bool,
+    /// Подсвечивать изменение цены цветом.
+    color: bool,
+    /// Писать в stdout котировки в JSON, по одной на строку.
+    json_lines: bool,
+    /// Последняя известная цена по каждому тикеру.
+    last_prices: HashMap<String, Price>,
+    /// Интервал прореживания вывода.
+    interval: Option<Duration>,
+    /// Последние котировки по тикерам, ожидающие вывода.
+    pending: BTreeMap<String, PendingQuote>,
+    /// Время последнего вывода накопленных котировок.
+    last_flush: Instant,
+    /// Вывод в консоль приостановлен пользователем.
+    paused: bool,
+    /// Мини-графики последних цен.
+    sparklines: Option<Sparklines>,
+}
+
+/// Котировка, ожидающая вывода при прореживании.
+type PendingQuote = (StockQuote, Vec<IndicatorValue>, Option<SocketAddr>);
+
+impl QuotePrinter {
+    /// Создать экземпляр.
+    ///
+    /// ## Args
+    /// - `verbose` — если `true`, котировки дублируются в консоль
+    /// - `color` — если `false`, вывод без ANSI-цветов (для простых терминалов)
+    pub fn new(verbose: bool, color: bool) -> Self {
+        Self {
+            verbose,
+            color,
+            json_lines: false,
+            last_prices: HashMap::new(),
+            interval: None,
+            pending: BTreeMap::new(),
+            last_flush: Instant::now(),
+            paused: false,
+            sparklines: None,
+        }
+    }
+
+    /// Создать экземпляр для машинного вывода: одна котировка в JSON
+    /// на строку, без таблицы и индикаторов.
+    pub fn json_lines() -> Self {
+        Self {
+            json_lines: true,
+            ..Self::new(false, false)
+        }
+    }
+
+    /// Прореживать вывод в консоль: не чаще одного раза за `interval`
+    /// показывать последнюю котировку по каждому тикеру.
+    pub fn with_interval(mut self, interval: Option<Duration>) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Добавить к строке котировки мини-график последних `window` цен.
+    pub fn with_sparklines(mut self, window: Option<usize>) -> Self {
+        self.sparklines = window.map(Sparklines::new);
+        self
+    }
+
+    /// Отобразить котировку: запись в log-файл и, при необходимости,
+    /// в консоль (вместе со значениями индикаторов).
+    ///
+    /// `source` — сервер-источник котировки, если потоки объединяются.
+    /// В log-файл попадает каждая котировка, даже при прореживании вывода.
+    pub fn show(
+        &mut self,
+        quote: &StockQuote,
+        indicators: &[IndicatorValue],
+        source: Option<SocketAddr>,
+    ) {
+        match source {
+            Some(source) => info!("{} {}", source, quote),
+            None => info!("{}", quote),
+        }
+
+        if self.json_lines {
+            write_json_line(quote);
+            return;
+        }
+        if !self.verbose {
+            return;
+        }
+        if let Some(sparklines) = self.sparklines.as_mut() {
+            sparklines.update(quote);
+        }
+        if self.paused {
+            return;
+        }
+
+        match self.interval {
+            Some(_) => {
+                self.pending.insert(
+                    quote.ticker.clone(),
+                    (quote.clone(), indicators.to_vec(), source),
+                );
+                self.tick();
+            }
+            None => self.render(quote, indicators, source),
+        }
+    }
+
+    /// Вывести накопленные при прореживании котировки, если интервал
+    /// истёк. Вызывается и по таймеру, чтобы последняя котировка тикера
+    /// не ждала следующей.
+    pub fn tick(&mut self) {
+        if let Some(interval) = self.interval
+            && !self.pending.is_empty()
+            && self.last_flush.elapsed() >= interval
+        {
+            self.flush();
+        }
+    }
+
+    /// Приостановить или возобновить вывод котировок в консоль.
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если вывод приостановлен.
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+        self.pending.clear();
+        self.paused
+    }
+
+    /// Вывести накопленные при прореживании котировки.
+    pub fn flush(&mut self) {
+        for (quote, indicators, source) in std::mem::take(&mut self.pending).into_values() {
+            self.render(&quote, &indicators, source);
+        }
+        self.last_flush = Instant::now();
+    }
+
+    /// Вывести строку котировки в консоль.
+    fn render(
+        &mut self,
+        quote: &StockQuote,
+        indicators: &[IndicatorValue],
+        source: Option<SocketAddr>,
+    ) {
+        let mut line = self.format_line(quote);
+        if let Some(source) = source {
+            line = format!("{:<21} {}", source.to_string(), line);
+        }
+
+        for (label, value) in indicators {
+            match value {
+                Some(value) => line.push_str(&format!(" {label} {:>10}", fmt_price(*value))),
+                None => line.push_str(&format!(" {label} {:>10}", "-")),
+            }
+        }
+        if let Some(sparklines) = &self.sparklines {
+            line.push(' ');
+            line.push_str(&sparklines.line(&quote.ticker));
+        }
+        println!("{}", line);
+    }
+
+    /// Сформировать выровненную строку для консоли и запомнить цену.
+    fn format_line(&mut self, quote: &StockQuote) -> String {
+        let price = format!("{:>12}", fmt_price(quote.price.to_f64()));
+        let price = match self.last_prices.insert(quote.ticker.clone(), quote.price) {
+            Some(prev) if self.color => paint_price(price, quote.price.partial_cmp(&prev)),
+            _ => price,
+        };
+
+        format!(
+            "{:<8} {} {:>8} {:<10} {}",
+            quote.ticker,
+            price,
+            fmt_volume(quote.volume),
+            quote.transaction.to_string(),
+            format_timestamp_ms(quote.timestamp)
+        )
+    }
+}
+
+/// Записать котировку в stdout одной строкой JSON и сразу сбросить буфер.
+fn write_json_line(quote: &StockQuote) {
+    let json = match serde_json::to_string(quote) {
+        Ok(json) => json,
+        Err(err) => {
+            warn!(
+                "Ошибка преобразования котировки {} в json: {err}",
+                quote.ticker
+            );
+            return;
+        }
+    };
+
+    let mut out = stdout().lock();
+    if let Err(err) = writeln!(out, "{json}").and_then(|_| out.flush()) {
+        warn!("Ошибка записи в stdout: {err}");
+    }
+}
+
+/// Проверить и разобрать интервал вывода: `250ms`, `2s` или число
+/// миллисекунд.
+pub fn validate_interval(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, millis) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 1)
+    } else if let Some(secs) = value.strip_suffix('s') {
+        (secs, 1000)
+    } else {
+        (value, 1)
+    };
+
+    match number.trim().parse::<u64>() {
+        Ok(n) if n > 0 => Ok(Duration::from_millis(n * millis)),
+        _ => Err(format!(
+            "некорректный интервал '{value}': ожидается, например, 250ms или 1s"
+        )),
+    }
+}
+
+/// Подсветить цену в зависимости от направления её изменения.
+fn paint_price(price: String, change: Option<Ordering>) -> String {
+    match change {
+        Some(Ordering::Greater) => format!("{ANSI_GREEN}{price}{ANSI_RESET}"),
+        Some(Ordering::Less) => format!("{ANSI_RED}{price}{ANSI_RESET}"),
+        _ => price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::{QuoteBuilder, quote};
+
+    #[test]
+    fn verbosity_levels_are_ordered() {
+        assert!(!Verbosity::Quiet.shows_chrome());
+        assert!(Verbosity::Normal.shows_chrome() && !Verbosity::Normal.shows_quotes());
+        assert!(Verbosity::Verbose.shows_quotes() && !Verbosity::Verbose.shows_parse_errors());
+        assert_eq!(Verbosity::Debug.log_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn interval_validator_accepts_units() {
+        assert_eq!(validate_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(validate_interval("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(validate_interval("100"), Ok(Duration::from_millis(100)));
+        assert!(validate_interval("0ms").is_err());
+        assert!(validate_interval("fast").is_err());
+    }
+
+    #[test]
+    fn conflation_keeps_latest_quote_per_ticker() {
+        let mut printer =
+            QuotePrinter::new(true, false).with_interval(Some(Duration::from_secs(3600)));
+        printer.show(&quote("AAPL", 10.0), &[], None);
+        printer.show(&quote("AAPL", 11.0), &[], None);
+        printer.show(&quote("TSLA", 5.0), &[], None);
+
+        assert_eq!(printer.pending.len(), 2);
+        assert_eq!(printer.pending["AAPL"].0.price.to_f64(), 11.0);
+
+        printer.flush();
+        assert!(printer.pending.is_empty());
+        assert_eq!(printer.last_prices["AAPL"].to_f64(), 11.0);
+    }
+
+    #[test]
+    fn tick_flushes_pending_quote_without_new_quotes() {
+        let mut printer =
+            QuotePrinter::new(true, false).with_interval(Some(Duration::from_millis(20)));
+        printer.show(&quote("AAPL", 10.0), &[], None);
+        printer.show(&quote("AAPL", 11.0), &[], None);
+        printer.tick();
+        assert_eq!(printer.pending.len(), 1);
+
+        std::thread::sleep(Duration::from_millis(30));
+        printer.tick();
+        assert!(printer.pending.is_empty());
+        assert_eq!(printer.last_prices["AAPL"].to_f64(), 11.0);
+    }
+
+    #[test]
+    fn paused_printer_keeps_nothing_pending() {
+        let mut printer =
+            QuotePrinter::new(true, false).with_interval(Some(Duration::from_secs(3600)));
+        assert!(printer.toggle_pause());
+        printer.show(&quote("AAPL", 10.0), &[], None);
+
+        assert!(printer.pending.is_empty());
+        assert!(!printer.toggle_pause());
+    }
+
+    #[test]
+    fn format_line_is_aligned() {
+        let mut printer = QuotePrinter::new(false, true);
+        let line = printer.format_line(&QuoteBuilder::new("AAPL").price(10.5).volume(100).build());
+
+        assert_eq!(
+            line,
+            "AAPL            10.50      100 buy        1970-01-01T00:00:00.001Z"
+        );
+
+        let mut large = quote("BRK", 612345.678);
+        large.volume = 2_500_000;
+        assert!(
+            printer
+                .format_line(&large)
+                .starts_with("BRK        612,345.68     2.5M buy")
+        );
+    }
+
+    #[test]
+    fn price_change_is_colored() {
+        let mut printer = QuotePrinter::new(false, true);
+        printer.format_line(&quote("AAPL", 10.0));
+
+        let up = printer.format_line(&quote("AAPL", 11.0));
+        assert!(up.contains(ANSI_GREEN));
+
+        let down = printer.format_line(&quote("AAPL", 9.0));
+        assert!(down.contains(ANSI_RED));
+
+        let first = printer.format_line(&quote("TSLA", 5.0));
+        assert!(!first.contains('\x1b'));
+    }
+
+    #[test]
+    fn no_color_output_is_plain() {
+        let mut printer = QuotePrinter::new(false, false);
+        printer.format_line(&quote("AAPL", 10.0));
+        let line = printer.format_line(&quote("AAPL", 11.0));
+
+        assert!(!line.contains('\x1b'));
+    }
+}
@@ -0,0 +1,249 @@
+//! Экспорт полученных котировок в формате Parquet.
+//!
+//! Доступен при сборке с feature `parquet`. Котировки накапливаются
+//! в памяти и записываются группами строк (row group) — по достижении
+//! заданного числа строк или по таймеру, чтобы при длительной записи
+//! данные регулярно попадали на диск.
+
+use crate::config::{PARQUET_FLUSH_SECS, PARQUET_ROW_GROUP_ROWS};
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+use log::{info, warn};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Схема файла: по колонке на поле [`StockQuote`].
+const SCHEMA: &str = "
+    message quote {
+        REQUIRED BYTE_ARRAY ticker (UTF8);
+        REQUIRED DOUBLE price;
+        REQUIRED INT64 volume;
+        REQUIRED INT64 timestamp (TIMESTAMP(MILLIS, true));
+        REQUIRED BYTE_ARRAY transaction (UTF8);
+        REQUIRED INT64 seq (INTEGER(64, false));
+        OPTIONAL DOUBLE bid;
+        OPTIONAL DOUBLE ask;
+        OPTIONAL BYTE_ARRAY exchange (UTF8);
+        OPTIONAL BYTE_ARRAY currency (UTF8);
+    }
+";
+
+/// Запись котировок в Parquet-файл.
+pub struct ParquetSink {
+    writer: Option<SerializedFileWriter<File>>,
+    rows: Vec<StockQuote>,
+    last_flush: Instant,
+    written: usize,
+}
+
+impl ParquetSink {
+    /// Создать (перезаписать) файл.
+    pub fn create(path: &Path) -> Result<Self, QuoteError> {
+        let file = File::create(path).map_err(|err| {
+            QuoteError::value_err(format!(
+                "не удалось создать файл {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        let schema = Arc::new(parse_message_type(SCHEMA).map_err(parquet_err)?);
+        let props = Arc::new(WriterProperties::builder().build());
+        let writer = SerializedFileWriter::new(file, schema, props).map_err(parquet_err)?;
+
+        info!("Котировки записываются в Parquet: {}", path.display());
+        Ok(Self {
+            writer: Some(writer),
+            rows: Vec::new(),
+            last_flush: Instant::now(),
+            written: 0,
+        })
+    }
+
+    /// Добавить котировку. Группа строк записывается на диск, когда набрано
+    /// достаточно строк или истёк интервал сброса.
+    pub fn write(&mut self, quote: &StockQuote) {
+        self.rows.push(quote.clone());
+
+        if self.rows.len() >= PARQUET_ROW_GROUP_ROWS
+            || self.last_flush.elapsed() >= Duration::from_secs(PARQUET_FLUSH_SECS)
+        {
+            self.flush();
+        }
+    }
+
+    /// Записать накопленные котировки отдельной группой строк.
+    pub fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        if self.rows.is_empty() {
+            return;
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+
+        let rows = std::mem::take(&mut self.rows);
+        match write_row_group(writer, &rows) {
+            Ok(()) => self.written += rows.len(),
+            Err(err) => warn!("Ошибка записи в Parquet: {err}"),
+        }
+    }
+
+    /// Записать остаток и завершить файл (метаданные Parquet).
+    pub fn close(&mut self) {
+        self.flush();
+        if let Some(writer) = self.writer.take() {
+            match writer.close() {
+                Ok(_) => info!("Parquet-файл закрыт, записано котировок: {}", self.written),
+                Err(err) => warn!("Ошибка закрытия Parquet-файла: {err}"),
+            }
+        }
+    }
+}
+
+impl Drop for ParquetSink {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Записать котировки одной группой строк.
+fn write_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    rows: &[StockQuote],
+) -> Result<(), ParquetError> {
+    let tickers: Vec<ByteArray> = rows.iter().map(|q| q.ticker.as_str().into()).collect();
+    let prices: Vec<f64> = rows.iter().map(|q| q.price.to_f64()).collect();
+    let volumes: Vec<i64> = rows.iter().map(|q| q.volume as i64).collect();
+    let timestamps: Vec<i64> = rows.iter().map(|q| q.timestamp as i64).collect();
+    let transactions: Vec<ByteArray> = rows
+        .iter()
+        .map(|q| q.transaction.to_string().as_str().into())
+        .collect();
+    let seqs: Vec<i64> = rows.iter().map(|q| q.seq as i64).collect();
+    let bids = optional_column(rows, |q| q.bid);
+    let asks = optional_column(rows, |q| q.ask);
+    let exchanges = optional_column(rows, |q| q.exchange.as_deref().map(ByteArray::from));
+    let currencies = optional_column(rows, |q| q.currency.as_deref().map(ByteArray::from));
+
+    let mut row_group = writer.next_row_group()?;
+    let mut idx = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        match idx {
+            0 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&tickers, None, None)?,
+            1 => column
+                .typed::<DoubleType>()
+                .write_batch(&prices, None, None)?,
+            2 => column
+                .typed::<Int64Type>()
+                .write_batch(&volumes, None, None)?,
+            3 => column
+                .typed::<Int64Type>()
+                .write_batch(&timestamps, None, None)?,
+            4 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&transactions, None, None)?,
+            5 => column.typed::<Int64Type>().write_batch(&seqs, None, None)?,
+            6 => column
+                .typed::<DoubleType>()
+                .write_batch(&bids.0, Some(&bids.1), None)?,
+            7 => column
+                .typed::<DoubleType>()
+                .write_batch(&asks.0, Some(&asks.1), None)?,
+            8 => column.typed::<ByteArrayType>().write_batch(
+                &exchanges.0,
+                Some(&exchanges.1),
+                None,
+            )?,
+            _ => column.typed::<ByteArrayType>().write_batch(
+                &currencies.0,
+                Some(&currencies.1),
+                None,
+            )?,
+        };
+        column.close()?;
+        idx += 1;
+    }
+    row_group.close()?;
+    Ok(())
+}
+
+/// Значения необязательной колонки и уровни определения (definition
+/// levels): `1` — значение есть, `0` — `NULL`.
+fn optional_column<T>(
+    rows: &[StockQuote],
+    value: impl Fn(&StockQuote) -> Option<T>,
+) -> (Vec<T>, Vec<i16>) {
+    let mut values = Vec::with_capacity(rows.len());
+    let mut levels = Vec::with_capacity(rows.len());
+    for row in rows {
+        match value(row) {
+            Some(v) => {
+                values.push(v);
+                levels.push(1);
+            }
+            None => levels.push(0),
+        }
+    }
+    (values, levels)
+}
+
+fn parquet_err(err: ParquetError) -> QuoteError {
+    QuoteError::runtime_err(format!("ошибка Parquet: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn quotes_are_written_in_row_groups() {
+        let path = std::env::temp_dir().join("qclient_sink_test.parquet");
+        let mut sink = ParquetSink::create(&path).unwrap();
+        for timestamp in 0..3 {
+            sink.write(&StockQuote {
+                ticker: "AAPL".to_string(),
+                price: "10.5".parse().unwrap(),
+                volume: 7,
+                timestamp,
+                transaction: Transaction::Sell,
+                ..Default::default()
+            });
+        }
+        sink.flush();
+        sink.write(&StockQuote {
+            ticker: "TSLA".to_string(),
+            price: "1".parse().unwrap(),
+            volume: 1,
+            timestamp: 9,
+            transaction: Transaction::Buy,
+            seq: 4,
+            bid: Some(0.9),
+            ask: Some(1.1),
+            exchange: Some("NYSE".to_string()),
+            ..Default::default()
+        });
+        drop(sink);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(metadata.file_metadata().num_rows(), 4);
+        let last_group = metadata.row_group(1);
+        assert_eq!(last_group.num_columns(), 10);
+        let stats = last_group.column(9).statistics().unwrap();
+        assert_eq!(stats.null_count_opt(), Some(1));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+//! Конвейер обработки полученных котировок.
+//!
+//! Используется и при сетевой трансляции, и при воспроизведении записи,
+//! чтобы котировки проходили одинаковые этапы: псевдонимы тикеров,
+//! пользовательский сценарий, ретрансляцию, оповещения, расчёт индикаторов,
+//! учёт портфеля, панель лидеров и вывод.
+
+use crate::alerts::AlertEngine;
+use crate::aliases::TickerAliases;
+use crate::cli::PipelineSet;
+use crate::indicators::Indicators;
+use crate::metrics::ClientMetrics;
+use crate::movers::TopMovers;
+use crate::output::{QuotePrinter, console};
+#[cfg(feature = "parquet")]
+use crate::parquet_sink::ParquetSink;
+use crate::portfolio::Portfolio;
+use crate::relay::QuoteRelay;
+use crate::scripting::QuoteScript;
+use commons::calendar::TradingCalendar;
+use commons::models::StockQuote;
+use commons::utils::get_timestamp_ms;
+use log::info;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+/// Конвейер обработки котировок.
+pub struct QuotePipeline {
+    aliases: Option<TickerAliases>,
+    script: Option<QuoteScript>,
+    printer: QuotePrinter,
+    alerts: AlertEngine,
+    indicators: Indicators,
+    portfolio: Option<Portfolio>,
+    movers: Option<TopMovers>,
+    #[cfg(feature = "parquet")]
+    parquet: Option<ParquetSink>,
+    relay: Option<QuoteRelay>,
+    metrics: ClientMetrics,
+    started: Instant,
+}
+
+impl QuotePipeline {
+    /// Создать конвейер по параметрам командной строки.
+    ///
+    /// ## Args
+    /// - `settings` — параметры конвейера
+    pub fn new(settings: PipelineSet) -> Self {
+        Self {
+            aliases: settings.aliases,
+            script: settings.script,
+            printer: if settings.stdout_only {
+                QuotePrinter::json_lines()
+            } else {
+                QuotePrinter::new(settings.verbosity.shows_quotes(), settings.color)
+                    .with_interval(settings.display_interval)
+                    .with_sparklines(settings.sparkline)
+            },
+            alerts: AlertEngine::new(settings.alert_rules, settings.alert_actions),
+            indicators: Indicators::new(settings.sma, settings.ema),
+            portfolio: settings.portfolio.map(Portfolio::new),
+            movers: settings.movers.then(TopMovers::new),
+            #[cfg(feature = "parquet")]
+            parquet: settings.parquet,
+            metrics: ClientMetrics::new(),
+            started: Instant::now(),
+            relay: None,
+        }
+    }
+
+    /// Добавить ретрансляцию котировок.
+    pub fn with_relay(mut self, relay: Option<QuoteRelay>) -> Self {
+        self.relay = relay;
+        self
+    }
+
+    /// Учесть датаграммы, потерянные подписками (пропуски номеров
+    /// трансляции).
+    pub fn record_lost(&self, count: u64) {
+        self.metrics.record_lost(count);
+    }
+
+    /// Учесть повреждённые датаграммы подписок.
+    pub fn record_corrupted(&self, count: u64) {
+        self.metrics.record_corrupted(count);
+    }
+
+    /// Пропустить котировку через все этапы конвейера.
+    ///
+    /// Сначала тикер заменяется псевдонимом, затем выполняется сценарий,
+    /// который может отбросить или изменить котировку.
+    /// `source` — адрес сервера-источника, если потоки объединяются.
+    pub fn process(&mut self, quote: StockQuote, source: Option<SocketAddr>) {
+        self.metrics.record(&quote);
+
+        let quote = match &self.aliases {
+            Some(aliases) => aliases.apply(quote),
+            None => quote,
+        };
+        let quote = match self.script.as_mut() {
+            Some(script) => match script.on_quote(quote) {
+                Some(quote) => quote,
+                None => {
+                    self.metrics.record_filtered();
+                    return;
+                }
+            },
+            None => quote,
+        };
+
+        if let Some(relay) = &self.relay {
+            relay.forward(&quote);
+        }
+        #[cfg(feature = "parquet")]
+        if let Some(sink) = self.parquet.as_mut() {
+            sink.write(&quote);
+        }
+        self.alerts.check(&quote);
+        let indicators = self.indicators.update(&quote);
+        self.printer.show(&quote, &indicators, source);
+
+        if let Some(line) = self.portfolio.as_mut().and_then(|p| p.update(&quote)) {
+            console(line);
+        }
+
+        if let Some(movers) = self.movers.as_mut() {
+            movers.update(&quote);
+            if movers.due() {
+                console(movers.render());
+            }
+        }
+    }
+
+    /// Вывести котировки, накопленные при прореживании, если интервал
+    /// истёк ([`QuotePrinter::tick`]).
+    pub fn tick(&mut self) {
+        self.printer.tick();
+    }
+
+    /// Приостановить или возобновить вывод котировок в консоль.
+    ///
+    /// ## Returns
+    ///
+    /// `true`, если вывод приостановлен.
+    pub fn toggle_pause(&mut self) -> bool {
+        self.printer.toggle_pause()
+    }
+
+    /// Статистика сеанса: число котировок, тикеров, средняя частота,
+    /// задержка доставки за последнюю минуту и состояние торгов NYSE.
+    pub fn stats(&mut self) -> String {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            self.metrics.received() as f64 / elapsed
+        } else {
+            0.0
+        };
+        let mut stats = format!(
+            "Получено котировок: {} за {:.0} с ({:.1}/с), тикеров: {}",
+            self.metrics.received(),
+            elapsed,
+            rate,
+            self.metrics.tickers()
+        );
+        if let Some(latency) = self.metrics.latency_summary() {
+            stats.push_str(&format!("\n{latency}"));
+        }
+        stats.push_str(&format!(
+            "\nБиржа NYSE: {}",
+            TradingCalendar::nyse().state(get_timestamp_ms())
+        ));
+        stats
+    }
+
+    /// Завершить обработку: вывести отложенные котировки и итоговые сводки.
+    pub fn finish(&mut self) {
+        self.printer.flush();
+        info!("Метрики сеанса:\n{}", self.metrics.render());
+        #[cfg(feature = "parquet")]
+        if let Some(sink) = self.parquet.as_mut() {
+            sink.close();
+        }
+        if let Some(portfolio) = &self.portfolio {
+            let summary = portfolio.summary();
+            info!("Итоговый P&L портфеля:\n{}", summary);
+            console(format!("\n{}", summary));
+        }
+    }
+}
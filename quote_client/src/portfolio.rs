@@ -0,0 +1,238 @@
+//! Учёт нереализованной прибыли и убытков (P&L) по портфелю позиций.
+//!
+//! Позиции загружаются из CSV-файла со строками `тикер,количество,цена
+//! покупки`. Строка заголовка и строки, начинающиеся с `#`, пропускаются.
+
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Позиция портфеля.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// Тикер.
+    pub ticker: String,
+    /// Количество акций (отрицательное — короткая позиция).
+    pub quantity: f64,
+    /// Средняя цена покупки за единицу.
+    pub cost_basis: f64,
+}
+
+impl Position {
+    /// Нереализованный P&L позиции при заданной цене.
+    pub fn pnl(&self, price: f64) -> f64 {
+        (price - self.cost_basis) * self.quantity
+    }
+}
+
+/// Загрузить позиции из CSV-файла.
+pub fn load_positions(path: &Path) -> Result<Vec<Position>, QuoteError> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        QuoteError::value_err(format!(
+            "не удалось прочитать файл портфеля {}: {}",
+            path.display(),
+            err
+        ))
+    })?;
+
+    let positions = parse_positions(&content)?;
+    if positions.is_empty() {
+        return Err(QuoteError::value_err(format!(
+            "файл портфеля {} не содержит позиций",
+            path.display()
+        )));
+    }
+    Ok(positions)
+}
+
+/// Разобрать содержимое CSV-файла позиций.
+fn parse_positions(content: &str) -> Result<Vec<Position>, QuoteError> {
+    let mut positions = Vec::new();
+
+    for (num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(QuoteError::value_err(format!(
+                "строка {} портфеля: ожидается 3 поля, получено {}",
+                num + 1,
+                fields.len()
+            )));
+        }
+
+        let (Ok(quantity), Ok(cost_basis)) = (fields[1].parse(), fields[2].parse()) else {
+            // Заголовок допускается только первой строкой.
+            if positions.is_empty() && num == 0 {
+                continue;
+            }
+            return Err(QuoteError::value_err(format!(
+                "строка {} портфеля: некорректные числа в '{}'",
+                num + 1,
+                line
+            )));
+        };
+
+        positions.push(Position {
+            ticker: fields[0].to_uppercase(),
+            quantity,
+            cost_basis,
+        });
+    }
+
+    Ok(positions)
+}
+
+/// Портфель с последними известными ценами.
+pub struct Portfolio {
+    positions: Vec<Position>,
+    last_prices: HashMap<String, f64>,
+}
+
+impl Portfolio {
+    /// Создать портфель из позиций.
+    pub fn new(positions: Vec<Position>) -> Self {
+        Self {
+            positions,
+            last_prices: HashMap::new(),
+        }
+    }
+
+    /// Учесть котировку.
+    ///
+    /// ## Returns
+    ///
+    /// Строку с P&L по позициям тикера и общим P&L, если тикер есть
+    /// в портфеле.
+    pub fn update(&mut self, quote: &StockQuote) -> Option<String> {
+        // Тикеры позиций приведены к верхнему регистру, тикер котировки
+        // может быть псевдонимом в смешанном регистре (`--aliases`).
+        let ticker = quote.ticker.to_uppercase();
+        let pnl: f64 = self
+            .positions
+            .iter()
+            .filter(|position| position.ticker == ticker)
+            .map(|position| position.pnl(quote.price.to_f64()))
+            .reduce(|a, b| a + b)?;
+
+        self.last_prices.insert(ticker, quote.price.to_f64());
+
+        Some(format!(
+            "P&L {:<8} {:>+12.2} | total {:>+12.2}",
+            quote.ticker,
+            pnl,
+            self.total_pnl()
+        ))
+    }
+
+    /// Общий P&L по позициям с известной ценой.
+    pub fn total_pnl(&self) -> f64 {
+        self.positions
+            .iter()
+            .filter_map(|position| {
+                self.last_prices
+                    .get(&position.ticker)
+                    .map(|price| position.pnl(*price))
+            })
+            .sum()
+    }
+
+    /// Итоговая сводка по всем позициям.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "{:<8} {:>10} {:>12} {:>12} {:>12}",
+            "TICKER", "QTY", "COST", "LAST", "P&L"
+        )];
+
+        for position in &self.positions {
+            let last = self.last_prices.get(&position.ticker);
+            lines.push(format!(
+                "{:<8} {:>10} {:>12.2} {:>12} {:>12}",
+                position.ticker,
+                position.quantity,
+                position.cost_basis,
+                last.map_or("-".to_string(), |price| format!("{price:.2}")),
+                last.map_or("-".to_string(), |price| format!(
+                    "{:+.2}",
+                    position.pnl(*price)
+                )),
+            ));
+        }
+
+        lines.push(format!(
+            "{:<8} {:>49}",
+            "TOTAL",
+            format!("{:+.2}", self.total_pnl())
+        ));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::testing::quote;
+
+    #[test]
+    fn parse_positions_skips_header_and_comments() {
+        let content = "ticker,quantity,cost\n# comment\naapl, 10, 150.5\nTSLA,-2,200\n";
+        let positions = parse_positions(content).unwrap();
+
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].ticker, "AAPL");
+        assert_eq!(positions[1].quantity, -2.0);
+    }
+
+    #[test]
+    fn positions_match_mixed_case_aliases() {
+        let positions = parse_positions("Yandex,2,3000\n").unwrap();
+        let mut portfolio = Portfolio::new(positions);
+
+        let line = portfolio.update(&quote("Yandex", 3100.0)).unwrap();
+        assert!(line.contains("+200.00"));
+        assert_eq!(portfolio.total_pnl(), 200.0);
+    }
+
+    #[test]
+    fn parse_positions_rejects_bad_rows() {
+        assert!(parse_positions("AAPL,10\n").is_err());
+        assert!(parse_positions("AAPL,10,1\nTSLA,x,2\n").is_err());
+    }
+
+    #[test]
+    fn portfolio_tracks_pnl() {
+        let mut portfolio = Portfolio::new(vec![
+            Position {
+                ticker: "AAPL".into(),
+                quantity: 10.0,
+                cost_basis: 100.0,
+            },
+            Position {
+                ticker: "TSLA".into(),
+                quantity: -2.0,
+                cost_basis: 50.0,
+            },
+        ]);
+
+        assert!(portfolio.update(&quote("MSFT", 1.0)).is_none());
+
+        let line = portfolio.update(&quote("AAPL", 110.0)).unwrap();
+        assert!(line.contains("+100.00"));
+
+        portfolio.update(&quote("TSLA", 60.0));
+        assert_eq!(portfolio.total_pnl(), 80.0);
+        assert!(
+            portfolio
+                .summary()
+                .lines()
+                .last()
+                .unwrap()
+                .ends_with("+80.00")
+        );
+    }
+}
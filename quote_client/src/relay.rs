@@ -0,0 +1,151 @@
+//! Ретрансляция полученных котировок на другой UDP-адрес.
+//!
+//! Позволяет строить простые цепочки раздачи и связывать сегменты сети:
+//! клиент пересылает каждую корректную котировку дальше, в исходном JSON
+//! или в текстовом формате `QuoteDisplay`.
+
+use clap::ValueEnum;
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+use log::{info, warn};
+use std::net::{SocketAddr, UdpSocket};
+use url::Url;
+
+/// Формат ретранслируемых котировок.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum RelayFormat {
+    /// JSON, как при трансляции с сервера.
+    Json,
+    /// Текстовая строка с полями, разделёнными `|`.
+    Text,
+}
+
+/// Ретранслятор котировок.
+pub struct QuoteRelay {
+    socket: UdpSocket,
+    target: SocketAddr,
+    format: RelayFormat,
+}
+
+impl QuoteRelay {
+    /// Открыть сокет для ретрансляции на адрес `url` (схема `udp`).
+    pub fn new(url: &Url, format: RelayFormat) -> Result<Self, QuoteError> {
+        let target = url
+            .socket_addrs(|| None)
+            .ok()
+            .and_then(|addrs| addrs.first().cloned())
+            .ok_or_else(|| {
+                QuoteError::value_err(format!("некорректный адрес ретрансляции: {url}"))
+            })?;
+
+        let bind_addr = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_addr).map_err(|err| {
+            QuoteError::server_err(format!("не удалось открыть сокет ретрансляции: {err}"))
+        })?;
+
+        info!("Ретрансляция котировок на {target} ({format:?})");
+        Ok(Self {
+            socket,
+            target,
+            format,
+        })
+    }
+
+    /// Переслать котировку. Ошибки отправки не прерывают приём.
+    pub fn forward(&self, quote: &StockQuote) {
+        let payload = match encode(quote, self.format) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("Котировка {} не ретранслирована: {err}", quote.ticker);
+                return;
+            }
+        };
+
+        if let Err(err) = self.socket.send_to(payload.as_bytes(), self.target) {
+            warn!("Ошибка ретрансляции на {}: {err}", self.target);
+        }
+    }
+}
+
+/// Закодировать котировку в выбранный формат.
+fn encode(quote: &StockQuote, format: RelayFormat) -> Result<String, QuoteError> {
+    match format {
+        RelayFormat::Json => serde_json::to_string(quote)
+            .map_err(|err| QuoteError::value_err(format!("ошибка сериализации: {err}"))),
+        RelayFormat::Text => Ok(quote.to_string()),
+    }
+}
+
+/// Валидатор для ключа `--relay`: допускается только схема `udp`.
+pub fn validate_relay_url(s: &str) -> Result<Url, String> {
+    let url = Url::parse(s).map_err(|err| format!("invalid relay url '{s}': {err}"))?;
+    if url.scheme() != "udp" {
+        return Err(format!("relay supports only udp:// urls: {s}"));
+    }
+    if url.host().is_none() || url.port().is_none() {
+        return Err(format!("relay url must contain host and port: {s}"));
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+    use std::time::Duration;
+
+    fn sample_quote() -> StockQuote {
+        StockQuote {
+            ticker: "AAPL".to_string(),
+            price: "10.5".parse().unwrap(),
+            volume: 3,
+            timestamp: 1,
+            transaction: Transaction::Sell,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn relay_url_validator() {
+        assert!(validate_relay_url("udp://127.0.0.1:40000").is_ok());
+        assert!(validate_relay_url("http://127.0.0.1:40000").is_err());
+        assert!(validate_relay_url("udp://127.0.0.1").is_err());
+    }
+
+    #[test]
+    fn forward_reencodes_as_text() {
+        let target = UdpSocket::bind("127.0.0.1:0").unwrap();
+        target
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let url = Url::parse(&format!("udp://{}", target.local_addr().unwrap())).unwrap();
+
+        let relay = QuoteRelay::new(&url, RelayFormat::Text).unwrap();
+        relay.forward(&sample_quote());
+
+        let mut buf = [0u8; 256];
+        let (size, _) = target.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..size], b"AAPL|10.5|3|1|sell|||||0");
+    }
+
+    #[test]
+    fn forward_keeps_json() {
+        let target = UdpSocket::bind("127.0.0.1:0").unwrap();
+        target
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let url = Url::parse(&format!("udp://{}", target.local_addr().unwrap())).unwrap();
+
+        let relay = QuoteRelay::new(&url, RelayFormat::Json).unwrap();
+        relay.forward(&sample_quote());
+
+        let mut buf = [0u8; 256];
+        let (size, _) = target.recv_from(&mut buf).unwrap();
+        let quote: StockQuote = serde_json::from_slice(&buf[..size]).unwrap();
+        assert_eq!(quote.ticker, "AAPL");
+    }
+}
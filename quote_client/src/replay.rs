@@ -0,0 +1,115 @@
+//! Локальное воспроизведение записанной трансляции котировок.
+//!
+//! Файл записи содержит по одной котировке в JSON на строку — в том же
+//! формате, в котором сервер отправляет данные по UDP. Котировки проходят
+//! через тот же конвейер отбора, оповещений и вывода, что и при сетевой трансляции.
+
+use crate::cli::ReplaySet;
+use crate::pipeline::QuotePipeline;
+use commons::errors::{QuoteError, ResultExt};
+use commons::models::StockQuote;
+use commons::shutdown::ShutdownToken;
+use log::{info, warn};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    time::Duration,
+};
+
+/// Воспроизвести запись до её окончания или получения сигнала остановки.
+///
+/// Паузы между котировками рассчитываются по их временным меткам
+/// и сокращаются в `speed` раз.
+pub fn replay(settings: ReplaySet, shutdown: ShutdownToken) -> Result<(), QuoteError> {
+    let file = File::open(&settings.record).with_context(|| {
+        format!(
+            "не удалось открыть файл записи {}",
+            settings.record.display()
+        )
+    })?;
+
+    info!("Воспроизведение записи: {}", settings.record.display());
+
+    let mut pipeline = QuotePipeline::new(settings.pipeline);
+    let mut last_timestamp: Option<u64> = None;
+
+    for (num, line) in BufReader::new(file).lines().enumerate() {
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        let line = line.with_context(|| format!("ошибка чтения строки {}", num + 1))?;
+        let Some(quote) = parse_record_line(&line, num + 1) else {
+            continue;
+        };
+
+        if !settings.tickers.is_empty() && !settings.tickers.contains(&quote.ticker) {
+            continue;
+        }
+
+        if let Some(prev) = last_timestamp
+            && shutdown.cancelled_wait(replay_delay(prev, quote.timestamp, settings.speed))
+        {
+            break;
+        }
+        last_timestamp = Some(quote.timestamp);
+
+        pipeline.process(quote, None);
+    }
+
+    pipeline.finish();
+    info!("Воспроизведение записи завершено");
+    Ok(())
+}
+
+/// Разобрать строку записи. Пустые и некорректные строки пропускаются.
+pub fn parse_record_line(line: &str, num: usize) -> Option<StockQuote> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    match serde_json::from_str::<StockQuote>(line) {
+        Ok(quote) => Some(quote),
+        Err(err) => {
+            warn!("Строка {num} записи пропущена: {err}");
+            None
+        }
+    }
+}
+
+/// Пауза между двумя котировками с учётом скорости воспроизведения.
+fn replay_delay(prev: u64, next: u64, speed: f64) -> Duration {
+    let secs = next.saturating_sub(prev) as f64 / 1000.0;
+    Duration::from_secs_f64(secs / speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record_line_reads_json() {
+        let line = r#"{"ticker":"AAPL","price":10.5,"volume":3,"timestamp":1,"transaction":"Buy"}"#;
+        let quote = parse_record_line(line, 1).unwrap();
+
+        assert_eq!(quote.ticker, "AAPL");
+        assert_eq!(quote.volume, 3);
+    }
+
+    #[test]
+    fn parse_record_line_skips_garbage() {
+        assert!(parse_record_line("", 1).is_none());
+        assert!(parse_record_line("AAPL|10.5", 2).is_none());
+    }
+
+    #[test]
+    fn replay_delay_respects_speed() {
+        assert_eq!(replay_delay(10_000, 12_000, 1.0), Duration::from_secs(2));
+        assert_eq!(
+            replay_delay(10_000, 12_000, 4.0),
+            Duration::from_millis(500)
+        );
+        assert_eq!(replay_delay(12_000, 10_000, 1.0), Duration::ZERO);
+    }
+}
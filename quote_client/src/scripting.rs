@@ -0,0 +1,285 @@
+//! Пользовательские сценарии обработки котировок на языке Rhai.
+//!
+//! Сценарий может объявить функцию `on_quote(q)`, которая вызывается для
+//! каждой котировки. Котировка передаётся как объект с полями `ticker`,
+//! `price`, `volume`, `timestamp`, `transaction`, `seq` (только чтение),
+//! а также `bid`, `ask`, `exchange` и `currency` (`()`, если не заданы).
+//! Результат функции:
+//! - `false` — котировка отбрасывается;
+//! - объект — котировка заменяется изменённой (отсутствующие поля берутся
+//!   из исходной);
+//! - иное значение — котировка проходит без изменений.
+//!
+//! Помимо встроенного `print`, сценарию доступны `alert(msg)` (оповещение
+//! в консоль и log-файл) и `log(msg)` (запись в log-файл). `print`
+//! и `debug` пишут в log-файл, `print` — ещё и в stderr при выводе для
+//! человека: stdout занят котировками (`--stdout-only`).
+//!
+//! ## Пример
+//!
+//! ```text
+//! fn on_quote(q) {
+//!     if q.ticker == "AAPL" && q.price > 150.0 {
+//!         alert(`AAPL дорожает: ${q.price}`);
+//!     }
+//!     q.price > 10.0
+//! }
+//! ```
+
+use crate::output::{console, human_output};
+use commons::errors::QuoteError;
+use commons::models::StockQuote;
+use commons::price::Price;
+use log::{debug, info, warn};
+use rhai::{AST, CallFnOptions, Dynamic, Engine, Map, Scope};
+use std::path::Path;
+
+/// Имя функции-обработчика котировки в сценарии.
+const ON_QUOTE_FN: &str = "on_quote";
+
+/// Загруженный сценарий.
+pub struct QuoteScript {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    has_on_quote: bool,
+}
+
+impl QuoteScript {
+    /// Загрузить и выполнить сценарий из файла.
+    pub fn load(path: &Path) -> Result<Self, QuoteError> {
+        let source = std::fs::read_to_string(path).map_err(|err| {
+            QuoteError::value_err(format!(
+                "не удалось прочитать сценарий {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        let script = Self::from_source(&source)?;
+        info!("Загружен сценарий: {}", path.display());
+        Ok(script)
+    }
+
+    /// Скомпилировать сценарий из строки и выполнить его верхний уровень.
+    fn from_source(source: &str) -> Result<Self, QuoteError> {
+        let mut engine = Engine::new();
+        engine.register_fn("alert", |msg: &str| {
+            let message = format!("Оповещение (сценарий): {msg}");
+            info!("{}", message);
+            console(&message);
+        });
+        engine.register_fn("log", |msg: &str| info!("Сценарий: {msg}"));
+        engine.on_print(|text| {
+            info!("Сценарий: {text}");
+            if human_output() {
+                eprintln!("{text}");
+            }
+        });
+        engine.on_debug(|text, source, pos| {
+            debug!("Сценарий {} {pos}: {text}", source.unwrap_or_default());
+        });
+
+        let ast = engine
+            .compile(source)
+            .map_err(|err| QuoteError::value_err(format!("ошибка компиляции сценария: {err}")))?;
+
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|err| QuoteError::value_err(format!("ошибка выполнения сценария: {err}")))?;
+
+        let has_on_quote = ast
+            .iter_functions()
+            .any(|f| f.name == ON_QUOTE_FN && f.params.len() == 1);
+        if !has_on_quote {
+            warn!("В сценарии нет функции {ON_QUOTE_FN}(q)");
+        }
+
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            has_on_quote,
+        })
+    }
+
+    /// Передать котировку сценарию.
+    ///
+    /// ## Returns
+    ///
+    /// Котировку (возможно, изменённую) или `None`, если сценарий её
+    /// отбросил. При ошибке сценария котировка проходит без изменений.
+    pub fn on_quote(&mut self, quote: StockQuote) -> Option<StockQuote> {
+        if !self.has_on_quote {
+            return Some(quote);
+        }
+
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            ON_QUOTE_FN,
+            (quote_to_map(&quote),),
+        );
+
+        match result {
+            Ok(value) => match value.as_bool() {
+                Ok(false) => None,
+                Ok(true) => Some(quote),
+                Err(_) => match value.try_cast::<Map>() {
+                    Some(map) => Some(map_to_quote(&map, quote)),
+                    None => Some(quote),
+                },
+            },
+            Err(err) => {
+                warn!("Ошибка сценария для {}: {err}", quote.ticker);
+                Some(quote)
+            }
+        }
+    }
+}
+
+/// Представить котировку объектом Rhai.
+fn quote_to_map(quote: &StockQuote) -> Map {
+    let mut map = Map::new();
+    map.insert("ticker".into(), quote.ticker.clone().into());
+    map.insert("price".into(), quote.price.to_f64().into());
+    map.insert("volume".into(), (quote.volume as i64).into());
+    map.insert("timestamp".into(), (quote.timestamp as i64).into());
+    map.insert("transaction".into(), quote.transaction.to_string().into());
+    map.insert("seq".into(), (quote.seq as i64).into());
+    map.insert("bid".into(), quote.bid.map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert("ask".into(), quote.ask.map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert(
+        "exchange".into(),
+        quote.exchange.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert(
+        "currency".into(),
+        quote.currency.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map
+}
+
+/// Собрать котировку из объекта Rhai, дополняя отсутствующие
+/// и некорректные поля значениями из `base`.
+///
+/// Необязательные поля (`bid`, `ask`, `exchange`, `currency`) сбрасываются,
+/// если сценарий присвоил им `()`. Номер `seq` сценарий изменить не может.
+fn map_to_quote(map: &Map, base: StockQuote) -> StockQuote {
+    let get = |key: &str| map.get(key).cloned();
+    let as_float = |v: Dynamic| {
+        v.as_float()
+            .ok()
+            .or_else(|| v.as_int().ok().map(|i| i as f64))
+    };
+    let optional = |key: &str, base: Option<Dynamic>| match get(key) {
+        Some(v) if v.is_unit() => None,
+        Some(v) => Some(v),
+        None => base,
+    };
+
+    StockQuote {
+        ticker: get("ticker")
+            .and_then(|v| v.into_string().ok())
+            .unwrap_or(base.ticker),
+        price: get("price")
+            .and_then(as_float)
+            .and_then(Price::from_f64)
+            .unwrap_or(base.price),
+        volume: get("volume")
+            .and_then(|v| v.as_int().ok())
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(base.volume),
+        timestamp: get("timestamp")
+            .and_then(|v| v.as_int().ok())
+            .and_then(|v| u64::try_from(v).ok())
+            .unwrap_or(base.timestamp),
+        transaction: get("transaction")
+            .and_then(|v| v.into_string().ok())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(base.transaction),
+        seq: base.seq,
+        bid: optional("bid", base.bid.map(Dynamic::from)).and_then(as_float),
+        ask: optional("ask", base.ask.map(Dynamic::from)).and_then(as_float),
+        exchange: optional("exchange", base.exchange.map(Dynamic::from))
+            .and_then(|v| v.into_string().ok()),
+        currency: optional("currency", base.currency.map(Dynamic::from))
+            .and_then(|v| v.into_string().ok()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+    use commons::testing::{QuoteBuilder, quote};
+
+    #[test]
+    fn script_filters_quotes() {
+        let mut script = QuoteScript::from_source("fn on_quote(q) { q.price > 10.0 }").unwrap();
+
+        assert!(script.on_quote(quote("AAPL", 5.0)).is_none());
+        assert!(script.on_quote(quote("AAPL", 15.0)).is_some());
+    }
+
+    #[test]
+    fn script_transforms_quotes() {
+        let source = r#"
+            fn on_quote(q) {
+                q.ticker = "X:" + q.ticker;
+                q.price = q.price * 2.0;
+                q.transaction = "sell";
+                q
+            }
+        "#;
+        let mut script = QuoteScript::from_source(source).unwrap();
+        let base = QuoteBuilder::new("AAPL").price(5.0).volume(10).build();
+        let result = script.on_quote(base).unwrap();
+
+        assert_eq!(result.ticker, "X:AAPL");
+        assert_eq!(result.price.to_f64(), 10.0);
+        assert_eq!(result.volume, 10);
+        assert!(matches!(result.transaction, Transaction::Sell));
+    }
+
+    #[test]
+    fn script_reads_and_sets_optional_fields() {
+        let source = r#"
+            fn on_quote(q) {
+                if q.bid == () { q.bid = q.price - 0.5; }
+                q.exchange = "MOEX";
+                q.currency = ();
+                q
+            }
+        "#;
+        let mut script = QuoteScript::from_source(source).unwrap();
+        let mut base = quote("AAPL", 5.0);
+        base.currency = Some("USD".to_string());
+        let result = script.on_quote(base).unwrap();
+
+        assert_eq!(result.bid, Some(4.5));
+        assert_eq!(result.ask, None);
+        assert_eq!(result.exchange.as_deref(), Some("MOEX"));
+        assert_eq!(result.currency, None);
+    }
+
+    #[test]
+    fn script_without_handler_passes_quotes() {
+        let mut script = QuoteScript::from_source("let x = 1;").unwrap();
+        assert!(script.on_quote(quote("AAPL", 5.0)).is_some());
+    }
+
+    #[test]
+    fn script_errors_do_not_drop_quotes() {
+        let mut script = QuoteScript::from_source("fn on_quote(q) { q.missing.field }").unwrap();
+        assert!(script.on_quote(quote("AAPL", 5.0)).is_some());
+    }
+
+    #[test]
+    fn invalid_script_is_rejected() {
+        assert!(QuoteScript::from_source("fn on_quote(q) {").is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Мини-графики (sparkline) последних цен по тикерам.
+
+use commons::history::HistoryBuffer;
+use commons::models::StockQuote;
+
+/// Символы уровней графика, от минимального к максимальному.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// История последних цен по каждому тикеру.
+pub struct Sparklines {
+    history: HistoryBuffer<f64>,
+}
+
+impl Sparklines {
+    /// Создать экземпляр.
+    ///
+    /// ## Args
+    /// - `window` — количество последних цен на графике (больше нуля)
+    pub fn new(window: usize) -> Self {
+        Self {
+            history: HistoryBuffer::new(window),
+        }
+    }
+
+    /// Учесть котировку.
+    pub fn update(&mut self, quote: &StockQuote) {
+        self.history
+            .push(quote.ticker.clone(), quote.price.to_f64());
+    }
+
+    /// График по тикеру (пустая строка, если цен ещё нет).
+    pub fn line(&self, ticker: &str) -> String {
+        render(self.history.snapshot(ticker).into_iter())
+    }
+}
+
+/// Построить график по последовательности цен.
+///
+/// Цены масштабируются между минимумом и максимумом; при равных ценах
+/// выводится средний уровень.
+pub fn render(prices: impl Iterator<Item = f64> + Clone) -> String {
+    let (min, max) = prices
+        .clone()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            (min.min(p), max.max(p))
+        });
+    let range = max - min;
+
+    prices
+        .map(|price| {
+            let level = if range > 0.0 {
+                ((price - min) / range * (BARS.len() - 1) as f64).round() as usize
+            } else {
+                BARS.len() / 2
+            };
+            BARS[level.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+    use commons::price::Price;
+
+    #[test]
+    fn render_scales_between_min_and_max() {
+        assert_eq!(render([1.0, 2.0, 3.0].into_iter()), "▁▅█");
+        assert_eq!(render([5.0, 5.0].into_iter()), "▅▅");
+        assert_eq!(render(std::iter::empty()), "");
+    }
+
+    #[test]
+    fn history_is_limited_by_window() {
+        let mut sparklines = Sparklines::new(3);
+        for price in [1.0, 2.0, 3.0, 4.0] {
+            sparklines.update(&StockQuote {
+                ticker: "AAPL".to_string(),
+                price: Price::try_from(price).unwrap(),
+                volume: 1,
+                timestamp: 1,
+                transaction: Transaction::Buy,
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(sparklines.line("AAPL").chars().count(), 3);
+        assert_eq!(sparklines.line("TSLA"), "");
+    }
+}
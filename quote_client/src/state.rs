@@ -0,0 +1,312 @@
+//! Сохранение и восстановление состояния подписки.
+//!
+//! Во время трансляции клиент периодически записывает в файл состояния
+//! активные команды, UDP-порты, отметку последней полученной котировки
+//! и её сквозной номер (`seq`) для каждого сервера. После сбоя или
+//! перезагрузки `qclient resume` восстанавливает ту же подписку и сообщает,
+//! сколько трансляции было пропущено, а по первой котировке от сервера —
+//! сколько номеров `seq` он выдал за время перерыва.
+
+use crate::cli::Subscription;
+use crate::config::STATE_SAVE_INTERVAL_SECS;
+use crate::output::console;
+use commons::config::MIN_QUOTE_TIMESTAMP_MS;
+use commons::errors::{QuoteError, ResultExt};
+use commons::models::StockQuote;
+use commons::seq::{seq_after, seq_gap};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Сохранённая подписка на один сервер.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionState {
+    /// Адрес TCP-сервера.
+    pub server_addr: SocketAddr,
+    /// UDP-порт приёма котировок.
+    pub udp_port: u16,
+    /// Команда, отправленная серверу.
+    pub command: String,
+    /// Сквозной номер (`seq`) последней котировки от сервера; в файлах
+    /// старого формата отсутствует.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seq: Option<u64>,
+}
+
+/// Состояние сеанса клиента.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Активные подписки.
+    pub subscriptions: Vec<SubscriptionState>,
+    /// Временная метка последней полученной котировки, миллисекунды
+    /// от начала эпохи UNIX.
+    pub last_timestamp: Option<u64>,
+    /// Количество котировок, полученных за все сеансы.
+    pub received: u64,
+}
+
+impl SessionState {
+    /// Создать состояние для новых подписок.
+    pub fn new(subscriptions: &[Subscription]) -> Self {
+        Self {
+            subscriptions: subscriptions
+                .iter()
+                .map(|s| SubscriptionState {
+                    server_addr: s.server_addr,
+                    udp_port: s.udp_url.port().unwrap_or_default(),
+                    command: s.command.clone(),
+                    last_seq: None,
+                })
+                .collect(),
+            last_timestamp: None,
+            received: 0,
+        }
+    }
+
+    /// Загрузить состояние из файла.
+    ///
+    /// Метка `last_timestamp` в секундах (файлы версий до перехода
+    /// на миллисекунды) переводится в миллисекунды; метка, не похожая
+    /// ни на секунды, ни на миллисекунды, отбрасывается.
+    pub fn load(path: &Path) -> Result<Self, QuoteError> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("не удалось прочитать состояние {}", path.display()))?;
+
+        let mut state: Self = serde_json::from_str(&content)
+            .with_context(|| format!("некорректный файл состояния {}", path.display()))?;
+        if state.subscriptions.is_empty() {
+            return Err(QuoteError::value_err("файл состояния не содержит подписок"));
+        }
+        state.last_timestamp = state.last_timestamp.and_then(|last| {
+            let millis = legacy_timestamp_ms(last);
+            if millis != Some(last) {
+                warn!("Метка последней котировки {last} в файле состояния переведена: {millis:?}");
+            }
+            millis
+        });
+        Ok(state)
+    }
+
+    /// Сохранить состояние в файл.
+    ///
+    /// Запись выполняется через временный файл, чтобы сбой во время записи
+    /// не оставил повреждённое состояние.
+    pub fn save(&self, path: &Path) -> Result<(), QuoteError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("не удалось создать каталог {}", dir.display()))?;
+        }
+
+        let json = serde_json::to_string_pretty(self).context("ошибка сериализации состояния")?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, path))
+            .with_context(|| format!("не удалось сохранить состояние {}", path.display()))
+    }
+
+    /// Сколько секунд трансляции пропущено к моменту `now`; `now`, как
+    /// и сохранённая метка, — миллисекунды от начала эпохи UNIX.
+    pub fn missed_secs(&self, now: u64) -> Option<u64> {
+        self.last_timestamp
+            .map(|last| now.saturating_sub(last) / 1000)
+    }
+}
+
+/// Метка `timestamp` в миллисекундах: метки не раньше
+/// [`MIN_QUOTE_TIMESTAMP_MS`] уже в миллисекундах, метки не раньше той же
+/// даты в секундах умножаются на 1000, остальные некорректны (`None`).
+fn legacy_timestamp_ms(timestamp: u64) -> Option<u64> {
+    if timestamp >= MIN_QUOTE_TIMESTAMP_MS {
+        Some(timestamp)
+    } else if timestamp >= MIN_QUOTE_TIMESTAMP_MS / 1000 {
+        Some(timestamp * 1000)
+    } else {
+        None
+    }
+}
+
+/// Периодическая запись состояния во время трансляции.
+///
+/// Последнее состояние записывается и при уничтожении экземпляра.
+pub struct StateRecorder {
+    state: SessionState,
+    path: PathBuf,
+    last_save: Instant,
+    /// Номера `seq`, сохранённые прошлым сеансом, по серверам, от которых
+    /// в этом сеансе ещё не было котировок.
+    resumed_seq: HashMap<SocketAddr, u64>,
+}
+
+impl StateRecorder {
+    /// Создать экземпляр и сразу сохранить исходное состояние.
+    pub fn new(state: SessionState, path: PathBuf) -> Self {
+        let resumed_seq = state
+            .subscriptions
+            .iter()
+            .filter_map(|saved| Some((saved.server_addr, saved.last_seq?)))
+            .collect();
+        let mut recorder = Self {
+            state,
+            path,
+            last_save: Instant::now(),
+            resumed_seq,
+        };
+        recorder.save();
+        info!(
+            "Состояние подписки сохраняется в {}",
+            recorder.path.display()
+        );
+        recorder
+    }
+
+    /// Учесть котировку, полученную от сервера `source`.
+    ///
+    /// По первой котировке от сервера, номер которого сохранил прошлый
+    /// сеанс, сообщается, сколько номеров `seq` пропущено с тех пор.
+    pub fn record(&mut self, quote: &StockQuote, source: SocketAddr) {
+        self.state.received += 1;
+        self.state.last_timestamp = Some(
+            self.state
+                .last_timestamp
+                .map_or(quote.timestamp, |last| last.max(quote.timestamp)),
+        );
+        if quote.seq > 0 {
+            self.record_seq(quote.seq, source);
+        }
+
+        if self.last_save.elapsed() >= Duration::from_secs(STATE_SAVE_INTERVAL_SECS) {
+            self.save();
+        }
+    }
+
+    /// Запомнить номер `seq` котировки от сервера `source`; опоздавшие
+    /// котировки номер не сдвигают.
+    fn record_seq(&mut self, seq: u64, source: SocketAddr) {
+        if let Some(last) = self.resumed_seq.remove(&source) {
+            let message = format!(
+                "Сервер {source}: с прошлого сеанса пропущено котировок: {}",
+                seq_gap(last, seq)
+            );
+            info!("{message}");
+            console(&message);
+        }
+        if let Some(saved) = self
+            .state
+            .subscriptions
+            .iter_mut()
+            .find(|saved| saved.server_addr == source)
+            && saved.last_seq.is_none_or(|last| seq_after(seq, last))
+        {
+            saved.last_seq = Some(seq);
+        }
+    }
+
+    fn save(&mut self) {
+        if let Err(err) = self.state.save(&self.path) {
+            warn!("{err}");
+        }
+        self.last_save = Instant::now();
+    }
+}
+
+impl Drop for StateRecorder {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+
+    fn state() -> SessionState {
+        SessionState {
+            subscriptions: vec![SubscriptionState {
+                server_addr: "127.0.0.1:8888".parse().unwrap(),
+                udp_port: 34254,
+                command: "STREAM udp://127.0.0.1:34254 ALL".to_string(),
+                last_seq: Some(40),
+            }],
+            last_timestamp: Some(1_700_000_100_000),
+            received: 5,
+        }
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let path = std::env::temp_dir().join("qclient_state_test.json");
+        state().save(&path).unwrap();
+
+        assert_eq!(SessionState::load(&path).unwrap(), state());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn state_without_last_seq_is_accepted() {
+        let json = r#"{"subscriptions":[{"server_addr":"127.0.0.1:8888","udp_port":34254,"command":"STREAM udp://127.0.0.1:34254 ALL"}],"last_timestamp":null,"received":0}"#;
+        let state: SessionState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.subscriptions[0].last_seq, None);
+    }
+
+    #[test]
+    fn legacy_second_timestamps_are_converted() {
+        let path = std::env::temp_dir().join("qclient_state_legacy_test.json");
+        for (saved, loaded) in [
+            (Some(1_700_000_100), Some(1_700_000_100_000)),
+            (Some(1_700_000_100_000), Some(1_700_000_100_000)),
+            (Some(5), None),
+        ] {
+            SessionState {
+                last_timestamp: saved,
+                ..state()
+            }
+            .save(&path)
+            .unwrap();
+            assert_eq!(SessionState::load(&path).unwrap().last_timestamp, loaded);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missed_secs_counts_from_last_quote() {
+        assert_eq!(state().missed_secs(1_700_000_130_500), Some(30));
+        assert_eq!(state().missed_secs(1_700_000_050_000), Some(0));
+    }
+
+    #[test]
+    fn recorder_tracks_latest_timestamp() {
+        let path = std::env::temp_dir().join("qclient_recorder_test.json");
+        let source = state().subscriptions[0].server_addr;
+        let mut recorder = StateRecorder::new(state(), path.clone());
+        assert_eq!(recorder.resumed_seq.get(&source), Some(&40));
+        for (timestamp, seq) in [(1_700_000_120_000, 45), (1_700_000_110_000, 44)] {
+            recorder.record(
+                &StockQuote {
+                    ticker: "AAPL".to_string(),
+                    price: "1".parse().unwrap(),
+                    volume: 1,
+                    timestamp,
+                    transaction: Transaction::Buy,
+                    seq,
+                    ..Default::default()
+                },
+                source,
+            );
+        }
+        assert!(recorder.resumed_seq.is_empty());
+        drop(recorder);
+
+        let saved = SessionState::load(&path).unwrap();
+        assert_eq!(saved.last_timestamp, Some(1_700_000_120_000));
+        assert_eq!(saved.subscriptions[0].last_seq, Some(45));
+        assert_eq!(saved.received, 7);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! Список наблюдения с горячей перезагрузкой.
+//!
+//! Изменения файла тикеров (`stream --file ... --watch`) отслеживаются
+//! через [`commons::watch`]; если содержимое изменилось, действующие
+//! подписки заменяются на том же соединении (`CANCEL` и новая команда
+//! `STREAM`).
+
+use crate::cli::stream_command;
+use crate::config::WATCHLIST_DEBOUNCE_MS;
+use crate::output::console;
+use commons::i18n::tr;
+use commons::shutdown::ShutdownToken;
+use commons::tickers::{TickerInfo, TickerRegistry};
+use commons::watch::FileWatcher;
+use log::{info, warn};
+use quote_client_lib::StreamControl;
+use std::{
+    path::PathBuf,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Отслеживаемый файл тикеров.
+pub struct Watchlist {
+    path: PathBuf,
+    registry: TickerRegistry,
+}
+
+impl Watchlist {
+    /// Начать отслеживание файла с уже применённым списком тикеров.
+    pub fn new(path: PathBuf, tickers: Vec<String>) -> Self {
+        Self {
+            path,
+            registry: TickerRegistry::new(tickers.into_iter().map(TickerInfo::new)),
+        }
+    }
+
+    /// Перечитать файл.
+    ///
+    /// ## Returns
+    ///
+    /// Новый список тикеров, если содержимое файла отличается от
+    /// применённого (пустой список — все тикеры), иначе `None`.
+    pub fn reload(&mut self) -> Option<Vec<String>> {
+        match self.registry.reload_from(&self.path) {
+            Ok(true) => Some(self.registry.tickers()),
+            Ok(false) => None,
+            Err(err) => {
+                warn!("Список наблюдения не перечитан: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Запустить фоновое отслеживание списка и обновление подписок.
+pub fn spawn_watcher(
+    mut watchlist: Watchlist,
+    controls: Vec<StreamControl>,
+    shutdown: ShutdownToken,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let path = watchlist.path.clone();
+        let watcher = FileWatcher::spawn(
+            &path,
+            Duration::from_millis(WATCHLIST_DEBOUNCE_MS),
+            move || apply(&mut watchlist, &controls),
+        );
+        match watcher {
+            Ok(_watcher) => {
+                info!("Отслеживается список наблюдения: {}", path.display());
+                while !shutdown.cancelled_wait(Duration::from_secs(1)) {}
+            }
+            Err(err) => warn!("Список наблюдения не отслеживается: {err}"),
+        }
+    })
+}
+
+/// Перечитать список и обновить подписки, если он изменился.
+fn apply(watchlist: &mut Watchlist, controls: &[StreamControl]) {
+    let Some(tickers) = watchlist.reload() else {
+        return;
+    };
+
+    let shown = if tickers.is_empty() {
+        "ALL".to_string()
+    } else {
+        tickers.join(",")
+    };
+    for control in controls {
+        let command = stream_command(control.udp_url(), &tickers);
+        match control.resubscribe(&command) {
+            Ok(()) => info!("Подписка обновлена: {command}"),
+            Err(err) => warn!("Подписка не обновлена ({}): {err}", control.udp_url()),
+        }
+    }
+    console(tr(
+        format!("Список наблюдения обновлён: {shown}"),
+        format!("Watchlist updated: {shown}"),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reload_reports_only_changed_content() {
+        let path = std::env::temp_dir().join("watchlist_test.txt");
+        fs::write(&path, "AAPL\n").unwrap();
+        let mut watchlist = Watchlist::new(path.clone(), vec!["AAPL".to_string()]);
+        assert!(watchlist.reload().is_none());
+
+        fs::write(&path, "AAPL\nTSLA\n").unwrap();
+        assert_eq!(
+            watchlist.reload(),
+            Some(vec!["AAPL".to_string(), "TSLA".to_string()])
+        );
+        assert!(watchlist.reload().is_none());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watchlist.reload().is_none());
+    }
+}
@@ -0,0 +1,38 @@
+//! Параметры взаимодействия с Quote Server.
+
+use commons::netdefaults;
+
+/// Строка-терминатор, которой сервер завершает приветствие.
+pub const WELCOME_TERMINATOR: &str = "READY";
+
+/// Интервал отправки ping-сообщений (секунды).
+pub const PING_INTERVAL_SECS: u64 = 2;
+
+/// Timeout на операцию чтения из UDP-сокета (миллисекунды).
+pub const UDP_READ_TIMEOUT_MS: u64 = 500;
+
+/// Размер буфера приёма UDP-датаграмм (байты).
+pub const UDP_BUFFER_SIZE: usize = 1024;
+
+/// Timeout на подключение и ответ сервера при отмене подписки
+/// во время завершения работы (миллисекунды).
+pub const SHUTDOWN_TIMEOUT_MS: u64 = 1000;
+
+/// Коды ошибок сервера, при которых команду имеет смысл повторить.
+pub const TRANSIENT_ERROR_CODES: [&str; 2] = [netdefaults::RATE_LIMITED, netdefaults::SERVER_FULL];
+
+/// Максимальное количество повторов команды подписки.
+pub const SUBSCRIBE_RETRY_ATTEMPTS: u32 = 4;
+
+/// Пауза перед первым повтором команды подписки; каждая следующая
+/// удваивается с небольшим случайным отклонением (миллисекунды).
+pub const SUBSCRIBE_RETRY_DELAY_MS: u64 = 500;
+
+/// Окно буфера переупорядочивания датаграмм: сколько котировок можно
+/// удерживать в ожидании пропущенного номера.
+pub const REORDER_WINDOW: usize = 64;
+
+/// Наибольшее ожидание пропущенного номера датаграммы, после которого
+/// удержанные котировки выдаются, а номер считается потерянным
+/// (миллисекунды).
+pub const REORDER_WAIT_MS: u64 = 200;
@@ -0,0 +1,39 @@
+//! Quote Client Library. Встраиваемый подписчик на котировки Quote Server.
+//!
+//! Библиотека берёт на себя рукопожатие с TCP-сервером, отправку команды,
+//! приём котировок по UDP и фоновую отправку `Ping`. Полученные котировки
+//! доступны как итератор ([`QuoteStream`]) или через обратный вызов
+//! ([`UdpClient::recv_loop`]). В итераторе повторы и нарушение порядка
+//! датаграмм устраняются буфером [`ReorderBuffer`] по номеру датаграммы
+//! в трансляции.
+//!
+//! ## Пример
+//!
+//! ```no_run
+//! use quote_client_lib::QuoteStream;
+//! use std::sync::{Arc, atomic::AtomicBool};
+//! use url::Url;
+//!
+//! let server = "127.0.0.1:8888".parse().unwrap();
+//! let udp_url = Url::parse("udp://127.0.0.1:34254").unwrap();
+//! let command = format!("STREAM {udp_url} AAPL,TSLA");
+//! let stop = Arc::new(AtomicBool::new(false));
+//!
+//! let quotes = QuoteStream::subscribe(server, &udp_url, &command, stop).unwrap();
+//! for quote in quotes.take(10) {
+//!     println!("{} — {}", quote.ticker, quote.price);
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod config;
+pub mod reorder;
+pub mod stream;
+pub mod tcp;
+pub mod udp;
+
+pub use reorder::ReorderBuffer;
+pub use stream::{QuoteStream, StreamControl};
+pub use tcp::{Handshake, ServerConnection};
+pub use udp::UdpClient;
@@ -0,0 +1,180 @@
+//! Буфер устранения дублей и восстановления порядка по номеру
+//! последовательности.
+//!
+//! При ретрансляции и повторной отправке датаграмм котировки могут приходить
+//! повторно или не по порядку. [`ReorderBuffer`] удерживает небольшое окно
+//! ожидающих элементов и выдаёт их ровно один раз в порядке возрастания номера.
+
+use std::collections::BTreeMap;
+
+/// Буфер переупорядочивания с ограниченным окном.
+///
+/// ## Пример
+///
+/// ```
+/// use quote_client_lib::ReorderBuffer;
+///
+/// let mut buffer = ReorderBuffer::new(4);
+/// assert_eq!(buffer.push(1, "a"), vec!["a"]);
+/// assert!(buffer.push(3, "c").is_empty());
+/// assert_eq!(buffer.push(2, "b"), vec!["b", "c"]);
+/// assert!(buffer.push(2, "b").is_empty()); // дубль отброшен
+/// ```
+#[derive(Debug)]
+pub struct ReorderBuffer<T> {
+    window: usize,
+    next: Option<u64>,
+    pending: BTreeMap<u64, T>,
+    duplicates: u64,
+    skipped: u64,
+}
+
+impl<T> ReorderBuffer<T> {
+    /// Создать буфер.
+    ///
+    /// ## Args
+    /// - `window` — сколько элементов можно удерживать в ожидании пропущенного
+    ///   номера; при переполнении пропуск считается потерянным
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            next: None,
+            pending: BTreeMap::new(),
+            duplicates: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Принять элемент с номером `seq`.
+    ///
+    /// ## Returns
+    /// Элементы, готовые к выдаче, в порядке возрастания номера (возможно,
+    /// пустой вектор). Повторы и опоздавшие элементы отбрасываются.
+    pub fn push(&mut self, seq: u64, item: T) -> Vec<T> {
+        let next = *self.next.get_or_insert(seq);
+        if seq < next || self.pending.contains_key(&seq) {
+            self.duplicates += 1;
+            return Vec::new();
+        }
+        self.pending.insert(seq, item);
+
+        let mut ready = self.drain_in_order();
+        while self.pending.len() > self.window {
+            self.skip_gap();
+            ready.extend(self.drain_in_order());
+        }
+        ready
+    }
+
+    /// Выдать все ожидающие элементы в порядке номеров, не дожидаясь
+    /// пропущенных.
+    pub fn flush(&mut self) -> Vec<T> {
+        let mut ready = Vec::with_capacity(self.pending.len());
+        while !self.pending.is_empty() {
+            self.skip_gap();
+            ready.extend(self.drain_in_order());
+        }
+        ready
+    }
+
+    /// Начать новую последовательность номеров с `first` (например, после
+    /// повторной подписки): выдать ожидающие элементы, как
+    /// [`ReorderBuffer::flush`], и ждать номер `first`. Счётчики сохраняются.
+    pub fn restart(&mut self, first: u64) -> Vec<T> {
+        let ready = self.flush();
+        self.next = Some(first);
+        ready
+    }
+
+    /// Количество элементов, ожидающих пропущенного номера.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Количество отброшенных повторов и опоздавших элементов.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Количество номеров, так и не полученных до переполнения окна.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Выдать элементы, идущие подряд начиная с ожидаемого номера.
+    fn drain_in_order(&mut self) -> Vec<T> {
+        let mut ready = Vec::new();
+        while let Some(next) = self.next {
+            match self.pending.remove(&next) {
+                Some(item) => {
+                    ready.push(item);
+                    self.next = Some(next + 1);
+                }
+                None => break,
+            }
+        }
+        ready
+    }
+
+    /// Перейти к наименьшему ожидающему номеру, считая пропуск потерянным.
+    fn skip_gap(&mut self) {
+        if let (Some(next), Some(&first)) = (self.next, self.pending.keys().next()) {
+            self.skipped += first.saturating_sub(next);
+            self.next = Some(first);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivers_in_order_exactly_once() {
+        let mut buffer = ReorderBuffer::new(8);
+        let mut out = Vec::new();
+        for seq in [10, 12, 11, 11, 13, 10] {
+            out.extend(buffer.push(seq, seq));
+        }
+
+        assert_eq!(out, vec![10, 11, 12, 13]);
+        assert_eq!(buffer.duplicates(), 2);
+        assert_eq!(buffer.skipped(), 0);
+    }
+
+    #[test]
+    fn window_overflow_skips_lost_number() {
+        let mut buffer = ReorderBuffer::new(2);
+        assert_eq!(buffer.push(1, 1), vec![1]);
+        assert!(buffer.push(3, 3).is_empty());
+        assert!(buffer.push(4, 4).is_empty());
+        assert_eq!(buffer.push(5, 5), vec![3, 4, 5]);
+
+        assert_eq!(buffer.skipped(), 1);
+        assert!(buffer.push(2, 2).is_empty());
+    }
+
+    #[test]
+    fn restart_accepts_new_numbering() {
+        let mut buffer = ReorderBuffer::new(8);
+        buffer.push(7, 7);
+        buffer.push(9, 9);
+
+        assert_eq!(buffer.restart(1), vec![9]);
+        assert_eq!(buffer.pending(), 0);
+        assert!(buffer.push(2, 2).is_empty());
+        assert_eq!(buffer.push(1, 1), vec![1, 2]);
+        assert_eq!(buffer.skipped(), 1);
+    }
+
+    #[test]
+    fn flush_returns_pending_sorted() {
+        let mut buffer = ReorderBuffer::new(8);
+        buffer.push(1, 1);
+        buffer.push(5, 5);
+        buffer.push(3, 3);
+
+        assert_eq!(buffer.flush(), vec![3, 5]);
+        assert_eq!(buffer.skipped(), 2);
+    }
+}
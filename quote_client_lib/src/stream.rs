@@ -0,0 +1,684 @@
+//! Высокоуровневая подписка на поток котировок.
+
+use crate::config::{
+    REORDER_WAIT_MS, REORDER_WINDOW, SHUTDOWN_TIMEOUT_MS, SUBSCRIBE_RETRY_ATTEMPTS,
+    SUBSCRIBE_RETRY_DELAY_MS, TRANSIENT_ERROR_CODES,
+};
+use crate::reorder::ReorderBuffer;
+use crate::tcp::{Handshake, ServerConnection};
+use crate::udp::UdpClient;
+use commons::errors::{ErrorKind, QuoteError};
+use commons::i18n::tr;
+use commons::models::{StockQuote, StreamQuote};
+use commons::retry::{RetryPolicy, retry_with_backoff_if};
+use log::{info, warn};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Подписка на котировки Quote Server.
+///
+/// Реализует [`Iterator`]: каждый вызов `next` блокируется до получения
+/// очередной котировки и возвращает `None` после установки флага остановки
+/// или при ошибке сокета. Датаграммы проходят через [`ReorderBuffer`]
+/// по номеру в трансляции (`stream_seq`): повторы отбрасываются, а
+/// нарушенный порядок восстанавливается в пределах [`REORDER_WINDOW`]
+/// котировок и [`REORDER_WAIT_MS`] ожидания; не дождавшиеся номера
+/// учитываются как потерянные датаграммы ([`StreamControl::lost`]).
+/// Датаграммы без номера выдаются сразу. TCP-соединение с сервером удерживается, пока жив
+/// экземпляр; при уничтожении экземпляра действующая подписка отменяется
+/// командой `CANCEL`.
+pub struct QuoteStream {
+    connection: Arc<Mutex<ServerConnection>>,
+    udp_url: Url,
+    active: Arc<AtomicBool>,
+    udp: UdpClient,
+    stop: Arc<AtomicBool>,
+    ping_handle: Option<JoinHandle<()>>,
+    reorder: ReorderBuffer<StockQuote>,
+    /// Адрес трансляции, чьи номера сейчас упорядочиваются.
+    source: Option<SocketAddr>,
+    /// Начало ожидания пропущенного номера.
+    waiting_since: Option<Instant>,
+    ready: VecDeque<StockQuote>,
+    lost: Arc<AtomicU64>,
+    duplicates: Arc<AtomicU64>,
+}
+
+impl QuoteStream {
+    /// Подключиться к серверу, отправить команду и начать приём котировок.
+    ///
+    /// Команды, отклонённые с временной ошибкой (см.
+    /// [`TRANSIENT_ERROR_CODES`]), повторяются с нарастающей паузой.
+    ///
+    /// ## Args
+    ///
+    /// - `server_addr` — адрес TCP-сервера
+    /// - `udp_url` — UDP-адрес для приёма данных (должен совпадать с адресом
+    ///   в команде)
+    /// - `command` — команда серверу, например `STREAM udp://... ALL`
+    /// - `stop` — флаг остановки приёма и фонового `Ping`
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ServerError`] при сетевых ошибках
+    /// и [`QuoteError::CommandError`], если сервер отклонил команду.
+    pub fn subscribe(
+        server_addr: SocketAddr,
+        udp_url: &Url,
+        command: &str,
+        stop: Arc<AtomicBool>,
+    ) -> Result<Self, QuoteError> {
+        Self::subscribe_with_retry(server_addr, udp_url, command, stop, |_, _, _| {})
+    }
+
+    /// То же, что [`QuoteStream::subscribe`], но перед каждым повтором
+    /// команды вызывается `on_retry` с номером попытки, паузой до неё
+    /// и ответом сервера — например, чтобы сообщить пользователю о ходе
+    /// подписки.
+    pub fn subscribe_with_retry(
+        server_addr: SocketAddr,
+        udp_url: &Url,
+        command: &str,
+        stop: Arc<AtomicBool>,
+        mut on_retry: impl FnMut(u32, Duration, &str),
+    ) -> Result<Self, QuoteError> {
+        let policy = RetryPolicy::new(
+            SUBSCRIBE_RETRY_ATTEMPTS + 1,
+            Duration::from_millis(SUBSCRIBE_RETRY_DELAY_MS),
+        );
+        let connection = retry_with_backoff_if(
+            &policy,
+            |_| {
+                let mut connection =
+                    match ServerConnection::open(server_addr).map_err(Subscribe::Failed)? {
+                        Handshake::Rejected(response) if is_transient(&response) => {
+                            return Err(Subscribe::Rejected(response));
+                        }
+                        handshake => handshake.ready().map_err(Subscribe::Failed)?,
+                    };
+                let response = connection
+                    .send_command(command)
+                    .map_err(Subscribe::Failed)?;
+                if is_transient(&response) {
+                    return Err(Subscribe::Rejected(response));
+                }
+                expect_ok(response).map_err(Subscribe::Failed)?;
+                Ok(connection)
+            },
+            |err, attempt, delay| match err {
+                Subscribe::Rejected(response) if !stop.load(Ordering::SeqCst) => {
+                    warn!(
+                        "Сервер {server_addr} временно отклонил команду: {response}, \
+                         повтор {attempt}/{SUBSCRIBE_RETRY_ATTEMPTS} через {delay:?}"
+                    );
+                    on_retry(attempt, delay, response);
+                    true
+                }
+                _ => false,
+            },
+        )
+        .map_err(|err| match err {
+            Subscribe::Failed(err) => err,
+            Subscribe::Rejected(response) => rejected_err(&response),
+        })?;
+
+        let udp = UdpClient::bind_url(udp_url).map_err(|err| {
+            QuoteError::server_err(tr(
+                format!("не удалось открыть UDP-сокет {udp_url}: {err}"),
+                format!("failed to open UDP socket {udp_url}: {err}"),
+            ))
+        })?;
+        let ping_handle = udp.spawn_ping(stop.clone()).map_err(|err| {
+            QuoteError::server_err(tr(
+                format!("не удалось клонировать UDP-сокет для {udp_url}: {err}"),
+                format!("failed to clone UDP socket for {udp_url}: {err}"),
+            ))
+        })?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            udp_url: udp_url.clone(),
+            active: Arc::new(AtomicBool::new(true)),
+            udp,
+            stop,
+            ping_handle: Some(ping_handle),
+            reorder: ReorderBuffer::new(REORDER_WINDOW),
+            source: None,
+            waiting_since: None,
+            ready: VecDeque::new(),
+            lost: Arc::new(AtomicU64::new(0)),
+            duplicates: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Флаг остановки подписки.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Пульт управления подпиской, который можно передать в другой поток,
+    /// пока текущий поток читает котировки.
+    pub fn control(&self) -> StreamControl {
+        StreamControl {
+            connection: self.connection.clone(),
+            udp_url: self.udp_url.clone(),
+            active: self.active.clone(),
+            rejected: self.udp.rejected_counter(),
+            corrupted: self.udp.corrupted_counter(),
+            lost: self.lost.clone(),
+            duplicates: self.duplicates.clone(),
+        }
+    }
+
+    /// Установить обработчик датаграмм, которые не удалось разобрать
+    /// ([`UdpClient::set_parse_error_hook`]).
+    pub fn set_parse_error_hook(&mut self, hook: fn(&str)) {
+        self.udp.set_parse_error_hook(hook);
+    }
+
+    /// UDP-клиент подписки — для приёма данных в обход итератора, например
+    /// без десериализации ([`UdpClient::recv_raw`]).
+    pub fn udp(&self) -> &UdpClient {
+        &self.udp
+    }
+
+    /// Отменить подписку при завершении работы, дождавшись ответа сервера
+    /// не дольше [`SHUTDOWN_TIMEOUT_MS`]. Если соединение уже разорвано,
+    /// для отмены устанавливается новое.
+    fn cancel_on_shutdown(&self) {
+        if !self.active.load(Ordering::SeqCst) {
+            return;
+        }
+        let timeout = Duration::from_millis(SHUTDOWN_TIMEOUT_MS);
+        let control = self.control();
+
+        let result = match control.lock() {
+            Ok(mut connection) => connection.set_read_timeout(Some(timeout)),
+            Err(err) => Err(err),
+        }
+        .and_then(|_| control.cancel());
+
+        let result = match result {
+            Err(err) if matches!(err.kind(), ErrorKind::Server | ErrorKind::Timeout) => {
+                warn!(
+                    "Отмена подписки {}: {err}, повторное подключение",
+                    self.udp_url
+                );
+                let server_addr = control.lock().map(|connection| connection.server_addr());
+                server_addr
+                    .and_then(|addr| ServerConnection::connect_timeout(addr, timeout))
+                    .and_then(|mut connection| {
+                        expect_ok(connection.send_command(&format!("CANCEL {}", self.udp_url))?)
+                    })
+            }
+            other => other,
+        };
+
+        match result {
+            Ok(()) => info!("Подписка {} отменена при завершении работы", self.udp_url),
+            Err(err) => warn!("Не удалось отменить подписку {}: {err}", self.udp_url),
+        }
+    }
+}
+
+/// Управление действующей подпиской через её TCP-соединение.
+#[derive(Clone)]
+pub struct StreamControl {
+    connection: Arc<Mutex<ServerConnection>>,
+    udp_url: Url,
+    active: Arc<AtomicBool>,
+    rejected: Arc<AtomicU64>,
+    corrupted: Arc<AtomicU64>,
+    lost: Arc<AtomicU64>,
+    duplicates: Arc<AtomicU64>,
+}
+
+impl StreamControl {
+    /// UDP-адрес, на который сервер отправляет котировки.
+    pub fn udp_url(&self) -> &Url {
+        &self.udp_url
+    }
+
+    /// Количество котировок подписки, отклонённых проверкой
+    /// ([`UdpClient::rejected`]).
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Количество повреждённых датаграмм подписки
+    /// ([`UdpClient::corrupted`]).
+    pub fn corrupted(&self) -> u64 {
+        self.corrupted.load(Ordering::Relaxed)
+    }
+
+    /// Количество датаграмм подписки, потерянных по пути: номеров
+    /// `stream_seq`, которые так и не пришли ([`ReorderBuffer::skipped`]).
+    pub fn lost(&self) -> u64 {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Количество отброшенных повторных и опоздавших датаграмм
+    /// ([`ReorderBuffer::duplicates`]).
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Заменить подписку: отменить текущую (`CANCEL`) и отправить новую
+    /// команду, не разрывая соединение и не закрывая UDP-сокет.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::CommandError`], если сервер отклонил одну
+    /// из команд, и [`QuoteError::ServerError`] при сетевых ошибках.
+    pub fn resubscribe(&self, command: &str) -> Result<(), QuoteError> {
+        let mut connection = self.lock()?;
+        expect_ok(connection.send_command(&format!("CANCEL {}", self.udp_url))?)?;
+        self.active.store(false, Ordering::SeqCst);
+        expect_ok(connection.send_command(command)?)?;
+        self.active.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Отменить подписку (`CANCEL`), не разрывая соединение.
+    pub fn cancel(&self) -> Result<(), QuoteError> {
+        let mut connection = self.lock()?;
+        expect_ok(connection.send_command(&format!("CANCEL {}", self.udp_url))?)?;
+        self.active.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, ServerConnection>, QuoteError> {
+        self.connection
+            .lock()
+            .map_err(|_| QuoteError::runtime_err("соединение с сервером недоступно"))
+    }
+}
+
+/// Неудачная попытка подписки.
+enum Subscribe {
+    /// Сервер временно отклонил команду: её можно повторить.
+    Rejected(String),
+    /// Ошибка, при которой повтор бессмысленен.
+    Failed(QuoteError),
+}
+
+/// Код ошибки из ответа сервера `ERROR|<код>|<сообщение>`.
+fn error_code(response: &str) -> Option<&str> {
+    let rest = response.strip_prefix("ERROR|")?;
+    rest.split('|').next().map(str::trim)
+}
+
+/// Ответ сервера — временная ошибка, после которой команду можно повторить.
+fn is_transient(response: &str) -> bool {
+    error_code(response).is_some_and(|code| TRANSIENT_ERROR_CODES.contains(&code))
+}
+
+/// Проверить, что сервер принял команду.
+///
+/// Ответ `ERROR|<код>|...` означает отказ в команде, любой другой ответ —
+/// нарушение протокола.
+fn expect_ok(response: String) -> Result<(), QuoteError> {
+    if response.starts_with("OK") {
+        Ok(())
+    } else if response.starts_with("ERROR") {
+        Err(rejected_err(&response))
+    } else {
+        Err(QuoteError::protocol_err(tr(
+            format!("неожиданный ответ сервера: {response}"),
+            format!("unexpected server response: {response}"),
+        )))
+    }
+}
+
+/// Отказ сервера в команде с его ответом `response`.
+fn rejected_err(response: &str) -> QuoteError {
+    QuoteError::command_err(tr(
+        format!("сервер отклонил команду: {response}"),
+        format!("server rejected command: {response}"),
+    ))
+}
+
+impl QuoteStream {
+    /// Пропустить датаграмму через буфер переупорядочивания.
+    ///
+    /// Датаграмма с нового адреса — новая трансляция (в том числе первая
+    /// или после [`StreamControl::resubscribe`]) с номерами от 1: ожидающие
+    /// котировки прежней выдаются, и нумерация начинается заново.
+    fn reorder(&mut self, source: SocketAddr, datagram: StreamQuote) {
+        if datagram.stream_seq == 0 {
+            self.ready.push_back(datagram.quote);
+            return;
+        }
+        if self.source != Some(source) {
+            self.source = Some(source);
+            let ready = self.reorder.restart(1);
+            self.ready.extend(ready);
+        }
+        let ready = self.reorder.push(datagram.stream_seq, datagram.quote);
+        self.ready.extend(ready);
+        self.sync_reorder();
+    }
+
+    /// Выдать котировки, ждущие пропущенного номера дольше
+    /// [`REORDER_WAIT_MS`].
+    fn flush_stale(&mut self) {
+        if self
+            .waiting_since
+            .is_some_and(|since| since.elapsed() >= Duration::from_millis(REORDER_WAIT_MS))
+        {
+            let ready = self.reorder.flush();
+            self.ready.extend(ready);
+            self.sync_reorder();
+        }
+    }
+
+    /// Обновить счётчики подписки и начало ожидания по состоянию буфера.
+    fn sync_reorder(&mut self) {
+        self.lost.store(self.reorder.skipped(), Ordering::Relaxed);
+        self.duplicates
+            .store(self.reorder.duplicates(), Ordering::Relaxed);
+        self.waiting_since = match self.reorder.pending() {
+            0 => None,
+            _ => self.waiting_since.or_else(|| Some(Instant::now())),
+        };
+    }
+}
+
+impl Iterator for QuoteStream {
+    type Item = StockQuote;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.stop.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            if let Some(quote) = self.ready.pop_front() {
+                return Some(quote);
+            }
+
+            match self.udp.recv_stream_quote() {
+                Ok(Some((source, datagram))) => self.reorder(source, datagram),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!("Приём котировок прерван: {err}");
+                    return None;
+                }
+            }
+            self.flush_stale();
+        }
+    }
+}
+
+impl Drop for QuoteStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.ping_handle.take() {
+            let _ = handle.join();
+        }
+        self.cancel_on_shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, UdpSocket};
+    use std::thread;
+
+    /// Поднять фиктивный сервер, отвечающий `response` на первую команду.
+    fn fake_server(response: &'static str) -> (SocketAddr, thread::JoinHandle<Vec<String>>) {
+        fake_server_with(vec![response])
+    }
+
+    /// Поднять фиктивный сервер, отвечающий по порядку на команды
+    /// и возвращающий полученные команды.
+    fn fake_server_with(
+        responses: Vec<&'static str>,
+    ) -> (SocketAddr, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"READY\n").unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut commands = Vec::new();
+            for response in responses {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                commands.push(line.trim_end().to_string());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            commands
+        });
+
+        (addr, handle)
+    }
+
+    #[test]
+    fn subscribe_yields_quotes() {
+        let (addr, server) = fake_server("OK|stream started\n");
+
+        let udp_port = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let udp_url = Url::parse(&format!("udp://127.0.0.1:{udp_port}")).unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut quotes =
+            QuoteStream::subscribe(addr, &udp_url, "STREAM ALL", stop.clone()).unwrap();
+        server.join().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let json = r#"{"ticker":"TSLA","price":1.0,"volume":1,"timestamp":1700000000000,"transaction":"Sell"}"#;
+        sender
+            .send_to(json.as_bytes(), ("127.0.0.1", udp_port))
+            .unwrap();
+
+        let quote = quotes.next().unwrap();
+        assert_eq!(quote.ticker, "TSLA");
+
+        stop.store(true, Ordering::SeqCst);
+        assert!(quotes.next().is_none());
+    }
+
+    /// Подписаться у фиктивного сервера на UDP-порт, свободный в момент
+    /// вызова.
+    fn subscribe_fake() -> (QuoteStream, u16, Arc<AtomicBool>) {
+        let (addr, server) = fake_server("OK|stream started\n");
+        let udp_port = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let udp_url = Url::parse(&format!("udp://127.0.0.1:{udp_port}")).unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let quotes = QuoteStream::subscribe(addr, &udp_url, "STREAM ALL", stop.clone()).unwrap();
+        server.join().unwrap();
+        (quotes, udp_port, stop)
+    }
+
+    /// Отправить датаграммы `(stream_seq, seq)` на порт `port`.
+    fn send_datagrams(sender: &UdpSocket, port: u16, datagrams: &[(u64, u64)]) {
+        for (stream_seq, seq) in datagrams {
+            let json = format!(
+                r#"{{"stream_seq":{stream_seq},"ticker":"TSLA","price":1.0,"volume":1,"timestamp":1700000000000,"transaction":"Sell","seq":{seq}}}"#
+            );
+            sender
+                .send_to(json.as_bytes(), ("127.0.0.1", port))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn duplicates_and_reordering_are_resolved() {
+        let (mut quotes, udp_port, stop) = subscribe_fake();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        send_datagrams(
+            &sender,
+            udp_port,
+            &[(1, 10), (3, 30), (2, 20), (2, 20), (4, 40), (1, 10)],
+        );
+
+        let seqs: Vec<u64> = quotes.by_ref().take(4).map(|quote| quote.seq).collect();
+        assert_eq!(seqs, vec![10, 20, 30, 40]);
+
+        // Новая трансляция (другой адрес) нумерует датаграммы заново.
+        let resubscribed = UdpSocket::bind("127.0.0.1:0").unwrap();
+        send_datagrams(&resubscribed, udp_port, &[(2, 60), (1, 50)]);
+        let seqs: Vec<u64> = quotes.by_ref().take(2).map(|quote| quote.seq).collect();
+        assert_eq!(seqs, vec![50, 60]);
+
+        let control = quotes.control();
+        assert_eq!((control.duplicates(), control.lost()), (2, 0));
+        stop.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn stream_seq_gaps_are_counted_as_lost() {
+        let (mut quotes, udp_port, stop) = subscribe_fake();
+
+        // Сквозные номера `seq` с пропусками (отфильтрованные тикеры)
+        // потерями не считаются, пропуск `stream_seq` 3 — считается.
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        send_datagrams(&sender, udp_port, &[(1, 10), (2, 15), (4, 30)]);
+
+        let seqs: Vec<u64> = quotes.by_ref().take(3).map(|quote| quote.seq).collect();
+        assert_eq!(seqs, vec![10, 15, 30]);
+        assert_eq!(quotes.control().lost(), 1);
+        stop.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn subscribe_fails_when_rejected() {
+        let (addr, server) = fake_server("ERROR|command|invalid command\n");
+        let udp_url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let result = QuoteStream::subscribe(addr, &udp_url, "BOGUS", stop);
+        server.join().unwrap();
+
+        assert!(matches!(result, Err(QuoteError::CommandError(_))));
+    }
+
+    #[test]
+    fn resubscribe_sends_cancel_and_new_command() {
+        let (addr, server) = fake_server_with(vec!["OK\n", "OK|canceled\n", "OK\n"]);
+        let udp_url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let quotes = QuoteStream::subscribe(addr, &udp_url, "STREAM ALL", stop).unwrap();
+        quotes.control().resubscribe("STREAM AAPL").unwrap();
+        let commands = server.join().unwrap();
+
+        assert_eq!(
+            commands,
+            vec!["STREAM ALL", "CANCEL udp://127.0.0.1:0", "STREAM AAPL"]
+        );
+    }
+
+    #[test]
+    fn drop_cancels_active_subscription() {
+        let (addr, server) = fake_server_with(vec!["OK\n", "OK|canceled\n"]);
+        let udp_url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let quotes = QuoteStream::subscribe(addr, &udp_url, "STREAM ALL", stop).unwrap();
+        drop(quotes);
+        let commands = server.join().unwrap();
+
+        assert_eq!(commands, vec!["STREAM ALL", "CANCEL udp://127.0.0.1:0"]);
+    }
+
+    #[test]
+    fn transient_rejection_is_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for response in ["ERROR|rate_limited\n", "OK\n"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream.write_all(b"READY\n").unwrap();
+                let mut line = String::new();
+                BufReader::new(stream.try_clone().unwrap())
+                    .read_line(&mut line)
+                    .unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        let udp_url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut retries = Vec::new();
+        let quotes =
+            QuoteStream::subscribe_with_retry(addr, &udp_url, "STREAM ALL", stop, |n, _, resp| {
+                retries.push((n, resp.to_string()))
+            });
+        server.join().unwrap();
+
+        assert!(quotes.is_ok());
+        assert_eq!(retries, vec![(1, "ERROR|rate_limited".to_string())]);
+    }
+
+    #[test]
+    fn connection_rejected_by_full_server_is_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"ERROR|server_full|busy\n").unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"READY\n").unwrap();
+            let mut line = String::new();
+            BufReader::new(stream.try_clone().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+            stream.write_all(b"OK\n").unwrap();
+        });
+        let udp_url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut retries = Vec::new();
+        let quotes =
+            QuoteStream::subscribe_with_retry(addr, &udp_url, "STREAM ALL", stop, |n, _, resp| {
+                retries.push((n, resp.to_string()))
+            });
+        server.join().unwrap();
+
+        assert!(quotes.is_ok());
+        assert_eq!(retries, vec![(1, "ERROR|server_full|busy".to_string())]);
+    }
+
+    #[test]
+    fn unexpected_response_is_protocol_error() {
+        let err = expect_ok("HELLO".to_string()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Protocol);
+        let err = expect_ok("ERROR|command|bad".to_string()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Command);
+    }
+
+    #[test]
+    fn transient_codes_are_classified() {
+        assert!(is_transient("ERROR|server_full"));
+        assert!(is_transient("ERROR|rate_limited|too many commands"));
+        assert!(!is_transient("ERROR|command|invalid command"));
+        assert_eq!(error_code("ERROR|value|bad port"), Some("value"));
+        assert!(!is_transient("OK"));
+    }
+}
@@ -0,0 +1,281 @@
+//! TCP-взаимодействие с сервером: рукопожатие и отправка команд.
+
+use crate::config::WELCOME_TERMINATOR;
+use commons::errors::{ErrorKind, QuoteError};
+use commons::i18n::tr;
+use commons::traits::ReadExt;
+use log::info;
+use std::{
+    io::{BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// Итог подключения к серверу.
+pub enum Handshake {
+    /// Сервер прислал приветствие и готов принимать команды.
+    Ready(ServerConnection),
+    /// Сервер отказал в подключении ответом `ERROR|<код>|...` вместо
+    /// приветствия, например `ERROR|server_full|...`.
+    Rejected(String),
+}
+
+/// Установленное TCP-соединение с Quote Server.
+pub struct ServerConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    server_addr: SocketAddr,
+}
+
+impl ServerConnection {
+    /// Подключиться к серверу и пропустить приветствие вплоть до строки
+    /// [`WELCOME_TERMINATOR`].
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::CommandError`], если сервер отказал
+    /// в подключении (см. [`ServerConnection::open`]).
+    pub fn connect(server_addr: SocketAddr) -> Result<Self, QuoteError> {
+        Self::open(server_addr)?.ready()
+    }
+
+    /// Подключиться к серверу; отказ сервера вместо приветствия
+    /// возвращается как [`Handshake::Rejected`].
+    pub fn open(server_addr: SocketAddr) -> Result<Handshake, QuoteError> {
+        let stream = TcpStream::connect(server_addr).map_err(|err| {
+            QuoteError::server_err(tr(
+                format!("ошибка подключения к {server_addr}: {err}"),
+                format!("failed to connect to {server_addr}: {err}"),
+            ))
+        })?;
+        Self::handshake(stream, server_addr)
+    }
+
+    /// Подключиться к серверу, ограничив временем `timeout` как установку
+    /// соединения, так и ожидание каждого ответа.
+    pub fn connect_timeout(server_addr: SocketAddr, timeout: Duration) -> Result<Self, QuoteError> {
+        let stream = TcpStream::connect_timeout(&server_addr, timeout).map_err(|err| {
+            QuoteError::server_err(tr(
+                format!("ошибка подключения к {server_addr}: {err}"),
+                format!("failed to connect to {server_addr}: {err}"),
+            ))
+        })?;
+        let mut connection = Self::handshake(stream, server_addr)?.ready()?;
+        connection.set_read_timeout(Some(timeout))?;
+        Ok(connection)
+    }
+
+    /// Подготовить соединение и пропустить приветствие.
+    fn handshake(stream: TcpStream, server_addr: SocketAddr) -> Result<Handshake, QuoteError> {
+        let reader = BufReader::new(stream.try_clone().map_err(|err| {
+            QuoteError::server_err(format!("ошибка клонирования TCP-потока: {err}"))
+        })?);
+
+        let mut connection = Self {
+            reader,
+            writer: stream,
+            server_addr,
+        };
+
+        info!("Установлено соединение с сервером: {}", server_addr);
+
+        Ok(match connection.skip_welcome()? {
+            Some(response) => Handshake::Rejected(response),
+            None => Handshake::Ready(connection),
+        })
+    }
+
+    /// Ограничить время ожидания ответа сервера (`None` — без ограничения).
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), QuoteError> {
+        self.writer
+            .set_read_timeout(timeout)
+            .map_err(|err| QuoteError::server_err(format!("ошибка настройки TCP-потока: {err}")))
+    }
+
+    /// Адрес сервера, с которым установлено соединение.
+    pub fn server_addr(&self) -> SocketAddr {
+        self.server_addr
+    }
+
+    /// Пропуск приветствия и служебной информации.
+    ///
+    /// ## Returns
+    ///
+    /// Ответ `ERROR|...`, если сервер отказал в подключении вместо
+    /// приветствия.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ProtocolError`], если сервер закрыл
+    /// соединение, не отправив [`WELCOME_TERMINATOR`].
+    fn skip_welcome(&mut self) -> Result<Option<String>, QuoteError> {
+        loop {
+            let line = self.read_line()?.ok_or_else(|| {
+                QuoteError::protocol_err(tr(
+                    format!("соединение закрыто до получения {WELCOME_TERMINATOR}"),
+                    format!("connection closed before {WELCOME_TERMINATOR}"),
+                ))
+            })?;
+            if line.starts_with("ERROR|") {
+                return Ok(Some(line));
+            }
+            if line.trim().eq_ignore_ascii_case(WELCOME_TERMINATOR) {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Отправить команду серверу и вернуть его ответ.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ServerError`] при сетевых ошибках или если
+    /// сервер закрыл соединение, не ответив.
+    pub fn send_command(&mut self, command: &str) -> Result<String, QuoteError> {
+        let send = |writer: &mut TcpStream| -> std::io::Result<()> {
+            writer.write_all(command.as_bytes())?;
+            writer.write_all(b"\n")?;
+            writer.flush()
+        };
+        send(&mut self.writer).map_err(|err| {
+            QuoteError::server_err(tr(
+                format!("ошибка отправки команды: {err}"),
+                format!("failed to send command: {err}"),
+            ))
+        })?;
+
+        info!("Отправлена команда: {}", command);
+
+        let response = self.read_line()?.ok_or_else(|| {
+            QuoteError::server_err(tr(
+                "пустой ответ от сервера или сервер закрыл соединение",
+                "empty response or connection closed by server",
+            ))
+        })?;
+
+        info!("Ответ сервера: {}", response);
+        Ok(response)
+    }
+
+    /// Прочитать строку от сервера. `None`, если соединение закрыто.
+    fn read_line(&mut self) -> Result<Option<String>, QuoteError> {
+        self.reader
+            .read_line_timeout(None)
+            .map(|line| line.map(|line| line.trim_end().to_string()))
+            .map_err(|err| self.server_read_err(err))
+    }
+
+    /// Уточнить ошибку чтения адресом сервера.
+    fn server_read_err(&self, err: QuoteError) -> QuoteError {
+        match err.kind() {
+            ErrorKind::Timeout => QuoteError::timeout_err(tr(
+                format!("сервер {} не ответил", self.server_addr),
+                format!("server {} did not respond", self.server_addr),
+            )),
+            ErrorKind::Io => QuoteError::server_err(tr(
+                format!("ошибка чтения от сервера: {err}"),
+                format!("failed to read from server: {err}"),
+            )),
+            _ => err,
+        }
+    }
+}
+
+impl Handshake {
+    /// Соединение, если сервер его принял.
+    ///
+    /// ## Ошибки
+    ///
+    /// [`QuoteError::CommandError`] с ответом сервера при отказе.
+    pub fn ready(self) -> Result<ServerConnection, QuoteError> {
+        match self {
+            Handshake::Ready(connection) => Ok(connection),
+            Handshake::Rejected(response) => Err(QuoteError::command_err(tr(
+                format!("сервер отклонил подключение: {response}"),
+                format!("server refused connection: {response}"),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn connect_skips_welcome_and_reads_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"Hello!\n\nCommands...\nREADY\n").unwrap();
+
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line.trim_end(), "STREAM udp://127.0.0.1:34254 ALL");
+
+            stream.write_all(b"OK|stream started\n").unwrap();
+        });
+
+        let mut connection = ServerConnection::connect(addr).unwrap();
+        let response = connection
+            .send_command("STREAM udp://127.0.0.1:34254 ALL")
+            .unwrap();
+
+        assert_eq!(response, "OK|stream started");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn send_command_fails_on_closed_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"READY\n").unwrap();
+        });
+
+        let mut connection = ServerConnection::connect(addr).unwrap();
+        server.join().unwrap();
+
+        assert!(connection.send_command("CANCEL").is_err());
+    }
+
+    #[test]
+    fn connect_fails_without_welcome_terminator() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"Hello!\n").unwrap();
+        });
+
+        let result = ServerConnection::connect(addr);
+        server.join().unwrap();
+
+        assert!(matches!(result, Err(QuoteError::ProtocolError(_))));
+    }
+
+    #[test]
+    fn rejection_instead_of_welcome_is_returned() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"ERROR|server_full|busy\n").unwrap();
+        });
+
+        let handshake = ServerConnection::open(addr).unwrap();
+        server.join().unwrap();
+
+        assert!(matches!(handshake, Handshake::Rejected(r) if r == "ERROR|server_full|busy"));
+    }
+}
@@ -0,0 +1,355 @@
+//! UDP-клиент для приёма котировок и отправки Ping.
+
+use crate::config::{PING_INTERVAL_SECS, UDP_BUFFER_SIZE, UDP_READ_TIMEOUT_MS};
+use commons::models::{StockQuote, StreamQuote};
+use commons::netdefaults;
+use commons::traits::Validate;
+use log::{error, info, warn};
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// UDP-клиент.
+pub struct UdpClient {
+    socket: UdpSocket,
+    server_addr: Arc<Mutex<Option<SocketAddr>>>,
+    parse_error_hook: Option<fn(&str)>,
+    rejected: Arc<AtomicU64>,
+    corrupted: Arc<AtomicU64>,
+    binary: bool,
+}
+
+impl UdpClient {
+    /// Создать UDP-сокет для приёма котировок по ссылке. Если ссылка
+    /// содержит [`BINARY_FORMAT_QUERY`](netdefaults::BINARY_FORMAT_QUERY),
+    /// датаграммы принимаются в двоичном виде с контрольной суммой.
+    pub fn bind_url(url: &Url) -> io::Result<Self> {
+        let addr = url.socket_addrs(|| None)?.first().cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Некорректная UDP-ссылка")
+        })?;
+
+        let mut client = Self::bind(addr)?;
+        client.binary = netdefaults::is_binary_format(url.query());
+        Ok(client)
+    }
+
+    /// Создать UDP-сокет для приёма котировок (по адресу сокета).
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(Duration::from_millis(UDP_READ_TIMEOUT_MS)))?;
+        Ok(Self {
+            socket,
+            server_addr: Arc::new(Mutex::new(None)),
+            parse_error_hook: None,
+            rejected: Arc::new(AtomicU64::new(0)),
+            corrupted: Arc::new(AtomicU64::new(0)),
+            binary: false,
+        })
+    }
+
+    /// Запустить поток Ping.
+    pub fn spawn_ping(&self, stop: Arc<AtomicBool>) -> io::Result<JoinHandle<()>> {
+        let socket = self.socket.try_clone()?;
+        let addr = Arc::clone(&self.server_addr);
+
+        Ok(thread::spawn(move || {
+            let mut last = Instant::now();
+
+            // Ping ping ping.
+            loop {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                if last.elapsed() >= Duration::from_secs(PING_INTERVAL_SECS) {
+                    if let Ok(guard) = addr.lock()
+                        && let Some(target) = *guard
+                    {
+                        let _ = socket.send_to(b"Ping", target);
+                    }
+
+                    last = Instant::now();
+                }
+
+                thread::sleep(Duration::from_millis(100));
+            }
+        }))
+    }
+
+    /// Принять одну датаграмму в `buf` без разбора.
+    ///
+    /// ## Returns
+    ///
+    /// Размер полученных данных или `Ok(None)`, если за время ожидания данных
+    /// не поступило. Иные ошибки сокета возвращаются как есть.
+    pub fn recv_raw(&self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        Ok(self.recv_raw_from(buf)?.map(|(size, _)| size))
+    }
+
+    /// Принять одну датаграмму в `buf` вместе с адресом отправителя.
+    fn recv_raw_from(&self, buf: &mut [u8]) -> io::Result<Option<(usize, SocketAddr)>> {
+        match self.socket.recv_from(buf) {
+            Ok((size, addr)) => {
+                self.set_server_addr(addr);
+                Ok(Some((size, addr)))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Принять одну котировку.
+    ///
+    /// ## Returns
+    ///
+    /// `Ok(None)`, если за время ожидания данных не поступило, пришла
+    /// строка, которую не удалось десериализовать, или котировка не прошла
+    /// проверку ([`Validate`]; такие котировки учитываются в
+    /// [`UdpClient::rejected`]). Двоичные датаграммы с неверной контрольной
+    /// суммой или размером учитываются в [`UdpClient::corrupted`]. Иные
+    /// ошибки сокета возвращаются как есть.
+    pub fn recv_quote(&self) -> io::Result<Option<StockQuote>> {
+        Ok(self
+            .recv_stream_quote()?
+            .map(|(_, datagram)| datagram.quote))
+    }
+
+    /// Принять одну котировку вместе с адресом отправителя и номером
+    /// датаграммы в трансляции ([`StreamQuote`]); `stream_seq = 0`, если
+    /// сервер его не передал. Каждая трансляция сервера отправляет
+    /// датаграммы со своего адреса, поэтому номера сравнимы только
+    /// у датаграмм одного отправителя. Возвращает то же, что
+    /// [`UdpClient::recv_quote`].
+    pub fn recv_stream_quote(&self) -> io::Result<Option<(SocketAddr, StreamQuote)>> {
+        let mut buf = [0u8; UDP_BUFFER_SIZE];
+
+        let Some((size, source)) = self.recv_raw_from(&mut buf)? else {
+            return Ok(None);
+        };
+        let Some(datagram) = self.decode(&buf[..size]) else {
+            return Ok(None);
+        };
+        match datagram.quote.validate() {
+            Ok(()) => Ok(Some((source, datagram))),
+            Err(err) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                warn!("Котировка от сервера отклонена: {err}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Разобрать датаграмму: двоичную с контрольной суммой или JSON.
+    fn decode(&self, bytes: &[u8]) -> Option<StreamQuote> {
+        if self.binary {
+            return StreamQuote::from_checked_bytes(bytes)
+                .inspect_err(|err| {
+                    self.corrupted.fetch_add(1, Ordering::Relaxed);
+                    warn!("Повреждённая датаграмма от сервера: {err}");
+                })
+                .ok();
+        }
+        let msg = String::from_utf8_lossy(bytes);
+        serde_json::from_str::<StreamQuote>(&msg)
+            .inspect_err(|_| {
+                error!("Ошибка десериализации строки от сервера: {msg}");
+                if let Some(hook) = self.parse_error_hook {
+                    hook(&msg);
+                }
+            })
+            .ok()
+    }
+
+    /// Установить обработчик датаграмм, которые не удалось разобрать как
+    /// котировку (например, для вывода в консоль при отладке).
+    pub fn set_parse_error_hook(&mut self, hook: fn(&str)) {
+        self.parse_error_hook = Some(hook);
+    }
+
+    /// Количество котировок, отклонённых проверкой с момента создания.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Счётчик отклонённых котировок, разделяемый с другими потоками.
+    pub fn rejected_counter(&self) -> Arc<AtomicU64> {
+        self.rejected.clone()
+    }
+
+    /// Количество повреждённых двоичных датаграмм с момента создания.
+    pub fn corrupted(&self) -> u64 {
+        self.corrupted.load(Ordering::Relaxed)
+    }
+
+    /// Счётчик повреждённых датаграмм, разделяемый с другими потоками.
+    pub fn corrupted_counter(&self) -> Arc<AtomicU64> {
+        self.corrupted.clone()
+    }
+
+    /// Запускает цикл приёма сообщений до получения сигнала остановки.
+    ///
+    /// ## Args
+    /// - `stop` — атомарный флаг для остановки цикла
+    /// - `on_quote` — обработчик каждой полученной котировки
+    ///
+    pub fn recv_loop<F>(&self, stop: Arc<AtomicBool>, mut on_quote: F)
+    where
+        F: FnMut(StockQuote),
+    {
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            match self.recv_quote() {
+                Ok(Some(quote)) => on_quote(quote),
+                Ok(None) => {}
+                Err(_) => break,
+            }
+        }
+
+        info!("UDP-приёмник остановлен");
+    }
+
+    fn set_server_addr(&self, addr: SocketAddr) {
+        let mut guard = self.server_addr.lock().unwrap();
+        if guard.is_none() {
+            *guard = Some(addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+    #[test]
+    fn bind_url_parses_valid_udp() {
+        let url = Url::parse("udp://127.0.0.1:34567").unwrap();
+        let client = UdpClient::bind_url(&url);
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn server_addr_set_only_once() {
+        let url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let client = UdpClient::bind_url(&url).unwrap();
+
+        let addr1 = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 1111));
+        let addr2 = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 2222));
+
+        client.set_server_addr(addr1);
+        client.set_server_addr(addr2);
+
+        let stored = client.server_addr.lock().unwrap().unwrap();
+        assert_eq!(stored, addr1);
+    }
+
+    #[test]
+    fn recv_quote_deserializes_json() {
+        let url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let client = UdpClient::bind_url(&url).unwrap();
+        let target = client.socket.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let json = r#"{"ticker":"AAPL","price":10.5,"volume":3,"timestamp":1700000000000,"transaction":"Buy"}"#;
+        sender.send_to(json.as_bytes(), target).unwrap();
+
+        let quote = client.recv_quote().unwrap().unwrap();
+        assert_eq!(quote.ticker, "AAPL");
+        assert_eq!(client.rejected(), 0);
+        assert_eq!(
+            *client.server_addr.lock().unwrap(),
+            Some(sender.local_addr().unwrap())
+        );
+    }
+
+    #[test]
+    fn recv_stream_quote_reads_stream_seq() {
+        let url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let client = UdpClient::bind_url(&url).unwrap();
+        let target = client.socket.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let json = r#"{"stream_seq":2,"ticker":"AAPL","price":10.5,"volume":3,"timestamp":1700000000000,"transaction":"Buy","seq":90}"#;
+        sender.send_to(json.as_bytes(), target).unwrap();
+
+        let (source, datagram) = client.recv_stream_quote().unwrap().unwrap();
+        assert_eq!(source, sender.local_addr().unwrap());
+        assert_eq!((datagram.stream_seq, datagram.quote.seq), (2, 90));
+    }
+
+    #[test]
+    fn recv_quote_rejects_invalid_quotes() {
+        let url = Url::parse("udp://127.0.0.1:0").unwrap();
+        let client = UdpClient::bind_url(&url).unwrap();
+        let target = client.socket.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let json = r#"{"ticker":"AAPL","price":0.0,"volume":3,"timestamp":1700000000000,"transaction":"Buy"}"#;
+        sender.send_to(json.as_bytes(), target).unwrap();
+
+        assert!(client.recv_quote().unwrap().is_none());
+        assert_eq!(client.rejected(), 1);
+    }
+
+    #[test]
+    fn damaged_binary_datagram_is_counted_as_corrupted() {
+        let url = Url::parse("udp://127.0.0.1:0?format=binary").unwrap();
+        let client = UdpClient::bind_url(&url).unwrap();
+        let target = client.socket.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let quote: StockQuote = "AAPL|10.5|3|1700000000000|buy".parse().unwrap();
+        let bytes = StreamQuote {
+            stream_seq: 4,
+            quote,
+        }
+        .to_checked_bytes()
+        .unwrap();
+        sender.send_to(&bytes, target).unwrap();
+        let (_, datagram) = client.recv_stream_quote().unwrap().unwrap();
+        assert_eq!(
+            (datagram.stream_seq, datagram.quote.ticker.as_str()),
+            (4, "AAPL")
+        );
+
+        let mut damaged = bytes.clone();
+        damaged[20] ^= 0x01;
+        sender.send_to(&damaged, target).unwrap();
+        sender.send_to(&bytes[..bytes.len() - 1], target).unwrap();
+
+        assert!(client.recv_stream_quote().unwrap().is_none());
+        assert!(client.recv_stream_quote().unwrap().is_none());
+        assert_eq!((client.corrupted(), client.rejected()), (2, 0));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn recv_quote_accepts_arbitrary_quotes(quote: StockQuote) {
+            let url = Url::parse("udp://127.0.0.1:0").unwrap();
+            let client = UdpClient::bind_url(&url).unwrap();
+            let target = client.socket.local_addr().unwrap();
+
+            let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let json = serde_json::to_string(&quote).unwrap();
+            sender.send_to(json.as_bytes(), target).unwrap();
+
+            let received = client.recv_quote().unwrap().unwrap();
+            prop_assert_eq!(received.to_string(), quote.to_string());
+            prop_assert_eq!(client.rejected(), 0);
+        }
+    }
+}
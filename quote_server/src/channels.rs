@@ -1,33 +1,105 @@
 //! Каналы трансляции данных и поддерживающие методы.
 
-use crate::config::{CHANNEL_TIMEOUT_MS, GEN_TICKERS_DURATION_MS};
+use crate::config::{
+    BACKFILL_DEPTH, CHANNEL_TIMEOUT_MS, GEN_TICKERS_DURATION_MS, HEALTH_STALE_SECS,
+    THROUGHPUT_WINDOW_SECS,
+};
 use crate::generator::QuoteGenerator;
+use crate::metrics::{HEALTH, METRICS, Throughput};
 use crate::models::ClientManager;
+use commons::calendar::TradingCalendar;
+use commons::errors::QuoteError;
+use commons::history::HistoryBuffer;
+use commons::id::Id;
+use commons::models::StockQuote;
+use commons::shutdown::ShutdownToken;
+use commons::traits::Validate;
+use commons::utils::{fmt_duration, get_timestamp_ms};
 use crossbeam_channel::{Receiver, SendTimeoutError, Sender};
 use log::{error, info, warn};
-use std::sync::atomic::AtomicBool;
 use std::{
+    collections::HashSet,
     sync::atomic::Ordering,
-    sync::{Arc, Mutex},
+    sync::{Arc, LazyLock, Mutex},
     thread,
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// Последние котировки каждого тикера для первичного заполнения новых
+/// подписок ([`backfill`]).
+pub static QUOTE_HISTORY: LazyLock<HistoryBuffer<StockQuote>> =
+    LazyLock::new(|| HistoryBuffer::new(BACKFILL_DEPTH));
+
 /// Запустить ленту котировок.
-pub fn start_generator(tx: Sender<String>) -> JoinHandle<()> {
-    let mut generator = QuoteGenerator::new().unwrap_or_else(|err| {
+///
+/// Котировки, не прошедшие проверку ([`Validate`]), в канал не попадают;
+/// их количество, как и длительность работы, выводится в журнал при
+/// остановке генератора. Раз в [`THROUGHPUT_WINDOW_SECS`] секунд в журнал
+/// выводится статистика котировок в секунду за это время. Отправленные
+/// котировки сохраняются в [`QUOTE_HISTORY`]. Вне торговой сессии `calendar` котировки не
+/// генерируются. Активность генератора отмечается в [`HEALTH`] (`generator`).
+///
+/// ## Args
+///
+/// - `tx` — канал для котировок в формате JSON
+/// - `seed` — зерно генератора для воспроизводимой последовательности
+/// - `calendar` — расписание торгов
+/// - `shutdown` — остановка генератора
+///
+/// ## Ошибки
+///
+/// Ошибка создания генератора ([`QuoteGenerator::new`]).
+pub fn start_generator(
+    tx: Sender<String>,
+    seed: Option<u64>,
+    calendar: TradingCalendar,
+    shutdown: ShutdownToken,
+) -> Result<JoinHandle<()>, QuoteError> {
+    let generator = match seed {
+        Some(seed) => {
+            info!("Генератор котировок использует зерно {seed}");
+            QuoteGenerator::with_seed(seed)
+        }
+        None => QuoteGenerator::new(),
+    };
+    let mut generator = generator.inspect_err(|err| {
         error!("Создать генератор не удалось: {}", err);
-        panic!("ошибка генератора: {err}")
-    });
+    })?;
 
-    thread::spawn(move || {
+    let health = HEALTH.register("generator", Some(Duration::from_secs(HEALTH_STALE_SECS)));
+    Ok(thread::spawn(move || {
         info!("Генератор котировок запущен");
+        let started = Instant::now();
+        let mut market_open = true;
+        let mut throughput = Throughput::new(THROUGHPUT_WINDOW_SECS, started);
+        let mut last_report = started;
 
-        loop {
-            thread::sleep(Duration::from_millis(GEN_TICKERS_DURATION_MS));
+        while !shutdown.cancelled_wait(Duration::from_millis(GEN_TICKERS_DURATION_MS)) {
+            health.beat();
+            let now = Instant::now();
+            throughput.tick(now);
+            if now.duration_since(last_report) >= Duration::from_secs(THROUGHPUT_WINDOW_SECS) {
+                last_report = now;
+                if let Some(stats) = throughput.stats() {
+                    info!("Котировок в секунду за {THROUGHPUT_WINDOW_SECS} с: {stats}");
+                }
+            }
 
+            let state = calendar.state(get_timestamp_ms());
+            if state.is_open() != market_open {
+                market_open = state.is_open();
+                info!("Генератор котировок: {state}");
+            }
+            if !market_open {
+                continue;
+            }
             if let Ok(quote) = generator.next_gen() {
+                if let Err(err) = quote.validate() {
+                    METRICS.quotes_rejected.inc();
+                    warn!("Котировка не отправлена: {err}");
+                    continue;
+                }
                 let quote_json = match serde_json::to_string(&quote) {
                     Ok(json) => json,
                     Err(err) => {
@@ -36,8 +108,13 @@ pub fn start_generator(tx: Sender<String>) -> JoinHandle<()> {
                     }
                 };
                 match tx.send_timeout(quote_json, Duration::from_millis(GEN_TICKERS_DURATION_MS)) {
-                    Ok(_) => (),
+                    Ok(_) => {
+                        METRICS.quotes_generated.inc();
+                        throughput.record();
+                        QUOTE_HISTORY.push(quote.ticker.clone(), quote);
+                    }
                     Err(SendTimeoutError::Timeout(_)) => {
+                        METRICS.quotes_dropped.inc();
                         warn!("Канал котировок занят (timeout)");
                     }
                     Err(SendTimeoutError::Disconnected(_)) => {
@@ -48,28 +125,32 @@ pub fn start_generator(tx: Sender<String>) -> JoinHandle<()> {
             }
         }
 
-        info!("Генератор котировок остановлен");
-    })
+        info!(
+            "Генератор котировок остановлен через {}, отклонено котировок: {}",
+            fmt_duration(started.elapsed()),
+            METRICS.quotes_rejected.get()
+        );
+    }))
 }
 
 /// Диспетчер-генератор подписчиков на канал генерации тикеров.
 ///
+/// Активность диспетчера отмечается в [`HEALTH`] (`dispatcher`).
+///
 /// ## Args
 ///
 /// - `main_receiver` — основной канал-отправитель данных
 /// - `clients` — экземпляр [`ClientManager`] с данными о клиентах
-/// - `stop` — прерывание работы диспетчера внешней командой
+/// - `shutdown` — прерывание работы диспетчера внешней командой
 pub fn gen_tickers_dispatcher(
     main_receiver: Receiver<String>,
     clients: Arc<Mutex<ClientManager>>,
-    stop: Arc<AtomicBool>,
+    shutdown: ShutdownToken,
 ) -> JoinHandle<()> {
+    let health = HEALTH.register("dispatcher", Some(Duration::from_secs(HEALTH_STALE_SECS)));
     thread::spawn(move || {
-        loop {
-            if stop.load(Ordering::SeqCst) {
-                break;
-            }
-
+        while !shutdown.is_cancelled() {
+            health.beat();
             match main_receiver.recv_timeout(Duration::from_millis(CHANNEL_TIMEOUT_MS)) {
                 Ok(quote) => {
                     let senders: Vec<_> = {
@@ -105,7 +186,7 @@ pub fn gen_tickers_dispatcher(
 ///
 /// - `senders` — HashMap с id клиентов и отправителями активных подписчиков
 /// - `message` — сообщение для рассылки
-fn tickers_sender(senders: Vec<(usize, Sender<String>)>, message: &str) {
+fn tickers_sender(senders: Vec<(Id, Sender<String>)>, message: &str) {
     for (id, tx) in senders {
         match tx.send_timeout(
             message.to_string(),
@@ -122,3 +203,80 @@ fn tickers_sender(senders: Vec<(usize, Sender<String>)>, message: &str) {
         }
     }
 }
+
+/// Отправить в канал новой подписки сохранённую историю котировок, чтобы
+/// клиент сразу получил последние цены, не дожидаясь новых сделок.
+///
+/// ## Args
+///
+/// - `history` — история котировок по тикерам
+/// - `sender` — канал подписки
+/// - `tickers` — тикеры подписки; пустое множество — все тикеры
+///
+/// ## Returns
+///
+/// Количество отправленных котировок. Котировки идут в порядке генерации.
+pub fn backfill(
+    history: &HistoryBuffer<StockQuote>,
+    sender: &Sender<String>,
+    tickers: &HashSet<String>,
+) -> usize {
+    let mut quotes: Vec<StockQuote> = history
+        .snapshot_all()
+        .into_iter()
+        .filter(|(ticker, _)| tickers.is_empty() || tickers.contains(ticker))
+        .flat_map(|(_, quotes)| quotes)
+        .collect();
+    quotes.sort_by_key(|quote| quote.seq);
+
+    let mut sent = 0;
+    for quote in quotes {
+        let Ok(json) = serde_json::to_string(&quote) else {
+            continue;
+        };
+        if sender.send(json).is_err() {
+            break;
+        }
+        sent += 1;
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commons::models::Transaction;
+    use commons::price::Price;
+    use crossbeam_channel::unbounded;
+
+    fn quote(ticker: &str, seq: u64) -> StockQuote {
+        StockQuote::builder()
+            .ticker(ticker)
+            .price("100".parse::<Price>().unwrap())
+            .volume(1u32)
+            .transaction(Transaction::Buy)
+            .timestamp(1u64)
+            .seq(seq)
+            .build()
+    }
+
+    #[test]
+    fn backfill_sends_subscribed_history_in_order() {
+        let history = HistoryBuffer::new(2);
+        for (ticker, seq) in [("AAPL", 1), ("MSFT", 2), ("AAPL", 3), ("AAPL", 4)] {
+            history.push(ticker.to_string(), quote(ticker, seq));
+        }
+        let (tx, rx) = unbounded();
+
+        assert_eq!(backfill(&history, &tx, &HashSet::new()), 3);
+        let seqs: Vec<u64> = rx
+            .try_iter()
+            .map(|json| serde_json::from_str::<StockQuote>(&json).unwrap().seq)
+            .collect();
+        assert_eq!(seqs, [2, 3, 4]);
+
+        let tickers = HashSet::from(["MSFT".to_string()]);
+        assert_eq!(backfill(&history, &tx, &tickers), 1);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+}
@@ -5,32 +5,52 @@
 //! $ qserver --port 8888
 //! ```
 
-use crate::config::{DEFAULT_SERVER_PORT, SERVER_ADDRESS, TCP_PORTS_ALLOWED};
+use crate::config::ServerConfig;
 use clap::Parser;
+use commons::config::validate_port;
+use commons::errors::QuoteError;
+use commons::netdefaults;
+use commons::paths::Paths;
+use log::LevelFilter;
+use std::env;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[clap(about = "Quote Server. Generating and broadcasting real-time ticker quotes.")]
 #[clap(author, version, about, long_about = None)]
 struct CliArgs {
-    /// TCP port to listen on (server binds to 127.0.0.1:PORT).
-    #[clap(short, long, required = false, default_value_t = DEFAULT_SERVER_PORT, value_parser=port_in_range)]
-    port: u16,
-}
+    /// TCP port to listen on (server binds to 127.0.0.1:PORT). Default: 8888.
+    #[clap(short, long, required = false, value_parser = validate_port)]
+    port: Option<u16>,
 
-/// Валидатор для поля `port`.
-fn port_in_range(s: &str) -> Result<u16, String> {
-    let port: usize = s.parse().map_err(|_| format!("invalid port number: {s}"))?;
-    if TCP_PORTS_ALLOWED.contains(&port) {
-        Ok(port as u16)
-    } else {
-        Err(format!(
-            "port number {} not in range {} — {}",
-            s,
-            TCP_PORTS_ALLOWED.start(),
-            TCP_PORTS_ALLOWED.end()
-        ))
-    }
+    /// Configuration file (TOML). Default: qserver.toml in the project root
+    /// (QUOTE_HOME), if present.
+    #[clap(long, required = false, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Seed for the quote generator, to replay the same quote sequence.
+    #[clap(long, required = false, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Directory with data files (tickers.txt). Default: data in the project
+    /// root (QUOTE_HOME).
+    #[clap(long, required = false, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Directory for log files. Default: log_folder from the configuration
+    /// in the project root (QUOTE_HOME).
+    #[clap(long, required = false, value_name = "DIR")]
+    log_dir: Option<PathBuf>,
+
+    /// Log file level (off, error, warn, info, debug, trace).
+    /// Overrides the QUOTE_LOG environment variable.
+    #[clap(long, required = false, value_name = "LEVEL")]
+    log_level: Option<LevelFilter>,
+
+    /// Also print log messages of this level and above to stderr.
+    #[clap(long, required = false, value_name = "LEVEL")]
+    log_console: Option<LevelFilter>,
 }
 
 /// Параметры, полученные из командной строки при запуске приложения.
@@ -38,58 +58,166 @@ fn port_in_range(s: &str) -> Result<u16, String> {
 /// ## Доступные данные
 /// - `server_addr` — сформированный экземпляр [`SocketAddr`] с адресом сокета
 ///   сервера и портом. Например, `127.0.0.1:8888`.
+/// - `log_level` — уровень журналирования, если задан ключом `--log-level`.
+/// - `log_console` — уровень дублирования журнала в консоль (`--log-console`).
+/// - `config` — итоговая конфигурация сервера.
+/// - `paths` — директории данных и log-файлов.
 #[derive(Debug)]
 pub struct ServerSet {
     /// Адрес работы TCP-сервера.
     pub server_addr: SocketAddr,
+    /// Уровень журналирования из командной строки.
+    pub log_level: Option<LevelFilter>,
+    /// Уровень дублирования журнала в консоль.
+    pub log_console: Option<LevelFilter>,
+    /// Конфигурация сервера с учётом файла, окружения и ключей.
+    pub config: ServerConfig,
+    /// Директории приложения с учётом `QUOTE_HOME`, конфигурации и ключей.
+    pub paths: Paths,
 }
 
 impl ServerSet {
-    /// Создать экземпляр на основе аргументов из командной строки.
-    fn new(args: &CliArgs) -> Self {
-        let server_addr = Self::get_server_addr(args.port);
+    /// Создать экземпляр на основе аргументов из командной строки
+    /// и загруженной конфигурации.
+    fn new(args: &CliArgs, config: ServerConfig, paths: Paths) -> Self {
+        Self {
+            server_addr: Self::get_server_addr(config.port),
+            log_level: args.log_level,
+            log_console: args.log_console,
+            paths: Self::resolve_paths(args, &config, paths),
+            config,
+        }
+    }
 
-        Self { server_addr }
+    /// Применить к директориям `log_folder` из конфигурации и ключи
+    /// `--data-dir` и `--log-dir`.
+    fn resolve_paths(args: &CliArgs, config: &ServerConfig, paths: Paths) -> Paths {
+        let log_dir = args
+            .log_dir
+            .clone()
+            .unwrap_or_else(|| paths.root().join(&config.log_folder));
+        let paths = paths.with_log_dir(log_dir);
+        match &args.data_dir {
+            Some(dir) => paths.with_data_dir(dir),
+            None => paths,
+        }
     }
 
     /// Предоставить адрес TCP-сервера.
     fn get_server_addr(port: u16) -> SocketAddr {
-        SocketAddr::from((SERVER_ADDRESS, port))
+        netdefaults::server_addr(port)
     }
 }
 
 /// Получить от пользователя первичные настройки приложения.
-pub fn parse_cli_args() -> ServerSet {
+///
+/// ## Ошибки
+///
+/// Возвращает [`QuoteError`], если файл конфигурации не найден
+/// или содержит некорректные значения.
+pub fn parse_cli_args() -> Result<ServerSet, QuoteError> {
     let args = CliArgs::parse();
+    let paths = Paths::from_env();
+    let config = ServerConfig::load(
+        &paths,
+        args.config.as_deref(),
+        env::vars(),
+        args.port,
+        args.seed,
+    )?;
 
-    ServerSet::new(&args)
+    Ok(ServerSet::new(&args, config, paths))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CONFIG_FILE;
     use clap::Parser;
+    use std::fs;
+    use std::path::Path;
+
+    /// Корневая директория без файла конфигурации.
+    const NO_CONFIG_ROOT: &str = "/nonexistent/quote";
+
+    /// Конфигурация по ключам `args` без файла и переменных окружения.
+    fn load(args: &CliArgs) -> ServerConfig {
+        let paths = Paths::new(NO_CONFIG_ROOT);
+        ServerConfig::load(&paths, None, [], args.port, args.seed).unwrap()
+    }
+
+    #[test]
+    fn server_set_builds_correct_addr() {
+        let port = 9000;
+        let args = CliArgs::parse_from(["qserver", "--port", &port.to_string()]);
+        let set = ServerSet::new(&args, load(&args), Paths::new(NO_CONFIG_ROOT));
+
+        assert_eq!(set.server_addr, netdefaults::server_addr(port));
+        assert!(set.log_level.is_none());
+    }
 
     #[test]
     fn port_validator_accepts_allowed_port() {
-        let ok_port = TCP_PORTS_ALLOWED.start().to_string();
-        let res = port_in_range(&ok_port);
-        assert!(res.is_ok());
+        let ok_port = netdefaults::PORTS_ALLOWED.start().to_string();
+        let args = CliArgs::try_parse_from(["qserver", "--port", &ok_port]).unwrap();
+        assert_eq!(args.port, Some(*netdefaults::PORTS_ALLOWED.start()));
     }
 
     #[test]
-    fn port_validator_rejects_out_of_range() {
-        let bad_port = (TCP_PORTS_ALLOWED.end() + 1).to_string();
-        let res = port_in_range(&bad_port);
-        assert!(res.is_err());
+    fn port_outside_range_is_rejected() {
+        assert!(CliArgs::try_parse_from(["qserver", "--port", "80"]).is_err());
     }
 
     #[test]
-    fn server_set_builds_correct_addr() {
-        let port = DEFAULT_SERVER_PORT;
-        let args = CliArgs::parse_from(["qserver", "--port", &port.to_string()]);
-        let set = ServerSet::new(&args);
+    fn seed_is_passed_to_config() {
+        let args = CliArgs::parse_from(["qserver", "--seed", "42"]);
+        let config = load(&args);
+
+        assert_eq!(config.seed, Some(42));
+        assert!(CliArgs::try_parse_from(["qserver", "--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn log_level_is_parsed() {
+        let args = CliArgs::parse_from(["qserver", "--log-level", "debug"]);
+        assert_eq!(args.log_level, Some(LevelFilter::Debug));
+        assert!(CliArgs::try_parse_from(["qserver", "--log-level", "loud"]).is_err());
+
+        let args = CliArgs::parse_from(["qserver", "--log-console", "warn"]);
+        assert_eq!(args.log_console, Some(LevelFilter::Warn));
+    }
+
+    #[test]
+    fn dir_flags_override_paths() {
+        let config = ServerConfig {
+            log_folder: "logs".to_string(),
+            ..ServerConfig::default()
+        };
+        let args = CliArgs::parse_from(["qserver"]);
+        let set = ServerSet::new(&args, config.clone(), Paths::new("/opt/quote"));
+        assert_eq!(set.paths.log_dir(), Path::new("/opt/quote/logs"));
+        assert_eq!(set.paths.data_dir(), Path::new("/opt/quote/data"));
+
+        let args =
+            CliArgs::parse_from(["qserver", "--data-dir", "/srv/data", "--log-dir", "/tmp/q"]);
+        let set = ServerSet::new(&args, config, Paths::new("/opt/quote"));
+        assert_eq!(set.paths.data_dir(), Path::new("/srv/data"));
+        assert_eq!(set.paths.log_dir(), Path::new("/tmp/q"));
+    }
+
+    #[test]
+    fn file_env_and_flags_are_layered() {
+        let root = env::temp_dir().join("qserver_config_test");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(CONFIG_FILE), "port = 9100\nseed = 1\n").unwrap();
+
+        let args = CliArgs::parse_from(["qserver", "--seed", "3"]);
+        let vars = [("QSERVER_PORT".to_string(), "9200".to_string())];
+        let config =
+            ServerConfig::load(&Paths::new(&root), None, vars, args.port, args.seed).unwrap();
+        fs::remove_dir_all(&root).unwrap();
 
-        assert_eq!(set.server_addr, SocketAddr::from((SERVER_ADDRESS, port)));
+        assert_eq!(config.port, 9200);
+        assert_eq!(config.seed, Some(3));
     }
 }
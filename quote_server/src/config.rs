@@ -1,11 +1,96 @@
 //! Конфигурация приложения.
+//!
+//! Параметры запуска, которые можно переопределить, собраны в
+//! [`ServerConfig`] и загружаются по уровням: значения по умолчанию, файл
+//! [`CONFIG_FILE`] (или файл из ключа `--config`), переменные окружения
+//! с префиксом [`ENV_PREFIX`] и ключи командной строки.
+
+use commons::calendar::TradingCalendar;
+use commons::config::{ConfigLoader, LOG_FOLDER, check_port};
+use commons::errors::QuoteError;
+use commons::i18n::{Lang, Msg};
+use commons::models::Transaction;
+use commons::netdefaults::DEFAULT_SERVER_PORT;
+use commons::paths::Paths;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Имя файла конфигурации сервера в корневой директории ([`Paths::root`]).
+pub const CONFIG_FILE: &str = "qserver.toml";
+
+/// Префикс переменных окружения сервера, например `QSERVER_PORT`.
+pub const ENV_PREFIX: &str = "QSERVER";
+
+/// Переопределяемые параметры запуска сервера.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Порт TCP, на котором сервер принимает подключения.
+    pub port: u16,
+    /// Директория log-файлов относительно корневой директории; ключ
+    /// `--log-dir` задаёт её полный путь.
+    pub log_folder: String,
+    /// Зерно генератора котировок; если задано, последовательность
+    /// котировок воспроизводится от запуска к запуску.
+    pub seed: Option<u64>,
+    /// Язык сообщений клиентам и консоли; если не задан — из переменной
+    /// окружения `QUOTE_LANG`.
+    pub lang: Option<Lang>,
+    /// Генерировать котировки только во время основной сессии биржи
+    /// ([`TradingCalendar::nyse`]); по умолчанию торги круглосуточные.
+    pub market_hours: bool,
+}
 
-use std::ops::RangeInclusive;
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_SERVER_PORT,
+            log_folder: LOG_FOLDER.to_string(),
+            seed: None,
+            lang: None,
+            market_hours: false,
+        }
+    }
+}
 
-/// Название каталога для хранения данных проекта.
-pub const DATA_FOLDER: &str = "data";
-/// Название директории для log-файлов.
-pub const LOG_FOLDER: &str = "log";
+impl ServerConfig {
+    /// Загрузить конфигурацию.
+    ///
+    /// ## Args
+    /// - `paths` — директории приложения
+    /// - `file` — файл конфигурации из командной строки; если не задан,
+    ///   используется [`CONFIG_FILE`] в корневой директории, при его наличии
+    /// - `vars` — переменные окружения (`std::env::vars()`)
+    /// - `port` — порт из командной строки
+    /// - `seed` — зерно генератора из командной строки
+    pub fn load(
+        paths: &Paths,
+        file: Option<&Path>,
+        vars: impl IntoIterator<Item = (String, String)>,
+        port: Option<u16>,
+        seed: Option<u64>,
+    ) -> Result<Self, QuoteError> {
+        let default_file = paths.root().join(CONFIG_FILE);
+        let config: Self = ConfigLoader::new(ENV_PREFIX)?
+            .file(file.unwrap_or(&default_file), file.is_some())?
+            .env(vars)
+            .set_opt("port", port)
+            .set_opt("seed", seed)
+            .build()?;
+
+        check_port(config.port).map_err(QuoteError::value_err)?;
+        Ok(config)
+    }
+
+    /// Торговый календарь генератора котировок.
+    pub fn calendar(&self) -> TradingCalendar {
+        if self.market_hours {
+            TradingCalendar::nyse()
+        } else {
+            TradingCalendar::always_open()
+        }
+    }
+}
 
 /// Название файла, который содержит названия тикеров.
 pub const TICKERS_FILENAME: &str = "tickers.txt";
@@ -25,6 +110,14 @@ pub struct QuoteGenerateSettings {
     /// Доля "средних" тикеров (по умолчанию 0.4 = 40 %).
     pub middle_share: f64,
 
+    /// Относительная частота сделок с тикерами каждого эшелона (дорогие,
+    /// средние, низший): ликвидные бумаги торгуются чаще.
+    pub trade_weights: (f64, f64, f64),
+
+    /// Относительная частота видов транзакций: кроме покупок и продаж,
+    /// генератор изредка выдаёт короткие продажи, отмены и исправления.
+    pub transaction_weights: [(Transaction, f64); 5],
+
     /// Диапазон возможных значений объёма разовой сделки с тикерами.
     pub units_per_trade: (u32, u32),
     /// Вероятность изменения цены при очередной генерации. Возможное значение
@@ -39,12 +132,30 @@ pub static QUOTE_SETTINGS: QuoteGenerateSettings = QuoteGenerateSettings {
     low: (0.5, 99.9),
     top_share: 0.10,
     middle_share: 0.40,
+    trade_weights: (5.0, 2.0, 1.0),
+    transaction_weights: [
+        (Transaction::Buy, 45.0),
+        (Transaction::Sell, 40.0),
+        (Transaction::ShortSell, 10.0),
+        (Transaction::Cancel, 3.0),
+        (Transaction::Correction, 2.0),
+    ],
     units_per_trade: (1, 500_000),
     probability_change_price: 0.9,
 };
 
-pub const WELCOME_SERVER: &str = "Успешное подключение к Quote Server!\n\n";
-pub const WELCOME_INFO: &str = r#"Commands:
+/// Количество знаков после запятой в ценах генератора (шаг цены — цент).
+pub const PRICE_TICK_DECIMALS: u32 = 2;
+
+/// Приветствие сервера при подключении клиента.
+pub const WELCOME_SERVER: Msg = Msg::new(
+    "Успешное подключение к Quote Server!\n\n",
+    "Connected to Quote Server!\n\n",
+);
+
+/// Справка по командам сервера, отправляемая после приветствия.
+pub const WELCOME_INFO: Msg = Msg::new(
+    r#"Commands:
 1. Получать данные о всех тикерах:
 STREAM <URL>:<PORT> ALL
  Пример: udp://127.0.0.1:34254 ALL
@@ -56,31 +167,82 @@ STREAM <URL>:<PORT> <TICKERS, ...>
 
 3. Отменить ранее заказанную отправку данных:
 CANCEL <URL>:<PORT>
+ Ошибки: нет подписки на этот адрес с вашего IP
+
+4. Получить JSON Schema котировок (для проверки данных UDP):
+SCHEMA
 
 Важно: отправка новой команды БЕЗ ОТМЕНЫ (CANCEL) вернёт ошибку.
 
-"#;
+"#,
+    r#"Commands:
+1. Receive quotes for all tickers:
+STREAM <URL>:<PORT> ALL
+ Example: udp://127.0.0.1:34254 ALL
 
-/// Строка-терминатор после приветствия сервера.
-pub const WELCOME_TERMINATOR: &str = "READY\n";
+2. Receive quotes for selected tickers:
+STREAM <URL>:<PORT> <TICKERS, ...>
+ Example: udp://127.0.0.1:34254 PSA,EMR,DUK,PYPL
+ Errors: unknown ticker names
+
+3. Cancel a previously requested stream:
+CANCEL <URL>:<PORT>
+ Errors: no subscription to this address from your IP
 
-/// Адрес сервера для подключения клиентов.
-pub const SERVER_ADDRESS: [u8; 4] = [127, 0, 0, 1];
+4. Get the JSON Schema of quotes (to validate UDP payloads):
+SCHEMA
 
-/// Порт TCP, на котором сервер принимает подключения.
-pub const DEFAULT_SERVER_PORT: u16 = 8888;
+Note: sending a new command WITHOUT CANCEL returns an error.
 
-/// Допустимые значения порта TCP.
-pub const TCP_PORTS_ALLOWED: RangeInclusive<usize> = 1024..=49151;
+"#,
+);
+
+/// Строка-терминатор после приветствия сервера.
+pub const WELCOME_TERMINATOR: &str = "READY\n";
 
 /// Интервал между генерациями тикеров.
 pub const GEN_TICKERS_DURATION_MS: u64 = 100;
 
+/// Сколько последних котировок каждого тикера сервер хранит и отправляет
+/// новой подписке до котировок в реальном времени.
+pub const BACKFILL_DEPTH: usize = 10;
+
+/// Окно статистики пропускной способности генератора и период её вывода
+/// в журнал (секунды).
+pub const THROUGHPUT_WINDOW_SECS: u64 = 60;
+
 /// Лимит времени ожидания пинга от клиента (в секундах).
 pub const UDP_PING_TIMEOUT_SECS: u64 = 5;
 
+/// Timeout записи в управляющее TCP-соединение клиента (секунды): клиент,
+/// не принимающий ответы дольше, считается отключившимся.
+pub const TCP_WRITE_TIMEOUT_SECS: u64 = 5;
+
+/// Через сколько секунд без активности генератор, диспетчер или
+/// UDP-трансляция считаются неработающими.
+pub const HEALTH_STALE_SECS: u64 = 5;
+
+/// Период проверки работоспособности подсистем (секунды).
+pub const HEALTH_CHECK_SECS: u64 = 10;
+
 /// Timeout ожидания сообщения из канала тикеров (миллисекунды).
 pub const CHANNEL_TIMEOUT_MS: u64 = 200;
 
 /// Timeout на операцию чтения из UDP-сокета (миллисекунды).
 pub const SOCKET_READ_TIMEOUT_MS: u64 = 500;
+
+/// Наибольшее число одновременно обслуживаемых TCP-клиентов и UDP-трансляций;
+/// сверх него подключения и подписки получают отказ `ERROR|server_full`.
+pub const MAX_CLIENTS: usize = 64;
+
+/// Допустимый всплеск команд от одного TCP-клиента.
+pub const COMMAND_BURST: u32 = 5;
+
+/// Средняя частота команд от одного TCP-клиента (команд в секунду).
+pub const COMMAND_RATE_PER_SEC: f64 = 1.0;
+
+/// Тип сервиса для анонса сервера через mDNS.
+pub const MDNS_SERVICE_TYPE: &str = "_quote._tcp.local.";
+
+/// Имя хоста, под которым сервер анонсируется через mDNS.
+pub const MDNS_HOST_NAME: &str = "qserver.local.";
@@ -0,0 +1,58 @@
+//! Анонс сервера в локальной сети через mDNS (zeroconf).
+
+use crate::config::{MDNS_HOST_NAME, MDNS_SERVICE_TYPE};
+use commons::errors::QuoteError;
+use log::info;
+use mdns_sd::{IfKind, ServiceDaemon, ServiceInfo};
+use std::net::SocketAddr;
+
+/// Зарегистрировать сервер как сервис [`MDNS_SERVICE_TYPE`].
+///
+/// Анонс действует, пока жив возвращённый [`ServiceDaemon`].
+pub fn announce(server_addr: SocketAddr) -> Result<ServiceDaemon, QuoteError> {
+    let daemon = ServiceDaemon::new()
+        .map_err(|err| QuoteError::server_err(format!("ошибка запуска mDNS: {err}")))?;
+
+    // По умолчанию mDNS не работает через loopback, а сервер может слушать
+    // только его.
+    if server_addr.ip().is_loopback() {
+        daemon
+            .enable_interface(IfKind::LoopbackV4)
+            .map_err(|err| QuoteError::server_err(format!("ошибка настройки mDNS: {err}")))?;
+    }
+
+    let instance_name = instance_name(server_addr);
+    let properties = [("version", env!("CARGO_PKG_VERSION"))];
+    let service = ServiceInfo::new(
+        MDNS_SERVICE_TYPE,
+        &instance_name,
+        MDNS_HOST_NAME,
+        server_addr.ip(),
+        server_addr.port(),
+        &properties[..],
+    )
+    .map_err(|err| QuoteError::server_err(format!("некорректные данные mDNS-сервиса: {err}")))?;
+
+    daemon
+        .register(service)
+        .map_err(|err| QuoteError::server_err(format!("ошибка регистрации mDNS-сервиса: {err}")))?;
+
+    info!("Сервер анонсирован через mDNS: {instance_name}.{MDNS_SERVICE_TYPE}");
+    Ok(daemon)
+}
+
+/// Имя экземпляра сервиса: уникально для порта на хосте.
+fn instance_name(server_addr: SocketAddr) -> String {
+    format!("qserver-{}", server_addr.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_name_contains_port() {
+        let addr: SocketAddr = "127.0.0.1:8888".parse().unwrap();
+        assert_eq!(instance_name(addr), "qserver-8888");
+    }
+}
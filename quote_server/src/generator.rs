@@ -2,18 +2,27 @@
 
 use crate::config::*;
 use commons::errors::QuoteError;
-use commons::get_ticker_data;
 use commons::models::{StockQuote, Transaction};
-use commons::randomizer::{random_bool, random_by_tuple, random_choice_str, shuffle_vec};
-use commons::utils::{get_timestamp, get_workspace_root};
-use std::collections::{HashMap, HashSet};
+use commons::paths;
+use commons::price::Price;
+use commons::randomizer::Randomizer;
+use commons::seq::SeqGen;
+use commons::tickers::{TickerInfo, TickerRegistry};
+use commons::utils::get_timestamp_ms;
+use macros::quote_instrument;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+/// "Табло котировок": текущая цена каждого тикера.
+type QuoteBoard = Arc<Mutex<HashMap<String, Price>>>;
+
 /// Генератор котировок тикеров.
 ///
 /// ## Доступные методы
 ///
 /// - [`QuoteGenerator::new`] — создание и настройка экземпляра
+/// - [`QuoteGenerator::with_seed`] — то же, с воспроизводимой
+///   последовательностью котировок
 /// - [`QuoteGenerator::next_gen`] — генерация нового тикера, с обновлением
 ///   "табло котировок". Параметры генерации задаются в настройках приложения.
 ///
@@ -31,23 +40,41 @@ use std::sync::{Arc, Mutex};
 /// println!("{}", quote2);
 /// ```
 pub struct QuoteGenerator {
-    /// Данные об известных тикерах (например, загруженные из файла).
-    ticker_data: HashSet<String>,
+    /// Известные тикеры (например, загруженные из файла) с весом,
+    /// определяющим частоту сделок с ними.
+    ticker_weights: Vec<(String, f64)>,
     /// Актуальное состояние "доски котировок" тикеров.
-    quote_board: Arc<Mutex<HashMap<String, f64>>>,
+    quote_board: QuoteBoard,
+    /// Источник случайных значений генератора.
+    rng: Randomizer,
+    /// Номер следующей котировки.
+    seq: SeqGen,
 }
 
 impl QuoteGenerator {
     /// Инициализация генератора. Проверка данных и их адаптация для работы
     /// генератора.
     pub fn new() -> Result<Self, QuoteError> {
-        let tickers_vec = Self::get_ticker_data()?;
-        let quote_board = Self::init_quote_board(tickers_vec.clone());
-        let ticker_data = tickers_vec.into_iter().collect::<HashSet<String>>();
+        Self::with_randomizer(Randomizer::new())
+    }
+
+    /// Инициализация генератора с заданным зерном: при одинаковом зерне
+    /// генератор выдаёт одну и ту же последовательность тикеров, цен
+    /// и объёмов.
+    pub fn with_seed(seed: u64) -> Result<Self, QuoteError> {
+        Self::with_randomizer(Randomizer::with_seed(seed))
+    }
+
+    fn with_randomizer(mut rng: Randomizer) -> Result<Self, QuoteError> {
+        let registry = Self::ticker_registry()?;
+        let tickers = registry.iter().cloned().collect();
+        let (quote_board, ticker_weights) = Self::init_quote_board(tickers, &mut rng)?;
 
         let generator = Self {
-            ticker_data,
+            ticker_weights,
             quote_board,
+            rng,
+            seq: SeqGen::new(),
         };
 
         Ok(generator)
@@ -58,121 +85,171 @@ impl QuoteGenerator {
     ///
     /// ## Используются:
     ///
-    /// - [`DATA_FOLDER`] — директория для хранения файлов с данными
+    /// - директория данных ([`paths::current`])
     /// - [`TICKERS_FILENAME`] — название файла с данными о тикерах.
     ///
     /// ## Returns
     ///
-    /// Вектор с названиями тикеров.
-    pub fn get_ticker_data() -> Result<Vec<String>, QuoteError> {
-        let tickers_file = get_workspace_root()
-            .join(DATA_FOLDER)
-            .join(TICKERS_FILENAME);
-
-        get_ticker_data(&tickers_file)?
-            .ok_or_else(|| QuoteError::ticker_err("отсутствуют данные по тикерам"))
+    /// Реестр тикеров (см. [`commons::tickers`]).
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::TickerError`], если файл не содержит
+    /// тикеров.
+    pub fn ticker_registry() -> Result<TickerRegistry, QuoteError> {
+        let tickers_file = paths::current().data_file(TICKERS_FILENAME);
+
+        let registry = TickerRegistry::load(tickers_file)?;
+        if registry.is_empty() {
+            return Err(QuoteError::ticker_err("отсутствуют данные по тикерам"));
+        }
+        Ok(registry)
     }
 
     /// Инициализация "табло котировок".
     ///
-    /// Формирует первичные значения на основе настроек приложения.
-    fn init_quote_board(tickers: Vec<String>) -> Arc<Mutex<HashMap<String, f64>>> {
+    /// Формирует первичные значения на основе настроек приложения. Если
+    /// в сведениях о тикере задана базовая цена, она становится начальной,
+    /// а эшелон определяется по ней.
+    ///
+    /// ## Returns
+    ///
+    /// Табло котировок и веса тикеров для выбора при генерации: вес
+    /// зависит от эшелона, в который попал тикер.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`], если начальная цена не
+    /// представима в [`Price`].
+    fn init_quote_board(
+        tickers: Vec<TickerInfo>,
+        rng: &mut Randomizer,
+    ) -> Result<(QuoteBoard, Vec<(String, f64)>), QuoteError> {
         let settings = QUOTE_SETTINGS;
 
-        let shuffle_tickers = shuffle_vec(tickers);
+        let shuffle_tickers = rng.shuffle_vec(tickers);
 
         let total = shuffle_tickers.len();
         let expensive_count = ((total as f64) * settings.top_share).ceil() as usize;
         let middle_count = ((total as f64) * settings.middle_share).ceil() as usize;
 
         let mut map = HashMap::with_capacity(total);
+        let mut weights = Vec::with_capacity(total);
 
-        for (i, ticker) in shuffle_tickers.into_iter().enumerate() {
-            let price = if i < expensive_count {
-                random_by_tuple(settings.expensive)
+        for (i, info) in shuffle_tickers.into_iter().enumerate() {
+            let tier_price = if i < expensive_count {
+                rng.random_by_tuple(settings.expensive)
             } else if i < expensive_count + middle_count {
-                random_by_tuple(settings.middle)
+                rng.random_by_tuple(settings.middle)
             } else {
-                random_by_tuple(settings.low)
+                rng.random_by_tuple(settings.low)
+            };
+            let price = info.base_price.unwrap_or(tier_price);
+            let weight = if price >= settings.expensive.0 {
+                settings.trade_weights.0
+            } else if price >= settings.middle.0 {
+                settings.trade_weights.1
+            } else {
+                settings.trade_weights.2
             };
 
-            map.insert(ticker, price);
+            weights.push((info.ticker.clone(), weight));
+            map.insert(info.ticker, Self::to_price(price)?);
         }
 
-        Arc::new(Mutex::new(map))
+        Ok((Arc::new(Mutex::new(map)), weights))
     }
 
     /// Сформировать экземпляр на основе предустановленных в конфигурации
     /// значений ([`QUOTE_SETTINGS`]).
     ///
-    /// При генерации новой цены она сохраняется для выбранного тикера
-    /// в "табло котировок".
+    /// Тикер выбирается с учётом веса его эшелона, поэтому ликвидные бумаги
+    /// появляются в потоке чаще. Котировке присваивается очередной сквозной
+    /// номер (`seq`). При генерации новой цены она сохраняется для выбранного
+    /// тикера в "табло котировок".
+    #[quote_instrument(level = "trace")]
     pub fn next_gen(&mut self) -> Result<StockQuote, QuoteError> {
-        let ticker = random_choice_str(&self.ticker_data)
+        let ticker = self
+            .rng
+            .choice_weighted(&self.ticker_weights)
+            .cloned()
             .ok_or_else(|| QuoteError::ticker_err("неудачная попытка случайного выбора тикера"))?;
         let price = self.update_price_random(&ticker)?;
-        let volume: u32 = random_by_tuple(QUOTE_SETTINGS.units_per_trade);
-        let transaction = if random_bool(0.5) {
-            Transaction::Sell
-        } else {
-            Transaction::Buy
-        };
+        let volume: u32 = self.rng.random_by_tuple(QUOTE_SETTINGS.units_per_trade);
+        let transaction = self
+            .rng
+            .choice_weighted(&QUOTE_SETTINGS.transaction_weights)
+            .copied()
+            .ok_or_else(|| QuoteError::value_err("некорректные веса видов транзакций"))?;
 
-        let new_quote = Self::new_quote(ticker, price, volume, transaction);
+        let seq = self.seq.next_seq();
+        let new_quote = Self::new_quote(seq, ticker, price, volume, transaction);
 
         Ok(new_quote)
     }
 
     /// Создать новый экземпляр [`StockQuote`] с предоставленными значениями.
-    fn new_quote(ticker: String, price: f64, volume: u32, transaction: Transaction) -> StockQuote {
-        let timestamp = get_timestamp();
-
-        StockQuote {
-            ticker,
-            price,
-            volume,
-            transaction,
-            timestamp,
-        }
+    fn new_quote(
+        seq: u64,
+        ticker: String,
+        price: Price,
+        volume: u32,
+        transaction: Transaction,
+    ) -> StockQuote {
+        StockQuote::builder()
+            .ticker(ticker)
+            .price(price)
+            .volume(volume)
+            .transaction(transaction)
+            .timestamp(get_timestamp_ms())
+            .seq(seq)
+            .build()
     }
 
     /// Обновить стоимость тикера в табло котировок.
     ///
     /// Для равномерности изменения цен, предусмотрен механизм плавного случайного
     /// колебания цены (+/- 10 % от предыдущей), но в пределах установленных
-    /// настройками.
+    /// настройками. Границы колебания вычисляются в [`Price`]; если они не
+    /// помещаются в [`Price`], цена не меняется.
     ///
     /// ## Ошибки
     ///
     /// Возвращает [`QuoteError::LockError`] если возникла ошибка блокировки
-    /// доступа к данным.
-    fn update_price_random(&mut self, ticker: &str) -> Result<f64, QuoteError> {
+    /// доступа к данным и [`QuoteError::ValueError`], если пределы цен
+    /// в настройках не представимы в [`Price`].
+    fn update_price_random(&mut self, ticker: &str) -> Result<Price, QuoteError> {
         let old_price = self.read_price(ticker)?;
 
         // Цена меняется?
-        if !random_bool(QUOTE_SETTINGS.probability_change_price) {
+        if !self
+            .rng
+            .random_bool(QUOTE_SETTINGS.probability_change_price)
+        {
             return Ok(old_price);
         }
 
-        let calc_min = old_price * 90.0 / 100.0;
-        let calc_max = old_price * 110.0 / 100.0;
+        let (Some(calc_min), Some(calc_max)) = (old_price.mul_f64(0.9), old_price.mul_f64(1.1))
+        else {
+            return Ok(old_price);
+        };
+        let low = Self::to_price_range(QUOTE_SETTINGS.low)?;
+        let expensive = Self::to_price_range(QUOTE_SETTINGS.expensive)?;
 
-        let range = match (
-            calc_min < QUOTE_SETTINGS.low.0,
-            calc_max > QUOTE_SETTINGS.expensive.1,
-        ) {
-            (true, false) => (QUOTE_SETTINGS.low.0, QUOTE_SETTINGS.low.1),
-            (false, true) => (QUOTE_SETTINGS.expensive.0, QUOTE_SETTINGS.expensive.1),
+        let (min, max) = match (calc_min < low.0, calc_max > expensive.1) {
+            (true, false) => low,
+            (false, true) => expensive,
             _ => (calc_min, calc_max),
         };
 
-        let new_price = random_by_tuple(range);
+        let units = self.rng.random(min.units(), max.units());
+        let new_price = Price::from_units(units).round_dp(PRICE_TICK_DECIMALS);
         self.write_price(ticker, new_price)?;
         Ok(new_price)
     }
 
     /// Считать стоимость по заданному тикеру.
-    fn read_price(&self, ticker: &str) -> Result<f64, QuoteError> {
+    fn read_price(&self, ticker: &str) -> Result<Price, QuoteError> {
         self.quote_board
             .lock()?
             .get(ticker)
@@ -181,10 +258,28 @@ impl QuoteGenerator {
     }
 
     /// Сохранить новое значение стоимости для тикера.
-    fn write_price(&mut self, ticker: &str, price: f64) -> Result<(), QuoteError> {
+    fn write_price(&mut self, ticker: &str, price: Price) -> Result<(), QuoteError> {
         self.quote_board.lock()?.insert(ticker.to_string(), price);
         Ok(())
     }
+
+    /// Цена из значения настроек или сведений о тикере, округлённая
+    /// до [`PRICE_TICK_DECIMALS`] знаков.
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::ValueError`], если значение не конечно или
+    /// не помещается в [`Price`].
+    fn to_price(value: f64) -> Result<Price, QuoteError> {
+        Price::from_f64(value)
+            .map(|price| price.round_dp(PRICE_TICK_DECIMALS))
+            .ok_or_else(|| QuoteError::value_err(format!("некорректная цена: {value}")))
+    }
+
+    /// Диапазон цен из пары значений настроек (см. [`Self::to_price`]).
+    fn to_price_range(range: (f64, f64)) -> Result<(Price, Price), QuoteError> {
+        Ok((Self::to_price(range.0)?, Self::to_price(range.1)?))
+    }
 }
 
 #[cfg(test)]
@@ -203,16 +298,131 @@ mod tests {
         let quote = generator.next_gen().unwrap();
 
         assert!(!quote.ticker.is_empty());
-        assert!(quote.price > 0.0);
+        assert!(quote.price.is_positive());
         assert!(quote.volume > 0);
     }
 
     #[test]
     fn generated_ticker_is_known() {
         let mut generator = QuoteGenerator::new().unwrap();
-        let tickers = QuoteGenerator::get_ticker_data().unwrap();
+        let registry = QuoteGenerator::ticker_registry().unwrap();
 
         let quote = generator.next_gen().unwrap();
-        assert!(tickers.contains(&quote.ticker));
+        assert!(registry.contains(&quote.ticker));
+    }
+
+    #[test]
+    fn quotes_are_numbered_sequentially() {
+        let mut generator = QuoteGenerator::new().unwrap();
+        let seqs: Vec<u64> = (0..5).map(|_| generator.next_gen().unwrap().seq).collect();
+
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn same_seed_reproduces_quotes() {
+        let mut first = QuoteGenerator::with_seed(42).unwrap();
+        let mut second = QuoteGenerator::with_seed(42).unwrap();
+
+        for _ in 0..50 {
+            let a = first.next_gen().unwrap();
+            let b = second.next_gen().unwrap();
+            assert_eq!(
+                (a.ticker, a.price, a.volume, a.transaction),
+                (b.ticker, b.price, b.volume, b.transaction)
+            );
+        }
+    }
+
+    #[test]
+    fn generated_prices_are_whole_cents() {
+        let mut generator = QuoteGenerator::with_seed(7).unwrap();
+        for _ in 0..200 {
+            let price = generator.next_gen().unwrap().price;
+            assert_eq!(price.round_dp(PRICE_TICK_DECIMALS), price);
+        }
+    }
+
+    #[test]
+    fn transaction_kinds_follow_weights() {
+        let mut generator = QuoteGenerator::with_seed(3).unwrap();
+        let mut counts = HashMap::new();
+        for _ in 0..2000 {
+            *counts
+                .entry(generator.next_gen().unwrap().transaction)
+                .or_insert(0) += 1;
+        }
+
+        assert!(Transaction::ALL.iter().all(|t| counts.contains_key(t)));
+        let amendments: u32 = counts
+            .iter()
+            .filter(|(t, _)| t.is_amendment())
+            .map(|(_, n)| n)
+            .sum();
+        // Ожидается около 5 % из 2000.
+        assert!(
+            (40..=180).contains(&amendments),
+            "amendments = {amendments}"
+        );
+    }
+
+    #[test]
+    fn ticker_weights_follow_price_tiers() {
+        let generator = QuoteGenerator::new().unwrap();
+        let board = generator.quote_board.lock().unwrap();
+
+        assert_eq!(generator.ticker_weights.len(), board.len());
+        for (ticker, weight) in &generator.ticker_weights {
+            let price = board[ticker].to_f64();
+            let expected = if price >= QUOTE_SETTINGS.expensive.0 {
+                QUOTE_SETTINGS.trade_weights.0
+            } else if price >= QUOTE_SETTINGS.middle.0 {
+                QUOTE_SETTINGS.trade_weights.1
+            } else {
+                QUOTE_SETTINGS.trade_weights.2
+            };
+            assert_eq!(*weight, expected, "{ticker} = {price}");
+        }
+    }
+
+    #[test]
+    fn base_price_from_ticker_info_is_used() {
+        let mut rng = Randomizer::with_seed(1);
+        let tickers = vec![
+            TickerInfo {
+                base_price: Some(QUOTE_SETTINGS.expensive.0 + 1.0),
+                ..TickerInfo::new("AAA")
+            },
+            TickerInfo::new("BBB"),
+        ];
+        let (board, weights) = QuoteGenerator::init_quote_board(tickers, &mut rng).unwrap();
+        let board = board.lock().unwrap();
+
+        assert_eq!(board["AAA"].to_f64(), QUOTE_SETTINGS.expensive.0 + 1.0);
+        assert!(board.contains_key("BBB"));
+        let aaa = weights.iter().find(|(ticker, _)| ticker == "AAA").unwrap();
+        assert_eq!(aaa.1, QUOTE_SETTINGS.trade_weights.0);
+    }
+
+    #[test]
+    fn price_changes_stay_within_band() {
+        let mut generator = QuoteGenerator::with_seed(11).unwrap();
+        let ticker = generator.ticker_weights[0].0.clone();
+        let middle = QuoteGenerator::to_price(QUOTE_SETTINGS.middle.0).unwrap();
+        generator.write_price(&ticker, middle).unwrap();
+
+        for _ in 0..200 {
+            let old = generator.read_price(&ticker).unwrap();
+            let new = generator.update_price_random(&ticker).unwrap();
+            assert!(new.is_positive());
+            assert!(new >= old.mul_f64(0.9).unwrap().round_dp(PRICE_TICK_DECIMALS));
+            assert!(new <= old.mul_f64(1.1).unwrap().round_dp(PRICE_TICK_DECIMALS));
+        }
+    }
+
+    #[test]
+    fn unrepresentable_price_is_an_error() {
+        assert!(QuoteGenerator::to_price(f64::NAN).is_err());
+        assert!(QuoteGenerator::to_price(f64::MAX).is_err());
     }
 }
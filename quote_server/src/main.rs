@@ -5,27 +5,33 @@
 mod channels;
 mod cli;
 mod config;
+mod discovery;
 mod generator;
+mod metrics;
 mod models;
 mod tcp;
 mod udp;
 
 use cli::parse_cli_args;
-use commons::{errors::QuoteError, init_simple_logger, utils::get_workspace_root};
-use config::LOG_FOLDER;
-use log::{error, info, warn};
-use std::{io, process::exit};
+use commons::{errors::QuoteError, i18n, init_logger_with_level, paths, resolve_log_level};
+use log::{LevelFilter, error, info, warn};
+use std::{io, path::PathBuf, process::exit};
 use tcp::run_server;
 
 fn main() -> io::Result<()> {
-    if let Err(err) = init_logger() {
+    let cli_args = parse_cli_args().unwrap_or_else(|err| {
+        eprintln!("{}: {err}", i18n::ERROR);
+        exit(err.code().into());
+    });
+    i18n::init(cli_args.config.lang);
+    let paths = paths::init(cli_args.paths.clone());
+    let level = resolve_log_level(cli_args.log_level, LevelFilter::Info);
+    if let Err(err) = init_logger(paths.log_dir().to_path_buf(), level, cli_args.log_console) {
         error!("{}", err);
         exit(1);
     }
 
     info!("Инициализация Quote Server...");
-
-    let cli_args = parse_cli_args();
     info!("Конфигурация получена: {:?}", cli_args);
 
     if let Err(err) = run_server(cli_args) {
@@ -38,11 +44,14 @@ fn main() -> io::Result<()> {
 
 /// Инициализировать логгер приложения.
 ///
-/// Используется метод [`init_simple_logger`] из коробки [`commons`].
-fn init_logger() -> Result<(), QuoteError> {
-    let log_folder = get_workspace_root().join(LOG_FOLDER);
+/// Используется метод [`init_logger_with_level`] из коробки [`commons`].
+fn init_logger(
+    log_folder: PathBuf,
+    level: LevelFilter,
+    console_level: Option<LevelFilter>,
+) -> Result<(), QuoteError> {
     let app_name = env!("CARGO_PKG_NAME");
-    init_simple_logger(app_name, log_folder)?;
+    init_logger_with_level(app_name, log_folder, level, console_level)?;
 
     Ok(())
 }
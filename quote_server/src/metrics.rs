@@ -0,0 +1,156 @@
+//! Метрики и работоспособность подсистем сервера.
+
+use commons::health::Health;
+use commons::metrics::{Counter, Gauge, MetricsRegistry};
+use commons::rolling::{RollingStats, RollingWindow};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+/// Метрики сервера, общие для всех потоков.
+pub static METRICS: LazyLock<ServerMetrics> = LazyLock::new(ServerMetrics::new);
+
+/// Работоспособность генератора, диспетчера и UDP-трансляций.
+pub static HEALTH: LazyLock<Health> = LazyLock::new(Health::new);
+
+/// Набор метрик сервера.
+pub struct ServerMetrics {
+    /// Реестр, в котором зарегистрированы метрики.
+    pub registry: MetricsRegistry,
+    /// Котировки, переданные генератором в канал рассылки.
+    pub quotes_generated: Arc<Counter>,
+    /// Котировки, не прошедшие проверку.
+    pub quotes_rejected: Arc<Counter>,
+    /// Котировки, не отправленные из-за переполнения канала.
+    pub quotes_dropped: Arc<Counter>,
+    /// Подключённые по TCP клиенты.
+    pub clients_active: Arc<Gauge>,
+    /// Подключения за всё время работы.
+    pub clients_total: Arc<Counter>,
+    /// Отправленные клиентам UDP-датаграммы.
+    pub datagrams_sent: Arc<Counter>,
+    /// Повреждённые входящие UDP-датаграммы: полученные от клиента
+    /// данные, не являющиеся пингом.
+    pub datagrams_corrupted: Arc<Counter>,
+}
+
+impl ServerMetrics {
+    fn new() -> Self {
+        let registry = MetricsRegistry::new();
+        Self {
+            quotes_generated: registry
+                .counter("qserver_quotes_generated_total", "Сгенерировано котировок"),
+            quotes_rejected: registry.counter(
+                "qserver_quotes_rejected_total",
+                "Котировок, не прошедших проверку",
+            ),
+            quotes_dropped: registry.counter(
+                "qserver_quotes_dropped_total",
+                "Котировок, не отправленных из-за переполнения канала",
+            ),
+            clients_active: registry.gauge("qserver_clients_active", "Подключённых клиентов"),
+            clients_total: registry.counter("qserver_clients_total", "Подключений клиентов"),
+            datagrams_sent: registry
+                .counter("qserver_datagrams_sent_total", "Отправлено UDP-датаграмм"),
+            datagrams_corrupted: registry.counter(
+                "qserver_datagrams_corrupted_total",
+                "Повреждённых входящих UDP-датаграмм",
+            ),
+            registry,
+        }
+    }
+}
+
+/// Пропускная способность: количество событий за каждую секунду
+/// в скользящем окне из последних секунд.
+pub struct Throughput {
+    /// Количество событий по завершённым секундам.
+    per_sec: RollingWindow,
+    /// Размер окна, секунды.
+    window_secs: u64,
+    /// Событий в текущей секунде.
+    current: u64,
+    /// Начало текущей секунды.
+    second_started: Instant,
+}
+
+impl Throughput {
+    /// Окно из `window_secs` последних секунд, отсчёт с момента `now`.
+    pub fn new(window_secs: u64, now: Instant) -> Self {
+        let window_secs = window_secs.max(1);
+        Self {
+            per_sec: RollingWindow::with_count(window_secs as usize),
+            window_secs,
+            current: 0,
+            second_started: now,
+        }
+    }
+
+    /// Учесть событие.
+    pub fn record(&mut self) {
+        self.current += 1;
+    }
+
+    /// Завершить секунды, истёкшие к моменту `now`; секунды без событий
+    /// учитываются нулями.
+    pub fn tick(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.second_started).as_secs();
+        if elapsed == 0 {
+            return;
+        }
+        self.per_sec.push(self.current as f64);
+        // Пустых секунд больше размера окна учитывать незачем.
+        for _ in 0..(elapsed - 1).min(self.window_secs) {
+            self.per_sec.push(0.0);
+        }
+        self.current = 0;
+        self.second_started += Duration::from_secs(elapsed);
+    }
+
+    /// Сводка по событиям в секунду; `None`, пока ни одна секунда
+    /// не завершилась.
+    pub fn stats(&self) -> Option<RollingStats> {
+        self.per_sec.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throughput_counts_events_per_second() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new(10, start);
+        assert!(throughput.stats().is_none());
+
+        for _ in 0..4 {
+            throughput.record();
+        }
+        throughput.tick(start + Duration::from_millis(500));
+        assert!(throughput.stats().is_none());
+
+        throughput.tick(start + Duration::from_millis(1100));
+        for _ in 0..2 {
+            throughput.record();
+        }
+        // Третья секунда прошла без событий.
+        throughput.tick(start + Duration::from_millis(3000));
+
+        let stats = throughput.stats().unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!((stats.min, stats.max), (0.0, 4.0));
+        assert_eq!(stats.mean, 2.0);
+    }
+
+    #[test]
+    fn long_idle_period_fills_window_with_zeros() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new(5, start);
+        throughput.record();
+        throughput.tick(start + Duration::from_secs(3600));
+
+        let stats = throughput.stats().unwrap();
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.max, 0.0);
+    }
+}
@@ -1,17 +1,18 @@
 //! Модели данных для приложения.
 
 use commons::errors::QuoteError;
+use commons::id::Id;
 use crossbeam_channel::{Receiver, Sender};
 use std::collections::{HashMap, HashSet};
-use std::net::SocketAddr;
-use std::sync::{atomic::AtomicBool, Arc};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, atomic::AtomicBool};
 use url::Url;
 
 /// Подписчик на котировки.
 #[derive(Debug, Clone)]
 pub(crate) struct ClientSubscription {
     /// Уникальный ID сессии.
-    pub unique_id: usize,
+    pub unique_id: Id,
     /// TCP-адрес клиента.
     pub tcp_addr: SocketAddr,
     /// UDP-адрес для стрима.
@@ -36,7 +37,7 @@ impl ClientSubscription {
     /// - `sender` — канал для отправки сообщений клиенту
     /// - `recv` — канал для получения сообщений от клиента
     pub fn new(
-        unique_id: usize,
+        unique_id: Id,
         tcp_addr: SocketAddr,
         udp_url: Url,
         tickers: HashSet<String>,
@@ -60,7 +61,7 @@ impl ClientSubscription {
 #[derive(Debug, Default)]
 pub struct ClientManager {
     /// `HashMap` активных клиентов, где ключ — уникальный id сессии.
-    pub clients: HashMap<usize, ClientSubscription>,
+    pub clients: HashMap<Id, ClientSubscription>,
 }
 
 impl ClientManager {
@@ -72,7 +73,7 @@ impl ClientManager {
     }
 
     /// Проверить, существует ли клиент с предоставленным id.
-    pub fn id_exists(&self, unique_id: usize) -> bool {
+    pub fn id_exists(&self, unique_id: Id) -> bool {
         self.clients.contains_key(&unique_id)
     }
 
@@ -86,9 +87,24 @@ impl ClientManager {
     }
 
     /// Удалить клиента.
-    pub fn remove_client(&mut self, unique_id: usize) -> Result<ClientSubscription, QuoteError> {
+    pub fn remove_client(&mut self, unique_id: Id) -> Result<ClientSubscription, QuoteError> {
         self.clients
             .remove(&unique_id)
             .ok_or_else(|| QuoteError::command_err("задачи отсутствуют"))
     }
+
+    /// Удалить подписки с адресом трансляции `udp_url`, оформленные
+    /// с IP-адреса `peer`, во всех его сессиях: чужие подписки на тот же
+    /// адрес не затрагиваются.
+    pub fn remove_by_udp(&mut self, udp_url: &Url, peer: IpAddr) -> Vec<ClientSubscription> {
+        let ids: Vec<Id> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| &client.udp_url == udp_url && client.tcp_addr.ip() == peer)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| self.clients.remove(&id))
+            .collect()
+    }
 }
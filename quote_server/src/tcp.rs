@@ -3,44 +3,57 @@
 use crate::channels;
 use crate::channels::gen_tickers_dispatcher;
 use crate::cli::ServerSet;
-use crate::config::{WELCOME_INFO, WELCOME_SERVER, WELCOME_TERMINATOR};
+use crate::config::{
+    COMMAND_BURST, COMMAND_RATE_PER_SEC, HEALTH_CHECK_SECS, MAX_CLIENTS, TCP_WRITE_TIMEOUT_SECS,
+    WELCOME_INFO, WELCOME_SERVER, WELCOME_TERMINATOR,
+};
+use crate::discovery;
 use crate::generator::QuoteGenerator;
+use crate::metrics::{HEALTH, METRICS};
 use crate::models::{ClientManager, ClientSubscription};
 use crate::udp::spawn_stream;
-use commons::{errors::QuoteError, traits::WriteExt};
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use log::{error, info};
-use macros::QuoteEnumDisplay;
-use std::sync::{
-    atomic::{AtomicBool, AtomicUsize, Ordering}, Arc,
-    Mutex,
+use commons::{
+    errors::{QuoteError, ResultExt},
+    health::HealthReport,
+    i18n::tr,
+    id::{Id, IdGen},
+    models::StockQuote,
+    netdefaults::{RATE_LIMITED, SERVER_FULL},
+    pool::ThreadPool,
+    ratelimit::RateLimiter,
+    schema::root_schema,
+    shutdown::ShutdownToken,
+    traits::{ReadExt, WriteExt},
 };
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use log::{debug, error, info, warn};
+use macros::{QuoteCommand, quote_instrument};
+use std::sync::{Arc, Mutex, atomic::Ordering};
 use std::{
     collections::HashSet,
     fmt::Display,
     io,
-    io::{BufRead, BufReader},
+    io::BufReader,
     net::{SocketAddr, TcpListener, TcpStream},
     str::FromStr,
-    thread::{sleep, spawn},
-    time::Duration,
+    time::{Duration, Instant},
 };
+use tracing::info_span;
 use url::Url;
 
-/// Счётчик клиентов.
-static CLIENTS_COUNTER: AtomicUsize = AtomicUsize::new(1000);
-
-/// Увеличить значение счётчика клиентов и вернуть уникальное значение.
-fn gen_id() -> usize {
-    CLIENTS_COUNTER.fetch_add(1, Ordering::SeqCst)
-}
+/// Генератор идентификаторов сессий клиентов.
+static SESSION_IDS: IdGen = IdGen::new();
 
 /// Тип ответа сервера клиенту.
 enum ServerResponse {
     /// Успешное исполнение команды.
     Ok { message: Option<String> },
-    /// Ошибка при выполнении команды.
-    Err { message: Option<String> },
+    /// Ошибка при выполнении команды: код вида ошибки
+    /// ([`commons::errors::ErrorKind::as_str`]) и сообщение.
+    Err {
+        code: &'static str,
+        message: Option<String>,
+    },
 }
 
 impl Display for ServerResponse {
@@ -50,9 +63,9 @@ impl Display for ServerResponse {
                 Some(msg) => write!(f, "OK|{}", msg),
                 None => write!(f, "OK"),
             },
-            ServerResponse::Err { message } => match message {
-                Some(msg) => write!(f, "ERROR|{}", msg),
-                None => write!(f, "ERROR"),
+            ServerResponse::Err { code, message } => match message {
+                Some(msg) => write!(f, "ERROR|{}|{}", code, msg),
+                None => write!(f, "ERROR|{}", code),
             },
         }
     }
@@ -68,13 +81,23 @@ impl ServerResponse {
     /// - `writer` — TCP-поток для записи ответа
     /// - `addr` — адрес TCP-сокета клиента
     /// - `log` — если `true`, сообщение также записывается в лог-файл
-    pub fn send(&self, writer: &mut TcpStream, addr: SocketAddr, log: bool) {
+    ///
+    /// ## Ошибки
+    ///
+    /// Возвращает [`QuoteError::IoError`] или [`QuoteError::TimeoutError`],
+    /// если ответ не удалось записать: соединение с клиентом потеряно.
+    pub fn send(
+        &self,
+        writer: &mut TcpStream,
+        addr: SocketAddr,
+        log: bool,
+    ) -> Result<(), QuoteError> {
         let response = self.to_string();
         if log {
             info!("Ответ: {} для клиента {}", response, addr);
         }
-        writer.write_str(&response);
-        writer.flush_ext();
+        writer.write_str(&response)?;
+        writer.flush_ext()
     }
 
     /// Успешный ответ.
@@ -88,81 +111,65 @@ impl ServerResponse {
         }
     }
 
-    /// Ответ с ошибкой.
-    pub fn err(message: &str) -> Self {
-        if message.trim().is_empty() {
-            ServerResponse::Err { message: None }
-        } else {
-            ServerResponse::Err {
-                message: Some(message.to_string()),
-            }
+    /// Временный отказ с кодом из [`commons::netdefaults`]
+    /// (например, [`RATE_LIMITED`]): клиент может повторить команду.
+    pub fn reject(code: &'static str, message: &str) -> Self {
+        ServerResponse::Err {
+            code,
+            message: Some(message.to_string()),
+        }
+    }
+
+    /// Ответ с ошибкой: `ERROR|<вид ошибки>|<сообщение>`.
+    ///
+    /// Пример: `ERROR|command|неизвестная команда: 'PING'`.
+    pub fn err(err: &QuoteError) -> Self {
+        let message = err.to_string();
+        ServerResponse::Err {
+            code: err.kind().as_str(),
+            message: (!message.trim().is_empty()).then_some(message),
         }
     }
 }
 
 /// Команды клиента.
-#[derive(Debug, QuoteEnumDisplay)]
+#[derive(Debug, QuoteCommand)]
 enum Command {
     /// Подписка на поток.
     #[str("stream")]
-    Stream,
+    Stream {
+        /// Адрес трансляции.
+        udp: UdpUrl,
+        /// Тикеры; перечень может содержать пробелы после запятых.
+        #[arg(rest)]
+        tickers: TickerList,
+    },
     /// Отменить подписку.
-    #[str("cancel")]
-    Cancel,
+    #[str("cancel", alias = "stop", alias = "unsubscribe")]
+    Cancel {
+        /// Адрес трансляции: отменяются подписки с этим адресом во всех
+        /// сессиях с того же IP-адреса (например, после переподключения
+        /// клиента). Без адреса отменяется подписка текущей сессии.
+        udp: Option<UdpUrl>,
+    },
+    /// JSON Schema котировок, которые сервер отправляет по UDP.
+    #[str("schema")]
+    Schema,
 }
 
 impl Command {
-    /// Создать подписку клиента.
+    /// Создать подписку клиента по команде `stream`.
     pub fn make_client(
-        &self,
-        unique_id: usize,
+        self,
+        unique_id: Id,
         tcp_addr: SocketAddr,
         sender: Sender<String>,
         recv: Receiver<String>,
-        cmd_parts: Vec<String>,
     ) -> Result<ClientSubscription, QuoteError> {
         match self {
-            Command::Stream => {
-                if cmd_parts.len() < 2 {
-                    return Err(QuoteError::command_err("команда неполная"));
-                }
-
-                let udp_url = Url::parse(&cmd_parts[0]).map_err(|err| {
-                    QuoteError::command_err(format!(
-                        "некорректный udp-адрес '{}': {}",
-                        &cmd_parts[0], err
-                    ))
-                })?;
-                if udp_url.scheme() != "udp" {
-                    return Err(QuoteError::command_err("поддерживается только UDP"));
-                }
-
-                let tickers = match cmd_parts[1].to_uppercase().as_str() {
-                    "ALL" => HashSet::new(),
-                    _ => {
-                        let tickers_set: HashSet<String> = QuoteGenerator::get_ticker_data()
-                            .map_err(|_| QuoteError::command_err("отсутствуют тикеры"))?
-                            .into_iter()
-                            .collect();
-
-                        let input_set: HashSet<String> = cmd_parts[1]
-                            .split(',')
-                            .map(|s| s.trim().to_uppercase())
-                            .filter(|s| !s.is_empty())
-                            .collect();
-
-                        if input_set.is_subset(&tickers_set) {
-                            input_set
-                        } else {
-                            return Err(QuoteError::command_err("некорректные тикеры"));
-                        }
-                    }
-                };
-
-                Ok(ClientSubscription::new(
-                    unique_id, tcp_addr, udp_url, tickers, sender, recv,
-                ))
-            }
+            Command::Stream { udp, tickers } => Ok(ClientSubscription::new(
+                unique_id, tcp_addr, udp.0, tickers.0, sender, recv,
+            )),
             _ => Err(QuoteError::value_err(
                 "Данный метод не поддерживает этот вариант перечисления",
             )),
@@ -170,86 +177,249 @@ impl Command {
     }
 }
 
+/// Адрес трансляции клиента: ссылка со схемой `udp`.
+#[derive(Debug, Clone)]
+struct UdpUrl(Url);
+
+impl FromStr for UdpUrl {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|err| QuoteError::command_err(err.to_string()))?;
+        if url.scheme() != "udp" {
+            return Err(QuoteError::command_err(tr(
+                "поддерживается только UDP",
+                "only UDP is supported",
+            )));
+        }
+        Ok(Self(url))
+    }
+}
+
+/// Тикеры подписки: `ALL` — все (пустой набор), иначе перечень через
+/// запятую, сверенный с реестром тикеров сервера.
+#[derive(Debug, Clone)]
+struct TickerList(HashSet<String>);
+
+impl FromStr for TickerList {
+    type Err = QuoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("ALL") {
+            return Ok(Self(HashSet::new()));
+        }
+        let tickers = QuoteGenerator::ticker_registry()
+            .map_err(|_| QuoteError::command_err(tr("отсутствуют тикеры", "no tickers available")))?
+            .parse_list(s)?;
+        Ok(Self(tickers))
+    }
+}
+
 /// Организатор работы TCP-сервера.
+///
+/// Раз в [`HEALTH_CHECK_SECS`] секунд проверяет работоспособность подсистем
+/// ([`HEALTH`]) и выводит в журнал неработающие.
 pub fn run_server(settings: ServerSet) -> io::Result<()> {
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .unwrap_or_else(|e| panic!("Ошибка установки Ctrl-C: {}", e));
+    let shutdown = ShutdownToken::install().unwrap_or_else(|e| panic!("{}", e));
 
     // Инициализация окружения.
     let client_manager = Arc::new(Mutex::new(ClientManager::new()));
     let clients = Arc::clone(&client_manager);
 
     let (quote_tx, quote_rx) = unbounded();
-    let handle_gen = channels::start_generator(quote_tx);
+    let handle_gen = channels::start_generator(
+        quote_tx,
+        settings.config.seed,
+        settings.config.calendar(),
+        shutdown.child(),
+    )
+    .map_err(io::Error::other)?;
+
+    let handle_tickers_dispatcher = gen_tickers_dispatcher(quote_rx, clients, shutdown.child());
 
-    let stop_dispatcher = Arc::new(AtomicBool::new(false));
-    let handle_tickers_dispatcher =
-        gen_tickers_dispatcher(quote_rx, clients, stop_dispatcher.clone());
+    let connections = ThreadPool::new("qserver-client", MAX_CLIENTS).map_err(io::Error::other)?;
+    let streams =
+        Arc::new(ThreadPool::new("qserver-stream", MAX_CLIENTS).map_err(io::Error::other)?);
 
     // Запуск сервера.
     let listener = TcpListener::bind(settings.server_addr)?;
     listener.set_nonblocking(true)?;
 
-    println!("Запущен сервер по адресу {}", settings.server_addr);
-    println!("Завершить работу сервера с помощью CTRL-C/CTRL-BREAK.\n");
+    // Анонс в локальной сети: без него сервер доступен по явному адресу.
+    let _mdns = discovery::announce(settings.server_addr)
+        .inspect_err(|err| warn!("Анонс через mDNS недоступен: {err}"))
+        .ok();
+
+    let addr = settings.server_addr;
+    println!(
+        "{}",
+        tr(
+            format!("Запущен сервер по адресу {addr}"),
+            format!("Server started at {addr}"),
+        )
+    );
+    println!(
+        "{}\n",
+        tr(
+            "Завершить работу сервера с помощью CTRL-C/CTRL-BREAK.",
+            "Press CTRL-C/CTRL-BREAK to stop the server.",
+        )
+    );
     info!("Quote Server запущен");
 
+    accept_clients(
+        &listener,
+        &client_manager,
+        &connections,
+        &streams,
+        &shutdown,
+    );
+
+    info!("Завершение работы...");
+    info!("Работоспособность подсистем: {}", HEALTH.report().to_json());
+
+    // Остановка клиентов.
+    if let Ok(mut manager) = client_manager.lock() {
+        for (_, client) in manager.clients.iter_mut() {
+            client.stop_flag.store(true, Ordering::SeqCst);
+            info!("Клиент {} деактивирован", client.tcp_addr);
+        }
+    }
+
+    // Остановка потоков.
+    if let Err(err) = handle_gen.join() {
+        error!("Поток генератора завершился с паникой: {:?}", err);
+    }
+
+    // Остановка диспетчера.
+    if let Err(err) = handle_tickers_dispatcher.join() {
+        error!("Диспетчер потока завершился паникой: {:?}", err);
+    }
+
+    info!("Метрики сервера:\n{}", METRICS.registry.render_text());
+
+    Ok(())
+}
+
+/// Принимать подключения клиентов до отмены `shutdown`.
+///
+/// Каждое подключение обслуживается в пуле `connections`, трансляции
+/// подписок — в пуле `streams`.
+fn accept_clients(
+    listener: &TcpListener,
+    client_manager: &Arc<Mutex<ClientManager>>,
+    connections: &ThreadPool,
+    streams: &Arc<ThreadPool>,
+    shutdown: &ShutdownToken,
+) {
+    let mut last_health_check = Instant::now();
+    let mut healthy = true;
+
     loop {
-        if !running.load(Ordering::SeqCst) {
+        if shutdown.is_cancelled() {
             info!("Работа сервера прервана...");
-            stop_dispatcher.store(true, Ordering::SeqCst);
             break;
         }
 
+        if last_health_check.elapsed() >= Duration::from_secs(HEALTH_CHECK_SECS) {
+            last_health_check = Instant::now();
+            healthy = log_health(&HEALTH.report(), healthy);
+        }
+
         match listener.accept() {
             Ok((stream, addr)) => {
-                let id_client = gen_id();
+                let id_client = SESSION_IDS.next_id();
 
                 // Создание персонального канала Диспечтер - клиент.
                 let (tx_client, rx_client) = unbounded();
 
-                let clients = Arc::clone(&client_manager);
+                let clients = Arc::clone(client_manager);
+                let streams = Arc::clone(streams);
 
                 info!("Рукопожатие: {:?}", addr);
-                spawn(move || {
-                    handle_client(stream, addr, tx_client, rx_client, clients, id_client)
+                METRICS.clients_total.inc();
+                if is_saturated(connections) {
+                    warn!("Все обработчики клиентов заняты, подключение {addr} отклонено");
+                    reject_client(stream, addr);
+                    continue;
+                }
+                let submitted = connections.execute(move || {
+                    let _session = info_span!("session", id = %id_client, peer = %addr).entered();
+                    METRICS.clients_active.inc();
+                    if let Err(err) = handle_client(
+                        stream,
+                        addr,
+                        tx_client,
+                        rx_client,
+                        Arc::clone(&clients),
+                        &streams,
+                        id_client,
+                    ) {
+                        warn!("Клиент {addr}: {err}");
+                        end_session(&clients, id_client);
+                    }
+                    METRICS.clients_active.dec();
                 });
+                if let Err(err) = submitted {
+                    error!("Подключение {addr} не обслужено: {err}");
+                }
             }
             Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                sleep(Duration::from_millis(50));
+                shutdown.cancelled_wait(Duration::from_millis(50));
             }
             Err(e) => {
                 error!("Ошибка работы сервера: {}", e);
+                shutdown.cancel();
                 break;
             }
         }
     }
+}
 
-    info!("Завершение работы...");
+/// Заняты ли все потоки пула: новая задача ждала бы в очереди.
+fn is_saturated(pool: &ThreadPool) -> bool {
+    pool.busy() + pool.queued() >= pool.size()
+}
 
-    // Остановка клиентов.
-    if let Ok(mut manager) = client_manager.lock() {
-        for (_, client) in manager.clients.iter_mut() {
-            client.stop_flag.store(true, Ordering::SeqCst);
-            info!("Клиент {} деактивирован", client.tcp_addr);
-        }
+/// Отказать клиенту в подключении ответом `ERROR|server_full|...` вместо
+/// приветствия и закрыть соединение.
+fn reject_client(mut stream: TcpStream, addr: SocketAddr) {
+    let sent = stream
+        .set_write_timeout(Some(Duration::from_secs(TCP_WRITE_TIMEOUT_SECS)))
+        .context("ошибка настройки таймаута записи")
+        .and_then(|_| {
+            ServerResponse::reject(SERVER_FULL, "all client handlers are busy").send(
+                &mut stream,
+                addr,
+                true,
+            )
+        });
+    if let Err(err) = sent {
+        debug!("Отказ клиенту {addr} не отправлен: {err}");
     }
+}
 
-    // Остановка потоков.
-    if let Err(err) = handle_gen.join() {
-        error!("Поток генератора завершился с паникой: {:?}", err);
+/// Вывести в журнал неработающие подсистемы или восстановление работы.
+///
+/// ## Args
+///
+/// - `report` — сводка о работоспособности
+/// - `was_up` — все подсистемы работали при предыдущей проверке
+///
+/// ## Returns
+///
+/// Все ли подсистемы работают.
+fn log_health(report: &HealthReport, was_up: bool) -> bool {
+    if report.is_up() {
+        if !was_up {
+            info!("Работа подсистем восстановлена");
+        }
+        return true;
     }
-
-    // Остановка диспетчера.
-    if let Err(err) = handle_tickers_dispatcher.join() {
-        error!("Диспетчер потока завершился паникой: {:?}", err);
+    for problem in report.problems() {
+        warn!("Подсистема {problem}");
     }
-
-    Ok(())
+    false
 }
 
 /// Взаимодействие с новым клиентом.
@@ -262,95 +432,214 @@ pub fn run_server(settings: ServerSet) -> io::Result<()> {
 /// - `receiver` — канал получения сообщения клиентом (`crossbeam_channel`)
 ///   для получения трансляции тикеров
 /// - `clients` — ссылка на структуру клиентов [`ClientManager`]
-/// - `id_clients` — индвидуальный ID клиента
+/// - `streams` — пул потоков UDP-трансляций
+/// - `id_client` — идентификатор сессии клиента
+///
+/// ## Ошибки
+///
+/// Возвращает ошибку, если клиенту не удалось отправить ответ (в том числе
+/// за [`TCP_WRITE_TIMEOUT_SECS`]); подписку клиента после этого нужно
+/// остановить ([`end_session`]).
+#[quote_instrument(name = "session")]
 fn handle_client(
     stream: TcpStream,
     addr: SocketAddr,
     sender: Sender<String>,
     receiver: Receiver<String>,
     clients: Arc<Mutex<ClientManager>>,
-    id_client: usize,
-) -> io::Result<()> {
-    let mut writer = stream.try_clone()?;
+    streams: &ThreadPool,
+    id_client: Id,
+) -> Result<(), QuoteError> {
+    let mut writer = stream
+        .try_clone()
+        .context("не удалось получить поток записи")?;
+    writer
+        .set_write_timeout(Some(Duration::from_secs(TCP_WRITE_TIMEOUT_SECS)))
+        .context("ошибка настройки таймаута записи")?;
     let mut reader = BufReader::new(stream);
 
-    writer.write_str(WELCOME_SERVER);
-    writer.write_str(WELCOME_INFO);
-    writer.flush_ext();
-    writer.write_str(WELCOME_TERMINATOR);
+    writer.write_str(WELCOME_SERVER.text())?;
+    writer.write_str(WELCOME_INFO.text())?;
+    writer.flush_ext()?;
+    writer.write_str(WELCOME_TERMINATOR)?;
+
+    let mut limiter = RateLimiter::new(COMMAND_BURST, COMMAND_RATE_PER_SEC);
 
-    let mut line = String::new();
     loop {
-        line.clear();
-        match reader.read_line(&mut line) {
-            Ok(0) => return Ok(()),
-            Ok(_) => {
+        match reader.read_line_timeout(None) {
+            Ok(None) => return Ok(()),
+            Ok(Some(line)) => {
                 let input = line.trim();
                 if input.is_empty() {
-                    ServerResponse::err("empty line").send(&mut writer, addr, false);
+                    ServerResponse::err(&QuoteError::command_err("empty line")).send(
+                        &mut writer,
+                        addr,
+                        false,
+                    )?;
                     continue;
                 }
 
-                let mut parts: Vec<String> =
-                    input.split_whitespace().map(|s| s.to_string()).collect();
+                if !limiter.try_acquire() {
+                    warn!("Превышена частота команд от клиента {}", addr);
+                    ServerResponse::reject(RATE_LIMITED, "too many commands").send(
+                        &mut writer,
+                        addr,
+                        false,
+                    )?;
+                    continue;
+                }
 
-                let cmd = parts.remove(0);
-                match Command::from_str(&cmd) {
-                    Ok(Command::Stream) => {
-                        let client = match Command::Stream.make_client(
+                match Command::from_str(input) {
+                    Ok(command @ Command::Stream { .. }) => {
+                        let client = match command.make_client(
                             id_client,
                             addr,
                             sender.clone(),
                             receiver.clone(),
-                            parts,
                         ) {
                             Ok(c) => c,
                             Err(err) => {
-                                ServerResponse::err(err.to_string().as_str()).send(
-                                    &mut writer,
-                                    addr,
-                                    false,
-                                );
+                                ServerResponse::err(&err).send(&mut writer, addr, false)?;
                                 continue;
                             }
                         };
 
+                        if is_saturated(streams) {
+                            warn!("Все потоки трансляций заняты, подписка {addr} отклонена");
+                            ServerResponse::reject(SERVER_FULL, "all stream slots are busy").send(
+                                &mut writer,
+                                addr,
+                                false,
+                            )?;
+                            continue;
+                        }
+
+                        // История отправляется до регистрации подписки, чтобы
+                        // не оказаться после котировок диспетчера.
+                        let sent = channels::backfill(
+                            &channels::QUOTE_HISTORY,
+                            &client.sender,
+                            &client.tickers,
+                        );
+                        debug!("Клиенту {addr} отправлено котировок из истории: {sent}");
+
                         if let Ok(mut clients) = clients.lock() {
                             clients.add_client(client.clone()).ok();
-                            spawn_stream(client);
+                            if let Err(err) = spawn_stream(streams, client) {
+                                error!("Трансляция для {} не запущена: {}", addr, err);
+                                clients.remove_client(id_client).ok();
+                                ServerResponse::err(&QuoteError::server_err("stream unavailable"))
+                                    .send(&mut writer, addr, false)?;
+                                continue;
+                            }
                         }
 
-                        ServerResponse::ok("stream started").send(&mut writer, addr, false);
+                        ServerResponse::ok("stream started").send(&mut writer, addr, false)?;
                     }
 
-                    Ok(Command::Cancel) => {
-                        if let Ok(mut clients) = clients.lock()
-                            && let Ok(client) = clients.remove_client(id_client)
-                        {
+                    Ok(Command::Cancel { udp }) => {
+                        let canceled = match clients.lock() {
+                            Ok(mut clients) => match udp {
+                                Some(UdpUrl(url)) => clients.remove_by_udp(&url, addr.ip()),
+                                None => clients.remove_client(id_client).into_iter().collect(),
+                            },
+                            Err(_) => Vec::new(),
+                        };
+                        for client in &canceled {
                             client.stop_flag.store(true, Ordering::SeqCst);
                         }
 
-                        ServerResponse::ok("canceled").send(&mut writer, addr, false);
+                        let response = match canceled.is_empty() {
+                            true => ServerResponse::err(&QuoteError::command_err(
+                                "no subscription to cancel",
+                            )),
+                            false => ServerResponse::ok("canceled"),
+                        };
+                        response.send(&mut writer, addr, false)?;
+                    }
+
+                    Ok(Command::Schema) => {
+                        let schema = root_schema::<StockQuote>().to_string();
+                        ServerResponse::ok(&schema).send(&mut writer, addr, false)?;
                     }
 
-                    Err(_) => {
-                        ServerResponse::err("invalid command").send(&mut writer, addr, false);
+                    Err(err) => {
+                        ServerResponse::err(&err).send(&mut writer, addr, false)?;
                     }
                 }
             }
-            Err(_) => {
-                error!("Ошибка чтения: '{}' от {}", line.trim_end(), addr);
+            Err(err) => {
+                error!("Ошибка чтения от {}: {}", addr, err);
                 return Ok(());
             }
         }
     }
 }
 
+/// Остановить подписку клиента, управляющее соединение с которым
+/// потеряно.
+fn end_session(clients: &Mutex<ClientManager>, id_client: Id) {
+    if let Ok(mut clients) = clients.lock()
+        && let Ok(client) = clients.remove_client(id_client)
+    {
+        client.stop_flag.store(true, Ordering::SeqCst);
+        info!("Подписка клиента {} остановлена", client.tcp_addr);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crossbeam_channel::unbounded;
+    use quote_client_lib::config::TRANSIENT_ERROR_CODES;
+    use quote_client_lib::{Handshake, ServerConnection};
     use std::net::{IpAddr, Ipv4Addr};
+    use std::thread::{self, JoinHandle};
+
+    /// Сервер на свободном порту с пулами по `size` обработчиков, без
+    /// генератора котировок. Останавливается при уничтожении.
+    struct TestServer {
+        addr: SocketAddr,
+        clients: Arc<Mutex<ClientManager>>,
+        shutdown: ShutdownToken,
+        handle: Option<JoinHandle<()>>,
+    }
+
+    impl TestServer {
+        fn start(size: usize) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.set_nonblocking(true).unwrap();
+            let addr = listener.local_addr().unwrap();
+            let clients = Arc::new(Mutex::new(ClientManager::new()));
+            let shutdown = ShutdownToken::new();
+
+            let handle = thread::spawn({
+                let clients = Arc::clone(&clients);
+                let shutdown = shutdown.clone();
+                move || {
+                    let connections = ThreadPool::new("test-client", size).unwrap();
+                    let streams = Arc::new(ThreadPool::new("test-stream", size).unwrap());
+                    accept_clients(&listener, &clients, &connections, &streams, &shutdown);
+                }
+            });
+
+            Self {
+                addr,
+                clients,
+                shutdown,
+                handle: Some(handle),
+            }
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            self.shutdown.cancel();
+            if let Some(handle) = self.handle.take() {
+                handle.join().unwrap();
+            }
+        }
+    }
 
     #[test]
     fn server_response_format_ok() {
@@ -362,37 +651,224 @@ mod tests {
 
     #[test]
     fn server_response_format_err() {
-        let r1 = ServerResponse::err("");
-        let r2 = ServerResponse::err("bad");
-        assert_eq!(r1.to_string(), "ERROR");
-        assert_eq!(r2.to_string(), "ERROR|bad");
+        let r1 = ServerResponse::err(&QuoteError::command_err(""));
+        let r2 = ServerResponse::err(&QuoteError::command_err("bad"));
+        let r3 = ServerResponse::err(&QuoteError::value_err("port"));
+        assert_eq!(r1.to_string(), "ERROR|command");
+        assert_eq!(r2.to_string(), "ERROR|command|bad");
+        assert_eq!(r3.to_string(), "ERROR|value|port");
+    }
+
+    /// Подписка по команде `stream` с аргументами `args`.
+    fn stream(args: &str) -> Result<ClientSubscription, QuoteError> {
+        let (tx, rx) = unbounded();
+        let tcp_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 1234);
+        Command::from_str(&format!("stream {args}"))?.make_client(Id::from(1), tcp_addr, tx, rx)
     }
 
     #[test]
-    fn stream_command_all_is_valid() {
-        let (tx, _) = unbounded();
-        let (_, rx2) = unbounded();
+    fn cancel_command_accepts_aliases() {
+        for cmd in [
+            "CANCEL",
+            "stop",
+            "Unsubscribe",
+            "cancel udp://127.0.0.1:34254",
+        ] {
+            assert!(matches!(Command::from_str(cmd), Ok(Command::Cancel { .. })));
+        }
+        assert_eq!(Command::variant_strs(), ["stream", "cancel", "schema"]);
+        assert_eq!(
+            Command::usages(),
+            ["stream <udp> <tickers...>", "cancel [udp]", "schema"]
+        );
+    }
 
-        let cmd = Command::Stream;
-        let tcp_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 1234);
+    #[test]
+    fn schema_response_is_single_line() {
+        assert!(matches!(Command::from_str("SCHEMA"), Ok(Command::Schema)));
+        let response = ServerResponse::ok(&root_schema::<StockQuote>().to_string()).to_string();
+        assert!(response.starts_with("OK|{"));
+        assert!(!response.contains('\n'));
+    }
 
-        let parts = vec!["udp://127.0.0.1:34254".into(), "ALL".into()];
-        let client = cmd.make_client(1, tcp_addr, tx, rx2, parts);
+    #[test]
+    fn command_errors_show_usage() {
+        let err = Command::from_str("stream udp://127.0.0.1:34254").unwrap_err();
+        assert!(
+            err.to_string().contains("stream <udp> <tickers...>"),
+            "{err}"
+        );
+
+        let err = Command::from_str("subscribe").unwrap_err();
+        assert!(err.to_string().contains("cancel [udp]"), "{err}");
 
-        assert!(client.is_ok());
+        assert!(Command::from_str("cancel udp://127.0.0.1:1 extra").is_err());
     }
 
     #[test]
-    fn stream_command_rejects_bad_udp_scheme() {
-        let (tx, _) = unbounded();
-        let (_, rx2) = unbounded();
+    fn send_to_closed_connection_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut writer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (peer, addr) = listener.accept().unwrap();
+        drop(peer);
+
+        // Первые записи могут уйти в буфер ОС до получения сброса соединения.
+        let failed = (0..100).any(|_| {
+            ServerResponse::ok("hello")
+                .send(&mut writer, addr, false)
+                .is_err()
+        });
+        assert!(failed);
+    }
 
-        let cmd = Command::Stream;
+    #[test]
+    fn end_session_stops_subscription() {
+        let (tx, rx) = unbounded();
         let tcp_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 1234);
+        let client = Command::from_str("stream udp://127.0.0.1:34254 ALL")
+            .unwrap()
+            .make_client(Id::from(7), tcp_addr, tx, rx)
+            .unwrap();
+        let stop = Arc::clone(&client.stop_flag);
+
+        let clients = Mutex::new(ClientManager::new());
+        clients.lock().unwrap().add_client(client).unwrap();
+        end_session(&clients, Id::from(7));
+
+        assert!(stop.load(Ordering::SeqCst));
+        assert!(clients.lock().unwrap().clients.is_empty());
+        // Повторный вызов ничего не делает.
+        end_session(&clients, Id::from(7));
+    }
+
+    #[test]
+    fn stream_command_all_is_valid() {
+        assert!(
+            stream("udp://127.0.0.1:34254 ALL")
+                .unwrap()
+                .tickers
+                .is_empty()
+        );
+        assert!(stream("udp://127.0.0.1:34254 all").is_ok());
+    }
 
-        let parts = vec!["http://127.0.0.1:34254".into(), "ALL".into()];
-        let client = cmd.make_client(1, tcp_addr, tx, rx2, parts);
+    #[test]
+    fn stream_command_rejects_bad_udp_scheme() {
+        assert!(stream("http://127.0.0.1:34254 ALL").is_err());
+        assert!(stream("not-a-url ALL").is_err());
+    }
+
+    #[test]
+    fn stream_command_checks_tickers_in_registry() {
+        let client = stream("udp://127.0.0.1:34254 aapl, MSFT").unwrap();
+        assert_eq!(client.tickers.len(), 2);
+        assert!(client.tickers.contains("AAPL"));
+
+        assert!(stream("udp://127.0.0.1:34254 AAPL,NOPE").is_err());
+    }
+
+    #[test]
+    fn log_health_tracks_recovery() {
+        let health = commons::health::Health::new();
+        let udp = health.register("udp 127.0.0.1:34254", None);
+
+        assert!(log_health(&health.report(), true));
+        udp.degraded("датаграммы не отправляются");
+        assert!(!log_health(&health.report(), true));
+        udp.up();
+        assert!(log_health(&health.report(), false));
+    }
 
-        assert!(client.is_err());
+    #[test]
+    fn command_flood_is_rejected_as_transient() {
+        let server = TestServer::start(2);
+        let mut conn = ServerConnection::connect(server.addr).unwrap();
+
+        let responses: Vec<String> = (0..=COMMAND_BURST)
+            .map(|_| conn.send_command("SCHEMA").unwrap())
+            .collect();
+
+        let last = responses.last().unwrap();
+        assert_eq!(last, &format!("ERROR|{RATE_LIMITED}|too many commands"));
+        let code = last.split('|').nth(1).unwrap();
+        assert!(TRANSIENT_ERROR_CODES.contains(&code));
+        assert!(
+            responses[..COMMAND_BURST as usize]
+                .iter()
+                .all(|r| r.starts_with("OK|"))
+        );
+    }
+
+    #[test]
+    fn cancel_by_udp_url_ends_subscription_of_another_session() {
+        let server = TestServer::start(2);
+        let udp_url = "udp://127.0.0.1:34999";
+
+        let mut session = ServerConnection::connect(server.addr).unwrap();
+        let response = session
+            .send_command(&format!("STREAM {udp_url} ALL"))
+            .unwrap();
+        assert_eq!(response, "OK|stream started");
+        assert_eq!(server.clients.lock().unwrap().clients.len(), 1);
+
+        // Отмена из нового соединения, как при завершении работы клиента,
+        // потерявшего прежнее.
+        let mut reconnected = ServerConnection::connect(server.addr).unwrap();
+        let response = reconnected
+            .send_command(&format!("CANCEL {udp_url}"))
+            .unwrap();
+        assert_eq!(response, "OK|canceled");
+        assert!(server.clients.lock().unwrap().clients.is_empty());
+    }
+
+    #[test]
+    fn cancel_by_udp_url_from_another_peer_is_refused() {
+        let server = TestServer::start(2);
+        let udp_url = Url::parse("udp://127.0.0.1:34998").unwrap();
+
+        // Подписка, оформленная с другого IP-адреса.
+        let (tx, rx) = unbounded();
+        let foreign = ClientSubscription::new(
+            Id::from(1),
+            "10.0.0.5:40000".parse().unwrap(),
+            udp_url.clone(),
+            HashSet::new(),
+            tx,
+            rx,
+        );
+        server.clients.lock().unwrap().add_client(foreign).unwrap();
+
+        let mut session = ServerConnection::connect(server.addr).unwrap();
+        let response = session.send_command(&format!("CANCEL {udp_url}")).unwrap();
+        assert!(response.starts_with("ERROR|command|"), "{response}");
+        assert_eq!(server.clients.lock().unwrap().clients.len(), 1);
+
+        // Без подписки в текущей сессии отменять нечего.
+        let response = session.send_command("CANCEL").unwrap();
+        assert!(response.starts_with("ERROR|command|"), "{response}");
+    }
+
+    #[test]
+    fn client_beyond_pool_size_is_rejected_as_server_full() {
+        let server = TestServer::start(1);
+        let busy = ServerConnection::connect(server.addr).unwrap();
+
+        let Handshake::Rejected(response) = ServerConnection::open(server.addr).unwrap() else {
+            panic!("подключение сверх размера пула принято");
+        };
+        assert_eq!(
+            response,
+            format!("ERROR|{SERVER_FULL}|all client handlers are busy")
+        );
+        let code = response.split('|').nth(1).unwrap();
+        assert!(TRANSIENT_ERROR_CODES.contains(&code));
+
+        // После отключения клиента обработчик освобождается.
+        drop(busy);
+        let ready = (0..50).any(|_| {
+            thread::sleep(Duration::from_millis(20));
+            matches!(ServerConnection::open(server.addr), Ok(Handshake::Ready(_)))
+        });
+        assert!(ready);
     }
 }
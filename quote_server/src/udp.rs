@@ -1,84 +1,170 @@
 //! Механизация серверного UDP-протокола.
 
-use crate::config::{CHANNEL_TIMEOUT_MS, SOCKET_READ_TIMEOUT_MS, UDP_PING_TIMEOUT_SECS};
+use crate::config::{
+    CHANNEL_TIMEOUT_MS, HEALTH_STALE_SECS, SOCKET_READ_TIMEOUT_MS, UDP_PING_TIMEOUT_SECS,
+};
+use crate::metrics::{HEALTH, METRICS};
 use crate::models::ClientSubscription;
-use commons::models::StockQuote;
-use log::{error, info, warn};
+use commons::errors::QuoteError;
+use commons::models::{StockQuote, StreamQuote};
+use commons::netdefaults;
+use commons::pool::ThreadPool;
+use commons::seq::SeqGen;
+use log::{debug, error, info, warn};
+use macros::quote_instrument;
 use std::{
     net::UdpSocket,
     sync::atomic::Ordering,
-    thread,
     time::{Duration, Instant},
 };
+use tracing::{Span, info_span};
+
+/// Запустить UDP-трансляцию для клиента в пуле потоков `pool`.
+///
+/// Если свободных потоков нет, трансляция начнётся, когда один из них
+/// освободится. Котировки отправляются в конверте [`StreamQuote`]:
+/// сквозной номер `seq` сохраняется, а датаграммы трансляции нумеруются
+/// заново (`stream_seq` с 1), чтобы клиент по пропускам номеров видел
+/// потерянные датаграммы своей подписки, а не котировки отфильтрованных
+/// тикеров. Журнал трансляции ведётся в области `stream`, вложенной
+/// в текущую (сеанс клиента). На время трансляции она зарегистрирована
+/// в [`HEALTH`] как `udp <адрес>`; ошибка отправки датаграммы отмечается
+/// как работа с ошибками.
+///
+/// Датаграммы отправляются в JSON, а если UDP-ссылка подписки содержит
+/// [`netdefaults::BINARY_FORMAT_QUERY`] — в двоичном виде с контрольной суммой
+/// ([`StreamQuote::to_checked_bytes`]).
+pub fn spawn_stream(pool: &ThreadPool, client: ClientSubscription) -> Result<(), QuoteError> {
+    let session = Span::current();
+    pool.execute(move || {
+        let _stream =
+            info_span!(parent: &session, "stream", tickers = client.tickers.len()).entered();
+        run_stream(client);
+    })
+}
 
-/// Запустить UDP-поток для клиента.
-pub fn spawn_stream(client: ClientSubscription) {
-    thread::spawn(move || {
-        let udp_addr = client
-            .udp_url
-            .socket_addrs(|| None)
-            .ok()
-            .and_then(|v| v.first().cloned());
-
-        let Some(udp_addr) = udp_addr else {
-            error!("Некорректный UDP адрес");
-            return;
-        };
-
-        let socket = UdpSocket::bind("0.0.0.0:0").expect("Не удалось привязаться к UDP-сокету");
-        socket
-            .set_read_timeout(Some(Duration::from_millis(SOCKET_READ_TIMEOUT_MS)))
-            .expect("Ошибка параметра `set_read_timeout`");
-
-        info!("UDP трансляция на адрес: {}", udp_addr);
+/// Трансляция котировок клиенту до остановки подписки или таймаута пинга.
+/// Длительность трансляции записывается в журнал (`quote_instrument`).
+#[quote_instrument(level = "info", name = "stream")]
+fn run_stream(client: ClientSubscription) {
+    let udp_addr = client
+        .udp_url
+        .socket_addrs(|| None)
+        .ok()
+        .and_then(|v| v.first().cloned());
+
+    let Some(udp_addr) = udp_addr else {
+        error!("Некорректный UDP адрес");
+        return;
+    };
 
-        let mut last_ping = Instant::now();
-        let mut buf = [0u8; 64];
+    let socket = UdpSocket::bind("0.0.0.0:0").expect("Не удалось привязаться к UDP-сокету");
+    socket
+        .set_read_timeout(Some(Duration::from_millis(SOCKET_READ_TIMEOUT_MS)))
+        .expect("Ошибка параметра `set_read_timeout`");
+
+    info!("UDP трансляция на адрес: {}", udp_addr);
+    let health = HEALTH.register(
+        format!("udp {udp_addr}"),
+        Some(Duration::from_secs(HEALTH_STALE_SECS)),
+    );
+
+    let binary = netdefaults::is_binary_format(client.udp_url.query());
+    let seq = SeqGen::new();
+    let mut last_ping = Instant::now();
+    let mut buf = [0u8; 64];
+
+    loop {
+        if client.stop_flag.load(Ordering::SeqCst) {
+            break;
+        }
+        health.beat();
 
-        loop {
-            if client.stop_flag.load(Ordering::SeqCst) {
-                break;
-            }
+        if last_ping.elapsed() > Duration::from_secs(UDP_PING_TIMEOUT_SECS) {
+            info!("Таймаут ожидания пинга от клиента. Трансляция прервана");
+            break;
+        }
 
-            if last_ping.elapsed() > Duration::from_secs(UDP_PING_TIMEOUT_SECS) {
-                info!("Таймаут ожидания пинга от клиента. Трансляция прервана");
-                break;
+        if let Ok((size, _)) = socket.recv_from(&mut buf) {
+            let msg = String::from_utf8_lossy(&buf[..size]).to_ascii_lowercase();
+            if msg.trim() == "ping" {
+                debug!("Пинг от клиента");
+                last_ping = Instant::now();
+            } else {
+                METRICS.datagrams_corrupted.inc();
+                debug!("Повреждённая датаграмма от клиента: {size} байт");
             }
+        }
 
-            if let Ok((size, _)) = socket.recv_from(&mut buf) {
-                let msg = String::from_utf8_lossy(&buf[..size]).to_ascii_lowercase();
-                if msg.trim() == "ping" {
-                    last_ping = Instant::now();
+        // За итерацию отправляются все накопившиеся котировки (например,
+        // история, переданная при подписке), а не одна.
+        let first = client
+            .recv
+            .recv_timeout(Duration::from_millis(CHANNEL_TIMEOUT_MS))
+            .ok();
+        for quote in first.into_iter().chain(client.recv.try_iter()) {
+            let stock_quote: StockQuote = match serde_json::from_str(&quote) {
+                Ok(q) => q,
+                Err(e) => {
+                    warn!("Некорректная строка от генератора: {quote} — {e}");
+                    health.remove();
+                    return;
                 }
+            };
+
+            if !client.tickers.is_empty() && !client.tickers.contains(&stock_quote.ticker) {
+                continue;
             }
 
-            if let Ok(quote) = client
-                .recv
-                .recv_timeout(Duration::from_millis(CHANNEL_TIMEOUT_MS))
-            {
-                let stock_quote: StockQuote = match serde_json::from_str(&quote) {
-                    Ok(q) => q,
-                    Err(e) => {
-                        warn!("Некорректная строка от генератора: {quote} — {e}");
-                        return;
-                    }
-                };
-
-                if !client.tickers.is_empty() && !client.tickers.contains(&stock_quote.ticker) {
+            let stream_quote = StreamQuote {
+                stream_seq: seq.next_seq(),
+                quote: stock_quote,
+            };
+            let datagram = match encode_datagram(&stream_quote, binary) {
+                Ok(datagram) => datagram,
+                Err(e) => {
+                    warn!("Не удалось сериализовать котировку: {e}");
                     continue;
                 }
-
-                let _ = socket.send_to(quote.as_bytes(), udp_addr);
+            };
+
+            match socket.send_to(&datagram, udp_addr) {
+                Ok(size) => {
+                    METRICS.datagrams_sent.inc();
+                    health.up();
+                    debug!(
+                        "Датаграмма {} #{}: {size} байт",
+                        stream_quote.quote.ticker, stream_quote.stream_seq
+                    );
+                }
+                Err(e) => {
+                    debug!(
+                        "Датаграмма {} не отправлена: {e}",
+                        stream_quote.quote.ticker
+                    );
+                    health.degraded(format!("датаграммы не отправляются: {e}"));
+                }
             }
         }
+    }
 
-        info!("UDP трансляция остановлена");
-    });
+    health.remove();
+    info!("UDP трансляция на {} остановлена", udp_addr);
+}
+
+/// Датаграмма трансляции: двоичная с контрольной суммой (`binary`) или JSON.
+fn encode_datagram(stream_quote: &StreamQuote, binary: bool) -> Result<Vec<u8>, QuoteError> {
+    if binary {
+        stream_quote.to_checked_bytes()
+    } else {
+        serde_json::to_vec(stream_quote).map_err(|e| QuoteError::json_err("датаграмма", e))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use commons::id::Id;
     use commons::models::{StockQuote, Transaction};
     use crossbeam_channel::unbounded;
     use std::collections::HashSet;
@@ -98,7 +184,7 @@ mod tests {
         stop: Arc<AtomicBool>,
     ) -> ClientSubscription {
         ClientSubscription {
-            unique_id: 1,
+            unique_id: Id::from(1),
             tcp_addr: "127.0.0.1:1".parse().unwrap(),
             udp_url: Url::parse(&format!("udp://{}", udp_addr)).unwrap(),
             tickers,
@@ -111,10 +197,11 @@ mod tests {
     fn sample_quote(ticker: &str) -> StockQuote {
         StockQuote {
             ticker: ticker.to_string(),
-            price: 100.0,
+            price: "100".parse().unwrap(),
             volume: 1000,
             transaction: Transaction::Buy,
             timestamp: 1,
+            ..Default::default()
         }
     }
 
@@ -130,20 +217,66 @@ mod tests {
         let stop = Arc::new(AtomicBool::new(false));
         let client = make_client(udp_addr, HashSet::new(), tx.clone(), rx, stop.clone());
 
-        spawn_stream(client);
+        let pool = ThreadPool::new("test-stream", 1).unwrap();
+        spawn_stream(&pool, client).unwrap();
 
-        let quote = sample_quote("AAPL");
+        let quote = StockQuote {
+            seq: 40,
+            ..sample_quote("AAPL")
+        };
         let quote_json = serde_json::to_string(&quote).unwrap();
         tx.send(quote_json).unwrap();
 
         let mut buf = [0u8; 1024];
         let (size, _) = recv_socket.recv_from(&mut buf).unwrap();
         let json = std::str::from_utf8(&buf[..size]).unwrap();
-        let parsed: StockQuote = serde_json::from_str(json).unwrap();
+        let parsed: StreamQuote = serde_json::from_str(json).unwrap();
+
+        assert_eq!(parsed.quote.ticker, "AAPL");
+        assert_eq!(parsed.quote.seq, 40);
+        assert_eq!(parsed.stream_seq, 1);
+
+        stop.store(true, Ordering::SeqCst);
+        pool.join();
+    }
+
+    #[test]
+    fn stream_sends_checked_binary_when_requested() {
+        let recv_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        recv_socket
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let udp_addr = recv_socket.local_addr().unwrap();
+
+        let (tx, rx) = unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut client = make_client(udp_addr, HashSet::new(), tx.clone(), rx, stop.clone());
+        client
+            .udp_url
+            .set_query(Some(netdefaults::BINARY_FORMAT_QUERY));
+
+        let pool = ThreadPool::new("test-stream", 1).unwrap();
+        spawn_stream(&pool, client).unwrap();
+        tx.send(serde_json::to_string(&sample_quote("AAPL")).unwrap())
+            .unwrap();
 
-        assert_eq!(parsed.ticker, "AAPL");
+        let mut buf = [0u8; 1024];
+        let (size, server) = recv_socket.recv_from(&mut buf).unwrap();
+        let parsed = StreamQuote::from_checked_bytes(&buf[..size]).unwrap();
+        assert_eq!(parsed.quote.ticker, "AAPL");
+        assert_eq!(parsed.stream_seq, 1);
+
+        // Данные, не являющиеся пингом, учитываются как повреждённые.
+        let before = METRICS.datagrams_corrupted.get();
+        recv_socket.send_to(b"P\xffng", server).unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while METRICS.datagrams_corrupted.get() == before && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(METRICS.datagrams_corrupted.get() > before);
 
         stop.store(true, Ordering::SeqCst);
+        pool.join();
     }
 
     #[test]
@@ -162,7 +295,8 @@ mod tests {
 
         let client = make_client(udp_addr, tickers, tx.clone(), rx, stop.clone());
 
-        spawn_stream(client);
+        let pool = ThreadPool::new("test-stream", 1).unwrap();
+        spawn_stream(&pool, client).unwrap();
 
         let quote = sample_quote("MSFT");
         let quote_json = serde_json::to_string(&quote).unwrap();
@@ -174,5 +308,6 @@ mod tests {
         assert!(res.is_err());
 
         stop.store(true, Ordering::SeqCst);
+        pool.join();
     }
 }